@@ -0,0 +1,12 @@
+#version 330 core
+
+in vec2 texCoords;
+
+uniform sampler2D graphic;
+
+out vec4 FragColor;
+
+void main()
+{
+    FragColor = texture(graphic, texCoords);
+}
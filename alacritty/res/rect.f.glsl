@@ -1,10 +1,48 @@
 #version 330 core
 
+// Kinds of rects, in sync with `RectKind`.
+#define RECT_NORMAL 0
+#define RECT_UNDERCURL 1
+
+#define PI 3.1415926538
+
 flat in vec4 color;
 
 out vec4 FragColor;
 
+uniform int rectKind;
+
+// Cell dimensions.
+uniform vec2 cellDim;
+
+// Top left corner of the grid, with the Y axis pointing up.
+uniform vec2 gridOrigin;
+
+// Position of the center from the top of the cell, amplitude and thickness of the undercurl.
+uniform vec3 undercurl;
+
 void main()
 {
-    FragColor = color;
+    if (rectKind != RECT_UNDERCURL) {
+        FragColor = color;
+        return;
+    }
+
+    // Position relative to the top left corner of the cell.
+    float x = gl_FragCoord.x - gridOrigin.x;
+    float y = mod(gridOrigin.y - gl_FragCoord.y, cellDim.y);
+
+    // Draw one period of the sine wave per cell.
+    float frequency = 2. * PI / cellDim.x;
+    float waveY = undercurl.x + undercurl.y * sin(x * frequency);
+
+    // Use the distance perpendicular to the wave, to keep the stroke width on slopes.
+    float slope = undercurl.y * frequency * cos(x * frequency);
+    float waveDistance = abs(y - waveY) / sqrt(1. + slope * slope);
+
+    // Smooth the edges of the stroke over one pixel.
+    float halfThickness = undercurl.z / 2.;
+    float alpha = 1. - smoothstep(halfThickness - 0.5, halfThickness + 0.5, waveDistance);
+
+    FragColor = vec4(color.rgb, color.a * alpha);
 }
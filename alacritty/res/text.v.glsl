@@ -16,6 +16,9 @@ layout(location = 3) in vec4 textColor;
 // Background color.
 layout(location = 4) in vec4 backgroundColor;
 
+// Number of cells covered by the background.
+layout(location = 5) in float backgroundColumns;
+
 out vec2 TexCoords;
 flat out vec4 fg;
 flat out vec4 bg;
@@ -26,8 +29,6 @@ uniform vec4 projection;
 
 uniform int backgroundPass;
 
-#define WIDE_CHAR 1
-
 void main() {
     vec2 projectionOffset = projection.xy;
     vec2 projectionScale = projection.zw;
@@ -41,11 +42,8 @@ void main() {
     vec2 cellPosition = cellDim * gridCoords;
 
     if (backgroundPass != 0) {
-        vec2 backgroundDim = cellDim;
-        if ((int(textColor.a) & WIDE_CHAR) != 0) {
-            // Update wide char x dimension so it'll cover the following spacer.
-            backgroundDim.x *= 2;
-        }
+        // Backgrounds span the entire run of cells, which includes the spacers of wide chars.
+        vec2 backgroundDim = vec2(cellDim.x * backgroundColumns, cellDim.y);
         vec2 finalPosition = cellPosition + backgroundDim * position;
         gl_Position =
             vec4(projectionOffset + projectionScale * finalPosition, 0.0, 1.0);
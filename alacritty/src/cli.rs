@@ -1,15 +1,23 @@
 use std::cmp::max;
 use std::path::PathBuf;
 
-use clap::{crate_authors, crate_description, crate_name, crate_version, App, Arg};
+use clap::{crate_authors, crate_description, crate_name, crate_version, App, Arg, SubCommand};
 use log::{self, error, LevelFilter};
 use serde_yaml::Value;
 
-use alacritty_terminal::config::Program;
+use alacritty_terminal::config::{Login, Program, Shell};
+#[cfg(unix)]
+use alacritty_terminal::index::{Column, Line, Point};
+#[cfg(unix)]
+use alacritty_terminal::selection::SelectionType;
 
 use crate::config::serde_utils;
 use crate::config::window::DEFAULT_NAME;
 use crate::config::Config;
+#[cfg(unix)]
+use crate::event::FontSizeChange;
+#[cfg(unix)]
+use crate::ipc::{SocketMessage, WindowTarget};
 
 #[cfg(not(any(target_os = "macos", windows)))]
 const CONFIG_PATH: &str = "$XDG_CONFIG_HOME/alacritty/alacritty.yml";
@@ -21,14 +29,26 @@ const CONFIG_PATH: &str = "$HOME/.config/alacritty/alacritty.yml";
 /// Options specified on the command line.
 pub struct Options {
     pub print_events: bool,
+    pub print_config: bool,
+    pub measure_startup: bool,
     pub ref_test: bool,
     pub title: Option<String>,
     pub class_instance: Option<String>,
     pub class_general: Option<String>,
     pub embed: Option<String>,
+    pub monitor: Option<usize>,
+    pub workspace: Option<u32>,
     pub log_level: LevelFilter,
     pub command: Option<Program>,
     pub hold: bool,
+    pub pager: bool,
+    pub replay: Option<ReplayOptions>,
+    pub migrate: Option<MigrateOptions>,
+    pub create_config: Option<CreateConfigOptions>,
+    #[cfg(unix)]
+    pub message: Option<MessageOptions>,
+    #[cfg(unix)]
+    pub window_name: Option<String>,
     pub working_directory: Option<PathBuf>,
     pub config_path: Option<PathBuf>,
     pub config_options: Value,
@@ -38,14 +58,26 @@ impl Default for Options {
     fn default() -> Options {
         Options {
             print_events: false,
+            print_config: false,
+            measure_startup: false,
             ref_test: false,
             title: None,
             class_instance: None,
             class_general: None,
             embed: None,
+            monitor: None,
+            workspace: None,
             log_level: LevelFilter::Warn,
             command: None,
             hold: false,
+            pager: false,
+            replay: None,
+            migrate: None,
+            create_config: None,
+            #[cfg(unix)]
+            message: None,
+            #[cfg(unix)]
+            window_name: None,
             working_directory: None,
             config_path: None,
             config_options: Value::Null,
@@ -64,7 +96,13 @@ impl Options {
 
         let mut options = Options::default();
 
-        let matches = App::new(crate_name!())
+        let title_help = format!("Defines the window title [default: {}]", DEFAULT_NAME);
+        let class_help =
+            format!("Defines window class/app_id on X11/Wayland [default: {}]", DEFAULT_NAME);
+        let config_file_help =
+            format!("Specify alternative configuration file [default: {}]", CONFIG_PATH);
+
+        let app = App::new(crate_name!())
             .version(version.as_str())
             .author(crate_authors!("\n"))
             .about(crate_description!())
@@ -74,12 +112,22 @@ impl Options {
                     .long("print-events")
                     .help("Print all events to stdout"),
             )
+            .arg(
+                Arg::with_name("print-config")
+                    .long("print-config")
+                    .help("Print the effective configuration after applying all defaults"),
+            )
+            .arg(
+                Arg::with_name("measure-startup")
+                    .long("measure-startup")
+                    .help("Print the time spent in each stage of the startup to stdout"),
+            )
             .arg(
                 Arg::with_name("title")
                     .long("title")
                     .short("t")
                     .takes_value(true)
-                    .help(&format!("Defines the window title [default: {}]", DEFAULT_NAME)),
+                    .help(&title_help),
             )
             .arg(
                 Arg::with_name("class")
@@ -87,16 +135,25 @@ impl Options {
                     .value_name("instance> | <instance>,<general")
                     .takes_value(true)
                     .use_delimiter(true)
-                    .help(&format!(
-                        "Defines window class/app_id on X11/Wayland [default: {}]",
-                        DEFAULT_NAME
-                    )),
+                    .help(&class_help),
             )
             .arg(
                 Arg::with_name("embed").long("embed").takes_value(true).help(
                     "Defines the X11 window ID (as a decimal integer) to embed Alacritty within",
                 ),
             )
+            .arg(
+                Arg::with_name("monitor")
+                    .long("monitor")
+                    .takes_value(true)
+                    .help("Index of the monitor the window is placed on, unsupported on Wayland"),
+            )
+            .arg(
+                Arg::with_name("workspace")
+                    .long("workspace")
+                    .takes_value(true)
+                    .help("Index of the workspace the window is opened on, only supported on X11"),
+            )
             .arg(
                 Arg::with_name("q")
                     .short("q")
@@ -117,9 +174,12 @@ impl Options {
                     .takes_value(true)
                     .help("Start the shell in the specified working directory"),
             )
-            .arg(Arg::with_name("config-file").long("config-file").takes_value(true).help(
-                &format!("Specify alternative configuration file [default: {}]", CONFIG_PATH),
-            ))
+            .arg(
+                Arg::with_name("config-file")
+                    .long("config-file")
+                    .takes_value(true)
+                    .help(&config_file_help),
+            )
             .arg(
                 Arg::with_name("command")
                     .long("command")
@@ -130,6 +190,12 @@ impl Options {
                     .help("Command and args to execute (must be last argument)"),
             )
             .arg(Arg::with_name("hold").long("hold").help("Remain open after child process exits"))
+            .arg(
+                Arg::with_name("pager")
+                    .long("pager")
+                    .conflicts_with("command")
+                    .help("Display STDIN in vi mode instead of starting a shell"),
+            )
             .arg(
                 Arg::with_name("option")
                     .long("option")
@@ -138,7 +204,159 @@ impl Options {
                     .takes_value(true)
                     .help("Override configuration file options [example: cursor.style=Beam]"),
             )
-            .get_matches();
+            .subcommand(
+                SubCommand::with_name("play")
+                    .about("Replay an asciicast recording")
+                    .arg(Arg::with_name("file").required(true).help("Path of the recording"))
+                    .arg(
+                        Arg::with_name("speed")
+                            .long("speed")
+                            .takes_value(true)
+                            .help("Playback speed multiplier [default: 1]"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("migrate")
+                    .about("Update outdated options in the configuration file")
+                    .arg(
+                        Arg::with_name("dry-run")
+                            .long("dry-run")
+                            .help("Print the required changes without modifying the file"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("create-config")
+                    .about("Write the default configuration file")
+                    .arg(
+                        Arg::with_name("with-comments")
+                            .long("with-comments")
+                            .help("Include the documentation of all options"),
+                    )
+                    .arg(
+                        Arg::with_name("force")
+                            .long("force")
+                            .help("Replace the configuration file if it already exists"),
+                    ),
+            );
+
+        #[cfg(unix)]
+        let app = app
+            .arg(
+                Arg::with_name("name")
+                    .long("name")
+                    .takes_value(true)
+                    .help("Defines the window name used for targeting IPC messages"),
+            )
+            .subcommand(
+                SubCommand::with_name("msg")
+                    .about("Send a message to a running Alacritty instance")
+                    .arg(
+                        Arg::with_name("socket")
+                            .long("socket")
+                            .short("s")
+                            .takes_value(true)
+                            .help("IPC socket connection path [default: $ALACRITTY_SOCKET]"),
+                    )
+                    .arg(
+                        Arg::with_name("window-id")
+                            .long("window-id")
+                            .takes_value(true)
+                            .conflicts_with_all(&["socket", "name", "all"])
+                            .help("Send the message to the window with this ID"),
+                    )
+                    .arg(
+                        Arg::with_name("name")
+                            .long("name")
+                            .takes_value(true)
+                            .conflicts_with_all(&["socket", "all"])
+                            .help("Send the message to all windows with this name"),
+                    )
+                    .arg(
+                        Arg::with_name("all")
+                            .long("all")
+                            .conflicts_with("socket")
+                            .help("Send the message to all windows"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("get-text")
+                            .about("Print the text of the terminal")
+                            .arg(
+                                Arg::with_name("history")
+                                    .long("history")
+                                    .help("Include the scrollback history"),
+                            )
+                            .arg(
+                                Arg::with_name("escapes")
+                                    .long("escapes")
+                                    .help("Preserve colors and text attributes using ANSI escapes"),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("get-state")
+                            .about("Print the terminal state as JSON"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("select")
+                            .about(
+                                "Select text between two points in LINE:COLUMN format, with \
+                                 negative lines in the scrollback history",
+                            )
+                            .arg(
+                                Arg::with_name("type")
+                                    .long("type")
+                                    .takes_value(true)
+                                    .possible_values(&["simple", "block", "semantic", "lines"])
+                                    .default_value("simple")
+                                    .help("Kind of selection"),
+                            )
+                            .arg(Arg::with_name("start").required(true).allow_hyphen_values(true))
+                            .arg(Arg::with_name("end").required(true).allow_hyphen_values(true)),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("get-selection")
+                            .about("Print the text of the active selection"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("clear-selection")
+                            .about("Remove the active selection"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("set-name")
+                            .about("Change the window name used for targeting IPC messages")
+                            .arg(
+                                Arg::with_name("name").help("New window name, empty to remove it"),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("font-size")
+                            .about("Change the font size of the window")
+                            .arg(
+                                Arg::with_name("size")
+                                    .required(true)
+                                    .allow_hyphen_values(true)
+                                    .help("Change with +N or -N, set with =N, or reset"),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("stats")
+                            .about("Print estimates of the window's memory usage as JSON"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("trim")
+                            .about("Free memory which is not required for the terminal content"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("screenshot")
+                            .about("Save the window as PNG image")
+                            .arg(
+                                Arg::with_name("selection")
+                                    .long("selection")
+                                    .help("Only save the active selection"),
+                            ),
+                    ),
+            );
+
+        let matches = app.get_matches();
 
         if matches.is_present("ref-test") {
             options.ref_test = true;
@@ -148,6 +366,14 @@ impl Options {
             options.print_events = true;
         }
 
+        if matches.is_present("print-config") {
+            options.print_config = true;
+        }
+
+        if matches.is_present("measure-startup") {
+            options.measure_startup = true;
+        }
+
         if let Some(mut class) = matches.values_of("class") {
             options.class_instance = class.next().map(|instance| instance.to_owned());
             options.class_general = class.next().map(|general| general.to_owned());
@@ -155,6 +381,9 @@ impl Options {
 
         options.title = matches.value_of("title").map(ToOwned::to_owned);
         options.embed = matches.value_of("embed").map(ToOwned::to_owned);
+        options.monitor = matches.value_of("monitor").and_then(|monitor| monitor.parse().ok());
+        options.workspace =
+            matches.value_of("workspace").and_then(|workspace| workspace.parse().ok());
 
         match matches.occurrences_of("q") {
             0 => (),
@@ -190,6 +419,117 @@ impl Options {
             options.hold = true;
         }
 
+        if matches.is_present("pager") {
+            options.pager = true;
+        }
+
+        if let Some(matches) = matches.subcommand_matches("play") {
+            // The following unwrap is guaranteed to succeed since `file` is required.
+            let path = PathBuf::from(matches.value_of("file").unwrap());
+            let speed = match matches.value_of("speed").map(str::parse::<f64>) {
+                Some(Ok(speed)) if speed.is_finite() && speed > 0. => speed,
+                Some(_) => {
+                    eprintln!("Invalid playback speed, using default");
+                    1.
+                },
+                None => 1.,
+            };
+            options.replay = Some(ReplayOptions { path, speed });
+        }
+
+        if let Some(matches) = matches.subcommand_matches("migrate") {
+            options.migrate = Some(MigrateOptions { dry_run: matches.is_present("dry-run") });
+        }
+
+        if let Some(matches) = matches.subcommand_matches("create-config") {
+            options.create_config = Some(CreateConfigOptions {
+                with_comments: matches.is_present("with-comments"),
+                force: matches.is_present("force"),
+            });
+        }
+
+        #[cfg(unix)]
+        {
+            options.window_name = matches.value_of("name").map(ToOwned::to_owned);
+        }
+
+        #[cfg(unix)]
+        if let Some(matches) = matches.subcommand_matches("msg") {
+            let socket = matches.value_of("socket").map(PathBuf::from);
+
+            let target = if let Some(window_id) = matches.value_of("window-id") {
+                match window_id.parse() {
+                    Ok(window_id) => Some(WindowTarget::Id(window_id)),
+                    Err(_) => {
+                        eprintln!("Invalid window ID: {:?}", window_id);
+                        std::process::exit(1);
+                    },
+                }
+            } else if let Some(name) = matches.value_of("name") {
+                Some(WindowTarget::Name(name.to_owned()))
+            } else if matches.is_present("all") {
+                Some(WindowTarget::All)
+            } else {
+                None
+            };
+
+            let message = match matches.subcommand() {
+                ("get-text", Some(matches)) => Some(SocketMessage::GetText {
+                    history: matches.is_present("history"),
+                    escapes: matches.is_present("escapes"),
+                }),
+                ("get-state", Some(_)) => Some(SocketMessage::GetState),
+                ("select", Some(matches)) => {
+                    let ty = match matches.value_of("type") {
+                        Some("block") => SelectionType::Block,
+                        Some("semantic") => SelectionType::Semantic,
+                        Some("lines") => SelectionType::Lines,
+                        _ => SelectionType::Simple,
+                    };
+
+                    let start = matches.value_of("start").and_then(parse_point);
+                    let end = matches.value_of("end").and_then(parse_point);
+                    match (start, end) {
+                        (Some(start), Some(end)) => Some(SocketMessage::Select { ty, start, end }),
+                        _ => {
+                            eprintln!("Invalid point, expected LINE:COLUMN");
+                            std::process::exit(1);
+                        },
+                    }
+                },
+                ("get-selection", Some(_)) => Some(SocketMessage::GetSelection),
+                ("clear-selection", Some(_)) => Some(SocketMessage::ClearSelection),
+                ("set-name", Some(matches)) => {
+                    Some(SocketMessage::SetName(matches.value_of("name").map(ToOwned::to_owned)))
+                },
+                ("font-size", Some(matches)) => {
+                    match matches.value_of("size").and_then(parse_font_size) {
+                        Some(change) => Some(SocketMessage::FontSize(change)),
+                        None => {
+                            eprintln!("Invalid font size, expected +N, -N, =N or reset");
+                            std::process::exit(1);
+                        },
+                    }
+                },
+                ("stats", Some(_)) => Some(SocketMessage::GetStats),
+                ("trim", Some(_)) => Some(SocketMessage::Trim),
+                ("screenshot", Some(matches)) => {
+                    Some(SocketMessage::Screenshot { selection: matches.is_present("selection") })
+                },
+                _ => None,
+            };
+
+            match message {
+                Some(message) => {
+                    options.message = Some(MessageOptions { socket, target, message });
+                },
+                None => {
+                    eprintln!("{}", matches.usage());
+                    std::process::exit(1);
+                },
+            }
+        }
+
         if let Some(config_options) = matches.values_of("option") {
             for option in config_options {
                 match option_as_value(option) {
@@ -224,8 +564,9 @@ impl Options {
             }
         }
 
+        // Commands are never started as login shell.
         if let Some(command) = &self.command {
-            config.shell = Some(command.clone());
+            config.shell = Shell { program: Some(command.clone()), login: Login::Auto };
         }
 
         config.hold = self.hold;
@@ -242,6 +583,8 @@ impl Options {
 
         config.ui_config.window.dynamic_title &= self.title.is_none();
         config.ui_config.window.embed = self.embed.as_ref().and_then(|embed| embed.parse().ok());
+        config.ui_config.window.monitor = self.monitor.or(config.ui_config.window.monitor);
+        config.ui_config.window.workspace = self.workspace.or(config.ui_config.window.workspace);
         config.ui_config.debug.print_events |= self.print_events;
         config.ui_config.debug.log_level = max(config.ui_config.debug.log_level, self.log_level);
         config.ui_config.debug.ref_test |= self.ref_test;
@@ -253,6 +596,47 @@ impl Options {
     }
 }
 
+/// Options for replaying a recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayOptions {
+    /// Path of the asciicast recording.
+    pub path: PathBuf,
+
+    /// Playback speed multiplier.
+    pub speed: f64,
+}
+
+/// Options for migrating the configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrateOptions {
+    /// Only print the changes without writing them.
+    pub dry_run: bool,
+}
+
+/// Options for writing the default configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateConfigOptions {
+    /// Include the documentation of all options.
+    pub with_comments: bool,
+
+    /// Replace existing configuration files.
+    pub force: bool,
+}
+
+/// Options for sending a message to a running Alacritty instance.
+#[cfg(unix)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageOptions {
+    /// IPC socket connection path override.
+    pub socket: Option<PathBuf>,
+
+    /// Windows receiving the message, instead of the socket.
+    pub target: Option<WindowTarget>,
+
+    /// Message which should be sent.
+    pub message: SocketMessage,
+}
+
 /// Format an option in the format of `parent.field=value` to a serde Value.
 fn option_as_value(option: &str) -> Result<Value, serde_yaml::Error> {
     let mut yaml_text = String::with_capacity(option.len());
@@ -278,6 +662,32 @@ fn option_as_value(option: &str) -> Result<Value, serde_yaml::Error> {
     serde_yaml::from_str(&yaml_text)
 }
 
+/// Parse a grid point in the format of `LINE:COLUMN`.
+#[cfg(unix)]
+fn parse_point(point: &str) -> Option<Point> {
+    let separator = point.find(':')?;
+    let line = point[..separator].parse().ok()?;
+    let column = point[separator + 1..].parse().ok()?;
+    Some(Point::new(Line(line), Column(column)))
+}
+
+/// Parse a font size change in the format of `+N`, `-N`, `=N` or `reset`.
+#[cfg(unix)]
+fn parse_font_size(size: &str) -> Option<FontSizeChange> {
+    if size == "reset" {
+        return Some(FontSizeChange::Reset);
+    }
+
+    let parse = |points: &str| points.parse::<f32>().ok().filter(|points| points.is_finite());
+    match size.strip_prefix('=') {
+        Some(points) => parse(points).filter(|points| *points > 0.).map(FontSizeChange::Set),
+        None if size.starts_with('+') || size.starts_with('-') => {
+            parse(size).map(FontSizeChange::Delta)
+        },
+        None => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,4 +760,25 @@ mod tests {
 
         assert_eq!(value, Value::Mapping(expected));
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn parse_points() {
+        assert_eq!(parse_point("-3:0"), Some(Point::new(Line(-3), Column(0))));
+        assert_eq!(parse_point("2:14"), Some(Point::new(Line(2), Column(14))));
+        assert_eq!(parse_point("2:-1"), None);
+        assert_eq!(parse_point("2"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn parse_font_sizes() {
+        assert_eq!(parse_font_size("+2"), Some(FontSizeChange::Delta(2.)));
+        assert_eq!(parse_font_size("-1.5"), Some(FontSizeChange::Delta(-1.5)));
+        assert_eq!(parse_font_size("=14"), Some(FontSizeChange::Set(14.)));
+        assert_eq!(parse_font_size("reset"), Some(FontSizeChange::Reset));
+        assert_eq!(parse_font_size("=0"), None);
+        assert_eq!(parse_font_size("14"), None);
+        assert_eq!(parse_font_size("=+"), None);
+    }
 }
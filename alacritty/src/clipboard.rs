@@ -1,5 +1,10 @@
+use std::collections::HashMap;
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 use std::ffi::c_void;
+#[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+use std::io::{ErrorKind, Write};
+#[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+use std::process::{Command, Stdio};
 
 use log::{debug, warn};
 
@@ -15,9 +20,33 @@ use copypasta::x11_clipboard::{Primary as X11SelectionClipboard, X11ClipboardCon
 use copypasta::ClipboardContext;
 use copypasta::ClipboardProvider;
 
+#[cfg(target_os = "macos")]
+use crate::macos::pasteboard;
+
+/// Whether the clipboard can hold an HTML version of the copied text.
+pub const HTML_SUPPORT: bool = cfg!(target_os = "macos");
+
+/// Whether there is a selection buffer separate from the clipboard.
+pub const SELECTION_SUPPORT: bool = cfg!(not(any(target_os = "macos", windows)));
+
 pub struct Clipboard {
     clipboard: Box<dyn ClipboardProvider>,
     selection: Option<Box<dyn ClipboardProvider>>,
+
+    /// Content of the named registers.
+    registers: HashMap<char, String>,
+
+    /// Text copied on Wayland, which is only available while Alacritty is running.
+    #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+    copied: Option<Copied>,
+}
+
+/// Last text stored in each clipboard.
+#[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+#[derive(Default)]
+struct Copied {
+    clipboard: Option<String>,
+    selection: Option<String>,
 }
 
 impl Clipboard {
@@ -32,7 +61,12 @@ impl Clipboard {
             Some(display) => {
                 let (selection, clipboard) =
                     wayland_clipboard::create_clipboards_from_external(display);
-                Self { clipboard: Box::new(clipboard), selection: Some(Box::new(selection)) }
+                Self {
+                    clipboard: Box::new(clipboard),
+                    selection: Some(Box::new(selection)),
+                    registers: HashMap::new(),
+                    copied: Some(Copied::default()),
+                }
             },
             None => Self::default(),
         }
@@ -42,19 +76,32 @@ impl Clipboard {
     /// feature.
     #[cfg(any(test, not(any(feature = "x11", target_os = "macos", windows))))]
     pub fn new_nop() -> Self {
-        Self { clipboard: Box::new(NopClipboardContext::new().unwrap()), selection: None }
+        Self {
+            clipboard: Box::new(NopClipboardContext::new().unwrap()),
+            selection: None,
+            registers: HashMap::new(),
+            #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+            copied: None,
+        }
     }
 }
 
 impl Default for Clipboard {
     fn default() -> Self {
         #[cfg(any(target_os = "macos", windows))]
-        return Self { clipboard: Box::new(ClipboardContext::new().unwrap()), selection: None };
+        return Self {
+            clipboard: Box::new(ClipboardContext::new().unwrap()),
+            selection: None,
+            registers: HashMap::new(),
+        };
 
         #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
         return Self {
             clipboard: Box::new(ClipboardContext::new().unwrap()),
             selection: Some(Box::new(X11ClipboardContext::<X11SelectionClipboard>::new().unwrap())),
+            registers: HashMap::new(),
+            #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+            copied: None,
         };
 
         #[cfg(not(any(feature = "x11", target_os = "macos", windows)))]
@@ -70,11 +117,74 @@ impl Clipboard {
             _ => &mut self.clipboard,
         };
 
-        clipboard.set_contents(text.into()).unwrap_or_else(|err| {
+        // Most applications would truncate the text at the first NUL character.
+        let mut text = text.into();
+        text.retain(|c| c != '\0');
+
+        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+        if let Some(copied) = &mut self.copied {
+            match ty {
+                ClipboardType::Clipboard => copied.clipboard = Some(text.clone()),
+                ClipboardType::Selection => copied.selection = Some(text.clone()),
+            }
+        }
+
+        clipboard.set_contents(text).unwrap_or_else(|err| {
             warn!("Unable to store text in clipboard: {}", err);
         });
     }
 
+    /// Store text together with an optional HTML version of it.
+    ///
+    /// Without [`HTML_SUPPORT`], only the plain text is stored.
+    pub fn store_html(&mut self, ty: ClipboardType, text: String, html: Option<String>) {
+        #[cfg(target_os = "macos")]
+        if let (ClipboardType::Clipboard, Some(html)) = (ty, &html) {
+            // Like `store`, strip NUL characters which would truncate the text.
+            let text = text.replace('\0', "");
+            let html = html.replace('\0', "");
+
+            if pasteboard::store(&[(pasteboard::PLAIN_TEXT, &text), (pasteboard::HTML, &html)]) {
+                return;
+            }
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        let _ = html;
+
+        self.store(ty, text);
+    }
+
+    /// Keep serving copied text after Alacritty has exited.
+    ///
+    /// On Wayland the clipboard content is provided by the application which copied it, so it is
+    /// handed over to `wl-copy` if Alacritty still owns the clipboard.
+    pub fn persist(&mut self) {
+        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+        if let Some(copied) = self.copied.take() {
+            let clipboards = [
+                (ClipboardType::Clipboard, copied.clipboard),
+                (ClipboardType::Selection, copied.selection),
+            ];
+
+            for (ty, text) in clipboards.iter() {
+                // Skip clipboards which have been overwritten by other applications.
+                let text = match text {
+                    Some(text) if *text == self.load(*ty) => text,
+                    _ => continue,
+                };
+
+                match wl_copy(*ty, text) {
+                    Err(err) if err.kind() == ErrorKind::NotFound => {
+                        debug!("Unable to persist clipboard, wl-copy is not installed");
+                    },
+                    Err(err) => warn!("Unable to persist clipboard using wl-copy: {}", err),
+                    Ok(()) => (),
+                }
+            }
+        }
+    }
+
     pub fn load(&mut self, ty: ClipboardType) -> String {
         let clipboard = match (ty, &mut self.selection) {
             (ClipboardType::Selection, Some(provider)) => provider,
@@ -89,4 +199,106 @@ impl Clipboard {
             Ok(text) => text,
         }
     }
+
+    /// Store text in a register.
+    pub fn store_register(&mut self, register: Register, text: String) {
+        match register {
+            Register::Clipboard => self.store(ClipboardType::Clipboard, text),
+            Register::Selection => self.store(ClipboardType::Selection, text),
+            Register::Named(name) => {
+                self.registers.insert(name, text);
+            },
+        }
+    }
+
+    /// Load text from a register.
+    pub fn load_register(&mut self, register: Register) -> String {
+        match register {
+            Register::Clipboard => self.load(ClipboardType::Clipboard),
+            Register::Selection => self.load(ClipboardType::Selection),
+            Register::Named(name) => self.registers.get(&name).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// Append text to the content of a clipboard, starting it on a new line.
+pub fn append_line(mut content: String, text: &str) -> String {
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(text);
+    content
+}
+
+/// Target for copying and pasting text, named like the registers in Vim.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Register {
+    /// System clipboard, named `+`.
+    Clipboard,
+
+    /// Selection buffer, named `*`, which only exists on X11 and Wayland.
+    Selection,
+
+    /// Register which only lives as long as Alacritty, named `a` to `z`.
+    Named(char),
+}
+
+impl Register {
+    /// Get the register with the specified name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        let register = match (chars.next()?, chars.next()) {
+            ('+', None) => Self::Clipboard,
+            ('*', None) if SELECTION_SUPPORT => Self::Selection,
+            (name @ 'a'..='z', None) => Self::Named(name),
+            _ => return None,
+        };
+
+        Some(register)
+    }
+}
+
+/// Serve text from a background `wl-copy` process.
+#[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+fn wl_copy(ty: ClipboardType, text: &str) -> std::io::Result<()> {
+    let mut command = Command::new("wl-copy");
+    command.args(&["--type", "text/plain;charset=utf-8"]);
+    if ty == ClipboardType::Selection {
+        command.arg("--primary");
+    }
+
+    let mut child = command.stdin(Stdio::piped()).stdout(Stdio::null()).spawn()?;
+    child.stdin.take().unwrap().write_all(text.as_bytes())?;
+
+    // The foreground process exits once `wl-copy` has forked into the background.
+    child.wait().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_registers() {
+        assert_eq!(Register::from_name("+"), Some(Register::Clipboard));
+        assert_eq!(Register::from_name("*"), Some(Register::Selection));
+        assert_eq!(Register::from_name("q"), Some(Register::Named('q')));
+        assert_eq!(Register::from_name("Q"), None);
+        assert_eq!(Register::from_name("ab"), None);
+        assert_eq!(Register::from_name(""), None);
+
+        let mut clipboard = Clipboard::new_nop();
+        clipboard.store_register(Register::Named('a'), String::from("first"));
+        clipboard.store_register(Register::Named('b'), String::from("second"));
+        assert_eq!(clipboard.load_register(Register::Named('a')), "first");
+        assert_eq!(clipboard.load_register(Register::Named('b')), "second");
+        assert_eq!(clipboard.load_register(Register::Named('c')), "");
+    }
+
+    #[test]
+    fn append_lines() {
+        assert_eq!(append_line(String::new(), "first"), "first");
+        assert_eq!(append_line(String::from("first"), "second"), "first\nsecond");
+        assert_eq!(append_line(String::from("first\n"), "second"), "first\nsecond");
+    }
 }
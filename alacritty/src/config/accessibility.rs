@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use alacritty_config_derive::ConfigDeserialize;
+use alacritty_terminal::term::color::Rgb;
+
+use crate::config::color::{BrightColors, Colors, InvertedCellColors, NormalColors, PrimaryColors};
+
+/// Factor applied to the thickness of underlines and the cursor with `thick_lines` enabled.
+const LINE_THICKNESS_FACTOR: f32 = 2.;
+
+#[derive(ConfigDeserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct Accessibility {
+    /// Replace the primary, normal and bright colors with a high-contrast palette and invert the
+    /// cell colors for the cursor and selection.
+    pub high_contrast: bool,
+
+    /// Disable cursor and text blinking, the visual bell and the opacity and theme transitions.
+    pub reduced_motion: bool,
+
+    /// Double the thickness of underlines, strikeouts and the cursor.
+    pub thick_lines: bool,
+}
+
+impl Accessibility {
+    /// Apply the high-contrast palette on top of a color scheme.
+    ///
+    /// Indexed colors, search and hint colors are kept, since they're already expected to stand
+    /// out from the regular text.
+    pub fn override_colors(&self, colors: &mut Colors) {
+        if !self.high_contrast {
+            return;
+        }
+
+        colors.primary = PrimaryColors {
+            foreground: Rgb { r: 0xff, g: 0xff, b: 0xff },
+            background: Rgb { r: 0x00, g: 0x00, b: 0x00 },
+            bright_foreground: None,
+            dim_foreground: None,
+        };
+        colors.normal = NormalColors {
+            black: Rgb { r: 0x00, g: 0x00, b: 0x00 },
+            red: Rgb { r: 0xff, g: 0x6b, b: 0x6b },
+            green: Rgb { r: 0x4e, g: 0xe4, b: 0x4e },
+            yellow: Rgb { r: 0xff, g: 0xeb, b: 0x3b },
+            blue: Rgb { r: 0x6c, g: 0xb6, b: 0xff },
+            magenta: Rgb { r: 0xff, g: 0x79, b: 0xff },
+            cyan: Rgb { r: 0x4e, g: 0xf0, b: 0xf0 },
+            white: Rgb { r: 0xe0, g: 0xe0, b: 0xe0 },
+        };
+        colors.bright = BrightColors {
+            black: Rgb { r: 0x80, g: 0x80, b: 0x80 },
+            red: Rgb { r: 0xff, g: 0x90, b: 0x90 },
+            green: Rgb { r: 0x90, g: 0xff, b: 0x90 },
+            yellow: Rgb { r: 0xff, g: 0xff, b: 0x80 },
+            blue: Rgb { r: 0xa0, g: 0xd0, b: 0xff },
+            magenta: Rgb { r: 0xff, g: 0xa0, b: 0xff },
+            cyan: Rgb { r: 0xa0, g: 0xff, b: 0xff },
+            white: Rgb { r: 0xff, g: 0xff, b: 0xff },
+        };
+        colors.dim = None;
+
+        // Invert the cell colors, which always has the maximum possible contrast.
+        colors.cursor = InvertedCellColors::default();
+        colors.vi_mode_cursor = InvertedCellColors::default();
+        colors.selection = InvertedCellColors::default();
+    }
+
+    /// Duration of an animation, taking reduced motion into account.
+    pub fn animation_duration(&self, duration: Duration) -> Duration {
+        if self.reduced_motion {
+            Duration::from_secs(0)
+        } else {
+            duration
+        }
+    }
+
+    /// Cursor thickness as fraction of the cell width.
+    pub fn cursor_thickness(&self, thickness: f32) -> f32 {
+        if self.thick_lines {
+            (thickness * LINE_THICKNESS_FACTOR).min(1.)
+        } else {
+            thickness
+        }
+    }
+
+    /// Font metrics used for drawing underlines and strikeouts.
+    pub fn line_metrics(&self, mut metrics: crossfont::Metrics) -> crossfont::Metrics {
+        if self.thick_lines {
+            metrics.underline_thickness *= LINE_THICKNESS_FACTOR;
+            metrics.strikeout_thickness *= LINE_THICKNESS_FACTOR;
+        }
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::term::color::CellRgb;
+
+    #[test]
+    fn high_contrast_override() {
+        let mut colors = Colors { indexed_colors: vec![Default::default()], ..Default::default() };
+        colors.cursor.background = CellRgb::Rgb(Rgb::default());
+
+        Accessibility::default().override_colors(&mut colors);
+        assert_ne!(colors.primary.background, Rgb { r: 0, g: 0, b: 0 });
+
+        let accessibility = Accessibility { high_contrast: true, ..Default::default() };
+        accessibility.override_colors(&mut colors);
+        assert_eq!(colors.primary.background, Rgb { r: 0, g: 0, b: 0 });
+        assert_eq!(colors.primary.foreground, Rgb { r: 0xff, g: 0xff, b: 0xff });
+        assert_eq!(colors.cursor, InvertedCellColors::default());
+        assert_eq!(colors.indexed_colors.len(), 1);
+    }
+}
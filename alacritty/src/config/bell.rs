@@ -7,16 +7,48 @@ use alacritty_terminal::term::color::Rgb;
 
 #[derive(ConfigDeserialize, Clone, Debug, PartialEq, Eq)]
 pub struct BellConfig {
-    /// Visual bell animation function.
+    /// Visual Bell Animation
+    ///
+    /// Animation effect for flashing the screen when the visual bell is rung.
+    ///
+    /// Values for `animation`:
+    ///   - Ease
+    ///   - EaseOut
+    ///   - EaseOutSine
+    ///   - EaseOutQuad
+    ///   - EaseOutCubic
+    ///   - EaseOutQuart
+    ///   - EaseOutQuint
+    ///   - EaseOutExpo
+    ///   - EaseOutCirc
+    ///   - Linear
     pub animation: BellAnimation,
 
-    /// Command to run on bell.
+    /// Bell Command
+    ///
+    /// This program is executed whenever the bell is rung.
+    ///
+    /// When set to `command: None`, no command will be executed.
+    ///
+    /// Example:
+    ///   command:
+    ///     program: notify-send
+    ///     args: ["Hello, World!"]
     pub command: Option<Program>,
 
-    /// Visual bell flash color.
+    /// Bell Sound
+    ///
+    /// Values for `sound`:
+    ///   - System: Play the system alert sound (not supported on Wayland)
+    ///   - Command: Execute the bell `command`
+    ///   - None: Stay silent
+    pub sound: BellSound,
+
+    /// Visual bell animation color.
     pub color: Rgb,
 
-    /// Visual bell duration in milliseconds.
+    /// Duration of the visual bell flash in milliseconds. A `duration` of `0` will disable the
+    /// visual bell animation.
     duration: u16,
 }
 
@@ -26,6 +58,7 @@ impl Default for BellConfig {
             color: Rgb { r: 255, g: 255, b: 255 },
             animation: Default::default(),
             command: Default::default(),
+            sound: Default::default(),
             duration: Default::default(),
         }
     }
@@ -37,6 +70,25 @@ impl BellConfig {
     }
 }
 
+/// Sound played when the bell is rung.
+#[derive(ConfigDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BellSound {
+    /// Native alert sound of the platform.
+    System,
+
+    /// Execute the bell command.
+    Command,
+
+    /// No sound at all.
+    None,
+}
+
+impl Default for BellSound {
+    fn default() -> Self {
+        BellSound::Command
+    }
+}
+
 /// `VisualBellAnimations` are modeled after a subset of CSS transitions and Robert
 /// Penner's Easing Functions.
 #[derive(ConfigDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
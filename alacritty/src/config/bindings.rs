@@ -1,6 +1,7 @@
 #![allow(clippy::enum_glob_use)]
 
 use std::fmt::{self, Debug, Display};
+use std::path::PathBuf;
 
 use bitflags::bitflags;
 use glutin::event::VirtualKeyCode::*;
@@ -10,18 +11,21 @@ use serde::de::{self, MapAccess, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer};
 use serde_yaml::Value as SerdeValue;
 
+use alacritty_config::{ConfigTemplate, Template};
 use alacritty_config_derive::ConfigDeserialize;
 
-use alacritty_terminal::config::Program;
+use alacritty_terminal::config::{Percentage, Program};
 use alacritty_terminal::term::TermMode;
 use alacritty_terminal::vi_mode::ViMotion;
 
+use crate::clipboard::{Register, SELECTION_SUPPORT};
+use crate::config::keyboard::{FunctionKeys, Keyboard};
 use crate::config::ui_config::Hint;
 
 /// Describes a state and action to take in that state.
 ///
 /// This is the shared component of `MouseBinding` and `KeyBinding`.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Binding<T> {
     /// Modifier keys required to activate binding.
     pub mods: ModifiersState,
@@ -83,7 +87,7 @@ impl<T: Eq> Binding<T> {
     }
 }
 
-#[derive(ConfigDeserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(ConfigDeserialize, Debug, Clone, PartialEq)]
 pub enum Action {
     /// Write an escape sequence.
     #[config(skip)]
@@ -109,12 +113,30 @@ pub enum Action {
     #[config(skip)]
     SearchAction(SearchAction),
 
+    /// Change the background opacity.
+    #[config(skip)]
+    SetOpacity(f32),
+
     /// Paste contents of system clipboard.
     Paste,
 
+    /// Paste contents of system clipboard in small chunks.
+    PasteSlowly,
+
     /// Store current selection into clipboard.
     Copy,
 
+    /// Append current selection to the clipboard, separated by a newline.
+    AppendSelectionToClipboard,
+
+    /// Store current selection into a register.
+    #[config(skip)]
+    CopyToRegister(Register),
+
+    /// Paste contents of a register.
+    #[config(skip)]
+    PasteRegister(Register),
+
     #[cfg(not(any(target_os = "macos", windows)))]
     /// Store current selection into selection buffer.
     CopySelection,
@@ -158,6 +180,15 @@ pub enum Action {
     /// Clear the display buffer(s) to remove history.
     ClearHistory,
 
+    /// Scroll to the previous prompt marked by the shell.
+    ScrollToPreviousPrompt,
+
+    /// Scroll to the next prompt marked by the shell.
+    ScrollToNextPrompt,
+
+    /// Select the output of the last command marked by the shell.
+    SelectLastCommandOutput,
+
     /// Hide the Alacritty window.
     Hide,
 
@@ -175,7 +206,8 @@ pub enum Action {
     ClearLogNotice,
 
     /// Spawn a new instance of Alacritty.
-    SpawnNewInstance,
+    #[config(skip)]
+    SpawnNewInstance(SpawnOptions),
 
     /// Toggle fullscreen.
     ToggleFullscreen,
@@ -184,6 +216,15 @@ pub enum Action {
     #[cfg(target_os = "macos")]
     ToggleSimpleFullscreen,
 
+    /// Toggle between the configured background opacity and an opaque background.
+    ToggleOpacity,
+
+    /// Swap the color scheme with the alternate colors.
+    ToggleTheme,
+
+    /// Toggle rendering the grid at a magnified scale.
+    ToggleZoom,
+
     /// Clear active selection.
     ClearSelection,
 
@@ -199,6 +240,18 @@ pub enum Action {
     /// Start a backward buffer search.
     SearchBackward,
 
+    /// Show hint labels for all visible URLs.
+    OpenURLHints,
+
+    /// Save the visible screen as SVG image.
+    ExportSvg,
+
+    /// Save the window as PNG image.
+    Screenshot,
+
+    /// Save the visible part of the selection as PNG image.
+    ScreenshotSelection,
+
     /// No action.
     None,
 }
@@ -238,6 +291,38 @@ impl Display for Action {
     }
 }
 
+/// Options for spawning a new instance of Alacritty.
+#[derive(ConfigDeserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct SpawnOptions {
+    /// Source of the working directory for the new instance.
+    pub working_directory: SpawnDirectory,
+
+    /// Configuration file used by the new instance.
+    pub profile: Option<PathBuf>,
+
+    /// Arguments replacing the ones Alacritty was started with.
+    pub args: Option<Vec<String>>,
+}
+
+/// Source of the working directory for new instances.
+#[derive(ConfigDeserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpawnDirectory {
+    /// Directory reported by the shell using `OSC 7`, falling back to the foreground process.
+    Osc7,
+
+    /// Working directory of the foreground process.
+    ForegroundProcess,
+
+    /// Home directory of the user.
+    Home,
+}
+
+impl Default for SpawnDirectory {
+    fn default() -> Self {
+        SpawnDirectory::ForegroundProcess
+    }
+}
+
 /// Vi mode specific actions.
 #[derive(ConfigDeserialize, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ViAction {
@@ -259,6 +344,20 @@ pub enum ViAction {
     SearchEnd,
     /// Launch the URL below the vi mode cursor.
     Open,
+    /// Set the mark named by the next character.
+    SetMark,
+    /// Jump to the mark named by the next character.
+    JumpToMark,
+    /// Jump to the first non-blank cell in the line of the mark named by the next character.
+    JumpToMarkLine,
+    /// Jump to the previous position in the jump list.
+    JumpBack,
+    /// Jump to the next position in the jump list.
+    JumpForward,
+    /// Select the inner text object named by the next character.
+    InnerObject,
+    /// Select the text object named by the next character, including its surroundings.
+    AroundObject,
 }
 
 /// Search mode specific actions.
@@ -353,6 +452,7 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         Copy,  +BindingMode::VI; Action::ClearSelection;
         Paste, ~BindingMode::VI; Action::Paste;
         L, ModifiersState::CTRL; Action::ClearLogNotice;
+        U, ModifiersState::CTRL | ModifiersState::SHIFT; Action::OpenURLHints;
         L,    ModifiersState::CTRL,  ~BindingMode::VI, ~BindingMode::SEARCH;
             Action::Esc("\x0c".into());
         Tab,  ModifiersState::SHIFT, ~BindingMode::VI, ~BindingMode::SEARCH;
@@ -402,37 +502,36 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         Delete,      ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[3~".into());
         PageUp,      ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[5~".into());
         PageDown,    ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[6~".into());
-        F1,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1bOP".into());
-        F2,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1bOQ".into());
-        F3,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1bOR".into());
-        F4,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1bOS".into());
-        F5,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[15~".into());
-        F6,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[17~".into());
-        F7,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[18~".into());
-        F8,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[19~".into());
-        F9,          ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[20~".into());
-        F10,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[21~".into());
-        F11,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[23~".into());
-        F12,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[24~".into());
-        F13,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[25~".into());
-        F14,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[26~".into());
-        F15,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[28~".into());
-        F16,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[29~".into());
-        F17,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[31~".into());
-        F18,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[32~".into());
-        F19,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[33~".into());
-        F20,         ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\x1b[34~".into());
-        NumpadEnter, ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc("\n".into());
+        NumpadEnter,    ~BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::Esc("\n".into());
+        NumpadEnter,    +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::Esc("\x1bOM".into());
+        NumpadMultiply, +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::Esc("\x1bOj".into());
+        NumpadAdd,      +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::Esc("\x1bOk".into());
+        NumpadComma,    +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::Esc("\x1bOl".into());
+        NumpadSubtract, +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::Esc("\x1bOm".into());
+        NumpadDivide,   +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::Esc("\x1bOo".into());
+        NumpadEquals,   +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::SEARCH;
+            Action::Esc("\x1bOX".into());
         Space, ModifiersState::SHIFT | ModifiersState::CTRL, +BindingMode::VI, ~BindingMode::SEARCH;
             Action::ScrollToBottom;
         Space, ModifiersState::SHIFT | ModifiersState::CTRL, ~BindingMode::SEARCH;
             Action::ToggleViMode;
         Escape,                        +BindingMode::VI, ~BindingMode::SEARCH;
             Action::ClearSelection;
-        I,                             +BindingMode::VI, ~BindingMode::SEARCH;
+        I,             +BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::SELECTION;
             Action::ScrollToBottom;
-        I,                             +BindingMode::VI, ~BindingMode::SEARCH;
+        I,             +BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::SELECTION;
             Action::ToggleViMode;
+        I,             +BindingMode::VI, +BindingMode::SELECTION, ~BindingMode::SEARCH;
+            ViAction::InnerObject;
+        A,             +BindingMode::VI, +BindingMode::SELECTION, ~BindingMode::SEARCH;
+            ViAction::AroundObject;
         C,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
             Action::ToggleViMode;
         Y,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
@@ -472,6 +571,16 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
             ViAction::SearchPrevious;
         Return,                        +BindingMode::VI, ~BindingMode::SEARCH;
             ViAction::Open;
+        M,                             +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::SetMark;
+        Grave,                         +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::JumpToMark;
+        Apostrophe,                    +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::JumpToMarkLine;
+        O,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::JumpBack;
+        I,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::JumpForward;
         K,                             +BindingMode::VI, ~BindingMode::SEARCH;
             ViMotion::Up;
         J,                             +BindingMode::VI, ~BindingMode::SEARCH;
@@ -488,7 +597,7 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
             ViMotion::Left;
         Right,                         +BindingMode::VI, ~BindingMode::SEARCH;
             ViMotion::Right;
-        Key0,                          +BindingMode::VI, ~BindingMode::SEARCH;
+        Key0,                 +BindingMode::VI, ~BindingMode::SEARCH, ~BindingMode::COUNT;
             ViMotion::First;
         Key4,   ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
             ViMotion::Last;
@@ -530,29 +639,7 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
             SearchAction::SearchFocusPrevious;
     );
 
-    //   Code     Modifiers
-    // ---------+---------------------------
-    //    2     | Shift
-    //    3     | Alt
-    //    4     | Shift + Alt
-    //    5     | Control
-    //    6     | Shift + Control
-    //    7     | Alt + Control
-    //    8     | Shift + Alt + Control
-    // ---------+---------------------------
-    //
-    // from: https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-PC-Style-Function-Keys
-    let mut modifiers = vec![
-        ModifiersState::SHIFT,
-        ModifiersState::ALT,
-        ModifiersState::SHIFT | ModifiersState::ALT,
-        ModifiersState::CTRL,
-        ModifiersState::SHIFT | ModifiersState::CTRL,
-        ModifiersState::ALT | ModifiersState::CTRL,
-        ModifiersState::SHIFT | ModifiersState::ALT | ModifiersState::CTRL,
-    ];
-
-    for (index, mods) in modifiers.drain(..).enumerate() {
+    for (index, &mods) in encoded_modifiers().iter().enumerate() {
         let modifiers_code = index + 2;
         bindings.extend(bindings!(
             KeyBinding;
@@ -566,46 +653,6 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
                 Action::Esc(format!("\x1b[1;{}C", modifiers_code));
             Left,   mods, ~BindingMode::VI, ~BindingMode::SEARCH;
                 Action::Esc(format!("\x1b[1;{}D", modifiers_code));
-            F1,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[1;{}P", modifiers_code));
-            F2,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[1;{}Q", modifiers_code));
-            F3,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[1;{}R", modifiers_code));
-            F4,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[1;{}S", modifiers_code));
-            F5,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[15;{}~", modifiers_code));
-            F6,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[17;{}~", modifiers_code));
-            F7,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[18;{}~", modifiers_code));
-            F8,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[19;{}~", modifiers_code));
-            F9,     mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[20;{}~", modifiers_code));
-            F10,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[21;{}~", modifiers_code));
-            F11,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[23;{}~", modifiers_code));
-            F12,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[24;{}~", modifiers_code));
-            F13,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[25;{}~", modifiers_code));
-            F14,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[26;{}~", modifiers_code));
-            F15,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[28;{}~", modifiers_code));
-            F16,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[29;{}~", modifiers_code));
-            F17,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[31;{}~", modifiers_code));
-            F18,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[32;{}~", modifiers_code));
-            F19,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[33;{}~", modifiers_code));
-            F20,    mods, ~BindingMode::VI, ~BindingMode::SEARCH;
-                Action::Esc(format!("\x1b[34;{}~", modifiers_code));
         ));
 
         // We're adding the following bindings with `Shift` manually above, so skipping them here.
@@ -631,6 +678,93 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
     bindings
 }
 
+/// Modifiers which are encoded in escape sequences, starting with code 2.
+///
+///   Code     Modifiers
+/// ---------+---------------------------
+///    2     | Shift
+///    3     | Alt
+///    4     | Shift + Alt
+///    5     | Control
+///    6     | Shift + Control
+///    7     | Alt + Control
+///    8     | Shift + Alt + Control
+/// ---------+---------------------------
+///
+/// from: https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-PC-Style-Function-Keys
+fn encoded_modifiers() -> [ModifiersState; 7] {
+    [
+        ModifiersState::SHIFT,
+        ModifiersState::ALT,
+        ModifiersState::SHIFT | ModifiersState::ALT,
+        ModifiersState::CTRL,
+        ModifiersState::SHIFT | ModifiersState::CTRL,
+        ModifiersState::ALT | ModifiersState::CTRL,
+        ModifiersState::SHIFT | ModifiersState::ALT | ModifiersState::CTRL,
+    ]
+}
+
+/// Function keys with the parameter of their `CSI <code> ~` sequence.
+const FUNCTION_KEYS: [(VirtualKeyCode, u8); 20] = [
+    (F1, 11),
+    (F2, 12),
+    (F3, 13),
+    (F4, 14),
+    (F5, 15),
+    (F6, 17),
+    (F7, 18),
+    (F8, 19),
+    (F9, 20),
+    (F10, 21),
+    (F11, 23),
+    (F12, 24),
+    (F13, 25),
+    (F14, 26),
+    (F15, 28),
+    (F16, 29),
+    (F17, 31),
+    (F18, 32),
+    (F19, 33),
+    (F20, 34),
+];
+
+/// Create the bindings for all function keys using the configured encoding.
+pub fn function_key_bindings(keyboard: &Keyboard) -> Vec<KeyBinding> {
+    let mut bindings = Vec::new();
+
+    for (index, &(key, code)) in FUNCTION_KEYS.iter().enumerate() {
+        // The xterm encoding sends F1-F4 as SS3 sequences, like the VT100 PF1-PF4 keys.
+        let final_char = match keyboard.function_keys {
+            FunctionKeys::Xterm if index < 4 => Some((b'P' + index as u8) as char),
+            _ => None,
+        };
+
+        let sequence = |modifiers_code: Option<usize>| match (final_char, modifiers_code) {
+            (Some(c), None) => format!("\x1bO{}", c),
+            (Some(c), Some(modifiers_code)) => format!("\x1b[1;{}{}", modifiers_code, c),
+            (None, None) => format!("\x1b[{}~", code),
+            (None, Some(modifiers_code)) => format!("\x1b[{};{}~", code, modifiers_code),
+        };
+
+        bindings.extend(bindings!(
+            KeyBinding;
+            key, ~BindingMode::VI, ~BindingMode::SEARCH; Action::Esc(sequence(None));
+        ));
+
+        // Without modifier encoding, modified function keys send their plain sequence.
+        for (index, &mods) in encoded_modifiers().iter().enumerate() {
+            let modifiers_code = if keyboard.modify_function_keys { Some(index + 2) } else { None };
+            bindings.extend(bindings!(
+                KeyBinding;
+                key, mods, ~BindingMode::VI, ~BindingMode::SEARCH;
+                    Action::Esc(sequence(modifiers_code));
+            ));
+        }
+    }
+
+    bindings
+}
+
 #[cfg(not(any(target_os = "macos", test)))]
 fn common_keybindings() -> Vec<KeyBinding> {
     bindings!(
@@ -655,7 +789,15 @@ fn common_keybindings() -> Vec<KeyBinding> {
 
 #[cfg(not(any(target_os = "macos", target_os = "windows", test)))]
 pub fn platform_key_bindings() -> Vec<KeyBinding> {
-    common_keybindings()
+    let mut bindings = bindings!(
+        KeyBinding;
+        Y, ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            Action::CopyToRegister(Register::Selection);
+        Y, ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            Action::ClearSelection;
+    );
+    bindings.extend(common_keybindings());
+    bindings
 }
 
 #[cfg(all(target_os = "windows", not(test)))]
@@ -684,7 +826,7 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
             Action::Esc("\x0c".into());
         K, ModifiersState::LOGO, ~BindingMode::VI, ~BindingMode::SEARCH;  Action::ClearHistory;
         V, ModifiersState::LOGO, ~BindingMode::VI; Action::Paste;
-        N, ModifiersState::LOGO; Action::SpawnNewInstance;
+        N, ModifiersState::LOGO; Action::SpawnNewInstance(SpawnOptions::default());
         F, ModifiersState::CTRL | ModifiersState::LOGO; Action::ToggleFullscreen;
         C, ModifiersState::LOGO; Action::Copy;
         C, ModifiersState::LOGO, +BindingMode::VI, ~BindingMode::SEARCH; Action::ClearSelection;
@@ -726,6 +868,15 @@ impl<'a> Deserialize<'a> for Key {
     }
 }
 
+impl ConfigTemplate for Key {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        match self {
+            Key::Scancode(scancode) => template.value(key, scancode),
+            Key::Keycode(keycode) => template.value(key, format_args!("{:?}", keycode)),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ModeWrapper {
     pub mode: BindingMode,
@@ -740,17 +891,21 @@ bitflags! {
         const ALT_SCREEN          = 0b0000_0100;
         const VI                  = 0b0000_1000;
         const SEARCH              = 0b0001_0000;
+        const SELECTION           = 0b0010_0000;
+        const COUNT               = 0b0100_0000;
     }
 }
 
 impl BindingMode {
-    pub fn new(mode: &TermMode, search: bool) -> BindingMode {
+    pub fn new(mode: &TermMode, search: bool, selection: bool, count: bool) -> BindingMode {
         let mut binding_mode = BindingMode::empty();
         binding_mode.set(BindingMode::APP_CURSOR, mode.contains(TermMode::APP_CURSOR));
         binding_mode.set(BindingMode::APP_KEYPAD, mode.contains(TermMode::APP_KEYPAD));
         binding_mode.set(BindingMode::ALT_SCREEN, mode.contains(TermMode::ALT_SCREEN));
         binding_mode.set(BindingMode::VI, mode.contains(TermMode::VI));
         binding_mode.set(BindingMode::SEARCH, search);
+        binding_mode.set(BindingMode::SELECTION, selection);
+        binding_mode.set(BindingMode::COUNT, count);
         binding_mode
     }
 }
@@ -761,6 +916,31 @@ impl Default for ModeWrapper {
     }
 }
 
+impl ConfigTemplate for ModeWrapper {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        let names = [
+            (BindingMode::APP_CURSOR, "AppCursor"),
+            (BindingMode::APP_KEYPAD, "AppKeypad"),
+            (BindingMode::ALT_SCREEN, "Alt"),
+            (BindingMode::VI, "Vi"),
+            (BindingMode::SEARCH, "Search"),
+            (BindingMode::SELECTION, "Selection"),
+            (BindingMode::COUNT, "Count"),
+        ];
+
+        let mut modes = Vec::new();
+        for (mode, name) in names.iter() {
+            if self.mode.contains(*mode) {
+                modes.push(name.to_string());
+            } else if self.not_mode.contains(*mode) {
+                modes.push(format!("~{}", name));
+            }
+        }
+
+        template.value(key, modes.join("|"));
+    }
+}
+
 impl<'a> Deserialize<'a> for ModeWrapper {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -773,7 +953,8 @@ impl<'a> Deserialize<'a> for ModeWrapper {
 
             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 f.write_str(
-                    "a combination of AppCursor | AppKeypad | Alt | Vi, possibly with negation (~)",
+                    "a combination of AppCursor | AppKeypad | Alt | Vi | Search | Selection | \
+                     Count, possibly with negation (~)",
                 )
             }
 
@@ -796,6 +977,10 @@ impl<'a> Deserialize<'a> for ModeWrapper {
                         "~vi" => res.not_mode |= BindingMode::VI,
                         "search" => res.mode |= BindingMode::SEARCH,
                         "~search" => res.not_mode |= BindingMode::SEARCH,
+                        "selection" => res.mode |= BindingMode::SELECTION,
+                        "~selection" => res.not_mode |= BindingMode::SELECTION,
+                        "count" => res.mode |= BindingMode::COUNT,
+                        "~count" => res.not_mode |= BindingMode::COUNT,
                         _ => return Err(E::invalid_value(Unexpected::Str(modifier), &self)),
                     }
                 }
@@ -858,7 +1043,7 @@ impl<'a> Deserialize<'a> for MouseButtonWrapper {
 
 /// Bindings are deserialized into a `RawBinding` before being parsed as a
 /// `KeyBinding` or `MouseBinding`.
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq)]
 struct RawBinding {
     key: Option<Key>,
     mouse: Option<MouseButton>,
@@ -898,6 +1083,106 @@ impl RawBinding {
     }
 }
 
+/// Get the payload of a `{ text: "..." }` action.
+fn text_action(value: &SerdeValue) -> Option<&str> {
+    let map = value.as_mapping().filter(|map| map.len() == 1)?;
+    map.get(&SerdeValue::String("text".into()))?.as_str()
+}
+
+/// Get the payload of a `{ SetOpacity: ... }` action.
+fn opacity_action(value: &SerdeValue) -> Option<f32> {
+    let map = value.as_mapping().filter(|map| map.len() == 1)?;
+    let opacity = map.get(&SerdeValue::String("SetOpacity".into()))?.as_f64()?;
+    Some(Percentage::new(opacity as f32).as_f32())
+}
+
+/// Get the options of a `SpawnNewInstance` or `{ SpawnNewInstance: { ... } }` action.
+fn spawn_action<E: SerdeError>(value: &SerdeValue) -> Option<Result<SpawnOptions, E>> {
+    const NAME: &str = "SpawnNewInstance";
+
+    if value.as_str().map_or(false, |name| name.eq_ignore_ascii_case(NAME)) {
+        return Some(Ok(SpawnOptions::default()));
+    }
+
+    let map = value.as_mapping().filter(|map| map.len() == 1)?;
+    let options = map.get(&SerdeValue::String(NAME.into()))?;
+    Some(SpawnOptions::deserialize(options.clone()).map_err(E::custom))
+}
+
+/// Get a `{ CopyToRegister: ... }` or `{ PasteRegister: ... }` action.
+fn register_action<E: SerdeError>(value: &SerdeValue) -> Option<Result<Action, E>> {
+    let map = value.as_mapping().filter(|map| map.len() == 1)?;
+    let (name, register) = map.iter().next()?;
+
+    let action: fn(Register) -> Action = match name.as_str()? {
+        "CopyToRegister" => Action::CopyToRegister,
+        "PasteRegister" => Action::PasteRegister,
+        _ => return None,
+    };
+
+    let register = register.as_str().and_then(Register::from_name);
+    let expected = if SELECTION_SUPPORT {
+        "register must be `+`, `*` or `a`-`z`"
+    } else {
+        "register must be `+` or `a`-`z`"
+    };
+    Some(register.map(action).ok_or_else(|| E::custom(expected)))
+}
+
+/// Expand the escape sequences in the text payload of a binding.
+///
+/// Supported escapes are `\\`, `\e`, `\n`, `\r`, `\t`, `\xHH` and `\u{HHHHHH}`.
+fn unescape(text: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some('\\') => '\\',
+            Some('e') => '\x1b',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(byte) if hex.len() == 2 && byte.is_ascii() => byte as char,
+                    _ => return Err(format!("invalid escape `\\x{}` in text `{}`", hex, text)),
+                }
+            },
+            Some('u') => {
+                let rest = chars.as_str();
+                let end = rest.find('}').filter(|_| rest.starts_with('{'));
+                let hex = match end {
+                    Some(end) => &rest[1..end],
+                    None => return Err(format!("unterminated escape `\\u` in text `{}`", text)),
+                };
+
+                let escaped = u32::from_str_radix(hex, 16).ok().and_then(std::char::from_u32);
+                chars = rest[hex.len() + 2..].chars();
+
+                match escaped {
+                    Some(escaped) => escaped,
+                    None => {
+                        return Err(format!("invalid escape `\\u{{{}}}` in text `{}`", hex, text))
+                    },
+                }
+            },
+            Some(c) => return Err(format!("unknown escape `\\{}` in text `{}`", c, text)),
+            None => return Err(format!("trailing backslash in text `{}`", text)),
+        };
+
+        unescaped.push(escaped);
+    }
+
+    Ok(unescaped)
+}
+
 impl<'a> Deserialize<'a> for RawBinding {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -1018,7 +1303,15 @@ impl<'a> Deserialize<'a> for RawBinding {
 
                             let value = map.next_value::<SerdeValue>()?;
 
-                            action = if let Ok(vi_action) = ViAction::deserialize(value.clone()) {
+                            action = if let Some(text) = text_action(&value) {
+                                Some(Action::Esc(unescape(text).map_err(V::Error::custom)?))
+                            } else if let Some(opacity) = opacity_action(&value) {
+                                Some(Action::SetOpacity(opacity))
+                            } else if let Some(options) = spawn_action::<V::Error>(&value) {
+                                Some(Action::SpawnNewInstance(options?))
+                            } else if let Some(action) = register_action::<V::Error>(&value) {
+                                Some(action?)
+                            } else if let Ok(vi_action) = ViAction::deserialize(value.clone()) {
                                 Some(vi_action.into())
                             } else if let Ok(vi_motion) = ViMotion::deserialize(value.clone()) {
                                 Some(vi_motion.into())
@@ -1158,6 +1451,29 @@ impl ModsWrapper {
     }
 }
 
+impl ConfigTemplate for ModsWrapper {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        let names = [
+            (ModifiersState::LOGO, "Super"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::CTRL, "Control"),
+        ];
+
+        let mods: Vec<_> = names
+            .iter()
+            .filter(|(mods, _)| self.0.contains(*mods))
+            .map(|(_, name)| *name)
+            .collect();
+
+        if mods.is_empty() {
+            template.value(key, "None");
+        } else {
+            template.value(key, mods.join("|"));
+        }
+    }
+}
+
 impl<'a> de::Deserialize<'a> for ModsWrapper {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -1401,4 +1717,160 @@ mod tests {
         assert!(!binding.is_triggered_by(BindingMode::ALT_SCREEN, mods, &t));
         assert!(!binding.is_triggered_by(BindingMode::ALT_SCREEN | BindingMode::VI, mods, &t));
     }
+
+    #[test]
+    fn vi_prefix_bindings() {
+        let actions = |key: VirtualKeyCode, mode: BindingMode| -> Vec<Action> {
+            default_key_bindings()
+                .into_iter()
+                .filter(|b| b.is_triggered_by(mode, ModifiersState::empty(), &Key::Keycode(key)))
+                .map(|b| b.action)
+                .collect()
+        };
+
+        let vi = BindingMode::VI;
+        let selection = BindingMode::VI | BindingMode::SELECTION;
+        let count = BindingMode::VI | BindingMode::COUNT;
+
+        assert_eq!(actions(I, vi), [Action::ScrollToBottom, Action::ToggleViMode]);
+        assert_eq!(actions(I, selection), [ViAction::InnerObject.into()]);
+        assert_eq!(actions(A, selection), [ViAction::AroundObject.into()]);
+        assert_eq!(actions(Key0, vi), [ViMotion::First.into()]);
+        assert!(actions(Key0, count).is_empty());
+    }
+
+    #[test]
+    fn function_key_encodings() {
+        let sequences = |keyboard: Keyboard, mods: ModifiersState| -> Vec<Action> {
+            function_key_bindings(&keyboard)
+                .into_iter()
+                .filter(|b| {
+                    b.mods == mods && [Key::Keycode(F1), Key::Keycode(F5)].contains(&b.trigger)
+                })
+                .map(|b| b.action)
+                .collect()
+        };
+
+        let xterm = Keyboard::default();
+        let vt220 = Keyboard { function_keys: FunctionKeys::Vt220, ..xterm };
+        let unmodified = Keyboard { modify_function_keys: false, ..vt220 };
+
+        assert_eq!(sequences(xterm, ModifiersState::empty()), [
+            Action::Esc("\x1bOP".into()),
+            Action::Esc("\x1b[15~".into())
+        ]);
+        assert_eq!(sequences(xterm, ModifiersState::CTRL), [
+            Action::Esc("\x1b[1;5P".into()),
+            Action::Esc("\x1b[15;5~".into())
+        ]);
+        assert_eq!(sequences(vt220, ModifiersState::SHIFT), [
+            Action::Esc("\x1b[11;2~".into()),
+            Action::Esc("\x1b[15;2~".into())
+        ]);
+        assert_eq!(sequences(unmodified, ModifiersState::SHIFT), [
+            Action::Esc("\x1b[11~".into()),
+            Action::Esc("\x1b[15~".into())
+        ]);
+    }
+
+    #[test]
+    fn unescape_text() {
+        assert_eq!(unescape("a\\x1b[1;5C\\u{1F980}\\\\\\e\\n").unwrap(), "a\x1b[1;5C🦀\\\x1b\n");
+        assert!(unescape("\\u{}").is_err());
+        assert!(unescape("\\x1").is_err());
+        assert!(unescape("\\xff").is_err());
+        assert!(unescape("\\u{d800}").is_err());
+        assert!(unescape("\\u{41").is_err());
+        assert!(unescape("\\q").is_err());
+        assert!(unescape("\\").is_err());
+    }
+
+    #[test]
+    fn deserialize_text_action() {
+        let value: SerdeValue =
+            serde_yaml::from_str(r#"{ key: Right, mods: Control, action: { text: '\x1b[1;5C' } }"#)
+                .unwrap();
+        let binding = KeyBinding::deserialize(value).unwrap();
+        assert_eq!(binding.action, Action::Esc("\x1b[1;5C".into()));
+
+        let value: SerdeValue =
+            serde_yaml::from_str(r#"{ key: Right, action: { text: '\x' } }"#).unwrap();
+        assert!(KeyBinding::deserialize(value).is_err());
+    }
+
+    #[test]
+    fn deserialize_opacity_action() {
+        let value: SerdeValue =
+            serde_yaml::from_str("{ key: O, mods: Control, action: { SetOpacity: 0.5 } }").unwrap();
+        let binding = KeyBinding::deserialize(value).unwrap();
+        assert_eq!(binding.action, Action::SetOpacity(0.5));
+
+        let value: SerdeValue =
+            serde_yaml::from_str("{ key: O, action: { SetOpacity: 2 } }").unwrap();
+        let binding = KeyBinding::deserialize(value).unwrap();
+        assert_eq!(binding.action, Action::SetOpacity(1.));
+    }
+
+    #[test]
+    fn deserialize_spawn_action() {
+        let value: SerdeValue =
+            serde_yaml::from_str("{ key: N, action: SpawnNewInstance }").unwrap();
+        let binding = KeyBinding::deserialize(value).unwrap();
+        assert_eq!(binding.action, Action::SpawnNewInstance(SpawnOptions::default()));
+
+        let value: SerdeValue = serde_yaml::from_str(
+            "{ key: N, action: { SpawnNewInstance: { working_directory: Home, args: [-e, top] } } }",
+        )
+        .unwrap();
+        let binding = KeyBinding::deserialize(value).unwrap();
+        assert_eq!(
+            binding.action,
+            Action::SpawnNewInstance(SpawnOptions {
+                working_directory: SpawnDirectory::Home,
+                profile: None,
+                args: Some(vec![String::from("-e"), String::from("top")]),
+            })
+        );
+
+        let value: SerdeValue =
+            serde_yaml::from_str("{ key: N, action: { SpawnNewInstance: { profile: work.yml } } }")
+                .unwrap();
+        let binding = KeyBinding::deserialize(value).unwrap();
+        assert_eq!(
+            binding.action,
+            Action::SpawnNewInstance(SpawnOptions {
+                profile: Some(PathBuf::from("work.yml")),
+                ..SpawnOptions::default()
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_register_action() {
+        let value: SerdeValue =
+            serde_yaml::from_str("{ key: Y, mode: Vi, action: { CopyToRegister: a } }").unwrap();
+        let binding = KeyBinding::deserialize(value).unwrap();
+        assert_eq!(binding.action, Action::CopyToRegister(Register::Named('a')));
+
+        let value: SerdeValue =
+            serde_yaml::from_str(r#"{ key: P, action: { PasteRegister: "*" } }"#).unwrap();
+        let binding = KeyBinding::deserialize(value);
+        if SELECTION_SUPPORT {
+            assert_eq!(binding.unwrap().action, Action::PasteRegister(Register::Selection));
+        } else {
+            assert!(binding.is_err());
+        }
+
+        let value: SerdeValue =
+            serde_yaml::from_str("{ key: P, action: { PasteRegister: ab } }").unwrap();
+        assert!(KeyBinding::deserialize(value).is_err());
+    }
+
+    #[test]
+    fn deserialize_append_action() {
+        let binding = "{ key: A, mods: Control|Shift, action: AppendSelectionToClipboard }";
+        let value: SerdeValue = serde_yaml::from_str(binding).unwrap();
+        let binding = KeyBinding::deserialize(value).unwrap();
+        assert_eq!(binding.action, Action::AppendSelectionToClipboard);
+    }
 }
@@ -1,21 +1,74 @@
 use serde::de::Error as SerdeError;
 use serde::{Deserialize, Deserializer};
 
+use alacritty_config::{ConfigTemplate, Template};
 use alacritty_config_derive::ConfigDeserialize;
 use alacritty_terminal::term::color::{CellRgb, Rgb};
 
 #[derive(ConfigDeserialize, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Colors {
+    /// Default colors
     pub primary: PrimaryColors,
+
+    /// Cursor colors
+    ///
+    /// Allowed values are CellForeground/CellBackground, which reference the affected cell, or
+    /// hexadecimal colors like #ff00ff.
     pub cursor: InvertedCellColors,
+
+    /// Vi mode cursor colors
+    ///
+    /// Allowed values are CellForeground/CellBackground, which reference the affected cell, or
+    /// hexadecimal colors like #ff00ff.
     pub vi_mode_cursor: InvertedCellColors,
+
+    /// Selection colors
+    ///
+    /// Allowed values are CellForeground/CellBackground, which reference the affected cell, or
+    /// hexadecimal colors like #ff00ff.
     pub selection: InvertedCellColors,
+
+    /// Normal colors
     pub normal: NormalColors,
+
+    /// Bright colors
     pub bright: BrightColors,
+
+    /// Dim colors
+    ///
+    /// If the dim colors are not set, they will be calculated automatically based on the `normal`
+    /// colors.
     pub dim: Option<DimColors>,
+
+    /// Indexed Colors
+    ///
+    /// The indexed colors include all colors from 16 to 256. When these are not set, they're
+    /// filled with sensible defaults.
+    ///
+    /// Example:
+    ///   `- { index: 16, color: '#ff00ff' }`
     pub indexed_colors: Vec<IndexedColor>,
+
+    /// Search colors
+    ///
+    /// Colors used for the search bar and match highlighting. Match colors also allow
+    /// CellForeground/CellBackground, which reference the affected cell.
     pub search: SearchColors,
+
+    /// Line indicator
+    ///
+    /// Color used for the indicator displaying the position in history during search and vi mode.
+    ///
+    /// By default, these will use the opposing primary color.
     pub line_indicator: LineIndicatorColors,
+
+    /// Keyboard regex hints
+    ///
+    /// The `start` colors are used for the first character in the hint label, the `end` colors
+    /// for all characters after it.
+    ///
+    /// Allowed values are CellForeground/CellBackground, which reference the affected cell, or
+    /// hexadecimal colors like #ff00ff.
     pub hints: HintColors,
 }
 
@@ -85,6 +138,15 @@ impl IndexedColor {
     }
 }
 
+impl ConfigTemplate for IndexedColor {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.section(key, |template| {
+            self.index().write_template("index", template);
+            self.color.write_template("color", template);
+        });
+    }
+}
+
 #[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
 struct ColorIndex(u8);
 
@@ -167,7 +229,16 @@ pub struct BarColors {
 pub struct PrimaryColors {
     pub foreground: Rgb,
     pub background: Rgb,
+
+    /// Bright foreground color
+    ///
+    /// If the bright foreground color is not set, or `draw_bold_text_with_bright_colors` is
+    /// `false`, the normal foreground color will be used.
     pub bright_foreground: Option<Rgb>,
+
+    /// Dim foreground color
+    ///
+    /// The dimmed foreground color is calculated automatically if it is not present.
     pub dim_foreground: Option<Rgb>,
 }
 
@@ -5,16 +5,30 @@ use alacritty_config_derive::ConfigDeserialize;
 /// Debugging options.
 #[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Debug {
+    /// Log level
+    ///
+    /// Values for `log_level`:
+    ///   - Off
+    ///   - Error
+    ///   - Warn
+    ///   - Info
+    ///   - Debug
+    ///   - Trace
     pub log_level: LevelFilter,
 
+    /// Print all received window events.
     pub print_events: bool,
 
-    /// Keep the log file after quitting.
+    /// Keep the log file after quitting Alacritty.
     pub persistent_logging: bool,
 
-    /// Should show render timer.
+    /// Display the time it takes to redraw each frame.
     pub render_timer: bool,
 
+    /// Draw an overlay showing the padding around the grid, the tab stops, the scrolling region
+    /// and all cells which changed since the last frame.
+    pub overlay: bool,
+
     /// Record ref test.
     #[config(skip)]
     pub ref_test: bool,
@@ -27,6 +41,7 @@ impl Default for Debug {
             print_events: Default::default(),
             persistent_logging: Default::default(),
             render_timer: Default::default(),
+            overlay: Default::default(),
             ref_test: Default::default(),
         }
     }
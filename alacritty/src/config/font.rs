@@ -1,9 +1,10 @@
 use std::fmt;
 
-use crossfont::Size as FontSize;
+use crossfont::{Metrics, Size as FontSize};
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer};
 
+use alacritty_config::{ConfigTemplate, Template};
 use alacritty_config_derive::ConfigDeserialize;
 
 use crate::config::ui_config::Delta;
@@ -16,28 +17,72 @@ use crate::config::ui_config::Delta;
 /// doesn't provide complete config is Ok.
 #[derive(ConfigDeserialize, Default, Debug, Clone, PartialEq, Eq)]
 pub struct Font {
-    /// Extra spacing per character.
+    /// Offset is the extra space around each character. `offset.y` can be thought of as
+    /// modifying the line spacing, and `offset.x` as modifying the letter spacing. Negative values
+    /// remove space from the cell instead.
+    ///
+    /// The additional space is added to the top and right of the glyphs, to keep them centered
+    /// use a `glyph_offset` of half the `offset`.
     pub offset: Delta<i8>,
 
-    /// Glyph offset within character cell.
+    /// Glyph offset determines the locations of the glyphs within their cells with the default
+    /// being at the bottom. Increasing `x` moves the glyph to the right, increasing `y` moves the
+    /// glyph upward.
     pub glyph_offset: Delta<i8>,
 
+    /// Thin stroke font rendering (macOS only)
+    ///
+    /// Thin strokes are suitable for retina displays, but for non-retina screens it is
+    /// recommended to set `use_thin_strokes` to `false`.
     pub use_thin_strokes: bool,
 
-    /// Normal font face.
+    /// Normal (roman) font face
+    ///
+    /// Default `family`:
+    ///   - (macOS) Menlo
+    ///   - (Linux/BSD) monospace
+    ///   - (Windows) Consolas
     normal: FontDescription,
 
-    /// Bold font face.
+    /// Bold font face
+    ///
+    /// If the bold family is not specified, it will fall back to the value specified for the
+    /// normal font.
     bold: SecondaryFontDescription,
 
-    /// Italic font face.
+    /// Italic font face
+    ///
+    /// If the italic family is not specified, it will fall back to the value specified for the
+    /// normal font.
     italic: SecondaryFontDescription,
 
-    /// Bold italic font face.
+    /// Bold italic font face
+    ///
+    /// If the bold italic family is not specified, it will fall back to the value specified for
+    /// the normal font.
     bold_italic: SecondaryFontDescription,
 
-    /// Font size in points.
+    /// Point size
     size: Size,
+
+    /// Style synthesis
+    ///
+    /// When the font family does not provide a bold or italic font, the glyphs of the closest
+    /// available font are emboldened or slanted instead. Fontconfig might already synthesize
+    /// missing styles on its own.
+    pub synthesize: Synthesis,
+
+    /// Font metrics override
+    ///
+    /// Replaces the metrics reported by the font, for fonts with broken metrics. All values are in
+    /// pixels at a scale factor of 1 and are not affected by changes to the font size.
+    pub metrics: MetricsOverride,
+
+    /// Missing glyph notice
+    ///
+    /// Characters which are missing from all fonts are logged once. When this is enabled, a
+    /// single message bar notice lists their codepoints as well.
+    pub missing_glyph_notice: bool,
 }
 
 impl Font {
@@ -72,10 +117,69 @@ impl Font {
     }
 }
 
+/// Emulation of font styles.
+#[derive(ConfigDeserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Synthesis {
+    /// Embolden regular glyphs when no bold font is available.
+    pub bold: bool,
+
+    /// Slant regular glyphs when no italic font is available.
+    pub italic: bool,
+}
+
+impl Default for Synthesis {
+    fn default() -> Self {
+        Self { bold: true, italic: true }
+    }
+}
+
+/// Font metrics replacing the values reported by the font, in pixels at a scale factor of 1.
+#[derive(ConfigDeserialize, Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MetricsOverride {
+    /// Width of a cell.
+    pub cell_width: Option<u16>,
+
+    /// Height of a cell.
+    pub cell_height: Option<u16>,
+
+    /// Distance from the top of the cell to the baseline.
+    pub ascent: Option<u16>,
+
+    /// Distance from the baseline to the underline.
+    pub underline_position: Option<u16>,
+}
+
+impl MetricsOverride {
+    /// Replace the metrics of a font rasterized with the scale factor `dpr`.
+    pub fn apply(&self, mut metrics: Metrics, dpr: f64) -> Metrics {
+        if let Some(cell_width) = self.cell_width {
+            metrics.average_advance = f64::from(cell_width) * dpr;
+        }
+
+        if let Some(cell_height) = self.cell_height {
+            metrics.line_height = f64::from(cell_height) * dpr;
+        }
+
+        // The descent is negative, since it is below the baseline.
+        if let Some(ascent) = self.ascent {
+            metrics.descent = (f64::from(ascent) * dpr - metrics.line_height) as f32;
+        }
+
+        if let Some(underline_position) = self.underline_position {
+            metrics.underline_position = -(f64::from(underline_position) * dpr) as f32;
+        }
+
+        metrics
+    }
+}
+
 /// Description of the normal font.
 #[derive(ConfigDeserialize, Debug, Clone, PartialEq, Eq)]
 pub struct FontDescription {
+    /// Font family
     pub family: String,
+
+    /// The `style` can be specified to pick a specific face.
     pub style: Option<String>,
 }
 
@@ -96,7 +200,10 @@ impl Default for FontDescription {
 /// Description of the italic and bold font.
 #[derive(ConfigDeserialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct SecondaryFontDescription {
+    /// Font family
     family: Option<String>,
+
+    /// The `style` can be specified to pick a specific face.
     style: Option<String>,
 }
 
@@ -118,6 +225,12 @@ impl Default for Size {
     }
 }
 
+impl ConfigTemplate for Size {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        self.0.as_f32_pts().write_template(key, template);
+    }
+}
+
 impl<'de> Deserialize<'de> for Size {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -143,3 +256,37 @@ impl<'de> Deserialize<'de> for Size {
         deserializer.deserialize_any(NumVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_override() {
+        let metrics = Metrics {
+            average_advance: 7.,
+            line_height: 15.,
+            descent: -3.,
+            underline_position: -2.,
+            underline_thickness: 1.,
+            strikeout_position: 4.,
+            strikeout_thickness: 1.,
+        };
+
+        let unchanged = MetricsOverride::default().apply(metrics, 2.);
+        assert_eq!(unchanged.line_height, 15.);
+        assert_eq!(unchanged.descent, -3.);
+
+        let metrics_override = MetricsOverride {
+            cell_height: Some(10),
+            ascent: Some(8),
+            underline_position: Some(1),
+            ..Default::default()
+        };
+        let metrics = metrics_override.apply(metrics, 2.);
+        assert_eq!(metrics.average_advance, 7.);
+        assert_eq!(metrics.line_height, 20.);
+        assert_eq!(metrics.descent, -4.);
+        assert_eq!(metrics.underline_position, -2.);
+    }
+}
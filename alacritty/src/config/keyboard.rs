@@ -0,0 +1,69 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Keyboard {
+    /// Function key encoding
+    ///
+    /// Values for `function_keys`:
+    ///   - Xterm: F1-F4 send `\x1bOP` through `\x1bOS`
+    ///   - Vt220: F1-F4 send `\x1b[11~` through `\x1b[14~`
+    ///
+    /// All other function keys send the same sequences in both modes.
+    pub function_keys: FunctionKeys,
+
+    /// Encode modifiers in the sequences of modified function keys, like `\x1b[1;5P` for
+    /// Control+F1. When disabled, modified function keys send the same sequence as without any
+    /// modifiers.
+    pub modify_function_keys: bool,
+
+    /// Flow control
+    ///
+    /// Values for `flow_control`:
+    ///   - Passthrough: `Control+S` and `Control+Q` are sent to the shell, where the terminal
+    ///     driver usually stops and resumes the output
+    ///   - Local: `Control+S` stops reading the output of the shell and shows an indicator until
+    ///     the output is resumed with `Control+Q`
+    pub flow_control: FlowControl,
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self {
+            function_keys: Default::default(),
+            modify_function_keys: true,
+            flow_control: Default::default(),
+        }
+    }
+}
+
+/// Handling of `Control+S` (XOFF) and `Control+Q` (XON).
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FlowControl {
+    /// Send the characters to the PTY, leaving flow control to the terminal driver.
+    Passthrough,
+
+    /// Stop and resume reading from the PTY inside Alacritty.
+    Local,
+}
+
+impl Default for FlowControl {
+    fn default() -> Self {
+        FlowControl::Passthrough
+    }
+}
+
+/// Encoding of the function keys.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FunctionKeys {
+    /// F1-F4 are sent as `SS3 P` through `SS3 S`.
+    Xterm,
+
+    /// F1-F4 are sent as `CSI 11 ~` through `CSI 14 ~`.
+    Vt220,
+}
+
+impl Default for FunctionKeys {
+    fn default() -> Self {
+        FunctionKeys::Xterm
+    }
+}
@@ -0,0 +1,237 @@
+//! Source locations of configuration problems.
+//!
+//! Deserialization works on the merged YAML values of all configuration files, which do not carry
+//! any position information. To still point users at the offending line, the sources of all files
+//! are kept around while loading and the option referenced by a problem is searched in them.
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+/// Quotes which can surround YAML keys.
+const QUOTES: &[char] = &['"', '\''];
+
+thread_local! {
+    /// Configuration files which are currently being loaded.
+    static SOURCES: RefCell<Vec<Source>> = RefCell::new(Vec::new());
+}
+
+/// Content of a single configuration file.
+struct Source {
+    path: PathBuf,
+    contents: String,
+}
+
+/// Position inside a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+
+    /// Line number, starting at 1.
+    pub line: usize,
+
+    /// Column number, starting at 1.
+    pub column: usize,
+}
+
+/// Register a configuration file for locating problems.
+pub fn register(path: &Path, contents: &str) {
+    SOURCES.with(|sources| {
+        sources.borrow_mut().push(Source { path: path.to_owned(), contents: contents.to_owned() })
+    });
+}
+
+/// Remove all registered configuration files.
+pub fn clear() {
+    SOURCES.with(|sources| sources.borrow_mut().clear());
+}
+
+/// Append the location of the referenced option to a configuration problem.
+///
+/// Returns `None` if the problem could not be traced back to a registered file.
+pub fn annotate(message: &str) -> Option<String> {
+    let subject = subject(message)?;
+    let path = alacritty_config::section_path();
+    let location = SOURCES.with(|sources| {
+        let sources = sources.borrow();
+        sources
+            .iter()
+            .find_map(|source| find_key(source, &path, subject))
+            .or_else(|| sources.iter().find_map(|source| find_value(source, subject)))
+    })?;
+
+    Some(format!(
+        "{}\n  --> {}:{}:{}",
+        message,
+        location.path.display(),
+        location.line,
+        location.column
+    ))
+}
+
+/// Option or value referenced by a configuration problem.
+///
+/// Errors for specific fields are formatted as `Config error: field: description`, all other
+/// problems highlight the offending value using backticks.
+fn subject(message: &str) -> Option<&str> {
+    let field = message
+        .strip_prefix("Config error: ")
+        .and_then(|error| error.find(':').map(|end| &error[..end]))
+        .filter(|field| field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+
+    field.or_else(|| {
+        let start = message.find('`')? + 1;
+        let end = start + message[start..].find('`')?;
+        Some(&message[start..end])
+    })
+}
+
+/// Find an option using its key, inside of the section at `path`.
+///
+/// Only sections deserialized by `#[derive(ConfigDeserialize)]` are part of the path, so options
+/// nested further below the section are used if there is no direct match.
+fn find_key(source: &Source, path: &[String], key: &str) -> Option<Location> {
+    let mut parents: Vec<(usize, &str)> = Vec::new();
+    let mut nested = None;
+
+    for (line, text) in lines(source) {
+        let (column, option) = match option_key(text) {
+            Some(option) => option,
+            None => continue,
+        };
+
+        // Leave all sections which do not contain this option.
+        while parents.last().map_or(false, |(indent, _)| *indent >= column) {
+            parents.pop();
+        }
+
+        let in_section = parents.len() >= path.len()
+            && parents.iter().zip(path).all(|((_, parent), section)| parent == section);
+        if option == key && in_section {
+            let location = location(source, line, &text[..column]);
+            if parents.len() == path.len() {
+                return Some(location);
+            }
+            nested = nested.or(Some(location));
+        }
+
+        parents.push((column, option));
+    }
+
+    nested
+}
+
+/// Key and column of the option defined on a line.
+fn option_key(text: &str) -> Option<(usize, &str)> {
+    let text = text.trim_end();
+    let indent = text.len() - text.trim_start().len();
+    let mut option = &text[indent..];
+    let mut column = indent;
+
+    // Skip the sequence indicator for keys inside of sequences.
+    if let Some(stripped) = option.strip_prefix("- ") {
+        column += option.len() - stripped.trim_start().len();
+        option = stripped.trim_start();
+    }
+
+    // Keys are separated from their value by a colon followed by whitespace.
+    let end = match option.find(": ") {
+        Some(end) => end,
+        None if option.ends_with(':') => option.len() - 1,
+        None => return None,
+    };
+
+    Some((column, option[..end].trim_end().trim_matches(QUOTES)))
+}
+
+/// Find the first occurrence of a value.
+fn find_value(source: &Source, value: &str) -> Option<Location> {
+    lines(source).find_map(|(line, text)| {
+        let column = text.find(value)?;
+        Some(location(source, line, &text[..column]))
+    })
+}
+
+/// Iterate over all lines of a file with their comments removed.
+fn lines(source: &Source) -> impl Iterator<Item = (usize, &str)> {
+    source.contents.lines().enumerate().filter_map(|(line, text)| {
+        let text = match text.find(" #") {
+            Some(comment) => &text[..comment],
+            None => text,
+        };
+
+        if text.trim_start().starts_with('#') {
+            None
+        } else {
+            Some((line, text))
+        }
+    })
+}
+
+/// Create the location for a line index and the text preceding the target.
+fn location(source: &Source, line: usize, prefix: &str) -> Location {
+    Location { path: source.path.clone(), line: line + 1, column: prefix.chars().count() + 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(contents: &str) -> Source {
+        Source { path: PathBuf::from("alacritty.yml"), contents: contents.into() }
+    }
+
+    #[test]
+    fn subject_of_field_error() {
+        let message = "Config error: style: unknown variant `Round`, expected one of `Block`";
+        assert_eq!(subject(message), Some("style"));
+    }
+
+    #[test]
+    fn subject_of_unknown_field() {
+        let message = "Config warning: unknown field `keybindings`; did you mean `key_bindings`?";
+        assert_eq!(subject(message), Some("keybindings"));
+
+        let message = "Config error: unknown variant `Cpy`, expected `Copy`; ignoring binding";
+        assert_eq!(subject(message), Some("Cpy"));
+    }
+
+    fn path(sections: &[&str]) -> Vec<String> {
+        sections.iter().map(|section| section.to_string()).collect()
+    }
+
+    #[test]
+    fn find_nested_key() {
+        let source = source("# keybindings: []\nwindow:\n  opacity: 1\n  keybindings: []\n");
+
+        let location = find_key(&source, &path(&["window"]), "keybindings").unwrap();
+
+        assert_eq!((location.line, location.column), (4, 3));
+    }
+
+    #[test]
+    fn find_duplicate_key() {
+        let source = source(
+            "bell:\n  duration: 0\nhints:\n  duration: 1\n  enabled:\n    - mouse:\n        \
+             duration: 2\nduration: 3\n",
+        );
+
+        let find = |sections: &[&str]| {
+            find_key(&source, &path(sections), "duration").map(|l| (l.line, l.column))
+        };
+
+        assert_eq!(find(&[]), Some((8, 1)));
+        assert_eq!(find(&["bell"]), Some((2, 3)));
+        assert_eq!(find(&["hints"]), Some((4, 3)));
+        assert_eq!(find(&["hints", "enabled"]), Some((7, 9)));
+    }
+
+    #[test]
+    fn find_key_in_sequence() {
+        let source = source("key_bindings:\n  - { key: A, action: Cpy }\n  -   mouse: Left\n");
+        let path = path(&["key_bindings"]);
+
+        assert_eq!(find_key(&source, &path, "key"), None);
+        assert_eq!(find_key(&source, &path, "mouse").map(|l| (l.line, l.column)), Some((3, 7)));
+        assert_eq!(find_value(&source, "Cpy").map(|l| (l.line, l.column)), Some((2, 23)));
+    }
+}
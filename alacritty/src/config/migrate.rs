@@ -0,0 +1,203 @@
+//! Migration of outdated configuration options.
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::mapping::Mapping;
+use serde_yaml::Value;
+
+use crate::cli::{MigrateOptions, Options};
+use crate::config::{self, Error, Result};
+
+/// Changes to the configuration file format.
+const MIGRATIONS: &[Migration] = &[
+    Migration::Rename("visual_bell", "bell"),
+    Migration::Rename("dynamic_title", "window.dynamic_title"),
+    Migration::Rename("dimensions", "window.dimensions"),
+    Migration::Rename("cursor_style", "cursor.style"),
+    Migration::Rename("unfocused_hollow_cursor", "cursor.unfocused_hollow"),
+    Migration::Rename("hide_cursor_when_typing", "mouse.hide_when_typing"),
+    Migration::Rename("persistent_logging", "debug.persistent_logging"),
+    Migration::Rename("render_timer", "debug.render_timer"),
+    Migration::Remove("tabspaces"),
+    Migration::Remove("auto_scroll"),
+    Migration::Remove("custom_cursor_colors"),
+    Migration::Remove("scrolling.faux_multiplier"),
+    Migration::Remove("mouse.faux_scrollback_lines"),
+];
+
+/// Single change to the configuration file format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Migration {
+    /// Option was moved to a new location.
+    Rename(&'static str, &'static str),
+
+    /// Option was removed without any replacement.
+    Remove(&'static str),
+}
+
+impl Display for Migration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Migration::Rename(old, new) => write!(f, "`{}` has been renamed to `{}`", old, new),
+            Migration::Remove(old) => write!(f, "`{}` has been removed", old),
+        }
+    }
+}
+
+/// Apply all migrations to a configuration file.
+///
+/// Returns all migrations which changed the configuration. When both the old and new location of
+/// a renamed option are present, the new location is preferred.
+pub fn migrate(config: &mut Value) -> Vec<Migration> {
+    let mut applied = Vec::new();
+
+    for migration in MIGRATIONS {
+        let (old, new) = match migration {
+            Migration::Rename(old, new) => (old, Some(new)),
+            Migration::Remove(old) => (old, None),
+        };
+
+        let value = match take(config, old) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        if let Some(new) = new {
+            insert(config, new, value);
+        }
+
+        applied.push(*migration);
+    }
+
+    applied
+}
+
+/// Migrate the configuration file in place.
+///
+/// Since YAML comments cannot be preserved, a backup of the original file is created.
+pub fn migrate_file(options: &Options, migrate_options: &MigrateOptions) -> Result<()> {
+    let path = options.config_path().or_else(config::installed_config).ok_or(Error::NotFound)?;
+
+    let contents = fs::read_to_string(&path)?;
+    let mut value: Value = serde_yaml::from_str(&contents)?;
+
+    let migrations = migrate(&mut value);
+    if migrations.is_empty() {
+        println!("No migrations necessary for {:?}", path);
+        return Ok(());
+    }
+
+    for migration in &migrations {
+        println!("{}", migration);
+    }
+
+    if migrate_options.dry_run {
+        return Ok(());
+    }
+
+    let backup_path = backup_path(&path);
+    fs::copy(&path, &backup_path)?;
+    fs::write(&path, serde_yaml::to_string(&value)?)?;
+
+    println!("Migrated {:?}, the original file was saved to {:?}", path, backup_path);
+
+    Ok(())
+}
+
+/// Path for the backup of a configuration file.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+/// Remove the value at a dot-separated path.
+fn take(value: &mut Value, path: &str) -> Option<Value> {
+    let (parent, key) = match path.rfind('.') {
+        Some(index) => (get_mut(value, &path[..index])?, &path[index + 1..]),
+        None => (value, path),
+    };
+
+    match parent {
+        Value::Mapping(mapping) => mapping.remove(&Value::String(key.into())),
+        _ => None,
+    }
+}
+
+/// Insert a value at a dot-separated path, without replacing existing values.
+fn insert(value: &mut Value, path: &str, new_value: Value) {
+    let (parent, key) = match path.rfind('.') {
+        Some(index) => (get_or_create(value, &path[..index]), &path[index + 1..]),
+        None => (Some(value), path),
+    };
+
+    if let Some(Value::Mapping(mapping)) = parent {
+        let key = Value::String(key.into());
+        if !mapping.contains_key(&key) {
+            mapping.insert(key, new_value);
+        }
+    }
+}
+
+/// Get the value at a dot-separated path.
+fn get_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Mapping(mapping) => mapping.get_mut(&Value::String(key.into())),
+        _ => None,
+    })
+}
+
+/// Get the value at a dot-separated path, creating all missing mappings.
+fn get_or_create<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Mapping(mapping) => {
+            let key = Value::String(key.into());
+            if !mapping.contains_key(&key) {
+                mapping.insert(key.clone(), Value::Mapping(Mapping::new()));
+            }
+            mapping.get_mut(&key)
+        },
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_rename() {
+        let mut config: Value = serde_yaml::from_str("dynamic_title: false").unwrap();
+
+        let applied = migrate(&mut config);
+
+        let expected: Value = serde_yaml::from_str("window:\n  dynamic_title: false").unwrap();
+        assert_eq!(config, expected);
+        assert_eq!(applied, vec![Migration::Rename("dynamic_title", "window.dynamic_title")]);
+    }
+
+    #[test]
+    fn migrate_prefers_new_location() {
+        let mut config: Value =
+            serde_yaml::from_str("dynamic_title: false\nwindow:\n  dynamic_title: true").unwrap();
+
+        migrate(&mut config);
+
+        let expected: Value = serde_yaml::from_str("window:\n  dynamic_title: true").unwrap();
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn migrate_remove_nested() {
+        let mut config: Value =
+            serde_yaml::from_str("scrolling:\n  history: 5\n  faux_multiplier: 3").unwrap();
+
+        let applied = migrate(&mut config);
+
+        let expected: Value = serde_yaml::from_str("scrolling:\n  history: 5").unwrap();
+        assert_eq!(config, expected);
+        assert_eq!(applied, vec![Migration::Remove("scrolling.faux_multiplier")]);
+    }
+}
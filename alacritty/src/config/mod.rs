@@ -2,19 +2,24 @@ use std::fmt::{self, Display, Formatter};
 use std::path::{Path, PathBuf};
 use std::{env, fs, io};
 
-use log::{error, info};
+use log::{error, info, warn};
 use serde::Deserialize;
 use serde_yaml::mapping::Mapping;
 use serde_yaml::Value;
 
 use alacritty_terminal::config::{Config as TermConfig, LOG_TARGET_CONFIG};
 
+pub mod accessibility;
 pub mod bell;
 pub mod color;
 pub mod debug;
 pub mod font;
+pub mod keyboard;
+pub mod location;
+pub mod migrate;
 pub mod monitor;
 pub mod serde_utils;
+pub mod template;
 pub mod ui_config;
 pub mod window;
 
@@ -22,7 +27,10 @@ mod bindings;
 mod mouse;
 
 use crate::cli::Options;
-pub use crate::config::bindings::{Action, Binding, BindingMode, Key, SearchAction, ViAction};
+pub use crate::config::bindings::{
+    Action, Binding, BindingMode, Key, SearchAction, SpawnDirectory, SpawnOptions, ViAction,
+};
+pub use crate::config::mouse::Presentation;
 #[cfg(test)]
 pub use crate::config::mouse::{ClickHandler, Mouse};
 use crate::config::ui_config::UiConfig;
@@ -120,6 +128,11 @@ pub fn load(options: &Options) -> Config {
 
     after_loading(&mut config, options);
 
+    // Print the effective configuration, even if the config file could not be loaded.
+    if options.print_config {
+        println!("{:#?}", config);
+    }
+
     config
 }
 
@@ -139,8 +152,12 @@ fn after_loading(config: &mut Config, options: &Options) {
     // Override config with CLI options.
     options.override_config(config);
 
-    // Create key bindings for regex hints.
+    // Create key bindings for function keys and regex hints.
+    config.ui_config.generate_function_key_bindings();
     config.ui_config.generate_hint_bindings();
+
+    // Apply the accessibility overrides to all color schemes.
+    config.ui_config.apply_accessibility_colors();
 }
 
 /// Load configuration file and log errors.
@@ -156,6 +173,16 @@ fn load_from(path: &Path, cli_config: Value) -> Result<Config> {
 
 /// Deserialize configuration file from path.
 fn read_config(path: &Path, cli_config: Value) -> Result<Config> {
+    let config = deserialize_config(path, cli_config);
+
+    // Stop tracking the file contents once deserialization is done.
+    location::clear();
+
+    config
+}
+
+/// Deserialize configuration file and its imports.
+fn deserialize_config(path: &Path, cli_config: Value) -> Result<Config> {
     let mut config_paths = Vec::new();
     let mut config_value = parse_config(&path, &mut config_paths, IMPORT_RECURSION_LIMIT)?;
 
@@ -184,8 +211,11 @@ fn parse_config(
         contents = contents.split_off(3);
     }
 
+    // Keep the file around for reporting the location of problems.
+    location::register(path, &contents);
+
     // Load configuration file as Value.
-    let config: Value = match serde_yaml::from_str(&contents) {
+    let mut config: Value = match serde_yaml::from_str(&contents) {
         Ok(config) => config,
         Err(error) => {
             // Prevent parsing error with an empty string and commented out file.
@@ -197,8 +227,24 @@ fn parse_config(
         },
     };
 
+    // Update outdated options.
+    for migration in migrate::migrate(&mut config) {
+        warn!(
+            target: LOG_TARGET_CONFIG,
+            "Config warning: {} in {:?}; use `alacritty migrate` to update the file",
+            migration,
+            path
+        );
+    }
+
     // Merge config with imports.
     let imports = load_imports(&config, config_paths, recursion_limit);
+    if let Value::Mapping(mapping) = &mut config {
+        mapping.remove(&Value::String("import".into()));
+
+        // Color schemes are only defined for referencing them with YAML anchors.
+        mapping.remove(&Value::String("schemes".into()));
+    }
     Ok(serde_utils::merge(imports, config))
 }
 
@@ -298,6 +344,8 @@ fn installed_config() -> Option<PathBuf> {
 mod tests {
     use super::*;
 
+    use std::process;
+
     static DEFAULT_ALACRITTY_CONFIG: &str =
         concat!(env!("CARGO_MANIFEST_DIR"), "/../alacritty.yml");
 
@@ -308,4 +356,22 @@ mod tests {
         config.ui_config.config_paths = Vec::new();
         assert_eq!(config, Config::default());
     }
+
+    #[test]
+    fn ignore_color_schemes() {
+        let file_name = format!("alacritty-test-schemes-{}.yml", process::id());
+        let config_path = env::temp_dir().join(file_name);
+        let contents = "schemes:\n  dark: &dark\n    primary:\n      background: '#101010'\n\
+                        colors: *dark\n";
+        fs::write(&config_path, contents).unwrap();
+
+        let mut config_paths = Vec::new();
+        let config = parse_config(&config_path, &mut config_paths, IMPORT_RECURSION_LIMIT);
+        fs::remove_file(&config_path).unwrap();
+        location::clear();
+
+        let config = config.unwrap();
+        assert_eq!(config.get("schemes"), None);
+        assert_eq!(config["colors"]["primary"]["background"], Value::from("#101010"));
+    }
 }
@@ -1,13 +1,29 @@
 use std::time::Duration;
 
 use alacritty_config_derive::ConfigDeserialize;
+use alacritty_terminal::term::color::Rgb;
 
 #[derive(ConfigDeserialize, Default, Clone, Debug, PartialEq, Eq)]
 pub struct Mouse {
+    /// Click settings
+    ///
+    /// The `double_click` and `triple_click` settings control the time in milliseconds alacritty
+    /// should wait for accepting multiple clicks as one double or triple click.
     pub double_click: ClickHandler,
     pub triple_click: ClickHandler,
+
+    /// Distance in pixels the mouse has to move while a button is held, before the selection
+    /// starts following it.
+    pub drag_threshold: u16,
+
+    /// If this is `true`, the cursor is temporarily hidden when typing.
     pub hide_when_typing: bool,
-    #[config(deprecated = "use `hints` section instead")]
+
+    /// Presentation mode
+    ///
+    /// Highlighting of the mouse cursor, making it easy to follow in screencasts.
+    pub presentation: Presentation,
+    #[config(deprecated = "use `mouse.mods` of the URL hint in the `hints` section instead")]
     pub url: Option<serde_yaml::Value>,
 }
 
@@ -22,6 +38,35 @@ impl Default for ClickHandler {
     }
 }
 
+/// Highlighting of the mouse cursor for screencasts.
+#[derive(ConfigDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Presentation {
+    /// Draw a ring around the mouse cursor and flash clicked cells.
+    pub enabled: bool,
+
+    /// Color of the ring and the flash.
+    pub color: Rgb,
+
+    /// Duration of the flash in milliseconds.
+    flash_duration: u16,
+}
+
+impl Default for Presentation {
+    fn default() -> Self {
+        Self {
+            enabled: Default::default(),
+            color: Rgb { r: 0xff, g: 0xcc, b: 0x00 },
+            flash_duration: 300,
+        }
+    }
+}
+
+impl Presentation {
+    pub fn flash_duration(&self) -> Duration {
+        Duration::from_millis(self.flash_duration as u64)
+    }
+}
+
 impl ClickHandler {
     pub fn threshold(&self) -> Duration {
         Duration::from_millis(self.threshold as u64)
@@ -0,0 +1,117 @@
+//! Generation of the default configuration file.
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use alacritty_config::{ConfigTemplate, Template};
+
+use crate::cli::{CreateConfigOptions, Options};
+use crate::config::Config;
+
+/// First line of the configuration file.
+const HEADER: &str = "Configuration for Alacritty, the GPU enhanced terminal emulator.";
+
+/// Documentation of the `import` option.
+const IMPORT_DOCS: &[&str] = &[
+    "Import additional configuration files",
+    "",
+    "Imports are loaded in order, skipping all missing files, with the importing file being",
+    "loaded last. If a field is already present in a previous import, it will be replaced.",
+    "",
+    "All imports must either be absolute paths starting with `/`, or paths relative to the",
+    "user's home directory starting with `~/`.",
+];
+
+/// Write the default configuration file.
+///
+/// The file is written to `--config-file` if present, otherwise the default location is used.
+/// Existing files are only replaced with `--force`.
+pub fn create_config(
+    options: &Options,
+    create_options: &CreateConfigOptions,
+) -> io::Result<PathBuf> {
+    let path = match options.config_path() {
+        Some(path) => path,
+        None => default_path()?,
+    };
+
+    if path.exists() && !create_options.force {
+        let message = format!("{:?} already exists, use --force to replace it", path);
+        return Err(io::Error::new(ErrorKind::AlreadyExists, message));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, template(create_options.with_comments).as_bytes())?;
+
+    Ok(path)
+}
+
+/// Default configuration file content.
+///
+/// All options are commented out, so the defaults can still be changed by future versions.
+pub fn template(with_comments: bool) -> String {
+    let mut template = Template::new(with_comments);
+
+    template.comment(&[HEADER]);
+
+    // Imports are resolved before the configuration is deserialized.
+    template.option("import", IMPORT_DOCS, &Vec::<PathBuf>::new());
+    Config::default().write_fields(&mut template);
+
+    template.into_text()
+}
+
+/// Default location of the configuration file.
+#[cfg(not(windows))]
+fn default_path() -> io::Result<PathBuf> {
+    xdg::BaseDirectories::with_prefix("alacritty")
+        .map_err(|err| io::Error::new(ErrorKind::NotFound, err))?
+        .place_config_file("alacritty.yml")
+}
+
+#[cfg(windows)]
+fn default_path() -> io::Result<PathBuf> {
+    dirs::config_dir()
+        .map(|path| path.join("alacritty\\alacritty.yml"))
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "unable to find config directory"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_yaml::Value;
+
+    #[test]
+    fn template_documents_options() {
+        let template = template(true);
+
+        assert!(template.starts_with("# Configuration for Alacritty"));
+        assert!(template.contains("\n# Import additional configuration files\n"));
+        assert!(template.contains("\n  # Window title\n  #title: \"Alacritty\"\n"));
+    }
+
+    #[test]
+    fn template_without_comments() {
+        let template = template(false);
+
+        assert!(template.starts_with("#import: []\n"));
+        assert!(template.contains("\n  #dimensions:\n    #columns: 0\n    #lines: 0\n"));
+        assert!(!template.contains("# Window title"));
+    }
+
+    #[test]
+    fn uncommented_template_is_default_config() {
+        // Remove the `#` in front of every option.
+        let options: String =
+            template(false).lines().map(|line| line.replacen('#', "", 1) + "\n").collect();
+
+        let value: Value = serde_yaml::from_str(&options).unwrap();
+        let config: Config = serde_yaml::from_value(value).unwrap();
+
+        assert_eq!(config, Config::default());
+    }
+}
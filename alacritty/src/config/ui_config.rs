@@ -1,17 +1,21 @@
 use std::cell::RefCell;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 
-use glutin::event::{ModifiersState, VirtualKeyCode};
+use crossfont::Size as FontSize;
 use log::error;
 use serde::de::Error as SerdeError;
 use serde::{self, Deserialize, Deserializer};
 use unicode_width::UnicodeWidthChar;
 
+use alacritty_config::{ConfigTemplate, Template};
 use alacritty_config_derive::ConfigDeserialize;
 use alacritty_terminal::config::{Percentage, Program, LOG_TARGET_CONFIG};
 use alacritty_terminal::term::search::RegexSearch;
+use alacritty_terminal::term::Padding;
 
+use crate::config::accessibility::Accessibility;
 use crate::config::bell::BellConfig;
 use crate::config::bindings::{
     self, Action, Binding, Key, KeyBinding, ModeWrapper, ModsWrapper, MouseBinding,
@@ -19,8 +23,12 @@ use crate::config::bindings::{
 use crate::config::color::Colors;
 use crate::config::debug::Debug;
 use crate::config::font::Font;
+use crate::config::keyboard::Keyboard;
 use crate::config::mouse::Mouse;
-use crate::config::window::WindowConfig;
+use crate::config::window::{MonitorProfile, WindowConfig};
+
+/// Minimum time between blinks of text in milliseconds.
+const MIN_TEXT_BLINK_INTERVAL: u16 = 10;
 
 /// Regex used for the default URL hint.
 #[rustfmt::skip]
@@ -29,46 +37,415 @@ const URL_REGEX: &str = "(ipfs:|ipns:|magnet:|mailto:|gemini:|gopher:|https:|htt
 
 #[derive(ConfigDeserialize, Debug, PartialEq)]
 pub struct UiConfig {
-    /// Font configuration.
+    /// Font configuration
     pub font: Font,
 
-    /// Window configuration.
+    /// Window
     pub window: WindowConfig,
 
+    /// Monitor profiles
+    ///
+    /// Each profile overrides the font size and padding while the window is on the monitor with
+    /// the matching name. Monitor names are the ones reported by the windowing system, like
+    /// `DP-1` or `HDMI-A-1` on X11.
+    ///
+    /// Example:
+    ///   `- { name: DP-1, font_size: 14.0, padding: { x: 4, y: 4 } }`
+    pub monitors: Vec<MonitorProfile>,
+
+    /// Mouse
     pub mouse: Mouse,
 
-    /// Debug options.
+    /// Keyboard
+    pub keyboard: Keyboard,
+
+    /// Debugging
     pub debug: Debug,
 
-    /// Send escape sequences using the alt key.
+    /// Send ESC (\x1b) before characters when alt is pressed.
     pub alt_send_esc: bool,
 
-    /// Live config reload.
+    /// Live config reload (changes require restart)
     pub live_config_reload: bool,
 
-    /// Bell configuration.
+    /// Bell
+    ///
+    /// The bell is rung every time the BEL control character is received. Bells received within
+    /// 100ms of the previous one are ignored, so rapid sequences of BEL characters only ring the
+    /// bell once.
     pub bell: BellConfig,
 
-    /// RGB values for colors.
+    /// Colors (Tomorrow Night)
     pub colors: Colors,
 
-    /// Should draw bold text with brighter colors instead of bold font.
+    /// Alternate colors
+    ///
+    /// Second color scheme, which is swapped with `colors` by the `ToggleTheme` action. It accepts
+    /// all fields of the `colors` section.
+    pub alternate_colors: Option<Colors>,
+
+    /// Accessibility
+    ///
+    /// These options are applied on top of the configured colors and cursor settings, including
+    /// the `alternate_colors`.
+    pub accessibility: Accessibility,
+
+    /// If `true`, bold text is drawn using the bright color variants.
     pub draw_bold_text_with_bright_colors: bool,
 
     /// Path where config was loaded from.
     #[config(skip)]
     pub config_paths: Vec<PathBuf>,
 
-    /// Regex hints for interacting with terminal content.
+    /// Regex hints
+    ///
+    /// Terminal hints can be used to find text in the visible part of the terminal and pipe it to
+    /// other applications.
+    ///
+    /// Visible URLs can always be opened using the `OpenURLHints` action, which does not depend
+    /// on any of the hints configured here.
     pub hints: Hints,
 
-    /// Keybindings.
+    /// Buffer search
+    ///
+    /// Searches wrap around at the top and bottom of the scrollback buffer, which is indicated in
+    /// the search bar. The search bar also shows the position of the focused match among all
+    /// matches in the buffer.
+    pub search: Search,
+
+    /// Pasting with the `PasteSlowly` action
+    ///
+    /// Some receivers like serial consoles or bootloaders drop bytes when too much text is pasted
+    /// at once. The `PasteSlowly` action writes the clipboard to the terminal in chunks, waiting
+    /// between each of them.
+    pub slow_paste: SlowPaste,
+
+    /// Text blinking
+    ///
+    /// Text using the blink attribute (`CSI 5 m` or `CSI 6 m`) is alternately shown and hidden.
+    pub text_blink: TextBlink,
+
+    /// Key bindings
+    ///
+    /// Key bindings are specified as a list of objects. For example, this is the
+    /// default paste binding:
+    ///
+    /// `- { key: V, mods: Control|Shift, action: Paste }`
+    ///
+    /// Each key binding will specify a:
+    ///
+    /// - `key`: Identifier of the key pressed
+    ///
+    ///    - A-Z
+    ///    - F1-F24
+    ///    - Key0-Key9
+    ///    - Numpad0-Numpad9
+    ///
+    ///    Keys on the numeric keypad are separate from their main keyboard
+    ///    counterparts, so `Numpad1` and `Key1` can be bound independently.
+    ///
+    ///    A full list with available key codes can be found here:
+    ///    https://docs.rs/glutin/*/glutin/event/enum.VirtualKeyCode.html#variants
+    ///
+    ///    Instead of using the name of the keys, the `key` field also supports using
+    ///    the scancode of the desired key. Scancodes have to be specified as a
+    ///    decimal number. This command will allow you to display the hex scancodes
+    ///    for certain keys:
+    ///
+    ///       `showkey --scancodes`.
+    ///
+    /// Then exactly one of:
+    ///
+    /// - `chars`: Send a byte sequence to the running application
+    ///
+    ///    The `chars` field writes the specified string to the terminal. This makes
+    ///    it possible to pass escape sequences. To find escape codes for bindings
+    ///    like `PageUp` (`"\x1b[5~"`), you can run the command `showkey -a` outside
+    ///    of tmux. Note that applications use terminfo to map escape sequences back
+    ///    to keys. It is therefore required to update the terminfo when changing an
+    ///    escape sequence.
+    ///
+    /// - `action`: Execute a predefined action
+    ///
+    ///   - { text: "..." }
+    ///       Send text to the running application like `chars`, after expanding
+    ///       the escapes `\\`, `\e`, `\n`, `\r`, `\t`, `\xHH` and `\u{HHHHHH}`.
+    ///       Since escapes are expanded by Alacritty, the text is best written in
+    ///       single quotes, like `action: { text: '\e[1;5C' }`. Invalid escapes
+    ///       are reported when the configuration is loaded.
+    ///   - ToggleViMode
+    ///   - SearchForward
+    ///       Start searching toward the right of the search origin.
+    ///   - SearchBackward
+    ///       Start searching toward the left of the search origin.
+    ///   - OpenURLHints
+    ///       Show hint labels for all visible URLs, opening the selected one with
+    ///       the system's default handler.
+    ///   - Copy
+    ///   - AppendSelectionToClipboard
+    ///       Add the selection to the end of the clipboard, on a new line.
+    ///   - { CopyToRegister: a }
+    ///       Copy the selection into a register, like Vim's `"ay`. Registers are
+    ///       named `+` for the clipboard, `*` for the selection buffer and `a` to
+    ///       `z` for registers which are kept until Alacritty exits. Since `*` is
+    ///       special in YAML, it has to be quoted like `{ CopyToRegister: "*" }`.
+    ///   - { PasteRegister: a }
+    ///       Paste the contents of a register, like Vim's `"ap`.
+    ///   - Paste
+    ///   - PasteSlowly
+    ///       Paste the clipboard in small chunks, according to `slow_paste`.
+    ///   - IncreaseFontSize
+    ///   - DecreaseFontSize
+    ///   - ResetFontSize
+    ///   - ScrollPageUp
+    ///   - ScrollPageDown
+    ///   - ScrollHalfPageUp
+    ///   - ScrollHalfPageDown
+    ///   - ScrollLineUp
+    ///   - ScrollLineDown
+    ///   - ScrollToTop
+    ///   - ScrollToBottom
+    ///   - ClearHistory
+    ///       Remove the terminal's scrollback history.
+    ///   - ScrollToPreviousPrompt
+    ///   - ScrollToNextPrompt
+    ///       Scroll the closest prompt marked by the shell through `OSC 133` to the
+    ///       top of the screen.
+    ///   - SelectLastCommandOutput
+    ///       Select the output of the last command marked by the shell through
+    ///       `OSC 133`.
+    ///   - Hide
+    ///       Hide the Alacritty window.
+    ///   - Minimize
+    ///       Minimize the Alacritty window.
+    ///   - Quit
+    ///       Quit Alacritty.
+    ///   - ToggleFullscreen
+    ///   - ToggleOpacity
+    ///       Toggle between `background_opacity` and an opaque background.
+    ///   - { SetOpacity: 0.8 }
+    ///       Change the background opacity to a value from `0.0` to `1.0`.
+    ///   - ToggleTheme
+    ///       Swap the color scheme with `alternate_colors`.
+    ///   - ToggleZoom
+    ///       Render the terminal at twice its size around the cursor, without
+    ///       changing the number of lines and columns. Mouse input is not adjusted
+    ///       while zoomed.
+    ///   - SpawnNewInstance
+    ///       Spawn a new instance of Alacritty, in the working directory of the
+    ///       foreground process and with the arguments of this instance.
+    ///   - { SpawnNewInstance: { working_directory: Osc7, args: ["-e", "htop"] } }
+    ///       Spawn a new instance of Alacritty with custom options:
+    ///         - `working_directory`: Source of the working directory, which is
+    ///           passed as `--working-directory` (default: ForegroundProcess)
+    ///             - Osc7: Directory reported by the shell with `OSC 7`, falling
+    ///               back to the foreground process
+    ///             - ForegroundProcess: Working directory of the foreground process
+    ///             - Home: Home directory of the user
+    ///         - `profile`: Configuration file of the new instance, which is passed
+    ///           as `--config-file`
+    ///         - `args`: Arguments replacing the ones of this instance
+    ///   - ClearLogNotice
+    ///       Clear Alacritty's UI warning and error notice.
+    ///   - ClearSelection
+    ///       Remove the active selection.
+    ///   - ExportSvg
+    ///       Save the visible screen as SVG image in the pictures directory.
+    ///   - Screenshot
+    ///       Save the window as PNG image in the pictures directory.
+    ///   - ScreenshotSelection
+    ///       Save the visible part of the selection as PNG image in the pictures
+    ///       directory.
+    ///   - ReceiveChar
+    ///   - None
+    ///
+    /// - Vi mode exclusive actions:
+    ///
+    ///   - Open
+    ///       Perform the action of the first matching hint under the vi mode cursor
+    ///       with `mouse.enabled` set to `true`.
+    ///   - ToggleNormalSelection
+    ///   - ToggleLineSelection
+    ///   - ToggleBlockSelection
+    ///   - ToggleSemanticSelection
+    ///       Toggle semantic selection based on `selection.semantic_escape_chars`.
+    ///   - SetMark
+    ///       Set the mark named by the next character, from `a` to `z`.
+    ///   - JumpToMark
+    ///       Jump to the mark named by the next character. The marks ` and ' refer
+    ///       to the position before the latest jump.
+    ///   - JumpToMarkLine
+    ///       Jump to the first non-blank cell in the line of the mark named by the
+    ///       next character.
+    ///   - JumpBack
+    ///       Go back to the previous position in the jump list.
+    ///   - JumpForward
+    ///       Go forward to the next position in the jump list.
+    ///   - InnerObject
+    ///       Select the inner text object named by the next character.
+    ///   - AroundObject
+    ///       Select the text object named by the next character, including
+    ///       surrounding whitespace, quotes or brackets.
+    ///
+    /// - Vi mode exclusive cursor motion actions:
+    ///
+    ///   - Up
+    ///       One line up.
+    ///   - Down
+    ///       One line down.
+    ///   - Left
+    ///       One character left.
+    ///   - Right
+    ///       One character right.
+    ///   - First
+    ///       First column, or beginning of the line when already at the first column.
+    ///   - Last
+    ///       Last column, or beginning of the line when already at the last column.
+    ///   - FirstOccupied
+    ///       First non-empty cell in this terminal row, or first non-empty cell of
+    ///       the line when already at the first cell of the row.
+    ///   - High
+    ///       Top of the screen.
+    ///   - Middle
+    ///       Center of the screen.
+    ///   - Low
+    ///       Bottom of the screen.
+    ///   - SemanticLeft
+    ///       Start of the previous semantically separated word.
+    ///   - SemanticRight
+    ///       Start of the next semantically separated word.
+    ///   - SemanticLeftEnd
+    ///       End of the previous semantically separated word.
+    ///   - SemanticRightEnd
+    ///       End of the next semantically separated word.
+    ///   - WordLeft
+    ///       Start of the previous whitespace separated word.
+    ///   - WordRight
+    ///       Start of the next whitespace separated word.
+    ///   - WordLeftEnd
+    ///       End of the previous whitespace separated word.
+    ///   - WordRightEnd
+    ///       End of the next whitespace separated word.
+    ///   - Bracket
+    ///       Character matching the bracket at the cursor's location.
+    ///   - SearchNext
+    ///       Beginning of the next match.
+    ///   - SearchPrevious
+    ///       Beginning of the previous match.
+    ///   - SearchStart
+    ///       Start of the match to the left of the vi mode cursor.
+    ///   - SearchEnd
+    ///       End of the match to the right of the vi mode cursor.
+    ///
+    /// - Search mode exclusive actions:
+    ///   - SearchFocusNext
+    ///       Move the focus to the next search match.
+    ///   - SearchFocusPrevious
+    ///       Move the focus to the previous search match.
+    ///   - SearchConfirm
+    ///   - SearchCancel
+    ///   - SearchClear
+    ///       Reset the search regex.
+    ///   - SearchDeleteWord
+    ///       Delete the last word in the search regex.
+    ///   - SearchHistoryPrevious
+    ///       Go to the previous regex in the search history.
+    ///   - SearchHistoryNext
+    ///       Go to the next regex in the search history.
+    ///
+    /// - macOS exclusive actions:
+    ///   - ToggleSimpleFullscreen
+    ///       Enter fullscreen without occupying another space.
+    ///
+    /// - Linux/BSD exclusive actions:
+    ///
+    ///   - CopySelection
+    ///       Copy from the selection buffer.
+    ///   - PasteSelection
+    ///       Paste from the selection buffer.
+    ///
+    /// - `command`: Fork and execute a specified command plus arguments
+    ///
+    ///    The `command` field must be a map containing a `program` string and an
+    ///    `args` array of command line parameter strings. For example:
+    ///       `{ program: "alacritty", args: ["-e", "vttest"] }`
+    ///
+    /// And optionally:
+    ///
+    /// - `mods`: Key modifiers to filter binding actions
+    ///
+    ///    - Command
+    ///    - Control
+    ///    - Option
+    ///    - Super
+    ///    - Shift
+    ///    - Alt
+    ///
+    ///    Multiple `mods` can be combined using `|` like this:
+    ///       `mods: Control|Shift`.
+    ///    Whitespace and capitalization are relevant and must match the example.
+    ///
+    /// - `mode`: Indicate a binding for only specific terminal reported modes
+    ///
+    ///    This is mainly used to send applications the correct escape sequences
+    ///    when in different modes.
+    ///
+    ///    - AppCursor
+    ///    - AppKeypad
+    ///        Set by applications using `DECKPAM` or `DECNKM`. By default, the
+    ///        keypad operators and `NumpadEnter` send their application sequences
+    ///        in this mode, while digits are sent unchanged since they are only
+    ///        reported with Num Lock enabled.
+    ///    - Search
+    ///    - Alt
+    ///    - Vi
+    ///    - Selection
+    ///        Active while text is selected.
+    ///    - Count
+    ///        Active while a count for the next vi mode action is being typed.
+    ///
+    ///    A `~` operator can be used before a mode to apply the binding whenever
+    ///    the mode is *not* active, e.g. `~Alt`.
+    ///
+    /// Bindings are always filled by default, but will be replaced when a new
+    /// binding with the same triggers is defined. To unset a default binding, it can
+    /// be mapped to the `ReceiveChar` action. Alternatively, you can use `None` for
+    /// a no-op if you do not wish to receive input characters for that binding.
+    ///
+    /// If the same trigger is assigned to multiple actions, all of them are executed
+    /// in the order they were defined in.
     key_bindings: KeyBindings,
 
-    /// Bindings for the mouse.
+    /// Mouse bindings
+    ///
+    /// Mouse bindings are specified as a list of objects, much like the key bindings above.
+    ///
+    /// To trigger mouse bindings when an application running within Alacritty captures the mouse,
+    /// the `Shift` modifier is automatically added as a requirement.
+    ///
+    /// Each mouse binding will specify a:
+    ///
+    /// - `mouse`:
+    ///
+    ///   - Middle
+    ///   - Left
+    ///   - Right
+    ///   - Numeric identifier such as `5`
+    ///
+    /// - `action` (see key bindings)
+    ///
+    /// And optionally:
+    ///
+    /// - `mods` (see key bindings)
+    ///
+    /// Example:
+    ///   `- { mouse: Middle, action: PasteSelection }`
     mouse_bindings: MouseBindings,
 
-    /// Background opacity from 0.0 to 1.0.
+    /// Background opacity
+    ///
+    /// Window opacity as a floating point number from `0.0` to `1.0`. The value `0.0` is
+    /// completely transparent and `1.0` is opaque.
     background_opacity: Percentage,
 }
 
@@ -79,7 +456,9 @@ impl Default for UiConfig {
             live_config_reload: true,
             font: Default::default(),
             window: Default::default(),
+            monitors: Default::default(),
             mouse: Default::default(),
+            keyboard: Default::default(),
             debug: Default::default(),
             config_paths: Default::default(),
             key_bindings: Default::default(),
@@ -87,8 +466,13 @@ impl Default for UiConfig {
             background_opacity: Default::default(),
             bell: Default::default(),
             colors: Default::default(),
+            alternate_colors: Default::default(),
+            accessibility: Default::default(),
             draw_bold_text_with_bright_colors: Default::default(),
             hints: Default::default(),
+            search: Default::default(),
+            slow_paste: Default::default(),
+            text_blink: Default::default(),
         }
     }
 }
@@ -110,7 +494,54 @@ impl UiConfig {
                 action: Action::Hint(hint.clone()),
             };
 
-            self.key_bindings.0.push(binding);
+            self.key_bindings.bindings.push(binding);
+        }
+    }
+
+    /// Apply the high-contrast accessibility palette to the active and alternate colors.
+    pub fn apply_accessibility_colors(&mut self) {
+        self.accessibility.override_colors(&mut self.colors);
+        if let Some(alternate_colors) = &mut self.alternate_colors {
+            self.accessibility.override_colors(alternate_colors);
+        }
+    }
+
+    /// Generate key bindings for all function keys.
+    ///
+    /// Function keys which have been bound explicitly are skipped.
+    pub fn generate_function_key_bindings(&mut self) {
+        for binding in bindings::function_key_bindings(&self.keyboard) {
+            if !self.key_bindings.bindings.iter().any(|b| b.triggers_match(&binding)) {
+                self.key_bindings.bindings.push(binding);
+            }
+        }
+    }
+
+    /// Overrides for the monitor with the given name.
+    pub fn monitor_profile(&self, monitor: Option<&str>) -> Option<&MonitorProfile> {
+        let monitor = monitor?;
+        self.monitors.iter().find(|profile| profile.name == monitor)
+    }
+
+    /// Default font size on a monitor.
+    pub fn font_size(&self, monitor: Option<&str>) -> FontSize {
+        match self.monitor_profile(monitor).and_then(|profile| profile.font_size) {
+            Some(size) => FontSize::new(size),
+            None => self.font.size(),
+        }
+    }
+
+    /// Padding on a monitor in physical pixels.
+    pub fn padding(
+        &self,
+        monitor: Option<&str>,
+        dpr: f64,
+        cell_width: f32,
+        cell_height: f32,
+    ) -> Padding {
+        match self.monitor_profile(monitor).and_then(|profile| profile.padding) {
+            Some(padding) => padding.to_pixels(dpr, cell_width, cell_height),
+            None => self.window.padding(dpr, cell_width, cell_height),
         }
     }
 
@@ -121,7 +552,13 @@ impl UiConfig {
 
     #[inline]
     pub fn key_bindings(&self) -> &[KeyBinding] {
-        &self.key_bindings.0.as_slice()
+        &self.key_bindings.bindings.as_slice()
+    }
+
+    /// Number of bindings at the start of `key_bindings` which are configured by the user.
+    #[inline]
+    pub fn configured_key_bindings(&self) -> usize {
+        self.key_bindings.configured
     }
 
     #[inline]
@@ -131,11 +568,23 @@ impl UiConfig {
 }
 
 #[derive(Debug, PartialEq)]
-struct KeyBindings(Vec<KeyBinding>);
+struct KeyBindings {
+    bindings: Vec<KeyBinding>,
+
+    /// Number of bindings at the start of `bindings` which are configured by the user.
+    configured: usize,
+}
 
 impl Default for KeyBindings {
     fn default() -> Self {
-        Self(bindings::default_key_bindings())
+        Self { bindings: bindings::default_key_bindings(), configured: 0 }
+    }
+}
+
+impl ConfigTemplate for KeyBindings {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        // Bindings are added to the defaults, so none are configured by default.
+        template.value(key, "[]");
     }
 }
 
@@ -144,7 +593,12 @@ impl<'de> Deserialize<'de> for KeyBindings {
     where
         D: Deserializer<'de>,
     {
-        Ok(Self(deserialize_bindings(deserializer, Self::default().0)?))
+        let mut default = Self::default().bindings;
+        let mut bindings = deserialize_bindings(deserializer, &mut default)?;
+        let configured = bindings.len();
+        bindings.extend(default);
+
+        Ok(Self { bindings, configured })
     }
 }
 
@@ -157,18 +611,29 @@ impl Default for MouseBindings {
     }
 }
 
+impl ConfigTemplate for MouseBindings {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.value(key, "[]");
+    }
+}
+
 impl<'de> Deserialize<'de> for MouseBindings {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        Ok(Self(deserialize_bindings(deserializer, Self::default().0)?))
+        let mut default = Self::default().0;
+        let mut bindings = deserialize_bindings(deserializer, &mut default)?;
+        bindings.extend(default);
+
+        Ok(Self(bindings))
     }
 }
 
+/// Deserialize the configured bindings, removing the default bindings they replace.
 fn deserialize_bindings<'a, D, T>(
     deserializer: D,
-    mut default: Vec<Binding<T>>,
+    default: &mut Vec<Binding<T>>,
 ) -> Result<Vec<Binding<T>>, D::Error>
 where
     D: Deserializer<'a>,
@@ -193,8 +658,6 @@ where
         default.retain(|b| !b.triggers_match(binding));
     }
 
-    bindings.extend(default);
-
     Ok(bindings)
 }
 
@@ -207,45 +670,141 @@ pub struct Delta<T: Default> {
     pub y: T,
 }
 
+/// Buffer search configuration.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Search {
+    /// Maximum number of matches counted for the match indicator. Matches are counted in the
+    /// background and counting stops at this limit.
+    max_match_count: usize,
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self { max_match_count: 1000 }
+    }
+}
+
+impl Search {
+    pub fn max_match_count(&self) -> usize {
+        self.max_match_count
+    }
+}
+
+/// Chunking of text pasted by the `PasteSlowly` action.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SlowPaste {
+    /// Number of bytes written at once.
+    chunk_size: usize,
+
+    /// Delay between chunks in milliseconds.
+    delay: u16,
+}
+
+impl Default for SlowPaste {
+    fn default() -> Self {
+        Self { chunk_size: 16, delay: 10 }
+    }
+}
+
+impl SlowPaste {
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size.max(1)
+    }
+
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay as u64)
+    }
+}
+
+/// Blinking of text with the blink attribute.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextBlink {
+    /// Time between showing and hiding the text in milliseconds. Rapidly blinking text blinks
+    /// three times as often.
+    interval: u16,
+
+    /// Time after which the text stops blinking and stays visible in seconds. The value `0` never
+    /// stops blinking.
+    timeout: u16,
+}
+
+impl Default for TextBlink {
+    fn default() -> Self {
+        Self { interval: 500, timeout: 30 }
+    }
+}
+
+impl TextBlink {
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval.max(MIN_TEXT_BLINK_INTERVAL) as u64)
+    }
+
+    /// Time after which blinking stops, `None` if it never stops.
+    pub fn timeout(&self) -> Option<Duration> {
+        if self.timeout == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(self.timeout as u64))
+        }
+    }
+}
+
 /// Regex terminal hints.
 #[derive(ConfigDeserialize, Debug, PartialEq, Eq)]
 pub struct Hints {
-    /// Characters for the hint labels.
+    /// Keys used for the hint labels.
     alphabet: HintsAlphabet,
 
-    /// All configured terminal hints.
+    /// Position of the hint labels
+    ///
+    /// Labels are drawn above the text of their match, covering either its first (`Start`) or its
+    /// last (`End`) characters. While hints are active, all text which is not part of a
+    /// selectable hint is dimmed.
+    pub label_position: HintLabelPosition,
+
+    /// List with all available hints
+    ///
+    /// Each hint must have a `regex` and either an `action` or a `command` field. The fields
+    /// `mouse`, `binding` and `post_processing` are optional.
+    ///
+    /// The fields `command`, `binding.key`, `binding.mods`, `binding.mode` and `mouse.mods`
+    /// accept the same values as they do in the `key_bindings` section.
+    ///
+    /// The `mouse.enabled` field controls if the hint should be underlined while the mouse with
+    /// all `mouse.mods` keys held or the vi mode cursor is above it. Setting `mouse.mods` to
+    /// `Control` for the URL hint for example, prevents clicks from opening URLs unless Control
+    /// is held.
+    ///
+    /// If the `post_processing` field is set to `true`, heuristics will be used to shorten the
+    /// match if there are characters likely not to be part of the hint (e.g. a trailing `.`).
+    /// This is most useful for URIs.
+    ///
+    /// Values for `action`:
+    ///   - Copy
+    ///       Copy the hint's text to the clipboard.
+    ///   - Paste
+    ///       Paste the hint's text to the terminal or search.
+    ///   - Select
+    ///       Select the hint's text.
+    ///   - MoveViModeCursor
+    ///       Move the vi mode cursor to the beginning of the hint.
     pub enabled: Vec<Hint>,
+
+    /// Built-in URL hint used by the `OpenURLHints` action.
+    #[config(skip)]
+    url: Hint,
 }
 
 impl Default for Hints {
     fn default() -> Self {
-        // Add URL hint by default when no other hint is present.
-        let pattern = LazyRegexVariant::Pattern(String::from(URL_REGEX));
-        let regex = LazyRegex(Rc::new(RefCell::new(pattern)));
-
-        #[cfg(not(any(target_os = "macos", windows)))]
-        let action = HintAction::Command(Program::Just(String::from("xdg-open")));
-        #[cfg(target_os = "macos")]
-        let action = HintAction::Command(Program::Just(String::from("open")));
-        #[cfg(windows)]
-        let action = HintAction::Command(Program::WithArgs {
-            program: String::from("cmd"),
-            args: vec!["/c".to_string(), "start".to_string(), "".to_string()],
-        });
+        let url = Hint::url();
 
         Self {
-            enabled: vec![Hint {
-                regex,
-                action,
-                post_processing: true,
-                mouse: Some(HintMouse { enabled: true, mods: Default::default() }),
-                binding: Some(HintBinding {
-                    key: Key::Keycode(VirtualKeyCode::U),
-                    mods: ModsWrapper(ModifiersState::SHIFT | ModifiersState::CTRL),
-                    mode: Default::default(),
-                }),
-            }],
+            // Add URL hint by default when no other hint is present.
+            enabled: vec![url.clone()],
             alphabet: Default::default(),
+            label_position: Default::default(),
+            url,
         }
     }
 }
@@ -255,6 +814,27 @@ impl Hints {
     pub fn alphabet(&self) -> &str {
         &self.alphabet.0
     }
+
+    /// Built-in hint for opening URLs.
+    pub fn url(&self) -> &Hint {
+        &self.url
+    }
+}
+
+/// Placement of the keyboard hint labels.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HintLabelPosition {
+    /// Cover the first characters of the match.
+    Start,
+
+    /// Cover the last characters of the match.
+    End,
+}
+
+impl Default for HintLabelPosition {
+    fn default() -> Self {
+        HintLabelPosition::Start
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -266,6 +846,12 @@ impl Default for HintsAlphabet {
     }
 }
 
+impl ConfigTemplate for HintsAlphabet {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        self.0.write_template(key, template);
+    }
+}
+
 impl<'de> Deserialize<'de> for HintsAlphabet {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -335,6 +921,66 @@ pub struct Hint {
     binding: Option<HintBinding>,
 }
 
+impl Hint {
+    /// Hint opening URLs with the system's default handler.
+    ///
+    /// This is only highlighted by the mouse, keyboard hints for URLs are bound to the
+    /// `OpenURLHints` action instead.
+    fn url() -> Self {
+        let pattern = LazyRegexVariant::Pattern(String::from(URL_REGEX));
+        let regex = LazyRegex(Rc::new(RefCell::new(pattern)));
+
+        #[cfg(not(any(target_os = "macos", windows)))]
+        let action = HintAction::Command(Program::Just(String::from("xdg-open")));
+        #[cfg(target_os = "macos")]
+        let action = HintAction::Command(Program::Just(String::from("open")));
+        #[cfg(windows)]
+        let action = HintAction::Command(Program::WithArgs {
+            program: String::from("cmd"),
+            args: vec!["/c".to_string(), "start".to_string(), "".to_string()],
+        });
+
+        Self {
+            regex,
+            action,
+            post_processing: true,
+            mouse: Some(HintMouse { enabled: true, mods: Default::default() }),
+            binding: None,
+        }
+    }
+}
+
+impl ConfigTemplate for Hint {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.section(key, |template| {
+            if let LazyRegexVariant::Pattern(regex) = &*self.regex.0.borrow() {
+                regex.write_template("regex", template);
+            }
+
+            match &self.action {
+                HintAction::Action(action) => action.write_template("action", template),
+                HintAction::Command(program) => program.write_template("command", template),
+            }
+
+            self.post_processing.write_template("post_processing", template);
+
+            if let Some(mouse) = &self.mouse {
+                mouse.write_template("mouse", template);
+            }
+
+            if let Some(binding) = &self.binding {
+                template.section("binding", |template| {
+                    binding.key.write_template("key", template);
+                    binding.mods.write_template("mods", template);
+                    if binding.mode != ModeWrapper::default() {
+                        binding.mode.write_template("mode", template);
+                    }
+                });
+            }
+        });
+    }
+}
+
 /// Binding for triggering a keyboard hint.
 #[derive(Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct HintBinding {
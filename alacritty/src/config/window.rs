@@ -1,54 +1,146 @@
 use std::fmt::{self, Formatter};
 use std::os::raw::c_ulong;
+use std::time::Duration;
 
 use glutin::window::Fullscreen;
 use log::error;
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 
+use alacritty_config::{ConfigTemplate, Template};
 use alacritty_config_derive::ConfigDeserialize;
 use alacritty_terminal::config::LOG_TARGET_CONFIG;
 use alacritty_terminal::index::Column;
+use alacritty_terminal::term::Padding as TermPadding;
 
 use crate::config::ui_config::Delta;
 
 /// Default Alacritty name, used for window title and class.
 pub const DEFAULT_NAME: &str = "Alacritty";
 
-#[derive(ConfigDeserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(ConfigDeserialize, Debug, Clone, PartialEq)]
 pub struct WindowConfig {
-    /// Initial position.
+    /// Window position (changes require restart)
+    ///
+    /// Specified in number of pixels. If the position is not set, the window manager will handle
+    /// the placement.
     pub position: Option<Delta<i32>>,
 
-    /// Draw the window with title bar / borders.
+    /// Window monitor (changes require restart)
+    ///
+    /// Index of the monitor the window is placed on. The window position is relative to the top
+    /// left corner of this monitor. This is not supported on Wayland.
+    pub monitor: Option<usize>,
+
+    /// Window workspace (changes require restart)
+    ///
+    /// Index of the workspace the window is opened on. This is only supported on X11 window
+    /// managers following the EWMH specification.
+    pub workspace: Option<u32>,
+
+    /// Window decorations
+    ///
+    /// Values for `decorations`:
+    ///     - full: Borders and title bar
+    ///     - none: Neither borders nor title bar
+    ///
+    /// Values for `decorations` (macOS only):
+    ///     - transparent: Title bar, transparent background and title bar buttons
+    ///     - buttonless: Title bar, transparent background and no title bar buttons
+    ///
+    /// Title bars drawn by Alacritty on Wayland and transparent title bars on macOS use the
+    /// terminal's background color, following changes made by applications at runtime.
     pub decorations: Decorations,
 
-    /// Startup mode.
+    /// Startup Mode (changes require restart)
+    ///
+    /// Values for `startup_mode`:
+    ///   - Windowed
+    ///   - Maximized
+    ///   - Fullscreen
+    ///
+    /// Values for `startup_mode` (macOS only):
+    ///   - SimpleFullscreen
     pub startup_mode: StartupMode,
 
     /// XEmbed parent.
     #[config(skip)]
     pub embed: Option<c_ulong>,
 
-    /// GTK theme variant.
+    /// GTK theme variant (Linux/BSD only)
+    ///
+    /// Override the variant of the GTK theme. Commonly supported values are `dark` and `light`.
+    /// Set this to `None` to use the default theme variant.
     pub gtk_theme_variant: Option<String>,
 
-    /// Spread out additional padding evenly.
+    /// Spread additional padding evenly around the terminal content.
     pub dynamic_padding: bool,
 
-    /// Use dynamic title.
+    /// Allow terminal applications to change Alacritty's window title.
     pub dynamic_title: bool,
 
-    /// Window title.
+    /// Resize the window to 80 or 132 columns when requested by a terminal application using
+    /// DECCOLM (`CSI ? 3 h` and `CSI ? 3 l`).
+    ///
+    /// The screen is always cleared, even if resizing is disabled.
+    pub column_mode_resize: bool,
+
+    /// Briefly show the number of columns and lines in the center of the window after it has
+    /// been resized.
+    pub resize_overlay: bool,
+
+    /// Let the window manager resize the window in steps of whole cells, removing the leftover
+    /// space between the grid and the padding. Has no effect on Wayland and Windows, or when
+    /// starting maximized or fullscreen. Changes of this option or the font size require a
+    /// restart.
+    pub resize_snapping: bool,
+
+    /// Ask for confirmation before closing the window or quitting while a job other than the
+    /// shell is running in the foreground. Closing the window again while the warning is shown
+    /// closes it anyway. Has no effect on Windows.
+    pub confirm_close: bool,
+
+    /// Duration of the `ToggleOpacity`, `SetOpacity` and `ToggleTheme` transitions in
+    /// milliseconds. The value `0` applies changes immediately.
+    transition_duration: u16,
+
+    /// Window title
     pub title: String,
 
-    /// Window class.
+    /// Format of the window title while `dynamic_title` is enabled.
+    ///
+    /// Available placeholders:
+    ///  - {title}: Title set by the terminal application, or `title` if there is none
+    ///  - {cwd}: Working directory of the foreground process
+    ///  - {command}: Name of the foreground process
+    ///  - {columns}: Number of columns of the terminal grid
+    ///  - {lines}: Number of lines of the terminal grid
+    ///
+    /// Unknown placeholders are kept verbatim. Since Alacritty is not notified when the
+    /// foreground process changes, `{cwd}` and `{command}` are refreshed every second. They are
+    /// not available on Windows.
+    pub title_format: String,
+
+    /// Window class (Linux/BSD only)
     pub class: Class,
 
-    /// Pixel padding.
-    padding: Delta<u8>,
-
-    /// Initial dimensions.
+    /// Window padding (changes require restart)
+    ///
+    /// Blank space added around the window in pixels. This padding is scaled by DPI and the
+    /// `x`/`y` values are always added at both opposing sides.
+    ///
+    /// The padding of individual edges can be set using `top`, `right`, `bottom` and `left`,
+    /// which take precedence over the `x`/`y` values of their axis.
+    ///
+    /// Values can either be pixels, or strings with a `px` (pixels), `pt` (points) or `%`
+    /// (percentage of the cell width/height) unit.
+    padding: Padding,
+
+    /// Window dimensions (changes require restart)
+    ///
+    /// Number of lines/columns (not pixels) in the terminal. The number of columns must be at
+    /// least `2`, while using a value of `0` for columns and lines will fall back to the window
+    /// manager's recommended size.
     dimensions: Dimensions,
 }
 
@@ -57,12 +149,20 @@ impl Default for WindowConfig {
         Self {
             dynamic_title: true,
             title: DEFAULT_NAME.into(),
+            title_format: String::from("{title}"),
             position: Default::default(),
+            monitor: Default::default(),
+            workspace: Default::default(),
             decorations: Default::default(),
             startup_mode: Default::default(),
             embed: Default::default(),
             gtk_theme_variant: Default::default(),
             dynamic_padding: Default::default(),
+            column_mode_resize: Default::default(),
+            resize_overlay: true,
+            resize_snapping: Default::default(),
+            confirm_close: Default::default(),
+            transition_duration: Default::default(),
             class: Default::default(),
             padding: Default::default(),
             dimensions: Default::default(),
@@ -71,6 +171,11 @@ impl Default for WindowConfig {
 }
 
 impl WindowConfig {
+    #[inline]
+    pub fn transition_duration(&self) -> Duration {
+        Duration::from_millis(self.transition_duration as u64)
+    }
+
     #[inline]
     pub fn dimensions(&self) -> Option<Dimensions> {
         if self.dimensions.columns.0 != 0
@@ -84,10 +189,14 @@ impl WindowConfig {
     }
 
     #[inline]
-    pub fn padding(&self, dpr: f64) -> (f32, f32) {
-        let padding_x = (f32::from(self.padding.x) * dpr as f32).floor();
-        let padding_y = (f32::from(self.padding.y) * dpr as f32).floor();
-        (padding_x, padding_y)
+    pub fn set_dimensions(&mut self, dimensions: Dimensions) {
+        self.dimensions = dimensions;
+    }
+
+    /// Padding in physical pixels.
+    #[inline]
+    pub fn padding(&self, dpr: f64, cell_width: f32, cell_height: f32) -> TermPadding {
+        self.padding.to_pixels(dpr, cell_width, cell_height)
     }
 
     #[inline]
@@ -148,6 +257,154 @@ pub struct Dimensions {
     pub lines: usize,
 }
 
+/// Padding around the terminal grid.
+///
+/// The `x` and `y` values are used for both edges of their axis, unless a specific edge is set.
+#[derive(ConfigDeserialize, Default, Debug, Copy, Clone, PartialEq)]
+pub struct Padding {
+    x: PaddingSize,
+    y: PaddingSize,
+    top: Option<PaddingSize>,
+    right: Option<PaddingSize>,
+    bottom: Option<PaddingSize>,
+    left: Option<PaddingSize>,
+}
+
+impl Padding {
+    /// Padding in physical pixels.
+    pub fn to_pixels(&self, dpr: f64, cell_width: f32, cell_height: f32) -> TermPadding {
+        let horizontal =
+            |edge: Option<PaddingSize>| edge.unwrap_or(self.x).to_pixels(dpr, cell_width).floor();
+        let vertical =
+            |edge: Option<PaddingSize>| edge.unwrap_or(self.y).to_pixels(dpr, cell_height).floor();
+
+        TermPadding {
+            top: vertical(self.top),
+            right: horizontal(self.right),
+            bottom: vertical(self.bottom),
+            left: horizontal(self.left),
+        }
+    }
+}
+
+/// Overrides applied while the window is on a specific monitor.
+#[derive(ConfigDeserialize, Default, Debug, Clone, PartialEq)]
+pub struct MonitorProfile {
+    /// Name of the monitor, as reported by the windowing system.
+    pub name: String,
+
+    /// Font size in points.
+    pub font_size: Option<f32>,
+
+    /// Padding around the terminal grid.
+    pub padding: Option<Padding>,
+}
+
+/// Padding size with its unit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PaddingSize {
+    /// Pixels, scaled by DPI.
+    Pixels(f32),
+
+    /// Points, scaled by DPI.
+    Points(f32),
+
+    /// Percentage of the cell size along the padding's axis.
+    Percent(f32),
+}
+
+impl Default for PaddingSize {
+    fn default() -> Self {
+        PaddingSize::Pixels(0.)
+    }
+}
+
+impl PaddingSize {
+    /// Size in physical pixels.
+    fn to_pixels(self, dpr: f64, cell_size: f32) -> f32 {
+        match self {
+            PaddingSize::Pixels(pixels) => pixels * dpr as f32,
+            PaddingSize::Points(points) => points * 96. / 72. * dpr as f32,
+            PaddingSize::Percent(percent) => percent / 100. * cell_size,
+        }
+    }
+}
+
+impl ConfigTemplate for PaddingSize {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        match self {
+            PaddingSize::Pixels(pixels) => template.value(key, pixels),
+            PaddingSize::Points(points) => template.value(key, format_args!("{}pt", points)),
+            PaddingSize::Percent(percent) => template.value(key, format_args!("{}%", percent)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PaddingSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PaddingSizeVisitor;
+        impl<'a> Visitor<'a> for PaddingSizeVisitor {
+            type Value = PaddingSize;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a positive size in pixels, or a string with a px, pt or % unit")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(PaddingSize::Pixels(value as f32))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visit_f64(value as f64)
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if value >= 0. {
+                    Ok(PaddingSize::Pixels(value as f32))
+                } else {
+                    Err(E::invalid_value(de::Unexpected::Float(value), &self))
+                }
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                let value = value.trim();
+                let (number, unit): (&str, fn(f32) -> PaddingSize) =
+                    if let Some(number) = value.strip_suffix("px") {
+                        (number, PaddingSize::Pixels)
+                    } else if let Some(number) = value.strip_suffix("pt") {
+                        (number, PaddingSize::Points)
+                    } else if let Some(number) = value.strip_suffix('%') {
+                        (number, PaddingSize::Percent)
+                    } else {
+                        (value, PaddingSize::Pixels)
+                    };
+
+                match number.trim().parse::<f32>() {
+                    Ok(number) if number >= 0. => Ok(unit(number)),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(PaddingSizeVisitor)
+    }
+}
+
 /// Window class hint.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Class {
@@ -161,6 +418,15 @@ impl Default for Class {
     }
 }
 
+impl ConfigTemplate for Class {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.section(key, |template| {
+            template.option("instance", &["Application instance name."], &self.instance);
+            template.option("general", &["General application class."], &self.general);
+        });
+    }
+}
+
 impl<'de> Deserialize<'de> for Class {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -218,3 +484,37 @@ impl<'de> Deserialize<'de> for Class {
         deserializer.deserialize_any(ClassVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padding_units() {
+        let config: WindowConfig =
+            serde_yaml::from_str("padding: { x: 2, y: 4pt, left: 50%, bottom: 1.5px }").unwrap();
+
+        let padding = config.padding(2., 10., 20.);
+
+        assert_eq!(padding, TermPadding { top: 10., right: 4., bottom: 3., left: 5. });
+    }
+
+    #[test]
+    fn padding_rejects_negative() {
+        assert!(serde_yaml::from_str::<PaddingSize>("-3").is_err());
+        assert!(serde_yaml::from_str::<PaddingSize>("-3%").is_err());
+        assert_eq!(serde_yaml::from_str::<PaddingSize>("3").unwrap(), PaddingSize::Pixels(3.));
+    }
+
+    #[test]
+    fn monitor_profile() {
+        let profile: MonitorProfile =
+            serde_yaml::from_str("{ name: DP-1, font_size: 14.0, padding: { x: 4 } }").unwrap();
+        assert_eq!(profile.name, "DP-1");
+        assert_eq!(profile.font_size, Some(14.));
+
+        let padding = profile.padding.unwrap().to_pixels(2., 10., 20.);
+        assert_eq!(padding.left, 8.);
+        assert_eq!(padding.top, 0.);
+    }
+}
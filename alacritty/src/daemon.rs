@@ -1,10 +1,14 @@
 use std::ffi::OsStr;
 use std::fmt::Debug;
+#[cfg(not(any(target_os = "macos", windows)))]
+use std::fs;
 use std::io;
 #[cfg(not(windows))]
 use std::os::unix::process::CommandExt;
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
+#[cfg(not(windows))]
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
 use log::{debug, warn};
@@ -12,6 +16,12 @@ use log::{debug, warn};
 #[cfg(windows)]
 use winapi::um::winbase::{CREATE_NEW_PROCESS_GROUP, CREATE_NO_WINDOW};
 
+#[cfg(not(windows))]
+use alacritty_terminal::tty;
+
+#[cfg(target_os = "macos")]
+use crate::macos;
+
 /// Start the daemon and log error on failure.
 pub fn start_daemon<I, S>(program: &str, args: I)
 where
@@ -74,3 +84,58 @@ where
             .map(|_| ())
     }
 }
+
+/// Get the PID of the shell spawned in the terminal.
+#[cfg(not(windows))]
+pub fn shell_pid() -> Option<libc::pid_t> {
+    // Without a PTY there is no shell.
+    let pid = tty::child_pid();
+    if pid > 0 {
+        Some(pid)
+    } else {
+        None
+    }
+}
+
+/// Get the PID of the foreground process in the terminal.
+///
+/// Falls back to the PID of the shell if there is no foreground process group.
+#[cfg(not(windows))]
+pub fn foreground_process_pid() -> Option<libc::pid_t> {
+    let shell_pid = shell_pid()?;
+    Some(tty::foreground_process_group().unwrap_or(shell_pid))
+}
+
+/// Get the working directory of the foreground process in the terminal.
+#[cfg(not(windows))]
+pub fn foreground_process_path() -> Option<PathBuf> {
+    let pid = foreground_process_pid()?;
+
+    #[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
+    let link_path = format!("/proc/{}/cwd", pid);
+    #[cfg(target_os = "freebsd")]
+    let link_path = format!("/compat/linux/proc/{}/cwd", pid);
+    #[cfg(not(target_os = "macos"))]
+    let cwd = fs::read_link(link_path);
+    #[cfg(target_os = "macos")]
+    let cwd = macos::proc::cwd(pid);
+
+    cwd.ok()
+}
+
+/// Get the name of the foreground process in the terminal.
+#[cfg(not(windows))]
+pub fn foreground_process_name() -> Option<String> {
+    let pid = foreground_process_pid()?;
+
+    #[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
+    let comm_path = format!("/proc/{}/comm", pid);
+    #[cfg(target_os = "freebsd")]
+    let comm_path = format!("/compat/linux/proc/{}/comm", pid);
+    #[cfg(not(target_os = "macos"))]
+    let name = fs::read_to_string(comm_path).map(|name| name.trim_end().to_owned());
+    #[cfg(target_os = "macos")]
+    let name = macos::proc::name(pid);
+
+    name.ok()
+}
@@ -1,6 +1,12 @@
 use std::time::{Duration, Instant};
 
-use crate::config::bell::{BellAnimation, BellConfig};
+use crate::config::bell::BellAnimation;
+use crate::config::ui_config::UiConfig;
+
+/// Minimum time without any bell before another bell is rung.
+///
+/// This coalesces bell storms, like the ones caused by printing binary data, into a single bell.
+const BELL_STORM_INTERVAL: Duration = Duration::from_millis(100);
 
 pub struct VisualBell {
     /// Visual bell animation.
@@ -11,14 +17,26 @@ pub struct VisualBell {
 
     /// The last time the visual bell rang, if at all.
     start_time: Option<Instant>,
+
+    /// The last time a bell was received, including coalesced bells.
+    last_bell: Option<Instant>,
 }
 
 impl VisualBell {
-    /// Ring the visual bell, and return its intensity.
-    pub fn ring(&mut self) -> f64 {
+    /// Ring the visual bell.
+    ///
+    /// Returns `false` if the bell was coalesced with a preceding bell, in which case no other
+    /// bell actions should be performed either.
+    pub fn ring(&mut self) -> bool {
         let now = Instant::now();
+
+        let last_bell = self.last_bell.replace(now);
+        if last_bell.map_or(false, |last| now.duration_since(last) < BELL_STORM_INTERVAL) {
+            return false;
+        }
+
         self.start_time = Some(now);
-        self.intensity_at_instant(now)
+        true
     }
 
     /// Get the currently intensity of the visual bell. The bell's intensity
@@ -98,18 +116,19 @@ impl VisualBell {
         }
     }
 
-    pub fn update_config(&mut self, bell_config: &BellConfig) {
-        self.animation = bell_config.animation;
-        self.duration = bell_config.duration();
+    pub fn update_config(&mut self, config: &UiConfig) {
+        self.animation = config.bell.animation;
+        self.duration = config.accessibility.animation_duration(config.bell.duration());
     }
 }
 
-impl From<&BellConfig> for VisualBell {
-    fn from(bell_config: &BellConfig) -> VisualBell {
+impl From<&UiConfig> for VisualBell {
+    fn from(config: &UiConfig) -> VisualBell {
         VisualBell {
-            animation: bell_config.animation,
-            duration: bell_config.duration(),
+            animation: config.bell.animation,
+            duration: config.accessibility.animation_duration(config.bell.duration()),
             start_time: None,
+            last_bell: None,
         }
     }
 }
@@ -120,3 +139,20 @@ fn cubic_bezier(p0: f64, p1: f64, p2: f64, p3: f64, x: f64) -> f64 {
         + 3.0 * (1.0 - x) * x.powi(2) * p2
         + x.powi(3) * p3
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coalesce_bell_storm() {
+        let mut bell = VisualBell::from(&UiConfig::default());
+
+        assert!(bell.ring());
+        assert!(!bell.ring());
+        assert!(!bell.ring());
+
+        bell.last_bell = bell.last_bell.map(|last_bell| last_bell - BELL_STORM_INTERVAL);
+        assert!(bell.ring());
+    }
+}
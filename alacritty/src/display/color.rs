@@ -6,6 +6,7 @@ use alacritty_terminal::ansi::NamedColor;
 use alacritty_terminal::term::color::{Rgb, COUNT};
 
 use crate::config::color::Colors;
+use crate::display::transition::Lerp;
 
 /// Factor for automatic computation of dim colors.
 pub const DIM_FACTOR: f32 = 0.66;
@@ -134,6 +135,15 @@ impl List {
     }
 }
 
+impl Lerp for List {
+    fn lerp(mut self, other: Self, progress: f32) -> Self {
+        for (color, target) in self.0.iter_mut().zip(other.0.iter()) {
+            *color = color.lerp(*target, progress);
+        }
+        self
+    }
+}
+
 impl Index<usize> for List {
     type Output = Rgb;
 
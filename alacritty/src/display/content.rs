@@ -6,10 +6,12 @@ use std::ops::{Deref, DerefMut, RangeInclusive};
 use alacritty_terminal::ansi::{Color, CursorShape, NamedColor};
 use alacritty_terminal::config::Config;
 use alacritty_terminal::event::EventListener;
+use alacritty_terminal::graphics::GraphicCell;
 use alacritty_terminal::grid::{Dimensions, Indexed};
 use alacritty_terminal::index::{Column, Direction, Line, Point};
 use alacritty_terminal::term::cell::{Cell, Flags};
 use alacritty_terminal::term::color::{CellRgb, Rgb};
+use alacritty_terminal::term::runs::{self, RunCell};
 use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
 use alacritty_terminal::term::{RenderableContent as TerminalContent, Term, TermMode};
 
@@ -31,9 +33,9 @@ pub struct RenderableContent<'a> {
     cursor_shape: CursorShape,
     cursor_point: Point<usize>,
     search: Option<Regex<'a>>,
-    hint: Option<Hint<'a>>,
+    hint: Option<Regex<'a>>,
     config: &'a Config<UiConfig>,
-    colors: &'a List,
+    colors: List,
     focused_match: Option<&'a Match>,
 }
 
@@ -67,13 +69,13 @@ impl<'a> RenderableContent<'a> {
 
         let hint = if display.hint_state.active() {
             display.hint_state.update_matches(term);
-            Some(Hint::from(&display.hint_state))
+            Some(Regex::from(&display.hint_state))
         } else {
             None
         };
 
         Self {
-            colors: &display.colors,
+            colors: display.colors.value(),
             cursor: None,
             terminal_content,
             focused_match,
@@ -186,6 +188,7 @@ impl<'a> Iterator for RenderableContent<'a> {
 pub struct RenderableCell {
     pub character: char,
     pub zerowidth: Option<Vec<char>>,
+    pub graphic: Option<GraphicCell>,
     pub point: Point<usize>,
     pub fg: Rgb,
     pub bg: Rgb,
@@ -215,22 +218,9 @@ impl RenderableCell {
         });
 
         let display_offset = content.terminal_content.display_offset;
-        let viewport_start = Point::new(Line(-(display_offset as i32)), Column(0));
         let colors = &content.config.ui_config.colors;
-        let mut character = cell.c;
 
-        if let Some((c, is_first)) =
-            content.hint.as_mut().and_then(|hint| hint.advance(viewport_start, cell.point))
-        {
-            let (config_fg, config_bg) = if is_first {
-                (colors.hints.start.foreground, colors.hints.start.background)
-            } else {
-                (colors.hints.end.foreground, colors.hints.end.background)
-            };
-            Self::compute_cell_rgb(&mut fg, &mut bg, &mut bg_alpha, config_fg, config_bg);
-
-            character = c;
-        } else if is_selected {
+        if is_selected {
             let config_fg = colors.selection.foreground;
             let config_bg = colors.selection.background;
             Self::compute_cell_rgb(&mut fg, &mut bg, &mut bg_alpha, config_fg, config_bg);
@@ -251,14 +241,20 @@ impl RenderableCell {
             Self::compute_cell_rgb(&mut fg, &mut bg, &mut bg_alpha, config_fg, config_bg);
         }
 
+        // Dim all text which can not be selected while hints are active.
+        if content.hint.as_mut().map_or(false, |hint| !hint.advance(cell.point)) {
+            fg = fg * DIM_FACTOR;
+        }
+
         // Convert cell point to viewport position.
         let cell_point = cell.point;
         let point = display::point_to_viewport(display_offset, cell_point).unwrap();
 
         RenderableCell {
             zerowidth: cell.zerowidth().map(|zerowidth| zerowidth.to_vec()),
+            graphic: cell.graphic().cloned(),
             flags: cell.flags,
-            character,
+            character: cell.c,
             bg_alpha,
             point,
             fg,
@@ -271,7 +267,17 @@ impl RenderableCell {
         self.bg_alpha == 0.
             && self.character == ' '
             && self.zerowidth.is_none()
-            && !self.flags.intersects(Flags::UNDERLINE | Flags::STRIKEOUT | Flags::DOUBLE_UNDERLINE)
+            && self.graphic.is_none()
+            && !self.flags.intersects(Flags::ALL_UNDERLINES | Flags::STRIKEOUT)
+    }
+
+    /// Last column covered by the cell, including the spacer of wide characters.
+    pub fn end_column(&self) -> Column {
+        if self.flags.contains(Flags::WIDE_CHAR) {
+            self.point.column + 1
+        } else {
+            self.point.column
+        }
     }
 
     /// Apply [`CellRgb`] colors to the cell's colors.
@@ -359,6 +365,18 @@ impl RenderableCell {
     }
 }
 
+impl RunCell for RenderableCell {
+    /// Wide character spacers are not rendered, so wide characters span two columns instead.
+    fn continues_with(&self, next: &Self) -> bool {
+        next.point.line == self.point.line
+            && next.point.column == self.end_column() + 1
+            && next.fg == self.fg
+            && next.bg == self.bg
+            && next.bg_alpha == self.bg_alpha
+            && runs::style_flags(next.flags) == runs::style_flags(self.flags)
+    }
+}
+
 /// Cursor storing all information relevant for rendering.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct RenderableCursor {
@@ -387,52 +405,6 @@ impl RenderableCursor {
     }
 }
 
-/// Regex hints for keyboard shortcuts.
-struct Hint<'a> {
-    /// Hint matches and position.
-    regex: Regex<'a>,
-
-    /// Last match checked against current cell position.
-    labels: &'a Vec<Vec<char>>,
-}
-
-impl<'a> Hint<'a> {
-    /// Advance the hint iterator.
-    ///
-    /// If the point is within a hint, the keyboard shortcut character that should be displayed at
-    /// this position will be returned.
-    ///
-    /// The tuple's [`bool`] will be `true` when the character is the first for this hint.
-    fn advance(&mut self, viewport_start: Point, point: Point) -> Option<(char, bool)> {
-        // Check if we're within a match at all.
-        if !self.regex.advance(point) {
-            return None;
-        }
-
-        // Match starting position on this line; linebreaks interrupt the hint labels.
-        let start = self
-            .regex
-            .matches
-            .get(self.regex.index)
-            .map(|regex_match| max(*regex_match.start(), viewport_start))
-            .filter(|start| start.line == point.line)?;
-
-        // Position within the hint label.
-        let label_position = point.column.0 - start.column.0;
-        let is_first = label_position == 0;
-
-        // Hint label character.
-        self.labels[self.regex.index].get(label_position).copied().map(|c| (c, is_first))
-    }
-}
-
-impl<'a> From<&'a HintState> for Hint<'a> {
-    fn from(hint_state: &'a HintState) -> Self {
-        let regex = Regex { matches: Cow::Borrowed(hint_state.matches()), index: 0 };
-        Self { labels: hint_state.labels(), regex }
-    }
-}
-
 /// Wrapper for finding visible regex matches.
 #[derive(Default, Clone)]
 pub struct RegexMatches(pub Vec<RangeInclusive<Point>>);
@@ -444,10 +416,8 @@ impl RegexMatches {
         let viewport_end = viewport_start + term.bottommost_line();
 
         // Compute start of the first and end of the last line.
-        let start_point = Point::new(viewport_start, Column(0));
-        let mut start = term.line_search_left(start_point);
-        let end_point = Point::new(viewport_end, term.last_column());
-        let mut end = term.line_search_right(end_point);
+        let mut start = term.logical_line(viewport_start).start_point();
+        let mut end = term.logical_line(viewport_end).end_point(term);
 
         // Set upper bound on search before/after the viewport to prevent excessive blocking.
         start.line = max(start.line, viewport_start - MAX_SEARCH_LINES);
@@ -509,3 +479,61 @@ impl<'a> Regex<'a> {
         false
     }
 }
+
+impl<'a> From<&'a HintState> for Regex<'a> {
+    fn from(hint_state: &'a HintState) -> Self {
+        // Ignore matches which can no longer be selected with the keys pressed so far.
+        let matches = hint_state
+            .matches()
+            .iter()
+            .zip(hint_state.labels())
+            .filter(|(_, label)| !label.is_empty())
+            .map(|(bounds, _)| bounds.clone())
+            .collect();
+
+        Self { matches: Cow::Owned(RegexMatches(matches)), index: 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::term::runs::RenderableRuns;
+
+    fn cell(line: usize, column: usize, fg: Rgb, flags: Flags) -> RenderableCell {
+        RenderableCell {
+            character: 'x',
+            zerowidth: None,
+            graphic: None,
+            point: Point::new(line, Column(column)),
+            fg,
+            bg: Rgb { r: 0, g: 0, b: 0 },
+            bg_alpha: 0.,
+            flags,
+        }
+    }
+
+    #[test]
+    fn runs_split_at_style_and_gaps() {
+        let red = Rgb { r: 255, g: 0, b: 0 };
+        let blue = Rgb { r: 0, g: 0, b: 255 };
+        let cells = [
+            cell(0, 0, red, Flags::empty()),
+            cell(0, 1, red, Flags::WIDE_CHAR),
+            cell(0, 3, red, Flags::empty()),
+            cell(0, 4, blue, Flags::empty()),
+            cell(0, 6, blue, Flags::empty()),
+            cell(1, 0, blue, Flags::BOLD),
+            cell(1, 1, blue, Flags::BOLD),
+        ];
+
+        let runs: Vec<_> = RenderableRuns::new(&cells)
+            .map(|run| {
+                (run[0].point.line, run[0].point.column.0, run[run.len() - 1].end_column().0)
+            })
+            .collect();
+
+        assert_eq!(runs, [(0, 0, 3), (0, 4, 4), (0, 6, 6), (1, 0, 1)]);
+    }
+}
@@ -0,0 +1,371 @@
+//! Export of the terminal content to files and rich text.
+
+use std::cmp::min;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::BufWriter;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use png::{BitDepth, ColorType, Encoder, EncodingError};
+
+use alacritty_terminal::ansi::{Color, NamedColor};
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Line};
+use alacritty_terminal::selection::SelectionRange;
+use alacritty_terminal::term::cell::{Flags, LineLength};
+use alacritty_terminal::term::color::Rgb;
+use alacritty_terminal::term::{SizeInfo, Term};
+
+use crate::config::font::Font;
+use crate::display::color::{List, DIM_FACTOR};
+use crate::display::content::RenderableCell;
+
+/// Path for a new export file with the specified extension.
+///
+/// Exports are placed in the user's pictures directory, falling back to the home directory.
+pub fn export_path(extension: &str) -> PathBuf {
+    let timestamp = time::strftime("%F_%H-%M-%S", &time::now()).unwrap_or_default();
+    let directory = dirs::picture_dir().or_else(dirs::home_dir).unwrap_or_default();
+    directory.join(format!("Alacritty_{}.{}", timestamp, extension))
+}
+
+/// Rectangular area of the window in pixels, relative to the top-left corner.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// Region covering the entire window.
+    pub fn window(size_info: &SizeInfo) -> Self {
+        Self { x: 0, y: 0, width: size_info.width() as u32, height: size_info.height() as u32 }
+    }
+
+    /// Region covering the visible part of a selection.
+    ///
+    /// Returns `None` if the selection is not inside the viewport.
+    pub fn selection(
+        range: &SelectionRange,
+        display_offset: usize,
+        size_info: &SizeInfo,
+    ) -> Option<Self> {
+        let last_line = size_info.screen_lines() as i32 - 1;
+        let start_line = range.start.line.0 + display_offset as i32;
+        let end_line = range.end.line.0 + display_offset as i32;
+        if end_line < 0 || start_line > last_line {
+            return None;
+        }
+        let start_line = start_line.max(0) as f32;
+        let end_line = min(end_line, last_line) as f32;
+
+        // Only block selections are limited to the selected columns.
+        let (start_column, end_column) = if range.is_block {
+            (range.start.column.0, range.end.column.0)
+        } else {
+            (0, size_info.columns() - 1)
+        };
+
+        let cell_width = size_info.cell_width();
+        let cell_height = size_info.cell_height();
+        let x = start_column as f32 * cell_width + size_info.padding_x();
+        let y = start_line * cell_height + size_info.padding_y();
+        let width = (end_column - start_column + 1) as f32 * cell_width;
+        let height = (end_line - start_line + 1.) * cell_height;
+
+        Some(Self { x: x as u32, y: y as u32, width: width as u32, height: height as u32 })
+    }
+}
+
+/// Write RGBA pixels to a PNG file.
+pub fn png(path: &Path, pixels: &[u8], region: Region) -> Result<(), EncodingError> {
+    let writer = BufWriter::new(File::create(path)?);
+
+    let mut encoder = Encoder::new(writer, region.width, region.height);
+    encoder.set_color(ColorType::RGBA);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)
+}
+
+/// Render terminal cells as SVG image.
+pub fn svg<I>(
+    cells: I,
+    background: Rgb,
+    size_info: &SizeInfo,
+    font: &Font,
+    dpr: f64,
+    descent: f32,
+) -> String
+where
+    I: IntoIterator<Item = RenderableCell>,
+{
+    let cell_width = size_info.cell_width();
+    let cell_height = size_info.cell_height();
+
+    // Match the font size used by the rasterizer, assuming 96 DPI.
+    let font_size = font.size().as_f32_pts() * dpr as f32 * 96. / 72.;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" font-family=\"{family}\" font-size=\"{size}\" \
+         xml:space=\"preserve\">",
+        w = size_info.width(),
+        h = size_info.height(),
+        family = escape(&font.normal().family),
+        size = font_size,
+    );
+    let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", hex(background));
+
+    let mut text = String::new();
+    for cell in cells {
+        let x = cell.point.column.0 as f32 * cell_width + size_info.padding_x();
+        let y = cell.point.line as f32 * cell_height + size_info.padding_y();
+        let width = if cell.flags.contains(Flags::WIDE_CHAR) { 2. } else { 1. } * cell_width;
+
+        // Draw the cell background.
+        if cell.bg_alpha > 0. {
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" \
+                 fill-opacity=\"{}\"/>",
+                x,
+                y,
+                width,
+                cell_height,
+                hex(cell.bg),
+                cell.bg_alpha,
+            );
+        }
+
+        if cell.flags.contains(Flags::HIDDEN) {
+            continue;
+        }
+
+        // Combine the character with its zerowidth characters.
+        let mut content = String::new();
+        content.push(cell.character);
+        for c in cell.zerowidth.iter().flatten() {
+            content.push(*c);
+        }
+        let underline = cell.flags.intersects(Flags::ALL_UNDERLINES);
+        let strikeout = cell.flags.contains(Flags::STRIKEOUT);
+        if content.trim().is_empty() && !underline && !strikeout {
+            continue;
+        }
+
+        let _ = write!(
+            text,
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\"",
+            x,
+            y + cell_height + descent,
+            hex(cell.fg)
+        );
+        if cell.flags.contains(Flags::BOLD) {
+            text.push_str(" font-weight=\"bold\"");
+        }
+        if cell.flags.contains(Flags::ITALIC) {
+            text.push_str(" font-style=\"italic\"");
+        }
+        match (underline, strikeout) {
+            (true, true) => text.push_str(" text-decoration=\"underline line-through\""),
+            (true, false) => text.push_str(" text-decoration=\"underline\""),
+            (false, true) => text.push_str(" text-decoration=\"line-through\""),
+            (false, false) => (),
+        }
+        let _ = writeln!(text, ">{}</text>", escape(&content));
+    }
+
+    // Draw text above all backgrounds.
+    svg.push_str(&text);
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Convert a selection to HTML, preserving the colors and attributes of the text.
+pub fn html<T>(terminal: &Term<T>, range: &SelectionRange, colors: &List) -> String {
+    let dynamic_colors = terminal.colors();
+    let rgb = |color: Color| match color {
+        Color::Spec(rgb) => rgb,
+        Color::Named(named) => dynamic_colors[named].unwrap_or(colors[named]),
+        Color::Indexed(index) => dynamic_colors[index as usize].unwrap_or(colors[index as usize]),
+    };
+
+    let foreground = rgb(Color::Named(NamedColor::Foreground));
+    let background = rgb(Color::Named(NamedColor::Background));
+    let mut html = format!(
+        "<pre style=\"color: {}; background-color: {};\">",
+        hex(foreground),
+        hex(background)
+    );
+
+    let grid = terminal.grid();
+    let last_column = grid.last_column();
+    let styles = Flags::BOLD | Flags::ITALIC | Flags::STRIKEOUT | Flags::ALL_UNDERLINES;
+
+    // Style of the currently open `span` element.
+    let mut style = None;
+
+    for line in (range.start.line.0..=range.end.line.0).map(Line::from) {
+        // Only block selections are limited to the selected columns on every line.
+        let start =
+            if range.is_block || line == range.start.line { range.start.column } else { Column(0) };
+        let end =
+            if range.is_block || line == range.end.line { range.end.column } else { last_column };
+
+        let grid_line = &grid[line];
+        let line_length = min(grid_line.line_length(), end + 1);
+
+        for column in (start.0..line_length.0).map(Column::from) {
+            let cell = &grid_line[column];
+            if cell.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
+                continue;
+            }
+
+            let mut fg = rgb(cell.fg);
+            let mut bg = rgb(cell.bg);
+            if cell.flags.contains(Flags::INVERSE) {
+                mem::swap(&mut fg, &mut bg);
+            }
+            if cell.flags.contains(Flags::DIM) {
+                fg = fg * DIM_FACTOR;
+            }
+
+            // Start a new element whenever the style changes.
+            let cell_style = Some((fg, bg, cell.flags & styles));
+            if cell_style != style {
+                if style.is_some() {
+                    html.push_str("</span>");
+                }
+                html.push_str(&span(fg, bg, cell.flags));
+                style = cell_style;
+            }
+
+            // Tabs are followed by spaces up to the next tabstop, so they are replaced by a space.
+            if cell.flags.contains(Flags::HIDDEN) || cell.c == '\t' {
+                html.push(' ');
+            } else {
+                let mut content = String::new();
+                content.push(cell.c);
+                content.extend(cell.zerowidth().into_iter().flatten());
+                html.push_str(&escape(&content));
+            }
+        }
+
+        let wrapped = !range.is_block
+            && end == last_column
+            && line_length.0 > 0
+            && grid_line[line_length - 1].flags.contains(Flags::WRAPLINE);
+        if line != range.end.line && !wrapped {
+            html.push('\n');
+        }
+    }
+
+    if style.is_some() {
+        html.push_str("</span>");
+    }
+    html.push_str("</pre>");
+
+    html
+}
+
+/// Opening HTML `span` element for text with the specified style.
+fn span(fg: Rgb, bg: Rgb, flags: Flags) -> String {
+    let mut span = format!("<span style=\"color: {}; background-color: {};", hex(fg), hex(bg));
+    if flags.contains(Flags::BOLD) {
+        span.push_str(" font-weight: bold;");
+    }
+    if flags.contains(Flags::ITALIC) {
+        span.push_str(" font-style: italic;");
+    }
+    let underline = flags.intersects(Flags::ALL_UNDERLINES);
+    match (underline, flags.contains(Flags::STRIKEOUT)) {
+        (true, true) => span.push_str(" text-decoration: underline line-through;"),
+        (true, false) => span.push_str(" text-decoration: underline;"),
+        (false, true) => span.push_str(" text-decoration: line-through;"),
+        (false, false) => (),
+    }
+    if flags.contains(Flags::DOUBLE_UNDERLINE) {
+        span.push_str(" text-decoration-style: double;");
+    } else if flags.contains(Flags::UNDERCURL) {
+        span.push_str(" text-decoration-style: wavy;");
+    } else if flags.contains(Flags::DOTTED_UNDERLINE) {
+        span.push_str(" text-decoration-style: dotted;");
+    } else if flags.contains(Flags::DASHED_UNDERLINE) {
+        span.push_str(" text-decoration-style: dashed;");
+    }
+    span.push_str("\">");
+    span
+}
+
+/// Format color in the hexadecimal `#rrggbb` notation.
+fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// Escape text for use in XML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::ansi::Processor;
+    use alacritty_terminal::config::MockConfig;
+    use alacritty_terminal::event::EventListener;
+    use alacritty_terminal::index::Point;
+
+    use crate::config::color::Colors;
+
+    struct MockEventProxy;
+    impl EventListener for MockEventProxy {}
+
+    #[test]
+    fn html_selection() {
+        let size = SizeInfo::new(5., 2., 1., 1., 0., 0., false);
+        let mut terminal = Term::new(&MockConfig::default(), size, MockEventProxy);
+        let mut parser = Processor::new();
+        for byte in b"a<\x1b[1;31mbc\x1b[0m\r\nd" {
+            parser.advance(&mut terminal, *byte);
+        }
+
+        let colors = List::from(&Colors::default());
+        let range = SelectionRange {
+            start: Point::new(Line(0), Column(1)),
+            end: Point::new(Line(1), Column(4)),
+            is_block: false,
+        };
+
+        let fg = hex(colors[NamedColor::Foreground]);
+        let bg = hex(colors[NamedColor::Background]);
+        let red = hex(colors[NamedColor::Red]);
+        let expected = format!(
+            "<pre style=\"color: {fg}; background-color: {bg};\">\
+             <span style=\"color: {fg}; background-color: {bg};\">&lt;</span>\
+             <span style=\"color: {red}; background-color: {bg}; font-weight: bold;\">bc\n</span>\
+             <span style=\"color: {fg}; background-color: {bg};\">d</span></pre>",
+            fg = fg,
+            bg = bg,
+            red = red,
+        );
+        assert_eq!(html(&terminal, &range, &colors), expected);
+    }
+}
@@ -2,15 +2,15 @@ use std::cmp::{max, min};
 
 use glutin::event::ModifiersState;
 
-use alacritty_terminal::grid::BidirectionalIterator;
-use alacritty_terminal::index::{Boundary, Direction, Point};
+use alacritty_terminal::grid::{BidirectionalIterator, Dimensions};
+use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point};
 use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
-use alacritty_terminal::term::{Term, TermMode};
+use alacritty_terminal::term::{SizeInfo, Term, TermMode};
 
-use crate::config::ui_config::{Hint, HintAction};
+use crate::config::ui_config::{Hint, HintAction, HintLabelPosition};
 use crate::config::Config;
 use crate::display::content::RegexMatches;
-use crate::display::MAX_SEARCH_LINES;
+use crate::display::{self, MAX_SEARCH_LINES};
 
 /// Percentage of characters in the hints alphabet used for the last character.
 const HINT_SPLIT_PERCENTAGE: f32 = 0.5;
@@ -155,6 +155,43 @@ impl HintState {
         &self.matches
     }
 
+    /// Viewport position of all hint labels which can still be selected.
+    ///
+    /// Labels are drawn on top of the terminal content, starting at the first character of the
+    /// match or ending at its last character. Matches partially outside of the viewport place
+    /// their label at the closest visible position.
+    pub fn visible_labels<'a>(
+        &'a self,
+        size_info: &SizeInfo,
+        display_offset: usize,
+        position: HintLabelPosition,
+    ) -> impl Iterator<Item = (Point<usize>, &'a [char])> + 'a {
+        let viewport_start = Point::new(Line(-(display_offset as i32)), Column(0));
+        let viewport_end =
+            Point::new(viewport_start.line + size_info.bottommost_line(), size_info.last_column());
+        let last_line = size_info.bottommost_line().0 as usize;
+
+        self.matches.iter().zip(&self.labels).filter(|(_, label)| !label.is_empty()).filter_map(
+            move |(bounds, label)| {
+                let point = match position {
+                    HintLabelPosition::Start => max(*bounds.start(), viewport_start),
+                    HintLabelPosition::End => {
+                        let end = min(*bounds.end(), viewport_end);
+                        let column = end.column.0.saturating_sub(label.len() - 1);
+                        Point::new(end.line, Column(column))
+                    },
+                };
+
+                let point = display::point_to_viewport(display_offset, point)?;
+                if point.line > last_line {
+                    return None;
+                }
+
+                Some((point, label.as_slice()))
+            },
+        )
+    }
+
     /// Update the alphabet used for hint labels.
     pub fn update_alphabet(&mut self, alphabet: &str) {
         if self.alphabet != alphabet {
@@ -261,9 +298,10 @@ pub fn highlighted_at<T>(
 
         hint.regex.with_compiled(|regex| {
             // Setup search boundaries.
-            let mut start = term.line_search_left(point);
+            let line = term.logical_line(point.line);
+            let mut start = line.start_point();
             start.line = max(start.line, point.line - MAX_SEARCH_LINES);
-            let mut end = term.line_search_right(point);
+            let mut end = line.end_point(term);
             end.line = min(end.line, point.line + MAX_SEARCH_LINES);
 
             // Function to verify that the specified point is inside the match.
@@ -442,4 +480,25 @@ mod tests {
         assert_eq!(generator.next(), vec!['3', '3', '3', '0']);
         assert_eq!(generator.next(), vec!['3', '3', '3', '1']);
     }
+
+    #[test]
+    fn label_positions() {
+        let mut state = HintState::new("ab");
+        state.matches.0 = vec![
+            Point::new(Line(-1), Column(3))..=Point::new(Line(0), Column(1)),
+            Point::new(Line(1), Column(2))..=Point::new(Line(1), Column(7)),
+        ];
+        state.labels = vec![vec!['a'], vec!['b', 'a']];
+        let size = SizeInfo::new(100., 50., 10., 10., 0., 0., false);
+
+        let start: Vec<_> = state.visible_labels(&size, 0, HintLabelPosition::Start).collect();
+        let start_labels =
+            [(Point::new(0, Column(0)), &['a'][..]), (Point::new(1, Column(2)), &['b', 'a'][..])];
+        assert_eq!(start, start_labels);
+
+        let end: Vec<_> = state.visible_labels(&size, 0, HintLabelPosition::End).collect();
+        let end_labels =
+            [(Point::new(0, Column(1)), &['a'][..]), (Point::new(1, Column(6)), &['b', 'a'][..])];
+        assert_eq!(end, end_labels);
+    }
 }
@@ -7,7 +7,7 @@ use std::f64;
 use std::fmt::{self, Formatter};
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 use std::sync::atomic::Ordering;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use glutin::dpi::{PhysicalPosition, PhysicalSize};
 use glutin::event::ModifiersState;
@@ -15,13 +15,13 @@ use glutin::event_loop::EventLoop;
 #[cfg(not(any(target_os = "macos", windows)))]
 use glutin::platform::unix::EventLoopWindowTargetExtUnix;
 use glutin::window::CursorIcon;
-use log::{debug, info};
+use log::{debug, error, info, warn};
 use parking_lot::MutexGuard;
 use unicode_width::UnicodeWidthChar;
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 use wayland_client::{Display as WaylandDisplay, EventQueue};
 
-use crossfont::{self, Rasterize, Rasterizer};
+use crossfont::{self, Rasterize, Rasterizer, Size as FontSize};
 
 use alacritty_terminal::ansi::NamedColor;
 use alacritty_terminal::event::{EventListener, OnResize};
@@ -29,33 +29,46 @@ use alacritty_terminal::grid::Dimensions as _;
 use alacritty_terminal::index::{Column, Direction, Line, Point};
 use alacritty_terminal::selection::Selection;
 use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::color::Rgb;
+use alacritty_terminal::term::runs::RenderableRuns;
 use alacritty_terminal::term::{SizeInfo, Term, TermMode, MIN_COLUMNS, MIN_SCREEN_LINES};
 
 use crate::config::font::Font;
-use crate::config::window::Dimensions;
-#[cfg(not(windows))]
-use crate::config::window::StartupMode;
+use crate::config::window::{Dimensions, StartupMode};
 use crate::config::Config;
+#[cfg(not(windows))]
+use crate::daemon::{foreground_process_name, foreground_process_path};
 use crate::display::bell::VisualBell;
 use crate::display::color::List;
-use crate::display::content::RenderableContent;
+use crate::display::content::{RenderableCell, RenderableContent};
 use crate::display::cursor::IntoRects;
+use crate::display::export::Region;
 use crate::display::hint::{HintMatch, HintState};
 use crate::display::meter::Meter;
-use crate::display::window::Window;
+use crate::display::overlay::{DebugOverlay, OverlayState};
+use crate::display::pointer::PointerHighlight;
+use crate::display::title::TitleComponents;
+use crate::display::transition::Transition;
+use crate::display::window::{ResizeIncrements, Window};
 use crate::event::{Mouse, SearchState};
 use crate::message_bar::{MessageBuffer, MessageType};
 use crate::renderer::rects::{RenderLines, RenderRect};
 use crate::renderer::{self, GlyphCache, QuadRenderer};
+use crate::startup::StartupTimer;
 
 pub mod content;
 pub mod cursor;
+pub mod export;
 pub mod hint;
+pub mod title;
 pub mod window;
 
 mod bell;
 mod color;
 mod meter;
+mod overlay;
+mod pointer;
+mod transition;
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 mod wayland_theme;
 
@@ -68,6 +81,15 @@ const FORWARD_SEARCH_LABEL: &str = "Search: ";
 /// Label for the backward terminal search bar.
 const BACKWARD_SEARCH_LABEL: &str = "Backward Search: ";
 
+/// Font size multiplier used by the zoom mode.
+const ZOOM_FACTOR: f32 = 2.;
+
+/// Duration the grid dimensions are shown for after resizing the window.
+pub const RESIZE_OVERLAY_DURATION: Duration = Duration::from_millis(1000);
+
+/// Number of times rapidly blinking text is shown and hidden while slowly blinking text is.
+pub const RAPID_BLINKS_PER_BLINK: u8 = 3;
+
 #[derive(Debug)]
 pub enum Error {
     /// Error with window management.
@@ -172,6 +194,9 @@ pub struct Display {
     pub size_info: SizeInfo,
     pub window: Window,
 
+    /// Name of the monitor the window is on, used for selecting a monitor profile.
+    pub monitor: Option<String>,
+
     /// Hint highlighted by the mouse.
     pub highlighted_hint: Option<HintMatch>,
 
@@ -181,54 +206,134 @@ pub struct Display {
     #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
     pub wayland_event_queue: Option<EventQueue>,
 
+    /// Background and foreground colors of the client side decorations.
+    #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+    decoration_colors: Option<(Rgb, Rgb)>,
+
     #[cfg(not(any(target_os = "macos", windows)))]
     pub is_x11: bool,
 
     /// UI cursor visibility for blinking.
     pub cursor_hidden: bool,
 
+    /// Time of the last input while the cursor is blinking.
+    pub cursor_blink_start: Option<Instant>,
+
+    /// Number of rapid blinks since text with the blink attribute was last shown.
+    ///
+    /// Slowly blinking text is hidden during the second half of each cycle, while rapidly blinking
+    /// text is hidden in every odd phase.
+    pub text_blink_phase: u8,
+
+    /// Text with the blink attribute was visible in the last frame.
+    pub blinking_text_visible: bool,
+
+    /// Time at which the currently visible text started blinking.
+    pub text_blink_start: Option<Instant>,
+
+    /// Reading from the PTY has been stopped using XOFF.
+    pub output_paused: bool,
+
     pub visual_bell: VisualBell,
 
+    /// Mouse cursor highlighting for presentations.
+    pub pointer_highlight: PointerHighlight,
+
+    /// Visualization of the terminal state for debugging.
+    debug_overlay: DebugOverlay,
+
     /// Mapped RGB values for each terminal color.
-    pub colors: List,
+    pub colors: Transition<List>,
+
+    /// Opacity of the window background.
+    pub opacity: Transition<f32>,
 
     /// State of the keyboard hints.
     pub hint_state: HintState,
 
+    /// Window region which will be saved as PNG after the next frame is rendered.
+    pub pending_screenshot: Option<Region>,
+
+    /// Time until which the grid dimensions are shown after resizing the window.
+    pub resize_overlay: Option<Instant>,
+
+    /// Horizontal and vertical magnification while the zoom mode is active.
+    zoom: Option<(f32, f32)>,
+
+    /// Size information of the last rendered grid, which is magnified while zoomed.
+    ///
+    /// This should be used to find the cell at a position in the window.
+    pub grid_size_info: SizeInfo,
+
     renderer: QuadRenderer,
     glyph_cache: GlyphCache,
     meter: Meter,
 }
 
 impl Display {
-    pub fn new<E>(config: &Config, event_loop: &EventLoop<E>) -> Result<Display, Error> {
+    pub fn new<E>(
+        config: &Config,
+        event_loop: &EventLoop<E>,
+        startup_timer: &mut StartupTimer,
+    ) -> Result<Display, Error> {
         #[cfg(any(not(feature = "x11"), target_os = "macos", windows))]
         let is_x11 = false;
         #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
         let is_x11 = event_loop.is_x11();
 
-        // Guess DPR based on first monitor. On Wayland the initial frame always renders at a DPR
+        // Monitor the window is placed on, the first one is used when no monitor is selected.
+        let monitor_index = config.ui_config.window.monitor;
+        let monitor = event_loop.available_monitors().nth(monitor_index.unwrap_or(0));
+        if let (None, Some(index)) = (&monitor, monitor_index) {
+            warn!("Unable to find monitor {}", index);
+        }
+
+        // Guess DPR based on the monitor. On Wayland the initial frame always renders at a DPR
         // of 1.
         let estimated_dpr = if cfg!(any(target_os = "macos", windows)) || is_x11 {
-            event_loop.available_monitors().next().map(|m| m.scale_factor()).unwrap_or(1.)
+            monitor.as_ref().map(|m| m.scale_factor()).unwrap_or(1.)
         } else {
             1.
         };
 
         // Guess the target window dimensions.
-        let metrics = GlyphCache::static_metrics(config.ui_config.font.clone(), estimated_dpr)?;
+        let estimated_monitor = monitor.as_ref().and_then(|monitor| monitor.name());
+        let font_size = config.ui_config.font_size(estimated_monitor.as_deref());
+        let font = config.ui_config.font.clone().with_size(font_size);
+        let metrics = GlyphCache::static_metrics(font, estimated_dpr)?;
         let (cell_width, cell_height) = compute_cell_size(config, &metrics);
+        startup_timer.stage("font load");
 
         // Guess the target window size if the user has specified the number of lines/columns.
         let dimensions = config.ui_config.window.dimensions();
         let estimated_size = dimensions.map(|dimensions| {
-            window_size(config, dimensions, cell_width, cell_height, estimated_dpr)
+            let monitor = estimated_monitor.as_deref();
+            window_size(config, monitor, dimensions, cell_width, cell_height, estimated_dpr)
         });
 
         debug!("Estimated DPR: {}", estimated_dpr);
         debug!("Estimated window size: {:?}", estimated_size);
         debug!("Estimated cell size: {} x {}", cell_width, cell_height);
 
+        // Let the window manager resize the window in steps of whole cells.
+        let window_config = &config.ui_config.window;
+        let resize_increments = if window_config.resize_snapping
+            && window_config.startup_mode == StartupMode::Windowed
+        {
+            let monitor = estimated_monitor.as_deref();
+            let padding = config.ui_config.padding(monitor, estimated_dpr, cell_width, cell_height);
+            Some(ResizeIncrements {
+                base: PhysicalSize::new(
+                    (padding.left + padding.right) as u32,
+                    (padding.top + padding.bottom) as u32,
+                ),
+                cell: PhysicalSize::new(cell_width as u32, cell_height as u32),
+                scale_factor: estimated_dpr,
+            })
+        } else {
+            None
+        };
+
         #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
         let mut wayland_event_queue = None;
 
@@ -244,6 +349,7 @@ impl Display {
             event_loop,
             &config,
             estimated_size,
+            resize_increments,
             #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
             wayland_event_queue.as_ref(),
         )?;
@@ -252,36 +358,41 @@ impl Display {
 
         // Create renderer.
         let mut renderer = QuadRenderer::new()?;
+        startup_timer.stage("gl init");
 
+        let monitor_name = window.current_monitor_name();
         let (glyph_cache, cell_width, cell_height) =
-            Self::new_glyph_cache(window.dpr, &mut renderer, config)?;
+            Self::new_glyph_cache(window.dpr, config, monitor_name.as_deref())?;
+        startup_timer.stage("glyph cache");
 
         if let Some(dimensions) = dimensions {
             if (estimated_dpr - window.dpr).abs() < f64::EPSILON {
                 info!("Estimated DPR correctly, skipping resize");
             } else {
                 // Resize the window again if the DPR was not estimated correctly.
-                let size = window_size(config, dimensions, cell_width, cell_height, window.dpr);
+                let monitor = monitor_name.as_deref();
+                let size =
+                    window_size(config, monitor, dimensions, cell_width, cell_height, window.dpr);
                 window.set_inner_size(size);
             }
         }
 
-        let padding = config.ui_config.window.padding(window.dpr);
+        let padding =
+            config.ui_config.padding(monitor_name.as_deref(), window.dpr, cell_width, cell_height);
         let viewport_size = window.inner_size();
 
         // Create new size with at least one column and row.
-        let size_info = SizeInfo::new(
+        let size_info = SizeInfo::with_padding(
             viewport_size.width as f32,
             viewport_size.height as f32,
             cell_width,
             cell_height,
-            padding.0,
-            padding.1,
+            padding,
             config.ui_config.window.dynamic_padding && dimensions.is_none(),
         );
 
         info!("Cell size: {} x {}", cell_width, cell_height);
-        info!("Padding: {} x {}", size_info.padding_x(), size_info.padding_y());
+        info!("Padding: {:?}", size_info.padding());
         info!("Width: {}, Height: {}", size_info.width(), size_info.height());
 
         // Update OpenGL projection.
@@ -289,8 +400,8 @@ impl Display {
 
         // Clear screen.
         let background_color = config.ui_config.colors.primary.background;
-        renderer.with_api(&config.ui_config, &size_info, |api| {
-            api.clear(background_color);
+        renderer.with_api(&size_info, |api| {
+            api.clear(background_color, config.ui_config.background_opacity());
         });
 
         // Set subpixel anti-aliasing.
@@ -306,19 +417,23 @@ impl Display {
         #[cfg(not(any(target_os = "macos", windows)))]
         if is_x11 {
             window.swap_buffers();
-            renderer.with_api(&config.ui_config, &size_info, |api| {
+            renderer.with_api(&size_info, |api| {
                 api.finish();
             });
         }
 
         window.set_visible(true);
 
-        // Set window position.
+        // Set window position, relative to the selected monitor.
         //
         // TODO: replace `set_position` with `with_position` once available.
         // Upstream issue: https://github.com/rust-windowing/winit/issues/806.
-        if let Some(position) = config.ui_config.window.position {
-            window.set_outer_position(PhysicalPosition::from((position.x, position.y)));
+        let position = config.ui_config.window.position;
+        let monitor_position = monitor.filter(|_| monitor_index.is_some()).map(|m| m.position());
+        if position.is_some() || monitor_position.is_some() {
+            let (x, y) = position.map_or((0, 0), |position| (position.x, position.y));
+            let (monitor_x, monitor_y) = monitor_position.map_or((0, 0), |pos| (pos.x, pos.y));
+            window.set_outer_position(PhysicalPosition::new(monitor_x + x, monitor_y + y));
         }
 
         #[allow(clippy::single_match)]
@@ -335,29 +450,44 @@ impl Display {
 
         Ok(Self {
             window,
+            monitor: monitor_name,
             renderer,
             glyph_cache,
             hint_state,
+            pending_screenshot: None,
+            resize_overlay: None,
+            zoom: None,
             meter: Meter::new(),
             size_info,
+            grid_size_info: size_info,
             highlighted_hint: None,
             vi_highlighted_hint: None,
             #[cfg(not(any(target_os = "macos", windows)))]
             is_x11,
             #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
             wayland_event_queue,
+            #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+            decoration_colors: None,
             cursor_hidden: false,
-            visual_bell: VisualBell::from(&config.ui_config.bell),
-            colors: List::from(&config.ui_config.colors),
+            cursor_blink_start: None,
+            text_blink_phase: 0,
+            blinking_text_visible: false,
+            text_blink_start: None,
+            output_paused: false,
+            visual_bell: VisualBell::from(&config.ui_config),
+            pointer_highlight: Default::default(),
+            debug_overlay: Default::default(),
+            colors: Transition::new(List::from(&config.ui_config.colors)),
+            opacity: Transition::new(config.ui_config.background_opacity()),
         })
     }
 
     fn new_glyph_cache(
         dpr: f64,
-        renderer: &mut QuadRenderer,
         config: &Config,
+        monitor: Option<&str>,
     ) -> Result<(GlyphCache, f32, f32), Error> {
-        let font = config.ui_config.font.clone();
+        let font = config.ui_config.font.clone().with_size(config.ui_config.font_size(monitor));
         let rasterizer = Rasterizer::new(dpr as f32, config.ui_config.font.use_thin_strokes)?;
 
         // Initialize glyph cache.
@@ -365,8 +495,7 @@ impl Display {
             info!("Initializing glyph cache...");
             let init_start = Instant::now();
 
-            let cache =
-                renderer.with_loader(|mut api| GlyphCache::new(rasterizer, &font, &mut api))?;
+            let cache = GlyphCache::new(rasterizer, &font, dpr)?;
 
             let stop = init_start.elapsed();
             let stop_f = stop.as_secs() as f64 + f64::from(stop.subsec_nanos()) / 1_000_000_000f64;
@@ -398,6 +527,77 @@ impl Display {
         compute_cell_size(config, &self.glyph_cache.font_metrics())
     }
 
+    /// Window size required for a number of columns, keeping the current height.
+    pub fn column_mode_size(&self, config: &Config, columns: usize) -> PhysicalSize<u32> {
+        let dimensions = Dimensions { columns: Column(columns), lines: 0 };
+        let (cell_width, cell_height) = (self.size_info.cell_width(), self.size_info.cell_height());
+        let monitor = self.monitor.as_deref();
+        let size =
+            window_size(config, monitor, dimensions, cell_width, cell_height, self.window.dpr);
+
+        PhysicalSize::new(size.width, self.size_info.height() as u32)
+    }
+
+    /// Window size required for the specified grid dimensions.
+    pub fn grid_size(&self, config: &Config, dimensions: Dimensions) -> PhysicalSize<u32> {
+        let (cell_width, cell_height) = (self.size_info.cell_width(), self.size_info.cell_height());
+        let monitor = self.monitor.as_deref();
+        window_size(config, monitor, dimensions, cell_width, cell_height, self.window.dpr)
+    }
+
+    /// Toggle rendering the grid at a magnified scale.
+    ///
+    /// The font is rasterized at a bigger size, but the terminal and PTY are not resized.
+    pub fn toggle_zoom(&mut self, config: &Config, font: &Font) {
+        if self.zoom.take().is_some() {
+            self.update_glyph_cache(config, font);
+            self.grid_size_info = self.size_info;
+            self.renderer.resize(&self.size_info);
+            return;
+        }
+
+        let font = font.clone().with_size(FontSize::new(font.size().as_f32_pts() * ZOOM_FACTOR));
+        let (cell_width, cell_height) = self.update_glyph_cache(config, &font);
+
+        let factor_x = cell_width / self.size_info.cell_width();
+        let factor_y = cell_height / self.size_info.cell_height();
+        self.zoom = Some((factor_x, factor_y));
+    }
+
+    /// Size information used for rendering the grid.
+    ///
+    /// While zoomed, the grid is magnified around the focused cell, keeping it in place.
+    fn zoomed_size_info(&self, focus: Option<Point<usize>>) -> SizeInfo {
+        let (factor_x, factor_y) = match self.zoom {
+            Some(zoom) => zoom,
+            None => return self.size_info,
+        };
+
+        let (x, y) = match focus {
+            Some(point) => {
+                let rect = self.size_info.cell_rect(point, 1);
+                (rect.x + rect.width / 2., rect.y + rect.height / 2.)
+            },
+            None => (self.size_info.width() / 2., self.size_info.height() / 2.),
+        };
+
+        self.size_info.magnify(factor_x, factor_y, x * (factor_x - 1.), y * (factor_y - 1.))
+    }
+
+    /// Prepare rendering of UI elements like the message bar, which are never magnified.
+    ///
+    /// Since the glyphs are rasterized at the zoomed font size, the magnified text area is
+    /// projected onto the regular one, scaling the glyphs back down.
+    fn ui_size_info(&mut self) -> SizeInfo {
+        match self.zoom {
+            Some((factor_x, factor_y)) => {
+                self.renderer.resize_magnified(&self.size_info, factor_x, factor_y);
+                self.size_info.magnify(factor_x, factor_y, 0., 0.)
+            },
+            None => self.size_info,
+        }
+    }
+
     /// Clear glyph cache.
     fn clear_glyph_cache(&mut self) {
         let cache = &mut self.glyph_cache;
@@ -406,6 +606,36 @@ impl Display {
         });
     }
 
+    /// Load the glyphs which are almost guaranteed to be drawn.
+    ///
+    /// This is not required for rendering, so it is done after the first frame to reduce the
+    /// startup time.
+    pub fn warm_up_glyph_cache(&mut self) {
+        let cache = &mut self.glyph_cache;
+        self.renderer.with_loader(|mut api| {
+            cache.load_common_glyphs(&mut api);
+        });
+    }
+
+    /// Evict all glyphs which are not visible and free the atlas textures no longer required.
+    pub fn trim_glyph_cache(&mut self) {
+        let cache = &mut self.glyph_cache;
+        self.renderer.with_loader(|mut api| {
+            cache.trim(&mut api);
+        });
+        self.renderer.trim_atlas();
+    }
+
+    /// Number of glyphs in the glyph cache.
+    pub fn glyph_count(&self) -> usize {
+        self.glyph_cache.glyph_count()
+    }
+
+    /// Estimate the GPU memory used by the glyph atlas.
+    pub fn atlas_memory_usage(&self) -> usize {
+        self.renderer.atlas_memory_usage()
+    }
+
     /// Process update events.
     pub fn handle_update<T>(
         &mut self,
@@ -423,6 +653,7 @@ impl Display {
 
         // Update font size and cell dimensions.
         if let Some(font) = update_pending.font() {
+            self.zoom = None;
             let cell_dimensions = self.update_glyph_cache(config, font);
             cell_width = cell_dimensions.0;
             cell_height = cell_dimensions.1;
@@ -438,15 +669,15 @@ impl Display {
             height = dimensions.height as f32;
         }
 
-        let padding = config.ui_config.window.padding(self.window.dpr);
+        let monitor = self.monitor.as_deref();
+        let padding = config.ui_config.padding(monitor, self.window.dpr, cell_width, cell_height);
 
-        self.size_info = SizeInfo::new(
+        self.size_info = SizeInfo::with_padding(
             width,
             height,
             cell_width,
             cell_height,
-            padding.0,
-            padding.1,
+            padding,
             config.ui_config.window.dynamic_padding,
         );
 
@@ -467,8 +698,9 @@ impl Display {
             PhysicalSize::new(self.size_info.width() as u32, self.size_info.height() as u32);
         self.window.resize(physical);
         self.renderer.resize(&self.size_info);
+        self.grid_size_info = self.size_info;
 
-        info!("Padding: {} x {}", self.size_info.padding_x(), self.size_info.padding_y());
+        info!("Padding: {:?}", self.size_info.padding());
         info!("Width: {}, Height: {}", self.size_info.width(), self.size_info.height());
     }
 
@@ -479,11 +711,14 @@ impl Display {
     /// This call may block if vsync is enabled.
     pub fn draw<T: EventListener>(
         &mut self,
-        terminal: MutexGuard<'_, Term<T>>,
+        mut terminal: MutexGuard<'_, Term<T>>,
         message_buffer: &MessageBuffer,
         config: &Config,
         search_state: &SearchState,
+        mouse: &Mouse,
     ) {
+        self.glyph_cache.start_frame();
+
         // Collect renderable content before the terminal is dropped.
         let mut content = RenderableContent::new(config, self, &terminal, search_state);
         let mut grid_cells = Vec::new();
@@ -491,72 +726,142 @@ impl Display {
             grid_cells.push(cell);
         }
         let background_color = content.color(NamedColor::Background as usize);
+        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+        let foreground_color = content.color(NamedColor::Foreground as usize);
         let display_offset = content.display_offset();
         let cursor = content.cursor();
+        let hint_labels = self.hint_labels(config, &grid_cells, display_offset, background_color);
 
-        let cursor_point = terminal.grid().cursor.point;
+        let size_info = self.zoomed_size_info(cursor.as_ref().map(|cursor| cursor.point()));
+        let cursor_rect = terminal.cursor_rect(&size_info);
         let total_lines = terminal.grid().total_lines();
         let metrics = self.glyph_cache.font_metrics();
-        let size_info = self.size_info;
 
         let vi_mode = terminal.mode().contains(TermMode::VI);
         let vi_mode_cursor = if vi_mode { Some(terminal.vi_mode_cursor) } else { None };
 
+        let graphics_queues = terminal.graphics_take_queues();
+
+        let overlay_state =
+            if config.ui_config.debug.overlay { Some(OverlayState::new(&terminal)) } else { None };
+
         // Drop terminal as early as possible to free lock.
         drop(terminal);
 
-        self.renderer.with_api(&config.ui_config, &size_info, |api| {
-            api.clear(background_color);
+        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+        self.update_decorations(config, background_color, foreground_color);
+
+        // Magnify the grid, the projection is reset for the UI elements afterwards.
+        if self.zoom.is_some() {
+            self.renderer.resize(&size_info);
+        }
+        self.grid_size_info = size_info;
+
+        let opacity = self.opacity.value();
+        self.renderer.with_api(&size_info, |api| {
+            api.clear(background_color, opacity);
         });
 
+        if let Some(graphics_queues) = graphics_queues {
+            self.renderer.update_graphics(graphics_queues);
+        }
+
         let mut lines = RenderLines::new();
 
         // Draw grid.
         {
             let _sampler = self.meter.sampler();
 
-            let glyph_cache = &mut self.glyph_cache;
+            // Underline hints hovered by mouse or vi mode cursor.
             let highlighted_hint = &self.highlighted_hint;
             let vi_highlighted_hint = &self.vi_highlighted_hint;
-            self.renderer.with_api(&config.ui_config, &size_info, |mut api| {
-                // Iterate over all non-empty cells in the grid.
-                for mut cell in grid_cells {
-                    // Underline hints hovered by mouse or vi mode cursor.
-                    let point = viewport_to_point(display_offset, cell.point);
-                    if highlighted_hint.as_ref().map_or(false, |h| h.bounds.contains(&point))
-                        || vi_highlighted_hint.as_ref().map_or(false, |h| h.bounds.contains(&point))
-                    {
-                        cell.flags.insert(Flags::UNDERLINE);
+            let blink_phase = self.text_blink_phase;
+            self.blinking_text_visible = false;
+            for cell in &mut grid_cells {
+                // Hide text with the blink attribute while it is blinked off.
+                if cell.flags.intersects(Flags::ALL_BLINKS) {
+                    self.blinking_text_visible = true;
+                    let hidden = if cell.flags.contains(Flags::RAPID_BLINK) {
+                        blink_phase % 2 == 1
+                    } else {
+                        blink_phase >= RAPID_BLINKS_PER_BLINK
+                    };
+                    if hidden {
+                        cell.flags.insert(Flags::HIDDEN);
                     }
+                }
 
+                let point = viewport_to_point(display_offset, cell.point);
+                if highlighted_hint.as_ref().map_or(false, |h| h.bounds.contains(&point))
+                    || vi_highlighted_hint.as_ref().map_or(false, |h| h.bounds.contains(&point))
+                {
+                    cell.flags.insert(Flags::UNDERLINE);
+                }
+            }
+
+            let glyph_cache = &mut self.glyph_cache;
+            self.renderer.with_api(&size_info, |mut api| {
+                // Iterate over all non-empty cells in the grid, grouped by their styling.
+                for run in RenderableRuns::new(&grid_cells) {
                     // Update underline/strikeout.
-                    lines.update(&cell);
+                    lines.update(run);
+
+                    // Draw the cells.
+                    api.render_run(run, glyph_cache);
+                }
+            });
+
+            // Draw graphics above the text of their cells, but below the hint labels.
+            self.renderer.draw_graphics(&size_info, &grid_cells);
 
-                    // Draw the cell.
+            // Draw hint labels in a separate batch, so they're rendered above the grid's text.
+            self.renderer.with_api(&size_info, |mut api| {
+                for cell in &hint_labels {
                     api.render_cell(cell, glyph_cache);
                 }
             });
         }
 
+        let metrics = config.ui_config.accessibility.line_metrics(metrics);
         let mut rects = lines.rects(&metrics, &size_info);
 
-        if let Some(vi_mode_cursor) = vi_mode_cursor {
+        let ui_size_info = self.ui_size_info();
+        if self.output_paused {
+            // Indicate that the output has been stopped, since it looks like a frozen terminal.
+            let vi_point = vi_mode_cursor.map(|cursor| cursor.point);
+            let text = "[Output paused, Ctrl+Q resumes]";
+            self.draw_indicator(config, &ui_size_info, vi_point, text);
+        } else if let Some(vi_mode_cursor) = vi_mode_cursor {
             // Indicate vi mode by showing the cursor's position in the top right corner.
             let vi_point = vi_mode_cursor.point;
             let line = (-vi_point.line.0 + size_info.bottommost_line().0) as usize;
-            self.draw_line_indicator(config, &size_info, total_lines, Some(vi_point), line);
+            self.draw_line_indicator(config, &ui_size_info, total_lines, Some(vi_point), line);
         } else if search_state.regex().is_some() {
             // Show current display offset in vi-less search to indicate match position.
-            self.draw_line_indicator(config, &size_info, total_lines, None, display_offset);
+            self.draw_line_indicator(config, &ui_size_info, total_lines, None, display_offset);
         }
 
         // Push the cursor rects for rendering.
         if let Some(cursor) = cursor {
-            for rect in cursor.rects(&size_info, config.cursor.thickness()) {
+            let thickness =
+                config.ui_config.accessibility.cursor_thickness(config.cursor.thickness());
+            for rect in cursor.rects(&size_info, thickness) {
                 rects.push(rect);
             }
         }
 
+        // Highlight the mouse cursor for presentations.
+        let presentation = &config.ui_config.mouse.presentation;
+        if presentation.enabled {
+            let highlight = &self.pointer_highlight;
+            rects.extend(highlight.rects(presentation, mouse, &size_info, display_offset));
+        }
+
+        // Visualize the terminal state for debugging.
+        if let Some(overlay_state) = overlay_state {
+            rects.extend(self.debug_overlay.rects(&overlay_state, &size_info, &grid_cells));
+        }
+
         // Push visual bell after url/underline/strikeout rects.
         let visual_bell_intensity = self.visual_bell.intensity();
         if visual_bell_intensity != 0. {
@@ -573,11 +878,12 @@ impl Display {
 
         if let Some(message) = message_buffer.message() {
             let search_offset = if search_state.regex().is_some() { 1 } else { 0 };
-            let text = message.text(&size_info);
+            let text = message.text(&self.size_info);
 
             // Create a new rectangle for the background.
-            let start_line = size_info.screen_lines() + search_offset;
-            let y = size_info.cell_height().mul_add(start_line as f32, size_info.padding_y());
+            let start_line = self.size_info.screen_lines() + search_offset;
+            let y =
+                self.size_info.cell_height().mul_add(start_line as f32, self.size_info.padding_y());
 
             let bg = match message.ty() {
                 MessageType::Error => config.ui_config.colors.normal.red,
@@ -591,23 +897,36 @@ impl Display {
             rects.push(message_bar_rect);
 
             // Draw rectangles.
-            self.renderer.draw_rects(&size_info, rects);
+            self.renderer.draw_rects(&size_info, &metrics, rects);
 
             // Relay messages to the user.
+            let ui_size_info = self.ui_size_info();
             let glyph_cache = &mut self.glyph_cache;
             let fg = config.ui_config.colors.primary.background;
             for (i, message_text) in text.iter().enumerate() {
                 let point = Point::new(start_line + i, Column(0));
-                self.renderer.with_api(&config.ui_config, &size_info, |mut api| {
+                self.renderer.with_api(&ui_size_info, |mut api| {
                     api.render_string(glyph_cache, point, fg, bg, &message_text);
                 });
             }
         } else {
             // Draw rectangles.
-            self.renderer.draw_rects(&size_info, rects);
+            self.renderer.draw_rects(&size_info, &metrics, rects);
         }
 
-        self.draw_render_timer(config, &size_info);
+        // Drawing rectangles resets the viewport to the magnified grid.
+        let ui_size_info = self.ui_size_info();
+
+        self.draw_render_timer(config, &ui_size_info);
+
+        // Show the grid dimensions while the window is being resized.
+        match self.resize_overlay {
+            Some(deadline) if deadline > Instant::now() => {
+                self.draw_resize_overlay(config, &ui_size_info)
+            },
+            Some(_) => self.resize_overlay = None,
+            None => (),
+        }
 
         // Handle search and IME positioning.
         let ime_position = match search_state.regex() {
@@ -617,20 +936,31 @@ impl Display {
                     Direction::Left => BACKWARD_SEARCH_LABEL,
                 };
 
-                let search_text = Self::format_search(&size_info, regex, search_label);
+                let search_status = Self::format_search_status(search_state);
+                let status_len = search_status.chars().count();
+                let num_cols = ui_size_info.columns().saturating_sub(status_len);
+                let search_text = Self::format_search(num_cols, regex, search_label);
 
                 // Render the search bar.
-                self.draw_search(config, &size_info, &search_text);
+                self.draw_search(config, &ui_size_info, &search_text, &search_status);
 
                 // Compute IME position.
-                let line = Line(size_info.screen_lines() as i32 + 1);
-                Point::new(line, Column(search_text.chars().count() - 1))
+                let line = self.size_info.screen_lines() + 1;
+                let point = Point::new(line, Column(search_text.chars().count() - 1));
+                Some(self.size_info.cell_rect(point, 1))
             },
-            None => cursor_point,
+            None => cursor_rect,
         };
 
         // Update IME position.
-        self.window.update_ime_position(ime_position, &self.size_info);
+        if let Some(ime_position) = ime_position {
+            self.window.update_ime_position(ime_position);
+        }
+
+        // Save the frame before it is presented.
+        if let Some(region) = self.pending_screenshot.take() {
+            self.save_screenshot(region);
+        }
 
         // Frame event should be requested before swaping buffers, since it requires surface
         // `commit`, which is done by swap buffers under the hood.
@@ -644,16 +974,198 @@ impl Display {
             // On X11 `swap_buffers` does not block for vsync. However the next OpenGl command
             // will block to synchronize (this is `glClear` in Alacritty), which causes a
             // permanent one frame delay.
-            self.renderer.with_api(&config.ui_config, &size_info, |api| {
+            self.renderer.with_api(&size_info, |api| {
                 api.finish();
             });
         }
     }
 
+    /// Save a region of the current frame as PNG image.
+    fn save_screenshot(&self, region: Region) {
+        if region.width == 0 || region.height == 0 {
+            warn!("Unable to save screenshot of empty region");
+            return;
+        }
+
+        let pixels = self.renderer.read_pixels(&self.size_info, region);
+
+        let path = export::export_path("png");
+        match export::png(&path, &pixels, region) {
+            Ok(()) => info!("Saved screenshot to {:?}", path),
+            Err(err) => error!("Unable to save screenshot to {:?}: {}", path, err),
+        }
+    }
+
+    /// Render the visible terminal content as SVG image.
+    pub fn svg<T: EventListener>(
+        &mut self,
+        terminal: &Term<T>,
+        config: &Config,
+        search_state: &SearchState,
+        font: &Font,
+    ) -> String {
+        let mut content = RenderableContent::new(config, self, terminal, search_state);
+        let mut grid_cells = Vec::new();
+        while let Some(cell) = content.next() {
+            grid_cells.push(cell);
+        }
+        let background_color = content.color(NamedColor::Background as usize);
+
+        let descent = self.glyph_cache.font_metrics().descent;
+
+        export::svg(grid_cells, background_color, &self.size_info, font, self.window.dpr, descent)
+    }
+
     /// Update to a new configuration.
     pub fn update_config(&mut self, config: &Config) {
-        self.visual_bell.update_config(&config.ui_config.bell);
-        self.colors = List::from(&config.ui_config.colors);
+        self.visual_bell.update_config(&config.ui_config);
+        self.colors.set(List::from(&config.ui_config.colors));
+        self.opacity.set(config.ui_config.background_opacity());
+
+        // Force an update of the decorations, since the theme colors might have changed.
+        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+        {
+            self.decoration_colors = None;
+        }
+    }
+
+    /// Characters missing from all fonts, if new ones were found since the last call.
+    pub fn take_missing_glyphs(&mut self) -> Option<Vec<char>> {
+        self.glyph_cache.take_missing_glyphs()
+    }
+
+    /// Set the window title from the configured title format and update the icon name.
+    pub fn update_title<T>(&mut self, config: &Config, terminal: &Term<T>) {
+        let window_config = &config.ui_config.window;
+        if !window_config.dynamic_title {
+            self.window.set_title(&window_config.title);
+            self.window.set_icon_name(&window_config.title);
+            return;
+        }
+
+        // The icon name is not formatted, since it should be short enough for taskbars.
+        self.window.set_icon_name(terminal.icon_name().unwrap_or(&window_config.title));
+
+        let format = &window_config.title_format;
+        #[cfg(not(windows))]
+        let (cwd, command) = if title::needs_process_info(format) {
+            (foreground_process_path(), foreground_process_name())
+        } else {
+            (None, None)
+        };
+        #[cfg(windows)]
+        let (cwd, command): (Option<std::path::PathBuf>, Option<String>) = (None, None);
+
+        let components = TitleComponents {
+            title: terminal.title().unwrap_or(&window_config.title),
+            cwd: cwd.as_deref(),
+            command: command.as_deref(),
+            columns: self.size_info.columns(),
+            lines: self.size_info.screen_lines(),
+        };
+        let title = title::format_title(format, &components);
+
+        self.window.set_title(&title);
+    }
+
+    /// Start transitioning to the colors of the current configuration.
+    pub fn transition_colors(&mut self, config: &Config) {
+        let ui_config = &config.ui_config;
+        let duration =
+            ui_config.accessibility.animation_duration(ui_config.window.transition_duration());
+        self.colors.start(List::from(&ui_config.colors), duration);
+    }
+
+    /// Start transitioning to a new background opacity.
+    pub fn transition_opacity(&mut self, config: &Config, opacity: f32) {
+        let ui_config = &config.ui_config;
+        let duration =
+            ui_config.accessibility.animation_duration(ui_config.window.transition_duration());
+        self.opacity.start(opacity, duration);
+    }
+
+    /// Update the client side decorations to match the terminal's colors.
+    ///
+    /// This makes sure the title bar follows changes to the background through escape sequences.
+    #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
+    fn update_decorations(&mut self, config: &Config, background: Rgb, foreground: Rgb) {
+        if self.window.wayland_surface().is_none()
+            || self.decoration_colors == Some((background, foreground))
+        {
+            return;
+        }
+
+        self.decoration_colors = Some((background, foreground));
+        self.window.set_wayland_theme(&config.ui_config.colors, background, foreground);
+    }
+
+    /// Create the cells for all visible keyboard hint labels.
+    ///
+    /// Label colors referencing the cell's colors use the terminal cell below the label.
+    fn hint_labels(
+        &self,
+        config: &Config,
+        grid_cells: &[RenderableCell],
+        display_offset: usize,
+        background: Rgb,
+    ) -> Vec<RenderableCell> {
+        let colors = &config.ui_config.colors;
+        let position = config.ui_config.hints.label_position;
+        let columns = self.size_info.columns();
+
+        let mut cells = Vec::new();
+        let labels = self.hint_state.visible_labels(&self.size_info, display_offset, position);
+        for (start, label) in labels {
+            for (i, character) in label.iter().enumerate() {
+                let point = Point::new(start.line, start.column + i);
+                if point.column.0 >= columns {
+                    break;
+                }
+
+                let cell = grid_cells
+                    .binary_search_by_key(&point, |cell| cell.point)
+                    .ok()
+                    .map(|index| &grid_cells[index]);
+                let (fg, bg) =
+                    cell.map_or((colors.primary.foreground, background), |cell| (cell.fg, cell.bg));
+
+                let (label_fg, label_bg) = if i == 0 {
+                    (colors.hints.start.foreground, colors.hints.start.background)
+                } else {
+                    (colors.hints.end.foreground, colors.hints.end.background)
+                };
+                let label_fg = label_fg.color(fg, bg);
+                let label_bg = label_bg.color(fg, bg);
+
+                cells.push(RenderableCell {
+                    character: *character,
+                    zerowidth: None,
+                    graphic: None,
+                    point,
+                    fg: label_fg,
+                    bg: label_bg,
+                    bg_alpha: 1.,
+                    flags: Flags::empty(),
+                });
+
+                // Hide the second half of wide characters partially covered by the label.
+                let is_wide = cell.map_or(false, |cell| cell.flags.contains(Flags::WIDE_CHAR));
+                if is_wide && i + 1 == label.len() && point.column.0 + 1 < columns {
+                    cells.push(RenderableCell {
+                        character: ' ',
+                        zerowidth: None,
+                        graphic: None,
+                        point: Point::new(point.line, point.column + 1),
+                        fg: label_fg,
+                        bg: label_bg,
+                        bg_alpha: 1.,
+                        flags: Flags::empty(),
+                    });
+                }
+            }
+        }
+
+        cells
     }
 
     /// Update the mouse/vi mode cursor hint highlighting.
@@ -685,7 +1197,7 @@ impl Display {
         }
 
         // Find highlighted hint at mouse position.
-        let point = mouse.point(&self.size_info, term.grid().display_offset());
+        let point = mouse.point(&self.grid_size_info, term.grid().display_offset());
         let highlighted_hint = hint::highlighted_at(&term, config, point, modifiers);
 
         // Update cursor shape.
@@ -706,7 +1218,7 @@ impl Display {
     }
 
     /// Format search regex to account for the cursor and fullwidth characters.
-    fn format_search(size_info: &SizeInfo, search_regex: &str, search_label: &str) -> String {
+    fn format_search(num_cols: usize, search_regex: &str, search_label: &str) -> String {
         // Add spacers for wide chars.
         let mut formatted_regex = String::with_capacity(search_regex.len());
         for c in search_regex.chars() {
@@ -720,7 +1232,6 @@ impl Display {
         formatted_regex.push('_');
 
         // Truncate beginning of the search regex if it exceeds the viewport width.
-        let num_cols = size_info.columns();
         let label_len = search_label.chars().count();
         let regex_len = formatted_regex.chars().count();
         let truncate_len = min((regex_len + label_len).saturating_sub(num_cols), regex_len);
@@ -736,19 +1247,41 @@ impl Display {
         bar_text
     }
 
+    /// Format the match count and wrap indicator shown at the end of the search bar.
+    fn format_search_status(search_state: &SearchState) -> String {
+        let mut status = String::new();
+
+        if search_state.wrapped() {
+            status.push_str("[wrapped] ");
+        }
+
+        if let Some(match_count) = search_state.match_count() {
+            let index = match match_count.index {
+                Some(index) => (index + 1).to_string(),
+                None => String::from("?"),
+            };
+            // Counts are incomplete while the search thread is still counting.
+            let limited = if match_count.limited || !match_count.complete { "+" } else { "" };
+            status.push_str(&format!("{}/{}{} ", index, match_count.total, limited));
+        }
+
+        status
+    }
+
     /// Draw current search regex.
-    fn draw_search(&mut self, config: &Config, size_info: &SizeInfo, text: &str) {
+    fn draw_search(&mut self, config: &Config, size_info: &SizeInfo, text: &str, status: &str) {
         let glyph_cache = &mut self.glyph_cache;
         let num_cols = size_info.columns();
 
-        // Assure text length is at least num_cols.
-        let text = format!("{:<1$}", text, num_cols);
+        // Assure text length is at least num_cols, with the status aligned to the right.
+        let text_len = num_cols.saturating_sub(status.chars().count());
+        let text = format!("{:<1$}{2}", text, text_len, status);
 
         let point = Point::new(size_info.screen_lines(), Column(0));
         let fg = config.ui_config.colors.search_bar_foreground();
         let bg = config.ui_config.colors.search_bar_background();
 
-        self.renderer.with_api(&config.ui_config, &size_info, |mut api| {
+        self.renderer.with_api(&size_info, |mut api| {
             api.render_string(glyph_cache, point, fg, bg, &text);
         });
     }
@@ -766,11 +1299,27 @@ impl Display {
         let fg = config.ui_config.colors.primary.background;
         let bg = config.ui_config.colors.normal.red;
 
-        self.renderer.with_api(&config.ui_config, &size_info, |mut api| {
+        self.renderer.with_api(&size_info, |mut api| {
             api.render_string(glyph_cache, point, fg, bg, &timing);
         });
     }
 
+    /// Draw the grid dimensions in the center of the window.
+    fn draw_resize_overlay(&mut self, config: &Config, size_info: &SizeInfo) {
+        let text = format!(" {}x{} ", self.size_info.columns(), self.size_info.screen_lines());
+        let column = Column(size_info.columns().saturating_sub(text.len()) / 2);
+        let point = Point::new(size_info.screen_lines() / 2, column);
+
+        let colors = &config.ui_config.colors;
+        let fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
+        let bg = colors.line_indicator.background.unwrap_or(colors.primary.foreground);
+
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.with_api(&size_info, |mut api| {
+            api.render_string(glyph_cache, point, fg, bg, &text);
+        });
+    }
+
     /// Draw an indicator for the position of a line in history.
     fn draw_line_indicator(
         &mut self,
@@ -781,6 +1330,17 @@ impl Display {
         line: usize,
     ) {
         let text = format!("[{}/{}]", line, total_lines - 1);
+        self.draw_indicator(config, size_info, vi_mode_point, &text);
+    }
+
+    /// Draw an indicator in the top right corner of the window.
+    fn draw_indicator(
+        &mut self,
+        config: &Config,
+        size_info: &SizeInfo,
+        vi_mode_point: Option<Point>,
+        text: &str,
+    ) {
         let column = Column(size_info.columns().saturating_sub(text.len()));
         let colors = &config.ui_config.colors;
         let fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
@@ -789,8 +1349,8 @@ impl Display {
         // Do not render anything if it would obscure the vi mode cursor.
         if vi_mode_point.map_or(true, |point| point.line != 0 || point.column < column) {
             let glyph_cache = &mut self.glyph_cache;
-            self.renderer.with_api(&config.ui_config, &size_info, |mut api| {
-                api.render_string(glyph_cache, Point::new(0, column), fg, bg, &text);
+            self.renderer.with_api(&size_info, |mut api| {
+                api.render_string(glyph_cache, Point::new(0, column), fg, bg, text);
             });
         }
     }
@@ -844,18 +1404,19 @@ fn compute_cell_size(config: &Config, metrics: &crossfont::Metrics) -> (f32, f32
 /// Calculate the size of the window given padding, terminal dimensions and cell size.
 fn window_size(
     config: &Config,
+    monitor: Option<&str>,
     dimensions: Dimensions,
     cell_width: f32,
     cell_height: f32,
     dpr: f64,
 ) -> PhysicalSize<u32> {
-    let padding = config.ui_config.window.padding(dpr);
+    let padding = config.ui_config.padding(monitor, dpr, cell_width, cell_height);
 
     let grid_width = cell_width * dimensions.columns.0.max(MIN_COLUMNS) as f32;
     let grid_height = cell_height * dimensions.lines.max(MIN_SCREEN_LINES) as f32;
 
-    let width = (padding.0).mul_add(2., grid_width).floor();
-    let height = (padding.1).mul_add(2., grid_height).floor();
+    let width = (padding.left + grid_width + padding.right).floor();
+    let height = (padding.top + grid_height + padding.bottom).floor();
 
     PhysicalSize::new(width as u32, height as u32)
 }
@@ -0,0 +1,200 @@
+//! Overlay visualizing terminal state, for diagnosing emulation bugs.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use alacritty_terminal::event::EventListener;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::term::color::Rgb;
+use alacritty_terminal::term::{SizeInfo, Term};
+
+use crate::display::content::RenderableCell;
+use crate::renderer::rects::RenderRect;
+
+/// Color of the scroll region borders.
+const SCROLL_REGION_COLOR: Rgb = Rgb { r: 0x00, g: 0x80, b: 0xff };
+
+/// Color of the tab stop markers.
+const TAB_STOP_COLOR: Rgb = Rgb { r: 0xff, g: 0xd0, b: 0x00 };
+
+/// Color of the padding around the grid.
+const MARGIN_COLOR: Rgb = Rgb { r: 0xff, g: 0x00, b: 0xff };
+
+/// Color of the cells which changed since the last frame.
+const DAMAGE_COLOR: Rgb = Rgb { r: 0xff, g: 0x00, b: 0x00 };
+
+/// Opacity of all overlay rects.
+const OVERLAY_ALPHA: f32 = 0.3;
+
+/// Terminal state shown by the overlay.
+pub struct OverlayState {
+    scroll_region: Range<Line>,
+    tab_stops: Vec<Column>,
+    display_offset: usize,
+}
+
+impl OverlayState {
+    pub fn new<T: EventListener>(terminal: &Term<T>) -> Self {
+        Self {
+            scroll_region: terminal.scroll_region().clone(),
+            tab_stops: terminal.tab_stops().collect(),
+            display_offset: terminal.grid().display_offset(),
+        }
+    }
+}
+
+/// Debug overlay for scroll regions, tab stops, margins and damaged cells.
+#[derive(Default)]
+pub struct DebugOverlay {
+    /// Cells of the previous frame.
+    last_frame: Vec<RenderableCell>,
+}
+
+impl DebugOverlay {
+    /// Rects for the overlay of the current frame.
+    pub fn rects(
+        &mut self,
+        state: &OverlayState,
+        size_info: &SizeInfo,
+        cells: &[RenderableCell],
+    ) -> Vec<RenderRect> {
+        let mut rects = Vec::new();
+
+        let grid_width = size_info.columns() as f32 * size_info.cell_width();
+        let grid_height = size_info.screen_lines() as f32 * size_info.cell_height();
+        let (padding_x, padding_y) = (size_info.padding_x(), size_info.padding_y());
+
+        // Margins around the grid.
+        let (width, height) = (size_info.width(), size_info.height());
+        let bottom = padding_y + grid_height;
+        let right = padding_x + grid_width;
+        for &(x, y, w, h) in [
+            (0., 0., width, padding_y),
+            (0., bottom, width, height - bottom),
+            (0., padding_y, padding_x, grid_height),
+            (right, padding_y, width - right, grid_height),
+        ]
+        .iter()
+        {
+            rects.push(RenderRect::new(x, y, w, h, MARGIN_COLOR, OVERLAY_ALPHA));
+        }
+
+        // Tab stops, marked at the left edge of their column.
+        for column in &state.tab_stops {
+            let x = padding_x + column.0 as f32 * size_info.cell_width();
+            rects.push(RenderRect::new(x, padding_y, 1., grid_height, TAB_STOP_COLOR, 1.));
+        }
+
+        // Top and bottom border of the scroll region.
+        let region = &state.scroll_region;
+        for line in [region.start.0, region.end.0].iter() {
+            let viewport_line = *line as usize + state.display_offset;
+            if viewport_line <= size_info.screen_lines() {
+                let y = padding_y + viewport_line as f32 * size_info.cell_height();
+                let y = y.min(bottom - 2.).max(padding_y);
+                rects.push(RenderRect::new(padding_x, y, grid_width, 2., SCROLL_REGION_COLOR, 1.));
+            }
+        }
+
+        // Cells which changed since the last frame.
+        for (line, (left, right)) in damage(&self.last_frame, cells) {
+            let rect = size_info.cell_rect(Point::new(line, Column(left)), right + 1 - left);
+            let (x, y, w, h) = (rect.x, rect.y, rect.width, rect.height);
+            rects.push(RenderRect::new(x, y, w, h, DAMAGE_COLOR, OVERLAY_ALPHA));
+        }
+        self.last_frame = cells.to_vec();
+
+        rects
+    }
+}
+
+/// Find the leftmost and rightmost changed column of every changed line.
+fn damage(old: &[RenderableCell], new: &[RenderableCell]) -> BTreeMap<usize, (usize, usize)> {
+    let mut old: Vec<_> = old.iter().collect();
+    let mut new: Vec<_> = new.iter().collect();
+    old.sort_by_key(|cell| (cell.point.line, cell.point.column));
+    new.sort_by_key(|cell| (cell.point.line, cell.point.column));
+
+    let mut damage = BTreeMap::new();
+    let mut add_damage = |point: Point<usize>| {
+        let span = damage.entry(point.line).or_insert((point.column.0, point.column.0));
+        span.0 = span.0.min(point.column.0);
+        span.1 = span.1.max(point.column.0);
+    };
+
+    // Walk both frames in grid order, cells only present in one of them are damaged too.
+    let (mut old, mut new) = (old.into_iter().peekable(), new.into_iter().peekable());
+    loop {
+        match (old.peek(), new.peek()) {
+            (Some(old_cell), Some(new_cell)) if old_cell.point == new_cell.point => {
+                if !same_content(old_cell, new_cell) {
+                    add_damage(new_cell.point);
+                }
+                old.next();
+                new.next();
+            },
+            (Some(old_cell), Some(new_cell)) => {
+                let old_key = (old_cell.point.line, old_cell.point.column);
+                if old_key < (new_cell.point.line, new_cell.point.column) {
+                    add_damage(old_cell.point);
+                    old.next();
+                } else {
+                    add_damage(new_cell.point);
+                    new.next();
+                }
+            },
+            (Some(old_cell), None) => {
+                add_damage(old_cell.point);
+                old.next();
+            },
+            (None, Some(new_cell)) => {
+                add_damage(new_cell.point);
+                new.next();
+            },
+            (None, None) => break,
+        }
+    }
+
+    damage
+}
+
+/// Check if two cells look identical.
+fn same_content(a: &RenderableCell, b: &RenderableCell) -> bool {
+    a.character == b.character
+        && a.zerowidth == b.zerowidth
+        && a.graphic == b.graphic
+        && a.fg == b.fg
+        && a.bg == b.bg
+        && (a.bg_alpha - b.bg_alpha).abs() < f32::EPSILON
+        && a.flags == b.flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::term::cell::Flags;
+
+    fn cell(line: usize, column: usize, character: char) -> RenderableCell {
+        RenderableCell {
+            character,
+            zerowidth: None,
+            graphic: None,
+            point: Point::new(line, Column(column)),
+            fg: Rgb::default(),
+            bg: Rgb::default(),
+            bg_alpha: 0.,
+            flags: Flags::empty(),
+        }
+    }
+
+    #[test]
+    fn damaged_cells() {
+        let old = [cell(0, 0, 'a'), cell(0, 1, 'b'), cell(1, 3, 'c'), cell(2, 0, 'd')];
+        let new = [cell(0, 0, 'a'), cell(0, 1, 'x'), cell(1, 5, 'c'), cell(2, 0, 'd')];
+
+        let damage: Vec<_> = damage(&old, &new).into_iter().collect();
+        assert_eq!(damage, vec![(0, (1, 1)), (1, (3, 5))]);
+    }
+}
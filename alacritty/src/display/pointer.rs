@@ -0,0 +1,142 @@
+//! Highlighting of the mouse cursor for screencasts and presentations.
+
+use std::time::Instant;
+
+use alacritty_terminal::index::Point;
+use alacritty_terminal::term::SizeInfo;
+
+use crate::config::Presentation;
+use crate::display;
+use crate::event::Mouse;
+use crate::renderer::rects::RenderRect;
+
+/// Outer radius of the ring around the mouse cursor, in multiples of the cell height.
+const RING_RADIUS: f32 = 1.5;
+
+/// Thickness of the ring around the mouse cursor, in multiples of the cell height.
+const RING_THICKNESS: f32 = 0.2;
+
+/// Opacity of the ring around the mouse cursor.
+const RING_ALPHA: f32 = 0.8;
+
+/// Opacity of the flash at the start of a click.
+const FLASH_ALPHA: f32 = 0.6;
+
+/// Ring around the mouse cursor and flash of the last clicked cell.
+#[derive(Default)]
+pub struct PointerHighlight {
+    /// Last clicked cell and the time of the click.
+    click: Option<(Point, Instant)>,
+}
+
+impl PointerHighlight {
+    /// Start flashing a clicked cell.
+    pub fn click(&mut self, point: Point) {
+        self.click = Some((point, Instant::now()));
+    }
+
+    /// Check whether or not the flash of the clicked cell has faded out.
+    pub fn completed(&mut self, config: &Presentation) -> bool {
+        match self.click {
+            Some((_, start)) => {
+                if start.elapsed() >= config.flash_duration() {
+                    self.click = None;
+                }
+                false
+            },
+            None => true,
+        }
+    }
+
+    /// Rects for the ring around the mouse cursor and the flashing cell.
+    pub fn rects(
+        &self,
+        config: &Presentation,
+        mouse: &Mouse,
+        size_info: &SizeInfo,
+        display_offset: usize,
+    ) -> Vec<RenderRect> {
+        let mut rects = Vec::new();
+
+        if let Some((point, start)) = self.click {
+            let duration = config.flash_duration().as_secs_f32();
+            let progress = start.elapsed().as_secs_f32() / duration;
+            let viewport_point = display::point_to_viewport(display_offset, point);
+            if let Some(viewport_point) = viewport_point.filter(|_| progress < 1.) {
+                let cell = size_info.cell_rect(viewport_point, 1);
+                let alpha = FLASH_ALPHA * (1. - progress);
+                rects.push(RenderRect::new(
+                    cell.x,
+                    cell.y,
+                    cell.width,
+                    cell.height,
+                    config.color,
+                    alpha,
+                ));
+            }
+        }
+
+        // Assemble the ring from one rect per pixel row, split in two where the row crosses the
+        // hole in the center.
+        let radius = (RING_RADIUS * size_info.cell_height()).round();
+        let inner_radius = radius - (RING_THICKNESS * size_info.cell_height()).round().max(2.);
+        let (x, y) = (mouse.x as f32, mouse.y as f32);
+        let color = config.color;
+        for row in 0..(2. * radius) as usize {
+            let dy = row as f32 + 0.5 - radius;
+            let outer = (radius * radius - dy * dy).sqrt();
+            let row_y = y - radius + row as f32;
+
+            if dy.abs() < inner_radius {
+                let inner = (inner_radius * inner_radius - dy * dy).sqrt();
+                rects.push(RenderRect::new(x - outer, row_y, outer - inner, 1., color, RING_ALPHA));
+                rects.push(RenderRect::new(x + inner, row_y, outer - inner, 1., color, RING_ALPHA));
+            } else {
+                rects.push(RenderRect::new(x - outer, row_y, 2. * outer, 1., color, RING_ALPHA));
+            }
+        }
+
+        rects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::index::{Column, Line};
+
+    #[test]
+    fn ring_around_cursor() {
+        let config = Presentation::default();
+        let size_info = SizeInfo::new(200., 200., 10., 20., 0., 0., false);
+        let mouse = Mouse { x: 100, y: 100, ..Mouse::default() };
+
+        let rects = PointerHighlight::default().rects(&config, &mouse, &size_info, 0);
+
+        // Rows inside the ring are split to leave the center uncovered.
+        assert_eq!(rects.len(), 2 * 52 + 8);
+        let covers = |x: f32, y: f32| {
+            rects.iter().any(|r| r.x <= x && x < r.x + r.width && r.y <= y && y < r.y + r.height)
+        };
+        assert!(!covers(100., 100.));
+        assert!(covers(72., 100.));
+        assert!(!covers(69., 100.));
+    }
+
+    #[test]
+    fn flash_clicked_cell() {
+        let config = Presentation::default();
+        let size_info = SizeInfo::new(200., 200., 10., 20., 0., 0., false);
+        let mouse = Mouse::default();
+
+        let mut highlight = PointerHighlight::default();
+        highlight.click(Point::new(Line(2), Column(3)));
+
+        let rects = highlight.rects(&config, &mouse, &size_info, 0);
+        let flash = rects[0];
+        assert_eq!((flash.x, flash.y, flash.width, flash.height), (30., 40., 10., 20.));
+        assert!(flash.alpha > 0. && flash.alpha <= FLASH_ALPHA);
+        assert!(!highlight.completed(&config));
+    }
+}
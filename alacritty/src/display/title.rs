@@ -0,0 +1,109 @@
+//! Window title formatting.
+
+use std::path::Path;
+
+/// Values available to the `window.title_format` placeholders.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TitleComponents<'a> {
+    /// Title set by the application, or the configured title.
+    pub title: &'a str,
+
+    /// Working directory of the foreground process.
+    pub cwd: Option<&'a Path>,
+
+    /// Name of the foreground process.
+    pub command: Option<&'a str>,
+
+    pub columns: usize,
+    pub lines: usize,
+}
+
+/// Check if a title format requires information about the foreground process.
+///
+/// Since there is no notification when the foreground process or its working directory change,
+/// titles using these placeholders have to be updated periodically.
+pub fn needs_process_info(format: &str) -> bool {
+    format.contains("{cwd}") || format.contains("{command}")
+}
+
+/// Replace all placeholders in the title format.
+///
+/// Unknown placeholders are kept verbatim, unavailable values are replaced by an empty string.
+pub fn format_title(format: &str, components: &TitleComponents<'_>) -> String {
+    let mut title = String::with_capacity(format.len());
+
+    let mut remaining = format;
+    while let Some(start) = remaining.find('{') {
+        title.push_str(&remaining[..start]);
+        remaining = &remaining[start..];
+
+        let end = match remaining.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+
+        match &remaining[1..end] {
+            "title" => title.push_str(components.title),
+            "cwd" => title.push_str(&components.cwd.map(abbreviate_home).unwrap_or_default()),
+            "command" => title.push_str(components.command.unwrap_or_default()),
+            "columns" => title.push_str(&components.columns.to_string()),
+            "lines" => title.push_str(&components.lines.to_string()),
+            _ => {
+                // Retry at the next brace, to allow for literal braces before a placeholder.
+                title.push('{');
+                remaining = &remaining[1..];
+                continue;
+            },
+        }
+
+        remaining = &remaining[end + 1..];
+    }
+    title.push_str(remaining);
+
+    title
+}
+
+/// Replace the home directory at the start of a path with `~`.
+fn abbreviate_home(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_owned)) {
+        Some(relative) if relative.as_os_str().is_empty() => String::from("~"),
+        Some(relative) => format!("~/{}", relative.display()),
+        None => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders() {
+        let cwd = Path::new("/tmp/alacritty");
+        let components = TitleComponents {
+            title: "vim",
+            cwd: Some(cwd),
+            command: Some("nvim"),
+            columns: 80,
+            lines: 24,
+        };
+
+        let title = format_title("{title} - {command} in {cwd} ({columns}x{lines})", &components);
+        assert_eq!(title, "vim - nvim in /tmp/alacritty (80x24)");
+    }
+
+    #[test]
+    fn unknown_and_unavailable_placeholders() {
+        let components = TitleComponents { title: "shell", ..TitleComponents::default() };
+
+        assert_eq!(format_title("{profile} {title}", &components), "{profile} shell");
+        assert_eq!(format_title("{{title}} {", &components), "{shell} {");
+        assert_eq!(format_title("[{command}] {title", &components), "[] {title");
+    }
+
+    #[test]
+    fn process_info_detection() {
+        assert!(needs_process_info("{title} - {cwd}"));
+        assert!(needs_process_info("{command}"));
+        assert!(!needs_process_info("{title} {columns}x{lines}"));
+    }
+}
@@ -0,0 +1,142 @@
+//! Gradual transitions between two values.
+
+use std::time::{Duration, Instant};
+
+use alacritty_terminal::term::color::Rgb;
+
+/// Values which can be interpolated linearly.
+pub trait Lerp: Copy {
+    /// Interpolate between `self` at a progress of `0.0` and `other` at `1.0`.
+    fn lerp(self, other: Self, progress: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: Self, progress: f32) -> Self {
+        self + (other - self) * progress
+    }
+}
+
+impl Lerp for Rgb {
+    fn lerp(self, other: Self, progress: f32) -> Self {
+        let channel = |from: u8, to: u8| {
+            f32::from(from).lerp(f32::from(to), progress).round().max(0.).min(255.) as u8
+        };
+
+        Rgb {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+        }
+    }
+}
+
+/// Value which changes to a new target over a fixed duration.
+pub struct Transition<T> {
+    /// Value at the start of the transition.
+    from: T,
+
+    /// Value at the end of the transition.
+    to: T,
+
+    /// Start of the running transition, if any.
+    start_time: Option<Instant>,
+
+    /// Duration of the running transition.
+    duration: Duration,
+}
+
+impl<T: Lerp> Transition<T> {
+    pub fn new(value: T) -> Self {
+        Self { from: value, to: value, start_time: None, duration: Duration::default() }
+    }
+
+    /// Start a transition from the current value to `target`.
+    ///
+    /// With a zero duration the target is applied immediately.
+    pub fn start(&mut self, target: T, duration: Duration) {
+        self.from = self.value();
+        self.to = target;
+        self.duration = duration;
+        self.start_time = if duration == Duration::default() { None } else { Some(Instant::now()) };
+    }
+
+    /// Change the value immediately, cancelling any running transition.
+    pub fn set(&mut self, value: T) {
+        self.from = value;
+        self.to = value;
+        self.start_time = None;
+    }
+
+    /// Value at the end of the transition.
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    /// Get the current value.
+    pub fn value(&self) -> T {
+        self.value_at(Instant::now())
+    }
+
+    /// Get the value at a particular instant.
+    pub fn value_at(&self, instant: Instant) -> T {
+        match self.start_time {
+            Some(start) if instant >= start => {
+                let elapsed = instant.duration_since(start).as_secs_f32();
+                let progress = (elapsed / self.duration.as_secs_f32()).min(1.);
+                self.from.lerp(self.to, progress)
+            },
+            Some(_) => self.from,
+            None => self.to,
+        }
+    }
+
+    /// Check whether or not the transition has completed.
+    ///
+    /// Just like the visual bell, this reports one more incomplete frame after the transition
+    /// has ended, so the final value is always drawn.
+    pub fn completed(&mut self) -> bool {
+        match self.start_time {
+            Some(start) => {
+                if Instant::now().duration_since(start) >= self.duration {
+                    self.start_time = None;
+                }
+                false
+            },
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate_over_duration() {
+        let mut transition = Transition::<f32>::new(0.5);
+        transition.start(1., Duration::from_millis(250));
+
+        let start = transition.start_time.unwrap();
+        assert_eq!(transition.value_at(start), 0.5);
+        assert_eq!(transition.value_at(start + Duration::from_millis(125)), 0.75);
+        assert_eq!(transition.value_at(start + Duration::from_millis(500)), 1.);
+        assert_eq!(transition.target(), 1.);
+    }
+
+    #[test]
+    fn zero_duration_is_immediate() {
+        let mut transition = Transition::new(Rgb { r: 0, g: 0, b: 0 });
+        transition.start(Rgb { r: 255, g: 128, b: 0 }, Duration::default());
+
+        assert!(transition.completed());
+        assert_eq!(transition.value(), Rgb { r: 255, g: 128, b: 0 });
+    }
+
+    #[test]
+    fn interpolate_colors() {
+        let from = Rgb { r: 0, g: 255, b: 100 };
+        let to = Rgb { r: 255, g: 0, b: 100 };
+
+        assert_eq!(from.lerp(to, 0.5), Rgb { r: 128, g: 128, b: 100 });
+    }
+}
@@ -17,12 +17,16 @@ pub struct AlacrittyWaylandTheme {
 }
 
 impl AlacrittyWaylandTheme {
-    pub fn new(colors: &Colors) -> Self {
+    /// Create a theme matching the terminal's primary colors.
+    ///
+    /// The `background` and `foreground` are used for the title bar, to blend the decorations
+    /// with the terminal content.
+    pub fn new(colors: &Colors, background: Rgb, foreground: Rgb) -> Self {
         let hovered_close_icon = colors.normal.red.into_rgba();
         let hovered_maximize_icon = colors.normal.green.into_rgba();
         let hovered_minimize_icon = colors.normal.yellow.into_rgba();
-        let foreground = colors.search_bar_foreground().into_rgba();
-        let background = colors.search_bar_background().into_rgba();
+        let foreground = foreground.into_rgba();
+        let background = background.into_rgba();
 
         let mut dim_foreground = foreground;
         dim_foreground.a = INACTIVE_OPACITY;
@@ -14,6 +14,8 @@ use {
     wayland_client::{Attached, EventQueue, Proxy},
     glutin::platform::unix::EventLoopWindowTargetExtUnix,
 
+    alacritty_terminal::term::color::Rgb,
+
     crate::config::color::Colors,
     crate::display::wayland_theme::AlacrittyWaylandTheme,
 };
@@ -22,6 +24,7 @@ use {
 #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
 use {
     std::io::Cursor,
+    std::os::raw::c_long,
 
     x11_dl::xlib::{Display as XDisplay, PropModeReplace, XErrorEvent, Xlib},
     glutin::window::Icon,
@@ -48,9 +51,10 @@ use objc::{msg_send, sel, sel_impl};
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 #[cfg(windows)]
 use winapi::shared::minwindef::WORD;
+#[cfg(windows)]
+use winapi::um::winuser::{MessageBeep, MB_OK};
 
-use alacritty_terminal::index::Point;
-use alacritty_terminal::term::SizeInfo;
+use alacritty_terminal::term::PixelRect;
 
 use crate::config::window::{Decorations, WindowConfig};
 use crate::config::Config;
@@ -68,6 +72,12 @@ const MAX_X11_DPR: f64 = 10.;
 #[cfg(windows)]
 const IDI_ICON: WORD = 0x101;
 
+#[cfg(target_os = "macos")]
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    fn NSBeep();
+}
+
 /// Window errors.
 #[derive(Debug)]
 pub enum Error {
@@ -145,6 +155,19 @@ fn create_gl_window<E>(
     Ok(windowed_context)
 }
 
+/// Steps in which the window manager resizes the window.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ResizeIncrements {
+    /// Size of the window without any cells.
+    pub base: PhysicalSize<u32>,
+
+    /// Size of a single cell.
+    pub cell: PhysicalSize<u32>,
+
+    /// Scale factor both sizes were computed for.
+    pub scale_factor: f64,
+}
+
 /// A window which can be used for displaying the terminal.
 ///
 /// Wraps the underlying windowing library to provide a stable API in Alacritty.
@@ -163,6 +186,14 @@ pub struct Window {
     windowed_context: WindowedContext<PossiblyCurrent>,
     current_mouse_cursor: CursorIcon,
     mouse_visible: bool,
+    title: String,
+
+    #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+    icon_name: String,
+
+    /// Xlib functions, loaded once for X11 requests which are not exposed by glutin.
+    #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+    xlib: Option<Xlib>,
 }
 
 impl Window {
@@ -173,12 +204,30 @@ impl Window {
         event_loop: &EventLoop<E>,
         config: &Config,
         size: Option<PhysicalSize<u32>>,
+        #[cfg_attr(windows, allow(unused_variables))] resize_increments: Option<ResizeIncrements>,
         #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
         wayland_event_queue: Option<&EventQueue>,
     ) -> Result<Window> {
         let window_config = &config.ui_config.window;
         let window_builder = Window::get_platform_window(&window_config.title, &window_config);
 
+        // Resize increments are only supported by X11 and macOS.
+        #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+        let window_builder = match resize_increments {
+            Some(increments) => window_builder
+                .with_base_size(increments.base)
+                .with_resize_increments(increments.cell),
+            None => window_builder,
+        };
+        #[cfg(target_os = "macos")]
+        let window_builder = match resize_increments {
+            Some(increments) => {
+                let cell = increments.cell.to_logical(increments.scale_factor);
+                window_builder.with_resize_increments(cell)
+            },
+            None => window_builder,
+        };
+
         // Check if we're running Wayland to disable vsync.
         #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
         let is_wayland = event_loop.is_wayland();
@@ -204,12 +253,22 @@ impl Window {
             if let Some(parent_window_id) = window_config.embed {
                 x_embed_window(windowed_context.window(), parent_window_id);
             }
+
+            // Open the window on the selected workspace, before it is mapped.
+            if let Some(workspace) = window_config.workspace {
+                x_set_workspace(windowed_context.window(), workspace);
+            }
         }
 
         #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
         let wayland_surface = if is_wayland {
             // Apply client side decorations theme.
-            let theme = AlacrittyWaylandTheme::new(&config.ui_config.colors);
+            let colors = &config.ui_config.colors;
+            let theme = AlacrittyWaylandTheme::new(
+                colors,
+                colors.primary.background,
+                colors.primary.foreground,
+            );
             windowed_context.window().set_wayland_theme(theme);
 
             // Attach surface to Alacritty's internal wayland queue to handle frame callbacks.
@@ -232,6 +291,11 @@ impl Window {
         Ok(Self {
             current_mouse_cursor,
             mouse_visible: true,
+            title: window_config.title.clone(),
+            #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+            icon_name: window_config.title.clone(),
+            #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+            xlib: if is_wayland { None } else { Xlib::open().ok() },
             windowed_context,
             #[cfg(not(any(target_os = "macos", windows)))]
             should_draw: Arc::new(AtomicBool::new(true)),
@@ -256,8 +320,11 @@ impl Window {
 
     /// Set the window title.
     #[inline]
-    pub fn set_title(&self, title: &str) {
-        self.window().set_title(title);
+    pub fn set_title(&mut self, title: &str) {
+        if title != self.title {
+            self.title = title.to_owned();
+            self.window().set_title(title);
+        }
     }
 
     #[inline]
@@ -360,6 +427,83 @@ impl Window {
         self.window().request_user_attention(attention);
     }
 
+    /// Play the system alert sound.
+    #[cfg(target_os = "macos")]
+    pub fn beep(&self) {
+        unsafe { NSBeep() };
+    }
+
+    /// Play the system alert sound.
+    #[cfg(windows)]
+    pub fn beep(&self) {
+        unsafe { MessageBeep(MB_OK) };
+    }
+
+    /// Play the system alert sound.
+    ///
+    /// This is only supported on X11, since Wayland has no protocol for it.
+    #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+    pub fn beep(&self) {
+        let (xlib, xlib_display, xlib_window) =
+            match (&self.xlib, self.window().xlib_display(), self.window().xlib_window()) {
+                (Some(xlib), Some(display), Some(window)) => (xlib, display, window),
+                _ => return,
+            };
+
+        unsafe {
+            (xlib.XkbBell)(xlib_display as _, xlib_window as _, 0, 0);
+            (xlib.XFlush)(xlib_display as _);
+        }
+    }
+
+    /// Play the system alert sound.
+    #[cfg(all(not(feature = "x11"), not(any(target_os = "macos", windows))))]
+    pub fn beep(&self) {}
+
+    /// Set the name shown for the minimized window.
+    ///
+    /// This is only supported on X11, other platforms always show the window title.
+    #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+    pub fn set_icon_name(&mut self, icon_name: &str) {
+        if icon_name == self.icon_name {
+            return;
+        }
+        self.icon_name = icon_name.to_owned();
+
+        let (xlib, xlib_display, xlib_window) =
+            match (&self.xlib, self.window().xlib_display(), self.window().xlib_window()) {
+                (Some(xlib), Some(display), Some(window)) => (xlib, display, window),
+                _ => return,
+            };
+
+        unsafe {
+            let property = b"_NET_WM_ICON_NAME\0".as_ptr() as *const _;
+            let property = (xlib.XInternAtom)(xlib_display as _, property, 0);
+            let utf8_string = b"UTF8_STRING\0".as_ptr() as *const _;
+            let utf8_string = (xlib.XInternAtom)(xlib_display as _, utf8_string, 0);
+            (xlib.XChangeProperty)(
+                xlib_display as _,
+                xlib_window as _,
+                property,
+                utf8_string,
+                8,
+                PropModeReplace,
+                icon_name.as_ptr(),
+                icon_name.len() as i32,
+            );
+            (xlib.XFlush)(xlib_display as _);
+        }
+    }
+
+    /// Set the name shown for the minimized window.
+    #[cfg(any(not(feature = "x11"), target_os = "macos", windows))]
+    pub fn set_icon_name(&mut self, _icon_name: &str) {}
+
+    /// Name of the monitor the window is on.
+    pub fn current_monitor_name(&self) -> Option<String> {
+        self.window().current_monitor().and_then(|monitor| monitor.name())
+    }
+
     pub fn set_outer_position(&self, pos: PhysicalPosition<i32>) {
         self.window().set_outer_position(pos);
     }
@@ -416,14 +560,14 @@ impl Window {
     }
 
     #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
-    pub fn set_wayland_theme(&mut self, colors: &Colors) {
-        self.window().set_wayland_theme(AlacrittyWaylandTheme::new(colors));
+    pub fn set_wayland_theme(&mut self, colors: &Colors, background: Rgb, foreground: Rgb) {
+        self.window().set_wayland_theme(AlacrittyWaylandTheme::new(colors, background, foreground));
     }
 
-    /// Adjust the IME editor position according to the new location of the cursor.
-    pub fn update_ime_position(&mut self, point: Point, size: &SizeInfo) {
-        let nspot_x = f64::from(size.padding_x() + point.column.0 as f32 * size.cell_width());
-        let nspot_y = f64::from(size.padding_y() + (point.line.0 + 1) as f32 * size.cell_height());
+    /// Adjust the IME editor position to appear below the cursor's rectangle.
+    pub fn update_ime_position(&mut self, cursor: PixelRect) {
+        let nspot_x = f64::from(cursor.x);
+        let nspot_y = f64::from(cursor.y + cursor.height);
 
         self.window().set_ime_position(PhysicalPosition::new(nspot_x, nspot_y));
     }
@@ -491,6 +635,38 @@ fn x_embed_window(window: &GlutinWindow, parent_id: std::os::raw::c_ulong) {
     }
 }
 
+/// Request opening the window on a workspace, using the EWMH `_NET_WM_DESKTOP` property.
+#[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+fn x_set_workspace(window: &GlutinWindow, workspace: u32) {
+    let (xlib_display, xlib_window) = match (window.xlib_display(), window.xlib_window()) {
+        (Some(display), Some(window)) => (display, window),
+        _ => return,
+    };
+
+    let xlib = Xlib::open().expect("get xlib");
+
+    unsafe {
+        let property = b"_NET_WM_DESKTOP\0".as_ptr() as *const _;
+        let property = (xlib.XInternAtom)(xlib_display as _, property, 0);
+        let cardinal = b"CARDINAL\0".as_ptr() as *const _;
+        let cardinal = (xlib.XInternAtom)(xlib_display as _, cardinal, 0);
+
+        // Properties with a format of 32 are stored as C longs.
+        let data = [workspace as c_long];
+        (xlib.XChangeProperty)(
+            xlib_display as _,
+            xlib_window as _,
+            property,
+            cardinal,
+            32,
+            PropModeReplace,
+            data.as_ptr() as *const _,
+            1,
+        );
+        (xlib.XFlush)(xlib_display as _);
+    }
+}
+
 #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
 unsafe extern "C" fn xembed_error_handler(_: *mut XDisplay, _: *mut XErrorEvent) -> i32 {
     log::error!("Could not embed into specified window.");
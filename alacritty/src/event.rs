@@ -2,66 +2,99 @@
 
 use std::borrow::Cow;
 use std::cmp::{max, min};
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::env;
 use std::f32;
 use std::fmt::Debug;
-#[cfg(not(any(target_os = "macos", windows)))]
-use std::fs;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::mem;
 use std::path::{Path, PathBuf};
 #[cfg(not(any(target_os = "macos", windows)))]
 use std::sync::atomic::Ordering;
+#[cfg(unix)]
+use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use glutin::dpi::PhysicalSize;
-use glutin::event::{ElementState, Event as GlutinEvent, ModifiersState, MouseButton, WindowEvent};
+use glutin::event::{
+    ElementState, Event as GlutinEvent, ModifiersState, MouseButton, VirtualKeyCode, WindowEvent,
+};
 use glutin::event_loop::{ControlFlow, EventLoop, EventLoopProxy, EventLoopWindowTarget};
 use glutin::platform::run_return::EventLoopExtRunReturn;
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 use glutin::platform::unix::EventLoopWindowTargetExtUnix;
-use log::info;
+use log::{debug, error, info};
+#[cfg(unix)]
+use serde::{Deserialize, Serialize};
 use serde_json as json;
 
 use crossfont::{self, Size};
 
 use alacritty_terminal::config::LOG_TARGET_CONFIG;
 use alacritty_terminal::event::{Event as TerminalEvent, EventListener, Notify, OnResize};
+use alacritty_terminal::event_loop::WriteQueue;
 use alacritty_terminal::grid::{Dimensions, Scroll};
 use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point, Side};
-use alacritty_terminal::selection::{Selection, SelectionType};
+use alacritty_terminal::selection::{Selection, SelectionRange, SelectionType};
 use alacritty_terminal::sync::FairMutex;
 use alacritty_terminal::term::search::{Match, RegexSearch};
 use alacritty_terminal::term::{ClipboardType, SizeInfo, Term, TermMode};
-#[cfg(not(windows))]
+#[cfg(unix)]
 use alacritty_terminal::tty;
+use alacritty_terminal::vi_mode::TextObjectScope;
 
 use crate::cli::Options as CLIOptions;
-use crate::clipboard::Clipboard;
+use crate::clipboard::{self, Clipboard, Register};
+use crate::config::bell::BellSound;
+use crate::config::keyboard::FlowControl;
 use crate::config::ui_config::{HintAction, HintInternalAction};
-use crate::config::{self, Config};
+use crate::config::{self, Config, SpawnDirectory, SpawnOptions};
+#[cfg(unix)]
+use crate::daemon::foreground_process_path;
 use crate::daemon::start_daemon;
+use crate::display::export::{self, Region};
 use crate::display::hint::HintMatch;
+use crate::display::title;
 use crate::display::window::Window;
-use crate::display::{self, Display, DisplayUpdate};
+use crate::display::{
+    self, Display, DisplayUpdate, RAPID_BLINKS_PER_BLINK, RESIZE_OVERLAY_DURATION,
+};
 use crate::input::{self, ActionContext as _, FONT_SIZE_STEP};
-#[cfg(target_os = "macos")]
-use crate::macos;
-use crate::message_bar::{Message, MessageBuffer};
+#[cfg(unix)]
+use crate::ipc::GlyphStats;
+use crate::message_bar::{Message, MessageBuffer, MessageType};
 use crate::scheduler::{Scheduler, TimerId};
+use crate::search::{MatchCount, SearchResult, SearchUpdate, SearchWorker};
+use crate::startup::StartupTimer;
+use crate::terminfo;
+use crate::vi_marks::{ViMark, ViMarks};
 
-/// Duration after the last user input until an unlimited search is performed.
-pub const TYPING_SEARCH_DELAY: Duration = Duration::from_millis(500);
-
-/// Maximum number of lines for the blocking search while still typing the search regex.
-const MAX_SEARCH_WHILE_TYPING: Option<usize> = Some(1000);
+/// Maximum number of lines searched on the event loop before continuing on the search thread.
+const MAX_SEARCH_LINES: usize = 1000;
 
 /// Maximum number of search terms stored in the history.
 const MAX_SEARCH_HISTORY_SIZE: usize = 255;
 
+/// Number of bytes in one MiB, the unit of the clipboard size limit.
+const BYTES_PER_MIB: usize = 1024 * 1024;
+
+/// Delay before retrying a paste while the PTY is not reading its input.
+const WRITE_QUEUE_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Message bar target of the warning shown instead of closing the window.
+const CONFIRM_CLOSE_TARGET: &str = "confirm_close";
+
+/// Message bar target of the notice listing characters missing from all fonts.
+const MISSING_GLYPHS_TARGET: &str = "missing_glyphs";
+
+/// Maximum number of characters listed by the missing glyphs notice.
+const MAX_MISSING_GLYPHS_LISTED: usize = 8;
+
+/// Interval for updating titles which depend on the foreground process.
+const TITLE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Events dispatched through the UI event loop.
 #[derive(Debug, Clone)]
 pub enum Event {
@@ -71,7 +104,34 @@ pub enum Event {
     ConfigReload(PathBuf),
     Message(Message),
     BlinkCursor,
-    SearchNext,
+    BlinkText,
+    UpdateTitle,
+    SearchResult(SearchResult),
+    Paste(Vec<u8>),
+    PasteSlowly(Vec<u8>),
+    ResizeGrid(config::window::Dimensions),
+    #[cfg(unix)]
+    FontSize(FontSizeChange),
+    #[cfg(unix)]
+    GlyphStats(Sender<GlyphStats>),
+    #[cfg(unix)]
+    Trim,
+    #[cfg(unix)]
+    Screenshot(bool),
+}
+
+/// Font size change requested over IPC.
+#[cfg(unix)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum FontSizeChange {
+    /// Increase or decrease the font size by the specified number of points.
+    Delta(f32),
+
+    /// Set the font size in points.
+    Set(f32),
+
+    /// Reset to the configured font size.
+    Reset,
 }
 
 impl From<Event> for GlutinEvent<'_, Event> {
@@ -112,7 +172,32 @@ pub struct SearchState {
     history_index: Option<usize>,
 
     /// Compiled search automatons.
-    dfas: Option<RegexSearch>,
+    dfas: Option<Arc<RegexSearch>>,
+
+    /// Whether the focused match was found after wrapping around the buffer boundaries.
+    wrapped: bool,
+
+    /// Position of the focused match among all matches.
+    match_count: Option<MatchCount>,
+
+    /// Search thread for searches which would block the event loop.
+    worker: Option<SearchWorker>,
+
+    /// Search still running on the search thread.
+    pending: Option<PendingSearch>,
+}
+
+/// Action applied once the search thread found the next match.
+#[derive(Debug, Clone)]
+enum PendingSearch {
+    /// Focus the match.
+    Focus,
+
+    /// Focus the match and move the search origin from the previously focused match to it.
+    Advance { direction: Direction, old_match: Option<Match> },
+
+    /// Move the vi mode cursor to the specified side of the match.
+    ViMotion(Side),
 }
 
 impl SearchState {
@@ -133,7 +218,31 @@ impl SearchState {
 
     /// Active search dfas.
     pub fn dfas(&self) -> Option<&RegexSearch> {
-        self.dfas.as_ref()
+        self.dfas.as_deref()
+    }
+
+    /// Whether the last search wrapped around the buffer boundaries.
+    pub fn wrapped(&self) -> bool {
+        self.wrapped
+    }
+
+    /// Position of the focused match among all matches.
+    pub fn match_count(&self) -> Option<MatchCount> {
+        self.match_count
+    }
+
+    /// Cancel all searches running on the search thread.
+    fn cancel_pending(&mut self) {
+        if let Some(worker) = &self.worker {
+            worker.cancel();
+        }
+        self.pending = None;
+    }
+
+    /// Drop the focused match if it was removed from the terminal's history.
+    fn clamp_to_grid<T>(&mut self, terminal: &Term<T>) {
+        let topmost_line = terminal.topmost_line();
+        self.focused_match = self.focused_match.take().filter(|m| m.start().line >= topmost_line);
     }
 
     /// Search regex text if a search is active.
@@ -152,17 +261,24 @@ impl Default for SearchState {
             history: Default::default(),
             origin: Default::default(),
             dfas: Default::default(),
+            wrapped: Default::default(),
+            match_count: Default::default(),
+            worker: Default::default(),
+            pending: Default::default(),
         }
     }
 }
 
 pub struct ActionContext<'a, N, T> {
     pub notifier: &'a mut N,
+    pub write_queue: &'a WriteQueue,
     pub terminal: &'a mut Term<T>,
     pub clipboard: &'a mut Clipboard,
     pub mouse: &'a mut Mouse,
     pub received_count: &'a mut usize,
     pub suppress_chars: &'a mut bool,
+    pub pressed_key: &'a mut Option<VirtualKeyCode>,
+    pub bound_keys: &'a mut HashSet<VirtualKeyCode>,
     pub modifiers: &'a mut ModifiersState,
     pub display: &'a mut Display,
     pub message_buffer: &'a mut MessageBuffer,
@@ -171,6 +287,8 @@ pub struct ActionContext<'a, N, T> {
     pub event_loop: &'a EventLoopWindowTarget<Event>,
     pub scheduler: &'a mut Scheduler,
     pub search_state: &'a mut SearchState,
+    pub vi_input: &'a mut ViInput,
+    pub vi_marks: &'a mut ViMarks,
     cli_options: &'a CLIOptions,
     font_size: &'a mut Size,
     dirty: &'a mut bool,
@@ -182,6 +300,11 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.notifier.notify(val);
     }
 
+    #[inline]
+    fn write_queue_full(&self) -> bool {
+        self.write_queue.available() == 0
+    }
+
     /// Request a redraw.
     #[inline]
     fn mark_dirty(&mut self) {
@@ -193,6 +316,10 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.display.size_info
     }
 
+    fn grid_size_info(&self) -> SizeInfo {
+        self.display.grid_size_info
+    }
+
     fn scroll(&mut self, scroll: Scroll) {
         let old_offset = self.terminal.grid().display_offset() as i32;
 
@@ -213,7 +340,7 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             || self.mouse.right_button_state == ElementState::Pressed
         {
             let display_offset = self.terminal.grid().display_offset();
-            let point = self.mouse.point(&self.size_info(), display_offset);
+            let point = self.mouse.point(&self.grid_size_info(), display_offset);
             self.update_selection(point, self.mouse.cell_side);
         }
         self.copy_selection(ClipboardType::Selection);
@@ -223,15 +350,66 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     // Copy text selection.
     fn copy_selection(&mut self, ty: ClipboardType) {
+        let range = match self.terminal.selection.as_ref().and_then(|s| s.to_range(self.terminal)) {
+            Some(range) => range,
+            None => return,
+        };
+
+        // Check the limit before building the text, to avoid allocating huge selections.
+        if !self.clipboard_size_allowed(self.selection_size(&range)) {
+            return;
+        }
+
         let text = match self.terminal.selection_to_string().filter(|s| !s.is_empty()) {
             Some(text) => text,
             None => return,
         };
 
+        let html = if clipboard::HTML_SUPPORT {
+            Some(export::html(self.terminal, &range, &self.display.colors.target()))
+        } else {
+            None
+        };
+
         if ty == ClipboardType::Selection && self.config.selection.save_to_clipboard {
-            self.clipboard.store(ClipboardType::Clipboard, text.clone());
+            self.clipboard.store_html(ClipboardType::Clipboard, text.clone(), html.clone());
+        }
+        self.clipboard.store_html(ty, text, html);
+    }
+
+    fn append_selection_to_clipboard(&mut self) {
+        let range = match self.terminal.selection.as_ref().and_then(|s| s.to_range(self.terminal)) {
+            Some(range) => range,
+            None => return,
+        };
+
+        if !self.clipboard_size_allowed(self.selection_size(&range)) {
+            return;
+        }
+
+        let selection = match self.terminal.selection_to_string().filter(|s| !s.is_empty()) {
+            Some(selection) => selection,
+            None => return,
+        };
+
+        let content = self.clipboard.load(ClipboardType::Clipboard);
+        let text = clipboard::append_line(content, &selection);
+
+        if self.clipboard_size_allowed(text.len()) {
+            self.clipboard.store(ClipboardType::Clipboard, text);
+        }
+    }
+
+    fn copy_to_register(&mut self, register: Register) {
+        let text = match register {
+            Register::Clipboard => return self.copy_selection(ClipboardType::Clipboard),
+            Register::Selection => return self.copy_selection(ClipboardType::Selection),
+            Register::Named(_) => self.terminal.selection_to_string(),
+        };
+
+        if let Some(text) = text.filter(|text| !text.is_empty()) {
+            self.clipboard.store_register(register, text);
         }
-        self.clipboard.store(ty, text);
     }
 
     fn selection_is_empty(&self) -> bool {
@@ -239,7 +417,7 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     }
 
     fn clear_selection(&mut self) {
-        self.terminal.selection = None;
+        self.terminal.clear_selection();
         *self.dirty = true;
     }
 
@@ -266,7 +444,7 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     }
 
     fn start_selection(&mut self, ty: SelectionType, point: Point, side: Side) {
-        self.terminal.selection = Some(Selection::new(ty, point, side));
+        self.terminal.start_selection(ty, point, side);
         *self.dirty = true;
 
         self.copy_selection(ClipboardType::Selection);
@@ -308,11 +486,26 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         &mut self.received_count
     }
 
+    #[inline]
+    fn vi_input(&mut self) -> &mut ViInput {
+        self.vi_input
+    }
+
     #[inline]
     fn suppress_chars(&mut self) -> &mut bool {
         &mut self.suppress_chars
     }
 
+    #[inline]
+    fn pressed_key(&mut self) -> &mut Option<VirtualKeyCode> {
+        &mut self.pressed_key
+    }
+
+    #[inline]
+    fn bound_keys(&mut self) -> &mut HashSet<VirtualKeyCode> {
+        &mut self.bound_keys
+    }
+
     #[inline]
     fn modifiers(&mut self) -> &mut ModifiersState {
         &mut self.modifiers
@@ -338,41 +531,36 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.terminal
     }
 
-    fn spawn_new_instance(&mut self) {
+    fn spawn_new_instance(&mut self, options: &SpawnOptions) {
         let mut env_args = env::args();
         let alacritty = env_args.next().unwrap();
 
-        #[cfg(unix)]
-        let mut args = {
-            // Use working directory of controlling process, or fallback to initial shell.
-            let mut pid = unsafe { libc::tcgetpgrp(tty::master_fd()) };
-            if pid < 0 {
-                pid = tty::child_pid();
-            }
+        // Add the working directory as parameter, falling back to the one of the initial shell.
+        let working_directory = self.spawn_working_directory(options.working_directory);
+        let mut args: Vec<PathBuf> = working_directory
+            .map(|path| vec!["--working-directory".into(), path])
+            .unwrap_or_default();
 
-            #[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
-            let link_path = format!("/proc/{}/cwd", pid);
-            #[cfg(target_os = "freebsd")]
-            let link_path = format!("/compat/linux/proc/{}/cwd", pid);
-            #[cfg(not(target_os = "macos"))]
-            let cwd = fs::read_link(link_path);
-            #[cfg(target_os = "macos")]
-            let cwd = macos::proc::cwd(pid);
-
-            // Add the current working directory as parameter.
-            cwd.map(|path| vec!["--working-directory".into(), path]).unwrap_or_default()
-        };
+        let working_directory_set = !args.is_empty();
 
-        #[cfg(not(unix))]
-        let mut args: Vec<PathBuf> = Vec::new();
+        // Use the configuration file of the profile.
+        if let Some(profile) = &options.profile {
+            args.push("--config-file".into());
+            args.push(profile.clone());
+        }
 
-        let working_directory_set = !args.is_empty();
+        // Use the arguments of the binding, or reuse the ones passed to Alacritty.
+        let mut instance_args: Box<dyn Iterator<Item = String>> = match &options.args {
+            Some(args) => Box::new(args.clone().into_iter()),
+            None => Box::new(env_args),
+        };
 
-        // Reuse the arguments passed to Alacritty for the new instance.
-        while let Some(arg) = env_args.next() {
-            // Drop working directory from existing parameters.
-            if working_directory_set && arg == "--working-directory" {
-                let _ = env_args.next();
+        while let Some(arg) = instance_args.next() {
+            // Drop working directory and configuration file from existing parameters.
+            if (working_directory_set && arg == "--working-directory")
+                || (options.profile.is_some() && arg == "--config-file")
+            {
+                let _ = instance_args.next();
                 continue;
             }
 
@@ -382,6 +570,30 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         start_daemon(&alacritty, &args);
     }
 
+    /// Close the window, asking for confirmation first while a foreground job is running.
+    fn quit(&mut self) {
+        #[cfg(unix)]
+        let job_running = tty::foreground_process_group().is_some();
+        #[cfg(not(unix))]
+        let job_running = false;
+
+        // Closing the window again while the warning is visible skips the confirmation.
+        if self.config.ui_config.window.confirm_close
+            && job_running
+            && !self.message_buffer.contains_target(CONFIRM_CLOSE_TARGET)
+        {
+            let text = "A process is still running, close the window again to quit";
+            let mut message = Message::new(text.into(), MessageType::Warning);
+            message.set_target(CONFIRM_CLOSE_TARGET.into());
+            self.message_buffer.push(message);
+            self.display_update_pending.dirty = true;
+            *self.dirty = true;
+            return;
+        }
+
+        self.terminal.exit();
+    }
+
     fn change_font_size(&mut self, delta: f32) {
         *self.font_size = max(*self.font_size + delta, Size::new(FONT_SIZE_STEP));
         let font = self.config.ui_config.font.clone().with_size(*self.font_size);
@@ -390,8 +602,46 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     }
 
     fn reset_font_size(&mut self) {
-        *self.font_size = self.config.ui_config.font.size();
-        self.display_update_pending.set_font(self.config.ui_config.font.clone());
+        *self.font_size = self.config.ui_config.font_size(self.display.monitor.as_deref());
+        let font = self.config.ui_config.font.clone().with_size(*self.font_size);
+        self.display_update_pending.set_font(font);
+        *self.dirty = true;
+    }
+
+    fn set_opacity(&mut self, opacity: f32) {
+        self.display.transition_opacity(self.config, opacity);
+
+        // Disable shadows for transparent windows on macOS.
+        #[cfg(target_os = "macos")]
+        self.window().set_has_shadow(opacity >= 1.0);
+
+        *self.dirty = true;
+    }
+
+    fn toggle_opacity(&mut self) {
+        let opacity = if self.display.opacity.target() < 1. {
+            1.
+        } else {
+            self.config.ui_config.background_opacity()
+        };
+        self.set_opacity(opacity);
+    }
+
+    fn toggle_theme(&mut self) {
+        let ui_config = &mut self.config.ui_config;
+        let alternate_colors = match &mut ui_config.alternate_colors {
+            Some(alternate_colors) => alternate_colors,
+            None => return,
+        };
+        mem::swap(&mut ui_config.colors, alternate_colors);
+
+        self.display.transition_colors(self.config);
+        *self.dirty = true;
+    }
+
+    fn toggle_zoom(&mut self) {
+        let font = self.config.ui_config.font.clone().with_size(*self.font_size);
+        self.display.toggle_zoom(self.config, &font);
         *self.dirty = true;
     }
 
@@ -441,9 +691,10 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             return;
         }
 
-        // Force unlimited search if the previous one was interrupted.
-        if self.scheduler.scheduled(TimerId::DelayedSearch) {
-            self.goto_match(None);
+        // Move the vi mode cursor once the search thread found the focused match.
+        match self.search_state.pending {
+            Some(_) => self.search_state.pending = Some(PendingSearch::ViMotion(Side::Left)),
+            None => self.search_state.cancel_pending(),
         }
 
         self.exit_search();
@@ -464,6 +715,7 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         }
 
         self.search_state.dfas = None;
+        self.search_state.cancel_pending();
 
         self.exit_search();
     }
@@ -536,6 +788,8 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     #[inline]
     fn advance_search_origin(&mut self, direction: Direction) {
+        let old_match = self.search_state.focused_match.clone();
+
         // Use focused match as new search origin if available.
         if let Some(focused_match) = &self.search_state.focused_match {
             let new_origin = match direction {
@@ -551,40 +805,24 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
         // Search for the next match using the supplied direction.
         let search_direction = mem::replace(&mut self.search_state.direction, direction);
-        self.goto_match(None);
+        self.goto_match();
         self.search_state.direction = search_direction;
 
-        // If we found a match, we set the search origin right in front of it to make sure that
-        // after modifications to the regex the search is started without moving the focused match
-        // around.
-        let focused_match = match &self.search_state.focused_match {
-            Some(focused_match) => focused_match,
-            None => return,
-        };
-
-        // Set new origin to the left/right of the match, depending on search direction.
-        let new_origin = match self.search_state.direction {
-            Direction::Right => *focused_match.start(),
-            Direction::Left => *focused_match.end(),
-        };
-
-        // Store the search origin with display offset by checking how far we need to scroll to it.
-        let old_display_offset = self.terminal.grid().display_offset() as i32;
-        self.terminal.scroll_to_point(new_origin);
-        let new_display_offset = self.terminal.grid().display_offset() as i32;
-        self.search_state.display_offset_delta = new_display_offset - old_display_offset;
+        // Finish advancing once the search thread found the next match.
+        if self.search_state.pending.is_some() {
+            self.search_state.pending = Some(PendingSearch::Advance { direction, old_match });
+            return;
+        }
 
-        // Store origin and scroll back to the match.
-        self.terminal.scroll_display(Scroll::Delta(-self.search_state.display_offset_delta));
-        self.search_state.origin = new_origin;
+        self.advance_search_origin_to_match(direction, old_match);
     }
 
     /// Find the next search match.
+    ///
+    /// Returns `None` if the search is continued on the search thread, which moves the vi mode
+    /// cursor to the match once it is found.
     fn search_next(&mut self, origin: Point, direction: Direction, side: Side) -> Option<Match> {
-        self.search_state
-            .dfas
-            .as_ref()
-            .and_then(|dfas| self.terminal.search_next(dfas, origin, direction, side, None))
+        self.search_nonblocking(origin, direction, side, PendingSearch::ViMotion(side))
     }
 
     #[inline]
@@ -605,12 +843,15 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     /// All features are re-enabled again automatically.
     #[inline]
     fn on_typing_start(&mut self) {
-        // Disable cursor blinking.
-        let blink_interval = self.config.cursor.blink_interval();
-        if let Some(timer) = self.scheduler.get_mut(TimerId::BlinkCursor) {
-            timer.deadline = Instant::now() + Duration::from_millis(blink_interval);
+        // Disable cursor blinking and restart its timeout.
+        let blink_interval = Duration::from_millis(self.config.cursor.blink_interval());
+        if self.scheduler.reschedule(TimerId::BlinkCursor, blink_interval) {
+            self.display.cursor_blink_start = Some(Instant::now());
             self.display.cursor_hidden = false;
             *self.dirty = true;
+        } else if self.display.cursor_blink_start.is_some() {
+            // Resume blinking after the timeout stopped it.
+            self.update_cursor_blinking();
         }
 
         // Hide mouse cursor.
@@ -676,34 +917,132 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             for c in text.chars() {
                 self.search_input(c);
             }
-        } else if self.terminal().mode().contains(TermMode::BRACKETED_PASTE) {
-            self.write_to_pty(&b"\x1b[200~"[..]);
-            self.write_to_pty(text.replace("\x1b", "").into_bytes());
-            self.write_to_pty(&b"\x1b[201~"[..]);
         } else {
-            // In non-bracketed (ie: normal) mode, terminal applications cannot distinguish
-            // pasted data from keystrokes.
-            // In theory, we should construct the keystrokes needed to produce the data we are
-            // pasting... since that's neither practical nor sensible (and probably an impossible
-            // task to solve in a general way), we'll just replace line breaks (windows and unix
-            // style) with a single carriage return (\r, which is what the Enter key produces).
-            self.write_to_pty(text.replace("\r\n", "\r").replace("\n", "\r").into_bytes());
+            let bytes = self.paste_bytes(text);
+            self.queue_paste(bytes);
+        }
+    }
+
+    /// Stop or resume reading from the PTY.
+    fn set_output_paused(&mut self, paused: bool) {
+        self.notifier.set_output_paused(paused);
+        self.display.output_paused = paused;
+        *self.dirty = true;
+    }
+
+    /// Paste a text into the terminal in small chunks.
+    ///
+    /// This gives slow receivers like serial consoles time to process the pasted text.
+    fn paste_slowly(&mut self, text: &str) {
+        if self.search_active() {
+            self.paste(text);
+            return;
+        }
+
+        // Replace any paste which is still in progress.
+        self.scheduler.unschedule(TimerId::PasteSlowly);
+
+        let event = Event::PasteSlowly(self.paste_bytes(text));
+        self.scheduler.schedule(event.into(), Duration::default(), false, TimerId::PasteSlowly);
+    }
+
+    /// Save the visible screen as SVG image.
+    fn export_svg(&mut self) {
+        let font = self.config.ui_config.font.clone().with_size(*self.font_size);
+        let svg = self.display.svg(self.terminal, self.config, self.search_state, &font);
+
+        let path = export::export_path("svg");
+        match fs::write(&path, svg) {
+            Ok(()) => info!("Saved screen to {:?}", path),
+            Err(err) => error!("Unable to save screen to {:?}: {}", path, err),
         }
     }
 
+    /// Save the window or selection as PNG image once the next frame is rendered.
+    fn screenshot(&mut self, selection_only: bool) {
+        let region = if selection_only {
+            let terminal = &*self.terminal;
+            let display_offset = terminal.grid().display_offset();
+            let range = terminal.selection.as_ref().and_then(|s| s.to_range(terminal));
+            let size_info = self.display.grid_size_info;
+            match range.and_then(|range| Region::selection(&range, display_offset, &size_info)) {
+                Some(region) => region,
+                None => return,
+            }
+        } else {
+            Region::window(&self.display.size_info)
+        };
+
+        self.display.pending_screenshot = Some(region);
+        *self.dirty = true;
+    }
+
     /// Toggle the vi mode status.
     #[inline]
     fn toggle_vi_mode(&mut self) {
-        if !self.terminal.mode().contains(TermMode::VI) {
+        let vi_mode = self.terminal.mode().contains(TermMode::VI);
+
+        // Keep vi mode in the pager, since its bindings are the only way to navigate and search.
+        if vi_mode && self.cli_options.pager {
+            return;
+        }
+
+        if !vi_mode {
             self.clear_selection();
         }
 
         self.cancel_search();
         self.terminal.toggle_vi_mode();
+        *self.vi_input = ViInput::default();
 
         *self.dirty = true;
     }
 
+    fn push_vi_jump(&mut self) {
+        let point = self.terminal.vi_mode_cursor.point;
+        self.vi_marks.push_jump(self.terminal, point);
+    }
+
+    fn vi_jump_back(&mut self) {
+        let point = self.terminal.vi_mode_cursor.point;
+        if let Some(point) = self.vi_marks.jump_back(self.terminal, point) {
+            self.terminal.vi_goto_point(point);
+            *self.dirty = true;
+        }
+    }
+
+    fn vi_jump_forward(&mut self) {
+        if let Some(point) = self.vi_marks.jump_forward(self.terminal) {
+            self.terminal.vi_goto_point(point);
+            *self.dirty = true;
+        }
+    }
+
+    fn set_vi_mark(&mut self, name: char) {
+        let mark = ViMark::new(self.terminal, self.terminal.vi_mode_cursor.point);
+        self.vi_marks.set(name, mark);
+    }
+
+    fn jump_to_vi_mark(&mut self, name: char, exact: bool) {
+        let mut point = match self.vi_marks.get(name).and_then(|mark| mark.point(self.terminal)) {
+            Some(point) => point,
+            None => return,
+        };
+
+        // Line jumps go to the first non-blank cell.
+        if !exact {
+            let row = &self.terminal.grid()[point.line];
+            point.column = (0..self.terminal.columns())
+                .map(Column)
+                .find(|&column| !matches!(row[column].c, ' ' | '\t'))
+                .unwrap_or_default();
+        }
+
+        self.push_vi_jump();
+        self.terminal.vi_goto_point(point);
+        *self.dirty = true;
+    }
+
     fn message(&self) -> Option<&Message> {
         self.message_buffer.message()
     }
@@ -726,6 +1065,124 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 }
 
 impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
+    /// Apply the profile of the monitor the window is on, after it moved to another monitor.
+    fn update_monitor(&mut self) {
+        let monitor = self.display.window.current_monitor_name();
+        if monitor == self.display.monitor {
+            return;
+        }
+
+        let ui_config = &self.config.ui_config;
+        let old_monitor = mem::replace(&mut self.display.monitor, monitor);
+        let old_profile = ui_config.monitor_profile(old_monitor.as_deref());
+        let new_profile = ui_config.monitor_profile(self.display.monitor.as_deref());
+        if old_profile == new_profile {
+            return;
+        }
+
+        info!("Applying profile of monitor {:?}", self.display.monitor);
+
+        // Do not update font size if it has been changed at runtime.
+        let old_font_size = ui_config.font_size(old_monitor.as_deref());
+        let new_font_size = ui_config.font_size(self.display.monitor.as_deref());
+        if *self.font_size == old_font_size && new_font_size != old_font_size {
+            *self.font_size = new_font_size;
+            let font = ui_config.font.clone().with_size(new_font_size);
+            self.display_update_pending.set_font(font);
+        }
+
+        // Recompute the padding.
+        self.display_update_pending.dirty = true;
+        *self.dirty = true;
+    }
+
+    /// Working directory for new instances of Alacritty.
+    fn spawn_working_directory(&self, source: SpawnDirectory) -> Option<PathBuf> {
+        #[cfg(unix)]
+        let foreground_process_path = foreground_process_path;
+        #[cfg(not(unix))]
+        let foreground_process_path = || None;
+
+        let path = match source {
+            SpawnDirectory::Osc7 => self
+                .terminal
+                .working_directory()
+                .map(Path::to_path_buf)
+                .or_else(foreground_process_path),
+            SpawnDirectory::ForegroundProcess => foreground_process_path(),
+            SpawnDirectory::Home => dirs::home_dir(),
+        };
+
+        // Directories reported through `OSC 7` might not exist on this machine.
+        path.filter(|path| path.is_dir())
+    }
+
+    /// Estimated size of the text in a selection, assuming one byte per cell.
+    fn selection_size(&self, range: &SelectionRange) -> usize {
+        let lines = (range.end.line - range.start.line).0 as usize + 1;
+        let columns = if range.is_block {
+            range.end.column.0 - range.start.column.0 + 1
+        } else {
+            // Every line but the last one also ends in a newline.
+            self.terminal.columns() + 1
+        };
+
+        lines * columns
+    }
+
+    /// Check if text is within the clipboard size limit, showing a warning if it is not.
+    fn clipboard_size_allowed(&mut self, size: usize) -> bool {
+        let limit = self.config.selection.clipboard_size_limit;
+        if limit == 0 || size <= limit * BYTES_PER_MIB {
+            return true;
+        }
+
+        let text = format!(
+            "Refusing to copy {} MiB of text, which exceeds the clipboard size limit of {} MiB",
+            size / BYTES_PER_MIB,
+            limit
+        );
+        self.message_buffer.push(Message::new(text, MessageType::Warning));
+        self.display_update_pending.dirty = true;
+        *self.dirty = true;
+
+        false
+    }
+
+    /// Convert pasted text to the bytes written to the PTY.
+    fn paste_bytes(&self, text: &str) -> Vec<u8> {
+        if self.terminal.mode().contains(TermMode::BRACKETED_PASTE) {
+            format!("\x1b[200~{}\x1b[201~", text.replace("\x1b", "")).into_bytes()
+        } else {
+            // In non-bracketed (ie: normal) mode, terminal applications cannot distinguish
+            // pasted data from keystrokes.
+            // In theory, we should construct the keystrokes needed to produce the data we are
+            // pasting... since that's neither practical nor sensible (and probably an impossible
+            // task to solve in a general way), we'll just replace line breaks (windows and unix
+            // style) with a single carriage return (\r, which is what the Enter key produces).
+            text.replace("\r\n", "\r").replace("\n", "\r").into_bytes()
+        }
+    }
+
+    /// Write pasted bytes to the PTY, holding back what does not fit into its write queue.
+    fn queue_paste(&mut self, mut bytes: Vec<u8>) {
+        // Pastes which are already held back are written first.
+        if let Some(timer) = self.scheduler.get_mut(TimerId::Paste) {
+            if let GlutinEvent::UserEvent(Event::Paste(held_back)) = &mut timer.event {
+                held_back.append(&mut bytes);
+                return;
+            }
+        }
+
+        let remaining = bytes.split_off(min(self.write_queue.available(), bytes.len()));
+        self.write_to_pty(bytes);
+
+        if !remaining.is_empty() {
+            let event = Event::Paste(remaining).into();
+            self.scheduler.schedule(event, WRITE_QUEUE_RETRY_DELAY, false, TimerId::Paste);
+        }
+    }
+
     fn update_search(&mut self) {
         let regex = match self.search_state.regex() {
             Some(regex) => regex,
@@ -743,10 +1200,10 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
             self.search_state.dfas = None;
         } else {
             // Create search dfas for the new regex string.
-            self.search_state.dfas = RegexSearch::new(&regex).ok();
+            self.search_state.dfas = RegexSearch::new(&regex).ok().map(Arc::new);
 
             // Update search highlighting.
-            self.goto_match(MAX_SEARCH_WHILE_TYPING);
+            self.goto_match();
         }
 
         *self.dirty = true;
@@ -754,11 +1211,13 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
 
     /// Reset terminal to the state before search was started.
     fn search_reset_state(&mut self) {
-        // Unschedule pending timers.
-        self.scheduler.unschedule(TimerId::DelayedSearch);
+        // Stop searches on the search thread.
+        self.search_state.cancel_pending();
 
         // Clear focused match.
         self.search_state.focused_match = None;
+        self.search_state.match_count = None;
+        self.search_state.wrapped = false;
 
         // The viewport reset logic is only needed for vi mode, since without it our origin is
         // always at the current display offset instead of at the vi cursor position which we need
@@ -776,56 +1235,204 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
     }
 
     /// Jump to the first regex match from the search origin.
-    fn goto_match(&mut self, mut limit: Option<usize>) {
-        let dfas = match &self.search_state.dfas {
-            Some(dfas) => dfas,
-            None => return,
-        };
-
-        // Limit search only when enough lines are available to run into the limit.
-        limit = limit.filter(|&limit| limit <= self.terminal.total_lines());
+    fn goto_match(&mut self) {
+        if self.search_state.dfas.is_none() {
+            return;
+        }
 
         // Jump to the next match.
         let direction = self.search_state.direction;
         let clamped_origin = self.search_state.origin.grid_clamp(self.terminal, Boundary::Grid);
-        match self.terminal.search_next(dfas, clamped_origin, direction, Side::Left, limit) {
-            Some(regex_match) => {
-                let old_offset = self.terminal.grid().display_offset() as i32;
-
-                if self.terminal.mode().contains(TermMode::VI) {
-                    // Move vi cursor to the start of the match.
-                    self.terminal.vi_goto_point(*regex_match.start());
-                } else {
-                    // Select the match when vi mode is not active.
-                    self.terminal.scroll_to_point(*regex_match.start());
-                }
+        let pending = PendingSearch::Focus;
+        match self.search_nonblocking(clamped_origin, direction, Side::Left, pending) {
+            Some(regex_match) => self.focus_match(regex_match),
+            // Reset viewport only when we know there is no match, to prevent unnecessary jumping.
+            None if self.search_state.pending.is_none() => self.search_reset_state(),
+            None => {
+                // Clear focused match while the search thread is looking for it.
+                self.search_state.focused_match = None;
+                self.search_state.match_count = None;
+                self.search_state.wrapped = false;
+            },
+        }
+
+        *self.dirty = true;
+    }
+
+    /// Find the next match without blocking the event loop for long searches.
+    ///
+    /// Searches which can't be completed within [`MAX_SEARCH_LINES`] are continued on the search
+    /// thread, applying the `pending` action once they're done.
+    fn search_nonblocking(
+        &mut self,
+        origin: Point,
+        direction: Direction,
+        side: Side,
+        pending: PendingSearch,
+    ) -> Option<Match> {
+        // Every new search replaces the ones running on the search thread.
+        self.search_state.cancel_pending();
 
-                // Update the focused match.
-                self.search_state.focused_match = Some(regex_match);
+        let dfas = self.search_state.dfas.clone()?;
+
+        // Limit search only when enough lines are available to run into the limit.
+        let limit = Some(MAX_SEARCH_LINES).filter(|&limit| limit + 1 < self.terminal.total_lines());
+        let regex_match = self.terminal.search_next(&dfas, origin, direction, side, limit);
+
+        // Matches behind the origin are only correct if the entire buffer has been searched.
+        let wrapped = regex_match.as_ref().map_or(false, |regex_match| {
+            let point = match side {
+                Side::Left => *regex_match.start(),
+                Side::Right => *regex_match.end(),
+            };
+
+            match direction {
+                Direction::Right => point < origin,
+                Direction::Left => point > origin,
+            }
+        });
 
-                // Store number of lines the viewport had to be moved.
-                let display_offset = self.terminal.grid().display_offset();
-                self.search_state.display_offset_delta = old_offset - display_offset as i32;
+        if limit.is_none() || (regex_match.is_some() && !wrapped) {
+            return regex_match;
+        }
 
-                // Since we found a result, we require no delayed re-search.
-                self.scheduler.unschedule(TimerId::DelayedSearch);
+        match &self.search_state.worker {
+            Some(worker) => {
+                let history_position = self.terminal.grid().history_position();
+                worker.find_next(dfas, origin, direction, side, history_position);
+                self.search_state.pending = Some(pending);
+                None
             },
-            // Reset viewport only when we know there is no match, to prevent unnecessary jumping.
-            None if limit.is_none() => self.search_reset_state(),
-            None => {
-                // Schedule delayed search if we ran into our search limit.
-                if !self.scheduler.scheduled(TimerId::DelayedSearch) {
-                    self.scheduler.schedule(
-                        Event::SearchNext.into(),
-                        TYPING_SEARCH_DELAY,
-                        false,
-                        TimerId::DelayedSearch,
-                    );
+            None => self.terminal.search_next(&dfas, origin, direction, side, None),
+        }
+    }
+
+    /// Move the search origin in front of the match focused by advancing the search.
+    fn advance_search_origin_to_match(&mut self, direction: Direction, old_match: Option<Match>) {
+        // If we found a match, we set the search origin right in front of it to make sure that
+        // after modifications to the regex the search is started without moving the focused match
+        // around.
+        let focused_match = match &self.search_state.focused_match {
+            Some(focused_match) => focused_match,
+            None => return,
+        };
+
+        // Since the origin moves with the focused match, wrapping is relative to the last match.
+        if let Some(old_match) = old_match {
+            self.search_state.wrapped = match direction {
+                Direction::Right => focused_match.start() <= old_match.start(),
+                Direction::Left => focused_match.start() >= old_match.start(),
+            };
+        }
+
+        // Set new origin to the left/right of the match, depending on search direction.
+        let new_origin = match self.search_state.direction {
+            Direction::Right => *focused_match.start(),
+            Direction::Left => *focused_match.end(),
+        };
+
+        // Store the search origin with display offset by checking how far we need to scroll to it.
+        let old_display_offset = self.terminal.grid().display_offset() as i32;
+        self.terminal.scroll_to_point(new_origin);
+        let new_display_offset = self.terminal.grid().display_offset() as i32;
+        self.search_state.display_offset_delta = new_display_offset - old_display_offset;
+
+        // Store origin and scroll back to the match.
+        self.terminal.scroll_display(Scroll::Delta(-self.search_state.display_offset_delta));
+        self.search_state.origin = new_origin;
+    }
+
+    /// Focus a search match and move the viewport to it.
+    fn focus_match(&mut self, regex_match: Match) {
+        let old_offset = self.terminal.grid().display_offset() as i32;
+
+        if self.terminal.mode().contains(TermMode::VI) {
+            // Move vi cursor to the start of the match.
+            self.terminal.vi_goto_point(*regex_match.start());
+        } else {
+            // Select the match when vi mode is not active.
+            self.terminal.scroll_to_point(*regex_match.start());
+        }
+
+        // Check if the search had to wrap around the buffer boundaries.
+        let origin = self.search_state.origin.grid_clamp(self.terminal, Boundary::Grid);
+        self.search_state.wrapped = match self.search_state.direction {
+            Direction::Right => *regex_match.start() < origin,
+            Direction::Left => *regex_match.start() > origin,
+        };
+
+        // Update the focused match.
+        self.search_state.focused_match = Some(regex_match.clone());
+
+        // Store number of lines the viewport had to be moved.
+        let display_offset = self.terminal.grid().display_offset();
+        self.search_state.display_offset_delta = old_offset - display_offset as i32;
+
+        // Count matches on the search thread, which also cancels any pending search.
+        self.search_state.pending = None;
+        self.search_state.match_count = None;
+        if let (Some(worker), Some(dfas)) = (&self.search_state.worker, &self.search_state.dfas) {
+            let limit = self.config.ui_config.search.max_match_count();
+            let history_position = self.terminal.grid().history_position();
+            worker.count(dfas.clone(), regex_match, limit, history_position);
+        }
+
+        *self.dirty = true;
+    }
+
+    /// Apply an update from the search thread.
+    fn search_result(&mut self, result: SearchResult) {
+        let current = self.search_state.worker.as_ref().map(SearchWorker::generation);
+        if current != Some(result.generation) {
+            return;
+        }
+
+        // Move the result along with the lines added to the history since it was found.
+        let shift = self.terminal.grid().history_shift(result.history_position);
+
+        let regex_match = match result.update {
+            SearchUpdate::Match(regex_match) => regex_match,
+            SearchUpdate::Count(match_count) => {
+                if self.search_active() {
+                    self.search_state.match_count = Some(match_count);
+                    *self.dirty = true;
                 }
+                return;
+            },
+        };
 
-                // Clear focused match.
-                self.search_state.focused_match = None;
+        // Drop matches which have been removed from the terminal in the meantime.
+        let topmost_line = self.terminal.topmost_line();
+        let bottommost_line = self.terminal.bottommost_line();
+        let regex_match = regex_match
+            .map(|regex_match| {
+                let mut start = *regex_match.start();
+                let mut end = *regex_match.end();
+                start.line -= shift;
+                end.line -= shift;
+                start..=end
+            })
+            .filter(|regex_match| {
+                regex_match.start().line >= topmost_line
+                    && regex_match.end().line <= bottommost_line
+            });
+
+        match (self.search_state.pending.take(), regex_match) {
+            (Some(PendingSearch::ViMotion(side)), Some(regex_match)) => {
+                let point = match side {
+                    Side::Left => *regex_match.start(),
+                    Side::Right => *regex_match.end(),
+                };
+                self.terminal.vi_goto_point(point);
+            },
+            (Some(PendingSearch::ViMotion(_)), None) => (),
+            _ if !self.search_active() => (),
+            (Some(PendingSearch::Advance { direction, old_match }), Some(regex_match)) => {
+                self.focus_match(regex_match);
+                self.advance_search_origin_to_match(direction, old_match);
             },
+            (_, Some(regex_match)) => self.focus_match(regex_match),
+            (_, None) => self.search_reset_state(),
         }
 
         *self.dirty = true;
@@ -839,6 +1446,8 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
 
         // Clear focused match.
         self.search_state.focused_match = None;
+        self.search_state.match_count = None;
+        self.search_state.wrapped = false;
     }
 
     /// Update the cursor blinking state.
@@ -851,11 +1460,13 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
 
         // Check terminal cursor style.
         let terminal_blinking = self.terminal.cursor_style().blinking;
-        let blinking = cursor_style.blinking_override().unwrap_or(terminal_blinking);
+        let blinking = cursor_style.blinking_override().unwrap_or(terminal_blinking)
+            && !self.config.ui_config.accessibility.reduced_motion;
 
         // Update cursor blinking state.
         self.scheduler.unschedule(TimerId::BlinkCursor);
         if blinking && self.terminal.is_focused {
+            self.display.cursor_blink_start = Some(Instant::now());
             self.scheduler.schedule(
                 GlutinEvent::UserEvent(Event::BlinkCursor),
                 Duration::from_millis(self.config.cursor.blink_interval()),
@@ -863,13 +1474,31 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
                 TimerId::BlinkCursor,
             )
         } else {
+            self.display.cursor_blink_start = None;
             self.display.cursor_hidden = false;
             *self.dirty = true;
         }
     }
+
+    /// Update the window title and the timer polling for foreground process changes.
+    fn update_title(&mut self) {
+        let window_config = &self.config.ui_config.window;
+        let needs_process_info =
+            window_config.dynamic_title && title::needs_process_info(&window_config.title_format);
+
+        // Poll for changes of the foreground process, since there's no notification for them.
+        if !needs_process_info || cfg!(windows) {
+            self.scheduler.unschedule(TimerId::UpdateTitle);
+        } else if !self.scheduler.scheduled(TimerId::UpdateTitle) {
+            let event = GlutinEvent::UserEvent(Event::UpdateTitle);
+            self.scheduler.schedule(event, TITLE_POLL_INTERVAL, true, TimerId::UpdateTitle);
+        }
+
+        self.display.update_title(self.config, self.terminal);
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ClickState {
     None,
     Click,
@@ -877,6 +1506,29 @@ pub enum ClickState {
     TripleClick,
 }
 
+/// Vi mode input which applies to the following keys.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ViInput {
+    /// Number of times the next motion is repeated.
+    pub count: Option<usize>,
+
+    /// Action waiting for the next character.
+    pub pending: Option<ViPending>,
+}
+
+/// Vi mode action which is completed by the next character.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViPending {
+    /// Text object, with the character selecting its type.
+    TextObject(TextObjectScope),
+
+    /// Mark, with the character as its name.
+    SetMark,
+
+    /// Jump to a mark, either to its exact position or to the start of its line.
+    JumpToMark { exact: bool },
+}
+
 /// State of the mouse.
 #[derive(Debug)]
 pub struct Mouse {
@@ -886,6 +1538,7 @@ pub struct Mouse {
     pub last_click_timestamp: Instant,
     pub last_click_button: MouseButton,
     pub click_state: ClickState,
+    pub drag_origin: Option<(usize, usize)>,
     pub scroll_px: f64,
     pub cell_side: Side,
     pub lines_scrolled: f32,
@@ -905,6 +1558,7 @@ impl Default for Mouse {
             middle_button_state: ElementState::Released,
             right_button_state: ElementState::Released,
             click_state: ClickState::None,
+            drag_origin: None,
             cell_side: Side::Left,
             hint_highlight_dirty: Default::default(),
             block_hint_launcher: Default::default(),
@@ -924,14 +1578,38 @@ impl Mouse {
     /// coordinates will be clamped to the closest grid coordinates.
     #[inline]
     pub fn point(&self, size: &SizeInfo, display_offset: usize) -> Point {
-        let col = self.x.saturating_sub(size.padding_x() as usize) / (size.cell_width() as usize);
+        let (x, y) = self.grid_position(size);
+
+        let col = (x / size.cell_width()) as usize;
         let col = min(Column(col), size.last_column());
 
-        let line = self.y.saturating_sub(size.padding_y() as usize) / (size.cell_height() as usize);
+        let line = (y / size.cell_height()) as usize;
         let line = min(line, size.bottommost_line().0 as usize);
 
         display::viewport_to_point(display_offset, Point::new(line, col))
     }
+
+    /// Position of the mouse in pixels, relative to the start of the text area.
+    pub fn pixel_position(&self, size: &SizeInfo) -> (usize, usize) {
+        let (x, y) = self.grid_position(size);
+
+        let max_x = (size.columns() as f32 * size.cell_width()) as usize - 1;
+        let x = min(x as usize, max_x);
+
+        let max_y = (size.screen_lines() as f32 * size.cell_height()) as usize - 1;
+        let y = min(y as usize, max_y);
+
+        (x, y)
+    }
+
+    /// Position of the mouse relative to the start of the grid, clamped to the grid's start.
+    ///
+    /// The grid starts outside of the window while it is zoomed, so the padding can be negative.
+    fn grid_position(&self, size: &SizeInfo) -> (f32, f32) {
+        let x = self.x as f32 - size.padding_x().floor();
+        let y = self.y as f32 - size.padding_y().floor();
+        (x.max(0.), y.max(0.))
+    }
 }
 
 /// The event processor.
@@ -940,9 +1618,12 @@ impl Mouse {
 /// triggered.
 pub struct Processor<N> {
     notifier: N,
+    write_queue: WriteQueue,
     mouse: Mouse,
     received_count: usize,
     suppress_chars: bool,
+    pressed_key: Option<VirtualKeyCode>,
+    bound_keys: HashSet<VirtualKeyCode>,
     modifiers: ModifiersState,
     config: Config,
     message_buffer: MessageBuffer,
@@ -950,7 +1631,10 @@ pub struct Processor<N> {
     font_size: Size,
     event_queue: Vec<GlutinEvent<'static, Event>>,
     search_state: SearchState,
+    vi_input: ViInput,
+    vi_marks: ViMarks,
     cli_options: CLIOptions,
+    startup_timer: Option<StartupTimer>,
     dirty: bool,
 }
 
@@ -960,21 +1644,29 @@ impl<N: Notify + OnResize> Processor<N> {
     /// Takes a writer which is expected to be hooked up to the write end of a PTY.
     pub fn new(
         notifier: N,
+        write_queue: WriteQueue,
         message_buffer: MessageBuffer,
         config: Config,
         display: Display,
         cli_options: CLIOptions,
+        startup_timer: StartupTimer,
     ) -> Processor<N> {
         Processor {
-            font_size: config.ui_config.font.size(),
+            font_size: config.ui_config.font_size(display.monitor.as_deref()),
+            startup_timer: Some(startup_timer),
             message_buffer,
             cli_options,
             notifier,
+            write_queue,
             display,
             config,
             received_count: Default::default(),
             suppress_chars: Default::default(),
+            pressed_key: Default::default(),
+            bound_keys: Default::default(),
             search_state: Default::default(),
+            vi_input: Default::default(),
+            vi_marks: Default::default(),
             event_queue: Default::default(),
             modifiers: Default::default(),
             mouse: Default::default(),
@@ -1010,16 +1702,23 @@ impl<N: Notify + OnResize> Processor<N> {
     /// Run the event loop.
     pub fn run<T>(&mut self, terminal: Arc<FairMutex<Term<T>>>, mut event_loop: EventLoop<Event>)
     where
-        T: EventListener,
+        T: EventListener + Send + 'static,
     {
         let mut scheduler = Scheduler::new();
 
+        // Start the thread for searches which would block the event loop.
+        let proxy = event_loop.create_proxy();
+        self.search_state.worker = Some(SearchWorker::new(Arc::clone(&terminal), proxy));
+
         // Start the initial cursor blinking timer.
         if self.config.cursor.style().blinking {
             let event: Event = TerminalEvent::CursorBlinkingChange(true).into();
             self.event_queue.push(event.into());
         }
 
+        // Apply the title format to the initial title.
+        self.event_queue.push(Event::UpdateTitle.into());
+
         // NOTE: Since this takes a pointer to the winit event loop, it MUST be dropped first.
         #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
         let mut clipboard = unsafe { Clipboard::new(event_loop.wayland_display()) };
@@ -1080,10 +1779,13 @@ impl<N: Notify + OnResize> Processor<N> {
             let context = ActionContext {
                 terminal: &mut terminal,
                 notifier: &mut self.notifier,
+                write_queue: &self.write_queue,
                 mouse: &mut self.mouse,
                 clipboard: &mut clipboard,
                 received_count: &mut self.received_count,
                 suppress_chars: &mut self.suppress_chars,
+                pressed_key: &mut self.pressed_key,
+                bound_keys: &mut self.bound_keys,
                 modifiers: &mut self.modifiers,
                 message_buffer: &mut self.message_buffer,
                 display_update_pending: &mut display_update_pending,
@@ -1092,6 +1794,8 @@ impl<N: Notify + OnResize> Processor<N> {
                 config: &mut self.config,
                 scheduler: &mut scheduler,
                 search_state: &mut self.search_state,
+                vi_input: &mut self.vi_input,
+                vi_marks: &mut self.vi_marks,
                 cli_options: &self.cli_options,
                 dirty: &mut self.dirty,
                 event_loop,
@@ -1102,9 +1806,17 @@ impl<N: Notify + OnResize> Processor<N> {
                 Processor::handle_event(event, &mut processor);
             }
 
+            // Discard search state pointing into the cleared scrollback history.
+            self.search_state.clamp_to_grid(&terminal);
+
             // Process DisplayUpdate events.
             if display_update_pending.dirty {
-                self.submit_display_update(&mut terminal, old_is_searching, display_update_pending);
+                self.submit_display_update(
+                    &mut terminal,
+                    &mut scheduler,
+                    old_is_searching,
+                    display_update_pending,
+                );
             }
 
             // Skip rendering on Wayland until we get frame event from compositor.
@@ -1126,8 +1838,12 @@ impl<N: Notify + OnResize> Processor<N> {
             if self.dirty {
                 self.dirty = false;
 
-                // Request immediate re-draw if visual bell animation is not finished yet.
-                if !self.display.visual_bell.completed() {
+                // Request immediate re-draw while the visual bell or other animations are running.
+                let presentation = &self.config.ui_config.mouse.presentation;
+                let animations_completed = self.display.opacity.completed()
+                    & self.display.colors.completed()
+                    & self.display.pointer_highlight.completed(presentation);
+                if !self.display.visual_bell.completed() || !animations_completed {
                     let event: Event = TerminalEvent::Wakeup.into();
                     self.event_queue.push(event.into());
 
@@ -1135,10 +1851,30 @@ impl<N: Notify + OnResize> Processor<N> {
                 }
 
                 // Redraw screen.
-                self.display.draw(terminal, &self.message_buffer, &self.config, &self.search_state);
+                self.display.draw(
+                    terminal,
+                    &self.message_buffer,
+                    &self.config,
+                    &self.search_state,
+                    &self.mouse,
+                );
+
+                self.update_text_blinking(&mut scheduler);
+                self.report_missing_glyphs();
+
+                // Load common glyphs once the first frame is visible.
+                if let Some(mut startup_timer) = self.startup_timer.take() {
+                    startup_timer.stage("first frame");
+                    self.display.warm_up_glyph_cache();
+                    startup_timer.stage("glyph warmup");
+                    startup_timer.finish();
+                }
             }
         });
 
+        // Keep copied text available after exit.
+        clipboard.persist();
+
         // Write ref tests to disk.
         if self.config.ui_config.debug.ref_test {
             self.write_ref_test_results(&terminal.lock());
@@ -1157,6 +1893,8 @@ impl<N: Notify + OnResize> Processor<N> {
         match event {
             GlutinEvent::UserEvent(event) => match event {
                 Event::DprChanged(scale_factor, (width, height)) => {
+                    processor.ctx.update_monitor();
+
                     let display_update_pending = &mut processor.ctx.display_update_pending;
 
                     // Push current font to update its DPR.
@@ -1174,56 +1912,177 @@ impl<N: Notify + OnResize> Processor<N> {
                     processor.ctx.display_update_pending.dirty = true;
                     *processor.ctx.dirty = true;
                 },
-                Event::SearchNext => processor.ctx.goto_match(None),
+                Event::SearchResult(result) => processor.ctx.search_result(result),
                 Event::ConfigReload(path) => Self::reload_config(&path, processor),
                 Event::Scroll(scroll) => processor.ctx.scroll(scroll),
+                #[cfg(unix)]
+                Event::FontSize(change) => match change {
+                    FontSizeChange::Delta(delta) => processor.ctx.change_font_size(delta),
+                    FontSizeChange::Set(size) => {
+                        let delta = size - processor.ctx.font_size.as_f32_pts();
+                        processor.ctx.change_font_size(delta);
+                    },
+                    FontSizeChange::Reset => processor.ctx.reset_font_size(),
+                },
+                #[cfg(unix)]
+                Event::GlyphStats(sender) => {
+                    let display = &processor.ctx.display;
+                    let stats = GlyphStats {
+                        glyphs: display.glyph_count(),
+                        atlas_size: display.atlas_memory_usage(),
+                    };
+                    let _ = sender.send(stats);
+                },
+                #[cfg(unix)]
+                Event::Trim => {
+                    processor.ctx.display.trim_glyph_cache();
+                    *processor.ctx.dirty = true;
+                },
+                #[cfg(unix)]
+                Event::Screenshot(selection_only) => processor.ctx.screenshot(selection_only),
+                Event::UpdateTitle => processor.ctx.update_title(),
                 Event::BlinkCursor => {
-                    processor.ctx.display.cursor_hidden ^= true;
+                    let display = &mut processor.ctx.display;
+                    let timeout = processor.ctx.config.cursor.blink_timeout();
+                    let expired = match (display.cursor_blink_start, timeout) {
+                        (Some(start), Some(timeout)) => start.elapsed() >= timeout,
+                        _ => false,
+                    };
+
+                    // Leave the cursor visible once blinking stopped, to let the event loop idle.
+                    if expired {
+                        processor.ctx.scheduler.unschedule(TimerId::BlinkCursor);
+                        display.cursor_hidden = false;
+                    } else {
+                        display.cursor_hidden ^= true;
+                    }
+
+                    *processor.ctx.dirty = true;
+                },
+                Event::BlinkText => {
+                    let display = &mut processor.ctx.display;
+                    let timeout = processor.ctx.config.ui_config.text_blink.timeout();
+                    let expired = match (display.text_blink_start, timeout) {
+                        (Some(start), Some(timeout)) => start.elapsed() >= timeout,
+                        _ => false,
+                    };
+
+                    // Leave the text visible once blinking stopped.
+                    if expired {
+                        processor.ctx.scheduler.unschedule(TimerId::BlinkText);
+                        display.text_blink_phase = 0;
+                    } else {
+                        let phases = 2 * RAPID_BLINKS_PER_BLINK;
+                        display.text_blink_phase = (display.text_blink_phase + 1) % phases;
+                    }
+
                     *processor.ctx.dirty = true;
                 },
+                Event::Paste(bytes) => processor.ctx.queue_paste(bytes),
+                Event::PasteSlowly(mut bytes) => {
+                    let paste_config = &processor.ctx.config.ui_config.slow_paste;
+
+                    // Wait for the application to read the previous chunks.
+                    let (remaining, delay) = if processor.ctx.write_queue_full() {
+                        (bytes, max(paste_config.delay(), WRITE_QUEUE_RETRY_DELAY))
+                    } else {
+                        let remaining =
+                            bytes.split_off(min(paste_config.chunk_size(), bytes.len()));
+                        processor.ctx.write_to_pty(bytes);
+                        (remaining, paste_config.delay())
+                    };
+
+                    if !remaining.is_empty() {
+                        let event = Event::PasteSlowly(remaining);
+                        processor.ctx.scheduler.schedule(
+                            event.into(),
+                            delay,
+                            false,
+                            TimerId::PasteSlowly,
+                        );
+                    }
+                },
+                Event::ResizeGrid(dimensions) => {
+                    let size = processor.ctx.display.grid_size(&processor.ctx.config, dimensions);
+                    processor.ctx.window().set_inner_size(size);
+
+                    // Resize immediately, since no resize event is emitted on Wayland.
+                    processor.ctx.display_update_pending.set_dimensions(size);
+                },
                 Event::TerminalEvent(event) => match event {
-                    TerminalEvent::Title(title) => {
-                        let ui_config = &processor.ctx.config.ui_config;
-                        if ui_config.window.dynamic_title {
-                            processor.ctx.window().set_title(&title);
-                        }
+                    TerminalEvent::Title(_)
+                    | TerminalEvent::ResetTitle
+                    | TerminalEvent::IconName(_)
+                    | TerminalEvent::ResetIconName => processor.ctx.update_title(),
+                    TerminalEvent::Wakeup => *processor.ctx.dirty = true,
+                    TerminalEvent::AlternateScreen(active) => {
+                        debug!("Alternate screen active: {}", active);
+                        *processor.ctx.dirty = true;
                     },
-                    TerminalEvent::ResetTitle => {
-                        let ui_config = &processor.ctx.config.ui_config;
-                        if ui_config.window.dynamic_title {
-                            processor.ctx.display.window.set_title(&ui_config.window.title);
+                    TerminalEvent::ColumnMode(columns) => {
+                        if processor.ctx.config.ui_config.window.column_mode_resize {
+                            let display = &processor.ctx.display;
+                            let size = display.column_mode_size(&processor.ctx.config, columns);
+                            processor.ctx.window().set_inner_size(size);
+
+                            // Resize immediately, since no resize event is emitted on Wayland.
+                            processor.ctx.display_update_pending.set_dimensions(size);
                         }
                     },
-                    TerminalEvent::Wakeup => *processor.ctx.dirty = true,
                     TerminalEvent::Bell => {
+                        // Ignore bells coalesced with a previous bell.
+                        if !processor.ctx.display.visual_bell.ring() {
+                            return;
+                        }
+
                         // Set window urgency.
                         if processor.ctx.terminal.mode().contains(TermMode::URGENCY_HINTS) {
                             let focused = processor.ctx.terminal.is_focused;
                             processor.ctx.window().set_urgent(!focused);
                         }
 
-                        // Ring visual bell.
-                        processor.ctx.display.visual_bell.ring();
-
-                        // Execute bell command.
-                        if let Some(bell_command) = &processor.ctx.config.ui_config.bell.command {
-                            start_daemon(bell_command.program(), bell_command.args());
+                        // Play the bell sound.
+                        let bell = &processor.ctx.config.ui_config.bell;
+                        match (bell.sound, &bell.command) {
+                            (BellSound::System, _) => processor.ctx.display.window.beep(),
+                            (BellSound::Command, Some(bell_command)) => {
+                                start_daemon(bell_command.program(), bell_command.args());
+                            },
+                            _ => (),
                         }
                     },
                     TerminalEvent::ClipboardStore(clipboard_type, content) => {
-                        processor.ctx.clipboard.store(clipboard_type, content);
+                        if processor.ctx.clipboard_size_allowed(content.len()) {
+                            processor.ctx.clipboard.store(clipboard_type, content);
+                        }
                     },
                     TerminalEvent::ClipboardLoad(clipboard_type, format) => {
                         let text = format(processor.ctx.clipboard.load(clipboard_type).as_str());
                         processor.ctx.write_to_pty(text.into_bytes());
                     },
                     TerminalEvent::ColorRequest(index, format) => {
-                        let text = format(processor.ctx.display.colors[index]);
+                        let text = format(processor.ctx.display.colors.target()[index]);
+                        processor.ctx.write_to_pty(text.into_bytes());
+                    },
+                    TerminalEvent::CapabilityRequest(name, format) => {
+                        let text = format(terminfo::capability(&name).as_deref());
                         processor.ctx.write_to_pty(text.into_bytes());
                     },
                     TerminalEvent::PtyWrite(text) => processor.ctx.write_to_pty(text.into_bytes()),
                     TerminalEvent::MouseCursorDirty => processor.reset_mouse_cursor(),
-                    TerminalEvent::Exit => (),
+                    TerminalEvent::Exit | TerminalEvent::UnhandledSequence(_) => (),
+                    TerminalEvent::ChildExit(status) => {
+                        info!("Child process {}", status);
+
+                        // Show failures when the window is kept open after the child has exited.
+                        if processor.ctx.config.hold && !status.success() {
+                            let text = format!("Child process {}", status);
+                            let message = Message::new(text, MessageType::Warning);
+                            processor.ctx.message_buffer.push(message);
+                            processor.ctx.display_update_pending.dirty = true;
+                            *processor.ctx.dirty = true;
+                        }
+                    },
                     TerminalEvent::CursorBlinkingChange(_) => {
                         processor.ctx.update_cursor_blinking();
                     },
@@ -1232,7 +2091,7 @@ impl<N: Notify + OnResize> Processor<N> {
             GlutinEvent::RedrawRequested(_) => *processor.ctx.dirty = true,
             GlutinEvent::WindowEvent { event, window_id, .. } => {
                 match event {
-                    WindowEvent::CloseRequested => processor.ctx.terminal.exit(),
+                    WindowEvent::CloseRequested => processor.ctx.quit(),
                     WindowEvent::Resized(size) => {
                         // Minimizing the window sends a Resize event with zero width and
                         // height. But there's no need to ever actually resize to this.
@@ -1300,8 +2159,8 @@ impl<N: Notify + OnResize> Processor<N> {
                     | WindowEvent::Destroyed
                     | WindowEvent::ThemeChanged(_)
                     | WindowEvent::HoveredFile(_)
-                    | WindowEvent::Touch(_)
-                    | WindowEvent::Moved(_) => (),
+                    | WindowEvent::Touch(_) => (),
+                    WindowEvent::Moved(_) => processor.ctx.update_monitor(),
                 }
             },
             GlutinEvent::Suspended { .. }
@@ -1327,7 +2186,6 @@ impl<N: Notify + OnResize> Processor<N> {
                     | WindowEvent::Destroyed
                     | WindowEvent::HoveredFile(_)
                     | WindowEvent::Touch(_)
-                    | WindowEvent::Moved(_)
             ),
             GlutinEvent::Suspended { .. }
             | GlutinEvent::NewEvents { .. }
@@ -1353,6 +2211,13 @@ impl<N: Notify + OnResize> Processor<N> {
         };
 
         processor.ctx.display.update_config(&config);
+
+        // Resume the output when local flow control has been disabled.
+        if config.ui_config.keyboard.flow_control != FlowControl::Local
+            && processor.ctx.display.output_paused
+        {
+            processor.ctx.set_output_paused(false);
+        }
         processor.ctx.terminal.update_config(&config);
 
         // Reload cursor if its thickness has changed.
@@ -1362,36 +2227,32 @@ impl<N: Notify + OnResize> Processor<N> {
             processor.ctx.display_update_pending.set_cursor_dirty();
         }
 
-        if processor.ctx.config.ui_config.font != config.ui_config.font {
+        let monitor = processor.ctx.display.monitor.as_deref();
+        let font_size = processor.ctx.config.ui_config.font_size(monitor);
+        if processor.ctx.config.ui_config.font != config.ui_config.font
+            || font_size != config.ui_config.font_size(monitor)
+        {
             // Do not update font size if it has been changed at runtime.
-            if *processor.ctx.font_size == processor.ctx.config.ui_config.font.size() {
-                *processor.ctx.font_size = config.ui_config.font.size();
+            if *processor.ctx.font_size == font_size {
+                *processor.ctx.font_size = config.ui_config.font_size(monitor);
             }
 
             let font = config.ui_config.font.clone().with_size(*processor.ctx.font_size);
             processor.ctx.display_update_pending.set_font(font);
+
+            // Missing characters are reported again for the new font.
+            processor.ctx.message_buffer.remove_target(MISSING_GLYPHS_TARGET);
         }
 
         // Update display if padding options were changed.
-        let window_config = &processor.ctx.config.ui_config.window;
-        if window_config.padding(1.) != config.ui_config.window.padding(1.)
-            || window_config.dynamic_padding != config.ui_config.window.dynamic_padding
+        let ui_config = &processor.ctx.config.ui_config;
+        let monitor = processor.ctx.display.monitor.as_deref();
+        if ui_config.padding(monitor, 1., 1., 1.) != config.ui_config.padding(monitor, 1., 1., 1.)
+            || ui_config.window.dynamic_padding != config.ui_config.window.dynamic_padding
         {
             processor.ctx.display_update_pending.dirty = true;
         }
 
-        // Live title reload.
-        if !config.ui_config.window.dynamic_title
-            || processor.ctx.config.ui_config.window.title != config.ui_config.window.title
-        {
-            processor.ctx.window().set_title(&config.ui_config.window.title);
-        }
-
-        #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
-        if processor.ctx.event_loop.is_wayland() {
-            processor.ctx.window().set_wayland_theme(&config.ui_config.colors);
-        }
-
         // Set subpixel anti-aliasing.
         #[cfg(target_os = "macos")]
         crossfont::set_font_smoothing(config.ui_config.font.use_thin_strokes);
@@ -1408,18 +2269,72 @@ impl<N: Notify + OnResize> Processor<N> {
         // Update cursor blinking.
         processor.ctx.update_cursor_blinking();
 
+        // Live title reload.
+        processor.ctx.update_title();
+
         *processor.ctx.dirty = true;
     }
 
+    /// Start or stop the text blinking timer, based on the text visible in the last frame.
+    fn update_text_blinking(&mut self, scheduler: &mut Scheduler) {
+        let ui_config = &self.config.ui_config;
+        if !self.display.blinking_text_visible || ui_config.accessibility.reduced_motion {
+            scheduler.unschedule(TimerId::BlinkText);
+            self.display.text_blink_phase = 0;
+            self.display.text_blink_start = None;
+        } else if self.display.text_blink_start.is_none() {
+            self.display.text_blink_start = Some(Instant::now());
+
+            let event = GlutinEvent::UserEvent(Event::BlinkText);
+            // The timer is driven by rapidly blinking text.
+            let interval = ui_config.text_blink.interval() / u32::from(RAPID_BLINKS_PER_BLINK);
+            scheduler.schedule(event, interval, true, TimerId::BlinkText);
+        }
+    }
+
+    /// Show a notice in the message bar for characters missing from all fonts.
+    fn report_missing_glyphs(&mut self) {
+        let missing = match self.display.take_missing_glyphs() {
+            Some(missing) if self.config.ui_config.font.missing_glyph_notice => missing,
+            _ => return,
+        };
+
+        let mut codepoints: Vec<_> = missing
+            .iter()
+            .take(MAX_MISSING_GLYPHS_LISTED)
+            .map(|character| format!("U+{:04X}", *character as u32))
+            .collect();
+        if missing.len() > MAX_MISSING_GLYPHS_LISTED {
+            codepoints.push(format!("and {} more", missing.len() - MAX_MISSING_GLYPHS_LISTED));
+        }
+
+        let text = format!(
+            "No font contains glyphs for {}; install a fallback font covering them or change \
+             `font.normal.family`",
+            codepoints.join(", ")
+        );
+        let mut message = Message::new(text, MessageType::Warning);
+        message.set_target(MISSING_GLYPHS_TARGET.into());
+
+        // Replace the previous notice, to keep only a single one around.
+        self.message_buffer.remove_target(MISSING_GLYPHS_TARGET);
+        self.event_queue.push(GlutinEvent::UserEvent(Event::Message(message)));
+    }
+
     /// Submit the pending changes to the `Display`.
     fn submit_display_update<T>(
         &mut self,
         terminal: &mut Term<T>,
+        scheduler: &mut Scheduler,
         old_is_searching: bool,
         display_update_pending: DisplayUpdate,
     ) where
         T: EventListener,
     {
+        let resized = display_update_pending.dimensions().is_some();
+        let old_dimensions =
+            (self.display.size_info.columns(), self.display.size_info.screen_lines());
+
         // Compute cursor positions before resize.
         let num_lines = terminal.screen_lines();
         let cursor_at_bottom = terminal.grid().cursor.point.line + 1 == num_lines;
@@ -1438,6 +2353,31 @@ impl<N: Notify + OnResize> Processor<N> {
             display_update_pending,
         );
 
+        // Update titles containing the grid dimensions.
+        let new_dimensions =
+            (self.display.size_info.columns(), self.display.size_info.screen_lines());
+        if new_dimensions != old_dimensions {
+            self.display.update_title(&self.config, terminal);
+        }
+
+        // Briefly show the new grid dimensions after resizing the window.
+        if resized
+            && new_dimensions != old_dimensions
+            && self.config.ui_config.window.resize_overlay
+        {
+            self.display.resize_overlay = Some(Instant::now() + RESIZE_OVERLAY_DURATION);
+
+            // Redraw once the overlay has expired.
+            let event: Event = TerminalEvent::Wakeup.into();
+            scheduler.unschedule(TimerId::ResizeOverlay);
+            scheduler.schedule(
+                event.into(),
+                RESIZE_OVERLAY_DURATION,
+                false,
+                TimerId::ResizeOverlay,
+            );
+        }
+
         let new_is_searching = self.search_state.history_index.is_some();
         if !old_is_searching && new_is_searching {
             // Scroll on search start to make sure origin is visible with minimal viewport motion.
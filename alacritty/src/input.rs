@@ -7,12 +7,14 @@
 
 use std::borrow::Cow;
 use std::cmp::{max, min, Ordering};
+use std::collections::HashSet;
 use std::marker::PhantomData;
 use std::time::{Duration, Instant};
 
 use glutin::dpi::PhysicalPosition;
 use glutin::event::{
     ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
+    VirtualKeyCode,
 };
 use glutin::event_loop::EventLoopWindowTarget;
 #[cfg(target_os = "macos")]
@@ -22,25 +24,39 @@ use glutin::window::CursorIcon;
 use alacritty_terminal::ansi::{ClearMode, Handler};
 use alacritty_terminal::event::EventListener;
 use alacritty_terminal::grid::{Dimensions, Scroll};
-use alacritty_terminal::index::{Boundary, Column, Direction, Point, Side};
+use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point, Side};
 use alacritty_terminal::selection::SelectionType;
 use alacritty_terminal::term::search::Match;
 use alacritty_terminal::term::{ClipboardType, SizeInfo, Term, TermMode};
-use alacritty_terminal::vi_mode::ViMotion;
+use alacritty_terminal::vi_mode::{TextObject, TextObjectScope, ViMotion};
 
-use crate::clipboard::Clipboard;
-use crate::config::{Action, BindingMode, Config, Key, SearchAction, ViAction};
+use crate::clipboard::{Clipboard, Register};
+use crate::config::keyboard::FlowControl;
+use crate::config::{Action, BindingMode, Config, Key, SearchAction, SpawnOptions, ViAction};
 use crate::daemon::start_daemon;
 use crate::display::hint::HintMatch;
 use crate::display::window::Window;
 use crate::display::Display;
-use crate::event::{ClickState, Event, Mouse, TYPING_SEARCH_DELAY};
+use crate::event::{ClickState, Event, Mouse, ViInput, ViPending};
 use crate::message_bar::{self, Message};
 use crate::scheduler::{Scheduler, TimerId};
 
+use keyboard::KeyEventType;
+
+mod keyboard;
+
 /// Font size change interval.
 pub const FONT_SIZE_STEP: f32 = 0.5;
 
+/// Character which stops the output when flow control is handled locally.
+const XOFF: char = '\x13';
+
+/// Character which resumes the output when flow control is handled locally.
+const XON: char = '\x11';
+
+/// Maximum number of times a vi mode motion can be repeated.
+const MAX_VI_COUNT: usize = 9999;
+
 /// Interval for mouse scrolling during selection outside of the boundaries.
 const SELECTION_SCROLLING_INTERVAL: Duration = Duration::from_millis(15);
 
@@ -61,9 +77,19 @@ pub struct Processor<T: EventListener, A: ActionContext<T>> {
 
 pub trait ActionContext<T: EventListener> {
     fn write_to_pty<B: Into<Cow<'static, [u8]>>>(&self, _data: B) {}
+    fn write_queue_full(&self) -> bool {
+        false
+    }
     fn mark_dirty(&mut self) {}
     fn size_info(&self) -> SizeInfo;
+
+    /// Size information of the rendered grid, which is magnified while zoomed.
+    fn grid_size_info(&self) -> SizeInfo {
+        self.size_info()
+    }
     fn copy_selection(&mut self, _ty: ClipboardType) {}
+    fn append_selection_to_clipboard(&mut self) {}
+    fn copy_to_register(&mut self, _register: Register) {}
     fn start_selection(&mut self, _ty: SelectionType, _point: Point, _side: Side) {}
     fn toggle_selection(&mut self, _ty: SelectionType, _point: Point, _side: Side) {}
     fn update_selection(&mut self, _point: Point, _side: Side) {}
@@ -72,16 +98,24 @@ pub trait ActionContext<T: EventListener> {
     fn mouse_mut(&mut self) -> &mut Mouse;
     fn mouse(&self) -> &Mouse;
     fn received_count(&mut self) -> &mut usize;
+    fn vi_input(&mut self) -> &mut ViInput;
     fn suppress_chars(&mut self) -> &mut bool;
+    fn pressed_key(&mut self) -> &mut Option<VirtualKeyCode>;
+    fn bound_keys(&mut self) -> &mut HashSet<VirtualKeyCode>;
     fn modifiers(&mut self) -> &mut ModifiersState;
     fn scroll(&mut self, _scroll: Scroll) {}
     fn window(&mut self) -> &mut Window;
     fn display(&mut self) -> &mut Display;
     fn terminal(&self) -> &Term<T>;
     fn terminal_mut(&mut self) -> &mut Term<T>;
-    fn spawn_new_instance(&mut self) {}
+    fn spawn_new_instance(&mut self, _options: &SpawnOptions) {}
+    fn quit(&mut self) {}
     fn change_font_size(&mut self, _delta: f32) {}
     fn reset_font_size(&mut self) {}
+    fn set_opacity(&mut self, _opacity: f32) {}
+    fn toggle_opacity(&mut self) {}
+    fn toggle_theme(&mut self) {}
+    fn toggle_zoom(&mut self) {}
     fn pop_message(&mut self) {}
     fn message(&self) -> Option<&Message>;
     fn config(&self) -> &Config;
@@ -102,12 +136,40 @@ pub trait ActionContext<T: EventListener> {
     fn search_active(&self) -> bool;
     fn on_typing_start(&mut self) {}
     fn toggle_vi_mode(&mut self) {}
+    fn push_vi_jump(&mut self) {}
+    fn vi_jump_back(&mut self) {}
+    fn vi_jump_forward(&mut self) {}
+    fn set_vi_mark(&mut self, _name: char) {}
+    fn jump_to_vi_mark(&mut self, _name: char, _exact: bool) {}
     fn hint_input(&mut self, _character: char) {}
     fn trigger_hint(&mut self, _hint: &HintMatch) {}
     fn paste(&mut self, _text: &str) {}
+    fn paste_slowly(&mut self, _text: &str) {}
+    fn set_output_paused(&mut self, _paused: bool) {}
+    fn export_svg(&mut self) {}
+    fn screenshot(&mut self, _selection_only: bool) {}
 }
 
 impl Action {
+    /// Check if the action moves the vi mode cursor to a distant position.
+    fn is_vi_jump(&self) -> bool {
+        matches!(
+            self,
+            Action::ScrollToTop
+                | Action::ScrollToBottom
+                | Action::ScrollToPreviousPrompt
+                | Action::ScrollToNextPrompt
+                | Action::ViMotion(ViMotion::High)
+                | Action::ViMotion(ViMotion::Middle)
+                | Action::ViMotion(ViMotion::Low)
+                | Action::ViMotion(ViMotion::Bracket)
+                | Action::ViAction(ViAction::SearchNext)
+                | Action::ViAction(ViAction::SearchPrevious)
+                | Action::ViAction(ViAction::SearchStart)
+                | Action::ViAction(ViAction::SearchEnd)
+        )
+    }
+
     fn toggle_selection<T, A>(ctx: &mut A, ty: SelectionType)
     where
         A: ActionContext<T>,
@@ -120,6 +182,32 @@ impl Action {
             selection.include_all();
         }
     }
+
+    fn scroll_to_prompt<T, A>(ctx: &mut A, direction: Direction)
+    where
+        A: ActionContext<T>,
+        T: EventListener,
+    {
+        let term = ctx.terminal();
+        let display_offset = term.grid().display_offset() as i32;
+        let origin = if term.mode().contains(TermMode::VI) {
+            term.vi_mode_cursor.point.line
+        } else {
+            Line(-display_offset)
+        };
+
+        let line = match term.prompt_line(origin, direction) {
+            Some(line) => line,
+            None => return,
+        };
+
+        // Scroll the prompt to the top of the viewport.
+        ctx.scroll(Scroll::Delta(max(-line.0, 0) - display_offset));
+
+        // Move vi mode cursor.
+        ctx.terminal_mut().vi_mode_cursor.point = Point::new(line, Column(0));
+        ctx.mark_dirty();
+    }
 }
 
 trait Execute<T: EventListener> {
@@ -129,6 +217,10 @@ trait Execute<T: EventListener> {
 impl<T: EventListener> Execute<T> for Action {
     #[inline]
     fn execute<A: ActionContext<T>>(&self, ctx: &mut A) {
+        if self.is_vi_jump() && ctx.terminal().mode().contains(TermMode::VI) {
+            ctx.push_vi_jump();
+        }
+
         match self {
             Action::Esc(s) => {
                 ctx.on_typing_start();
@@ -142,10 +234,20 @@ impl<T: EventListener> Execute<T> for Action {
                 ctx.display().hint_state.start(hint.clone());
                 ctx.mark_dirty();
             },
+            Action::OpenURLHints => {
+                let hint = ctx.config().ui_config.hints.url().clone();
+                ctx.display().hint_state.start(hint);
+                ctx.mark_dirty();
+            },
             Action::ToggleViMode => ctx.toggle_vi_mode(),
             Action::ViMotion(motion) => {
                 ctx.on_typing_start();
-                ctx.terminal_mut().vi_motion(*motion);
+
+                let count = ctx.vi_input().count.unwrap_or(1);
+                for _ in 0..count {
+                    ctx.terminal_mut().vi_motion(*motion);
+                }
+
                 ctx.mark_dirty();
             },
             Action::ViAction(ViAction::ToggleNormalSelection) => {
@@ -168,6 +270,23 @@ impl<T: EventListener> Execute<T> for Action {
                 }
                 ctx.display().vi_highlighted_hint = hint;
             },
+            Action::ViAction(ViAction::SetMark) => {
+                ctx.vi_input().pending = Some(ViPending::SetMark);
+            },
+            Action::ViAction(ViAction::JumpToMark) => {
+                ctx.vi_input().pending = Some(ViPending::JumpToMark { exact: true });
+            },
+            Action::ViAction(ViAction::JumpToMarkLine) => {
+                ctx.vi_input().pending = Some(ViPending::JumpToMark { exact: false });
+            },
+            Action::ViAction(ViAction::JumpBack) => ctx.vi_jump_back(),
+            Action::ViAction(ViAction::JumpForward) => ctx.vi_jump_forward(),
+            Action::ViAction(ViAction::InnerObject) => {
+                ctx.vi_input().pending = Some(ViPending::TextObject(TextObjectScope::Inner));
+            },
+            Action::ViAction(ViAction::AroundObject) => {
+                ctx.vi_input().pending = Some(ViPending::TextObject(TextObjectScope::Around));
+            },
             Action::ViAction(ViAction::SearchNext) => {
                 let terminal = ctx.terminal();
                 let direction = ctx.search_direction();
@@ -236,6 +355,8 @@ impl<T: EventListener> Execute<T> for Action {
             Action::SearchForward => ctx.start_search(Direction::Right),
             Action::SearchBackward => ctx.start_search(Direction::Left),
             Action::Copy => ctx.copy_selection(ClipboardType::Clipboard),
+            Action::AppendSelectionToClipboard => ctx.append_selection_to_clipboard(),
+            Action::CopyToRegister(register) => ctx.copy_to_register(*register),
             #[cfg(not(any(target_os = "macos", windows)))]
             Action::CopySelection => ctx.copy_selection(ClipboardType::Selection),
             Action::ClearSelection => ctx.clear_selection(),
@@ -243,13 +364,25 @@ impl<T: EventListener> Execute<T> for Action {
                 let text = ctx.clipboard_mut().load(ClipboardType::Clipboard);
                 ctx.paste(&text);
             },
+            Action::PasteSlowly => {
+                let text = ctx.clipboard_mut().load(ClipboardType::Clipboard);
+                ctx.paste_slowly(&text);
+            },
             Action::PasteSelection => {
                 let text = ctx.clipboard_mut().load(ClipboardType::Selection);
                 ctx.paste(&text);
             },
+            Action::PasteRegister(register) => {
+                let text = ctx.clipboard_mut().load_register(*register);
+                ctx.paste(&text);
+            },
             Action::ToggleFullscreen => ctx.window().toggle_fullscreen(),
             #[cfg(target_os = "macos")]
             Action::ToggleSimpleFullscreen => ctx.window().toggle_simple_fullscreen(),
+            Action::ToggleOpacity => ctx.toggle_opacity(),
+            Action::SetOpacity(opacity) => ctx.set_opacity(*opacity),
+            Action::ToggleTheme => ctx.toggle_theme(),
+            Action::ToggleZoom => ctx.toggle_zoom(),
             #[cfg(target_os = "macos")]
             Action::Hide => ctx.event_loop().hide_application(),
             #[cfg(target_os = "macos")]
@@ -257,7 +390,7 @@ impl<T: EventListener> Execute<T> for Action {
             #[cfg(not(target_os = "macos"))]
             Action::Hide => ctx.window().set_visible(false),
             Action::Minimize => ctx.window().set_minimized(true),
-            Action::Quit => ctx.terminal_mut().exit(),
+            Action::Quit => ctx.quit(),
             Action::IncreaseFontSize => ctx.change_font_size(FONT_SIZE_STEP),
             Action::DecreaseFontSize => ctx.change_font_size(FONT_SIZE_STEP * -1.),
             Action::ResetFontSize => ctx.reset_font_size(),
@@ -317,8 +450,21 @@ impl<T: EventListener> Execute<T> for Action {
                 ctx.mark_dirty();
             },
             Action::ClearHistory => ctx.terminal_mut().clear_screen(ClearMode::Saved),
+            Action::ScrollToPreviousPrompt => Self::scroll_to_prompt(ctx, Direction::Left),
+            Action::ScrollToNextPrompt => Self::scroll_to_prompt(ctx, Direction::Right),
+            Action::SelectLastCommandOutput => {
+                if let Some((start, end)) = ctx.terminal().last_command_output() {
+                    let start = Point::new(start, Column(0));
+                    let end = Point::new(end, ctx.terminal().last_column());
+                    ctx.start_selection(SelectionType::Lines, start, Side::Left);
+                    ctx.update_selection(end, Side::Right);
+                }
+            },
             Action::ClearLogNotice => ctx.pop_message(),
-            Action::SpawnNewInstance => ctx.spawn_new_instance(),
+            Action::SpawnNewInstance(options) => ctx.spawn_new_instance(options),
+            Action::ExportSvg => ctx.export_svg(),
+            Action::Screenshot => ctx.screenshot(false),
+            Action::ScreenshotSelection => ctx.screenshot(true),
             Action::ReceiveChar | Action::None => (),
         }
     }
@@ -332,6 +478,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     #[inline]
     pub fn mouse_moved(&mut self, position: PhysicalPosition<f64>) {
         let size_info = self.ctx.size_info();
+        let grid_size_info = self.ctx.grid_size_info();
 
         let (x, y) = position.into();
 
@@ -342,21 +489,31 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         }
 
         let display_offset = self.ctx.terminal().grid().display_offset();
-        let old_point = self.ctx.mouse().point(&size_info, display_offset);
+        let old_point = self.ctx.mouse().point(&grid_size_info, display_offset);
+        let old_position = (self.ctx.mouse().x, self.ctx.mouse().y);
 
         let x = min(max(x, 0), size_info.width() as i32 - 1) as usize;
         let y = min(max(y, 0), size_info.height() as i32 - 1) as usize;
         self.ctx.mouse_mut().x = x;
         self.ctx.mouse_mut().y = y;
 
+        // Move the highlight ring along with the mouse cursor.
+        if self.ctx.config().ui_config.mouse.presentation.enabled {
+            self.ctx.mark_dirty();
+        }
+
         let inside_text_area = size_info.contains_point(x, y);
         let cell_side = self.cell_side(x);
 
-        let point = self.ctx.mouse().point(&size_info, display_offset);
+        let point = self.ctx.mouse().point(&grid_size_info, display_offset);
         let cell_changed = old_point != point;
 
+        // Pixel mouse reports are sent whenever the mouse moves, even within a cell.
+        let pixel_mouse = self.ctx.terminal().mode().contains(TermMode::SGR_PIXEL_MOUSE);
+        let report_changed = cell_changed || (pixel_mouse && old_position != (x, y));
+
         // If the mouse hasn't changed cells, do nothing.
-        if !cell_changed
+        if !report_changed
             && self.ctx.mouse().cell_side == cell_side
             && self.ctx.mouse().inside_text_area == inside_text_area
         {
@@ -378,8 +535,10 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
         if (lmb_pressed || rmb_pressed) && (self.ctx.modifiers().shift() || !self.ctx.mouse_mode())
         {
-            self.ctx.update_selection(point, cell_side);
-        } else if cell_changed
+            if self.drag_started(x, y) {
+                self.ctx.update_selection(point, cell_side);
+            }
+        } else if report_changed
             && self.ctx.terminal().mode().intersects(TermMode::MOUSE_MOTION | TermMode::MOUSE_DRAG)
         {
             if lmb_pressed {
@@ -394,17 +553,35 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         }
     }
 
+    /// Check if the mouse moved far enough from the last click to start dragging a selection.
+    fn drag_started(&mut self, x: usize, y: usize) -> bool {
+        let (click_x, click_y) = match self.ctx.mouse().drag_origin {
+            Some(origin) => origin,
+            None => return true,
+        };
+
+        let threshold = f64::from(self.ctx.config().ui_config.mouse.drag_threshold);
+        let (delta_x, delta_y) = (x as f64 - click_x as f64, y as f64 - click_y as f64);
+        if delta_x.hypot(delta_y) < threshold {
+            return false;
+        }
+
+        // Once started, the drag continues even if the mouse returns to its origin.
+        self.ctx.mouse_mut().drag_origin = None;
+
+        true
+    }
+
     /// Check which side of a cell an X coordinate lies on.
     fn cell_side(&self, x: usize) -> Side {
-        let size_info = self.ctx.size_info();
+        let size_info = self.ctx.grid_size_info();
 
-        let cell_x =
-            x.saturating_sub(size_info.padding_x() as usize) % size_info.cell_width() as usize;
-        let half_cell_width = (size_info.cell_width() / 2.0) as usize;
+        // The padding is negative while the grid is zoomed.
+        let cell_x = (x as f32 - size_info.padding_x().floor()).max(0.) % size_info.cell_width();
+        let half_cell_width = (size_info.cell_width() / 2.0).floor();
 
-        let additional_padding =
-            (size_info.width() - size_info.padding_x() * 2.) % size_info.cell_width();
-        let end_of_grid = size_info.width() - size_info.padding_x() - additional_padding;
+        let end_of_grid =
+            size_info.padding_x() + size_info.columns() as f32 * size_info.cell_width();
 
         if cell_x > half_cell_width
             // Edge case when mouse leaves the window.
@@ -418,7 +595,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
     fn mouse_report(&mut self, button: u8, state: ElementState) {
         let display_offset = self.ctx.terminal().grid().display_offset();
-        let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
+        let point = self.ctx.mouse().point(&self.ctx.grid_size_info(), display_offset);
 
         // Assure the mouse point is not in the scrollback.
         if point.line < 0 {
@@ -439,8 +616,13 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         }
 
         // Report mouse events.
-        if self.ctx.terminal().mode().contains(TermMode::SGR_MOUSE) {
-            self.sgr_mouse_report(point, button + mods, state);
+        let mode = *self.ctx.terminal().mode();
+        if mode.contains(TermMode::SGR_PIXEL_MOUSE) {
+            let (x, y) = self.ctx.mouse().pixel_position(&self.ctx.grid_size_info());
+            self.sgr_mouse_report(x + 1, y + 1, button + mods, state);
+        } else if mode.contains(TermMode::SGR_MOUSE) {
+            let (column, line) = (point.column.0 + 1, point.line.0 as usize + 1);
+            self.sgr_mouse_report(column, line, button + mods, state);
         } else if let ElementState::Released = state {
             self.normal_mouse_report(point, 3 + mods);
         } else {
@@ -482,17 +664,30 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         self.ctx.write_to_pty(msg);
     }
 
-    fn sgr_mouse_report(&mut self, point: Point, button: u8, state: ElementState) {
+    /// Report a mouse event at a one-based cell or pixel position.
+    fn sgr_mouse_report(&mut self, x: usize, y: usize, button: u8, state: ElementState) {
         let c = match state {
             ElementState::Pressed => 'M',
             ElementState::Released => 'm',
         };
 
-        let msg = format!("\x1b[<{};{};{}{}", button, point.column + 1, point.line + 1, c);
+        let msg = format!("\x1b[<{};{};{}{}", button, x, y, c);
         self.ctx.write_to_pty(msg.into_bytes());
     }
 
     fn on_mouse_press(&mut self, button: MouseButton) {
+        // Selections only follow the mouse once it moved beyond the drag threshold.
+        let (x, y) = (self.ctx.mouse().x, self.ctx.mouse().y);
+        self.ctx.mouse_mut().drag_origin = Some((x, y));
+
+        // Flash the clicked cell for presentations.
+        if self.ctx.config().ui_config.mouse.presentation.enabled {
+            let display_offset = self.ctx.terminal().grid().display_offset();
+            let point = self.ctx.mouse().point(&self.ctx.grid_size_info(), display_offset);
+            self.ctx.display().pointer_highlight.click(point);
+            self.ctx.mark_dirty();
+        }
+
         // Handle mouse mode.
         if !self.ctx.modifiers().shift() && self.ctx.mouse_mode() {
             self.ctx.mouse_mut().click_state = ClickState::None;
@@ -531,7 +726,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
             // Load mouse point, treating message bar and padding as the closest cell.
             let display_offset = self.ctx.terminal().grid().display_offset();
-            let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
+            let point = self.ctx.mouse().point(&self.ctx.grid_size_info(), display_offset);
 
             match button {
                 MouseButton::Left => self.on_left_click(point),
@@ -614,6 +809,9 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     }
 
     fn on_mouse_release(&mut self, button: MouseButton) {
+        // Forget the origin of drags which never started.
+        self.ctx.mouse_mut().drag_origin = None;
+
         if !self.ctx.modifiers().shift() && self.ctx.mouse_mode() {
             let code = match button {
                 MouseButton::Left => 0,
@@ -667,7 +865,12 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             self.ctx.mouse_mut().scroll_px += new_scroll_px;
 
             let code = if new_scroll_px > 0. { 64 } else { 65 };
-            let lines = (self.ctx.mouse().scroll_px / height).abs() as i32;
+            // Skip scrolling while the application is not reading its input.
+            let lines = if self.ctx.write_queue_full() {
+                0
+            } else {
+                (self.ctx.mouse().scroll_px / height).abs() as i32
+            };
 
             for _ in 0..lines {
                 self.mouse_report(code, ElementState::Pressed);
@@ -683,7 +886,12 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             self.ctx.mouse_mut().scroll_px += new_scroll_px * multiplier;
 
             let cmd = if new_scroll_px > 0. { b'A' } else { b'B' };
-            let lines = (self.ctx.mouse().scroll_px / height).abs() as i32;
+            // Skip scrolling while the application is not reading its input.
+            let lines = if self.ctx.write_queue_full() {
+                0
+            } else {
+                (self.ctx.mouse().scroll_px / height).abs() as i32
+            };
 
             let mut content = Vec::with_capacity(lines as usize * 3);
             for _ in 0..lines {
@@ -767,19 +975,106 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             return;
         }
 
-        // Reset search delay when the user is still typing.
-        if self.ctx.search_active() {
-            if let Some(timer) = self.ctx.scheduler_mut().get_mut(TimerId::DelayedSearch) {
-                timer.deadline = Instant::now() + TYPING_SEARCH_DELAY;
-            }
+        // Keys are passed as characters to vi actions waiting for them.
+        if self.ctx.vi_input().pending.is_some() {
+            *self.ctx.suppress_chars() = false;
+            return;
         }
 
         match input.state {
             ElementState::Pressed => {
                 *self.ctx.received_count() = 0;
-                self.process_key_bindings(input);
+
+                let repeat = input.virtual_keycode.is_some()
+                    && *self.ctx.pressed_key() == input.virtual_keycode;
+                let event_type = if repeat { KeyEventType::Repeat } else { KeyEventType::Press };
+                let sequence = self.key_sequence(input, event_type);
+
+                self.process_key_bindings(input, sequence.is_some());
+
+                // Releasing keys which triggered a binding is not reported either.
+                if let (Some(key), true) = (input.virtual_keycode, *self.ctx.suppress_chars()) {
+                    self.ctx.bound_keys().insert(key);
+                }
+
+                // Keys are only reported if they did not trigger any binding.
+                if let (Some(sequence), false) = (sequence, *self.ctx.suppress_chars()) {
+                    self.ctx.on_typing_start();
+                    self.ctx.scroll(Scroll::Bottom);
+                    self.ctx.clear_selection();
+
+                    // The reported key replaces its text.
+                    *self.ctx.suppress_chars() = true;
+                    self.ctx.write_to_pty(sequence.into_bytes());
+                }
+
+                *self.ctx.pressed_key() = input.virtual_keycode;
+            },
+            ElementState::Released => {
+                *self.ctx.suppress_chars() = false;
+
+                if *self.ctx.pressed_key() == input.virtual_keycode {
+                    *self.ctx.pressed_key() = None;
+                }
+
+                let bound =
+                    input.virtual_keycode.map_or(false, |key| self.ctx.bound_keys().remove(&key));
+                if bound {
+                    return;
+                }
+
+                if let Some(sequence) = self.key_sequence(input, KeyEventType::Release) {
+                    self.ctx.write_to_pty(sequence.into_bytes());
+                }
+            },
+        }
+    }
+
+    /// Handle characters used as vi mode count, text object type or mark name.
+    fn vi_char_input(&mut self, c: char) {
+        let vi_input = self.ctx.vi_input();
+        let count = vi_input.count.take();
+
+        match vi_input.pending.take() {
+            Some(ViPending::TextObject(scope)) => {
+                if let Some(object) = TextObject::from_char(c) {
+                    self.ctx.terminal_mut().vi_select_text_object(object, scope);
+                    self.ctx.mark_dirty();
+                }
+            },
+            Some(ViPending::SetMark) => self.ctx.set_vi_mark(c),
+            Some(ViPending::JumpToMark { exact }) => self.ctx.jump_to_vi_mark(c, exact),
+            None => {
+                let digit = c.to_digit(10).filter(|&digit| digit != 0 || count.is_some());
+                if let Some(digit) = digit {
+                    let count = count.unwrap_or(0) * 10 + digit as usize;
+                    vi_input.count = Some(min(count, MAX_VI_COUNT));
+                }
             },
-            ElementState::Released => *self.ctx.suppress_chars() = false,
+        }
+    }
+
+    /// Escape sequence reporting a key event, based on the keyboard protocol requested by the
+    /// application.
+    ///
+    /// Returns `None` if the key is not reported, in which case the key bindings and received
+    /// characters are used.
+    fn key_sequence(&mut self, input: KeyboardInput, event_type: KeyEventType) -> Option<String> {
+        let mode = *self.ctx.terminal().mode();
+        if mode.contains(TermMode::VI) || self.ctx.search_active() {
+            return None;
+        }
+
+        let key = input.virtual_keycode?;
+        let mods = *self.ctx.modifiers();
+
+        if mode.intersects(TermMode::KITTY_KEYBOARD_PROTOCOL) {
+            keyboard::kitty_sequence(key, mods, event_type, mode)
+        } else if event_type != KeyEventType::Release {
+            let modify_other_keys = self.ctx.terminal().modify_other_keys();
+            keyboard::modify_other_keys_sequence(key, mods, modify_other_keys)
+        } else {
+            None
         }
     }
 
@@ -814,6 +1109,11 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
         // Pass keys to search and ignore them during `suppress_chars`.
         let search_active = self.ctx.search_active();
+        if !suppress_chars && !search_active && self.ctx.terminal().mode().contains(TermMode::VI) {
+            self.vi_char_input(c);
+            return;
+        }
+
         if suppress_chars || search_active || self.ctx.terminal().mode().contains(TermMode::VI) {
             if search_active && !suppress_chars {
                 self.ctx.search_input(c);
@@ -822,6 +1122,14 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             return;
         }
 
+        // Handle XOFF and XON locally, without passing them to the PTY.
+        if self.ctx.config().ui_config.keyboard.flow_control == FlowControl::Local
+            && (c == XOFF || c == XON)
+        {
+            self.ctx.set_output_paused(c == XOFF);
+            return;
+        }
+
         self.ctx.on_typing_start();
 
         self.ctx.scroll(Scroll::Bottom);
@@ -847,18 +1155,36 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         *self.ctx.received_count() += 1;
     }
 
+    /// Current mode used for matching bindings.
+    fn binding_mode(&mut self) -> BindingMode {
+        let terminal = self.ctx.terminal();
+        let mode = *terminal.mode();
+        let selection = terminal.selection.is_some();
+        let search = self.ctx.search_active();
+        let count = self.ctx.vi_input().count.is_some();
+
+        BindingMode::new(&mode, search, selection, count)
+    }
+
     /// Attempt to find a binding and execute its action.
     ///
     /// The provided mode, mods, and key must match what is allowed by a binding
-    /// for its action to be executed.
-    fn process_key_bindings(&mut self, input: KeyboardInput) {
-        let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
+    /// for its action to be executed. Default bindings sending escape sequences are skipped when
+    /// the key is `reported` using the application's keyboard protocol.
+    fn process_key_bindings(&mut self, input: KeyboardInput, reported: bool) {
+        let mode = self.binding_mode();
         let mods = *self.ctx.modifiers();
         let mut suppress_chars = None;
 
+        let configured = self.ctx.config().ui_config.configured_key_bindings();
         for i in 0..self.ctx.config().ui_config.key_bindings().len() {
             let binding = &self.ctx.config().ui_config.key_bindings()[i];
 
+            // Default escape sequences are replaced by the application's keyboard protocol.
+            if reported && i >= configured && matches!(binding.action, Action::Esc(_)) {
+                continue;
+            }
+
             let key = match (binding.trigger, input.virtual_keycode) {
                 (Key::Scancode(_), _) => Key::Scancode(input.scancode),
                 (_, Some(key)) => Key::Keycode(key),
@@ -874,6 +1200,11 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             }
         }
 
+        // Counts only apply to the first binding after them.
+        if suppress_chars.is_some() {
+            self.ctx.vi_input().count = None;
+        }
+
         // Don't suppress char if no bindings were triggered.
         *self.ctx.suppress_chars() = suppress_chars.unwrap_or(false);
     }
@@ -883,7 +1214,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     /// The provided mode, mods, and key must match what is allowed by a binding
     /// for its action to be executed.
     fn process_mouse_bindings(&mut self, button: MouseButton) {
-        let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
+        let mode = self.binding_mode();
         let mouse_mode = self.ctx.mouse_mode();
         let mods = *self.ctx.modifiers();
 
@@ -929,7 +1260,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     /// Icon state of the cursor.
     fn cursor_state(&mut self) -> CursorIcon {
         let display_offset = self.ctx.terminal().grid().display_offset();
-        let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
+        let point = self.ctx.mouse().point(&self.ctx.grid_size_info(), display_offset);
 
         // Function to check if mouse is on top of a hint.
         let hint_highlighted = |hint: &HintMatch| hint.bounds.contains(&point);
@@ -993,9 +1324,10 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 mod tests {
     use super::*;
 
-    use glutin::event::{Event as GlutinEvent, VirtualKeyCode, WindowEvent};
+    use glutin::event::{Event as GlutinEvent, WindowEvent};
 
     use alacritty_terminal::event::Event as TerminalEvent;
+    use alacritty_terminal::selection::Selection;
 
     use crate::config::Binding;
     use crate::message_bar::MessageBuffer;
@@ -1012,7 +1344,10 @@ mod tests {
         pub clipboard: &'a mut Clipboard,
         pub message_buffer: &'a mut MessageBuffer,
         pub received_count: usize,
+        pub vi_input: ViInput,
         pub suppress_chars: bool,
+        pub pressed_key: Option<VirtualKeyCode>,
+        pub bound_keys: HashSet<VirtualKeyCode>,
         pub modifiers: ModifiersState,
         config: &'a Config,
     }
@@ -1073,10 +1408,22 @@ mod tests {
             &mut self.received_count
         }
 
+        fn vi_input(&mut self) -> &mut ViInput {
+            &mut self.vi_input
+        }
+
         fn suppress_chars(&mut self) -> &mut bool {
             &mut self.suppress_chars
         }
 
+        fn pressed_key(&mut self) -> &mut Option<VirtualKeyCode> {
+            &mut self.pressed_key
+        }
+
+        fn bound_keys(&mut self) -> &mut HashSet<VirtualKeyCode> {
+            &mut self.bound_keys
+        }
+
         fn modifiers(&mut self) -> &mut ModifiersState {
             &mut self.modifiers
         }
@@ -1151,7 +1498,10 @@ mod tests {
                     size_info: &size,
                     clipboard: &mut clipboard,
                     received_count: 0,
+                    vi_input: Default::default(),
                     suppress_chars: false,
+                    pressed_key: None,
+                    bound_keys: Default::default(),
                     modifiers: Default::default(),
                     message_buffer: &mut message_buffer,
                     config: &cfg,
@@ -1292,6 +1642,140 @@ mod tests {
         end_state: ClickState::Click,
     }
 
+    #[test]
+    fn vi_mode_mouse_moves_cursor() {
+        let mut clipboard = Clipboard::new_nop();
+        let cfg = Config::default();
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0., 0., false);
+
+        let mut terminal = Term::new(&cfg, size, MockEventProxy);
+        terminal.toggle_vi_mode();
+
+        let mut mouse = Mouse { x: 7, y: 4, ..Mouse::default() };
+        let mut message_buffer = MessageBuffer::new();
+
+        let context = ActionContext {
+            terminal: &mut terminal,
+            mouse: &mut mouse,
+            size_info: &size,
+            clipboard: &mut clipboard,
+            received_count: 0,
+            vi_input: Default::default(),
+            suppress_chars: false,
+            pressed_key: None,
+            bound_keys: Default::default(),
+            modifiers: Default::default(),
+            message_buffer: &mut message_buffer,
+            config: &cfg,
+        };
+        let mut processor = Processor::new(context);
+
+        // Left clicks move the vi mode cursor to the clicked cell.
+        processor.mouse_input(ElementState::Pressed, MouseButton::Left);
+        let point = Point::new(Line(1), Column(2));
+        assert_eq!(processor.ctx.terminal.vi_mode_cursor.point, point);
+
+        // Expanding the vi selection with right clicks moves the cursor to the new end.
+        let selection = Selection::new(SelectionType::Simple, point, Side::Left);
+        processor.ctx.terminal.selection = Some(selection);
+        processor.ctx.mouse.x = 13;
+        processor.ctx.mouse.y = 10;
+        processor.mouse_input(ElementState::Pressed, MouseButton::Right);
+        let point = Point::new(Line(3), Column(4));
+        assert_eq!(processor.ctx.terminal.vi_mode_cursor.point, point);
+    }
+
+    #[test]
+    fn click_thresholds() {
+        let mut clipboard = Clipboard::new_nop();
+        let mut cfg = Config::default();
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0., 0., false);
+        let mut terminal = Term::new(&cfg, size, MockEventProxy);
+        let mut mouse = Mouse::default();
+        let mut message_buffer = MessageBuffer::new();
+
+        let mut click_states = |cfg: &Config| {
+            let context = ActionContext {
+                terminal: &mut terminal,
+                mouse: &mut mouse,
+                size_info: &size,
+                clipboard: &mut clipboard,
+                received_count: 0,
+                vi_input: Default::default(),
+                suppress_chars: false,
+                pressed_key: None,
+                bound_keys: Default::default(),
+                modifiers: Default::default(),
+                message_buffer: &mut message_buffer,
+                config: cfg,
+            };
+            let mut processor = Processor::new(context);
+
+            (0..3)
+                .map(|_| {
+                    processor.mouse_input(ElementState::Pressed, MouseButton::Left);
+                    processor.ctx.mouse.click_state
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let expected = [ClickState::Click, ClickState::DoubleClick, ClickState::TripleClick];
+        assert_eq!(click_states(&cfg), expected);
+
+        let mouse = "{ double_click: { threshold: 0 }, triple_click: { threshold: 0 } }";
+        cfg.ui_config.mouse = serde_yaml::from_str(mouse).unwrap();
+        assert_eq!(click_states(&cfg), [ClickState::Click; 3]);
+    }
+
+    #[test]
+    fn drag_threshold() {
+        let mut clipboard = Clipboard::new_nop();
+        let mut cfg = Config::default();
+        cfg.ui_config.mouse.drag_threshold = 5;
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0., 0., false);
+        let mut terminal = Term::new(&cfg, size, MockEventProxy);
+        let mut mouse = Mouse { x: 10, y: 10, ..Mouse::default() };
+        let mut message_buffer = MessageBuffer::new();
+
+        let context = ActionContext {
+            terminal: &mut terminal,
+            mouse: &mut mouse,
+            size_info: &size,
+            clipboard: &mut clipboard,
+            received_count: 0,
+            vi_input: Default::default(),
+            suppress_chars: false,
+            pressed_key: None,
+            bound_keys: Default::default(),
+            modifiers: Default::default(),
+            message_buffer: &mut message_buffer,
+            config: &cfg,
+        };
+        let mut processor = Processor::new(context);
+
+        processor.mouse_input(ElementState::Pressed, MouseButton::Left);
+        assert_eq!(processor.ctx.mouse.drag_origin, Some((10, 10)));
+
+        assert!(!processor.drag_started(12, 13));
+        assert!(processor.drag_started(14, 14));
+
+        // Once started, the drag continues even if the mouse returns to its origin.
+        assert!(processor.drag_started(10, 10));
+    }
+
+    #[test]
+    fn mouse_point_while_zoomed() {
+        // Zoomed grids start outside of the window, resulting in negative padding.
+        let size = SizeInfo::new(24., 44., 5., 10., 2., 2., false).magnify(2., 2., 10., 20.);
+
+        let mouse = Mouse { x: 0, y: 0, ..Mouse::default() };
+        assert_eq!(mouse.point(&size, 0), Point::new(Line(0), Column(0)));
+        assert_eq!(mouse.pixel_position(&size), (6, 16));
+
+        let mouse = Mouse { x: 20, y: 40, ..Mouse::default() };
+        assert_eq!(mouse.point(&size, 0), Point::new(Line(2), Column(2)));
+    }
+
     test_process_binding! {
         name: process_binding_nomode_shiftmod_require_shift,
         binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
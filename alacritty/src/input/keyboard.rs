@@ -0,0 +1,422 @@
+//! Key encoding for the kitty keyboard protocol.
+
+use glutin::event::{ModifiersState, VirtualKeyCode};
+
+use alacritty_terminal::ansi::ModifyOtherKeys;
+use alacritty_terminal::term::TermMode;
+
+/// Kind of key event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyEventType {
+    Press,
+    Repeat,
+    Release,
+}
+
+impl KeyEventType {
+    /// Event type number used in the escape sequence.
+    fn code(self) -> u8 {
+        match self {
+            KeyEventType::Press => 1,
+            KeyEventType::Repeat => 2,
+            KeyEventType::Release => 3,
+        }
+    }
+}
+
+/// Key identity used by the kitty keyboard protocol.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum KittyKey {
+    /// Key producing text, identified by its unshifted character.
+    Text(char),
+
+    /// Key without text, identified by its number and the final byte of its escape sequence.
+    Functional(u32, char),
+}
+
+/// Encode a key event according to the active kitty keyboard protocol modes.
+///
+/// Returns `None` if the key is not reported by the protocol, in which case text input is
+/// handled like it is without the protocol.
+pub fn kitty_sequence(
+    key: VirtualKeyCode,
+    mods: ModifiersState,
+    event_type: KeyEventType,
+    mode: TermMode,
+) -> Option<String> {
+    let report_all = mode.contains(TermMode::REPORT_ALL_KEYS_AS_ESC);
+
+    // Without event types, repeats are indistinguishable from presses.
+    let event_type = match event_type {
+        _ if mode.contains(TermMode::REPORT_EVENT_TYPES) => event_type,
+        KeyEventType::Release => return None,
+        _ => KeyEventType::Press,
+    };
+
+    let kitty_key = kitty_key(key, report_all)?;
+
+    if !report_all {
+        let legacy_mods = mods & !ModifiersState::SHIFT;
+        match kitty_key {
+            // Text is sent as is, unless it is modified by anything other than shift.
+            KittyKey::Text(_) if legacy_mods.is_empty() && event_type != KeyEventType::Release => {
+                return None;
+            },
+            // Enter, Tab and Backspace keep their legacy encoding, to keep the shell usable.
+            KittyKey::Functional(code, 'u') if mods.is_empty() && [9, 13, 127].contains(&code) => {
+                return match event_type {
+                    KeyEventType::Release => None,
+                    _ => Some((code as u8 as char).to_string()),
+                };
+            },
+            _ => (),
+        }
+    }
+
+    let (number, alternate, terminator) = match kitty_key {
+        KittyKey::Text(c) => {
+            let shifted = if mods.shift() && mode.contains(TermMode::REPORT_ALTERNATE_KEYS) {
+                Some(shifted_char(c)).filter(|&shifted| shifted != c)
+            } else {
+                None
+            };
+            (c as u32, shifted, 'u')
+        },
+        KittyKey::Functional(number, terminator) => (number, None, terminator),
+    };
+
+    let mut parameters = String::new();
+    let modifiers = modifiers_code(mods);
+    if modifiers != 0 || event_type != KeyEventType::Press {
+        parameters.push_str(&format!(";{}", modifiers + 1));
+        if event_type != KeyEventType::Press {
+            parameters.push_str(&format!(":{}", event_type.code()));
+        }
+    }
+
+    let mut sequence = String::from("\x1b[");
+
+    // The key number `1` is implied for keys like the arrows without any other parameters.
+    if number != 1 || terminator == '~' || !parameters.is_empty() {
+        sequence.push_str(&number.to_string());
+    }
+    if let Some(shifted) = alternate {
+        sequence.push_str(&format!(":{}", shifted as u32));
+    }
+
+    sequence.push_str(&parameters);
+    sequence.push(terminator);
+
+    Some(sequence)
+}
+
+/// Encode a key press according to xterm's `modifyOtherKeys`.
+///
+/// Returns `None` if the key uses its regular encoding.
+pub fn modify_other_keys_sequence(
+    key: VirtualKeyCode,
+    mods: ModifiersState,
+    mode: ModifyOtherKeys,
+) -> Option<String> {
+    let (code, text) = match kitty_key(key, false)? {
+        KittyKey::Text(c) if mods.shift() => (shifted_char(c) as u32, true),
+        KittyKey::Text(c) => (c as u32, true),
+        KittyKey::Functional(code, 'u') if [9, 13, 27, 127].contains(&code) => (code, false),
+        KittyKey::Functional(..) => return None,
+    };
+
+    let encode = match mode {
+        ModifyOtherKeys::Reset => false,
+        // Text modified only by shift is sent as is.
+        ModifyOtherKeys::EnableAll => !mods.is_empty() && (!text || mods != ModifiersState::SHIFT),
+        // Keys with a control character, like `Ctrl+A` or `Ctrl+[`, keep their encoding.
+        ModifyOtherKeys::EnableExceptWellDefined => {
+            let control_char =
+                (code as u8 as char).is_ascii_lowercase() || [32, 91, 92, 93, 127].contains(&code);
+            mods.ctrl() && (mods.shift() || mods.logo() || !control_char)
+        },
+    };
+
+    if encode {
+        Some(format!("\x1b[27;{};{}~", modifiers_code(mods) + 1, code))
+    } else {
+        None
+    }
+}
+
+/// Character produced by a text key while shift is held.
+///
+/// Key codes do not carry the keyboard layout, so the US layout is assumed.
+fn shifted_char(c: char) -> char {
+    match c {
+        '1' => '!',
+        '2' => '@',
+        '3' => '#',
+        '4' => '$',
+        '5' => '%',
+        '6' => '^',
+        '7' => '&',
+        '8' => '*',
+        '9' => '(',
+        '0' => ')',
+        '\'' => '"',
+        '\\' => '|',
+        ',' => '<',
+        '=' => '+',
+        '`' => '~',
+        '[' => '{',
+        '-' => '_',
+        '.' => '>',
+        ']' => '}',
+        ';' => ':',
+        '/' => '?',
+        c => c.to_ascii_uppercase(),
+    }
+}
+
+/// Bit mask of the active modifiers.
+fn modifiers_code(mods: ModifiersState) -> u8 {
+    let mut code = 0;
+    if mods.shift() {
+        code |= 0b0001;
+    }
+    if mods.alt() {
+        code |= 0b0010;
+    }
+    if mods.ctrl() {
+        code |= 0b0100;
+    }
+    if mods.logo() {
+        code |= 0b1000;
+    }
+    code
+}
+
+/// Kitty keyboard protocol identity of a key.
+///
+/// Modifiers, lock keys and the keypad are only reported when all keys are reported as escapes.
+fn kitty_key(key: VirtualKeyCode, report_all: bool) -> Option<KittyKey> {
+    use VirtualKeyCode::*;
+
+    let text = match key {
+        A => 'a',
+        B => 'b',
+        C => 'c',
+        D => 'd',
+        E => 'e',
+        F => 'f',
+        G => 'g',
+        H => 'h',
+        I => 'i',
+        J => 'j',
+        K => 'k',
+        L => 'l',
+        M => 'm',
+        N => 'n',
+        O => 'o',
+        P => 'p',
+        Q => 'q',
+        R => 'r',
+        S => 's',
+        T => 't',
+        U => 'u',
+        V => 'v',
+        W => 'w',
+        X => 'x',
+        Y => 'y',
+        Z => 'z',
+        Key0 => '0',
+        Key1 => '1',
+        Key2 => '2',
+        Key3 => '3',
+        Key4 => '4',
+        Key5 => '5',
+        Key6 => '6',
+        Key7 => '7',
+        Key8 => '8',
+        Key9 => '9',
+        Space => ' ',
+        Apostrophe => '\'',
+        Backslash => '\\',
+        Comma => ',',
+        Equals => '=',
+        Grave => '`',
+        LBracket => '[',
+        Minus => '-',
+        Period => '.',
+        RBracket => ']',
+        Semicolon => ';',
+        Slash => '/',
+        _ => return kitty_functional_key(key, report_all),
+    };
+
+    Some(KittyKey::Text(text))
+}
+
+/// Kitty keyboard protocol identity of a key without text.
+fn kitty_functional_key(key: VirtualKeyCode, report_all: bool) -> Option<KittyKey> {
+    use VirtualKeyCode::*;
+
+    let (number, terminator) = match key {
+        Escape => (27, 'u'),
+        Return => (13, 'u'),
+        Tab => (9, 'u'),
+        Back => (127, 'u'),
+        Insert => (2, '~'),
+        Delete => (3, '~'),
+        Left => (1, 'D'),
+        Right => (1, 'C'),
+        Up => (1, 'A'),
+        Down => (1, 'B'),
+        PageUp => (5, '~'),
+        PageDown => (6, '~'),
+        Home => (1, 'H'),
+        End => (1, 'F'),
+        F1 => (1, 'P'),
+        F2 => (1, 'Q'),
+        F3 => (13, '~'),
+        F4 => (1, 'S'),
+        F5 => (15, '~'),
+        F6 => (17, '~'),
+        F7 => (18, '~'),
+        F8 => (19, '~'),
+        F9 => (20, '~'),
+        F10 => (21, '~'),
+        F11 => (23, '~'),
+        F12 => (24, '~'),
+        F13 => (57376, 'u'),
+        F14 => (57377, 'u'),
+        F15 => (57378, 'u'),
+        F16 => (57379, 'u'),
+        F17 => (57380, 'u'),
+        F18 => (57381, 'u'),
+        F19 => (57382, 'u'),
+        F20 => (57383, 'u'),
+        F21 => (57384, 'u'),
+        F22 => (57385, 'u'),
+        F23 => (57386, 'u'),
+        F24 => (57387, 'u'),
+        NumpadEnter => (57414, 'u'),
+        _ if report_all => match key {
+            Capital => (57358, 'u'),
+            Scroll => (57359, 'u'),
+            Numlock => (57360, 'u'),
+            Snapshot => (57361, 'u'),
+            Pause => (57362, 'u'),
+            Numpad0 => (57399, 'u'),
+            Numpad1 => (57400, 'u'),
+            Numpad2 => (57401, 'u'),
+            Numpad3 => (57402, 'u'),
+            Numpad4 => (57403, 'u'),
+            Numpad5 => (57404, 'u'),
+            Numpad6 => (57405, 'u'),
+            Numpad7 => (57406, 'u'),
+            Numpad8 => (57407, 'u'),
+            Numpad9 => (57408, 'u'),
+            NumpadDecimal => (57409, 'u'),
+            NumpadDivide => (57410, 'u'),
+            NumpadMultiply => (57411, 'u'),
+            NumpadSubtract => (57412, 'u'),
+            NumpadAdd => (57413, 'u'),
+            NumpadEquals => (57415, 'u'),
+            LShift => (57441, 'u'),
+            LControl => (57442, 'u'),
+            LAlt => (57443, 'u'),
+            LWin => (57444, 'u'),
+            RShift => (57447, 'u'),
+            RControl => (57448, 'u'),
+            RAlt => (57449, 'u'),
+            RWin => (57450, 'u'),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(KittyKey::Functional(number, terminator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(key: VirtualKeyCode, mods: ModifiersState, mode: TermMode) -> Option<String> {
+        kitty_sequence(key, mods, KeyEventType::Press, mode)
+    }
+
+    #[test]
+    fn disambiguate_escape_codes() {
+        let mode = TermMode::DISAMBIGUATE_ESC_CODES;
+        let none = ModifiersState::empty();
+
+        assert_eq!(press(VirtualKeyCode::A, none, mode), None);
+        assert_eq!(press(VirtualKeyCode::A, ModifiersState::SHIFT, mode), None);
+        assert_eq!(press(VirtualKeyCode::Tab, none, mode), Some("\t".into()));
+        let ctrl = ModifiersState::CTRL;
+        assert_eq!(press(VirtualKeyCode::I, ctrl, mode), Some("\x1b[105;5u".into()));
+        assert_eq!(press(VirtualKeyCode::Escape, none, mode), Some("\x1b[27u".into()));
+        assert_eq!(press(VirtualKeyCode::Up, none, mode), Some("\x1b[A".into()));
+        assert_eq!(press(VirtualKeyCode::Up, ModifiersState::ALT, mode), Some("\x1b[1;3A".into()));
+        assert_eq!(press(VirtualKeyCode::Delete, none, mode), Some("\x1b[3~".into()));
+        assert_eq!(press(VirtualKeyCode::LShift, none, mode), None);
+    }
+
+    #[test]
+    fn report_event_types() {
+        let mode = TermMode::DISAMBIGUATE_ESC_CODES | TermMode::REPORT_EVENT_TYPES;
+        let none = ModifiersState::empty();
+
+        let release = kitty_sequence(VirtualKeyCode::A, none, KeyEventType::Release, mode);
+        assert_eq!(release, Some("\x1b[97;1:3u".into()));
+        let repeat = kitty_sequence(VirtualKeyCode::Left, none, KeyEventType::Repeat, mode);
+        assert_eq!(repeat, Some("\x1b[1;1:2D".into()));
+        let release = kitty_sequence(VirtualKeyCode::Return, none, KeyEventType::Release, mode);
+        assert_eq!(release, None);
+
+        // Releases are not reported without event types.
+        let mode = TermMode::DISAMBIGUATE_ESC_CODES;
+        let release = kitty_sequence(VirtualKeyCode::Escape, none, KeyEventType::Release, mode);
+        assert_eq!(release, None);
+    }
+
+    #[test]
+    fn modify_other_keys() {
+        let ctrl = ModifiersState::CTRL;
+        let ctrl_shift = ModifiersState::CTRL | ModifiersState::SHIFT;
+
+        let mode = ModifyOtherKeys::EnableExceptWellDefined;
+        let sequence = |key, mods| modify_other_keys_sequence(key, mods, mode);
+        assert_eq!(sequence(VirtualKeyCode::A, ctrl), None);
+        assert_eq!(sequence(VirtualKeyCode::A, ctrl_shift), Some("\x1b[27;6;65~".into()));
+        assert_eq!(sequence(VirtualKeyCode::Key1, ctrl), Some("\x1b[27;5;49~".into()));
+        assert_eq!(sequence(VirtualKeyCode::Key1, ctrl_shift), Some("\x1b[27;6;33~".into()));
+        assert_eq!(sequence(VirtualKeyCode::Slash, ctrl_shift), Some("\x1b[27;6;63~".into()));
+        assert_eq!(sequence(VirtualKeyCode::Tab, ctrl), Some("\x1b[27;5;9~".into()));
+        assert_eq!(sequence(VirtualKeyCode::Return, ModifiersState::ALT), None);
+
+        let mode = ModifyOtherKeys::EnableAll;
+        let sequence = |key, mods| modify_other_keys_sequence(key, mods, mode);
+        assert_eq!(sequence(VirtualKeyCode::A, ctrl), Some("\x1b[27;5;97~".into()));
+        assert_eq!(sequence(VirtualKeyCode::A, ModifiersState::SHIFT), None);
+        assert_eq!(
+            sequence(VirtualKeyCode::Return, ModifiersState::SHIFT),
+            Some("\x1b[27;2;13~".into())
+        );
+        assert_eq!(sequence(VirtualKeyCode::Up, ctrl), None);
+
+        let mode = ModifyOtherKeys::Reset;
+        assert_eq!(modify_other_keys_sequence(VirtualKeyCode::Key1, ctrl, mode), None);
+    }
+
+    #[test]
+    fn report_all_keys() {
+        let mode = TermMode::REPORT_ALL_KEYS_AS_ESC | TermMode::REPORT_ALTERNATE_KEYS;
+        let none = ModifiersState::empty();
+
+        assert_eq!(press(VirtualKeyCode::A, none, mode), Some("\x1b[97u".into()));
+        let shift = ModifiersState::SHIFT;
+        assert_eq!(press(VirtualKeyCode::A, shift, mode), Some("\x1b[97:65;2u".into()));
+        assert_eq!(press(VirtualKeyCode::Key2, shift, mode), Some("\x1b[50:64;2u".into()));
+        assert_eq!(press(VirtualKeyCode::Return, none, mode), Some("\x1b[13u".into()));
+        assert_eq!(press(VirtualKeyCode::LShift, none, mode), Some("\x1b[57441u".into()));
+    }
+}
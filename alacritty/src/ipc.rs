@@ -0,0 +1,421 @@
+//! Alacritty socket IPC.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+use std::{env, fs, process};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json as json;
+
+use alacritty_terminal::ansi::Handler;
+use alacritty_terminal::event::Event as TerminalEvent;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Boundary, Column, Line, Point, Side};
+use alacritty_terminal::selection::SelectionType;
+use alacritty_terminal::sync::FairMutex;
+use alacritty_terminal::term::{Term, TermMode};
+use alacritty_terminal::thread;
+
+use crate::cli::MessageOptions;
+use crate::daemon;
+use crate::event::{Event, EventProxy, FontSizeChange};
+
+/// Environment variable name for the IPC socket path.
+const ALACRITTY_SOCKET_ENV: &str = "ALACRITTY_SOCKET";
+
+/// Environment variable name for the IPC window ID.
+const ALACRITTY_WINDOW_ID_ENV: &str = "ALACRITTY_WINDOW_ID";
+
+/// File name prefix of all IPC sockets.
+const SOCKET_PREFIX: &str = "Alacritty-";
+
+/// Maximum time waiting for the event loop to answer a request.
+const EVENT_LOOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Messages which can be sent to a running Alacritty instance.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SocketMessage {
+    /// Request the text of the terminal.
+    GetText {
+        /// Include the scrollback history instead of only the viewport.
+        history: bool,
+
+        /// Preserve colors and text attributes using ANSI escapes.
+        escapes: bool,
+    },
+
+    /// Request the terminal state as JSON.
+    GetState,
+
+    /// Select the text between two points, replacing the active selection.
+    Select {
+        /// Kind of selection used for expanding the points.
+        ty: SelectionType,
+
+        /// First selected cell.
+        start: Point,
+
+        /// Last selected cell.
+        end: Point,
+    },
+
+    /// Request the text of the active selection.
+    GetSelection,
+
+    /// Remove the active selection.
+    ClearSelection,
+
+    /// Request the name of the window.
+    GetName,
+
+    /// Change the name of the window.
+    SetName(Option<String>),
+
+    /// Change the font size of the window.
+    FontSize(FontSizeChange),
+
+    /// Request memory usage estimates as JSON.
+    GetStats,
+
+    /// Free memory which is not required for the current terminal content.
+    Trim,
+
+    /// Save the window as PNG image.
+    Screenshot {
+        /// Only save the active selection.
+        selection: bool,
+    },
+}
+
+/// Windows which should receive an IPC message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowTarget {
+    /// Window with the specified ID.
+    Id(u32),
+
+    /// All windows with the specified name.
+    Name(String),
+
+    /// All windows.
+    All,
+}
+
+/// Terminal state reported over IPC.
+#[derive(Serialize, Debug)]
+struct TerminalState<'a> {
+    window_id: u32,
+    name: Option<&'a str>,
+    columns: usize,
+    screen_lines: usize,
+    history_size: usize,
+    display_offset: usize,
+    alternate_screen: bool,
+    cursor: Point,
+    title: Option<&'a str>,
+    cwd: Option<PathBuf>,
+    /// PID of the shell.
+    pid: Option<libc::pid_t>,
+    /// PID of the foreground process group, which is the shell while no command is running.
+    foreground_pid: Option<libc::pid_t>,
+}
+
+impl<'a> TerminalState<'a> {
+    fn new(terminal: &'a Term<EventProxy>) -> Self {
+        let grid = terminal.grid();
+
+        Self {
+            window_id: process::id(),
+            name: terminal.window_name(),
+            columns: grid.columns(),
+            screen_lines: grid.screen_lines(),
+            history_size: grid.history_size(),
+            display_offset: grid.display_offset(),
+            alternate_screen: terminal.mode().contains(TermMode::ALT_SCREEN),
+            cursor: grid.cursor.point,
+            title: terminal.title(),
+            cwd: daemon::foreground_process_path(),
+            pid: daemon::shell_pid(),
+            foreground_pid: daemon::foreground_process_pid(),
+        }
+    }
+}
+
+/// Glyph cache statistics collected by the event loop.
+#[derive(Serialize, Debug, Copy, Clone)]
+pub struct GlyphStats {
+    /// Number of glyphs loaded into the atlas.
+    pub glyphs: usize,
+
+    /// Estimated GPU memory used by the glyph atlas in bytes.
+    pub atlas_size: usize,
+}
+
+/// Memory usage estimates reported over IPC, all sizes are in bytes.
+#[derive(Serialize, Debug)]
+struct MemoryStats<'a> {
+    /// PID of the Alacritty process.
+    pid: u32,
+    name: Option<&'a str>,
+    history_size: usize,
+    grid_size: usize,
+    scrollback_size: usize,
+    glyphs: Option<usize>,
+    glyph_atlas_size: Option<usize>,
+}
+
+/// IPC server state.
+struct Server {
+    terminal: Arc<FairMutex<Term<EventProxy>>>,
+    event_proxy: EventProxy,
+}
+
+/// Create an IPC socket and spawn a thread answering all incoming requests.
+///
+/// Since every Alacritty instance has exactly one window, the window ID is the process ID.
+///
+/// The socket path and window ID are exported to child processes through the `ALACRITTY_SOCKET`
+/// and `ALACRITTY_WINDOW_ID` environment variables, so it must be created before the shell is
+/// spawned.
+pub fn spawn_ipc_socket(
+    terminal: Arc<FairMutex<Term<EventProxy>>>,
+    event_proxy: EventProxy,
+) -> Option<PathBuf> {
+    let window_id = process::id();
+    let socket_path = socket_path(window_id);
+
+    let listener = match UnixListener::bind(&socket_path) {
+        Ok(listener) => listener,
+        Err(err) => {
+            warn!("Unable to create socket at {:?}: {}", socket_path, err);
+            return None;
+        },
+    };
+
+    env::set_var(ALACRITTY_SOCKET_ENV, socket_path.as_os_str());
+    env::set_var(ALACRITTY_WINDOW_ID_ENV, window_id.to_string());
+
+    thread::spawn_named("socket listener", move || {
+        let server = Server { terminal, event_proxy };
+        for stream in listener.incoming().filter_map(Result::ok) {
+            if let Err(err) = server.handle_connection(stream) {
+                warn!("Unable to process socket message: {}", err);
+            }
+        }
+    });
+
+    Some(socket_path)
+}
+
+/// Send a message to running Alacritty instances and write the replies to STDOUT.
+pub fn send_message(options: &MessageOptions) -> io::Result<()> {
+    let socket_paths = match &options.target {
+        Some(WindowTarget::Id(window_id)) => vec![socket_path(*window_id)],
+        Some(WindowTarget::Name(_)) | Some(WindowTarget::All) => all_sockets()?,
+        None => {
+            let socket_path = options
+                .socket
+                .clone()
+                .or_else(|| env::var_os(ALACRITTY_SOCKET_ENV).map(PathBuf::from))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no socket specified"))?;
+            vec![socket_path]
+        },
+    };
+
+    // Sockets of other windows might be stale, so failures are only fatal for single targets.
+    let multiple = matches!(options.target, Some(WindowTarget::Name(_)) | Some(WindowTarget::All));
+
+    let mut matched = false;
+    for socket_path in &socket_paths {
+        let reply = match &options.target {
+            Some(WindowTarget::Name(name)) => match request(socket_path, &SocketMessage::GetName) {
+                Ok(reply) if reply == name.as_bytes() => request(socket_path, &options.message),
+                Ok(_) => continue,
+                Err(err) => Err(err),
+            },
+            _ => request(socket_path, &options.message),
+        };
+
+        match reply {
+            Ok(reply) => {
+                io::stdout().write_all(&reply)?;
+                matched = true;
+            },
+            Err(err) if multiple => {
+                debug!("Unable to send message to {:?}: {}", socket_path, err);
+            },
+            Err(err) => return Err(err),
+        }
+    }
+
+    if matched {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::NotFound, "no matching window"))
+    }
+}
+
+/// Remove the IPC socket file.
+pub fn remove_socket(socket_path: &Path) {
+    if let Err(err) = fs::remove_file(socket_path) {
+        warn!("Unable to remove socket at {:?}: {}", socket_path, err);
+    }
+}
+
+impl Server {
+    /// Read a single message from the socket and write the reply.
+    fn handle_connection(&self, mut stream: UnixStream) -> io::Result<()> {
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+
+        let reply = match json::from_str(&line)? {
+            SocketMessage::GetText { history, escapes } => {
+                get_text(&self.terminal.lock(), history, escapes)
+            },
+            SocketMessage::GetState => {
+                let terminal = self.terminal.lock();
+                let state = TerminalState::new(&terminal);
+                let mut state = json::to_string_pretty(&state)?;
+                state.push('\n');
+                state
+            },
+            SocketMessage::Select { ty, start, end } => {
+                let mut terminal = self.terminal.lock();
+                let start = start.grid_clamp(&*terminal, Boundary::Grid);
+                let end = end.grid_clamp(&*terminal, Boundary::Grid);
+                terminal.start_selection(ty, start, Side::Left);
+                terminal.update_selection(end, Side::Right);
+                self.redraw();
+                String::new()
+            },
+            SocketMessage::GetSelection => {
+                self.terminal.lock().selection_to_string().unwrap_or_default()
+            },
+            SocketMessage::ClearSelection => {
+                self.terminal.lock().clear_selection();
+                self.redraw();
+                String::new()
+            },
+            SocketMessage::GetName => {
+                self.terminal.lock().window_name().unwrap_or_default().to_owned()
+            },
+            SocketMessage::SetName(name) => {
+                self.terminal.lock().set_window_name(name.filter(|name| !name.is_empty()));
+                String::new()
+            },
+            SocketMessage::FontSize(change) => {
+                self.event_proxy.send_event(Event::FontSize(change));
+                String::new()
+            },
+            SocketMessage::GetStats => {
+                let (name, history_size, memory_usage) = {
+                    let terminal = self.terminal.lock();
+                    let name = terminal.window_name().map(ToOwned::to_owned);
+                    (name, terminal.grid().history_size(), terminal.memory_usage())
+                };
+
+                // The glyph cache is owned by the event loop, so it's omitted if that is busy.
+                let (sender, receiver) = mpsc::channel();
+                self.event_proxy.send_event(Event::GlyphStats(sender));
+                let glyph_stats = receiver.recv_timeout(EVENT_LOOP_TIMEOUT).ok();
+
+                let stats = MemoryStats {
+                    pid: process::id(),
+                    name: name.as_deref(),
+                    history_size,
+                    grid_size: memory_usage.visible,
+                    scrollback_size: memory_usage.history,
+                    glyphs: glyph_stats.map(|stats| stats.glyphs),
+                    glyph_atlas_size: glyph_stats.map(|stats| stats.atlas_size),
+                };
+                let mut stats = json::to_string_pretty(&stats)?;
+                stats.push('\n');
+                stats
+            },
+            SocketMessage::Trim => {
+                self.terminal.lock().shrink_to_fit();
+                self.event_proxy.send_event(Event::Trim);
+                String::new()
+            },
+            SocketMessage::Screenshot { selection } => {
+                self.event_proxy.send_event(Event::Screenshot(selection));
+                String::new()
+            },
+        };
+
+        stream.write_all(reply.as_bytes())
+    }
+
+    /// Request a redraw after changing the terminal.
+    fn redraw(&self) {
+        self.event_proxy.send_event(TerminalEvent::Wakeup.into());
+    }
+}
+
+/// Send a single message over the socket and read the entire reply.
+fn request(socket_path: &Path, message: &SocketMessage) -> io::Result<Vec<u8>> {
+    let mut stream = UnixStream::connect(socket_path)?;
+
+    let message = json::to_string(message)?;
+    stream.write_all(message.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply)?;
+
+    Ok(reply)
+}
+
+/// Text content of the terminal.
+fn get_text(terminal: &Term<EventProxy>, history: bool, escapes: bool) -> String {
+    let grid = terminal.grid();
+
+    let (start_line, end_line) = if history {
+        (grid.topmost_line(), grid.bottommost_line())
+    } else {
+        let top = -(grid.display_offset() as i32);
+        (Line(top), Line(top + grid.screen_lines() as i32 - 1))
+    };
+
+    let start = Point::new(start_line, Column(0));
+    let end = Point::new(end_line, grid.last_column());
+
+    if escapes {
+        terminal.bounds_to_escaped_string(start, end)
+    } else {
+        terminal.bounds_to_string(start, end)
+    }
+}
+
+/// Path of the IPC socket for a window.
+fn socket_path(window_id: u32) -> PathBuf {
+    socket_dir().join(format!("{}{}.sock", SOCKET_PREFIX, window_id))
+}
+
+/// Paths of the IPC sockets of all windows.
+fn all_sockets() -> io::Result<Vec<PathBuf>> {
+    let mut sockets = Vec::new();
+    for entry in fs::read_dir(socket_dir())? {
+        let path = entry?.path();
+        let is_socket = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with(SOCKET_PREFIX) && name.ends_with(".sock"));
+
+        if is_socket {
+            sockets.push(path);
+        }
+    }
+
+    Ok(sockets)
+}
+
+/// Directory used for storing IPC sockets.
+fn socket_dir() -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).unwrap_or_else(env::temp_dir)
+}
@@ -3,10 +3,11 @@
 //! The main executable is supposed to call `initialize()` exactly once during
 //! startup. All logging messages are written to stdout, given that their
 //! log-level is sufficient for the level configured in `cli::Options`.
+//! Configuration errors and warnings are written to stderr instead.
 
 use std::env;
 use std::fs::{File, OpenOptions};
-use std::io::{self, LineWriter, Stdout, Write};
+use std::io::{self, LineWriter, Stderr, Stdout, Write};
 use std::path::PathBuf;
 use std::process;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -15,7 +16,10 @@ use std::sync::{Arc, Mutex};
 use glutin::event_loop::EventLoopProxy;
 use log::{self, Level};
 
+use alacritty_terminal::config::LOG_TARGET_CONFIG;
+
 use crate::cli::Options;
+use crate::config::location;
 use crate::event::Event;
 use crate::message_bar::{Message, MessageType};
 
@@ -41,6 +45,7 @@ pub fn initialize(
 pub struct Logger {
     logfile: Mutex<OnDemandLogFile>,
     stdout: Mutex<LineWriter<Stdout>>,
+    stderr: Mutex<LineWriter<Stderr>>,
     event_proxy: Mutex<EventLoopProxy<Event>>,
 }
 
@@ -48,8 +53,9 @@ impl Logger {
     fn new(event_proxy: EventLoopProxy<Event>) -> Self {
         let logfile = Mutex::new(OnDemandLogFile::new());
         let stdout = Mutex::new(LineWriter::new(io::stdout()));
+        let stderr = Mutex::new(LineWriter::new(io::stderr()));
 
-        Logger { logfile, stdout, event_proxy: Mutex::new(event_proxy) }
+        Logger { logfile, stdout, stderr, event_proxy: Mutex::new(event_proxy) }
     }
 
     fn file_path(&self) -> Option<PathBuf> {
@@ -61,7 +67,7 @@ impl Logger {
     }
 
     /// Log a record to the message bar.
-    fn message_bar_log(&self, record: &log::Record<'_>, logfile_path: &str) {
+    fn message_bar_log(&self, record: &log::Record<'_>, text: &str, logfile_path: &str) {
         let event_proxy = match self.event_proxy.lock() {
             Ok(event_proxy) => event_proxy,
             Err(_) => return,
@@ -72,13 +78,8 @@ impl Logger {
         #[cfg(windows)]
         let env_var = format!("%{}%", ALACRITTY_LOG_ENV);
 
-        let message = format!(
-            "[{}] See log at {} ({}):\n{}",
-            record.level(),
-            logfile_path,
-            env_var,
-            record.args(),
-        );
+        let message =
+            format!("[{}] See log at {} ({}):\n{}", record.level(), logfile_path, env_var, text);
         let message_type = match record.level() {
             Level::Error => MessageType::Error,
             Level::Warn => MessageType::Warning,
@@ -107,8 +108,15 @@ impl log::Log for Logger {
             return;
         }
 
+        // Point configuration problems at the offending line of the configuration file.
+        let config_problem = record.target() == LOG_TARGET_CONFIG && record.level() <= Level::Warn;
+        let mut text = record.args().to_string();
+        if config_problem {
+            text = location::annotate(&text).unwrap_or(text);
+        }
+
         // Create log message for the given `record` and `target`.
-        let message = create_log_message(record, &target);
+        let message = create_log_message(record, &text, &target);
 
         if let Ok(mut logfile) = self.logfile.lock() {
             // Write to logfile.
@@ -116,12 +124,16 @@ impl log::Log for Logger {
 
             // Write to message bar.
             if record.level() <= Level::Warn {
-                self.message_bar_log(record, &logfile.path.to_string_lossy());
+                self.message_bar_log(record, &text, &logfile.path.to_string_lossy());
             }
         }
 
-        // Write to stdout.
-        if let Ok(mut stdout) = self.stdout.lock() {
+        // Write configuration problems to stderr and everything else to stdout.
+        if config_problem {
+            if let Ok(mut stderr) = self.stderr.lock() {
+                let _ = stderr.write_all(message.as_ref());
+            }
+        } else if let Ok(mut stdout) = self.stdout.lock() {
             let _ = stdout.write_all(message.as_ref());
         }
     }
@@ -129,7 +141,7 @@ impl log::Log for Logger {
     fn flush(&self) {}
 }
 
-fn create_log_message(record: &log::Record<'_>, target: &str) -> String {
+fn create_log_message(record: &log::Record<'_>, text: &str, target: &str) -> String {
     let now = time::strftime("%F %T.%f", &time::now()).unwrap();
     let mut message = format!("[{}] [{:<5}] [{}] ", now, record.level(), target);
 
@@ -138,8 +150,7 @@ fn create_log_message(record: &log::Record<'_>, target: &str) -> String {
     let alignment = message.len();
 
     // Push lines with added extra padding on the next line, which is trimmed later.
-    let lines = record.args().to_string();
-    for line in lines.split('\n') {
+    for line in text.split('\n') {
         let line = format!("{}\n{:width$}", line, "", width = alignment);
         message.push_str(&line);
     }
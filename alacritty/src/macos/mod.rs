@@ -1,2 +1,3 @@
 pub mod locale;
+pub mod pasteboard;
 pub mod proc;
@@ -0,0 +1,37 @@
+use cocoa::base::{id, nil, BOOL, NO};
+use cocoa::foundation::{NSInteger, NSString};
+use objc::runtime::Class;
+use objc::{msg_send, sel, sel_impl};
+
+/// Uniform type identifier for plain text.
+pub const PLAIN_TEXT: &str = "public.utf8-plain-text";
+
+/// Uniform type identifier for HTML.
+pub const HTML: &str = "public.html";
+
+/// Replace the content of the general pasteboard.
+///
+/// Every entry contains the uniform type identifier and the text stored for that type. Returns
+/// `false` if any of the types could not be stored.
+pub fn store(contents: &[(&str, &str)]) -> bool {
+    unsafe {
+        let pasteboard_class = match Class::get("NSPasteboard") {
+            Some(class) => class,
+            None => return false,
+        };
+        let pasteboard: id = msg_send![pasteboard_class, generalPasteboard];
+        let _: NSInteger = msg_send![pasteboard, clearContents];
+
+        contents.iter().all(|(ty, text)| {
+            let ty = NSString::alloc(nil).init_str(ty);
+            let text = NSString::alloc(nil).init_str(text);
+
+            let stored: BOOL = msg_send![pasteboard, setString: text forType: ty];
+
+            let _: () = msg_send![ty, release];
+            let _: () = msg_send![text, release];
+
+            stored != NO
+        })
+    }
+}
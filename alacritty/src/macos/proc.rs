@@ -2,10 +2,10 @@ use std::ffi::{CStr, CString, IntoStringError};
 use std::fmt::{self, Display, Formatter};
 use std::io;
 use std::mem::{self, MaybeUninit};
-use std::os::raw::{c_int, c_void};
+use std::os::raw::{c_char, c_int, c_void};
 use std::path::PathBuf;
 
-/// Error during working directory retrieval.
+/// Error during process information retrieval.
 #[derive(Debug)]
 pub enum Error {
     Io(io::Error),
@@ -31,9 +31,9 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Error::InvalidSize => write!(f, "Invalid proc_pidinfo return size"),
-            Error::Io(err) => write!(f, "Error getting current working directory: {}", err),
+            Error::Io(err) => write!(f, "Error getting process information: {}", err),
             Error::IntoString(err) => {
-                write!(f, "Error when parsing current working directory: {}", err)
+                write!(f, "Error when parsing process information: {}", err)
             },
         }
     }
@@ -68,12 +68,27 @@ pub fn cwd(pid: c_int) -> Result<PathBuf, Error> {
     Ok(CString::from(c_str).into_string().map(PathBuf::from)?)
 }
 
+pub fn name(pid: c_int) -> Result<String, Error> {
+    let mut buffer = [0 as c_char; sys::PROC_PIDPATHINFO_MAXSIZE];
+    let size = buffer.len() as u32;
+
+    let c_str = unsafe {
+        if sys::proc_name(pid, buffer.as_mut_ptr() as *mut c_void, size) <= 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        CStr::from_ptr(buffer.as_ptr())
+    };
+
+    Ok(CString::from(c_str).into_string()?)
+}
+
 /// Bindings for libproc.
 #[allow(non_camel_case_types)]
 mod sys {
     use std::os::raw::{c_char, c_int, c_longlong, c_void};
 
     pub const PROC_PIDVNODEPATHINFO: c_int = 9;
+    pub const PROC_PIDPATHINFO_MAXSIZE: usize = 4096;
 
     type gid_t = c_int;
     type off_t = c_longlong;
@@ -143,6 +158,8 @@ mod sys {
             buffer: *mut c_void,
             buffersize: c_int,
         ) -> c_int;
+
+        pub fn proc_name(pid: c_int, buffer: *mut c_void, buffersize: u32) -> c_int;
     }
 }
 
@@ -157,4 +174,9 @@ mod tests {
     fn cwd_matches_current_dir() {
         assert_eq!(cwd(process::id() as i32).ok(), env::current_dir().ok());
     }
+
+    #[test]
+    fn name_of_current_process() {
+        assert!(!name(process::id() as i32).unwrap().is_empty());
+    }
 }
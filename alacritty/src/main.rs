@@ -24,6 +24,7 @@ use log::{error, info};
 #[cfg(windows)]
 use winapi::um::wincon::{AttachConsole, FreeConsole, ATTACH_PARENT_PROCESS};
 
+use alacritty_terminal::ansi::Handler;
 use alacritty_terminal::event_loop::{self, EventLoop, Msg};
 use alacritty_terminal::grid::Dimensions;
 use alacritty_terminal::sync::FairMutex;
@@ -37,14 +38,22 @@ mod daemon;
 mod display;
 mod event;
 mod input;
+#[cfg(unix)]
+mod ipc;
 mod logging;
 #[cfg(target_os = "macos")]
 mod macos;
 mod message_bar;
+mod pager;
 #[cfg(windows)]
 mod panic;
 mod renderer;
+mod replay;
 mod scheduler;
+mod search;
+mod startup;
+mod terminfo;
+mod vi_marks;
 
 mod gl {
     #![allow(clippy::all)]
@@ -59,6 +68,8 @@ use crate::event::{Event, EventProxy, Processor};
 #[cfg(target_os = "macos")]
 use crate::macos::locale;
 use crate::message_bar::MessageBuffer;
+use crate::replay::Recording;
+use crate::startup::StartupTimer;
 
 fn main() {
     #[cfg(windows)]
@@ -74,6 +85,38 @@ fn main() {
 
     // Load command line options.
     let options = Options::new();
+    let mut startup_timer = StartupTimer::new(options.measure_startup);
+
+    // Send IPC messages without starting a new instance.
+    #[cfg(unix)]
+    if let Some(message_options) = &options.message {
+        if let Err(err) = ipc::send_message(message_options) {
+            eprintln!("Unable to send message: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Update the configuration file without starting a new instance.
+    if let Some(migrate_options) = &options.migrate {
+        if let Err(err) = config::migrate::migrate_file(&options, migrate_options) {
+            eprintln!("Unable to migrate config: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Write the default configuration file without starting a new instance.
+    if let Some(create_options) = &options.create_config {
+        match config::template::create_config(&options, create_options) {
+            Ok(path) => println!("Created config file at {:?}", path),
+            Err(err) => {
+                eprintln!("Unable to create config: {}", err);
+                std::process::exit(1);
+            },
+        }
+        return;
+    }
 
     // Setup glutin event loop.
     let window_event_loop = GlutinEventLoop::<Event>::with_user_event();
@@ -84,6 +127,7 @@ fn main() {
 
     // Load configuration file.
     let config = config::load(&options);
+    startup_timer.stage("config");
 
     // Update the log level from config.
     log::set_max_level(config.ui_config.debug.log_level);
@@ -99,7 +143,7 @@ fn main() {
     let persistent_logging = config.ui_config.debug.persistent_logging;
 
     // Run Alacritty.
-    if let Err(err) = run(window_event_loop, config, options) {
+    if let Err(err) = run(window_event_loop, config, options, startup_timer) {
         error!("Alacritty encountered an unrecoverable error:\n\n\t{}\n", err);
         std::process::exit(1);
     }
@@ -118,8 +162,9 @@ fn main() {
 /// config change monitor, and runs the main display loop.
 fn run(
     window_event_loop: GlutinEventLoop<Event>,
-    config: Config,
+    mut config: Config,
     options: Options,
+    mut startup_timer: StartupTimer,
 ) -> Result<(), Box<dyn Error>> {
     info!("Welcome to Alacritty");
 
@@ -129,12 +174,23 @@ fn run(
     // Set environment variables.
     tty::setup_env(&config);
 
+    // Load the recording before creating the window, to fail early.
+    let recording = match &options.replay {
+        Some(replay) => Some(Recording::load(&replay.path)?),
+        None => None,
+    };
+
+    // Open the window with the dimensions of the recording.
+    if let Some(dimensions) = recording.as_ref().and_then(Recording::dimensions) {
+        config.ui_config.window.set_dimensions(dimensions);
+    }
+
     let event_proxy = EventProxy::new(window_event_loop.create_proxy());
 
     // Create a display.
     //
     // The display manages a window and can draw the terminal.
-    let display = Display::new(&config, &window_event_loop)?;
+    let display = Display::new(&config, &window_event_loop, &mut startup_timer)?;
 
     info!(
         "PTY dimensions: {:?} x {:?}",
@@ -147,33 +203,53 @@ fn run(
     // This object contains all of the state about what's being displayed. It's
     // wrapped in a clonable mutex since both the I/O loop and display need to
     // access it.
-    let terminal = Term::new(&config, display.size_info, event_proxy.clone());
+    let mut terminal = Term::new(&config, display.size_info, event_proxy.clone());
+    terminal.set_window_name(options.window_name.clone());
+    if options.pager {
+        pager::setup(&mut terminal);
+    }
     let terminal = Arc::new(FairMutex::new(terminal));
 
-    // Create the PTY.
-    //
-    // The PTY forks a process to run the shell on the slave side of the
-    // pseudoterminal. A file descriptor for the master side is retained for
-    // reading/writing to the shell.
-    let pty = tty::new(&config, &display.size_info, display.window.x11_window_id());
+    // Create the IPC socket before spawning the shell, so its path is inherited.
+    #[cfg(unix)]
+    let socket_path = ipc::spawn_ipc_socket(Arc::clone(&terminal), event_proxy.clone());
 
-    // Create the pseudoterminal I/O loop.
+    // Create the terminal input source.
+    //
+    // In pager mode the terminal is fed from STDIN and input is discarded, while replays are
+    // fed from the recording with input controlling the playback. Otherwise the PTY forks a
+    // process to run the shell on the slave side of the pseudoterminal. A file descriptor for
+    // the master side is retained for reading/writing to the shell.
     //
     // PTY I/O is ran on another thread as to not occupy cycles used by the
     // renderer and input processing. Note that access to the terminal state is
     // synchronized since the I/O loop updates the state, and the display
     // consumes it periodically.
-    let event_loop = EventLoop::new(
-        Arc::clone(&terminal),
-        event_proxy.clone(),
-        pty,
-        config.hold,
-        config.ui_config.debug.ref_test,
-    );
-
-    // The event loop channel allows write requests from the event processor
-    // to be sent to the pty loop and ultimately written to the pty.
-    let loop_tx = event_loop.channel();
+    let (event_loop, loop_tx, _pager_rx) = if let Some(recording) = recording {
+        let (loop_tx, loop_rx) = mio_extras::channel::channel();
+        let speed = options.replay.as_ref().map_or(1., |replay| replay.speed);
+        replay::spawn(recording, speed, Arc::clone(&terminal), event_proxy.clone(), loop_rx);
+        (None, loop_tx, None)
+    } else if options.pager {
+        let (loop_tx, loop_rx) = mio_extras::channel::channel();
+        pager::spawn(Arc::clone(&terminal), event_proxy.clone());
+        (None, loop_tx, Some(loop_rx))
+    } else {
+        let pty = tty::new(&config, &display.size_info, display.window.x11_window_id());
+        let event_loop = EventLoop::new(
+            Arc::clone(&terminal),
+            event_proxy.clone(),
+            pty,
+            config.hold,
+            config.ui_config.debug.ref_test,
+        );
+
+        // The event loop channel allows write requests from the event processor
+        // to be sent to the pty loop and ultimately written to the pty.
+        let loop_tx = event_loop.channel();
+
+        (Some(event_loop), loop_tx, None)
+    };
 
     // Create a config monitor when config was loaded from path.
     //
@@ -186,17 +262,22 @@ fn run(
     // Setup storage for message UI.
     let message_buffer = MessageBuffer::new();
 
+    // Only the PTY event loop keeps track of its queued input.
+    let write_queue = event_loop.as_ref().map(EventLoop::write_queue).unwrap_or_default();
+
     // Event processor.
     let mut processor = Processor::new(
         event_loop::Notifier(loop_tx.clone()),
+        write_queue,
         message_buffer,
         config,
         display,
         options,
+        startup_timer,
     );
 
     // Kick off the I/O thread.
-    let io_thread = event_loop.spawn();
+    let io_thread = event_loop.map(EventLoop::spawn);
 
     info!("Initialisation complete");
 
@@ -217,8 +298,19 @@ fn run(
     drop(processor);
 
     // Shutdown PTY parser event loop.
+    //
+    // Since the window is already closed, waiting for the shell to exit after hanging it up does
+    // not block the UI.
     loop_tx.send(Msg::Shutdown).expect("Error sending shutdown to PTY event loop");
-    io_thread.join().expect("join io thread");
+    if let Some(io_thread) = io_thread {
+        io_thread.join().expect("join io thread");
+    }
+
+    // Clean up the IPC socket.
+    #[cfg(unix)]
+    if let Some(socket_path) = socket_path {
+        ipc::remove_socket(&socket_path);
+    }
 
     // FIXME patch notify library to have a shutdown method.
     // config_reloader.join().ok();
@@ -37,8 +37,8 @@ impl Message {
     /// Formatted message text lines.
     pub fn text(&self, size_info: &SizeInfo) -> Vec<String> {
         let num_cols = size_info.columns();
-        let total_lines =
-            (size_info.height() - 2. * size_info.padding_y()) / size_info.cell_height();
+        let text_height = size_info.height() - size_info.padding_y() - size_info.padding_bottom();
+        let total_lines = text_height / size_info.cell_height();
         let max_lines = (total_lines as usize).saturating_sub(MIN_FREE_LINES);
         let button_len = CLOSE_BUTTON_TEXT.chars().count();
 
@@ -180,6 +180,12 @@ impl MessageBuffer {
             .collect();
     }
 
+    /// Check if a message with a specific target is queued.
+    #[inline]
+    pub fn contains_target(&self, target: &str) -> bool {
+        self.messages.iter().any(|m| m.target().map(String::as_str) == Some(target))
+    }
+
     /// Add a new message to the queue.
     #[inline]
     pub fn push(&mut self, message: Message) {
@@ -304,6 +310,20 @@ mod tests {
         assert_eq!(lines, vec![String::from("t [X]"), String::from("est  ")]);
     }
 
+    #[test]
+    fn contains_target() {
+        let mut message_buffer = MessageBuffer::new();
+        let mut msg = Message::new(String::from("test"), MessageType::Warning);
+        msg.set_target("target".into());
+        message_buffer.push(msg);
+
+        assert!(message_buffer.contains_target("target"));
+        assert!(!message_buffer.contains_target("other"));
+
+        message_buffer.remove_target("target");
+        assert!(!message_buffer.contains_target("target"));
+    }
+
     #[test]
     fn remove_target() {
         let mut message_buffer = MessageBuffer::new();
@@ -0,0 +1,68 @@
+//! Pager mode, displaying data read from STDIN without spawning a shell.
+
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use log::error;
+
+use alacritty_terminal::ansi::{self, Handler, Mode};
+use alacritty_terminal::event::Event as TerminalEvent;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Point};
+use alacritty_terminal::sync::FairMutex;
+use alacritty_terminal::term::Term;
+use alacritty_terminal::thread;
+
+use crate::event::{Event, EventProxy};
+
+/// Max bytes read from STDIN before the terminal lock is released.
+const MAX_READ: usize = u16::max_value() as usize;
+
+/// Prepare the terminal for displaying piped content.
+pub fn setup(terminal: &mut Term<EventProxy>) {
+    // Piped content usually only terminates lines with LF.
+    terminal.set_mode(Mode::LineFeedNewLine);
+    terminal.toggle_vi_mode();
+}
+
+/// Spawn a thread which feeds STDIN into the terminal until EOF is reached.
+pub fn spawn(
+    terminal: Arc<FairMutex<Term<EventProxy>>>,
+    event_proxy: EventProxy,
+) -> JoinHandle<()> {
+    thread::spawn_named("STDIN reader", move || {
+        let mut parser = ansi::Processor::new();
+        let mut stdin = io::stdin();
+        let mut buf = vec![0u8; MAX_READ];
+
+        loop {
+            let got = match stdin.read(&mut buf) {
+                Ok(0) => break,
+                Ok(got) => got,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    error!("Error reading from STDIN: {}", err);
+                    break;
+                },
+            };
+
+            let mut terminal = terminal.lock();
+            for byte in &buf[..got] {
+                parser.advance(&mut *terminal, *byte);
+            }
+            drop(terminal);
+
+            event_proxy.send_event(Event::TerminalEvent(TerminalEvent::Wakeup));
+        }
+
+        // Flush pending synchronized updates and jump to the start of the content.
+        let mut terminal = terminal.lock();
+        parser.stop_sync(&mut *terminal);
+        let point = Point::new(terminal.grid().topmost_line(), Column(0));
+        terminal.vi_goto_point(point);
+        drop(terminal);
+
+        event_proxy.send_event(Event::TerminalEvent(TerminalEvent::Wakeup));
+    })
+}
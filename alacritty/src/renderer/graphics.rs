@@ -0,0 +1,311 @@
+//! Rendering of inline graphics.
+
+use std::collections::HashMap;
+use std::{mem, ptr};
+
+use alacritty_terminal::graphics::{GraphicCell, GraphicData, GraphicId, UpdateQueues};
+use alacritty_terminal::index::Point;
+use alacritty_terminal::term::SizeInfo;
+
+use crate::display::content::RenderableCell;
+use crate::gl;
+use crate::gl::types::*;
+use crate::renderer;
+
+static GRAPHICS_SHADER_F: &str = include_str!("../../res/graphics.f.glsl");
+static GRAPHICS_SHADER_V: &str = include_str!("../../res/graphics.v.glsl");
+
+/// Graphic uploaded to the GPU.
+#[derive(Debug)]
+struct GraphicTexture {
+    /// Texture id.
+    id: GLuint,
+
+    /// Width of the graphic, in pixels.
+    width: usize,
+
+    /// Height of the graphic, in pixels.
+    height: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct Vertex {
+    // Normalized screen coordinates.
+    x: f32,
+    y: f32,
+
+    // Texture coordinates.
+    u: f32,
+    v: f32,
+}
+
+#[derive(Debug)]
+pub struct GraphicsRenderer {
+    // GL buffer objects.
+    vao: GLuint,
+    vbo: GLuint,
+
+    program: GraphicsShaderProgram,
+
+    textures: HashMap<GraphicId, GraphicTexture>,
+
+    vertices: Vec<Vertex>,
+}
+
+impl GraphicsRenderer {
+    pub fn new() -> Result<Self, renderer::Error> {
+        let mut vao: GLuint = 0;
+        let mut vbo: GLuint = 0;
+        let program = GraphicsShaderProgram::new()?;
+
+        unsafe {
+            // Allocate buffers.
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+
+            gl::BindVertexArray(vao);
+
+            // VBO binding is not part of VAO itself, but VBO binding is stored in attributes.
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+            // Position.
+            gl::VertexAttribPointer(
+                0,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                mem::size_of::<Vertex>() as i32,
+                ptr::null(),
+            );
+            gl::EnableVertexAttribArray(0);
+
+            // Texture coordinates.
+            gl::VertexAttribPointer(
+                1,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                mem::size_of::<Vertex>() as i32,
+                (mem::size_of::<f32>() * 2) as *const _,
+            );
+            gl::EnableVertexAttribArray(1);
+
+            // Reset buffer bindings.
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        Ok(Self { vao, vbo, program, textures: HashMap::new(), vertices: Vec::new() })
+    }
+
+    /// Upload new graphics and delete the ones no longer shown in the grid.
+    pub fn update(&mut self, queues: UpdateQueues) {
+        for graphic in queues.pending {
+            self.upload(graphic);
+        }
+
+        for id in queues.remove_queue {
+            if let Some(texture) = self.textures.remove(&id) {
+                unsafe { gl::DeleteTextures(1, &texture.id) };
+            }
+        }
+    }
+
+    /// Draw the graphics of all cells showing one.
+    pub fn draw(&mut self, size_info: &SizeInfo, cells: &[RenderableCell]) {
+        // Group cells by their graphic, to draw each graphic with a single call.
+        let mut cells: Vec<_> = cells
+            .iter()
+            .filter_map(|cell| cell.graphic.as_ref().map(|graphic| (cell.point, graphic)))
+            .collect();
+        cells.sort_by_key(|(_, graphic)| graphic.texture.id);
+
+        unsafe {
+            // Bind VAO to enable vertex attribute slots.
+            gl::BindVertexArray(self.vao);
+
+            // Bind VBO only once for buffer data upload only.
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+
+            gl::UseProgram(self.program.id);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+
+        let half_width = size_info.width() / 2.;
+        let half_height = size_info.height() / 2.;
+
+        let mut start = 0;
+        while start < cells.len() {
+            let id = cells[start].1.texture.id;
+            let len = cells[start..].iter().take_while(|cell| cell.1.texture.id == id).count();
+            let group = &cells[start..start + len];
+            start += len;
+
+            let texture = match self.textures.get(&id) {
+                Some(texture) => texture,
+                None => continue,
+            };
+
+            // Build vertices for all cells showing this graphic.
+            self.vertices.clear();
+            for (point, graphic) in group {
+                let vertices = &mut self.vertices;
+                add_cell(vertices, size_info, half_width, half_height, texture, *point, graphic);
+            }
+
+            unsafe {
+                // Upload accumulated vertices.
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (self.vertices.len() * mem::size_of::<Vertex>()) as isize,
+                    self.vertices.as_ptr() as *const _,
+                    gl::STREAM_DRAW,
+                );
+
+                gl::BindTexture(gl::TEXTURE_2D, texture.id);
+
+                // Draw all vertices as list of triangles.
+                gl::DrawArrays(gl::TRIANGLES, 0, self.vertices.len() as i32);
+            }
+        }
+
+        unsafe {
+            // Disable program.
+            gl::UseProgram(0);
+
+            // Reset buffer bindings to nothing.
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+    }
+
+    /// Upload a graphic to a new texture.
+    fn upload(&mut self, graphic: GraphicData) {
+        let mut id: GLuint = 0;
+        unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D, id);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as i32,
+                graphic.width as i32,
+                graphic.height as i32,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                graphic.pixels.as_ptr() as *const _,
+            );
+
+            // Keep pixels sharp when the graphic is scaled, like in zoom mode.
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        let texture = GraphicTexture { id, width: graphic.width, height: graphic.height };
+        self.textures.insert(graphic.id, texture);
+    }
+}
+
+impl Drop for GraphicsRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            for texture in self.textures.values() {
+                gl::DeleteTextures(1, &texture.id);
+            }
+
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+/// Add the vertices for the part of a graphic shown in a cell.
+fn add_cell(
+    vertices: &mut Vec<Vertex>,
+    size_info: &SizeInfo,
+    half_width: f32,
+    half_height: f32,
+    texture: &GraphicTexture,
+    point: Point<usize>,
+    graphic: &GraphicCell,
+) {
+    // Source rectangle within the graphic, in pixels.
+    let (offset_x, offset_y) = (usize::from(graphic.offset_x), usize::from(graphic.offset_y));
+    let width = graphic.texture.cell_width.min(texture.width.saturating_sub(offset_x));
+    let height = graphic.texture.cell_height.min(texture.height.saturating_sub(offset_y));
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    // Scale the graphic when the cell size changed since it was inserted.
+    let scale_x = size_info.cell_width() / graphic.texture.cell_width as f32;
+    let scale_y = size_info.cell_height() / graphic.texture.cell_height as f32;
+
+    // Calculate the quad position in normalized device coordinates.
+    // NDC range from -1 to +1, with Y pointing up.
+    let cell_rect = size_info.cell_rect(point, 1);
+    let x = cell_rect.x / half_width - 1.0;
+    let y = -cell_rect.y / half_height + 1.0;
+    let quad_width = width as f32 * scale_x / half_width;
+    let quad_height = height as f32 * scale_y / half_height;
+
+    // Calculate the texture coordinates.
+    let u = offset_x as f32 / texture.width as f32;
+    let v = offset_y as f32 / texture.height as f32;
+    let uv_width = width as f32 / texture.width as f32;
+    let uv_height = height as f32 / texture.height as f32;
+
+    // Make quad vertices.
+    let quad = [
+        Vertex { x, y, u, v },
+        Vertex { x, y: y - quad_height, u, v: v + uv_height },
+        Vertex { x: x + quad_width, y, u: u + uv_width, v },
+        Vertex { x: x + quad_width, y: y - quad_height, u: u + uv_width, v: v + uv_height },
+    ];
+
+    // Append the vertices to form two triangles.
+    vertices.push(quad[0]);
+    vertices.push(quad[1]);
+    vertices.push(quad[2]);
+    vertices.push(quad[2]);
+    vertices.push(quad[3]);
+    vertices.push(quad[1]);
+}
+
+/// Graphics drawing program.
+#[derive(Debug)]
+pub struct GraphicsShaderProgram {
+    /// Program id.
+    id: GLuint,
+}
+
+impl GraphicsShaderProgram {
+    pub fn new() -> Result<Self, renderer::ShaderCreationError> {
+        let vertex_shader = renderer::create_shader(gl::VERTEX_SHADER, GRAPHICS_SHADER_V)?;
+        let fragment_shader = renderer::create_shader(gl::FRAGMENT_SHADER, GRAPHICS_SHADER_F)?;
+        let program = renderer::create_program(vertex_shader, fragment_shader)?;
+
+        unsafe {
+            gl::DeleteShader(fragment_shader);
+            gl::DeleteShader(vertex_shader);
+        }
+
+        Ok(Self { id: program })
+    }
+}
+
+impl Drop for GraphicsShaderProgram {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.id);
+        }
+    }
+}
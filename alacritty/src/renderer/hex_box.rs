@@ -0,0 +1,185 @@
+//! Procedural glyphs for characters missing from all fonts.
+//!
+//! Instead of rendering the font's generic replacement glyph, missing characters are drawn as a
+//! box containing their hexadecimal codepoint, so it is possible to tell which character failed.
+
+use crossfont::{BitmapBuffer, Metrics, RasterizedGlyph};
+use unicode_width::UnicodeWidthChar;
+
+/// Width of a hex digit in font units.
+const DIGIT_WIDTH: usize = 3;
+
+/// Height of a hex digit in font units.
+const DIGIT_HEIGHT: usize = 5;
+
+/// Bitmaps for all hex digits, with one 3-bit row per entry.
+#[rustfmt::skip]
+const DIGITS: [[u8; DIGIT_HEIGHT]; 16] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b001, 0b001, 0b001], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+    [0b111, 0b101, 0b111, 0b101, 0b101], // A
+    [0b110, 0b101, 0b110, 0b101, 0b110], // B
+    [0b111, 0b100, 0b100, 0b100, 0b111], // C
+    [0b110, 0b101, 0b101, 0b101, 0b110], // D
+    [0b111, 0b100, 0b111, 0b100, 0b111], // E
+    [0b111, 0b100, 0b111, 0b100, 0b100], // F
+];
+
+/// Rasterize a box showing the codepoint of a character.
+///
+/// Codepoints are split into two rows, using two digits per row for characters in the Basic
+/// Multilingual Plane and three digits per row for everything else.
+pub fn hex_box(character: char, metrics: &Metrics) -> RasterizedGlyph {
+    let cells = character.width().unwrap_or(1).max(1);
+    let width = (metrics.average_advance.floor() as usize * cells).max(1);
+    let height = (metrics.line_height.floor() as usize).max(1);
+
+    let mut canvas = Canvas { width, height, buffer: vec![0; width * height * 3] };
+
+    // Draw the box outline, leaving one pixel of space to neighboring boxes.
+    canvas.fill(1, 1, width.saturating_sub(2), 1);
+    canvas.fill(1, height.saturating_sub(2), width.saturating_sub(2), 1);
+    canvas.fill(1, 1, 1, height.saturating_sub(2));
+    canvas.fill(width.saturating_sub(2), 1, 1, height.saturating_sub(2));
+
+    let codepoint = character as u32;
+    let digits = if codepoint > 0xffff { 6 } else { 4 };
+    let digits_per_row = digits / 2;
+
+    // Scale digits to the largest size fitting inside the box, with one unit of spacing between
+    // them. Boxes which are too small to fit any digits are left empty.
+    let inner_width = width.saturating_sub(6);
+    let inner_height = height.saturating_sub(6);
+    let unit = (inner_width / (digits_per_row * (DIGIT_WIDTH + 1) - 1))
+        .min(inner_height / (2 * DIGIT_HEIGHT + 1));
+
+    if unit > 0 {
+        let text_width = (digits_per_row * (DIGIT_WIDTH + 1) - 1) * unit;
+        let text_height = (2 * DIGIT_HEIGHT + 1) * unit;
+        let x_start = (width - text_width) / 2;
+        let y_start = (height - text_height) / 2;
+
+        for index in 0..digits {
+            let shift = 4 * (digits - index - 1);
+            let digit = DIGITS[(codepoint >> shift) as usize & 0xf];
+
+            let x = x_start + (index % digits_per_row) * (DIGIT_WIDTH + 1) * unit;
+            let y = y_start + (index / digits_per_row) * (DIGIT_HEIGHT + 1) * unit;
+
+            for (row, bits) in digit.iter().enumerate() {
+                for column in 0..DIGIT_WIDTH {
+                    if bits & (0b100 >> column) != 0 {
+                        canvas.fill(x + column * unit, y + row * unit, unit, unit);
+                    }
+                }
+            }
+        }
+    }
+
+    RasterizedGlyph {
+        character,
+        width: width as i32,
+        height: height as i32,
+        top: height as i32 + metrics.descent as i32,
+        left: 0,
+        buffer: BitmapBuffer::Rgb(canvas.buffer),
+    }
+}
+
+/// RGB bitmap which is drawn on using opaque rectangles.
+struct Canvas {
+    width: usize,
+    height: usize,
+    buffer: Vec<u8>,
+}
+
+impl Canvas {
+    /// Fill a rectangle, clipping it to the canvas.
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        for row in y..(y + height).min(self.height) {
+            let start = (row * self.width + x.min(self.width)) * 3;
+            let end = (row * self.width + (x + width).min(self.width)) * 3;
+            for value in &mut self.buffer[start..end] {
+                *value = u8::max_value();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(width: f64, height: f64) -> Metrics {
+        Metrics {
+            average_advance: width,
+            line_height: height,
+            descent: -2.,
+            underline_position: 0.,
+            underline_thickness: 1.,
+            strikeout_position: 0.,
+            strikeout_thickness: 1.,
+        }
+    }
+
+    /// Convert the glyph to ASCII art for easier comparison.
+    fn ascii(glyph: &RasterizedGlyph) -> Vec<String> {
+        let buffer = match &glyph.buffer {
+            BitmapBuffer::Rgb(buffer) => buffer,
+            BitmapBuffer::Rgba(_) => unreachable!(),
+        };
+
+        buffer
+            .chunks(glyph.width as usize * 3)
+            .map(|row| row.chunks(3).map(|p| if p[0] == 0 { '.' } else { '#' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn hex_box_digits() {
+        let glyph = hex_box('\u{e0b0}', &metrics(13., 17.));
+
+        assert_eq!((glyph.width, glyph.height, glyph.top), (13, 17, 15));
+        assert_eq!(ascii(&glyph), [
+            ".............",
+            ".###########.",
+            ".#.........#.",
+            ".#.###.###.#.",
+            ".#.#...#.#.#.",
+            ".#.###.#.#.#.",
+            ".#.#...#.#.#.",
+            ".#.###.###.#.",
+            ".#.........#.",
+            ".#.##..###.#.",
+            ".#.#.#.#.#.#.",
+            ".#.##..#.#.#.",
+            ".#.#.#.#.#.#.",
+            ".#.##..###.#.",
+            ".#.........#.",
+            ".###########.",
+            ".............",
+        ]);
+    }
+
+    #[test]
+    fn hex_box_too_small_for_digits() {
+        let glyph = hex_box('\u{1f600}', &metrics(4., 5.));
+
+        // Wide characters span two cells.
+        assert_eq!(ascii(&glyph), [
+            "........",
+            ".######.",
+            ".#....#.",
+            ".######.",
+            "........",
+        ]);
+    }
+}
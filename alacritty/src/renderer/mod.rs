@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::{self, Display, Formatter};
 use std::hash::BuildHasherDefault;
 use std::io;
@@ -7,26 +7,33 @@ use std::ptr;
 
 use bitflags::bitflags;
 use crossfont::{
-    BitmapBuffer, Error as RasterizerError, FontDesc, FontKey, GlyphKey, Rasterize,
+    BitmapBuffer, Error as RasterizerError, FontDesc, FontKey, GlyphKey, Metrics, Rasterize,
     RasterizedGlyph, Rasterizer, Size, Slant, Style, Weight,
 };
 use fnv::FnvHasher;
 use log::{error, info};
 use unicode_width::UnicodeWidthChar;
 
+use alacritty_terminal::graphics::UpdateQueues;
 use alacritty_terminal::index::Point;
 use alacritty_terminal::term::cell::Flags;
 use alacritty_terminal::term::color::Rgb;
 use alacritty_terminal::term::SizeInfo;
 
 use crate::config::font::{Font, FontDescription};
-use crate::config::ui_config::{Delta, UiConfig};
+use crate::config::ui_config::Delta;
 use crate::display::content::RenderableCell;
+use crate::display::export::Region;
 use crate::gl;
 use crate::gl::types::*;
+use crate::renderer::graphics::GraphicsRenderer;
 use crate::renderer::rects::{RectRenderer, RenderRect};
+use crate::renderer::synthesis::{FontStyle, StyleFonts};
 
+mod graphics;
+mod hex_box;
 pub mod rects;
+mod synthesis;
 
 // Shader source.
 static TEXT_SHADER_F: &str = include_str!("../../res/text.f.glsl");
@@ -106,13 +113,32 @@ pub struct Glyph {
     uv_height: f32,
 }
 
+impl Glyph {
+    /// Check if the glyph has no visible pixels, like the glyph for a space.
+    fn is_blank(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Glyph loaded into the atlas.
+#[derive(Copy, Clone, Debug)]
+struct CachedGlyph {
+    glyph: Glyph,
+
+    /// Last frame the glyph was drawn in.
+    frame: usize,
+}
+
 /// Naïve glyph cache.
 ///
 /// Currently only keyed by `char`, and thus not possible to hold different
 /// representations of the same code point.
 pub struct GlyphCache {
     /// Cache of buffered glyphs.
-    cache: HashMap<GlyphKey, Glyph, BuildHasherDefault<FnvHasher>>,
+    cache: HashMap<GlyphKey, CachedGlyph, BuildHasherDefault<FnvHasher>>,
+
+    /// Number of the frame which is currently drawn.
+    frame: usize,
 
     /// Rasterizer for loading new glyphs.
     rasterizer: Rasterizer,
@@ -120,14 +146,8 @@ pub struct GlyphCache {
     /// Regular font.
     font_key: FontKey,
 
-    /// Bold font.
-    bold_key: FontKey,
-
-    /// Italic font.
-    italic_key: FontKey,
-
-    /// Bold italic font.
-    bold_italic_key: FontKey,
+    /// Bold and italic fonts, loaded on first use.
+    styles: StyleFonts,
 
     /// Font size.
     font_size: crossfont::Size,
@@ -137,18 +157,29 @@ pub struct GlyphCache {
 
     /// Font metrics.
     metrics: crossfont::Metrics,
+
+    /// Font used for loading the glyphs, ignoring its size.
+    font: Font,
+
+    /// Characters missing from all fonts.
+    missing_glyphs: BTreeSet<char>,
+
+    /// Whether characters were added to `missing_glyphs` since they were last taken.
+    missing_glyphs_pending: bool,
 }
 
 impl GlyphCache {
-    pub fn new<L>(
+    /// Create a new glyph cache.
+    ///
+    /// Glyphs are only rasterized once they are requested, common glyphs can be loaded ahead of
+    /// time using [`GlyphCache::load_common_glyphs`].
+    pub fn new(
         mut rasterizer: Rasterizer,
         font: &Font,
-        loader: &mut L,
-    ) -> Result<GlyphCache, crossfont::Error>
-    where
-        L: LoadGlyph,
-    {
-        let (regular, bold, italic, bold_italic) = Self::compute_font_keys(font, &mut rasterizer)?;
+        dpr: f64,
+    ) -> Result<GlyphCache, crossfont::Error> {
+        let regular_desc = Self::make_desc(&font.normal(), Slant::Normal, Weight::Normal);
+        let regular = Self::load_regular_font(&mut rasterizer, &regular_desc, font.size())?;
 
         // Need to load at least one glyph for the face before calling metrics.
         // The glyph requested here ('m' at the time of writing) has no special
@@ -156,22 +187,21 @@ impl GlyphCache {
         rasterizer.get_glyph(GlyphKey { font_key: regular, character: 'm', size: font.size() })?;
 
         let metrics = rasterizer.metrics(regular, font.size())?;
+        let metrics = font.metrics.apply(metrics, dpr);
 
-        let mut cache = Self {
+        Ok(Self {
             cache: HashMap::default(),
+            frame: 0,
             rasterizer,
             font_size: font.size(),
             font_key: regular,
-            bold_key: bold,
-            italic_key: italic,
-            bold_italic_key: bold_italic,
+            styles: StyleFonts::new(regular, font.synthesize),
             glyph_offset: font.glyph_offset,
             metrics,
-        };
-
-        cache.load_common_glyphs(loader);
-
-        Ok(cache)
+            font: font.clone(),
+            missing_glyphs: BTreeSet::new(),
+            missing_glyphs_pending: false,
+        })
     }
 
     fn load_glyphs_for_font<L: LoadGlyph>(&mut self, font: FontKey, loader: &mut L) {
@@ -183,43 +213,28 @@ impl GlyphCache {
         }
     }
 
-    /// Computes font keys for (Regular, Bold, Italic, Bold Italic).
-    fn compute_font_keys(
-        font: &Font,
+    /// Load the font family's font for a style.
+    ///
+    /// Returns `None` if the font family has no font for the style.
+    fn load_style_font(
         rasterizer: &mut Rasterizer,
-    ) -> Result<(FontKey, FontKey, FontKey, FontKey), crossfont::Error> {
-        let size = font.size();
-
-        // Load regular font.
-        let regular_desc = Self::make_desc(&font.normal(), Slant::Normal, Weight::Normal);
-
-        let regular = Self::load_regular_font(rasterizer, &regular_desc, size)?;
-
-        // Helper to load a description if it is not the `regular_desc`.
-        let mut load_or_regular = |desc: FontDesc| {
-            if desc == regular_desc {
-                regular
-            } else {
-                rasterizer.load_font(&desc, size).unwrap_or(regular)
-            }
+        font: &Font,
+        regular: FontKey,
+        style: FontStyle,
+    ) -> Option<FontKey> {
+        let desc = match style {
+            FontStyle::Bold => Self::make_desc(&font.bold(), Slant::Normal, Weight::Bold),
+            FontStyle::Italic => Self::make_desc(&font.italic(), Slant::Italic, Weight::Normal),
+            FontStyle::BoldItalic => {
+                Self::make_desc(&font.bold_italic(), Slant::Italic, Weight::Bold)
+            },
         };
 
-        // Load bold font.
-        let bold_desc = Self::make_desc(&font.bold(), Slant::Normal, Weight::Bold);
-
-        let bold = load_or_regular(bold_desc);
-
-        // Load italic font.
-        let italic_desc = Self::make_desc(&font.italic(), Slant::Italic, Weight::Normal);
-
-        let italic = load_or_regular(italic_desc);
-
-        // Load bold italic font.
-        let bold_italic_desc = Self::make_desc(&font.bold_italic(), Slant::Italic, Weight::Bold);
-
-        let bold_italic = load_or_regular(bold_italic_desc);
-
-        Ok((regular, bold, italic, bold_italic))
+        if desc == Self::make_desc(&font.normal(), Slant::Normal, Weight::Normal) {
+            Some(regular)
+        } else {
+            rasterizer.load_font(&desc, font.size()).ok()
+        }
     }
 
     fn load_regular_font(
@@ -262,32 +277,66 @@ impl GlyphCache {
         L: LoadGlyph,
     {
         // Try to load glyph from cache.
-        if let Some(glyph) = self.cache.get(&glyph_key) {
-            return *glyph;
+        if let Some(cached) = self.cache.get_mut(&glyph_key) {
+            cached.frame = self.frame;
+            return cached.glyph;
         };
 
         // Rasterize glyph.
-        let glyph = match self.rasterizer.get_glyph(glyph_key) {
+        let glyph = match self.rasterize(glyph_key) {
             Ok(rasterized) => self.load_glyph(loader, rasterized),
-            // Load fallback glyph.
-            Err(RasterizerError::MissingGlyph(rasterized)) if show_missing => {
-                // Use `\0` as "missing" glyph to cache it only once.
-                let missing_key = GlyphKey { character: '\0', ..glyph_key };
-                if let Some(glyph) = self.cache.get(&missing_key) {
-                    *glyph
-                } else {
-                    // If no missing glyph was loaded yet, insert it as `\0`.
-                    let glyph = self.load_glyph(loader, rasterized);
-                    self.cache.insert(missing_key, glyph);
+            // Show the codepoint of characters missing from all fonts.
+            Err(RasterizerError::MissingGlyph(_)) => {
+                self.add_missing_glyph(glyph_key.character);
 
-                    glyph
+                if show_missing {
+                    let hex_box = hex_box::hex_box(glyph_key.character, &self.metrics);
+                    self.load_glyph(loader, hex_box)
+                } else {
+                    self.load_glyph(loader, Default::default())
                 }
             },
             Err(_) => self.load_glyph(loader, Default::default()),
         };
 
         // Cache rasterized glyph.
-        *self.cache.entry(glyph_key).or_insert(glyph)
+        self.cache.entry(glyph_key).or_insert(CachedGlyph { glyph, frame: self.frame }).glyph
+    }
+
+    /// Log a character missing from all fonts, unless it has been logged before.
+    fn add_missing_glyph(&mut self, character: char) {
+        if self.missing_glyphs.insert(character) {
+            info!("No font contains a glyph for U+{:04X}", character as u32);
+            self.missing_glyphs_pending = true;
+        }
+    }
+
+    /// All characters missing from the fonts, if new ones were found since the last call.
+    pub fn take_missing_glyphs(&mut self) -> Option<Vec<char>> {
+        if !self.missing_glyphs_pending {
+            return None;
+        }
+
+        self.missing_glyphs_pending = false;
+        Some(self.missing_glyphs.iter().copied().collect())
+    }
+
+    /// Rasterize a glyph, emulating the style of synthetic fonts.
+    ///
+    /// The fonts for bold and italic text are loaded once their first glyph is requested.
+    fn rasterize(&mut self, glyph_key: GlyphKey) -> Result<RasterizedGlyph, RasterizerError> {
+        let (rasterizer, font, regular) = (&mut self.rasterizer, &self.font, self.font_key);
+        let load = |style| Self::load_style_font(rasterizer, font, regular, style);
+        let synthetic = match self.styles.resolve(glyph_key.font_key, load) {
+            Some(synthetic) => synthetic,
+            None => return self.rasterizer.get_glyph(glyph_key),
+        };
+
+        let base_key = GlyphKey { font_key: synthetic.base, ..glyph_key };
+        let mut glyph = self.rasterizer.get_glyph(base_key)?;
+        synthetic.transform(&mut glyph);
+
+        Ok(glyph)
     }
 
     /// Load glyph into the atlas.
@@ -323,6 +372,37 @@ impl GlyphCache {
         self.load_common_glyphs(loader);
     }
 
+    /// Start drawing a new frame, tracking which glyphs are used by it.
+    pub fn start_frame(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    /// Evict all glyphs which were not drawn in the last frame.
+    ///
+    /// The atlas is cleared and only the remaining glyphs are loaded into it again, freeing the
+    /// space used by the evicted ones.
+    pub fn trim<L: LoadGlyph>(&mut self, loader: &mut L) {
+        // Glyphs missing from all fonts are skipped, since they are loaded differently based on
+        // their context. They are loaded again the next time they are drawn.
+        let frame = self.frame;
+        let missing_glyphs = &self.missing_glyphs;
+        let used: Vec<GlyphKey> = self
+            .cache
+            .iter()
+            .filter(|(key, cached)| {
+                cached.frame == frame && !missing_glyphs.contains(&key.character)
+            })
+            .map(|(key, _)| *key)
+            .collect();
+
+        loader.clear();
+        self.cache = HashMap::default();
+
+        for glyph_key in used {
+            self.get(glyph_key, loader, true);
+        }
+    }
+
     pub fn update_font_size<L: LoadGlyph>(
         &mut self,
         font: &Font,
@@ -332,9 +412,9 @@ impl GlyphCache {
         // Update dpi scaling.
         self.rasterizer.update_dpr(dpr as f32);
 
-        // Recompute font keys.
-        let (regular, bold, italic, bold_italic) =
-            Self::compute_font_keys(font, &mut self.rasterizer)?;
+        // Reload the regular font, other styles are loaded once they are used.
+        let regular_desc = Self::make_desc(&font.normal(), Slant::Normal, Weight::Normal);
+        let regular = Self::load_regular_font(&mut self.rasterizer, &regular_desc, font.size())?;
 
         self.rasterizer.get_glyph(GlyphKey {
             font_key: regular,
@@ -342,14 +422,21 @@ impl GlyphCache {
             size: font.size(),
         })?;
         let metrics = self.rasterizer.metrics(regular, font.size())?;
+        let metrics = font.metrics.apply(metrics, dpr);
 
         info!("Font size changed to {:?} with DPR of {}", font.size(), dpr);
 
+        // Report missing characters again, since the new font might not contain them either.
+        if font.clone().with_size(self.font.size()) != self.font {
+            self.missing_glyphs.clear();
+            self.missing_glyphs_pending = false;
+        }
+        self.font = font.clone();
+
         self.font_size = font.size();
         self.font_key = regular;
-        self.bold_key = bold;
-        self.italic_key = italic;
-        self.bold_italic_key = bold_italic;
+        self.styles = StyleFonts::new(regular, font.synthesize);
+        self.glyph_offset = font.glyph_offset;
         self.metrics = metrics;
 
         self.clear_glyph_cache(loader);
@@ -361,12 +448,16 @@ impl GlyphCache {
         self.metrics
     }
 
+    /// Number of glyphs loaded into the atlas.
+    pub fn glyph_count(&self) -> usize {
+        self.cache.len()
+    }
+
     /// Prefetch glyphs that are almost guaranteed to be loaded anyways.
-    fn load_common_glyphs<L: LoadGlyph>(&mut self, loader: &mut L) {
+    ///
+    /// Only the regular font is used, so bold and italic fonts are still loaded on first use.
+    pub fn load_common_glyphs<L: LoadGlyph>(&mut self, loader: &mut L) {
         self.load_glyphs_for_font(self.font_key, loader);
-        self.load_glyphs_for_font(self.bold_key, loader);
-        self.load_glyphs_for_font(self.italic_key, loader);
-        self.load_glyphs_for_font(self.bold_italic_key, loader);
     }
 
     /// Calculate font metrics without access to a glyph cache.
@@ -376,7 +467,8 @@ impl GlyphCache {
         let regular = Self::load_regular_font(&mut rasterizer, &regular_desc, font.size())?;
         rasterizer.get_glyph(GlyphKey { font_key: regular, character: 'm', size: font.size() })?;
 
-        rasterizer.metrics(regular, font.size())
+        let metrics = rasterizer.metrics(regular, font.size())?;
+        Ok(font.metrics.apply(metrics, dpr))
     }
 }
 
@@ -384,8 +476,7 @@ impl GlyphCache {
 bitflags! {
     #[repr(C)]
     struct RenderingGlyphFlags: u8 {
-        const WIDE_CHAR = 0b0000_0001;
-        const COLORED   = 0b0000_0010;
+        const COLORED = 0b0000_0010;
     }
 }
 
@@ -425,6 +516,9 @@ struct InstanceData {
     bg_g: u8,
     bg_b: u8,
     bg_a: u8,
+
+    // Number of columns covered by the background.
+    bg_columns: u16,
 }
 
 #[derive(Debug)]
@@ -439,6 +533,7 @@ pub struct QuadRenderer {
     batch: Batch,
 
     rect_renderer: RectRenderer,
+    graphics_renderer: GraphicsRenderer,
 }
 
 #[derive(Debug)]
@@ -448,7 +543,6 @@ pub struct RenderApi<'a> {
     atlas: &'a mut Vec<Atlas>,
     current_atlas: &'a mut usize,
     program: &'a mut TextShaderProgram,
-    config: &'a UiConfig,
 }
 
 #[derive(Debug)]
@@ -470,14 +564,14 @@ impl Batch {
         Self { tex: 0, instances: Vec::with_capacity(BATCH_MAX) }
     }
 
-    pub fn add_item(&mut self, cell: &RenderableCell, glyph: &Glyph) {
+    /// Add a glyph, drawing the background of the following `bg_columns` cells.
+    pub fn add_item(&mut self, cell: &RenderableCell, glyph: &Glyph, bg_columns: usize) {
         if self.is_empty() {
             self.tex = glyph.tex_id;
         }
 
         let mut cell_flags = RenderingGlyphFlags::empty();
         cell_flags.set(RenderingGlyphFlags::COLORED, glyph.multicolor);
-        cell_flags.set(RenderingGlyphFlags::WIDE_CHAR, cell.flags.contains(Flags::WIDE_CHAR));
 
         self.instances.push(InstanceData {
             col: cell.point.column.0 as u16,
@@ -502,6 +596,8 @@ impl Batch {
             bg_g: cell.bg.g,
             bg_b: cell.bg.b,
             bg_a: (cell.bg_alpha * 255.0) as u8,
+
+            bg_columns: bg_columns as u16,
         });
     }
 
@@ -629,6 +725,9 @@ impl QuadRenderer {
             // Background color.
             add_attr!(4, gl::UNSIGNED_BYTE, u8);
 
+            // Background columns.
+            add_attr!(1, gl::UNSIGNED_SHORT, u16);
+
             // Cleanup.
             gl::BindVertexArray(0);
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
@@ -638,6 +737,7 @@ impl QuadRenderer {
         let mut renderer = Self {
             program,
             rect_renderer: RectRenderer::new()?,
+            graphics_renderer: GraphicsRenderer::new()?,
             vao,
             ebo,
             vbo_instance,
@@ -654,7 +754,7 @@ impl QuadRenderer {
     }
 
     /// Draw all rectangles simultaneously to prevent excessive program swaps.
-    pub fn draw_rects(&mut self, size_info: &SizeInfo, rects: Vec<RenderRect>) {
+    pub fn draw_rects(&mut self, size_info: &SizeInfo, metrics: &Metrics, rects: Vec<RenderRect>) {
         if rects.is_empty() {
             return;
         }
@@ -666,7 +766,40 @@ impl QuadRenderer {
             gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::SRC_ALPHA, gl::ONE);
         }
 
-        self.rect_renderer.draw(size_info, rects);
+        self.rect_renderer.draw(size_info, metrics, rects);
+
+        // Activate regular state again.
+        unsafe {
+            // Reset blending strategy.
+            gl::BlendFunc(gl::SRC1_COLOR, gl::ONE_MINUS_SRC1_COLOR);
+
+            // Restore viewport with padding.
+            set_viewport(size_info);
+        }
+    }
+
+    /// Upload new graphics and delete the ones which are no longer used.
+    pub fn update_graphics(&mut self, queues: UpdateQueues) {
+        self.graphics_renderer.update(queues);
+    }
+
+    /// Draw the graphics shown in the grid cells.
+    pub fn draw_graphics(&mut self, size_info: &SizeInfo, cells: &[RenderableCell]) {
+        if cells.iter().all(|cell| cell.graphic.is_none()) {
+            return;
+        }
+
+        // Prepare graphics rendering state.
+        unsafe {
+            // Remove padding from viewport.
+            gl::Viewport(0, 0, size_info.width() as i32, size_info.height() as i32);
+            gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::SRC_ALPHA, gl::ONE);
+        }
+
+        self.graphics_renderer.draw(size_info, cells);
+
+        // Force the glyph atlas to be bound again, since the graphics replaced it.
+        self.active_tex = 0;
 
         // Activate regular state again.
         unsafe {
@@ -674,15 +807,11 @@ impl QuadRenderer {
             gl::BlendFunc(gl::SRC1_COLOR, gl::ONE_MINUS_SRC1_COLOR);
 
             // Restore viewport with padding.
-            let padding_x = size_info.padding_x() as i32;
-            let padding_y = size_info.padding_y() as i32;
-            let width = size_info.width() as i32;
-            let height = size_info.height() as i32;
-            gl::Viewport(padding_x, padding_y, width - 2 * padding_x, height - 2 * padding_y);
+            set_viewport(size_info);
         }
     }
 
-    pub fn with_api<F, T>(&mut self, config: &UiConfig, props: &SizeInfo, func: F) -> T
+    pub fn with_api<F, T>(&mut self, props: &SizeInfo, func: F) -> T
     where
         F: FnOnce(RenderApi<'_>) -> T,
     {
@@ -702,7 +831,6 @@ impl QuadRenderer {
             atlas: &mut self.atlas,
             current_atlas: &mut self.current_atlas,
             program: &mut self.program,
-            config,
         });
 
         unsafe {
@@ -716,6 +844,43 @@ impl QuadRenderer {
         res
     }
 
+    /// Read the RGBA pixels of a region from the current framebuffer.
+    ///
+    /// The rows are returned from top to bottom.
+    pub fn read_pixels(&self, size_info: &SizeInfo, region: Region) -> Vec<u8> {
+        if region.width == 0 || region.height == 0 {
+            return Vec::new();
+        }
+
+        let row_len = region.width as usize * 4;
+        let mut pixels = vec![0u8; row_len * region.height as usize];
+
+        // OpenGL's origin is in the bottom-left corner.
+        let y = size_info.height() as i32 - (region.y + region.height) as i32;
+
+        unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                region.x as i32,
+                y,
+                region.width as i32,
+                region.height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _,
+            );
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+        }
+
+        // Flip the image vertically.
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for row in pixels.chunks_exact(row_len).rev() {
+            flipped.extend_from_slice(row);
+        }
+
+        flipped
+    }
+
     pub fn with_loader<F, T>(&mut self, func: F) -> T
     where
         F: FnOnce(LoaderApi<'_>) -> T,
@@ -732,26 +897,43 @@ impl QuadRenderer {
     }
 
     pub fn resize(&mut self, size: &SizeInfo) {
+        self.resize_magnified(size, 1., 1.);
+    }
+
+    /// Resize the viewport, projecting a magnified text area onto it.
+    ///
+    /// This allows rendering glyphs rasterized for a magnified grid at their regular size.
+    pub fn resize_magnified(&mut self, size: &SizeInfo, factor_x: f32, factor_y: f32) {
         // Viewport.
         unsafe {
-            gl::Viewport(
-                size.padding_x() as i32,
-                size.padding_y() as i32,
-                size.width() as i32 - 2 * size.padding_x() as i32,
-                size.height() as i32 - 2 * size.padding_y() as i32,
-            );
+            set_viewport(size);
 
             // Update projection.
             gl::UseProgram(self.program.id);
             self.program.update_projection(
-                size.width(),
-                size.height(),
-                size.padding_x(),
-                size.padding_y(),
+                (size.width() - size.padding_x() - size.padding_right()) * factor_x,
+                (size.height() - size.padding_y() - size.padding_bottom()) * factor_y,
             );
             gl::UseProgram(0);
         }
     }
+
+    /// Estimate the GPU memory used by all glyph atlas textures.
+    pub fn atlas_memory_usage(&self) -> usize {
+        // Atlas textures use four bytes per pixel.
+        self.atlas.iter().map(|atlas| atlas.width as usize * atlas.height as usize * 4).sum()
+    }
+
+    /// Delete all atlas textures after the one currently used for loading glyphs.
+    ///
+    /// This should only be called after clearing the glyph cache, since all atlases after the
+    /// current one are guaranteed to be empty at that point.
+    pub fn trim_atlas(&mut self) {
+        self.atlas.truncate(self.current_atlas + 1);
+
+        // Force the glyph atlas to be bound again, since the texture might have been deleted.
+        self.active_tex = 0;
+    }
 }
 
 impl Drop for QuadRenderer {
@@ -765,9 +947,8 @@ impl Drop for QuadRenderer {
 }
 
 impl<'a> RenderApi<'a> {
-    pub fn clear(&self, color: Rgb) {
+    pub fn clear(&self, color: Rgb, alpha: f32) {
         unsafe {
-            let alpha = self.config.background_opacity();
             gl::ClearColor(
                 (f32::from(color.r) / 255.0).min(1.0) * alpha,
                 (f32::from(color.g) / 255.0).min(1.0) * alpha,
@@ -842,6 +1023,7 @@ impl<'a> RenderApi<'a> {
                 point: Point::new(point.line, point.column + i),
                 character,
                 zerowidth: None,
+                graphic: None,
                 flags: Flags::empty(),
                 bg_alpha: 1.0,
                 fg,
@@ -849,19 +1031,19 @@ impl<'a> RenderApi<'a> {
             })
             .collect::<Vec<_>>();
 
-        for cell in cells {
+        for cell in &cells {
             self.render_cell(cell, glyph_cache);
         }
     }
 
     #[inline]
-    fn add_render_item(&mut self, cell: &RenderableCell, glyph: &Glyph) {
+    fn add_render_item(&mut self, cell: &RenderableCell, glyph: &Glyph, bg_columns: usize) {
         // Flush batch if tex changing.
         if !self.batch.is_empty() && self.batch.tex != glyph.tex_id {
             self.render_batch();
         }
 
-        self.batch.add_item(cell, glyph);
+        self.batch.add_item(cell, glyph, bg_columns);
 
         // Render batch and clear if it's full.
         if self.batch.full() {
@@ -869,34 +1051,68 @@ impl<'a> RenderApi<'a> {
         }
     }
 
-    pub fn render_cell(&mut self, mut cell: RenderableCell, glyph_cache: &mut GlyphCache) {
-        // Get font key for cell.
-        let font_key = match cell.flags & Flags::BOLD_ITALIC {
-            Flags::BOLD_ITALIC => glyph_cache.bold_italic_key,
-            Flags::ITALIC => glyph_cache.italic_key,
-            Flags::BOLD => glyph_cache.bold_key,
-            _ => glyph_cache.font_key,
-        };
+    /// Render all cells of a run.
+    ///
+    /// The font and visibility are resolved once for the whole run. A single instance draws the
+    /// background of all cells, so cells without any visible glyph need no instance at all.
+    pub fn render_run(&mut self, run: &[RenderableCell], glyph_cache: &mut GlyphCache) {
+        let (first, last) = (&run[0], &run[run.len() - 1]);
+        let flags = first.flags;
+        let font_key = Self::font_key(flags, glyph_cache);
+        let hidden = flags.contains(Flags::HIDDEN);
+
+        let mut bg_columns = last.end_column().0 + 1 - first.point.column.0;
+        for cell in run {
+            self.render_glyphs(cell, font_key, hidden, bg_columns, glyph_cache);
+            bg_columns = 0;
+        }
+    }
 
-        // Ignore hidden cells and render tabs as spaces to prevent font issues.
+    pub fn render_cell(&mut self, cell: &RenderableCell, glyph_cache: &mut GlyphCache) {
+        let font_key = Self::font_key(cell.flags, glyph_cache);
         let hidden = cell.flags.contains(Flags::HIDDEN);
-        if cell.character == '\t' || hidden {
-            cell.character = ' ';
+        let bg_columns = cell.end_column().0 + 1 - cell.point.column.0;
+        self.render_glyphs(cell, font_key, hidden, bg_columns, glyph_cache);
+    }
+
+    /// Get font key for cells with the specified text attributes.
+    fn font_key(flags: Flags, glyph_cache: &GlyphCache) -> FontKey {
+        match flags & Flags::BOLD_ITALIC {
+            Flags::BOLD_ITALIC => glyph_cache.styles.key(FontStyle::BoldItalic),
+            Flags::ITALIC => glyph_cache.styles.key(FontStyle::Italic),
+            Flags::BOLD => glyph_cache.styles.key(FontStyle::Bold),
+            _ => glyph_cache.font_key,
         }
+    }
+
+    /// Add the glyphs of a cell to the batch.
+    ///
+    /// Glyphs without any pixels are skipped, unless they have to draw a background.
+    fn render_glyphs(
+        &mut self,
+        cell: &RenderableCell,
+        font_key: FontKey,
+        hidden: bool,
+        bg_columns: usize,
+        glyph_cache: &mut GlyphCache,
+    ) {
+        // Ignore hidden cells and render tabs as spaces to prevent font issues.
+        let character = if cell.character == '\t' || hidden { ' ' } else { cell.character };
 
-        let mut glyph_key =
-            GlyphKey { font_key, size: glyph_cache.font_size, character: cell.character };
+        let mut glyph_key = GlyphKey { font_key, size: glyph_cache.font_size, character };
 
         // Add cell to batch.
         let glyph = glyph_cache.get(glyph_key, self, true);
-        self.add_render_item(&cell, &glyph);
+        if bg_columns > 0 || !glyph.is_blank() {
+            self.add_render_item(cell, &glyph, bg_columns);
+        }
 
         // Render visible zero-width characters.
-        if let Some(zerowidth) = cell.zerowidth.take().filter(|_| !hidden) {
+        if let Some(zerowidth) = cell.zerowidth.as_ref().filter(|_| !hidden) {
             for character in zerowidth {
-                glyph_key.character = character;
+                glyph_key.character = *character;
                 let glyph = glyph_cache.get(glyph_key, self, false);
-                self.add_render_item(&cell, &glyph);
+                self.add_render_item(cell, &glyph, 0);
             }
         }
     }
@@ -940,6 +1156,20 @@ fn load_glyph(
     }
 }
 
+/// Restrict the OpenGL viewport to the area inside the padding.
+///
+/// Since OpenGL's origin is in the bottom left corner, the bottom padding is used as Y offset.
+unsafe fn set_viewport(size: &SizeInfo) {
+    let width = size.width() - size.padding_x() - size.padding_right();
+    let height = size.height() - size.padding_y() - size.padding_bottom();
+    gl::Viewport(
+        size.padding_x() as i32,
+        size.padding_bottom() as i32,
+        width as i32,
+        height as i32,
+    );
+}
+
 #[inline]
 fn clear_atlas(atlas: &mut Vec<Atlas>, current_atlas: &mut usize) {
     for atlas in atlas.iter_mut() {
@@ -1029,17 +1259,18 @@ impl TextShaderProgram {
         Ok(shader)
     }
 
-    fn update_projection(&self, width: f32, height: f32, padding_x: f32, padding_y: f32) {
+    /// Update the projection for the area inside the padding.
+    fn update_projection(&self, width: f32, height: f32) {
         // Bounds check.
-        if (width as u32) < (2 * padding_x as u32) || (height as u32) < (2 * padding_y as u32) {
+        if width < 0. || height < 0. {
             return;
         }
 
         // Compute scale and offset factors, from pixel to ndc space. Y is inverted.
-        //   [0, width - 2 * padding_x] to [-1, 1]
-        //   [height - 2 * padding_y, 0] to [-1, 1]
-        let scale_x = 2. / (width - 2. * padding_x);
-        let scale_y = -2. / (height - 2. * padding_y);
+        //   [0, width] to [-1, 1]
+        //   [height, 0] to [-1, 1]
+        let scale_x = 2. / width;
+        let scale_y = -2. / height;
         let offset_x = -1.;
         let offset_y = 1.;
 
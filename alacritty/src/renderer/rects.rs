@@ -22,11 +22,58 @@ pub struct RenderRect {
     pub height: f32,
     pub color: Rgb,
     pub alpha: f32,
+    pub kind: RectKind,
 }
 
 impl RenderRect {
     pub fn new(x: f32, y: f32, width: f32, height: f32, color: Rgb, alpha: f32) -> Self {
-        RenderRect { x, y, width, height, color, alpha }
+        RenderRect { x, y, width, height, color, alpha, kind: RectKind::Normal }
+    }
+}
+
+/// Shape drawn inside a rect.
+///
+/// NOTE: The discriminants must be in sync with their usage in the rect.f.glsl shader.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RectKind {
+    /// Fill the entire rect.
+    Normal = 0,
+
+    /// Sine wave with one period per cell, shaped by [`Undercurl`].
+    Undercurl = 1,
+}
+
+/// Shape of the undercurl within its cell.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Undercurl {
+    /// Distance from the top of the cell to the center of the wave.
+    pub position: f32,
+
+    /// Distance from the center of the wave to its highest and lowest point.
+    pub amplitude: f32,
+
+    /// Thickness of the wave's stroke.
+    pub thickness: f32,
+}
+
+impl Undercurl {
+    pub fn new(metrics: &Metrics, size: &SizeInfo) -> Self {
+        let origin = Point::new(0, Column(0));
+        let line = RenderLine::create_rect(
+            size,
+            metrics.descent,
+            origin,
+            origin,
+            metrics.underline_position,
+            metrics.underline_thickness,
+            Rgb::default(),
+        );
+
+        // Keep the wave's lowest point above the next line.
+        let amplitude = (metrics.descent.abs() / 4.).max(1.);
+        let top = (line.y - size.padding_y()).min(size.cell_height() - line.height - amplitude);
+
+        Self { position: top + line.height / 2., amplitude, thickness: line.height }
     }
 }
 
@@ -80,6 +127,45 @@ impl RenderLine {
 
                 (bottom_pos, metrics.underline_thickness)
             },
+            Flags::UNDERCURL => {
+                let line = Self::create_rect(
+                    size,
+                    metrics.descent,
+                    start,
+                    end,
+                    metrics.underline_position,
+                    metrics.underline_thickness,
+                    color,
+                );
+
+                // The wave is drawn by the shader, so the rect covers the entire line.
+                let y = start.line as f32 * size.cell_height() + size.padding_y();
+                let height = size.cell_height();
+                rects.push(RenderRect { y, height, kind: RectKind::Undercurl, ..line });
+                return;
+            },
+            Flags::DOTTED_UNDERLINE | Flags::DASHED_UNDERLINE => {
+                let line = Self::create_rect(
+                    size,
+                    metrics.descent,
+                    start,
+                    end,
+                    metrics.underline_position,
+                    metrics.underline_thickness,
+                    color,
+                );
+
+                // Dots are square, while every cell has one centered dash.
+                let cell_width = size.cell_width();
+                let (length, period, offset) = if flag == Flags::DOTTED_UNDERLINE {
+                    (line.height, 2. * line.height, 0.)
+                } else {
+                    ((cell_width / 2.).round(), cell_width, (cell_width / 4.).round())
+                };
+
+                Self::push_segments(rects, size, line, length, period, offset);
+                return;
+            },
             Flags::UNDERLINE => (metrics.underline_position, metrics.underline_thickness),
             Flags::STRIKEOUT => (metrics.strikeout_position, metrics.strikeout_thickness),
             _ => unimplemented!("Invalid flag for cell line drawing specified"),
@@ -96,6 +182,31 @@ impl RenderLine {
         ));
     }
 
+    /// Split a line into segments of `length`, repeating every `period` pixels.
+    ///
+    /// Segments are aligned to the grid origin, so the pattern is continuous across cell runs.
+    fn push_segments(
+        rects: &mut Vec<RenderRect>,
+        size: &SizeInfo,
+        line: RenderRect,
+        length: f32,
+        period: f32,
+        offset: f32,
+    ) {
+        let line_end = line.x + line.width;
+        let origin = size.padding_x() + offset;
+
+        let mut x = origin + ((line.x - origin) / period).floor() * period;
+        while x < line_end {
+            let start = x.max(line.x);
+            let end = (x + length).min(line_end);
+            if end > start {
+                rects.push(RenderRect { x: start, width: end - start, ..line });
+            }
+            x += period;
+        }
+    }
+
     /// Create a line's rect at a position relative to the baseline.
     fn create_rect(
         size: &SizeInfo,
@@ -155,31 +266,33 @@ impl RenderLines {
             .collect()
     }
 
-    /// Update the stored lines with the next cell info.
+    /// Update the stored lines with the next run of cells.
     #[inline]
-    pub fn update(&mut self, cell: &RenderableCell) {
-        self.update_flag(&cell, Flags::UNDERLINE);
-        self.update_flag(&cell, Flags::DOUBLE_UNDERLINE);
-        self.update_flag(&cell, Flags::STRIKEOUT);
+    pub fn update(&mut self, run: &[RenderableCell]) {
+        self.update_flag(run, Flags::UNDERLINE);
+        self.update_flag(run, Flags::DOUBLE_UNDERLINE);
+        self.update_flag(run, Flags::UNDERCURL);
+        self.update_flag(run, Flags::DOTTED_UNDERLINE);
+        self.update_flag(run, Flags::DASHED_UNDERLINE);
+        self.update_flag(run, Flags::STRIKEOUT);
     }
 
     /// Update the lines for a specific flag.
-    fn update_flag(&mut self, cell: &RenderableCell, flag: Flags) {
-        if !cell.flags.contains(flag) {
+    fn update_flag(&mut self, run: &[RenderableCell], flag: Flags) {
+        let (first, last) = (&run[0], &run[run.len() - 1]);
+        if !first.flags.contains(flag) {
             return;
         }
 
-        // Include wide char spacer if the current cell is a wide char.
-        let mut end = cell.point;
-        if cell.flags.contains(Flags::WIDE_CHAR) {
-            end.column += 1;
-        }
+        let start = first.point;
+        let end = Point::new(last.point.line, last.end_column());
+        let color = first.fg;
 
         // Check if there's an active line.
         if let Some(line) = self.inner.get_mut(&flag).and_then(|lines| lines.last_mut()) {
-            if cell.fg == line.color
-                && cell.point.column == line.end.column + 1
-                && cell.point.line == line.end.line
+            if color == line.color
+                && start.column == line.end.column + 1
+                && start.line == line.end.line
             {
                 // Update the length of the line.
                 line.end = end;
@@ -188,7 +301,7 @@ impl RenderLines {
         }
 
         // Start new line if there currently is none.
-        let line = RenderLine { start: cell.point, end, color: cell.fg };
+        let line = RenderLine { start, end, color };
         match self.inner.get_mut(&flag) {
             Some(lines) => lines.push(line),
             None => {
@@ -276,7 +389,7 @@ impl RectRenderer {
         Ok(Self { vao, vbo, program, vertices: Vec::new() })
     }
 
-    pub fn draw(&mut self, size_info: &SizeInfo, rects: Vec<RenderRect>) {
+    pub fn draw(&mut self, size_info: &SizeInfo, metrics: &Metrics, rects: Vec<RenderRect>) {
         unsafe {
             // Bind VAO to enable vertex attribute slots.
             gl::BindVertexArray(self.vao);
@@ -287,16 +400,43 @@ impl RectRenderer {
             gl::UseProgram(self.program.id);
         }
 
+        self.program.update_uniforms(size_info, &Undercurl::new(metrics, size_info));
+
         let half_width = size_info.width() / 2.;
         let half_height = size_info.height() / 2.;
 
-        // Build rect vertices vector.
+        // Build rect vertices vector, drawing them whenever the kind of rect changes.
         self.vertices.clear();
+        let mut kind = RectKind::Normal;
         for rect in &rects {
+            if rect.kind != kind {
+                self.draw_vertices(kind);
+                kind = rect.kind;
+            }
+
             self.add_rect(half_width, half_height, rect);
         }
+        self.draw_vertices(kind);
 
         unsafe {
+            // Disable program.
+            gl::UseProgram(0);
+
+            // Reset buffer bindings to nothing.
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+    }
+
+    /// Draw all accumulated vertices as rects of the specified kind.
+    fn draw_vertices(&mut self, kind: RectKind) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        unsafe {
+            gl::Uniform1i(self.program.u_rect_kind, kind as i32);
+
             // Upload accumulated vertices.
             gl::BufferData(
                 gl::ARRAY_BUFFER,
@@ -307,14 +447,9 @@ impl RectRenderer {
 
             // Draw all vertices as list of triangles.
             gl::DrawArrays(gl::TRIANGLES, 0, self.vertices.len() as i32);
-
-            // Disable program.
-            gl::UseProgram(0);
-
-            // Reset buffer bindings to nothing.
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
         }
+
+        self.vertices.clear();
     }
 
     fn add_rect(&mut self, half_width: f32, half_height: f32, rect: &RenderRect) {
@@ -355,10 +490,24 @@ impl Drop for RectRenderer {
 }
 
 /// Rectangle drawing program.
+///
+/// Uniforms are prefixed with "u".
 #[derive(Debug)]
 pub struct RectShaderProgram {
     /// Program id.
     id: GLuint,
+
+    /// Kind of the rects which are drawn.
+    u_rect_kind: GLint,
+
+    /// Cell dimensions (pixels).
+    u_cell_dim: GLint,
+
+    /// Top left corner of the grid, with the Y axis pointing up (pixels).
+    u_grid_origin: GLint,
+
+    /// Position, amplitude and thickness of the undercurl (pixels).
+    u_undercurl: GLint,
 }
 
 impl RectShaderProgram {
@@ -373,12 +522,37 @@ impl RectShaderProgram {
             gl::UseProgram(program);
         }
 
-        let shader = Self { id: program };
+        let uniform =
+            |name: &[u8]| unsafe { gl::GetUniformLocation(program, name.as_ptr() as *const _) };
+
+        let shader = Self {
+            id: program,
+            u_rect_kind: uniform(b"rectKind\0"),
+            u_cell_dim: uniform(b"cellDim\0"),
+            u_grid_origin: uniform(b"gridOrigin\0"),
+            u_undercurl: uniform(b"undercurl\0"),
+        };
 
         unsafe { gl::UseProgram(0) }
 
         Ok(shader)
     }
+
+    /// Update the uniforms shared by all rects.
+    fn update_uniforms(&self, size_info: &SizeInfo, undercurl: &Undercurl) {
+        let origin_y = size_info.height() - size_info.padding_y();
+
+        unsafe {
+            gl::Uniform2f(self.u_cell_dim, size_info.cell_width(), size_info.cell_height());
+            gl::Uniform2f(self.u_grid_origin, size_info.padding_x(), origin_y);
+            gl::Uniform3f(
+                self.u_undercurl,
+                undercurl.position,
+                undercurl.amplitude,
+                undercurl.thickness,
+            );
+        }
+    }
 }
 
 impl Drop for RectShaderProgram {
@@ -0,0 +1,337 @@
+//! Emulation of font styles missing from the configured font family.
+
+use std::collections::HashMap;
+
+use crossfont::{BitmapBuffer, FontKey, RasterizedGlyph};
+
+use crate::config::font::Synthesis;
+
+/// Horizontal shift per pixel of height used for oblique glyphs, roughly 11 degrees.
+const SLANT: f32 = 0.2;
+
+/// Font whose glyphs are created by transforming another font.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SyntheticFont {
+    /// Font providing the original glyphs.
+    pub base: FontKey,
+
+    /// Emulate bold glyphs.
+    pub bold: bool,
+
+    /// Emulate italic glyphs.
+    pub italic: bool,
+}
+
+impl SyntheticFont {
+    /// Apply all transforms to a glyph of the base font.
+    ///
+    /// Colored glyphs like emojis are left untouched.
+    pub fn transform(&self, glyph: &mut RasterizedGlyph) {
+        if !self.bold && !self.italic {
+            return;
+        }
+
+        let buffer = match &mut glyph.buffer {
+            BitmapBuffer::Rgb(buffer) => buffer,
+            BitmapBuffer::Rgba(_) => return,
+        };
+
+        let width = glyph.width.max(0) as usize;
+        let height = glyph.height.max(0) as usize;
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let mut bitmap = Bitmap { width, height, buffer: std::mem::take(buffer) };
+
+        if self.bold {
+            bitmap = bitmap.embolden();
+        }
+
+        if self.italic {
+            let (slanted, offset) = bitmap.slant(glyph.top);
+            bitmap = slanted;
+            glyph.left += offset;
+        }
+
+        glyph.width = bitmap.width as i32;
+        glyph.height = bitmap.height as i32;
+        glyph.buffer = BitmapBuffer::Rgb(bitmap.buffer);
+    }
+}
+
+/// Font styles other than the regular one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    Bold,
+    Italic,
+    BoldItalic,
+}
+
+/// Fonts for all styles of the font family, loaded once they are first used.
+///
+/// Looking up a font and its fallbacks is expensive, so every style is represented by a
+/// placeholder key until a glyph is rasterized with it.
+#[derive(Debug)]
+pub struct StyleFonts {
+    regular: FontKey,
+    bold: FontKey,
+    italic: FontKey,
+    bold_italic: FontKey,
+
+    /// Emulation of styles missing from the font family.
+    synthesis: Synthesis,
+
+    /// Fonts of the font family, `None` if the style is not available.
+    loaded: HashMap<FontStyle, Option<FontKey>>,
+
+    /// Fonts used to rasterize the glyphs of each placeholder key.
+    resolved: HashMap<FontKey, SyntheticFont>,
+}
+
+impl StyleFonts {
+    pub fn new(regular: FontKey, synthesis: Synthesis) -> Self {
+        Self {
+            regular,
+            synthesis,
+            bold: FontKey::next(),
+            italic: FontKey::next(),
+            bold_italic: FontKey::next(),
+            loaded: HashMap::new(),
+            resolved: HashMap::new(),
+        }
+    }
+
+    /// Key identifying the glyphs of a style.
+    pub fn key(&self, style: FontStyle) -> FontKey {
+        match style {
+            FontStyle::Bold => self.bold,
+            FontStyle::Italic => self.italic,
+            FontStyle::BoldItalic => self.bold_italic,
+        }
+    }
+
+    /// Font used to rasterize the glyphs with the specified key.
+    ///
+    /// Returns `None` if the key does not belong to a style. The font family's own font for a
+    /// style is requested from `load` the first time it is required.
+    pub fn resolve<F>(&mut self, key: FontKey, mut load: F) -> Option<SyntheticFont>
+    where
+        F: FnMut(FontStyle) -> Option<FontKey>,
+    {
+        let style = match key {
+            key if key == self.bold => FontStyle::Bold,
+            key if key == self.italic => FontStyle::Italic,
+            key if key == self.bold_italic => FontStyle::BoldItalic,
+            _ => return None,
+        };
+
+        if let Some(font) = self.resolved.get(&key) {
+            return Some(*font);
+        }
+
+        let font = self.synthesize(style, &mut load);
+        self.resolved.insert(key, font);
+
+        Some(font)
+    }
+
+    /// Emulate a style based on the closest available font.
+    fn synthesize<F>(&mut self, style: FontStyle, load: &mut F) -> SyntheticFont
+    where
+        F: FnMut(FontStyle) -> Option<FontKey>,
+    {
+        let font = |base, bold, italic| SyntheticFont { base, bold, italic };
+
+        if let Some(base) = self.load(style, load) {
+            return font(base, false, false);
+        }
+
+        let Synthesis { bold, italic } = self.synthesis;
+        match style {
+            FontStyle::Bold => font(self.regular, bold, false),
+            FontStyle::Italic => font(self.regular, false, italic),
+            FontStyle::BoldItalic => match self.load(FontStyle::Italic, load) {
+                Some(base) => font(base, bold, false),
+                None => match self.load(FontStyle::Bold, load) {
+                    Some(base) => font(base, false, italic),
+                    None => font(self.regular, bold, italic),
+                },
+            },
+        }
+    }
+
+    /// Load the font family's font for a style, unless it has been loaded before.
+    fn load<F>(&mut self, style: FontStyle, load: &mut F) -> Option<FontKey>
+    where
+        F: FnMut(FontStyle) -> Option<FontKey>,
+    {
+        *self.loaded.entry(style).or_insert_with(|| load(style))
+    }
+}
+
+/// RGB glyph bitmap.
+struct Bitmap {
+    width: usize,
+    height: usize,
+    buffer: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Thicken all strokes by drawing the glyph a second time, one pixel to the right.
+    fn embolden(self) -> Self {
+        let width = self.width + 1;
+        let mut buffer = vec![0; width * self.height * 3];
+
+        for (row, pixels) in self.buffer.chunks(self.width * 3).enumerate() {
+            let target = &mut buffer[row * width * 3..(row + 1) * width * 3];
+            for (i, value) in pixels.iter().enumerate() {
+                target[i] = target[i].max(*value);
+                target[i + 3] = target[i + 3].max(*value);
+            }
+        }
+
+        Self { width, height: self.height, buffer }
+    }
+
+    /// Shear the glyph to the right, keeping the baseline in place.
+    ///
+    /// The `top` is the distance from the baseline to the top of the bitmap. Returns the slanted
+    /// bitmap and the horizontal offset of its left edge.
+    fn slant(self, top: i32) -> (Self, i32) {
+        let shift = |row: usize| ((top - row as i32) as f32 * SLANT).round() as i32;
+        let min_shift = shift(self.height - 1).min(shift(0));
+        let max_shift = shift(self.height - 1).max(shift(0));
+
+        let width = self.width + (max_shift - min_shift) as usize;
+        let mut buffer = vec![0; width * self.height * 3];
+
+        for (row, pixels) in self.buffer.chunks(self.width * 3).enumerate() {
+            let start = (row * width + (shift(row) - min_shift) as usize) * 3;
+            buffer[start..start + pixels.len()].copy_from_slice(pixels);
+        }
+
+        (Self { width, height: self.height, buffer }, min_shift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(width: i32, height: i32, top: i32, pixels: &[u8]) -> RasterizedGlyph {
+        let buffer = pixels.iter().flat_map(|p| vec![*p; 3]).collect();
+        RasterizedGlyph {
+            character: 'x',
+            width,
+            height,
+            top,
+            left: 0,
+            buffer: BitmapBuffer::Rgb(buffer),
+        }
+    }
+
+    fn pixels(glyph: &RasterizedGlyph) -> Vec<u8> {
+        match &glyph.buffer {
+            BitmapBuffer::Rgb(buffer) => buffer.iter().step_by(3).copied().collect(),
+            BitmapBuffer::Rgba(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn embolden() {
+        let mut glyph = glyph(2, 2, 2, &[255, 0, 0, 128]);
+        let font = SyntheticFont { base: FontKey::next(), bold: true, italic: false };
+
+        font.transform(&mut glyph);
+
+        assert_eq!((glyph.width, glyph.height), (3, 2));
+        assert_eq!(pixels(&glyph), [255, 255, 0, 0, 128, 128]);
+    }
+
+    #[test]
+    fn slant() {
+        let values: Vec<u8> = (1..=11).collect();
+        let mut glyph = glyph(1, 11, 6, &values);
+        let font = SyntheticFont { base: FontKey::next(), bold: false, italic: true };
+
+        font.transform(&mut glyph);
+
+        // Rows move from one pixel right at the top to one pixel left at the bottom.
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            0, 0, 1,
+            0, 0, 2,
+            0, 0, 3,
+            0, 0, 4,
+            0, 5, 0,
+            0, 6, 0,
+            0, 7, 0,
+            0, 8, 0,
+            0, 9, 0,
+            10, 0, 0,
+            11, 0, 0,
+        ];
+        assert_eq!((glyph.width, glyph.height, glyph.left), (3, 11, -1));
+        assert_eq!(pixels(&glyph), expected);
+    }
+
+    #[test]
+    fn styles_load_on_first_use() {
+        let regular = FontKey::next();
+        let italic = FontKey::next();
+        let mut fonts = StyleFonts::new(regular, Synthesis::default());
+
+        let mut requested = Vec::new();
+        let mut load = |style| {
+            requested.push(style);
+            if style == FontStyle::Italic {
+                Some(italic)
+            } else {
+                None
+            }
+        };
+
+        assert_eq!(fonts.resolve(regular, &mut load), None);
+
+        let key = fonts.key(FontStyle::Italic);
+        let expected = SyntheticFont { base: italic, bold: false, italic: false };
+        assert_eq!(fonts.resolve(key, &mut load), Some(expected));
+        assert_eq!(fonts.resolve(key, &mut load), Some(expected));
+
+        assert_eq!(requested, [FontStyle::Italic]);
+    }
+
+    #[test]
+    fn styles_synthesize_missing_fonts() {
+        let regular = FontKey::next();
+        let bold = FontKey::next();
+        let synthesis = Synthesis { bold: true, italic: false };
+        let mut fonts = StyleFonts::new(regular, synthesis);
+
+        let mut load_count = 0;
+        let mut load = |style| {
+            load_count += 1;
+            if style == FontStyle::Bold {
+                Some(bold)
+            } else {
+                None
+            }
+        };
+
+        let key = fonts.key(FontStyle::BoldItalic);
+        let expected = SyntheticFont { base: bold, bold: false, italic: false };
+        assert_eq!(fonts.resolve(key, &mut load), Some(expected));
+
+        let key = fonts.key(FontStyle::Italic);
+        let expected = SyntheticFont { base: regular, bold: false, italic: false };
+        assert_eq!(fonts.resolve(key, &mut load), Some(expected));
+
+        let key = fonts.key(FontStyle::Bold);
+        let expected = SyntheticFont { base: bold, bold: false, italic: false };
+        assert_eq!(fonts.resolve(key, &mut load), Some(expected));
+
+        // Every style of the font family is only looked up once.
+        assert_eq!(load_count, 3);
+    }
+}
@@ -0,0 +1,395 @@
+//! Replay of asciicast recordings.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::mpsc::TryRecvError;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use std::{fmt, thread as std_thread};
+
+use log::info;
+use mio_extras::channel::Receiver;
+use serde::Deserialize;
+use serde_json as json;
+
+use alacritty_terminal::ansi::{self, Handler};
+use alacritty_terminal::event::Event as TerminalEvent;
+use alacritty_terminal::event_loop::Msg;
+use alacritty_terminal::index::Column;
+use alacritty_terminal::sync::FairMutex;
+use alacritty_terminal::term::Term;
+use alacritty_terminal::thread;
+
+use crate::config::window::Dimensions;
+use crate::event::{Event, EventProxy};
+
+/// Interval for checking user input while waiting for the next frame.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Seconds skipped by a single seek.
+const SEEK_STEP: f64 = 5.;
+
+/// Supported asciicast format version.
+const ASCIICAST_VERSION: u8 = 2;
+
+/// Errors occurring while loading a recording.
+#[derive(Debug)]
+pub enum RecordingError {
+    /// Recording is not in the asciicast v2 format.
+    UnsupportedVersion(Option<u8>),
+
+    /// Recording has no header line.
+    MissingHeader,
+}
+
+impl Error for RecordingError {}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingError::UnsupportedVersion(Some(version)) => {
+                write!(f, "Unsupported asciicast version {}", version)
+            },
+            RecordingError::UnsupportedVersion(None) => write!(f, "Missing asciicast version"),
+            RecordingError::MissingHeader => write!(f, "Missing asciicast header"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: Option<u8>,
+    width: Option<usize>,
+    height: Option<usize>,
+}
+
+/// Event captured at a specific time of the recording.
+#[derive(Debug, PartialEq)]
+struct Frame {
+    time: f64,
+    kind: FrameKind,
+}
+
+#[derive(Debug, PartialEq)]
+enum FrameKind {
+    /// Output written to the terminal.
+    Output(String),
+
+    /// Terminal resized to new grid dimensions.
+    Resize(Dimensions),
+}
+
+/// Asciicast recording.
+pub struct Recording {
+    /// Initial terminal dimensions.
+    dimensions: Option<Dimensions>,
+    frames: Vec<Frame>,
+}
+
+impl Recording {
+    /// Load an asciicast v2 recording from disk.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let recording = Self::from_reader(File::open(path)?)?;
+
+        info!("Loaded recording {:?} with {} frames", path, recording.frames.len());
+
+        Ok(recording)
+    }
+
+    /// Parse an asciicast v2 recording.
+    fn from_reader<R: Read>(reader: R) -> Result<Self, Box<dyn Error>> {
+        let mut lines = BufReader::new(reader).lines();
+
+        let header: Header = match lines.next() {
+            Some(line) => json::from_str(&line?)?,
+            None => return Err(RecordingError::MissingHeader.into()),
+        };
+        if header.version != Some(ASCIICAST_VERSION) {
+            return Err(RecordingError::UnsupportedVersion(header.version).into());
+        }
+
+        let mut frames = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // Input events are ignored for replaying, since input is already echoed.
+            let (time, kind, data): (f64, String, String) = json::from_str(&line)?;
+            let kind = match kind.as_str() {
+                "o" => FrameKind::Output(data),
+                "r" => match parse_dimensions(&data) {
+                    Some(dimensions) => FrameKind::Resize(dimensions),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            frames.push(Frame { time, kind });
+        }
+
+        let dimensions = match (header.width, header.height) {
+            (Some(columns), Some(lines)) => Some(Dimensions { columns: Column(columns), lines }),
+            _ => None,
+        };
+
+        Ok(Self { dimensions, frames })
+    }
+
+    /// Initial terminal dimensions of the recording.
+    pub fn dimensions(&self) -> Option<Dimensions> {
+        self.dimensions
+    }
+
+    /// Total duration of the recording in seconds.
+    fn duration(&self) -> f64 {
+        self.frames.last().map_or(0., |frame| frame.time)
+    }
+}
+
+/// Parse the `{columns}x{lines}` dimensions of a resize event.
+fn parse_dimensions(data: &str) -> Option<Dimensions> {
+    let mut parts = data.splitn(2, 'x');
+    let columns = parts.next()?.parse().ok()?;
+    let lines = parts.next()?.parse().ok()?;
+    Some(Dimensions { columns: Column(columns), lines })
+}
+
+/// Playback position of the recording.
+struct Clock {
+    /// Recording time at the last clock change.
+    offset: f64,
+    /// Wall clock time of the last clock change.
+    start: Instant,
+    speed: f64,
+    paused: bool,
+}
+
+impl Clock {
+    fn new(speed: f64) -> Self {
+        Self { offset: 0., start: Instant::now(), speed, paused: false }
+    }
+
+    /// Current position in the recording in seconds.
+    fn position(&self) -> f64 {
+        if self.paused {
+            self.offset
+        } else {
+            self.offset + self.start.elapsed().as_secs_f64() * self.speed
+        }
+    }
+
+    fn seek(&mut self, position: f64) {
+        self.offset = position.max(0.);
+        self.start = Instant::now();
+    }
+
+    fn toggle_pause(&mut self) {
+        let position = self.position();
+        self.paused = !self.paused;
+        self.seek(position);
+    }
+
+    fn set_speed(&mut self, speed: f64) {
+        let position = self.position();
+        self.speed = speed;
+        self.seek(position);
+    }
+}
+
+/// Replay state driving the terminal parser.
+struct Player {
+    recording: Recording,
+    terminal: Arc<FairMutex<Term<EventProxy>>>,
+    event_proxy: EventProxy,
+    parser: ansi::Processor,
+    clock: Clock,
+    /// Index of the next frame which has not been written to the terminal.
+    next_frame: usize,
+    /// Grid dimensions at the current playback position.
+    dimensions: Option<Dimensions>,
+    /// Grid dimensions last requested for the window.
+    window_dimensions: Option<Dimensions>,
+}
+
+impl Player {
+    /// Write all frames up to the current playback position.
+    fn advance(&mut self) {
+        let position = self.clock.position();
+
+        let mut terminal = self.terminal.lock();
+        let start = self.next_frame;
+        while let Some(frame) = self.recording.frames.get(self.next_frame) {
+            if frame.time > position {
+                break;
+            }
+
+            match &frame.kind {
+                FrameKind::Output(data) => {
+                    for byte in data.as_bytes() {
+                        self.parser.advance(&mut *terminal, *byte);
+                    }
+                },
+                FrameKind::Resize(dimensions) => self.dimensions = Some(*dimensions),
+            }
+            self.next_frame += 1;
+        }
+        drop(terminal);
+
+        self.resize();
+
+        if self.next_frame != start {
+            self.event_proxy.send_event(Event::TerminalEvent(TerminalEvent::Wakeup));
+        }
+    }
+
+    /// Resize the window to the grid dimensions of the current playback position.
+    fn resize(&mut self) {
+        if self.dimensions == self.window_dimensions {
+            return;
+        }
+
+        self.window_dimensions = self.dimensions;
+        if let Some(dimensions) = self.dimensions {
+            self.event_proxy.send_event(Event::ResizeGrid(dimensions));
+        }
+    }
+
+    /// Jump to a different position in the recording.
+    fn seek(&mut self, position: f64) {
+        let position = position.min(self.recording.duration());
+
+        // Replay everything from the start when seeking backwards.
+        if position < self.clock.position() {
+            self.terminal.lock().reset_state();
+            self.parser = ansi::Processor::new();
+            self.next_frame = 0;
+            self.dimensions = self.recording.dimensions;
+        }
+
+        self.clock.seek(position);
+        self.advance();
+    }
+
+    /// Handle keyboard input sent to the terminal.
+    fn input(&mut self, bytes: &[u8]) {
+        match bytes {
+            b" " => self.clock.toggle_pause(),
+            b"\x1b[C" | b"\x1bOC" => self.seek(self.clock.position() + SEEK_STEP),
+            b"\x1b[D" | b"\x1bOD" => self.seek(self.clock.position() - SEEK_STEP),
+            b"+" => self.clock.set_speed(self.clock.speed * 2.),
+            b"-" => self.clock.set_speed(self.clock.speed / 2.),
+            _ => (),
+        }
+    }
+
+    /// Time until the next frame is due.
+    fn next_timeout(&self) -> Duration {
+        let frame = match self.recording.frames.get(self.next_frame) {
+            Some(frame) if !self.clock.paused => frame,
+            _ => return POLL_INTERVAL,
+        };
+
+        let delay = (frame.time - self.clock.position()).max(0.) / self.clock.speed;
+        Duration::from_secs_f64(delay).min(POLL_INTERVAL)
+    }
+}
+
+/// Spawn a thread replaying the recording into the terminal.
+///
+/// Keyboard input is used for controlling the playback:
+///  - Space toggles pause
+///  - Left/Right seek backwards/forwards
+///  - Plus/Minus double/halve the playback speed
+pub fn spawn(
+    recording: Recording,
+    speed: f64,
+    terminal: Arc<FairMutex<Term<EventProxy>>>,
+    event_proxy: EventProxy,
+    rx: Receiver<Msg>,
+) -> JoinHandle<()> {
+    thread::spawn_named("asciicast player", move || {
+        let mut player = Player {
+            clock: Clock::new(speed),
+            parser: ansi::Processor::new(),
+            next_frame: 0,
+            // The window is created with the initial dimensions of the recording.
+            dimensions: recording.dimensions,
+            window_dimensions: recording.dimensions,
+            recording,
+            terminal,
+            event_proxy,
+        };
+
+        loop {
+            loop {
+                match rx.try_recv() {
+                    Ok(Msg::Input(bytes)) => player.input(&bytes),
+                    Ok(Msg::Resize(_)) | Ok(Msg::Pause(_)) => (),
+                    Ok(Msg::Shutdown) | Err(TryRecvError::Disconnected) => return,
+                    Err(TryRecvError::Empty) => break,
+                }
+            }
+
+            player.advance();
+
+            std_thread::sleep(player.next_timeout());
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_recording() {
+        let recording = "{\"version\": 2, \"width\": 80, \"height\": 24}\n\
+                         [0.5, \"o\", \"hello\"]\n\
+                         [1.0, \"i\", \"x\"]\n\
+                         \n\
+                         [1.5, \"r\", \"100x30\"]\n\
+                         [2.0, \"o\", \"world\"]\n";
+        let recording = Recording::from_reader(recording.as_bytes()).unwrap();
+
+        assert_eq!(recording.dimensions(), Some(Dimensions { columns: Column(80), lines: 24 }));
+        assert_eq!(recording.frames, vec![
+            Frame { time: 0.5, kind: FrameKind::Output(String::from("hello")) },
+            Frame {
+                time: 1.5,
+                kind: FrameKind::Resize(Dimensions { columns: Column(100), lines: 30 }),
+            },
+            Frame { time: 2.0, kind: FrameKind::Output(String::from("world")) },
+        ]);
+        assert_eq!(recording.duration(), 2.0);
+    }
+
+    #[test]
+    fn load_recording_without_dimensions() {
+        let recording = "{\"version\": 2}\n[0.5, \"r\", \"invalid\"]\n";
+        let recording = Recording::from_reader(recording.as_bytes()).unwrap();
+
+        assert_eq!(recording.dimensions(), None);
+        assert!(recording.frames.is_empty());
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let error = Recording::from_reader("{\"version\": 1}\n".as_bytes()).err().unwrap();
+        assert_eq!(error.to_string(), "Unsupported asciicast version 1");
+
+        let error = Recording::from_reader("".as_bytes()).err().unwrap();
+        assert_eq!(error.to_string(), "Missing asciicast header");
+    }
+
+    #[test]
+    fn resize_dimensions() {
+        let dimensions = Dimensions { columns: Column(132), lines: 43 };
+        assert_eq!(parse_dimensions("132x43"), Some(dimensions));
+        assert_eq!(parse_dimensions("132"), None);
+        assert_eq!(parse_dimensions("x43"), None);
+    }
+}
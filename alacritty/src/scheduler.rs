@@ -1,4 +1,7 @@
 //! Scheduler for emitting events at a specific time in the future.
+//!
+//! All timers of the event loop are consolidated here, so it can sleep until the closest deadline
+//! and does not wake up at all while no timers are armed.
 
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
@@ -13,15 +16,19 @@ type Event = GlutinEvent<'static, AlacrittyEvent>;
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TimerId {
     SelectionScrolling,
-    DelayedSearch,
     BlinkCursor,
+    BlinkText,
+    Paste,
+    PasteSlowly,
+    ResizeOverlay,
+    UpdateTitle,
 }
 
 /// Event scheduled to be emitted at a specific time.
 pub struct Timer {
-    pub deadline: Instant,
     pub event: Event,
 
+    deadline: Instant,
     interval: Option<Duration>,
     id: TimerId,
 }
@@ -66,23 +73,27 @@ impl Scheduler {
     pub fn schedule(&mut self, event: Event, interval: Duration, repeat: bool, timer_id: TimerId) {
         let deadline = Instant::now() + interval;
 
-        // Get insert position in the schedule.
-        let mut index = self.timers.len();
-        loop {
-            if index == 0 {
-                break;
-            }
-            index -= 1;
-
-            if self.timers[index].deadline < deadline {
-                break;
-            }
-        }
-
         // Set the automatic event repeat rate.
         let interval = if repeat { Some(interval) } else { None };
 
-        self.timers.insert(index, Timer { interval, deadline, event, id: timer_id });
+        self.insert(Timer { interval, deadline, event, id: timer_id });
+    }
+
+    /// Delay a scheduled event, so it is emitted after `interval` from now.
+    ///
+    /// Returns `false` if there is no timer with this ID.
+    pub fn reschedule(&mut self, id: TimerId, interval: Duration) -> bool {
+        let index = match self.timers.iter().position(|timer| timer.id == id) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        if let Some(mut timer) = self.timers.remove(index) {
+            timer.deadline = Instant::now() + interval;
+            self.insert(timer);
+        }
+
+        true
     }
 
     /// Cancel a scheduled event.
@@ -100,4 +111,51 @@ impl Scheduler {
     pub fn get_mut(&mut self, id: TimerId) -> Option<&mut Timer> {
         self.timers.iter_mut().find(|timer| timer.id == id)
     }
+
+    /// Insert a timer, keeping the schedule sorted by deadline.
+    fn insert(&mut self, timer: Timer) {
+        let index = self
+            .timers
+            .iter()
+            .position(|scheduled| scheduled.deadline > timer.deadline)
+            .unwrap_or_else(|| self.timers.len());
+
+        self.timers.insert(index, timer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_sorted_by_deadline() {
+        let mut scheduler = Scheduler::new();
+        let event = GlutinEvent::UserEvent(AlacrittyEvent::BlinkCursor);
+
+        scheduler.schedule(event.clone(), Duration::from_secs(3), false, TimerId::BlinkCursor);
+        scheduler.schedule(event.clone(), Duration::from_secs(1), false, TimerId::BlinkText);
+        scheduler.schedule(event.clone(), Duration::from_secs(2), false, TimerId::PasteSlowly);
+        assert!(scheduler.reschedule(TimerId::BlinkText, Duration::from_secs(4)));
+        assert!(!scheduler.reschedule(TimerId::UpdateTitle, Duration::from_secs(1)));
+
+        let ids: Vec<_> = scheduler.timers.iter().map(|timer| timer.id).collect();
+        assert_eq!(ids, [TimerId::PasteSlowly, TimerId::BlinkCursor, TimerId::BlinkText]);
+
+        // The event loop sleeps until the closest deadline.
+        let deadline = scheduler.update(&mut Vec::new());
+        assert_eq!(deadline, Some(scheduler.timers[0].deadline));
+    }
+
+    #[test]
+    fn idle_without_timers() {
+        let mut scheduler = Scheduler::new();
+        let event = GlutinEvent::UserEvent(AlacrittyEvent::BlinkCursor);
+
+        scheduler.schedule(event, Duration::from_secs(0), false, TimerId::UpdateTitle);
+
+        let mut events = Vec::new();
+        assert_eq!(scheduler.update(&mut events), None);
+        assert_eq!(events.len(), 1);
+    }
 }
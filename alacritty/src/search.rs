@@ -0,0 +1,464 @@
+//! Buffer search on a separate thread.
+//!
+//! Searches which would block the event loop for too long are moved to the search thread, which
+//! only holds the terminal lock for a limited number of lines at a time. Every new search cancels
+//! all previous ones.
+
+use std::cmp::{max, min};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+
+use glutin::event_loop::EventLoopProxy;
+
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Direction, Line, Point, Side};
+use alacritty_terminal::sync::FairMutex;
+use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::thread;
+
+use crate::event::Event;
+
+/// Number of lines searched while holding the terminal lock.
+const CHUNK_LINES: usize = 1000;
+
+/// Position of the focused match among all search matches in the buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MatchCount {
+    /// Index of the focused match, if it is within the counted matches.
+    pub index: Option<usize>,
+
+    /// Number of counted matches.
+    pub total: usize,
+
+    /// Whether counting stopped at the configured limit.
+    pub limited: bool,
+
+    /// Whether the entire buffer has been searched.
+    pub complete: bool,
+}
+
+/// Update sent by the search thread.
+#[derive(Debug, Clone)]
+pub enum SearchUpdate {
+    /// First match from the search origin, `None` if the buffer has no matches.
+    Match(Option<Match>),
+
+    /// Matches counted so far.
+    Count(MatchCount),
+}
+
+/// Result of a search on the search thread.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// Search which produced this result.
+    pub generation: usize,
+
+    /// History position of the grid when this result was found.
+    ///
+    /// Lines of the results need to be moved up by the number of lines which have been added to
+    /// the history since then.
+    pub history_position: usize,
+
+    /// Match or count found by the search.
+    pub update: SearchUpdate,
+}
+
+/// Request for the search thread.
+enum SearchJob {
+    /// Find the first match from the origin.
+    Next { origin: Point, direction: Direction, side: Side },
+
+    /// Count all matches and locate the focused match between them.
+    Count { focused_match: Match, limit: usize },
+}
+
+/// Search job with the state shared by all job types.
+struct Job {
+    job: SearchJob,
+    dfas: Arc<RegexSearch>,
+    generation: usize,
+    history_position: usize,
+}
+
+/// Handle for submitting searches to the search thread.
+pub struct SearchWorker {
+    sender: Sender<Job>,
+    generation: Arc<AtomicUsize>,
+}
+
+impl SearchWorker {
+    pub fn new<T: Send + 'static>(
+        terminal: Arc<FairMutex<Term<T>>>,
+        proxy: EventLoopProxy<Event>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let generation = Arc::new(AtomicUsize::new(0));
+
+        let notify = Box::new(move |result| {
+            let _ = proxy.send_event(Event::SearchResult(result));
+        });
+
+        let worker = Worker { terminal, notify, generation: generation.clone() };
+        thread::spawn_named("search", move || {
+            for job in receiver {
+                worker.run(job);
+            }
+        });
+
+        Self { sender, generation }
+    }
+
+    /// Current search generation.
+    ///
+    /// Results from older generations are outdated and should be ignored.
+    pub fn generation(&self) -> usize {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// Cancel all pending searches.
+    pub fn cancel(&self) -> usize {
+        self.generation.fetch_add(1, Ordering::Relaxed).wrapping_add(1)
+    }
+
+    /// Find the first match from the origin in the specified direction.
+    ///
+    /// Like [`Term::search_next`], this wraps around at the buffer boundaries and the `side` of
+    /// the match is compared to the origin.
+    pub fn find_next(
+        &self,
+        dfas: Arc<RegexSearch>,
+        origin: Point,
+        direction: Direction,
+        side: Side,
+        history_position: usize,
+    ) {
+        self.submit(dfas, SearchJob::Next { origin, direction, side }, history_position);
+    }
+
+    /// Count all matches in the buffer, up to the specified limit.
+    pub fn count(
+        &self,
+        dfas: Arc<RegexSearch>,
+        focused_match: Match,
+        limit: usize,
+        history_position: usize,
+    ) {
+        self.submit(dfas, SearchJob::Count { focused_match, limit }, history_position);
+    }
+
+    fn submit(&self, dfas: Arc<RegexSearch>, job: SearchJob, history_position: usize) {
+        let generation = self.cancel();
+        let _ = self.sender.send(Job { job, dfas, generation, history_position });
+    }
+}
+
+/// State of the search thread.
+struct Worker<T> {
+    terminal: Arc<FairMutex<Term<T>>>,
+    notify: Box<dyn Fn(SearchResult) + Send>,
+    generation: Arc<AtomicUsize>,
+}
+
+impl<T> Worker<T> {
+    fn run(&self, job: Job) {
+        let Job { job, dfas, generation, history_position } = job;
+        let mut search = Search { worker: self, dfas: &dfas, generation, history_position };
+
+        match job {
+            SearchJob::Next { origin, direction, side } => {
+                search.find_next(origin, direction, side)
+            },
+            SearchJob::Count { focused_match, limit } => search.count(focused_match, limit),
+        }
+    }
+}
+
+/// Search in progress on the search thread.
+struct Search<'a, T> {
+    worker: &'a Worker<T>,
+    dfas: &'a RegexSearch,
+    generation: usize,
+    history_position: usize,
+}
+
+impl<'a, T> Search<'a, T> {
+    /// Check if a newer search was submitted.
+    fn cancelled(&self) -> bool {
+        self.worker.generation.load(Ordering::Relaxed) != self.generation
+    }
+
+    /// Number of lines all history lines moved up since the last chunk.
+    fn history_shift(&mut self, terminal: &Term<T>) -> i32 {
+        let shift = terminal.grid().history_shift(self.history_position);
+        self.history_position = terminal.grid().history_position();
+        shift
+    }
+
+    fn send(&self, update: SearchUpdate) {
+        let generation = self.generation;
+        let result = SearchResult { generation, history_position: self.history_position, update };
+        (self.worker.notify)(result);
+    }
+
+    fn find_next(&mut self, mut origin: Point, direction: Direction, side: Side) {
+        let mut chunk_start: Option<Point> = None;
+        let mut searched_lines = 0;
+
+        loop {
+            if self.cancelled() {
+                return;
+            }
+
+            let terminal = self.worker.terminal.lock();
+            let topmost_line = terminal.topmost_line();
+            let bottommost_line = terminal.bottommost_line();
+
+            // Move positions along with lines added to the history.
+            let shift = self.history_shift(&terminal);
+            let clamp = |line: Line| max(min(line, bottommost_line), topmost_line);
+            origin.line = clamp(origin.line - shift);
+            let start = match chunk_start {
+                Some(start) => Point::new(clamp(start.line - shift), start.column),
+                None => match direction {
+                    Direction::Right => terminal.logical_line(origin.line).start_point(),
+                    Direction::Left => terminal.logical_line(origin.line).end_point(&*terminal),
+                },
+            };
+
+            // Search whole logical lines, since matches can't span across them.
+            let end = match direction {
+                Direction::Right => {
+                    let line = min(start.line + CHUNK_LINES, bottommost_line);
+                    terminal.logical_line(line).end_point(&*terminal)
+                },
+                Direction::Left => {
+                    let line = max(start.line - CHUNK_LINES, topmost_line);
+                    terminal.logical_line(line).start_point()
+                },
+            };
+
+            // Ignore matches before the origin, unless the search wrapped around.
+            let first_chunk = chunk_start.is_none();
+            let regex_match =
+                RegexIter::new(start, end, direction, &terminal, self.dfas).find(|regex_match| {
+                    let point = match side {
+                        Side::Left => *regex_match.start(),
+                        Side::Right => *regex_match.end(),
+                    };
+
+                    !first_chunk
+                        || match direction {
+                            Direction::Right => point >= origin,
+                            Direction::Left => point <= origin,
+                        }
+                });
+
+            if let Some(regex_match) = regex_match {
+                self.send(SearchUpdate::Match(Some(regex_match)));
+                return;
+            }
+
+            // Stop once the entire buffer has been searched.
+            searched_lines += (end.line - start.line).0.abs() as usize + 1;
+            if searched_lines > terminal.total_lines() {
+                self.send(SearchUpdate::Match(None));
+                return;
+            }
+
+            // Continue after the last logical line, wrapping around at the buffer boundaries.
+            chunk_start = Some(match direction {
+                Direction::Right if end.line >= bottommost_line => {
+                    Point::new(topmost_line, Column(0))
+                },
+                Direction::Right => Point::new(end.line + 1, Column(0)),
+                Direction::Left if end.line <= topmost_line => {
+                    Point::new(bottommost_line, terminal.last_column())
+                },
+                Direction::Left => Point::new(end.line - 1, terminal.last_column()),
+            });
+        }
+    }
+
+    fn count(&mut self, mut focused_match: Match, limit: usize) {
+        let mut match_count = MatchCount { index: None, total: 0, limited: false, complete: false };
+        let mut chunk_start = None;
+
+        loop {
+            if self.cancelled() {
+                return;
+            }
+
+            let terminal = self.worker.terminal.lock();
+            let topmost_line = terminal.topmost_line();
+            let bottommost_line = terminal.bottommost_line();
+
+            // Move positions along with lines added to the history.
+            let shift = self.history_shift(&terminal);
+            let mut focused_start = *focused_match.start();
+            let mut focused_end = *focused_match.end();
+            focused_start.line -= shift;
+            focused_end.line -= shift;
+            focused_match = focused_start..=focused_end;
+
+            let mut start = chunk_start.unwrap_or_else(|| Point::new(topmost_line, Column(0)));
+            start.line = max(min(start.line - shift, bottommost_line), topmost_line);
+
+            let line = min(start.line + CHUNK_LINES, bottommost_line);
+            let end = terminal.logical_line(line).end_point(&*terminal);
+
+            for regex_match in RegexIter::new(start, end, Direction::Right, &terminal, self.dfas) {
+                if match_count.total == limit {
+                    match_count.limited = true;
+                    break;
+                }
+
+                if regex_match.start() == focused_match.start() {
+                    match_count.index = Some(match_count.total);
+                }
+
+                match_count.total += 1;
+            }
+
+            match_count.complete = match_count.limited || end.line >= bottommost_line;
+
+            // Release the terminal between chunks, so it can be updated while counting.
+            drop(terminal);
+            self.send(SearchUpdate::Count(match_count));
+
+            if match_count.complete {
+                return;
+            }
+
+            chunk_start = Some(Point::new(end.line + 1, Column(0)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use alacritty_terminal::ansi::Handler;
+    use alacritty_terminal::config::MockConfig;
+    use alacritty_terminal::event::EventListener;
+    use alacritty_terminal::index::Line;
+    use alacritty_terminal::term::cell::Flags;
+    use alacritty_terminal::term::SizeInfo;
+
+    struct MockEventProxy;
+    impl EventListener for MockEventProxy {}
+
+    type Terminal = Arc<FairMutex<Term<MockEventProxy>>>;
+
+    fn terminal(lines: usize) -> Terminal {
+        let size = SizeInfo::new(2., lines as f32, 1., 1., 0., 0., false);
+        Arc::new(FairMutex::new(Term::new(&MockConfig::default(), size, MockEventProxy)))
+    }
+
+    fn write(terminal: &Terminal, line: Line, column: Column, text: &str) {
+        let mut terminal = terminal.lock();
+        terminal.goto(line, column);
+        for c in text.chars() {
+            terminal.input(c);
+        }
+    }
+
+    /// Run a search job to completion, collecting all its results.
+    fn run<F>(terminal: &Terminal, regex: &str, job: SearchJob, on_result: F) -> Vec<SearchResult>
+    where
+        F: Fn(&SearchResult) + Send + 'static,
+    {
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let notify_results = results.clone();
+        let notify = Box::new(move |result: SearchResult| {
+            on_result(&result);
+            notify_results.lock().unwrap().push(result);
+        });
+
+        let generation = Arc::new(AtomicUsize::new(0));
+        let worker = Worker { terminal: terminal.clone(), notify, generation };
+
+        let dfas = Arc::new(RegexSearch::new(regex).unwrap());
+        let history_position = terminal.lock().grid().history_position();
+        worker.run(Job { job, dfas, generation: 0, history_position });
+
+        let results = results.lock().unwrap();
+        results.clone()
+    }
+
+    /// Match of a finished search for the next match.
+    fn found(results: Vec<SearchResult>) -> Option<Match> {
+        match &results[..] {
+            [SearchResult { update: SearchUpdate::Match(regex_match), .. }] => regex_match.clone(),
+            _ => panic!("unexpected search results: {:?}", results),
+        }
+    }
+
+    #[test]
+    fn find_next_across_chunks() {
+        let terminal = terminal(2500);
+        write(&terminal, Line(1500), Column(0), "x");
+
+        // Wrapped line across the boundary of the first chunk.
+        write(&terminal, Line(1000), Column(1), "ab");
+        assert!(terminal.lock().grid()[Line(1000)][Column(1)].flags.contains(Flags::WRAPLINE));
+
+        let origin = Point::new(Line(0), Column(0));
+        let job = SearchJob::Next { origin, direction: Direction::Right, side: Side::Left };
+        let start = Point::new(Line(1000), Column(1));
+        let end = Point::new(Line(1001), Column(0));
+        assert_eq!(found(run(&terminal, "ab", job, |_| ())), Some(start..=end));
+
+        // Wrap around to the bottom of the buffer, after searching the first chunk.
+        let origin = Point::new(Line(10), Column(0));
+        let job = SearchJob::Next { origin, direction: Direction::Left, side: Side::Left };
+        let point = Point::new(Line(1500), Column(0));
+        assert_eq!(found(run(&terminal, "x", job, |_| ())), Some(point..=point));
+
+        let job = SearchJob::Next { origin, direction: Direction::Left, side: Side::Left };
+        assert_eq!(found(run(&terminal, "y", job, |_| ())), None);
+    }
+
+    #[test]
+    fn count_with_history_shift() {
+        let terminal = terminal(2500);
+        write(&terminal, Line(0), Column(0), "x");
+        write(&terminal, Line(1500), Column(0), "x");
+        write(&terminal, Line(2400), Column(0), "x");
+
+        // Add lines to the history after the first chunk was counted.
+        let shifted = Arc::new(Mutex::new(false));
+        let notify_terminal = terminal.clone();
+        let on_result = move |_: &SearchResult| {
+            let mut shifted = shifted.lock().unwrap();
+            if !*shifted {
+                notify_terminal.lock().scroll_up(10);
+                *shifted = true;
+            }
+        };
+
+        let focused = Point::new(Line(1500), Column(0));
+        let job = SearchJob::Count { focused_match: focused..=focused, limit: 100 };
+        let results = run(&terminal, "x", job, on_result);
+
+        let counts: Vec<_> = results
+            .iter()
+            .filter_map(|result| match result.update {
+                SearchUpdate::Count(count) => Some(count),
+                SearchUpdate::Match(_) => None,
+            })
+            .collect();
+        assert_eq!(counts, vec![
+            MatchCount { index: None, total: 1, limited: false, complete: false },
+            MatchCount { index: Some(1), total: 2, limited: false, complete: false },
+            MatchCount { index: Some(1), total: 3, limited: false, complete: true },
+        ]);
+
+        let history_position = terminal.lock().grid().history_position();
+        assert_eq!(results.last().unwrap().history_position, history_position);
+    }
+}
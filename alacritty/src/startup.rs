@@ -0,0 +1,37 @@
+//! Timing of the startup stages, reported with `--measure-startup`.
+
+use std::time::{Duration, Instant};
+
+/// Measures the time spent in each stage of the startup.
+pub struct StartupTimer {
+    start: Instant,
+    last: Instant,
+    enabled: bool,
+}
+
+impl StartupTimer {
+    pub fn new(enabled: bool) -> Self {
+        let now = Instant::now();
+        Self { start: now, last: now, enabled }
+    }
+
+    /// Report the time since the previous stage was completed.
+    pub fn stage(&mut self, name: &str) {
+        let now = Instant::now();
+        if self.enabled {
+            println!("{:<16}{:>10}", name, format_duration(now - self.last));
+        }
+        self.last = now;
+    }
+
+    /// Report the total startup time.
+    pub fn finish(&self) {
+        if self.enabled {
+            println!("{:<16}{:>10}", "total", format_duration(self.last - self.start));
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.)
+}
@@ -0,0 +1,222 @@
+//! Capability lookup in Alacritty's terminfo, used to answer XTGETTCAP requests.
+
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+/// Terminfo source of all Alacritty entries.
+const TERMINFO: &str = include_str!("../../extra/alacritty.info");
+
+/// Terminfo entry used for answering capability requests.
+const TERMINAL_NAME: &str = "alacritty";
+
+/// Maximum depth of nested `use` capabilities.
+const MAX_USE_DEPTH: usize = 8;
+
+lazy_static! {
+    /// Capability fields of all entries, parsed on the first request.
+    static ref ENTRIES: HashMap<String, Vec<String>> = entries(TERMINFO);
+}
+
+/// Get the value of a capability in Alacritty's terminfo.
+///
+/// Boolean capabilities have an empty value. Like xterm, the `TN` and `Co` termcap names are
+/// supported for the terminal name and the number of colors.
+pub fn capability(name: &str) -> Option<String> {
+    let name = match name {
+        "TN" | "name" => return Some(TERMINAL_NAME.into()),
+        "Co" => "colors",
+        name => name,
+    };
+
+    lookup(&ENTRIES, TERMINAL_NAME, name, 0)
+}
+
+/// Find a capability in an entry or the entries it uses.
+fn lookup(
+    entries: &HashMap<String, Vec<String>>,
+    entry: &str,
+    name: &str,
+    depth: usize,
+) -> Option<String> {
+    let fields = entries.get(entry)?;
+
+    // Capabilities of the entry itself take precedence over the ones it uses.
+    for field in fields {
+        let value = match field.strip_prefix(name) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        if value.is_empty() {
+            return Some(String::new());
+        } else if value == "@" {
+            return None;
+        } else if let Some(number) = value.strip_prefix('#') {
+            return parse_number(number).map(|number| number.to_string());
+        } else if let Some(string) = value.strip_prefix('=') {
+            return Some(unescape(string));
+        }
+    }
+
+    if depth >= MAX_USE_DEPTH {
+        return None;
+    }
+
+    fields
+        .iter()
+        .filter_map(|field| field.strip_prefix("use="))
+        .find_map(|used| lookup(entries, used, name, depth + 1))
+}
+
+/// Parse the capability fields of all entries, indexed by every name of the entry.
+fn entries(source: &str) -> HashMap<String, Vec<String>> {
+    let mut entries = HashMap::new();
+
+    let mut lines = source.lines().filter(|line| !line.starts_with('#')).peekable();
+    while let Some(header) = lines.next() {
+        if header.trim().is_empty() {
+            continue;
+        }
+
+        // Indented lines continue the entry, their leading whitespace is ignored.
+        let mut text = header.trim().to_owned();
+        while let Some(line) = lines.peek().filter(|line| line.starts_with(char::is_whitespace)) {
+            text.push_str(line.trim());
+            lines.next();
+        }
+
+        let mut fields = split_fields(&text).into_iter();
+
+        // The last name is a description of the entry.
+        let names = match fields.next() {
+            Some(names) => names,
+            None => continue,
+        };
+        let mut names: Vec<_> = names.split('|').collect();
+        if names.len() > 1 {
+            names.pop();
+        }
+
+        let fields: Vec<_> = fields.filter(|field| !field.is_empty()).collect();
+        for name in names {
+            entries.insert(name.to_owned(), fields.clone());
+        }
+    }
+
+    entries
+}
+
+/// Split an entry at all unescaped commas.
+fn split_fields(text: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                field.push(c);
+                field.extend(chars.next());
+            },
+            ',' => {
+                fields.push(field.trim().to_owned());
+                field.clear();
+            },
+            c => field.push(c),
+        }
+    }
+
+    if !field.trim().is_empty() {
+        fields.push(field.trim().to_owned());
+    }
+
+    fields
+}
+
+/// Parse a numeric capability, which is either decimal, octal or hexadecimal.
+fn parse_number(number: &str) -> Option<u32> {
+    if let Some(hex) = number.strip_prefix("0x").or_else(|| number.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if number.len() > 1 && number.starts_with('0') {
+        u32::from_str_radix(&number[1..], 8).ok()
+    } else {
+        number.parse().ok()
+    }
+}
+
+/// Replace all escapes in a string capability with the characters they represent.
+fn unescape(string: &str) -> String {
+    let mut unescaped = String::with_capacity(string.len());
+
+    let mut chars = string.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('E') | Some('e') => unescaped.push('\x1b'),
+                Some('n') | Some('l') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                Some('t') => unescaped.push('\t'),
+                Some('b') => unescaped.push('\x08'),
+                Some('f') => unescaped.push('\x0c'),
+                Some('s') => unescaped.push(' '),
+                Some(digit @ '0'..='7') => {
+                    // Octal escapes have up to three digits.
+                    let mut value = digit.to_digit(8).unwrap_or(0);
+                    for _ in 0..2 {
+                        match chars.peek().and_then(|c| c.to_digit(8)) {
+                            Some(digit) => value = value * 8 + digit,
+                            None => break,
+                        }
+                        chars.next();
+                    }
+
+                    // Terminfo uses `\200` for NUL, since it can't be stored in C strings.
+                    let value = if value == 0o200 { 0 } else { value };
+                    unescaped.extend(std::char::from_u32(value));
+                },
+                Some(c) => unescaped.push(c),
+                None => unescaped.push('\\'),
+            },
+            '^' => match chars.next() {
+                Some('?') => unescaped.push('\x7f'),
+                Some(c) => unescaped.push(((c as u8) & 0x1f) as char),
+                None => unescaped.push('^'),
+            },
+            c => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_capabilities() {
+        assert_eq!(capability("TN"), Some("alacritty".into()));
+        assert_eq!(capability("bel"), Some("\x07".into()));
+        assert_eq!(capability("am"), Some(String::new()));
+        assert_eq!(capability("colors"), Some("256".into()));
+        assert_eq!(capability("Co"), Some("256".into()));
+        assert_eq!(capability("setb"), None);
+        assert_eq!(capability("invalid"), None);
+    }
+
+    #[test]
+    fn multiline_capability() {
+        let initc = capability("initc").unwrap();
+        assert!(initc.starts_with("\x1b]4;%p1%d;rgb:"));
+        assert!(initc.contains("%*%{1000}%/"));
+        assert!(initc.ends_with("\x1b\\"));
+    }
+
+    #[test]
+    fn unescape_strings() {
+        assert_eq!(unescape(r"\E[%i%p1%d;%p2%dH"), "\x1b[%i%p1%d;%p2%dH");
+        assert_eq!(unescape(r"^G^?\,\:\\"), "\x07\x7f,:\\");
+        assert_eq!(unescape(r"\0\177\s"), "\x00\x7f ");
+    }
+}
@@ -0,0 +1,206 @@
+//! Vi mode marks and jump list.
+
+use std::cmp::min;
+use std::collections::HashMap;
+
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::Point;
+use alacritty_terminal::term::{Term, TermMode};
+
+/// Maximum number of positions in the jump list.
+const MAX_JUMPS: usize = 100;
+
+/// Buffer position which moves along with its line when lines are added to the history.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ViMark {
+    point: Point,
+    history_position: usize,
+
+    /// Mark belongs to the alternate screen.
+    alt_screen: bool,
+}
+
+impl ViMark {
+    pub fn new<T>(term: &Term<T>, point: Point) -> Self {
+        Self {
+            point,
+            history_position: term.grid().history_position(),
+            alt_screen: term.mode().contains(TermMode::ALT_SCREEN),
+        }
+    }
+
+    /// Current position of the mark.
+    ///
+    /// Returns `None` if the marked line has been removed from the history, or if the mark
+    /// belongs to the screen which is currently inactive.
+    pub fn point<T>(&self, term: &Term<T>) -> Option<Point> {
+        if term.mode().contains(TermMode::ALT_SCREEN) != self.alt_screen {
+            return None;
+        }
+
+        let line = self.point.line - term.grid().history_shift(self.history_position);
+        if line < term.topmost_line() || line > term.bottommost_line() {
+            return None;
+        }
+
+        Some(Point::new(line, min(self.point.column, term.last_column())))
+    }
+}
+
+/// Named marks and jump history of the vi mode cursor.
+#[derive(Default, Debug)]
+pub struct ViMarks {
+    /// Marks set by the user, named `a` to `z`.
+    marks: HashMap<char, ViMark>,
+
+    /// Positions the vi mode cursor jumped away from, oldest first.
+    jumps: Vec<ViMark>,
+
+    /// Position in the jump list, equal to its length unless moving through the list.
+    index: usize,
+}
+
+impl ViMarks {
+    /// Set the mark with the specified name.
+    ///
+    /// Only the lowercase letters `a` to `z` can be used as names.
+    pub fn set(&mut self, name: char, mark: ViMark) {
+        if name.is_ascii_lowercase() {
+            self.marks.insert(name, mark);
+        }
+    }
+
+    /// Get the mark with the specified name.
+    ///
+    /// The names `'` and `` ` `` refer to the position before the latest jump.
+    pub fn get(&self, name: char) -> Option<ViMark> {
+        match name {
+            '\'' | '`' => self.jumps.last().copied(),
+            _ => self.marks.get(&name).copied(),
+        }
+    }
+
+    /// Remember the position the vi mode cursor is jumping away from.
+    pub fn push_jump<T>(&mut self, term: &Term<T>, point: Point) {
+        self.jumps.truncate(self.index);
+
+        // Keep only one position per line, dropping all lines which left the history.
+        let mark = ViMark::new(term, point);
+        self.jumps.retain(|jump| {
+            jump.alt_screen != mark.alt_screen
+                || jump.point(term).map_or(false, |jump| jump.line != point.line)
+        });
+
+        self.jumps.push(mark);
+        if self.jumps.len() > MAX_JUMPS {
+            self.jumps.remove(0);
+        }
+
+        self.index = self.jumps.len();
+    }
+
+    /// Move to the previous position in the jump list.
+    ///
+    /// Positions on the inactive screen are skipped.
+    pub fn jump_back<T>(&mut self, term: &Term<T>, point: Point) -> Option<Point> {
+        // Remember the current position, so it can be reached again with `jump_forward`.
+        if self.index == self.jumps.len() {
+            self.push_jump(term, point);
+            self.index -= 1;
+        }
+
+        while self.index > 0 {
+            self.index -= 1;
+            if let Some(point) = self.jumps[self.index].point(term) {
+                return Some(point);
+            }
+        }
+
+        None
+    }
+
+    /// Move to the next position in the jump list.
+    ///
+    /// Positions on the inactive screen are skipped.
+    pub fn jump_forward<T>(&mut self, term: &Term<T>) -> Option<Point> {
+        while self.index + 1 < self.jumps.len() {
+            self.index += 1;
+            if let Some(point) = self.jumps[self.index].point(term) {
+                return Some(point);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::ansi::{Handler, Mode};
+    use alacritty_terminal::index::{Column, Line};
+    use alacritty_terminal::term::test::mock_term;
+
+    #[test]
+    fn marks_follow_history() {
+        let mut term = mock_term("a\nb\nc");
+
+        let mut marks = ViMarks::default();
+        marks.set('a', ViMark::new(&term, Point::new(Line(1), Column(0))));
+        marks.set('A', ViMark::new(&term, Point::new(Line(1), Column(0))));
+        assert_eq!(marks.get('A'), None);
+
+        term.scroll_up(1);
+
+        let mark = marks.get('a').unwrap();
+        assert_eq!(mark.point(&term), Some(Point::new(Line(0), Column(0))));
+    }
+
+    #[test]
+    fn jump_list() {
+        let term = mock_term("a\nb\nc\nd");
+        let point = |line| Point::new(Line(line), Column(0));
+
+        let mut marks = ViMarks::default();
+        assert_eq!(marks.jump_back(&term, point(0)), None);
+
+        marks.push_jump(&term, point(0));
+        marks.push_jump(&term, point(1));
+        assert_eq!(marks.get('\''), Some(ViMark::new(&term, point(1))));
+
+        assert_eq!(marks.jump_back(&term, point(2)), Some(point(1)));
+        assert_eq!(marks.jump_back(&term, point(1)), Some(point(0)));
+        assert_eq!(marks.jump_back(&term, point(0)), None);
+        assert_eq!(marks.jump_forward(&term), Some(point(1)));
+        assert_eq!(marks.jump_forward(&term), Some(point(2)));
+        assert_eq!(marks.jump_forward(&term), None);
+
+        // Jumping from a line again replaces its previous position.
+        marks.push_jump(&term, point(0));
+        assert_eq!(marks.jump_back(&term, point(3)), Some(point(0)));
+        assert_eq!(marks.jump_back(&term, point(0)), Some(point(1)));
+    }
+
+    #[test]
+    fn marks_per_screen() {
+        let mut term = mock_term("a\nb\nc");
+        let point = |line| Point::new(Line(line), Column(0));
+
+        let mut marks = ViMarks::default();
+        marks.set('a', ViMark::new(&term, point(0)));
+        marks.push_jump(&term, point(0));
+
+        term.set_mode(Mode::SwapScreenAndSetRestoreCursor);
+        assert_eq!(marks.get('a').unwrap().point(&term), None);
+
+        // Jumps on the alternate screen keep the ones on the primary screen.
+        marks.push_jump(&term, point(0));
+        term.unset_mode(Mode::SwapScreenAndSetRestoreCursor);
+        assert_eq!(marks.get('a').unwrap().point(&term), Some(point(0)));
+
+        // Jumps on the inactive screen are skipped.
+        assert_eq!(marks.jump_back(&term, point(2)), Some(point(0)));
+        assert_eq!(marks.jump_forward(&term), Some(point(2)));
+    }
+}
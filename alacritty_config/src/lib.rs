@@ -0,0 +1,329 @@
+//! Configuration abstractions shared by Alacritty's config structs and their derives.
+
+#![warn(rust_2018_idioms, future_incompatible)]
+#![deny(clippy::all, clippy::if_not_else, clippy::enum_glob_use, clippy::wrong_pub_self_convention)]
+#![cfg_attr(feature = "cargo-clippy", deny(warnings))]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Write};
+use std::path::PathBuf;
+
+use log::LevelFilter;
+
+/// Key of the items in a sequence.
+const SEQUENCE_ITEM: &str = "-";
+
+thread_local! {
+    /// Keys of the sections which are currently being deserialized.
+    static SECTION_PATH: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+/// Deserialize the option `key`, making it part of the section path while doing so.
+///
+/// This is used by `#[derive(ConfigDeserialize)]`, so problems reported for nested options can be
+/// traced back to their section.
+pub fn with_section<T, F: FnOnce() -> T>(key: &str, deserialize: F) -> T {
+    /// Guard leaving the section again, even if deserialization panics.
+    struct Section;
+
+    impl Drop for Section {
+        fn drop(&mut self) {
+            SECTION_PATH.with(|path| path.borrow_mut().pop());
+        }
+    }
+
+    SECTION_PATH.with(|path| path.borrow_mut().push(key.to_owned()));
+    let _section = Section;
+
+    deserialize()
+}
+
+/// Keys of the sections which are currently being deserialized, starting at the root.
+pub fn section_path() -> Vec<String> {
+    SECTION_PATH.with(|path| path.borrow().clone())
+}
+
+/// Field most similar to an unknown key.
+///
+/// Returns `None` if no field is close enough to be a likely typo.
+pub fn similar_field<'a>(key: &str, fields: &[&'a str]) -> Option<&'a str> {
+    let max_distance = std::cmp::max(1, key.len() / 3);
+    fields
+        .iter()
+        .map(|field| (edit_distance(key, field), *field))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, field)| field)
+}
+
+/// Number of single-character edits necessary to turn one string into another.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let above = row[j + 1];
+            let edit = 1 + diagonal.min(above).min(row[j]);
+            row[j + 1] = if a == *b { diagonal } else { edit };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Configuration option which can be written to the configuration file template.
+///
+/// This is implemented by `#[derive(ConfigDeserialize)]`, using the doc comments of every field as
+/// the documentation of its option.
+pub trait ConfigTemplate {
+    /// Write this value as the option `key`.
+    fn write_template(&self, key: &str, template: &mut Template);
+
+    /// Write all options of a section, without the key of the section itself.
+    fn write_fields(&self, _template: &mut Template) {}
+}
+
+/// Configuration file with all options commented out.
+#[derive(Debug, Default)]
+pub struct Template {
+    text: String,
+    depth: usize,
+    with_comments: bool,
+    section_start: bool,
+}
+
+impl Template {
+    pub fn new(with_comments: bool) -> Self {
+        Self { with_comments, section_start: true, ..Self::default() }
+    }
+
+    /// Write an option, preceded by its documentation.
+    pub fn option<T>(&mut self, key: &str, docs: &[&str], value: &T)
+    where
+        T: ConfigTemplate + ?Sized,
+    {
+        if !docs.is_empty() {
+            self.comment(docs);
+        }
+
+        value.write_template(key, self);
+    }
+
+    /// Write documentation, unless comments are disabled.
+    pub fn comment(&mut self, lines: &[&str]) {
+        if !self.with_comments {
+            return;
+        }
+
+        // Separate documentation from the previous option with a blank line.
+        if !self.section_start {
+            self.text.push('\n');
+        }
+
+        for line in lines {
+            match line.trim_end() {
+                "" => self.line("#"),
+                line => self.line(format_args!("# {}", line)),
+            }
+        }
+    }
+
+    /// Write an option with a single value.
+    pub fn value<T: Display>(&mut self, key: &str, value: T) {
+        if key == SEQUENCE_ITEM {
+            self.line(format_args!("#- {}", value));
+        } else {
+            self.line(format_args!("#{}: {}", key, value));
+        }
+    }
+
+    /// Write a section, indenting all its options below the key.
+    pub fn section<F: FnOnce(&mut Self)>(&mut self, key: &str, options: F) {
+        if key == SEQUENCE_ITEM {
+            self.line("#-");
+        } else {
+            self.line(format_args!("#{}:", key));
+        }
+
+        self.depth += 1;
+        self.section_start = true;
+
+        options(self);
+
+        self.depth -= 1;
+        self.section_start = false;
+    }
+
+    /// Text of the configuration file.
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    fn line<T: Display>(&mut self, line: T) {
+        let _ = writeln!(self.text, "{:indent$}{}", "", line, indent = self.depth * 2);
+        self.section_start = false;
+    }
+}
+
+impl<T: ConfigTemplate> ConfigTemplate for Option<T> {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        match self {
+            Some(value) => value.write_template(key, template),
+            None => template.value(key, "None"),
+        }
+    }
+}
+
+impl<T: ConfigTemplate> ConfigTemplate for Vec<T> {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        if self.is_empty() {
+            template.value(key, "[]");
+            return;
+        }
+
+        template.section(key, |template| {
+            for item in self {
+                item.write_template(SEQUENCE_ITEM, template);
+            }
+        });
+    }
+}
+
+impl<T: ConfigTemplate> ConfigTemplate for HashMap<String, T> {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        if self.is_empty() {
+            template.value(key, "{}");
+            return;
+        }
+
+        // Sort the entries, to keep the template stable.
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by_key(|(key, _)| *key);
+
+        template.section(key, |template| {
+            for (key, value) in entries {
+                value.write_template(key, template);
+            }
+        });
+    }
+}
+
+impl ConfigTemplate for String {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.value(key, Quoted(self));
+    }
+}
+
+impl ConfigTemplate for PathBuf {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.value(key, Quoted(&self.to_string_lossy()));
+    }
+}
+
+impl ConfigTemplate for LevelFilter {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.value(key, format_args!("{:?}", self));
+    }
+}
+
+impl ConfigTemplate for f32 {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.value(key, format_args!("{:?}", self));
+    }
+}
+
+/// Double-quoted YAML string.
+struct Quoted<'a>(&'a str);
+
+impl<'a> Display for Quoted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\t' => f.write_str("\\t")?,
+                '\n' => f.write_str("\\n")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+
+        f.write_char('"')
+    }
+}
+
+macro_rules! impl_display_template {
+    ($($ty:ty),*) => {
+        $(
+            impl ConfigTemplate for $ty {
+                fn write_template(&self, key: &str, template: &mut Template) {
+                    template.value(key, self);
+                }
+            }
+        )*
+    };
+}
+
+impl_display_template!(bool, u8, u16, u32, u64, usize, i8, i16, i32, i64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similar_fields() {
+        let fields = ["key_bindings", "mouse_bindings", "font"];
+
+        assert_eq!(similar_field("keybindings", &fields), Some("key_bindings"));
+        assert_eq!(similar_field("fnt", &fields), Some("font"));
+        assert_eq!(similar_field("colors", &fields), None);
+    }
+
+    #[test]
+    fn nested_section_path() {
+        let path = with_section("window", || with_section("padding", section_path));
+
+        assert_eq!(path, ["window", "padding"]);
+        assert!(section_path().is_empty());
+    }
+
+    #[test]
+    fn template_sections() {
+        let mut template = Template::new(true);
+        template.option("first", &["First option.", "", "Details."], &Some(3u8));
+        template.option("list", &[], &vec![String::from("a")]);
+        template.option("empty", &["Empty option."], &Vec::<u8>::new());
+        template.option("missing", &[], &None::<bool>);
+
+        assert_eq!(
+            template.into_text(),
+            "# First option.\n#\n# Details.\n#first: 3\n#list:\n  #- \"a\"\n\n# Empty option.\n\
+             #empty: []\n#missing: None\n"
+        );
+    }
+
+    #[test]
+    fn template_quoted_strings() {
+        let mut template = Template::new(false);
+        template.option("string", &[], &String::from("\"\\\t\u{1}│"));
+
+        assert_eq!(template.into_text(), "#string: \"\\\"\\\\\\t\\u0001│\"\n");
+    }
+
+    #[test]
+    fn template_without_comments() {
+        let mut template = Template::new(false);
+        template.option("first", &["First option."], &1.5f32);
+        template.option("second", &["Second option."], &true);
+
+        assert_eq!(template.into_text(), "#first: 1.5\n#second: true\n");
+    }
+}
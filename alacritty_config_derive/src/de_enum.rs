@@ -8,7 +8,16 @@ pub fn derive_deserialize(ident: Ident, data_enum: DataEnum) -> TokenStream {
 
     // Create match arm streams and get a list with all available values.
     let mut match_arms_stream = TokenStream2::new();
+    let mut template_arms_stream = TokenStream2::new();
     let mut available_values = String::from("one of ");
+    for variant in data_enum.variants.iter() {
+        let variant_ident = &variant.ident;
+        let variant_str = variant_ident.to_string();
+        template_arms_stream.extend(quote! {
+            #ident :: #variant_ident { .. } => #variant_str,
+        });
+    }
+
     for variant in data_enum.variants.iter().filter(|variant| {
         // Skip deserialization for `#[config(skip)]` fields.
         variant.attrs.iter().all(|attr| {
@@ -60,6 +69,15 @@ pub fn derive_deserialize(ident: Ident, data_enum: DataEnum) -> TokenStream {
                 deserializer.deserialize_str(#visitor)
             }
         }
+
+        impl alacritty_config::ConfigTemplate for #ident {
+            fn write_template(&self, key: &str, template: &mut alacritty_config::Template) {
+                let value = match self {
+                    #template_arms_stream
+                };
+                template.value(key, value);
+            }
+        }
     };
 
     tokens.into()
@@ -4,7 +4,10 @@ use quote::{format_ident, quote};
 use syn::parse::{self, Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{Error, Field, GenericParam, Generics, Ident, LitStr, Token, Type, TypeParam};
+use syn::{
+    Attribute, Error, Field, GenericParam, Generics, Ident, Lit, LitStr, Meta, Token, Type,
+    TypeParam,
+};
 
 /// Error message when attempting to flatten multiple fields.
 const MULTIPLE_FLATTEN_ERROR: &str = "At most one instance of #[config(flatten)] is supported";
@@ -17,11 +20,25 @@ pub fn derive_deserialize<T>(
     fields: Punctuated<Field, T>,
 ) -> TokenStream {
     // Create all necessary tokens for the implementation.
-    let GenericsStreams { unconstrained, constrained, phantoms } =
+    let GenericsStreams { unconstrained, constrained, template_constrained, phantoms } =
         generics_streams(generics.params);
-    let FieldStreams { flatten, match_assignments } = fields_deserializer(&fields);
+    let FieldStreams { flatten, match_assignments, literals, skipped, template } =
+        fields_deserializer(&fields);
     let visitor = format_ident!("{}Visitor", ident);
 
+    // Pass unknown keys to the flattened field, or report them when there is none.
+    let (unused, unknown_field) = if flatten.is_empty() {
+        (TokenStream2::new(), unknown_field_warning(&literals, &skipped))
+    } else {
+        let unused = quote! {
+            let mut unused = serde_yaml::Mapping::new();
+        };
+        let unknown_field = quote! {
+            unused.insert(serde_yaml::Value::String(key), value);
+        };
+        (unused, unknown_field)
+    };
+
     // Generate deserialization impl.
     let tokens = quote! {
         #[derive(Default)]
@@ -43,14 +60,13 @@ pub fn derive_deserialize<T>(
             {
                 let mut config = Self::Value::default();
 
-                // NOTE: This could be used to print unused keys.
-                let mut unused = serde_yaml::Mapping::new();
+                #unused
 
                 while let Some((key, value)) = map.next_entry::<String, serde_yaml::Value>()? {
                     match key.as_str() {
                         #match_assignments
                         _ => {
-                            unused.insert(serde_yaml::Value::String(key), value);
+                            #unknown_field
                         },
                     }
                 }
@@ -69,16 +85,55 @@ pub fn derive_deserialize<T>(
                 deserializer.deserialize_map(#visitor :: default())
             }
         }
+
+        impl< #template_constrained > alacritty_config::ConfigTemplate for #ident < #unconstrained > {
+            fn write_template(&self, key: &str, template: &mut alacritty_config::Template) {
+                template.section(key, |template| self.write_fields(template));
+            }
+
+            fn write_fields(&self, template: &mut alacritty_config::Template) {
+                #template
+            }
+        }
     };
 
     tokens.into()
 }
 
+/// Create the tokens logging a warning for unknown keys.
+///
+/// If a field name is sufficiently similar to the unknown key, it is suggested as replacement.
+/// Keys of skipped fields are never deserialized, so suggesting another field is not helpful.
+fn unknown_field_warning(literals: &[String], skipped: &[String]) -> TokenStream2 {
+    quote! {
+        let fields: &[&str] = &[#(#literals),*];
+        let skipped: &[&str] = &[#(#skipped),*];
+        let suggestion = if skipped.contains(&key.as_str()) {
+            None
+        } else {
+            alacritty_config::similar_field(&key, fields)
+        };
+
+        match suggestion {
+            Some(field) => log::warn!(
+                target: #LOG_TARGET,
+                "Config warning: unknown field `{}`; did you mean `{}`?",
+                key,
+                field
+            ),
+            None => log::warn!(target: #LOG_TARGET, "Config warning: unknown field `{}`", key),
+        }
+    }
+}
+
 // Token streams created from the fields in the struct.
 #[derive(Default)]
 struct FieldStreams {
     match_assignments: TokenStream2,
     flatten: TokenStream2,
+    literals: Vec<String>,
+    skipped: Vec<String>,
+    template: TokenStream2,
 }
 
 /// Create the deserializers for match arms and flattened fields.
@@ -102,9 +157,15 @@ fn field_deserializer(field_streams: &mut FieldStreams, field: &Field) -> Result
     let literal = ident.to_string();
     let mut literals = vec![literal.clone()];
 
+    // Create default stream for writing the field to the configuration template.
+    let docs = field_docs(&field.attrs);
+    let mut template_stream = quote! {
+        template.option(#literal, &[#(#docs),*], &self.#ident);
+    };
+
     // Create default stream for deserializing fields.
     let mut match_assignment_stream = quote! {
-        match serde::Deserialize::deserialize(value) {
+        match alacritty_config::with_section(&key, || serde::Deserialize::deserialize(value)) {
             Ok(value) => config.#ident = value,
             Err(err) => {
                 log::error!(target: #LOG_TARGET, "Config error: {}: {}", #literal, err);
@@ -121,7 +182,10 @@ fn field_deserializer(field_streams: &mut FieldStreams, field: &Field) -> Result
 
         match parsed.ident.as_str() {
             // Skip deserialization for `#[config(skip)]` fields.
-            "skip" => return Ok(()),
+            "skip" => {
+                field_streams.skipped.push(literal);
+                return Ok(());
+            },
             "flatten" => {
                 // NOTE: Currently only a single instance of flatten is supported per struct
                 // for complexity reasons.
@@ -129,6 +193,11 @@ fn field_deserializer(field_streams: &mut FieldStreams, field: &Field) -> Result
                     return Err(Error::new(attr.span(), MULTIPLE_FLATTEN_ERROR));
                 }
 
+                // Write the flattened fields as part of this section.
+                template_stream = quote! {
+                    alacritty_config::ConfigTemplate::write_fields(&self.#ident, template);
+                };
+
                 // Create the tokens to deserialize the flattened struct from the unused fields.
                 field_streams.flatten.extend(quote! {
                     let unused = serde_yaml::Value::Mapping(unused);
@@ -146,6 +215,9 @@ fn field_deserializer(field_streams: &mut FieldStreams, field: &Field) -> Result
                 match_assignment_stream.extend(quote! {
                     log::warn!(target: #LOG_TARGET, #message);
                 });
+
+                // Omit deprecated fields from the configuration template.
+                template_stream = TokenStream2::new();
             },
             // Add aliases to match pattern.
             "alias" => {
@@ -174,10 +246,29 @@ fn field_deserializer(field_streams: &mut FieldStreams, field: &Field) -> Result
     field_streams.match_assignments.extend(quote! {
         #(#literals)|* => { #match_assignment_stream },
     });
+    field_streams.literals.extend(literals);
+    field_streams.template.extend(template_stream);
 
     Ok(())
 }
 
+/// Lines of the doc comments of a field.
+fn field_docs(attrs: &[Attribute]) -> Vec<String> {
+    let mut docs = Vec::new();
+
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("doc")) {
+        if let Ok(Meta::NameValue(meta)) = attr.parse_meta() {
+            if let Lit::Str(doc) = meta.lit {
+                // Remove the space separating `///` from the text.
+                let doc = doc.value();
+                docs.push(doc.strip_prefix(' ').unwrap_or(&doc).to_owned());
+            }
+        }
+    }
+
+    docs
+}
+
 /// Field attribute.
 struct Attr {
     ident: String,
@@ -197,14 +288,16 @@ impl Parse for Attr {
 struct GenericsStreams {
     unconstrained: TokenStream2,
     constrained: TokenStream2,
+    template_constrained: TokenStream2,
     phantoms: TokenStream2,
 }
 
 /// Create the necessary generics annotations.
 ///
-/// This will create three different token streams, which might look like this:
+/// This will create four different token streams, which might look like this:
 ///  - unconstrained: `T`
 ///  - constrained: `T: Default + Deserialize<'de>`
+///  - template_constrained: `T: Default + ConfigTemplate`
 ///  - phantoms: `T: PhantomData<T>,`
 fn generics_streams<T>(params: Punctuated<GenericParam, T>) -> GenericsStreams {
     let mut generics = GenericsStreams::default();
@@ -216,6 +309,9 @@ fn generics_streams<T>(params: Punctuated<GenericParam, T>) -> GenericsStreams {
             generics.constrained.extend(quote! {
                 #ident : Default + serde::Deserialize<'de> ,
             });
+            generics.template_constrained.extend(quote! {
+                #ident : Default + alacritty_config::ConfigTemplate ,
+            });
             generics.phantoms.extend(quote! {
                 #ident : std::marker::PhantomData < #ident >,
             });
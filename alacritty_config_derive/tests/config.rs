@@ -2,6 +2,7 @@ use std::sync::{Arc, Mutex};
 
 use log::{Level, Log, Metadata, Record};
 
+use alacritty_config::{ConfigTemplate, Template};
 use alacritty_config_derive::ConfigDeserialize;
 
 #[derive(ConfigDeserialize, Debug, PartialEq, Eq)]
@@ -54,6 +55,7 @@ impl Default for Test {
 
 #[derive(ConfigDeserialize, Default)]
 struct Test2<T: Default> {
+    /// Generic field.
     field1: T,
     field2: Option<usize>,
     #[config(skip)]
@@ -64,6 +66,7 @@ struct Test2<T: Default> {
 
 #[derive(ConfigDeserialize, Default)]
 struct Test3 {
+    /// Flattened field.
     flatty: usize,
 }
 
@@ -86,6 +89,8 @@ fn config_deserialize() {
           field2: None
           field3: 99
           aliased: 8
+          aliassed: 9
+          unrelated: true
         flatty: 123
         enom_small: "one"
         enom_big: "THREE"
@@ -117,10 +122,25 @@ fn config_deserialize() {
     let warn_logs = logger.warn_logs.lock().unwrap();
     assert_eq!(warn_logs.as_slice(), [
         "Config warning: field1 is deprecated; use field2 instead",
+        "Config warning: unknown field `field3`",
+        "Config warning: unknown field `aliassed`; did you mean `aliased`?",
+        "Config warning: unknown field `unrelated`",
         "Config warning: enom_error is deprecated",
     ]);
 }
 
+#[test]
+fn config_template() {
+    let mut template = Template::new(true);
+    Test::default().write_fields(&mut template);
+
+    assert_eq!(
+        template.into_text(),
+        "#field3: 23\n#nesting:\n  # Generic field.\n  #field1: 0\n  #field2: None\n  #field4: 0\n\n\
+         # Flattened field.\n#flatty: 0\n#enom_small: Nine\n#enom_big: Nine\n"
+    );
+}
+
 static mut LOGGER: Option<Logger> = None;
 
 /// Logger storing all messages for later validation.
@@ -0,0 +1,156 @@
+//! Parser and grid benchmarks using vtebench-like workloads.
+//!
+//! All workloads are fed directly into the terminal, so only parsing and grid updates are measured
+//! without any rendering involved.
+
+use std::fmt::Write;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use alacritty_terminal::ansi::Processor;
+use alacritty_terminal::config::MockConfig;
+use alacritty_terminal::event::{Event, EventListener};
+use alacritty_terminal::term::{SizeInfo, Term};
+
+/// Number of columns in the benchmark terminal.
+const COLUMNS: usize = 200;
+
+/// Number of visible lines in the benchmark terminal.
+const LINES: usize = 50;
+
+/// Number of screens written by workloads redrawing the entire viewport.
+const FRAMES: usize = 10;
+
+/// Number of lines written by workloads scrolling the terminal.
+const SCROLL_LINES: usize = 10_000;
+
+/// Characters used for the unicode workload, including fullwidth and zerowidth characters.
+const UNICODE_CHARS: &[char] =
+    &['a', 'ä', 'ß', 'λ', 'ж', '日', '本', '語', '한', '🦀', '😀', '\u{301}', '\u{20d7}'];
+
+#[derive(Copy, Clone)]
+struct Mock;
+
+impl EventListener for Mock {
+    fn send_event(&self, _event: Event) {}
+}
+
+/// Xorshift random number generator.
+///
+/// This uses a fixed seed, so every run of the benchmarks uses the same workloads.
+struct Rng(u32);
+
+impl Rng {
+    fn new() -> Self {
+        Self(0x2545_f491)
+    }
+
+    fn gen(&mut self, max: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as usize % max
+    }
+
+    fn ascii(&mut self) -> char {
+        (b'!' + self.gen(94) as u8) as char
+    }
+}
+
+/// Fill every cell with a random character and random colors and attributes.
+fn dense_cells() -> String {
+    let mut rng = Rng::new();
+    let mut output = String::new();
+
+    for _ in 0..FRAMES {
+        output.push_str("\x1b[H");
+        for _ in 0..LINES * COLUMNS {
+            let (fg, bg, c) = (rng.gen(256), rng.gen(256), rng.ascii());
+            let _ = write!(output, "\x1b[38;5;{};48;5;{};1;3;4m{}", fg, bg, c);
+        }
+    }
+
+    output.push_str("\x1b[0m");
+    output
+}
+
+/// Scroll through lines of plain text.
+fn scrolling() -> String {
+    let mut rng = Rng::new();
+    let mut output = String::new();
+
+    for _ in 0..SCROLL_LINES {
+        let length = rng.gen(COLUMNS);
+        output.extend((0..length).map(|_| rng.ascii()));
+        output.push_str("\r\n");
+    }
+
+    output
+}
+
+/// Scroll through lines of plain text inside a scrolling region.
+fn scrolling_in_region() -> String {
+    let mut output = format!("\x1b[2;{}r\x1b[{}H", LINES - 1, LINES - 1);
+    output.push_str(&scrolling());
+    output.push_str("\x1b[r");
+    output
+}
+
+/// Move the cursor to random positions before writing every character.
+fn cursor_motion() -> String {
+    let mut rng = Rng::new();
+    let mut output = String::new();
+
+    for _ in 0..FRAMES * LINES * COLUMNS {
+        let (line, column, c) = (rng.gen(LINES) + 1, rng.gen(COLUMNS) + 1, rng.ascii());
+        let _ = write!(output, "\x1b[{};{}H{}", line, column, c);
+    }
+
+    output
+}
+
+/// Scroll through lines of mixed-width unicode text.
+fn unicode() -> String {
+    let mut rng = Rng::new();
+    let mut output = String::new();
+
+    for _ in 0..SCROLL_LINES {
+        let length = rng.gen(COLUMNS / 2);
+        output.extend((0..length).map(|_| UNICODE_CHARS[rng.gen(UNICODE_CHARS.len())]));
+        output.push_str("\r\n");
+    }
+
+    output
+}
+
+fn parser(criterion: &mut Criterion) {
+    let workloads: [(&str, fn() -> String); 5] = [
+        ("dense_cells", dense_cells),
+        ("scrolling", scrolling),
+        ("scrolling_in_region", scrolling_in_region),
+        ("cursor_motion", cursor_motion),
+        ("unicode", unicode),
+    ];
+
+    let mut group = criterion.benchmark_group("parser");
+    for (name, workload) in &workloads {
+        let bytes = workload().into_bytes();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+
+        group.bench_function(*name, |bencher| {
+            let size = SizeInfo::new(COLUMNS as f32, LINES as f32, 1., 1., 0., 0., false);
+            let mut terminal = Term::new(&MockConfig::default(), size, Mock);
+            let mut parser = Processor::new();
+
+            bencher.iter(|| {
+                for byte in &bytes {
+                    parser.advance(&mut terminal, *byte);
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parser);
+criterion_main!(benches);
@@ -1,15 +1,18 @@
 //! ANSI Terminal Stream Parsing.
 
 use std::convert::TryFrom;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
-use std::{iter, str};
+use std::{iter, mem, str};
 
+use bitflags::bitflags;
 use log::{debug, trace};
 use serde::{Deserialize, Serialize};
 use vte::{Params, ParamsIter};
 
 use alacritty_config_derive::ConfigDeserialize;
 
+use crate::graphics::{kitty, sixel, GraphicData};
 use crate::index::{Column, Line};
 use crate::term::color::Rgb;
 
@@ -28,6 +31,34 @@ const SYNC_START_ESCAPE_START: [u8; SYNC_ESCAPE_START_LEN] = [b'\x1b', b'P', b'=
 /// Start of the DCS sequence for terminating synchronized updates.
 const SYNC_END_ESCAPE_START: [u8; SYNC_ESCAPE_START_LEN] = [b'\x1b', b'P', b'=', b'2', b's'];
 
+/// Private mode for synchronized updates.
+const SYNC_MODE: u16 = 2026;
+
+/// CSI sequence for beginning synchronized updates.
+const SYNC_MODE_START: &[u8] = b"\x1b[?2026h";
+
+/// CSI sequence for terminating synchronized updates.
+const SYNC_MODE_END: &[u8] = b"\x1b[?2026l";
+
+/// Maximum number of bytes in an APC sequence (16MiB).
+const MAX_APC_SIZE: usize = 0x100_0000;
+
+/// Maximum number of bytes in an unhandled DCS sequence (1MiB).
+const MAX_DCS_SIZE: usize = 0x10_0000;
+
+/// Data following the `t` action of the DCS sequence wrapping escapes for tmux passthrough.
+const TMUX_PASSTHROUGH_DATA: &[u8] = b"mux;";
+
+/// Maximum number of bytes in a DECRQSS request.
+const MAX_STATUS_REQUEST_SIZE: usize = 8;
+
+/// Maximum number of bytes in an XTGETTCAP request.
+const MAX_TCAP_REQUEST_SIZE: usize = 1024;
+
+/// Maximum number of input bytes reported for grid invariant violations.
+#[cfg(all(debug_assertions, feature = "debug_checks"))]
+const MAX_RECENT_INPUT: usize = 256;
+
 /// Parse colors in XParseColor format.
 fn xparse_color(color: &[u8]) -> Option<Rgb> {
     if !color.is_empty() && color[0] == b'#' {
@@ -97,6 +128,71 @@ fn parse_number(input: &[u8]) -> Option<u8> {
     Some(num)
 }
 
+/// Decode a string of hex digit pairs.
+fn hex_decode(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() % 2 != 0 {
+        return None;
+    }
+
+    input
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+/// Path reported as a `file://host/path` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileUrl {
+    /// Host of the URL, which is empty when it was omitted.
+    pub host: String,
+
+    /// Decoded path.
+    pub path: PathBuf,
+}
+
+impl FileUrl {
+    /// Check if the path is on the machine with the specified `hostname`.
+    ///
+    /// URLs without host or with `localhost` as host are always local.
+    pub fn is_local(&self, hostname: Option<&str>) -> bool {
+        self.host.is_empty()
+            || self.host.eq_ignore_ascii_case("localhost")
+            || hostname.map_or(false, |hostname| self.host.eq_ignore_ascii_case(hostname))
+    }
+}
+
+/// Parse a `file://host/path` URL, decoding all percent-encoded bytes of the path.
+fn parse_file_url(url: &[u8]) -> Option<FileUrl> {
+    if !url.starts_with(b"file://") {
+        return None;
+    }
+
+    let url = &url[b"file://".len()..];
+    let path_start = url.iter().position(|&byte| byte == b'/')?;
+
+    let host = str::from_utf8(&url[..path_start]).ok()?;
+    let path = &url[path_start..];
+
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [*bytes.next()?, *bytes.next()?];
+            decoded.extend(hex_decode(&hex)?);
+        } else {
+            decoded.push(byte);
+        }
+    }
+
+    let path = PathBuf::from(String::from_utf8(decoded).ok()?);
+
+    Some(FileUrl { host: host.to_owned(), path })
+}
+
 /// Internal state for VTE processor.
 #[derive(Debug, Default)]
 struct ProcessorState {
@@ -108,6 +204,44 @@ struct ProcessorState {
 
     /// State for synchronized terminal updates.
     sync_state: SyncState,
+
+    /// Progress of the APC sequence, which is not handled by the VTE parser.
+    apc_state: ApcState,
+
+    /// Bytes of the APC sequence.
+    apc: Vec<u8>,
+
+    /// Unwrapped bytes of the active tmux passthrough sequence.
+    passthrough: Option<Vec<u8>>,
+
+    /// Last byte of the passthrough sequence was an escape.
+    passthrough_escape: bool,
+
+    /// Input since the last escape, reported when a grid invariant is violated.
+    #[cfg(all(debug_assertions, feature = "debug_checks"))]
+    recent_input: Vec<u8>,
+}
+
+/// Progress of an APC sequence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ApcState {
+    /// Outside of any APC sequence.
+    Ground,
+
+    /// Escape which might start an APC sequence.
+    Escape,
+
+    /// Reading the APC sequence.
+    Apc,
+
+    /// Escape which might terminate the APC sequence.
+    ApcEscape,
+}
+
+impl Default for ApcState {
+    fn default() -> Self {
+        ApcState::Ground
+    }
 }
 
 #[derive(Debug)]
@@ -136,6 +270,33 @@ enum Dcs {
 
     /// End of the synchronized update.
     SyncEnd,
+
+    /// Sixel graphic.
+    Sixel(Box<sixel::Parser>),
+
+    /// Request for the state of a setting (DECRQSS).
+    StatusRequest(Vec<u8>),
+
+    /// Request for terminfo capabilities (XTGETTCAP).
+    CapabilityRequest(Vec<u8>),
+
+    /// Possible start of a tmux passthrough sequence, with the number of matching data bytes.
+    PassthroughStart(usize),
+
+    /// Passthrough sequence, which is unwrapped before reaching the VTE parser.
+    Passthrough,
+
+    /// Sequence forwarded to the handler without interpretation.
+    Unhandled(Box<UnhandledDcs>),
+}
+
+/// DCS sequence which is not handled by the parser.
+#[derive(Debug)]
+struct UnhandledDcs {
+    params: Vec<u16>,
+    intermediates: Vec<u8>,
+    action: char,
+    data: Vec<u8>,
 }
 
 /// The processor wraps a `vte::Parser` to ultimately call methods on a Handler.
@@ -158,13 +319,81 @@ impl Processor {
         H: Handler,
     {
         if self.state.sync_state.timeout.is_none() {
-            let mut performer = Performer::new(&mut self.state, handler);
-            self.parser.advance(&mut performer, byte);
+            self.advance_parser(handler, byte);
         } else {
             self.advance_sync(handler, byte);
         }
     }
 
+    /// Pass a byte to the VTE parser.
+    #[inline]
+    fn advance_parser<H>(&mut self, handler: &mut H, byte: u8)
+    where
+        H: Handler,
+    {
+        if self.state.passthrough.is_some() {
+            self.advance_passthrough(handler, byte);
+            return;
+        }
+
+        let mut performer = Performer::new(&mut self.state, handler);
+        performer.advance_apc(byte);
+        self.parser.advance(&mut performer, byte);
+
+        #[cfg(all(debug_assertions, feature = "debug_checks"))]
+        {
+            let recent_input = &mut self.state.recent_input;
+            if byte == 0x1b || recent_input.len() >= MAX_RECENT_INPUT {
+                recent_input.clear();
+            }
+            recent_input.push(byte);
+
+            handler.check_invariants(recent_input);
+        }
+    }
+
+    /// Unwrap the escapes of a tmux passthrough sequence.
+    ///
+    /// Escapes inside the sequence are doubled, a single escape terminates it.
+    #[cold]
+    fn advance_passthrough<H>(&mut self, handler: &mut H, byte: u8)
+    where
+        H: Handler,
+    {
+        let escape = mem::replace(&mut self.state.passthrough_escape, false);
+        let passthrough = match &mut self.state.passthrough {
+            Some(passthrough) => passthrough,
+            None => return,
+        };
+
+        match (escape, byte) {
+            (false, 0x1b) => self.state.passthrough_escape = true,
+            (false, byte) | (true, byte @ 0x1b) => {
+                // Passthrough sequences usually wrap graphics, so they share the APC size limit.
+                if passthrough.len() < MAX_APC_SIZE {
+                    passthrough.push(byte);
+                }
+            },
+            (true, byte) => {
+                let passthrough = self.state.passthrough.take().unwrap_or_default();
+
+                // Terminate the DCS sequence in the VTE parser.
+                self.advance_parser(handler, 0x1b);
+                self.advance_parser(handler, b'\\');
+
+                for unwrapped in passthrough {
+                    self.advance_parser(handler, unwrapped);
+                }
+
+                // Any escape other than ST starts a new sequence after the passthrough.
+                if byte != b'\\' {
+                    self.advance_parser(handler, 0x1b);
+                    self.advance_parser(handler, byte);
+                }
+            },
+        }
+    }
+
     /// End a synchronized update.
     pub fn stop_sync<H>(&mut self, handler: &mut H)
     where
@@ -173,8 +402,7 @@ impl Processor {
         // Process all synchronized bytes.
         for i in 0..self.state.sync_state.buffer.len() {
             let byte = self.state.sync_state.buffer[i];
-            let mut performer = Performer::new(&mut self.state, handler);
-            self.parser.advance(&mut performer, byte);
+            self.advance_parser(handler, byte);
         }
 
         // Resetting state after processing makes sure we don't interpret buffered sync escapes.
@@ -207,6 +435,14 @@ impl Processor {
             Some(_) => self.advance_sync_dcs_end(handler, byte),
             None => self.advance_sync_dcs_start(),
         }
+
+        // Handle sync private mode escape sequences.
+        let buffer = &self.state.sync_state.buffer;
+        if buffer.ends_with(SYNC_MODE_END) {
+            self.stop_sync(handler);
+        } else if buffer.ends_with(SYNC_MODE_START) {
+            self.state.sync_state.timeout = Some(Instant::now() + SYNC_UPDATE_TIMEOUT);
+        }
     }
 
     /// Find the start of sync DCS sequences.
@@ -240,7 +476,7 @@ impl Processor {
                     self.state.sync_state.timeout = Some(Instant::now() + SYNC_UPDATE_TIMEOUT);
                 },
                 Some(Dcs::SyncEnd) => self.stop_sync(handler),
-                None => (),
+                _ => (),
             },
         }
     }
@@ -261,6 +497,51 @@ impl<'a, H: Handler + 'a> Performer<'a, H> {
     pub fn new<'b>(state: &'b mut ProcessorState, handler: &'b mut H) -> Performer<'b, H> {
         Performer { state, handler }
     }
+
+    /// Track APC sequences, which are ignored by the VTE parser.
+    #[inline]
+    fn advance_apc(&mut self, byte: u8) {
+        self.state.apc_state = match (self.state.apc_state, byte) {
+            (ApcState::Ground, 0x1b) => ApcState::Escape,
+            (ApcState::Ground, _) => return,
+            (ApcState::Escape, b'_') | (ApcState::ApcEscape, b'_') => {
+                self.state.apc.clear();
+                ApcState::Apc
+            },
+            (ApcState::Apc, 0x1b) => ApcState::ApcEscape,
+            // Cancel the APC sequence.
+            (ApcState::Apc, 0x18) | (ApcState::Apc, 0x1a) => ApcState::Ground,
+            (ApcState::Apc, _) => {
+                if self.state.apc.len() < MAX_APC_SIZE {
+                    self.state.apc.push(byte);
+                }
+                ApcState::Apc
+            },
+            (ApcState::ApcEscape, b'\\') => {
+                self.apc_dispatch();
+                ApcState::Ground
+            },
+            (_, 0x1b) => ApcState::Escape,
+            (_, _) => ApcState::Ground,
+        };
+    }
+
+    /// Dispatch a terminated APC sequence.
+    fn apc_dispatch(&mut self) {
+        match self.state.apc.split_first() {
+            Some((b'G', data)) => match kitty::Command::parse(data) {
+                Ok(command) => self.handler.kitty_graphics(command),
+                Err(err) => debug!("[invalid kitty graphics command] {}", err),
+            },
+            _ => {
+                debug!("[unhandled apc] data={:?}", self.state.apc);
+                self.handler.unhandled_apc(&self.state.apc);
+            },
+        }
+
+        // Release the memory of large payloads.
+        self.state.apc = Vec::new();
+    }
 }
 
 /// Type that handles actions from the parser.
@@ -271,6 +552,9 @@ pub trait Handler {
     /// OSC to set window title.
     fn set_title(&mut self, _: Option<String>) {}
 
+    /// OSC to set the icon name, which is used for minimized windows and tabs.
+    fn set_icon_name(&mut self, _: Option<String>) {}
+
     /// Set the cursor style.
     fn set_cursor_style(&mut self, _: Option<CursorStyle>) {}
 
@@ -360,6 +644,17 @@ pub trait Handler {
     /// no mode flags).
     fn erase_chars(&mut self, _: Column) {}
 
+    /// Copy a rectangular area, moving its top left corner to `line` and `column`.
+    ///
+    /// The destination is 1-based, like the coordinates of the rectangle.
+    fn copy_rectangle(&mut self, _: Rectangle, _line: usize, _column: usize) {}
+
+    /// Fill a rectangular area with a character, using the current attributes.
+    fn fill_rectangle(&mut self, _: char, _: Rectangle) {}
+
+    /// Erase a rectangular area.
+    fn erase_rectangle(&mut self, _: Rectangle) {}
+
     /// Delete `count` chars.
     ///
     /// Deleting a character is like the delete key on the keyboard - everything
@@ -384,6 +679,15 @@ pub trait Handler {
     /// Clear screen.
     fn clear_screen(&mut self, _mode: ClearMode) {}
 
+    /// Clear all unprotected cells in the current line.
+    fn selective_clear_line(&mut self, _mode: LineClearMode) {}
+
+    /// Clear all unprotected cells on the screen.
+    fn selective_clear_screen(&mut self, _mode: ClearMode) {}
+
+    /// Set whether new characters are protected from selective erase.
+    fn set_character_protection(&mut self, _protected: bool) {}
+
     /// Clear tab stops.
     fn clear_tabs(&mut self, _mode: TabulationClearMode) {}
 
@@ -430,8 +734,8 @@ pub trait Handler {
     /// Set an indexed color value.
     fn set_color(&mut self, _: usize, _: Rgb) {}
 
-    /// Write a foreground/background color escape sequence with the current color.
-    fn dynamic_color_sequence(&mut self, _: u8, _: usize, _: &str) {}
+    /// Write a color escape sequence with the current color, starting with `prefix`.
+    fn dynamic_color_sequence(&mut self, _prefix: String, _: usize, _: &str) {}
 
     /// Reset an indexed color to original value.
     fn reset_color(&mut self, _: usize) {}
@@ -451,11 +755,67 @@ pub trait Handler {
     /// Pop the last title from the stack.
     fn pop_title(&mut self) {}
 
+    /// Push an icon name onto the stack.
+    fn push_icon_name(&mut self) {}
+
+    /// Pop the last icon name from the stack.
+    fn pop_icon_name(&mut self) {}
+
     /// Report text area size in pixels.
     fn text_area_size_pixels(&mut self) {}
 
+    /// Insert a graphic at the cursor position.
+    fn insert_graphic(&mut self, _graphic: GraphicData) {}
+
+    /// Kitty graphics protocol command.
+    fn kitty_graphics(&mut self, _command: kitty::Command) {}
+
+    /// Validate the handler's state after processing `input`.
+    #[cfg(feature = "debug_checks")]
+    fn check_invariants(&mut self, _input: &[u8]) {}
+
     /// Report text area size in characters.
     fn text_area_size_chars(&mut self) {}
+
+    /// Push keyboard modes onto the stack of the active screen.
+    fn push_keyboard_modes(&mut self, _modes: KeyboardModes) {}
+
+    /// Pop keyboard modes from the stack of the active screen.
+    fn pop_keyboard_modes(&mut self, _to_pop: u16) {}
+
+    /// Change the active keyboard modes.
+    fn set_keyboard_modes(&mut self, _modes: KeyboardModes, _behavior: KeyboardModesApplyBehavior) {
+    }
+
+    /// Report the active keyboard modes.
+    fn report_keyboard_modes(&mut self) {}
+
+    /// Set the `modifyOtherKeys` key encoding.
+    fn set_modify_other_keys(&mut self, _mode: ModifyOtherKeys) {}
+
+    /// Report the active `modifyOtherKeys` key encoding.
+    fn report_modify_other_keys(&mut self) {}
+
+    /// Report the state of a setting (DECRQSS), `None` for unsupported settings.
+    fn report_status_setting(&mut self, _setting: Option<StatusSetting>) {}
+
+    /// Report the value of a terminfo capability (XTGETTCAP), `None` for invalid names.
+    fn report_capability(&mut self, _name: Option<String>) {}
+
+    /// Mark the cursor line as part of a prompt or command (OSC 133).
+    fn set_shell_mark(&mut self, _: ShellMark) {}
+
+    /// DCS sequence which is not handled by the parser.
+    fn unhandled_dcs(&mut self, _params: &[u16], _intermediates: &[u8], _action: char, _: &[u8]) {}
+
+    /// APC sequence which is not handled by the parser.
+    fn unhandled_apc(&mut self, _data: &[u8]) {}
+
+    /// Set the working directory reported by the shell (OSC 7).
+    fn set_working_directory(&mut self, _: Option<FileUrl>) {}
+
+    /// Set the window name used for targeting IPC messages (OSC 1337).
+    fn set_window_name(&mut self, _: Option<String>) {}
 }
 
 /// Terminal cursor configuration.
@@ -492,6 +852,22 @@ impl Default for CursorShape {
     }
 }
 
+/// Shell integration marks.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ShellMark {
+    /// Start of the prompt.
+    PromptStart,
+
+    /// Start of the command input, after the prompt.
+    CommandStart,
+
+    /// Start of the command output.
+    OutputStart,
+
+    /// End of the command output.
+    CommandEnd,
+}
+
 /// Terminal modes.
 #[derive(Debug, Eq, PartialEq)]
 pub enum Mode {
@@ -529,6 +905,11 @@ pub enum Mode {
     LineFeedNewLine = 20,
     /// ?25
     ShowCursor = 25,
+    /// DECNKM Numeric Keypad Mode.
+    ///
+    /// * `CSI ? 66 h` change to application keypad mode
+    /// * `CSI ? 66 l` reset to numeric keypad mode
+    ApplicationKeypad = 66,
     /// ?1000
     ReportMouseClicks = 1000,
     /// ?1002
@@ -543,6 +924,8 @@ pub enum Mode {
     SgrMouse = 1006,
     /// ?1007
     AlternateScroll = 1007,
+    /// ?1016
+    SgrPixelMouse = 1016,
     /// ?1042
     UrgencyHints = 1042,
     /// ?1049
@@ -568,6 +951,7 @@ impl Mode {
                 7 => Mode::LineWrap,
                 12 => Mode::BlinkingCursor,
                 25 => Mode::ShowCursor,
+                66 => Mode::ApplicationKeypad,
                 1000 => Mode::ReportMouseClicks,
                 1002 => Mode::ReportCellMouseMotion,
                 1003 => Mode::ReportAllMouseMotion,
@@ -575,6 +959,7 @@ impl Mode {
                 1005 => Mode::Utf8Mouse,
                 1006 => Mode::SgrMouse,
                 1007 => Mode::AlternateScroll,
+                1016 => Mode::SgrPixelMouse,
                 1042 => Mode::UrgencyHints,
                 1049 => Mode::SwapScreenAndSetRestoreCursor,
                 2004 => Mode::BracketedPaste,
@@ -593,6 +978,64 @@ impl Mode {
     }
 }
 
+bitflags! {
+    /// Progressive enhancements of the kitty keyboard protocol.
+    ///
+    /// Reporting the text associated with a key is not supported.
+    pub struct KeyboardModes: u8 {
+        const NO_MODE                 = 0b0000_0000;
+        /// Report ambiguous keys like `Esc` or `Ctrl+I` using `CSI u` sequences.
+        const DISAMBIGUATE_ESC_CODES  = 0b0000_0001;
+        /// Report repeat and release events.
+        const REPORT_EVENT_TYPES      = 0b0000_0010;
+        /// Report the shifted key alongside the base key.
+        const REPORT_ALTERNATE_KEYS   = 0b0000_0100;
+        /// Report all keys, including text and modifiers, using escape sequences.
+        const REPORT_ALL_KEYS_AS_ESC  = 0b0000_1000;
+    }
+}
+
+/// Behavior when changing the active keyboard modes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyboardModesApplyBehavior {
+    /// Replace the active modes.
+    Replace,
+    /// Add the modes to the active ones.
+    Union,
+    /// Remove the modes from the active ones.
+    Difference,
+}
+
+/// Setting whose state can be requested with DECRQSS.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StatusSetting {
+    /// Character attributes (SGR).
+    Sgr,
+    /// Cursor style (DECSCUSR).
+    CursorStyle,
+    /// Top and bottom margin of the scrolling region (DECSTBM).
+    ScrollingRegion,
+    /// Character protection attribute (DECSCA).
+    CharacterProtection,
+}
+
+/// Encoding of modified keys requested through xterm's `modifyOtherKeys`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModifyOtherKeys {
+    /// Use the regular encoding for all keys.
+    Reset,
+    /// Report modified keys without a well-known encoding as `CSI 27 ; mods ; key ~`.
+    EnableExceptWellDefined,
+    /// Report all modified keys as `CSI 27 ; mods ; key ~`.
+    EnableAll,
+}
+
+impl Default for ModifyOtherKeys {
+    fn default() -> Self {
+        ModifyOtherKeys::Reset
+    }
+}
+
 /// Mode for clearing line.
 ///
 /// Relative to cursor.
@@ -621,6 +1064,18 @@ pub enum ClearMode {
     Saved,
 }
 
+/// Rectangular area of the screen used by the DEC rectangle operations.
+///
+/// Coordinates are 1-based and inclusive, a missing bottom or right edge extends the area to the
+/// end of the screen. Lines are relative to the scrolling region in origin mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rectangle {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: Option<usize>,
+    pub right: Option<usize>,
+}
+
 /// Mode for clearing tab stops.
 #[derive(Debug)]
 pub enum TabulationClearMode {
@@ -768,6 +1223,12 @@ pub enum Attr {
     Underline,
     /// Underlined twice.
     DoubleUnderline,
+    /// Wavy underline.
+    Undercurl,
+    /// Dotted underline.
+    DottedUnderline,
+    /// Dashed underline.
+    DashedUnderline,
     /// Blink cursor slowly.
     BlinkSlow,
     /// Blink cursor fast.
@@ -779,6 +1240,7 @@ pub enum Attr {
     /// Strikeout text.
     Strike,
     /// Cancel bold.
+    #[deprecated(note = "SGR 21 is parsed as `Attr::DoubleUnderline`")]
     CancelBold,
     /// Cancel bold and dim.
     CancelBoldDim,
@@ -908,25 +1370,104 @@ where
                     self.state.dcs = Some(Dcs::SyncStart);
                 }
             },
-            _ => debug!(
-                "[unhandled hook] params={:?}, ints: {:?}, ignore: {:?}, action: {:?}",
-                params, intermediates, ignore, action
-            ),
+            ('q', []) => {
+                let background = params.iter().nth(1).map_or(0, |param| param[0]);
+                self.state.dcs = Some(Dcs::Sixel(Box::new(sixel::Parser::new(background))));
+            },
+            ('q', [b'$']) => self.state.dcs = Some(Dcs::StatusRequest(Vec::new())),
+            ('q', [b'+']) => self.state.dcs = Some(Dcs::CapabilityRequest(Vec::new())),
+            ('t', []) if params.iter().all(|param| param[0] == 0) => {
+                self.state.dcs = Some(Dcs::PassthroughStart(0));
+            },
+            _ => {
+                debug!(
+                    "[unhandled hook] params={:?}, ints: {:?}, ignore: {:?}, action: {:?}",
+                    params, intermediates, ignore, action
+                );
+
+                self.state.dcs = Some(Dcs::Unhandled(Box::new(UnhandledDcs {
+                    params: params.iter().map(|param| param[0]).collect(),
+                    intermediates: intermediates.to_vec(),
+                    action,
+                    data: Vec::new(),
+                })));
+            },
         }
     }
 
     #[inline]
     fn put(&mut self, byte: u8) {
-        debug!("[unhandled put] byte={:?}", byte);
+        match &mut self.state.dcs {
+            Some(Dcs::Sixel(parser)) => parser.put(byte),
+            Some(Dcs::StatusRequest(request)) if request.len() < MAX_STATUS_REQUEST_SIZE => {
+                request.push(byte)
+            },
+            Some(Dcs::CapabilityRequest(request)) if request.len() < MAX_TCAP_REQUEST_SIZE => {
+                request.push(byte)
+            },
+            Some(Dcs::Unhandled(dcs)) if dcs.data.len() < MAX_DCS_SIZE => dcs.data.push(byte),
+            Some(Dcs::PassthroughStart(matched)) => {
+                if TMUX_PASSTHROUGH_DATA[*matched] != byte {
+                    let mut data = TMUX_PASSTHROUGH_DATA[..*matched].to_vec();
+                    data.push(byte);
+                    self.state.dcs = Some(Dcs::Unhandled(Box::new(UnhandledDcs {
+                        params: Vec::new(),
+                        intermediates: Vec::new(),
+                        action: 't',
+                        data,
+                    })));
+                } else if *matched + 1 < TMUX_PASSTHROUGH_DATA.len() {
+                    *matched += 1;
+                } else {
+                    // The remaining bytes of the sequence never reach the VTE parser.
+                    self.state.passthrough = Some(Vec::new());
+                    self.state.dcs = Some(Dcs::Passthrough);
+                }
+            },
+            Some(Dcs::Passthrough) => (),
+            _ => debug!("[unhandled put] byte={:?}", byte),
+        }
     }
 
     #[inline]
     fn unhook(&mut self) {
-        match self.state.dcs {
+        match self.state.dcs.take() {
             Some(Dcs::SyncStart) => {
                 self.state.sync_state.timeout = Some(Instant::now() + SYNC_UPDATE_TIMEOUT);
             },
             Some(Dcs::SyncEnd) => (),
+            Some(Dcs::Sixel(parser)) => {
+                if let Some(graphic) = parser.finish() {
+                    self.handler.insert_graphic(graphic);
+                }
+            },
+            Some(Dcs::StatusRequest(request)) => {
+                let setting = match request.as_slice() {
+                    b"m" => Some(StatusSetting::Sgr),
+                    b" q" => Some(StatusSetting::CursorStyle),
+                    b"r" => Some(StatusSetting::ScrollingRegion),
+                    b"\"q" => Some(StatusSetting::CharacterProtection),
+                    _ => {
+                        debug!("[unhandled DECRQSS] request={:?}", request);
+                        None
+                    },
+                };
+                self.handler.report_status_setting(setting);
+            },
+            Some(Dcs::CapabilityRequest(request)) => {
+                // Capability names are hex encoded and separated by semicolons.
+                for name in request.split(|&byte| byte == b';') {
+                    let name = hex_decode(name).and_then(|name| String::from_utf8(name).ok());
+                    self.handler.report_capability(name);
+                }
+            },
+            Some(Dcs::Unhandled(dcs)) => {
+                self.handler.unhandled_dcs(&dcs.params, &dcs.intermediates, dcs.action, &dcs.data);
+            },
+            Some(Dcs::PassthroughStart(matched)) => {
+                self.handler.unhandled_dcs(&[], &[], 't', &TMUX_PASSTHROUGH_DATA[..matched]);
+            },
+            Some(Dcs::Passthrough) => (),
             _ => debug!("[unhandled unhook]"),
         }
     }
@@ -952,8 +1493,8 @@ where
         }
 
         match params[0] {
-            // Set window title.
-            b"0" | b"2" => {
+            // Set icon name and window title.
+            b"0" | b"1" | b"2" => {
                 if params.len() >= 2 {
                     let title = params[1..]
                         .iter()
@@ -962,25 +1503,43 @@ where
                         .join(";")
                         .trim()
                         .to_owned();
-                    self.handler.set_title(Some(title));
+
+                    if params[0] != b"2" {
+                        self.handler.set_icon_name(Some(title.clone()));
+                    }
+                    if params[0] != b"1" {
+                        self.handler.set_title(Some(title));
+                    }
                     return;
                 }
                 unhandled(params);
             },
 
-            // Set color index.
+            // Get/set color index.
             b"4" => {
-                if params.len() > 1 && params.len() % 2 != 0 {
-                    for chunk in params[1..].chunks(2) {
-                        let index = parse_number(chunk[0]);
-                        let color = xparse_color(chunk[1]);
-                        if let (Some(i), Some(c)) = (index, color) {
-                            self.handler.set_color(i as usize, c);
-                            return;
-                        }
+                if params.len() <= 1 || params.len() % 2 == 0 {
+                    unhandled(params);
+                    return;
+                }
+
+                for chunk in params[1..].chunks(2) {
+                    let index = match parse_number(chunk[0]) {
+                        Some(index) => index,
+                        None => {
+                            unhandled(params);
+                            continue;
+                        },
+                    };
+
+                    if let Some(c) = xparse_color(chunk[1]) {
+                        self.handler.set_color(index as usize, c);
+                    } else if chunk[1] == b"?" {
+                        let prefix = format!("4;{}", index);
+                        self.handler.dynamic_color_sequence(prefix, index as usize, terminator);
+                    } else {
+                        unhandled(params);
                     }
                 }
-                unhandled(params);
             },
 
             // Get/set Foreground, Background, Cursor colors.
@@ -1001,11 +1560,8 @@ where
                             if let Some(color) = xparse_color(param) {
                                 self.handler.set_color(index, color);
                             } else if param == b"?" {
-                                self.handler.dynamic_color_sequence(
-                                    dynamic_code,
-                                    index,
-                                    terminator,
-                                );
+                                let prefix = dynamic_code.to_string();
+                                self.handler.dynamic_color_sequence(prefix, index, terminator);
                             } else {
                                 unhandled(params);
                             }
@@ -1048,10 +1604,47 @@ where
                 }
             },
 
+            // Current working directory.
+            b"7" => {
+                let url = params[1..].join(&b';');
+                if url.is_empty() {
+                    self.handler.set_working_directory(None);
+                } else if let Some(url) = parse_file_url(&url) {
+                    self.handler.set_working_directory(Some(url));
+                } else {
+                    unhandled(params);
+                }
+            },
+
+            // Window name, in iTerm2's `key=value` format.
+            b"1337" => {
+                const KEY: &[u8] = b"WindowName=";
+
+                let value = params[1..].join(&b';');
+                if value.starts_with(KEY) {
+                    let name = String::from_utf8_lossy(&value[KEY.len()..]).trim().to_owned();
+                    self.handler.set_window_name(Some(name).filter(|name| !name.is_empty()));
+                } else {
+                    unhandled(params);
+                }
+            },
+
+            // Shell integration marks.
+            b"133" => {
+                let mark = match params.get(1).and_then(|param| param.get(0)) {
+                    Some(b'A') => ShellMark::PromptStart,
+                    Some(b'B') => ShellMark::CommandStart,
+                    Some(b'C') => ShellMark::OutputStart,
+                    Some(b'D') => ShellMark::CommandEnd,
+                    _ => return unhandled(params),
+                };
+                self.handler.set_shell_mark(mark);
+            },
+
             // Reset color index.
             b"104" => {
                 // Reset all color indexes when no parameters are given.
-                if params.len() == 1 {
+                if params.len() == 1 || params[1].is_empty() {
                     for i in 0..256 {
                         self.handler.reset_color(i);
                     }
@@ -1151,6 +1744,12 @@ where
             },
             ('h', intermediates) => {
                 for param in params_iter.map(|param| param[0]) {
+                    // Start a synchronized update. The end is handled with a separate parser.
+                    if intermediates == [b'?'] && param == SYNC_MODE {
+                        self.state.sync_state.timeout = Some(Instant::now() + SYNC_UPDATE_TIMEOUT);
+                        continue;
+                    }
+
                     match Mode::from_primitive(intermediates.get(0), param) {
                         Some(mode) => handler.set_mode(mode),
                         None => unhandled!(),
@@ -1158,21 +1757,26 @@ where
                 }
             },
             ('I', []) => handler.move_forward_tabs(next_param_or(1)),
-            ('J', []) => {
+            ('J', []) | ('J', [b'?']) => {
                 let mode = match next_param_or(0) {
                     0 => ClearMode::Below,
                     1 => ClearMode::Above,
                     2 => ClearMode::All,
-                    3 => ClearMode::Saved,
+                    3 if intermediates.is_empty() => ClearMode::Saved,
                     _ => {
                         unhandled!();
                         return;
                     },
                 };
 
-                handler.clear_screen(mode);
+                // DECSED (CSI ? Ps J) -- Selective Erase in Display.
+                if intermediates.is_empty() {
+                    handler.clear_screen(mode);
+                } else {
+                    handler.selective_clear_screen(mode);
+                }
             },
-            ('K', []) => {
+            ('K', []) | ('K', [b'?']) => {
                 let mode = match next_param_or(0) {
                     0 => LineClearMode::Right,
                     1 => LineClearMode::Left,
@@ -1183,11 +1787,21 @@ where
                     },
                 };
 
-                handler.clear_line(mode);
+                // DECSEL (CSI ? Ps K) -- Selective Erase in Line.
+                if intermediates.is_empty() {
+                    handler.clear_line(mode);
+                } else {
+                    handler.selective_clear_line(mode);
+                }
             },
             ('L', []) => handler.insert_blank_lines(next_param_or(1) as usize),
             ('l', intermediates) => {
                 for param in params_iter.map(|param| param[0]) {
+                    // Synchronized updates are terminated by the sync parser.
+                    if intermediates == [b'?'] && param == SYNC_MODE {
+                        continue;
+                    }
+
                     match Mode::from_primitive(intermediates.get(0), param) {
                         Some(mode) => handler.unset_mode(mode),
                         None => unhandled!(),
@@ -1195,6 +1809,26 @@ where
                 }
             },
             ('M', []) => handler.delete_lines(next_param_or(1) as usize),
+            ('m', [b'>']) => {
+                // Only the `modifyOtherKeys` resource is supported.
+                if next_param_or(0) != 4 {
+                    unhandled!();
+                    return;
+                }
+
+                let mode = match next_param_or(0) {
+                    0 => ModifyOtherKeys::Reset,
+                    1 => ModifyOtherKeys::EnableExceptWellDefined,
+                    2 => ModifyOtherKeys::EnableAll,
+                    _ => {
+                        unhandled!();
+                        return;
+                    },
+                };
+
+                handler.set_modify_other_keys(mode);
+            },
+            ('m', [b'?']) if next_param_or(0) == 4 => handler.report_modify_other_keys(),
             ('m', []) => {
                 if params.is_empty() {
                     handler.terminal_attribute(Attr::Reset);
@@ -1227,6 +1861,14 @@ where
 
                 handler.set_cursor_style(cursor_style);
             },
+            ('q', [b'"']) => {
+                // DECSCA (CSI Ps " q) -- Select Character Protection Attribute.
+                match next_param_or(0) {
+                    0 | 2 => handler.set_character_protection(false),
+                    1 => handler.set_character_protection(true),
+                    _ => unhandled!(),
+                }
+            },
             ('r', []) => {
                 let top = next_param_or(1) as usize;
                 let bottom =
@@ -1240,13 +1882,72 @@ where
             ('t', []) => match next_param_or(1) as usize {
                 14 => handler.text_area_size_pixels(),
                 18 => handler.text_area_size_chars(),
-                22 => handler.push_title(),
-                23 => handler.pop_title(),
+                22 => match next_param_or(0) {
+                    0 => {
+                        handler.push_icon_name();
+                        handler.push_title();
+                    },
+                    1 => handler.push_icon_name(),
+                    2 => handler.push_title(),
+                    _ => unhandled!(),
+                },
+                23 => match next_param_or(0) {
+                    0 => {
+                        handler.pop_icon_name();
+                        handler.pop_title();
+                    },
+                    1 => handler.pop_icon_name(),
+                    2 => handler.pop_title(),
+                    _ => unhandled!(),
+                },
                 _ => unhandled!(),
             },
             ('u', []) => handler.restore_cursor_position(),
+            ('v', [b'$']) => {
+                let rectangle = parse_rectangle(&mut next_param_or);
+
+                // Source and destination page are ignored, since there is only one page.
+                let _source_page = next_param_or(1);
+                let line = next_param_or(1) as usize;
+                let column = next_param_or(1) as usize;
+
+                handler.copy_rectangle(rectangle, line, column);
+            },
+            ('u', [b'?']) => handler.report_keyboard_modes(),
+            ('u', [b'=']) => {
+                let modes = KeyboardModes::from_bits_truncate(next_param_or(0) as u8);
+                let behavior = match next_param_or(1) {
+                    1 => KeyboardModesApplyBehavior::Replace,
+                    2 => KeyboardModesApplyBehavior::Union,
+                    3 => KeyboardModesApplyBehavior::Difference,
+                    _ => {
+                        unhandled!();
+                        return;
+                    },
+                };
+
+                handler.set_keyboard_modes(modes, behavior);
+            },
+            ('u', [b'>']) => {
+                let modes = KeyboardModes::from_bits_truncate(next_param_or(0) as u8);
+                handler.push_keyboard_modes(modes);
+            },
+            ('u', [b'<']) => handler.pop_keyboard_modes(next_param_or(1)),
             ('X', []) => handler.erase_chars(Column(next_param_or(1) as usize)),
+            ('x', [b'$']) => {
+                // Only printable characters of Latin-1 are allowed for filling.
+                let c = match next_param_or(0) {
+                    c @ 32..=126 | c @ 160..=255 => c as u8 as char,
+                    _ => {
+                        unhandled!();
+                        return;
+                    },
+                };
+
+                handler.fill_rectangle(c, parse_rectangle(&mut next_param_or));
+            },
             ('Z', []) => handler.move_backward_tabs(next_param_or(1)),
+            ('z', [b'$']) => handler.erase_rectangle(parse_rectangle(&mut next_param_or)),
             _ => unhandled!(),
         }
     }
@@ -1304,6 +2005,16 @@ where
     }
 }
 
+/// Parse the top, left, bottom and right edge of a DEC rectangle operation.
+fn parse_rectangle<F: FnMut(u16) -> u16>(next_param_or: &mut F) -> Rectangle {
+    let top = next_param_or(1) as usize;
+    let left = next_param_or(1) as usize;
+    let bottom = Some(next_param_or(0) as usize).filter(|&bottom| bottom != 0);
+    let right = Some(next_param_or(0) as usize).filter(|&right| right != 0);
+
+    Rectangle { top, left, bottom, right }
+}
+
 #[inline]
 fn attrs_from_sgr_parameters(params: &mut ParamsIter<'_>) -> Vec<Option<Attr>> {
     let mut attrs = Vec::with_capacity(params.size_hint().0);
@@ -1316,13 +2027,16 @@ fn attrs_from_sgr_parameters(params: &mut ParamsIter<'_>) -> Vec<Option<Attr>> {
             [3] => Some(Attr::Italic),
             [4, 0] => Some(Attr::CancelUnderline),
             [4, 2] => Some(Attr::DoubleUnderline),
+            [4, 3] => Some(Attr::Undercurl),
+            [4, 4] => Some(Attr::DottedUnderline),
+            [4, 5] => Some(Attr::DashedUnderline),
             [4, ..] => Some(Attr::Underline),
             [5] => Some(Attr::BlinkSlow),
             [6] => Some(Attr::BlinkFast),
             [7] => Some(Attr::Reverse),
             [8] => Some(Attr::Hidden),
             [9] => Some(Attr::Strike),
-            [21] => Some(Attr::CancelBold),
+            [21] => Some(Attr::DoubleUnderline),
             [22] => Some(Attr::CancelBoldDim),
             [23] => Some(Attr::CancelItalic),
             [24] => Some(Attr::CancelUnderline),
@@ -1492,9 +2206,29 @@ mod tests {
         charset: StandardCharset,
         attr: Option<Attr>,
         identity_reported: bool,
+        title: Option<String>,
+        window_name: Option<String>,
+        unhandled_dcs: Option<(char, Vec<u8>)>,
+        unhandled_apc: Option<Vec<u8>>,
     }
 
     impl Handler for MockHandler {
+        fn set_title(&mut self, title: Option<String>) {
+            self.title = title;
+        }
+
+        fn set_window_name(&mut self, name: Option<String>) {
+            self.window_name = name;
+        }
+
+        fn unhandled_dcs(&mut self, _: &[u16], _: &[u8], action: char, data: &[u8]) {
+            self.unhandled_dcs = Some((action, data.to_vec()));
+        }
+
+        fn unhandled_apc(&mut self, data: &[u8]) {
+            self.unhandled_apc = Some(data.to_vec());
+        }
+
         fn terminal_attribute(&mut self, attr: Attr) {
             self.attr = Some(attr);
         }
@@ -1524,6 +2258,10 @@ mod tests {
                 charset: StandardCharset::Ascii,
                 attr: None,
                 identity_reported: false,
+                title: None,
+                window_name: None,
+                unhandled_dcs: None,
+                unhandled_apc: None,
             }
         }
     }
@@ -1542,6 +2280,81 @@ mod tests {
         assert_eq!(handler.attr, Some(Attr::Bold));
     }
 
+    #[test]
+    fn parse_double_underline_attribute() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in b"\x1b[21m" {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.attr, Some(Attr::DoubleUnderline));
+    }
+
+    #[test]
+    fn synchronized_update_mode() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in b"\x1b[?2026h\x1b[1m" {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert!(parser.sync_timeout().is_some());
+        assert_eq!(parser.sync_bytes_count(), 4);
+        assert_eq!(handler.attr, None);
+
+        for byte in SYNC_MODE_END {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert!(parser.sync_timeout().is_none());
+        assert_eq!(parser.sync_bytes_count(), 0);
+        assert_eq!(handler.attr, Some(Attr::Bold));
+    }
+
+    #[test]
+    fn tmux_passthrough() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in b"\x1bPtmux;\x1b\x1b]2;hi\x07\x1b\\\x1b[1m" {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.title, Some(String::from("hi")));
+        assert_eq!(handler.attr, Some(Attr::Bold));
+        assert_eq!(handler.unhandled_dcs, None);
+    }
+
+    #[test]
+    fn unhandled_dcs_and_apc() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in b"\x1bP1;2zdata\x1b\\\x1b_Xpayload\x1b\\" {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.unhandled_dcs, Some(('z', b"data".to_vec())));
+        assert_eq!(handler.unhandled_apc, Some(b"Xpayload".to_vec()));
+    }
+
+    #[test]
+    fn dcs_with_passthrough_prefix() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        // DCS sequences sharing a prefix with tmux passthrough are still forwarded.
+        for byte in b"\x1bPtmuxdata\x1b\\\x1b[1m" {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.unhandled_dcs, Some(('t', b"muxdata".to_vec())));
+        assert_eq!(handler.attr, Some(Attr::Bold));
+    }
+
     #[test]
     fn parse_terminal_identity_csi() {
         let bytes: &[u8] = &[0x1b, b'[', b'1', b'c'];
@@ -1722,4 +2535,56 @@ mod tests {
     fn parse_number_too_large() {
         assert_eq!(parse_number(b"321"), None);
     }
+
+    #[test]
+    fn parse_hex_pairs() {
+        assert_eq!(hex_decode(b"546e"), Some(b"Tn".to_vec()));
+        assert_eq!(hex_decode(b"436F"), Some(b"Co".to_vec()));
+        assert_eq!(hex_decode(b""), Some(Vec::new()));
+        assert_eq!(hex_decode(b"436"), None);
+        assert_eq!(hex_decode(b"4x"), None);
+    }
+
+    #[test]
+    fn parse_window_name() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in b"\x1b]1337;WindowName= a;b \x07" {
+            parser.advance(&mut handler, *byte);
+        }
+        assert_eq!(handler.window_name, Some(String::from("a;b")));
+
+        for byte in b"\x1b]1337;Other=c\x07" {
+            parser.advance(&mut handler, *byte);
+        }
+        assert_eq!(handler.window_name, Some(String::from("a;b")));
+
+        for byte in b"\x1b]1337;WindowName=\x1b\\" {
+            parser.advance(&mut handler, *byte);
+        }
+        assert_eq!(handler.window_name, None);
+    }
+
+    #[test]
+    fn parse_file_urls() {
+        let url = parse_file_url(b"file://host/tmp/a%20b").unwrap();
+        assert_eq!(url, FileUrl { host: String::from("host"), path: PathBuf::from("/tmp/a b") });
+
+        let path = |url: &[u8]| parse_file_url(url).map(|url| url.path);
+        assert_eq!(path(b"file:///home/%C3%A4"), Some(PathBuf::from("/home/ä")));
+        assert_eq!(path(b"file://host"), None);
+        assert_eq!(path(b"file:///tmp/%2"), None);
+        assert_eq!(path(b"https://host/tmp"), None);
+    }
+
+    #[test]
+    fn local_file_urls() {
+        let is_local = |url: &[u8], hostname| parse_file_url(url).unwrap().is_local(hostname);
+        assert!(is_local(b"file:///tmp", None));
+        assert!(is_local(b"file://localhost/tmp", None));
+        assert!(is_local(b"file://HOST/tmp", Some("host")));
+        assert!(!is_local(b"file://remote/tmp", Some("host")));
+        assert!(!is_local(b"file://host/tmp", None));
+    }
 }
@@ -1,9 +1,13 @@
 use std::cmp::max;
 use std::collections::HashMap;
+use std::fmt::{self, Formatter};
 use std::path::PathBuf;
+use std::time::Duration;
 
-use serde::Deserialize;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer};
 
+use alacritty_config::{ConfigTemplate, Template};
 use alacritty_config_derive::ConfigDeserialize;
 
 mod scrolling;
@@ -20,23 +24,63 @@ pub type MockConfig = Config<HashMap<String, serde_yaml::Value>>;
 /// Top-level config type.
 #[derive(ConfigDeserialize, Debug, PartialEq, Default)]
 pub struct Config<T> {
-    /// TERM env variable.
+    /// Environment variables
+    ///
+    /// All entries are added to the environment of the shell and can override variables set by
+    /// Alacritty itself. If `TERM` is not present, Alacritty checks the local terminfo database
+    /// and uses `alacritty` if it is available, otherwise `xterm-256color` is used.
     pub env: HashMap<String, String>,
 
+    /// Selection
     pub selection: Selection,
 
-    /// Path to a shell program to run on startup.
-    pub shell: Option<Program>,
-
-    /// How much scrolling history to keep.
+    /// Shell
+    ///
+    /// You can set `shell.program` to the path of your favorite shell, e.g. `/bin/fish`. Entries
+    /// in `shell.args` are passed unmodified as arguments to the shell.
+    ///
+    /// Setting `shell.login` to `true` starts the shell as login shell by prefixing its name with
+    /// a dash, `false` never does so. With `auto`, only the default shell on macOS is started as
+    /// login shell. Without `shell.program`, this applies to the default shell. This option has no
+    /// effect on Windows.
+    ///
+    /// Default:
+    ///   - (macOS) user login shell, started as login shell
+    ///   - (Linux/BSD) user login shell
+    ///   - (Windows) powershell
+    pub shell: Shell,
+
+    /// Scrolling
     pub scrolling: Scrolling,
 
-    /// Cursor configuration.
+    /// Cursor
     pub cursor: Cursor,
 
-    /// Shell startup directory.
+    /// Clipboard access through OSC 52 escape sequences
+    ///
+    /// Values for `osc52`:
+    ///   - Disabled: OSC 52 escapes are ignored
+    ///   - OnlyCopy: Applications can only write to the clipboard
+    ///   - OnlyPaste: Applications can only read from the clipboard
+    ///   - CopyPaste: Applications can write to and read from the clipboard
+    ///
+    /// Allowing applications to read the clipboard exposes its contents to every program running
+    /// in the terminal, including ones on remote hosts.
+    pub osc52: Osc52,
+
+    /// Startup directory
+    ///
+    /// Directory the shell is started in. If this is unset, or `None`, the working directory of
+    /// the parent process will be used.
     pub working_directory: Option<PathBuf>,
 
+    /// Shutdown (Linux/BSD/macOS only)
+    ///
+    /// When the terminal is closed, the shell and its foreground job are hung up using `SIGHUP`.
+    /// Shells which are still running after `kill_timeout` milliseconds are killed, `0` never
+    /// kills them.
+    pub shutdown: Shutdown,
+
     /// Additional configuration options not directly required by the terminal.
     #[config(flatten)]
     pub ui_config: T,
@@ -48,8 +92,18 @@ pub struct Config<T> {
 
 #[derive(ConfigDeserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Selection {
+    /// Characters used as separators for "semantic words" in Alacritty.
     pub semantic_escape_chars: String,
+
+    /// When set to `true`, selected text will be copied to the primary clipboard.
     pub save_to_clipboard: bool,
+
+    /// Maximum size of text stored in the clipboard in MiB
+    ///
+    /// Copying text which exceeds this limit, including through OSC 52, is refused with a warning.
+    /// The size of selections is estimated as one byte per selected cell. Setting this to `0`
+    /// disables the limit.
+    pub clipboard_size_limit: usize,
 }
 
 impl Default for Selection {
@@ -57,18 +111,113 @@ impl Default for Selection {
         Self {
             semantic_escape_chars: String::from(",│`|:\"' ()[]{}<>\t"),
             save_to_clipboard: Default::default(),
+            clipboard_size_limit: 64,
+        }
+    }
+}
+
+/// Clipboard operations allowed through OSC 52 escapes.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Osc52 {
+    /// Ignore all OSC 52 escapes.
+    Disabled,
+
+    /// Only allow writing to the clipboard.
+    OnlyCopy,
+
+    /// Only allow reading from the clipboard.
+    OnlyPaste,
+
+    /// Allow writing to and reading from the clipboard.
+    CopyPaste,
+}
+
+impl Default for Osc52 {
+    fn default() -> Self {
+        Osc52::OnlyCopy
+    }
+}
+
+impl Osc52 {
+    /// Check if applications may write to the clipboard.
+    pub fn allows_copy(self) -> bool {
+        self == Osc52::OnlyCopy || self == Osc52::CopyPaste
+    }
+
+    /// Check if applications may read from the clipboard.
+    pub fn allows_paste(self) -> bool {
+        self == Osc52::OnlyPaste || self == Osc52::CopyPaste
+    }
+}
+
+/// Termination of the shell when the terminal is closed.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Shutdown {
+    /// Milliseconds between hanging up the shell and killing it, `0` never kills it.
+    kill_timeout: u16,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self { kill_timeout: 2000 }
+    }
+}
+
+impl Shutdown {
+    /// Grace period for the shell to exit after the hangup, `None` if it is never killed.
+    #[inline]
+    pub fn kill_timeout(self) -> Option<Duration> {
+        if self.kill_timeout == 0 {
+            None
+        } else {
+            Some(Duration::from_millis(u64::from(self.kill_timeout)))
         }
     }
 }
 
 #[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq)]
 pub struct Cursor {
+    /// Cursor style
+    ///
+    /// Either a cursor shape, or a `shape` and `blinking` state like
+    /// `{ shape: Beam, blinking: On }`.
+    ///
+    /// Values for `shape`:
+    ///   - ▇ Block
+    ///   - _ Underline
+    ///   - | Beam
+    ///
+    /// Values for `blinking`:
+    ///   - Never: Prevent the cursor from ever blinking
+    ///   - Off: Disable blinking by default
+    ///   - On: Enable blinking by default
+    ///   - Always: Force the cursor to always blink
     pub style: ConfigCursorStyle,
+
+    /// Vi mode cursor style
+    ///
+    /// If the vi mode cursor style is `None` or not specified, it will fall back to the style of
+    /// the active value of the normal cursor.
+    ///
+    /// See `cursor.style` for available options.
     pub vi_mode_style: Option<ConfigCursorStyle>,
+
+    /// If this is `true`, the cursor will be rendered as a hollow box when the window is not
+    /// focused.
     pub unfocused_hollow: bool,
 
+    /// Thickness of the cursor relative to the cell width as floating point number from `0.0` to
+    /// `1.0`.
     thickness: Percentage,
+
+    /// Cursor blinking interval in milliseconds.
     blink_interval: u64,
+
+    /// Time after which the cursor stops blinking, in seconds.
+    ///
+    /// The timeout is restarted by any key press. Specifying `0` keeps the cursor blinking
+    /// forever, which prevents the terminal from idling.
+    blink_timeout: u8,
 }
 
 impl Default for Cursor {
@@ -77,6 +226,7 @@ impl Default for Cursor {
             thickness: Percentage(0.15),
             unfocused_hollow: true,
             blink_interval: 750,
+            blink_timeout: 5,
             style: Default::default(),
             vi_mode_style: Default::default(),
         }
@@ -103,6 +253,16 @@ impl Cursor {
     pub fn blink_interval(self) -> u64 {
         max(self.blink_interval, MIN_BLINK_INTERVAL)
     }
+
+    /// Time without input after which the cursor stops blinking, `None` to blink forever.
+    #[inline]
+    pub fn blink_timeout(self) -> Option<Duration> {
+        if self.blink_timeout == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(u64::from(self.blink_timeout)))
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
@@ -123,6 +283,18 @@ impl Default for ConfigCursorStyle {
     }
 }
 
+impl ConfigTemplate for ConfigCursorStyle {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        match self {
+            Self::Shape(shape) => shape.write_template(key, template),
+            Self::WithBlinking { shape, blinking } => template.section(key, |template| {
+                shape.write_template("shape", template);
+                blinking.write_template("blinking", template);
+            }),
+        }
+    }
+}
+
 impl ConfigCursorStyle {
     /// Check if blinking is force enabled/disabled.
     pub fn blinking_override(&self) -> Option<bool> {
@@ -174,6 +346,65 @@ impl From<CursorBlinking> for bool {
     }
 }
 
+/// Shell started inside the terminal.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Shell {
+    /// Shell program, the user's default shell is used if this is `None`.
+    pub program: Option<Program>,
+
+    /// Login shell invocation of the shell.
+    pub login: Login,
+}
+
+impl<'de> Deserialize<'de> for Shell {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ShellEntry {
+            Just(String),
+            WithOptions {
+                program: Option<String>,
+                #[serde(default)]
+                args: Vec<String>,
+                #[serde(default)]
+                login: Login,
+            },
+        }
+
+        let (program, args, login) = match ShellEntry::deserialize(deserializer)? {
+            ShellEntry::Just(program) => (Some(program), Vec::new(), Login::default()),
+            ShellEntry::WithOptions { program, args, login } => (program, args, login),
+        };
+
+        // Fall back to the default shell for `None`, like all other optional values.
+        let program = program.filter(|program| !program.eq_ignore_ascii_case("none"));
+
+        let program = match program {
+            Some(program) if args.is_empty() => Some(Program::Just(program)),
+            Some(program) => Some(Program::WithArgs { program, args }),
+            None if args.is_empty() => None,
+            None => return Err(de::Error::custom("shell.args requires shell.program")),
+        };
+
+        Ok(Self { program, login })
+    }
+}
+
+impl ConfigTemplate for Shell {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.section(key, |template| {
+            match &self.program {
+                Some(program) => program.write_fields(template),
+                None => template.value("program", "None"),
+            }
+            self.login.write_template("login", template);
+        });
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum Program {
@@ -201,6 +432,92 @@ impl Program {
     }
 }
 
+impl ConfigTemplate for Program {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        match self {
+            Program::Just(program) => program.write_template(key, template),
+            Program::WithArgs { .. } => {
+                template.section(key, |template| self.write_fields(template))
+            },
+        }
+    }
+
+    fn write_fields(&self, template: &mut Template) {
+        match self {
+            Program::Just(program) => program.write_template("program", template),
+            Program::WithArgs { program, args } => {
+                program.write_template("program", template);
+                args.write_template("args", template);
+            },
+        }
+    }
+}
+
+/// Login shell invocation of the shell program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Login {
+    /// Start the default shell as login shell only on platforms which expect it.
+    Auto,
+
+    /// Always start the shell as login shell.
+    Always,
+
+    /// Never start the shell as login shell.
+    Never,
+}
+
+impl Default for Login {
+    fn default() -> Self {
+        Login::Auto
+    }
+}
+
+impl<'de> Deserialize<'de> for Login {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LoginVisitor;
+        impl<'a> Visitor<'a> for LoginVisitor {
+            type Value = Login;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("true, false or auto")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(if value { Login::Always } else { Login::Never })
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if value.eq_ignore_ascii_case("auto") {
+                    Ok(Login::Auto)
+                } else {
+                    Err(E::invalid_value(de::Unexpected::Str(value), &self))
+                }
+            }
+        }
+
+        deserializer.deserialize_any(LoginVisitor)
+    }
+}
+
+impl ConfigTemplate for Login {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        match self {
+            Login::Auto => template.value(key, "auto"),
+            Login::Always => template.value(key, true),
+            Login::Never => template.value(key, false),
+        }
+    }
+}
+
 /// Wrapper around f32 that represents a percentage value between 0.0 and 1.0.
 #[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
 pub struct Percentage(f32);
@@ -226,3 +543,40 @@ impl Percentage {
         self.0
     }
 }
+
+impl ConfigTemplate for Percentage {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        self.0.write_template(key, template);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_shell_login() {
+        let shell: Shell = serde_yaml::from_str("program: zsh\nlogin: true").unwrap();
+        assert_eq!(shell.program, Some(Program::Just(String::from("zsh"))));
+        assert_eq!(shell.login, Login::Always);
+
+        let shell: Shell = serde_yaml::from_str("program: zsh\nlogin: Auto").unwrap();
+        assert_eq!(shell.login, Login::Auto);
+
+        let shell: Shell = serde_yaml::from_str("zsh").unwrap();
+        assert_eq!(shell.login, Login::Auto);
+
+        assert!(serde_yaml::from_str::<Shell>("program: zsh\nlogin: maybe").is_err());
+    }
+
+    #[test]
+    fn deserialize_default_shell_login() {
+        let shell: Shell = serde_yaml::from_str("login: true").unwrap();
+        assert_eq!(shell, Shell { program: None, login: Login::Always });
+
+        let shell: Shell = serde_yaml::from_str("program: None\nlogin: false").unwrap();
+        assert_eq!(shell, Shell { program: None, login: Login::Never });
+
+        assert!(serde_yaml::from_str::<Shell>("args: [-l]").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use serde::de::Error as SerdeError;
 use serde::{Deserialize, Deserializer};
 
+use alacritty_config::{ConfigTemplate, Template};
 use alacritty_config_derive::ConfigDeserialize;
 
 /// Maximum scrollback amount configurable.
@@ -9,14 +10,24 @@ const MAX_SCROLLBACK_LINES: u32 = 100_000;
 /// Struct for scrolling related settings.
 #[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Scrolling {
+    /// Scrolling distance multiplier.
     pub multiplier: u8,
 
+    /// Save lines scrolled out of scrolling regions in the scrollback buffer, even if the region
+    /// does not cover the entire screen.
+    ///
+    /// When this is disabled, only lines scrolled off the top of the entire screen are saved, so
+    /// applications which keep a status line at the bottom do not fill the scrollback buffer with
+    /// partial screens.
+    pub region_history: bool,
+
+    /// Maximum number of lines in the scrollback buffer, `0` disables scrolling.
     history: ScrollingHistory,
 }
 
 impl Default for Scrolling {
     fn default() -> Self {
-        Self { multiplier: 3, history: Default::default() }
+        Self { multiplier: 3, region_history: true, history: Default::default() }
     }
 }
 
@@ -40,6 +51,12 @@ impl Default for ScrollingHistory {
     }
 }
 
+impl ConfigTemplate for ScrollingHistory {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        self.0.write_template(key, template);
+    }
+}
+
 impl<'de> Deserialize<'de> for ScrollingHistory {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use crate::term::color::Rgb;
 use crate::term::{ClipboardType, SizeInfo};
+use crate::tty::ExitStatus;
 
 /// Terminal event.
 ///
@@ -20,6 +21,12 @@ pub enum Event {
     /// Reset to the default window title.
     ResetTitle,
 
+    /// Icon name change.
+    IconName(String),
+
+    /// Reset to the default icon name.
+    ResetIconName,
+
     /// Request to store a text string in the clipboard.
     ClipboardStore(ClipboardType, String),
 
@@ -35,6 +42,13 @@ pub enum Event {
     /// expected escape sequence format.
     ColorRequest(usize, Arc<dyn Fn(Rgb) -> String + Sync + Send + 'static>),
 
+    /// Request to write the value of a terminfo capability to the PTY.
+    ///
+    /// The attached function is a formatter which will transform the value of the capability into
+    /// the expected escape sequence format. Boolean capabilities are passed as empty strings,
+    /// unknown capabilities as `None`.
+    CapabilityRequest(String, Arc<dyn Fn(Option<&str>) -> String + Sync + Send + 'static>),
+
     /// Write some text to the PTY.
     PtyWrite(String),
 
@@ -47,8 +61,32 @@ pub enum Event {
     /// Terminal bell ring.
     Bell,
 
+    /// Alternate screen buffer has been entered (`true`) or left (`false`).
+    AlternateScreen(bool),
+
+    /// Request to resize the window to a number of columns, used for 80/132 column mode.
+    ColumnMode(usize),
+
     /// Shutdown request.
     Exit,
+
+    /// Child process exited.
+    ChildExit(ExitStatus),
+
+    /// Escape sequence which is not interpreted by the terminal.
+    UnhandledSequence(UnhandledSequence),
+}
+
+/// Escape sequence which is not interpreted by the terminal.
+///
+/// These are forwarded to the event listener, so embedders can implement additional protocols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnhandledSequence {
+    /// Device Control String (`DCS`).
+    Dcs { params: Vec<u16>, intermediates: Vec<u8>, action: char, data: Vec<u8> },
+
+    /// Application Program Command (`APC`).
+    Apc(Vec<u8>),
 }
 
 impl Debug for Event {
@@ -57,14 +95,21 @@ impl Debug for Event {
             Event::MouseCursorDirty => write!(f, "MouseCursorDirty"),
             Event::Title(title) => write!(f, "Title({})", title),
             Event::ResetTitle => write!(f, "ResetTitle"),
+            Event::IconName(name) => write!(f, "IconName({})", name),
+            Event::ResetIconName => write!(f, "ResetIconName"),
             Event::ClipboardStore(ty, text) => write!(f, "ClipboardStore({:?}, {})", ty, text),
             Event::ClipboardLoad(ty, _) => write!(f, "ClipboardLoad({:?})", ty),
             Event::ColorRequest(index, _) => write!(f, "ColorRequest({})", index),
+            Event::CapabilityRequest(name, _) => write!(f, "CapabilityRequest({})", name),
             Event::PtyWrite(text) => write!(f, "PtyWrite({})", text),
             Event::Wakeup => write!(f, "Wakeup"),
             Event::Bell => write!(f, "Bell"),
+            Event::AlternateScreen(active) => write!(f, "AlternateScreen({})", active),
+            Event::ColumnMode(columns) => write!(f, "ColumnMode({})", columns),
             Event::Exit => write!(f, "Exit"),
+            Event::ChildExit(status) => write!(f, "ChildExit({:?})", status),
             Event::CursorBlinkingChange(blinking) => write!(f, "CursorBlinking({})", blinking),
+            Event::UnhandledSequence(sequence) => write!(f, "UnhandledSequence({:?})", sequence),
         }
     }
 }
@@ -75,6 +120,9 @@ pub trait Notify {
     ///
     /// TODO this needs to be able to error somehow.
     fn notify<B: Into<Cow<'static, [u8]>>>(&self, _: B);
+
+    /// Stop or resume reading from the PTY, like XOFF and XON.
+    fn set_output_paused(&self, _paused: bool) {}
 }
 
 /// Types that are interested in when the display is resized.
@@ -87,6 +135,5 @@ pub trait EventListener {
     fn send_event(&self, _event: Event) {}
 }
 
-/// Placeholder implementation for tests.
-#[cfg(test)]
+/// Event listener ignoring all events, for tests.
 impl EventListener for () {}
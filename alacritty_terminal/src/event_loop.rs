@@ -5,6 +5,7 @@ use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{self, ErrorKind, Read, Write};
 use std::marker::Send;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread::JoinHandle;
 use std::time::Instant;
@@ -25,6 +26,15 @@ use crate::tty;
 /// Max bytes to read from the PTY.
 const MAX_READ: usize = u16::max_value() as usize;
 
+/// Number of queued bytes after which bulk input like pastes is held back.
+///
+/// Input received by the event loop is always queued, so keystrokes still reach applications
+/// which are slow to read their input.
+const MAX_WRITE_QUEUE_SIZE: usize = 1024 * 1024;
+
+/// Max size of a write after merging small consecutive inputs.
+const MAX_COALESCED_WRITE: usize = 4096;
+
 /// Messages that may be sent to the `EventLoop`.
 #[derive(Debug)]
 pub enum Msg {
@@ -36,6 +46,9 @@ pub enum Msg {
 
     /// Instruction to resize the PTY.
     Resize(SizeInfo),
+
+    /// Stop or resume reading from the PTY.
+    Pause(bool),
 }
 
 /// The main event!.. loop.
@@ -51,6 +64,7 @@ pub struct EventLoop<T: tty::EventedPty, U: EventListener> {
     event_proxy: U,
     hold: bool,
     ref_test: bool,
+    write_queue: WriteQueue,
 }
 
 /// Helper type which tracks how much of a buffer has been written.
@@ -74,6 +88,10 @@ impl event::Notify for Notifier {
 
         self.0.send(Msg::Input(bytes)).expect("send event loop msg");
     }
+
+    fn set_output_paused(&self, paused: bool) {
+        self.0.send(Msg::Pause(paused)).expect("send event loop msg");
+    }
 }
 
 impl event::OnResize for Notifier {
@@ -82,6 +100,25 @@ impl event::OnResize for Notifier {
     }
 }
 
+/// Number of bytes waiting to be written to the PTY, shared with its event loop.
+#[derive(Debug, Default, Clone)]
+pub struct WriteQueue(Arc<AtomicUsize>);
+
+impl WriteQueue {
+    /// Number of bytes of bulk input which can be queued before the write queue is full.
+    pub fn available(&self) -> usize {
+        MAX_WRITE_QUEUE_SIZE.saturating_sub(self.0.load(Ordering::Relaxed))
+    }
+
+    fn push(&self, len: usize) {
+        self.0.fetch_add(len, Ordering::Relaxed);
+    }
+
+    fn pop(&self, len: usize) {
+        self.0.fetch_sub(len, Ordering::Relaxed);
+    }
+}
+
 /// All of the mutable state needed to run the event loop.
 ///
 /// Contains list of items to write, current write state, etc. Anything that
@@ -91,9 +128,20 @@ pub struct State {
     write_list: VecDeque<Cow<'static, [u8]>>,
     writing: Option<Writing>,
     parser: ansi::Processor,
+    paused: bool,
 }
 
 impl State {
+    /// Add input to the write queue, merging it with the last queued input when both are small.
+    fn queue_write(&mut self, input: Cow<'static, [u8]>) {
+        match self.write_list.back_mut() {
+            Some(last) if last.len() + input.len() <= MAX_COALESCED_WRITE => {
+                last.to_mut().extend_from_slice(&input)
+            },
+            _ => self.write_list.push_back(input),
+        }
+    }
+
     #[inline]
     fn ensure_next(&mut self) {
         if self.writing.is_none() {
@@ -167,6 +215,7 @@ where
             event_proxy,
             hold,
             ref_test,
+            write_queue: Default::default(),
         }
     }
 
@@ -174,15 +223,24 @@ where
         self.tx.clone()
     }
 
+    /// Size of the input waiting to be written to the PTY.
+    pub fn write_queue(&self) -> WriteQueue {
+        self.write_queue.clone()
+    }
+
     /// Drain the channel.
     ///
     /// Returns `false` when a shutdown message was received.
     fn drain_recv_channel(&mut self, state: &mut State) -> bool {
         while let Ok(msg) = self.rx.try_recv() {
             match msg {
-                Msg::Input(input) => state.write_list.push_back(input),
+                Msg::Input(input) => {
+                    self.write_queue.push(input.len());
+                    state.queue_write(input);
+                },
                 Msg::Shutdown => return false,
                 Msg::Resize(size) => self.pty.on_resize(&size),
+                Msg::Pause(paused) => state.paused = paused,
             }
         }
 
@@ -277,6 +335,7 @@ where
                         break 'write_many;
                     },
                     Ok(n) => {
+                        self.write_queue.pop(n);
                         current.advance(n);
                         if current.finished() {
                             state.goto_next();
@@ -320,10 +379,14 @@ where
                 None
             };
 
+            // Time at which waiting for the child to exit after shutdown is stopped.
+            let mut shutdown_deadline = None;
+
             'event_loop: loop {
-                // Wakeup the event loop when a synchronized update timeout was reached.
-                let sync_timeout = state.parser.sync_timeout();
-                let timeout = sync_timeout.map(|st| st.saturating_duration_since(Instant::now()));
+                // Wakeup the event loop when a synchronized update or shutdown timeout was reached.
+                let sync_timeout = state.parser.sync_timeout().copied();
+                let deadline = sync_timeout.into_iter().chain(shutdown_deadline).min();
+                let timeout = deadline.map(|dl| dl.saturating_duration_since(Instant::now()));
 
                 if let Err(err) = self.poll.poll(&mut events, timeout) {
                     match err.kind() {
@@ -332,6 +395,11 @@ where
                     }
                 }
 
+                // Stop waiting for the child once the shutdown timeout was reached.
+                if shutdown_deadline.map_or(false, |deadline| deadline <= Instant::now()) {
+                    break 'event_loop;
+                }
+
                 // Handle synchronized update timeout.
                 if events.is_empty() {
                     state.parser.stop_sync(&mut *self.terminal.lock());
@@ -343,12 +411,24 @@ where
                     match event.token() {
                         token if token == channel_token => {
                             if !self.channel_event(channel_token, &mut state) {
-                                break 'event_loop;
+                                // Keep processing events until the child exited after hangup.
+                                match self.pty.hang_up() {
+                                    Some(timeout) => {
+                                        shutdown_deadline = Some(Instant::now() + timeout);
+                                    },
+                                    None => break 'event_loop,
+                                }
                             }
                         },
 
                         token if token == self.pty.child_event_token() => {
-                            if let Some(tty::ChildEvent::Exited) = self.pty.next_child_event() {
+                            if let Some(tty::ChildEvent::Exited(status)) =
+                                self.pty.next_child_event()
+                            {
+                                if let Some(status) = status {
+                                    self.event_proxy.send_event(Event::ChildExit(status));
+                                }
+
                                 if self.hold {
                                     // With hold enabled, make sure the PTY is drained.
                                     let _ = self.pty_read(&mut state, &mut buf, pipe.as_mut());
@@ -372,7 +452,7 @@ where
                                 continue;
                             }
 
-                            if event.readiness().is_readable() {
+                            if event.readiness().is_readable() && !state.paused {
                                 if let Err(err) = self.pty_read(&mut state, &mut buf, pipe.as_mut())
                                 {
                                     // On Linux, a `read` on the master side of a PTY can fail
@@ -401,8 +481,8 @@ where
                     }
                 }
 
-                // Register write interest if necessary.
-                let mut interest = Ready::readable();
+                // Register write interest if necessary, while only reading when not paused.
+                let mut interest = if state.paused { Ready::empty() } else { Ready::readable() };
                 if state.needs_write() {
                     interest.insert(Ready::writable());
                 }
@@ -418,3 +498,34 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coalesce_small_writes() {
+        let mut state = State::default();
+        state.queue_write(Cow::Borrowed(b"\x1bOA"));
+        state.queue_write(Cow::Owned(b"\x1bOA".to_vec()));
+        state.queue_write(Cow::Owned(vec![b'a'; MAX_COALESCED_WRITE]));
+        state.queue_write(Cow::Borrowed(b"b"));
+
+        let lengths: Vec<_> = state.write_list.iter().map(|input| input.len()).collect();
+        assert_eq!(lengths, [6, MAX_COALESCED_WRITE, 1]);
+        assert_eq!(&state.write_list[0][..], b"\x1bOA\x1bOA");
+    }
+    #[test]
+    fn write_queue_available() {
+        let write_queue = WriteQueue::default();
+        write_queue.push(MAX_WRITE_QUEUE_SIZE - 3);
+        assert_eq!(write_queue.available(), 3);
+
+        // Keystrokes are queued beyond the limit.
+        write_queue.push(5);
+        assert_eq!(write_queue.available(), 0);
+
+        write_queue.pop(MAX_WRITE_QUEUE_SIZE + 2);
+        assert_eq!(write_queue.available(), MAX_WRITE_QUEUE_SIZE);
+    }
+}
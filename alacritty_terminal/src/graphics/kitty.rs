@@ -0,0 +1,656 @@
+//! Kitty graphics protocol.
+//!
+//! Commands are sent in APC sequences starting with `G`, followed by comma separated key value
+//! pairs and the base64 encoded payload, like `ESC _ G a=T,f=100;<payload> ESC \`.
+//!
+//! See <https://sw.kovidgoyal.net/kitty/graphics-protocol/> for the full protocol.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::str;
+
+use crate::graphics::{GraphicData, GraphicId, MAX_GRAPHIC_DIMENSIONS};
+
+/// Maximum size of a payload, including all of its chunks.
+const MAX_PAYLOAD_SIZE: usize = MAX_GRAPHIC_DIMENSIONS.0 * MAX_GRAPHIC_DIMENSIONS.1 * 4;
+
+/// Maximum size of all stored images, in bytes.
+const MAX_STORED_BYTES: usize = 320 * 1024 * 1024;
+
+/// Maximum number of placements remembered per image.
+///
+/// Once exceeded, the oldest placements can no longer be deleted by their image's ID.
+const MAX_PLACEMENTS: usize = 1024;
+
+/// Operation requested by a command.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Store an image without displaying it.
+    Transmit,
+
+    /// Store an image and display it at the cursor position.
+    TransmitAndDisplay,
+
+    /// Display a previously stored image at the cursor position.
+    Display,
+
+    /// Remove images from the screen.
+    Delete,
+
+    /// Check if an image could be displayed, without storing it.
+    Query,
+}
+
+impl Default for Action {
+    fn default() -> Self {
+        Action::Transmit
+    }
+}
+
+/// Pixel format of the payload.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// 24 bit RGB pixels.
+    Rgb,
+
+    /// 32 bit RGBA pixels.
+    Rgba,
+
+    /// PNG image.
+    Png,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Rgba
+    }
+}
+
+/// Errors reported to the client.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Malformed control data.
+    InvalidCommand,
+
+    /// Payload could not be decoded, or does not match the image size.
+    InvalidData,
+
+    /// No image with the requested ID is stored.
+    NotFound,
+
+    /// Image exceeds the maximum dimensions.
+    TooLarge,
+
+    /// Transmission medium other than direct transmission.
+    Unsupported,
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidCommand => write!(f, "EINVAL:malformed command"),
+            Error::InvalidData => write!(f, "EINVAL:invalid image data"),
+            Error::NotFound => write!(f, "ENOENT:image not found"),
+            Error::TooLarge => write!(f, "EFBIG:image too large"),
+            Error::Unsupported => write!(f, "ENOTSUPPORTED:transmission medium not supported"),
+        }
+    }
+}
+
+/// Graphics command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Command {
+    /// Requested operation (`a`).
+    pub action: Action,
+
+    /// Pixel format of the payload (`f`).
+    pub format: Format,
+
+    /// Transmission medium (`t`).
+    pub medium: u8,
+
+    /// Payload is compressed with zlib (`o`).
+    pub compressed: bool,
+
+    /// Width of RGB and RGBA images, in pixels (`s`).
+    pub width: u32,
+
+    /// Height of RGB and RGBA images, in pixels (`v`).
+    pub height: u32,
+
+    /// Image ID chosen by the client (`i`).
+    pub image_id: u32,
+
+    /// Image number, asking the terminal to choose an ID (`I`).
+    pub image_number: u32,
+
+    /// Placement ID (`p`).
+    pub placement_id: u32,
+
+    /// More chunks of the payload follow (`m`).
+    pub more: bool,
+
+    /// Suppress OK responses with `1` and all responses with `2` (`q`).
+    pub quiet: u32,
+
+    /// Number of columns the image is scaled to (`c`).
+    pub columns: u32,
+
+    /// Number of lines the image is scaled to (`r`).
+    pub lines: u32,
+
+    /// Left edge of the displayed part of the image, in pixels (`x`).
+    pub source_x: u32,
+
+    /// Top edge of the displayed part of the image, in pixels (`y`).
+    pub source_y: u32,
+
+    /// Width of the displayed part of the image, in pixels (`w`).
+    pub source_width: u32,
+
+    /// Height of the displayed part of the image, in pixels (`h`).
+    pub source_height: u32,
+
+    /// Move the cursor after displaying the image (`C`).
+    pub move_cursor: bool,
+
+    /// Images affected by a delete command (`d`).
+    pub delete: u8,
+
+    /// Decoded payload.
+    pub payload: Vec<u8>,
+
+    /// Command only contains the keys allowed after the first chunk of a payload (`m` and `q`).
+    pub continuation: bool,
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Self {
+            action: Default::default(),
+            format: Default::default(),
+            medium: b'd',
+            compressed: false,
+            width: 0,
+            height: 0,
+            image_id: 0,
+            image_number: 0,
+            placement_id: 0,
+            more: false,
+            quiet: 0,
+            columns: 0,
+            lines: 0,
+            source_x: 0,
+            source_y: 0,
+            source_width: 0,
+            source_height: 0,
+            move_cursor: true,
+            delete: b'a',
+            payload: Vec::new(),
+            continuation: true,
+        }
+    }
+}
+
+impl Command {
+    /// Parse the content of an APC sequence, excluding the leading `G`.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (control, payload) = match data.iter().position(|byte| *byte == b';') {
+            Some(index) => (&data[..index], &data[index + 1..]),
+            None => (data, &[][..]),
+        };
+
+        let mut command = Command::default();
+        for pair in control.split(|byte| *byte == b',').filter(|pair| !pair.is_empty()) {
+            let (key, value) = match pair {
+                [key, b'=', value @ ..] if !value.is_empty() => (*key, value),
+                _ => return Err(Error::InvalidCommand),
+            };
+
+            if key != b'm' && key != b'q' {
+                command.continuation = false;
+            }
+
+            let number = || {
+                let value = str::from_utf8(value).map_err(|_| Error::InvalidCommand)?;
+                value.parse::<u32>().map_err(|_| Error::InvalidCommand)
+            };
+            let character = || match value {
+                [character] => Ok(*character),
+                _ => Err(Error::InvalidCommand),
+            };
+
+            match key {
+                b'a' => {
+                    command.action = match character()? {
+                        b't' => Action::Transmit,
+                        b'T' => Action::TransmitAndDisplay,
+                        b'p' => Action::Display,
+                        b'd' => Action::Delete,
+                        b'q' => Action::Query,
+                        _ => return Err(Error::InvalidCommand),
+                    }
+                },
+                b'f' => {
+                    command.format = match number()? {
+                        24 => Format::Rgb,
+                        32 => Format::Rgba,
+                        100 => Format::Png,
+                        _ => return Err(Error::InvalidCommand),
+                    }
+                },
+                b'o' if character()? == b'z' => command.compressed = true,
+                b'o' => return Err(Error::InvalidCommand),
+                b't' => command.medium = character()?,
+                b's' => command.width = number()?,
+                b'v' => command.height = number()?,
+                b'i' => command.image_id = number()?,
+                b'I' => command.image_number = number()?,
+                b'p' => command.placement_id = number()?,
+                b'm' => command.more = number()? == 1,
+                b'q' => command.quiet = number()?,
+                b'c' => command.columns = number()?,
+                b'r' => command.lines = number()?,
+                b'x' => command.source_x = number()?,
+                b'y' => command.source_y = number()?,
+                b'w' => command.source_width = number()?,
+                b'h' => command.source_height = number()?,
+                b'C' => command.move_cursor = number()? == 0,
+                b'd' => command.delete = character()?,
+                // Ignore unsupported keys, like the z-index and the offsets within cells.
+                _ => (),
+            }
+        }
+
+        command.payload = base64::decode(payload).map_err(|_| Error::InvalidData)?;
+
+        Ok(command)
+    }
+
+    /// Decode the transmitted image.
+    pub fn decode(&self) -> Result<GraphicData, Error> {
+        if self.medium != b'd' {
+            return Err(Error::Unsupported);
+        }
+
+        let decompressed;
+        let data = if self.compressed {
+            decompressed = miniz_oxide::inflate::decompress_to_vec_zlib(&self.payload)
+                .map_err(|_| Error::InvalidData)?;
+            &decompressed
+        } else {
+            &self.payload
+        };
+
+        let (width, height, pixels) = match self.format {
+            Format::Png => decode_png(data)?,
+            Format::Rgb | Format::Rgba => {
+                let (width, height) = (self.width as usize, self.height as usize);
+                check_dimensions(width, height)?;
+
+                let bytes_per_pixel = if self.format == Format::Rgb { 3 } else { 4 };
+                if data.len() != width * height * bytes_per_pixel {
+                    return Err(Error::InvalidData);
+                }
+
+                (width, height, rgba(data, bytes_per_pixel))
+            },
+        };
+
+        Ok(GraphicData { width, height, pixels, ..Default::default() })
+    }
+
+    /// Get the part of an image shown by this command's placement.
+    ///
+    /// This crops the image to the source rectangle and scales it to the requested number of
+    /// columns and lines, keeping the aspect ratio if only one of them is specified.
+    pub fn placement(
+        &self,
+        graphic: &GraphicData,
+        cell_width: usize,
+        cell_height: usize,
+    ) -> Result<GraphicData, Error> {
+        let x = (self.source_x as usize).min(graphic.width);
+        let y = (self.source_y as usize).min(graphic.height);
+        let source_width = match self.source_width as usize {
+            0 => graphic.width - x,
+            width => width.min(graphic.width - x),
+        };
+        let source_height = match self.source_height as usize {
+            0 => graphic.height - y,
+            height => height.min(graphic.height - y),
+        };
+
+        if source_width == 0 || source_height == 0 {
+            return Err(Error::InvalidCommand);
+        }
+
+        let (columns, lines) = (self.columns as usize, self.lines as usize);
+        let (width, height) = match (columns * cell_width, lines * cell_height) {
+            (0, 0) => (source_width, source_height),
+            (width, 0) => (width, source_height * width / source_width),
+            (0, height) => (source_width * height / source_height, height),
+            size => size,
+        };
+        let width = width.max(1).min(MAX_GRAPHIC_DIMENSIONS.0);
+        let height = height.max(1).min(MAX_GRAPHIC_DIMENSIONS.1);
+
+        if (x, y, source_width, source_height) == (0, 0, width, height)
+            && (width, height) == (graphic.width, graphic.height)
+        {
+            return Ok(graphic.clone());
+        }
+
+        // Resample the image using the nearest source pixel.
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in 0..height {
+            let source_row = y + row * source_height / height;
+            for column in 0..width {
+                let source_column = x + column * source_width / width;
+                let start = (source_row * graphic.width + source_column) * 4;
+                pixels.extend_from_slice(&graphic.pixels[start..start + 4]);
+            }
+        }
+
+        Ok(GraphicData { width, height, pixels, ..Default::default() })
+    }
+
+    /// Response to the command, if the client expects one.
+    pub fn response(&self, result: Result<(), Error>) -> Option<String> {
+        // Without an ID, the client cannot associate the response with its command.
+        if self.action == Action::Delete || (self.image_id == 0 && self.image_number == 0) {
+            return None;
+        }
+
+        let message = match result {
+            Ok(()) if self.quiet == 0 => String::from("OK"),
+            Err(err) if self.quiet < 2 => err.to_string(),
+            _ => return None,
+        };
+
+        let mut keys = format!("i={}", self.image_id);
+        if self.image_number != 0 {
+            keys.push_str(&format!(",I={}", self.image_number));
+        }
+        if self.placement_id != 0 {
+            keys.push_str(&format!(",p={}", self.placement_id));
+        }
+
+        Some(format!("\x1b_G{};{}\x1b\\", keys, message))
+    }
+}
+
+/// Image stored by the terminal.
+#[derive(Debug)]
+struct StoredImage {
+    /// Decoded image.
+    graphic: GraphicData,
+
+    /// Graphics created for the placements of this image.
+    placements: Vec<GraphicId>,
+
+    /// Position in the storage order, used for evicting the oldest images.
+    sequence: u64,
+}
+
+/// Images transmitted by the client.
+#[derive(Debug, Default)]
+pub(crate) struct Storage {
+    /// Stored images by their ID.
+    images: HashMap<u32, StoredImage>,
+
+    /// Command waiting for the remaining chunks of its payload.
+    chunked: Option<Command>,
+
+    /// Last ID assigned to an image transmitted with an image number.
+    last_id: u32,
+
+    /// Last assigned position in the storage order.
+    sequence: u64,
+
+    /// Size of all stored images, in bytes.
+    stored_bytes: usize,
+}
+
+impl Storage {
+    /// Combine chunked commands.
+    ///
+    /// Returns the complete command once its last chunk has been received. Payloads exceeding
+    /// the size limit are truncated, which causes decoding to fail. A pending payload is dropped
+    /// when any other command is received before its last chunk.
+    pub fn assemble(&mut self, mut command: Command) -> Option<Command> {
+        let mut first = match self.chunked.take() {
+            Some(first) if command.continuation => first,
+            _ if command.more => {
+                self.chunked = Some(command);
+                return None;
+            },
+            _ => return Some(command),
+        };
+
+        if first.payload.len() + command.payload.len() <= MAX_PAYLOAD_SIZE {
+            first.payload.append(&mut command.payload);
+        }
+
+        if command.more {
+            self.chunked = Some(first);
+            None
+        } else {
+            Some(first)
+        }
+    }
+
+    /// Drop the payload waiting for its remaining chunks.
+    pub fn cancel_chunked(&mut self) {
+        self.chunked = None;
+    }
+
+    /// Choose an ID for an image transmitted with an image number.
+    pub fn next_id(&mut self) -> u32 {
+        loop {
+            self.last_id = self.last_id.wrapping_add(1).max(1);
+            if !self.images.contains_key(&self.last_id) {
+                return self.last_id;
+            }
+        }
+    }
+
+    /// Store an image, evicting the oldest images once the storage limit is reached.
+    pub fn insert(&mut self, id: u32, graphic: GraphicData) {
+        self.remove(id);
+
+        self.sequence += 1;
+        self.stored_bytes += graphic.pixels.len();
+        let image = StoredImage { graphic, placements: Vec::new(), sequence: self.sequence };
+        self.images.insert(id, image);
+
+        while self.stored_bytes > MAX_STORED_BYTES {
+            let oldest = self.images.iter().min_by_key(|(_, image)| image.sequence);
+            match oldest.map(|(id, _)| *id) {
+                Some(oldest) => self.remove(oldest),
+                None => break,
+            }
+        }
+    }
+
+    /// Remove a stored image.
+    pub fn remove(&mut self, id: u32) {
+        if let Some(image) = self.images.remove(&id) {
+            self.stored_bytes -= image.graphic.pixels.len();
+        }
+    }
+
+    /// Remove all stored images.
+    pub fn clear(&mut self) {
+        self.images.clear();
+        self.stored_bytes = 0;
+    }
+
+    /// Get a stored image.
+    pub fn image(&self, id: u32) -> Option<&GraphicData> {
+        self.images.get(&id).map(|image| &image.graphic)
+    }
+
+    /// Remember the graphic created for a placement of an image.
+    pub fn add_placement(&mut self, id: u32, graphic: GraphicId) {
+        if let Some(image) = self.images.get_mut(&id) {
+            if image.placements.len() >= MAX_PLACEMENTS {
+                image.placements.remove(0);
+            }
+            image.placements.push(graphic);
+        }
+    }
+
+    /// Graphics created for the placements of an image.
+    pub fn placements(&self, id: u32) -> Vec<GraphicId> {
+        self.images.get(&id).map(|image| image.placements.clone()).unwrap_or_default()
+    }
+
+    /// Graphics created for the placements of all images.
+    pub fn all_placements(&self) -> Vec<GraphicId> {
+        self.images.values().flat_map(|image| image.placements.iter().copied()).collect()
+    }
+}
+
+/// Make sure an image does not exceed the maximum dimensions.
+fn check_dimensions(width: usize, height: usize) -> Result<(), Error> {
+    let (max_width, max_height) = MAX_GRAPHIC_DIMENSIONS;
+    if width == 0 || height == 0 {
+        Err(Error::InvalidData)
+    } else if width > max_width || height > max_height {
+        Err(Error::TooLarge)
+    } else {
+        Ok(())
+    }
+}
+
+/// Convert pixels with 1 to 4 channels to RGBA.
+///
+/// Pixels with one or two channels are grayscale, with an optional alpha channel.
+fn rgba(data: &[u8], bytes_per_pixel: usize) -> Vec<u8> {
+    if bytes_per_pixel == 4 {
+        return data.to_vec();
+    }
+
+    let mut pixels = Vec::with_capacity(data.len() / bytes_per_pixel * 4);
+    for pixel in data.chunks_exact(bytes_per_pixel) {
+        match pixel {
+            [gray] => pixels.extend_from_slice(&[*gray, *gray, *gray, 255]),
+            [gray, alpha] => pixels.extend_from_slice(&[*gray, *gray, *gray, *alpha]),
+            [r, g, b] => pixels.extend_from_slice(&[*r, *g, *b, 255]),
+            _ => unreachable!(),
+        }
+    }
+    pixels
+}
+
+/// Decode a PNG image to RGBA pixels.
+fn decode_png(data: &[u8]) -> Result<(usize, usize, Vec<u8>), Error> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let (info, mut reader) = decoder.read_info().map_err(|_| Error::InvalidData)?;
+
+    let (width, height) = (info.width as usize, info.height as usize);
+    check_dimensions(width, height)?;
+
+    let mut buffer = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buffer).map_err(|_| Error::InvalidData)?;
+
+    let bytes_per_pixel = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::RGB => 3,
+        png::ColorType::RGBA => 4,
+        png::ColorType::Indexed => return Err(Error::InvalidData),
+    };
+
+    Ok((width, height, rgba(&buffer, bytes_per_pixel)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_command() {
+        let command = Command::parse(b"a=T,f=24,s=2,v=1,i=7,c=3,C=1;AAD/AAD/").unwrap();
+
+        assert_eq!(command.action, Action::TransmitAndDisplay);
+        assert_eq!(command.format, Format::Rgb);
+        assert_eq!((command.width, command.height, command.image_id), (2, 1, 7));
+        assert_eq!(command.columns, 3);
+        assert!(!command.move_cursor);
+        assert_eq!(command.payload, [0, 0, 255, 0, 0, 255]);
+
+        let graphic = command.decode().unwrap();
+        assert_eq!(graphic.pixels, [0, 0, 255, 255, 0, 0, 255, 255]);
+
+        assert_eq!(Command::parse(b"a=x"), Err(Error::InvalidCommand));
+        assert_eq!(Command::parse(b"f=32,s"), Err(Error::InvalidCommand));
+    }
+
+    #[test]
+    fn assemble_chunks() {
+        let mut storage = Storage::default();
+
+        let first = Command::parse(b"a=T,f=24,s=2,v=1,m=1;AAD/").unwrap();
+        assert_eq!(storage.assemble(first), None);
+        let last = Command::parse(b"m=0;AAD/").unwrap();
+        let command = storage.assemble(last).unwrap();
+
+        assert_eq!(command.action, Action::TransmitAndDisplay);
+        assert_eq!(command.payload, [0, 0, 255, 0, 0, 255]);
+
+        // A new command drops the incomplete payload.
+        let first = Command::parse(b"a=T,f=24,s=2,v=1,m=1;AAD/").unwrap();
+        assert_eq!(storage.assemble(first), None);
+        let query = Command::parse(b"a=q,i=1;").unwrap();
+        assert_eq!(storage.assemble(query.clone()), Some(query));
+        let last = Command::parse(b"m=0;AAD/").unwrap();
+        assert_eq!(storage.assemble(last).unwrap().payload, [0, 0, 255]);
+    }
+
+    #[test]
+    fn limit_placements() {
+        let mut storage = Storage::default();
+        storage.insert(1, GraphicData::default());
+
+        for id in 0..MAX_PLACEMENTS as u64 + 1 {
+            storage.add_placement(1, GraphicId(id));
+        }
+
+        let placements = storage.placements(1);
+        assert_eq!(placements.len(), MAX_PLACEMENTS);
+        assert_eq!(placements[0], GraphicId(1));
+    }
+
+    #[test]
+    fn scale_placement() {
+        let command = Command { columns: 2, source_x: 1, ..Command::default() };
+        let pixels = (0..16).collect::<Vec<u8>>();
+        let graphic = GraphicData { width: 4, height: 1, pixels, ..GraphicData::default() };
+
+        // Three source pixels are scaled to two cells, keeping the aspect ratio.
+        let placement = command.placement(&graphic, 3, 10).unwrap();
+        assert_eq!((placement.width, placement.height), (6, 2));
+        assert_eq!(&placement.pixels[..4], &[4, 5, 6, 7]);
+        assert_eq!(&placement.pixels[20..24], &[12, 13, 14, 15]);
+    }
+
+    #[test]
+    fn responses() {
+        let command = Command { image_id: 3, image_number: 4, ..Command::default() };
+        assert_eq!(command.response(Ok(())).unwrap(), "\x1b_Gi=3,I=4;OK\x1b\\");
+        let error = command.response(Err(Error::NotFound)).unwrap();
+        assert_eq!(error, "\x1b_Gi=3,I=4;ENOENT:image not found\x1b\\");
+
+        let quiet = Command { quiet: 1, ..command.clone() };
+        assert_eq!(quiet.response(Ok(())), None);
+        assert!(quiet.response(Err(Error::InvalidData)).is_some());
+
+        let anonymous = Command::default();
+        assert_eq!(anonymous.response(Err(Error::InvalidData)), None);
+    }
+}
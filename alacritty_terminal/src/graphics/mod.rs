@@ -0,0 +1,229 @@
+//! Inline graphics displayed in the terminal grid.
+//!
+//! Graphics are split into cell sized pieces, which are stored in the grid like any other cell
+//! content. The pixels are only kept until the renderer has picked them up, after which the cells
+//! just reference the texture by its [`GraphicId`].
+
+use std::mem;
+use std::sync::{Arc, Weak};
+
+use parking_lot::Mutex;
+
+pub mod kitty;
+pub mod sixel;
+
+/// Maximum width and height of a graphic, in pixels.
+pub const MAX_GRAPHIC_DIMENSIONS: (usize, usize) = (4096, 4096);
+
+/// Maximum size of the graphics waiting to be uploaded, in bytes.
+///
+/// Once exceeded, the oldest graphics are dropped without ever being shown.
+const MAX_PENDING_BYTES: usize = 256 * 1024 * 1024;
+
+/// Unique identifier of a graphic.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GraphicId(u64);
+
+/// Decoded graphic.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct GraphicData {
+    /// Graphic identifier, assigned by the terminal once the graphic is inserted.
+    pub id: GraphicId,
+
+    /// Width of the graphic, in pixels.
+    pub width: usize,
+
+    /// Height of the graphic, in pixels.
+    pub height: usize,
+
+    /// RGBA pixels, row by row.
+    pub pixels: Vec<u8>,
+}
+
+/// Reference to a graphic shared by all cells showing a part of it.
+///
+/// Once the last cell is gone, the graphic is queued for removal from the renderer.
+#[derive(Debug)]
+pub struct TextureRef {
+    /// Graphic identifier.
+    pub id: GraphicId,
+
+    /// Cell width when the graphic was inserted, in pixels.
+    pub cell_width: usize,
+
+    /// Cell height when the graphic was inserted, in pixels.
+    pub cell_height: usize,
+
+    /// Queue receiving the identifier once the graphic is unused.
+    remove_queue: Weak<Mutex<Vec<GraphicId>>>,
+}
+
+impl Drop for TextureRef {
+    fn drop(&mut self) {
+        if let Some(remove_queue) = self.remove_queue.upgrade() {
+            remove_queue.lock().push(self.id);
+        }
+    }
+}
+
+/// Part of a graphic shown in a single cell.
+#[derive(Clone, Debug)]
+pub struct GraphicCell {
+    /// Graphic shown in the cell.
+    pub texture: Arc<TextureRef>,
+
+    /// Horizontal position of the cell within the graphic, in pixels.
+    pub offset_x: u16,
+
+    /// Vertical position of the cell within the graphic, in pixels.
+    pub offset_y: u16,
+}
+
+impl PartialEq for GraphicCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.texture.id == other.texture.id
+            && self.offset_x == other.offset_x
+            && self.offset_y == other.offset_y
+    }
+}
+
+impl Eq for GraphicCell {}
+
+/// Changes to the graphics since the last update of the renderer.
+#[derive(Debug, Default)]
+pub struct UpdateQueues {
+    /// Graphics which need to be uploaded.
+    pub pending: Vec<GraphicData>,
+
+    /// Graphics which are no longer referenced by any cell.
+    pub remove_queue: Vec<GraphicId>,
+}
+
+/// Graphics tracked by the terminal.
+#[derive(Debug, Default)]
+pub(crate) struct Graphics {
+    /// Last assigned graphic identifier.
+    last_id: u64,
+
+    /// Graphics waiting to be uploaded.
+    pending: Vec<GraphicData>,
+
+    /// Graphics which are no longer referenced by any cell.
+    remove_queue: Arc<Mutex<Vec<GraphicId>>>,
+
+    /// Images transmitted with the kitty graphics protocol.
+    pub(crate) kitty: kitty::Storage,
+}
+
+impl Graphics {
+    /// Register a new graphic, returning the reference which is shared by all of its cells.
+    pub fn add(
+        &mut self,
+        mut graphic: GraphicData,
+        cell_width: usize,
+        cell_height: usize,
+    ) -> Arc<TextureRef> {
+        self.last_id += 1;
+        graphic.id = GraphicId(self.last_id);
+
+        let texture = TextureRef {
+            id: graphic.id,
+            cell_width,
+            cell_height,
+            remove_queue: Arc::downgrade(&self.remove_queue),
+        };
+
+        self.drop_superseded();
+        self.pending.push(graphic);
+
+        // Limit the memory used by graphics the renderer has not picked up yet.
+        let mut pending_bytes: usize =
+            self.pending.iter().map(|graphic| graphic.pixels.len()).sum();
+        while pending_bytes > MAX_PENDING_BYTES && self.pending.len() > 1 {
+            pending_bytes -= self.pending.remove(0).pixels.len();
+        }
+
+        Arc::new(texture)
+    }
+
+    /// Take all changes since the last call.
+    pub fn take_queues(&mut self) -> Option<UpdateQueues> {
+        self.drop_superseded();
+
+        let remove_queue = mem::take(&mut *self.remove_queue.lock());
+        if self.pending.is_empty() && remove_queue.is_empty() {
+            return None;
+        }
+
+        Some(UpdateQueues { pending: mem::take(&mut self.pending), remove_queue })
+    }
+
+    /// Drop pending graphics which are no longer referenced by any cell.
+    ///
+    /// Since these were never uploaded, the renderer does not need to remove them either.
+    fn drop_superseded(&mut self) {
+        let pending = &mut self.pending;
+        self.remove_queue.lock().retain(|id| {
+            match pending.iter().position(|graphic| graphic.id == *id) {
+                Some(index) => {
+                    pending.remove(index);
+                    false
+                },
+                None => true,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_unused_graphics() {
+        let mut graphics = Graphics::default();
+
+        let texture = graphics.add(GraphicData::default(), 10, 20);
+        let cell = GraphicCell { texture, offset_x: 0, offset_y: 0 };
+        let copy = cell.clone();
+
+        let queues = graphics.take_queues().unwrap();
+        assert_eq!(queues.pending.len(), 1);
+        assert!(queues.remove_queue.is_empty());
+
+        // Graphics are only removed once the last cell is gone.
+        drop(cell);
+        assert!(graphics.take_queues().is_none());
+        drop(copy);
+        let queues = graphics.take_queues().unwrap();
+        assert_eq!(queues.remove_queue, vec![GraphicId(1)]);
+    }
+
+    #[test]
+    fn drop_superseded_graphics() {
+        let mut graphics = Graphics::default();
+
+        // Graphics which are replaced before they were uploaded are never sent to the renderer.
+        drop(graphics.add(GraphicData::default(), 10, 20));
+        let texture = graphics.add(GraphicData::default(), 10, 20);
+
+        let queues = graphics.take_queues().unwrap();
+        let ids: Vec<_> = queues.pending.iter().map(|graphic| graphic.id).collect();
+        assert_eq!(ids, vec![texture.id]);
+        assert!(queues.remove_queue.is_empty());
+    }
+
+    #[test]
+    fn limit_pending_graphics() {
+        let mut graphics = Graphics::default();
+
+        let graphic = GraphicData { pixels: vec![0; MAX_PENDING_BYTES / 2], ..Default::default() };
+        let _first = graphics.add(graphic.clone(), 10, 20);
+        let second = graphics.add(graphic.clone(), 10, 20);
+        let third = graphics.add(graphic, 10, 20);
+
+        let queues = graphics.take_queues().unwrap();
+        let ids: Vec<_> = queues.pending.iter().map(|graphic| graphic.id).collect();
+        assert_eq!(ids, vec![second.id, third.id]);
+    }
+}
@@ -0,0 +1,330 @@
+//! Parser for sixel graphics.
+//!
+//! Sixel graphics are sent as the data of a `DCS q` sequence. Every data byte describes a column
+//! of six vertically stacked pixels, which are drawn using the active color of the palette.
+//!
+//! See <https://vt100.net/docs/vt3xx-gp/chapter14.html> for the full format.
+
+use crate::graphics::{GraphicData, MAX_GRAPHIC_DIMENSIONS};
+use crate::term::color::Rgb;
+
+/// Number of colors in the palette.
+const PALETTE_SIZE: usize = 1024;
+
+/// Maximum number of parameters of a sixel command.
+const MAX_PARAMS: usize = 5;
+
+/// Default palette of the VT340.
+const VT340_PALETTE: [Rgb; 16] = [
+    Rgb { r: 0, g: 0, b: 0 },
+    Rgb { r: 51, g: 51, b: 204 },
+    Rgb { r: 204, g: 33, b: 33 },
+    Rgb { r: 51, g: 204, b: 51 },
+    Rgb { r: 204, g: 51, b: 204 },
+    Rgb { r: 51, g: 204, b: 204 },
+    Rgb { r: 204, g: 204, b: 51 },
+    Rgb { r: 135, g: 135, b: 135 },
+    Rgb { r: 66, g: 66, b: 66 },
+    Rgb { r: 84, g: 84, b: 153 },
+    Rgb { r: 153, g: 66, b: 66 },
+    Rgb { r: 84, g: 153, b: 84 },
+    Rgb { r: 153, g: 84, b: 153 },
+    Rgb { r: 84, g: 153, b: 153 },
+    Rgb { r: 153, g: 153, b: 84 },
+    Rgb { r: 204, g: 204, b: 204 },
+];
+
+/// Incremental sixel parser.
+#[derive(Debug)]
+pub struct Parser {
+    /// Command whose parameters are being read.
+    command: Option<u8>,
+
+    /// Parameters of the current command.
+    params: Vec<u16>,
+
+    /// Color registers.
+    palette: Vec<Rgb>,
+
+    /// Active color register.
+    color: usize,
+
+    /// Leave pixels without sixel data transparent, instead of using the background color.
+    transparent: bool,
+
+    /// Horizontal position of the next sixel, in pixels.
+    x: usize,
+
+    /// Vertical position of the current sixel row, in pixels.
+    y: usize,
+
+    /// Width of the image, in pixels.
+    width: usize,
+
+    /// Height of the image, in pixels.
+    height: usize,
+
+    /// Number of allocated pixels per row.
+    stride: usize,
+
+    /// RGBA pixels, with unset pixels being fully transparent.
+    pixels: Vec<u8>,
+}
+
+impl Parser {
+    /// Create a parser for a new sixel image.
+    ///
+    /// The `background` is the second parameter of the `DCS q` sequence. A value of `1` leaves
+    /// unset pixels transparent, while all other values fill them with the first palette color.
+    pub fn new(background: u16) -> Self {
+        let mut palette = VT340_PALETTE.to_vec();
+        palette.resize(PALETTE_SIZE, Rgb::default());
+
+        Self {
+            command: None,
+            params: Vec::new(),
+            palette,
+            color: 0,
+            transparent: background == 1,
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            stride: 0,
+            pixels: Vec::new(),
+        }
+    }
+
+    /// Process a data byte of the sixel sequence.
+    pub fn put(&mut self, byte: u8) {
+        match (self.command, byte) {
+            (Some(_), b'0'..=b'9') => {
+                let param = self.params.last_mut().unwrap();
+                *param = param.saturating_mul(10).saturating_add(u16::from(byte - b'0'));
+                return;
+            },
+            (Some(_), b';') => {
+                if self.params.len() < MAX_PARAMS {
+                    self.params.push(0);
+                }
+                return;
+            },
+            // Repeat the sixel following the repeat introducer.
+            (Some(b'!'), b'?'..=b'~') => {
+                let count = usize::from(self.params[0].max(1));
+                self.command = None;
+                self.draw_sixel(byte - b'?', count);
+                return;
+            },
+            (Some(_), _) => self.dispatch_command(),
+            (None, _) => (),
+        }
+
+        match byte {
+            b'"' | b'#' | b'!' => {
+                self.command = Some(byte);
+                self.params.clear();
+                self.params.push(0);
+            },
+            b'$' => self.x = 0,
+            b'-' => {
+                self.x = 0;
+                self.y += 6;
+            },
+            b'?'..=b'~' => self.draw_sixel(byte - b'?', 1),
+            _ => (),
+        }
+    }
+
+    /// Complete the image.
+    ///
+    /// Returns `None` if the image is empty.
+    pub fn finish(mut self) -> Option<GraphicData> {
+        if self.command.is_some() {
+            self.dispatch_command();
+        }
+
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+
+        // Crop the image to its final size.
+        let mut pixels = Vec::with_capacity(self.width * self.height * 4);
+        for row in self.pixels.chunks(self.stride * 4).take(self.height) {
+            pixels.extend_from_slice(&row[..self.width * 4]);
+        }
+
+        // Fill unset pixels with the background color.
+        if !self.transparent {
+            let Rgb { r, g, b } = self.palette[0];
+            for pixel in pixels.chunks_mut(4).filter(|pixel| pixel[3] == 0) {
+                pixel.copy_from_slice(&[r, g, b, 255]);
+            }
+        }
+
+        Some(GraphicData { width: self.width, height: self.height, pixels, ..Default::default() })
+    }
+
+    /// Execute the command whose parameters have been read.
+    fn dispatch_command(&mut self) {
+        match self.command.take() {
+            // Raster attributes, defining the aspect ratio and size of the image.
+            Some(b'"') if self.params.len() >= 4 => {
+                let (max_width, max_height) = MAX_GRAPHIC_DIMENSIONS;
+                let width = usize::from(self.params[2]).min(max_width);
+                let height = usize::from(self.params[3]).min(max_height);
+
+                self.reserve(width, height);
+                self.width = self.width.max(width);
+                self.height = self.height.max(height);
+            },
+            // Color introducer, selecting and optionally defining a color register.
+            Some(b'#') => {
+                self.color = usize::from(self.params[0]).min(PALETTE_SIZE - 1);
+
+                if self.params.len() >= 5 {
+                    let (x, y, z) = (self.params[2], self.params[3], self.params[4]);
+                    self.palette[self.color] = match self.params[1] {
+                        1 => hls_to_rgb(x, y, z),
+                        2 => Rgb { r: percent(x), g: percent(y), b: percent(z) },
+                        _ => return,
+                    };
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Draw a sixel `count` times at the current position.
+    fn draw_sixel(&mut self, bits: u8, count: usize) {
+        let (max_width, max_height) = MAX_GRAPHIC_DIMENSIONS;
+        let right = (self.x + count).min(max_width);
+        let bottom = (self.y + 6).min(max_height);
+
+        if self.x < right && self.y < bottom {
+            self.reserve(right, bottom);
+            self.width = self.width.max(right);
+            self.height = self.height.max(bottom);
+
+            let Rgb { r, g, b } = self.palette[self.color];
+            let top = self.y;
+            for row in (top..bottom).filter(|row| bits & (1 << (row - top)) != 0) {
+                let start = (row * self.stride + self.x) * 4;
+                let end = (row * self.stride + right) * 4;
+                for pixel in self.pixels[start..end].chunks_mut(4) {
+                    pixel.copy_from_slice(&[r, g, b, 255]);
+                }
+            }
+        }
+
+        self.x += count;
+    }
+
+    /// Make sure the pixel buffer can hold an image of the specified size.
+    fn reserve(&mut self, width: usize, height: usize) {
+        if width > self.stride {
+            // Grow rows exponentially, to avoid copying the buffer for every new column.
+            let stride = width.max(self.stride * 2).min(MAX_GRAPHIC_DIMENSIONS.0);
+            let rows = if self.stride == 0 { 0 } else { self.pixels.len() / (self.stride * 4) };
+
+            let mut pixels = vec![0; rows * stride * 4];
+            if self.stride != 0 {
+                for (row, old) in self.pixels.chunks(self.stride * 4).enumerate() {
+                    pixels[row * stride * 4..][..old.len()].copy_from_slice(old);
+                }
+            }
+
+            self.pixels = pixels;
+            self.stride = stride;
+        }
+
+        if height * self.stride * 4 > self.pixels.len() {
+            self.pixels.resize(height * self.stride * 4, 0);
+        }
+    }
+}
+
+/// Convert a color channel from percent to its 8 bit value.
+fn percent(value: u16) -> u8 {
+    ((u32::from(value.min(100)) * 255 + 50) / 100) as u8
+}
+
+/// Convert a color in the HLS color space to RGB.
+///
+/// Unlike the usual HLS conventions, sixel graphics have blue at a hue of 0 degrees.
+fn hls_to_rgb(hue: u16, lightness: u16, saturation: u16) -> Rgb {
+    let hue = f32::from((hue % 360 + 240) % 360) / 60.;
+    let lightness = f32::from(lightness.min(100)) / 100.;
+    let saturation = f32::from(saturation.min(100)) / 100.;
+
+    let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+    let x = chroma * (1. - (hue % 2. - 1.).abs());
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+
+    let min = lightness - chroma / 2.;
+    let channel = |value: f32| ((value + min) * 255.).round() as u8;
+    Rgb { r: channel(r), g: channel(g), b: channel(b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(background: u16, data: &[u8]) -> Option<GraphicData> {
+        let mut parser = Parser::new(background);
+        for byte in data {
+            parser.put(*byte);
+        }
+        parser.finish()
+    }
+
+    #[test]
+    fn repeat_sixel() {
+        let graphic = parse(1, b"#1;2;100;0;0!3~").unwrap();
+
+        assert_eq!((graphic.width, graphic.height), (3, 6));
+        assert!(graphic.pixels.chunks(4).all(|pixel| pixel == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn background_select() {
+        // Only the topmost pixel of the sixel is set.
+        let transparent = parse(1, b"#2@").unwrap();
+        assert_eq!(&transparent.pixels[..8], &[204, 33, 33, 255, 0, 0, 0, 0]);
+
+        let opaque = parse(0, b"#2@").unwrap();
+        assert_eq!(&opaque.pixels[..8], &[204, 33, 33, 255, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn raster_attributes_and_newlines() {
+        let graphic = parse(1, b"\"1;1;4;3#3~$-~~").unwrap();
+
+        assert_eq!((graphic.width, graphic.height), (4, 12));
+        let pixel = |x: usize, y: usize| &graphic.pixels[(y * 4 + x) * 4..][..4];
+        assert_eq!(pixel(0, 0), [51, 204, 51, 255]);
+        assert_eq!(pixel(1, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(1, 11), [51, 204, 51, 255]);
+        assert_eq!(pixel(2, 11), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn hls_colors() {
+        assert_eq!(hls_to_rgb(0, 50, 100), Rgb { r: 0, g: 0, b: 255 });
+        assert_eq!(hls_to_rgb(120, 50, 100), Rgb { r: 255, g: 0, b: 0 });
+        assert_eq!(hls_to_rgb(240, 50, 100), Rgb { r: 0, g: 255, b: 0 });
+        assert_eq!(hls_to_rgb(0, 100, 0), Rgb { r: 255, g: 255, b: 255 });
+    }
+
+    #[test]
+    fn empty_image() {
+        assert_eq!(parse(0, b"#0;2;0;0;0"), None);
+    }
+}
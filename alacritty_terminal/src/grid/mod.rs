@@ -15,7 +15,8 @@ mod storage;
 #[cfg(test)]
 mod tests;
 
-pub use self::row::Row;
+pub use self::row::{LineMarks, Row};
+pub use self::storage::MemoryUsage;
 use self::storage::Storage;
 
 pub trait GridCell: Sized {
@@ -40,6 +41,15 @@ pub struct Cursor<T> {
     /// Currently configured graphic character sets.
     pub charsets: Charsets,
 
+    /// Character set which is currently being mapped to ASCII.
+    pub active_charset: CharsetIndex,
+
+    /// Origin mode (DECOM) state at the time this cursor was saved.
+    ///
+    /// This is only used by saved cursors, the origin mode of the active cursor is tracked by the
+    /// terminal modes.
+    pub origin: bool,
+
     /// Tracks if the next call to input will need to first handle wrapping.
     ///
     /// This is true after the last column is set with the input function. Any function that
@@ -133,6 +143,14 @@ pub struct Grid<T> {
 
     /// Maximum number of lines in history.
     max_scroll_limit: usize,
+
+    /// Total number of lines moved into the scrollback history.
+    ///
+    /// This allows tracking a position in the history while new lines are being added. Lines
+    /// pulled back from the history move it backwards, while reflow moves it past all lines
+    /// since they cannot be tracked through it.
+    #[serde(skip)]
+    history_position: usize,
 }
 
 impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
@@ -140,6 +158,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         Grid {
             raw: Storage::with_capacity(lines, columns),
             max_scroll_limit,
+            history_position: 0,
             display_offset: 0,
             saved_cursor: Cursor::default(),
             cursor: Cursor::default(),
@@ -266,6 +285,8 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
             self.display_offset = min(self.display_offset + positions, self.max_scroll_limit);
         }
 
+        self.history_position = self.history_position.wrapping_add(positions);
+
         // Create scrollback for the new lines.
         self.increase_scroll_limit(positions);
 
@@ -296,6 +317,32 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         }
     }
 
+    /// Move lines inside the region toward the top, without saving them in the history.
+    pub fn scroll_up_in_region<D>(&mut self, region: &Range<Line>, positions: usize)
+    where
+        T: ResetDiscriminant<D>,
+        D: PartialEq,
+    {
+        // When rotating the entire region, just reset everything.
+        if region.end - region.start <= positions {
+            for i in (region.start.0..region.end.0).map(Line::from) {
+                self.raw[i].reset(&self.cursor.template);
+            }
+
+            return;
+        }
+
+        let range = region.start.0..(region.end - positions).0;
+        for line in range.map(Line::from) {
+            self.raw.swap(line, line + positions);
+        }
+
+        let range = (region.end - positions).0..region.end.0;
+        for line in range.map(Line::from) {
+            self.raw[line].reset(&self.cursor.template);
+        }
+    }
+
     pub fn clear_viewport<D>(&mut self)
     where
         T: ResetDiscriminant<D>,
@@ -372,10 +419,59 @@ impl<T> Grid<T> {
         }
     }
 
+    /// Copy a visible rectangular region, moving its top left corner to `destination`.
+    ///
+    /// Both corners of the source region are inclusive. The copied region must fit within the
+    /// screen at its destination, overlapping the source region is allowed.
+    pub fn copy_region(&mut self, start: Point, end: Point, destination: Point)
+    where
+        T: Clone,
+    {
+        debug_assert!(destination.line + (end.line - start.line) < self.screen_lines());
+        debug_assert!(destination.column + (end.column - start.column) < self.columns());
+
+        // Buffer the source region, since it might be overwritten while copying.
+        let region: Vec<Vec<T>> = (start.line.0..=end.line.0)
+            .map(|line| self[Line(line)][start.column..end.column + 1].to_vec())
+            .collect();
+
+        for (line, cells) in (destination.line.0..).map(Line).zip(region) {
+            let columns = destination.column..destination.column + cells.len();
+            self[line][columns].clone_from_slice(&cells);
+        }
+    }
+
+    /// Replace all cells of a visible rectangular region, with both corners being inclusive.
+    pub fn fill_region(&mut self, start: Point, end: Point, cell: &T)
+    where
+        T: Clone,
+    {
+        for line in (start.line.0..=end.line.0).map(Line) {
+            for target in &mut self[line][start.column..end.column + 1] {
+                *target = cell.clone();
+            }
+        }
+    }
+
     #[inline]
     pub fn clear_history(&mut self) {
         // Explicitly purge all lines from history.
         self.raw.shrink_lines(self.history_size());
+
+        // Reset display offset.
+        self.display_offset = 0;
+    }
+
+    /// Estimate the memory used by the grid's lines.
+    #[inline]
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.raw.memory_usage()
+    }
+
+    /// Free memory buffered for future scrollback or left over from resizing.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.raw.shrink_to_fit();
     }
 
     /// This is used only for initializing after loading ref-tests.
@@ -423,6 +519,22 @@ impl<T> Grid<T> {
         self.display_offset
     }
 
+    /// Total number of lines moved into the scrollback history, wrapping on overflow.
+    ///
+    /// The difference between two positions is the number of lines all history lines moved up.
+    #[inline]
+    pub fn history_position(&self) -> usize {
+        self.history_position
+    }
+
+    /// Number of lines all lines moved up since the specified history position.
+    ///
+    /// This is negative when lines were pulled from the history, like when growing the grid.
+    #[inline]
+    pub fn history_shift(&self, history_position: usize) -> i32 {
+        self.history_position.wrapping_sub(history_position) as i32
+    }
+
     #[inline]
     pub fn cursor_cell(&mut self) -> &mut T {
         let point = self.cursor.point;
@@ -430,6 +542,49 @@ impl<T> Grid<T> {
     }
 }
 
+impl<T: PartialEq> Grid<T> {
+    /// Find all cells which changed compared to an older snapshot of the grid.
+    ///
+    /// Lines which do not exist in the old grid, or all lines when the number of columns changed,
+    /// are reported as damaged entirely. Lines which were removed from the grid are not reported.
+    pub fn diff(&self, old: &Grid<T>) -> Vec<LineDamage> {
+        let mut damage = Vec::new();
+
+        for line in (self.topmost_line().0..=self.bottommost_line().0).map(Line::from) {
+            let row = &self[line];
+
+            let comparable = self.columns == old.columns
+                && line >= old.topmost_line()
+                && line <= old.bottommost_line();
+            if !comparable {
+                damage.push(LineDamage { line, left: Column(0), right: self.last_column() });
+                continue;
+            }
+
+            let old_row = &old[line];
+            let mut changed = (0..self.columns).filter(|&i| row[Column(i)] != old_row[Column(i)]);
+            if let Some(left) = changed.next() {
+                let right = changed.last().unwrap_or(left);
+                damage.push(LineDamage { line, left: Column(left), right: Column(right) });
+            }
+        }
+
+        damage
+    }
+}
+
+/// Range of changed cells within a single line.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LineDamage {
+    pub line: Line,
+
+    /// First changed column.
+    pub left: Column,
+
+    /// Last changed column, inclusive.
+    pub right: Column,
+}
+
 impl<T: PartialEq> PartialEq for Grid<T> {
     fn eq(&self, other: &Self) -> bool {
         // Compare struct fields and check result of grid comparison.
@@ -19,6 +19,8 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         // Use empty template cell for resetting cells due to resize.
         let template = mem::take(&mut self.cursor.template);
 
+        let reflowed = reflow && self.columns != columns;
+
         match self.lines.cmp(&lines) {
             Ordering::Less => self.grow_lines(lines),
             Ordering::Greater => self.shrink_lines(lines),
@@ -31,6 +33,11 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
             Ordering::Equal => (),
         }
 
+        // Move all tracked positions out of the grid, since lines can't be followed through reflow.
+        if reflowed {
+            self.history_position = self.history_position.wrapping_add(self.total_lines());
+        }
+
         // Restore template cell.
         self.cursor.template = template;
     }
@@ -46,6 +53,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         D: PartialEq,
     {
         let lines_added = target - self.lines;
+        let history_position = self.history_position;
 
         // Need to resize before updating buffer.
         self.raw.grow_visible_lines(target);
@@ -66,6 +74,9 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
 
         self.display_offset = self.display_offset.saturating_sub(lines_added);
         self.decrease_scroll_limit(lines_added);
+
+        // Move all lines down for every line pulled from history.
+        self.history_position = history_position.wrapping_sub(from_history);
     }
 
     /// Remove lines from the visible area.
@@ -164,6 +175,11 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
             // Add removed cells to previous row and reflow content.
             last_row.append(&mut cells);
 
+            // Keep the marks of rows which were merged into the previous one.
+            if row.is_clear() {
+                last_row.marks |= mem::take(&mut row.marks);
+            }
+
             let cursor_buffer_line = self.lines - self.cursor.point.line.0 as usize - 1;
 
             if i == cursor_buffer_line && reflow {
@@ -6,12 +6,26 @@ use std::ops::{Range, RangeFrom, RangeFull, RangeTo, RangeToInclusive};
 use std::ptr;
 use std::slice;
 
+use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
 use crate::grid::GridCell;
 use crate::index::Column;
 use crate::term::cell::ResetDiscriminant;
 
+bitflags! {
+    /// Shell integration marks set through `OSC 133`.
+    #[derive(Serialize, Deserialize, Default)]
+    pub struct LineMarks: u8 {
+        const PROMPT_START  = 0b0001;
+        const COMMAND_START = 0b0010;
+        const OUTPUT_START  = 0b0100;
+        const COMMAND_END   = 0b1000;
+        /// Output ends on this line, since it had no trailing newline.
+        const OUTPUT_END    = 0b1_0000;
+    }
+}
+
 /// A row in the grid.
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct Row<T> {
@@ -22,6 +36,10 @@ pub struct Row<T> {
     /// This is the upper bound on the number of elements in the row, which have been modified
     /// since the last reset. All cells after this point are guaranteed to be equal.
     pub(crate) occ: usize,
+
+    /// Shell integration marks, which move with the row into the scrollback history.
+    #[serde(default)]
+    pub(crate) marks: LineMarks,
 }
 
 impl<T: PartialEq> PartialEq for Row<T> {
@@ -52,7 +70,7 @@ impl<T: Clone + Default> Row<T> {
             inner.set_len(columns);
         }
 
-        Row { inner, occ: 0 }
+        Row { inner, occ: 0, marks: LineMarks::empty() }
     }
 
     /// Increase the number of columns in the row.
@@ -111,6 +129,7 @@ impl<T: Clone + Default> Row<T> {
         }
 
         self.occ = 0;
+        self.marks = LineMarks::empty();
     }
 }
 
@@ -118,7 +137,13 @@ impl<T: Clone + Default> Row<T> {
 impl<T> Row<T> {
     #[inline]
     pub fn from_vec(vec: Vec<T>, occ: usize) -> Row<T> {
-        Row { inner: vec, occ }
+        Row { inner: vec, occ, marks: LineMarks::empty() }
+    }
+
+    /// Shell integration marks of the row.
+    #[inline]
+    pub fn marks(&self) -> LineMarks {
+        self.marks
     }
 
     #[inline]
@@ -126,6 +151,18 @@ impl<T> Row<T> {
         self.inner.len()
     }
 
+    /// Number of cells the row can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Free memory allocated for cells beyond the row's length.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+
     #[inline]
     pub fn last(&self) -> Option<&T> {
         self.inner.last()
@@ -154,6 +191,16 @@ impl<T> Row<T> {
         self.inner = vec;
     }
 
+    /// Check that all cells after the occupied count are identical.
+    #[cfg(feature = "debug_checks")]
+    pub(crate) fn has_valid_occ(&self) -> bool
+    where
+        T: PartialEq,
+    {
+        self.occ <= self.inner.len()
+            && self.inner[self.occ..].windows(2).all(|cells| cells[0] == cells[1])
+    }
+
     /// Check if all cells in the row are empty.
     #[inline]
     pub fn is_clear(&self) -> bool
@@ -281,7 +328,7 @@ impl<T> Index<RangeToInclusive<Column>> for Row<T> {
 impl<T> IndexMut<RangeToInclusive<Column>> for Row<T> {
     #[inline]
     fn index_mut(&mut self, index: RangeToInclusive<Column>) -> &mut [T] {
-        self.occ = max(self.occ, *index.end);
+        self.occ = max(self.occ, *index.end + 1);
         &mut self.inner[..=(index.end.0)]
     }
 }
@@ -10,6 +10,16 @@ use crate::index::Line;
 /// Maximum number of buffered lines outside of the grid for performance optimization.
 const MAX_CACHE_SIZE: usize = 1_000;
 
+/// Estimated memory usage of a grid in bytes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Memory used by the visible lines.
+    pub visible: usize,
+
+    /// Memory used by the scrollback history.
+    pub history: usize,
+}
+
 /// A ring buffer for optimizing indexing and rotation.
 ///
 /// The [`Storage::rotate`] and [`Storage::rotate_down`] functions are fast modular additions on
@@ -139,16 +149,54 @@ impl<T> Storage<T> {
         self.len
     }
 
+    /// Estimate the memory used by the rows of the buffer.
+    ///
+    /// Rows buffered for future scrollback and unused capacity of the buffer are counted towards
+    /// the history.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let row_size = mem::size_of::<Row<T>>();
+        let cell_size = mem::size_of::<T>();
+
+        let mut usage = MemoryUsage::default();
+        let len = self.inner.len();
+        for (i, row) in self.inner.iter().enumerate() {
+            let size = row_size + row.capacity() * cell_size;
+
+            // Visible lines are stored in the first rows after the ring buffer's zero index.
+            if (i + len - self.zero) % len < self.visible_lines {
+                usage.visible += size;
+            } else {
+                usage.history += size;
+            }
+        }
+        usage.history += (self.inner.capacity() - len) * row_size;
+
+        usage
+    }
+
+    /// Free all memory which is not required for storing the active lines.
+    pub fn shrink_to_fit(&mut self) {
+        self.truncate();
+
+        self.inner.shrink_to_fit();
+        for row in &mut self.inner {
+            row.shrink_to_fit();
+        }
+    }
+
     /// Swap implementation for Row<T>.
     ///
     /// Exploits the known size of Row<T> to produce a slightly more efficient
     /// swap than going through slice::swap.
     ///
-    /// The default implementation from swap generates 8 movups and 4 movaps
-    /// instructions. This implementation achieves the swap in only 8 movups
-    /// instructions.
+    /// Row<T> is five words in size: three for the cells, one for the
+    /// occupied length and one for the shell integration marks. The marks
+    /// only need a byte, but alignment pads them to a full word. These 8
+    /// bytes per row are accepted, since storing the marks inline keeps them
+    /// attached to their line through rotation and resizing without any
+    /// separate bookkeeping.
     pub fn swap(&mut self, a: Line, b: Line) {
-        debug_assert_eq!(mem::size_of::<Row<T>>(), mem::size_of::<usize>() * 4);
+        debug_assert_eq!(mem::size_of::<Row<T>>(), mem::size_of::<usize>() * 5);
 
         let a = self.compute_index(a);
         let b = self.compute_index(b);
@@ -164,7 +212,7 @@ impl<T> Storage<T> {
             //
             // The optimizer unrolls this loop and vectorizes it.
             let mut tmp: usize;
-            for i in 0..4 {
+            for i in 0..5 {
                 tmp = *a_ptr.offset(i);
                 *a_ptr.offset(i) = *b_ptr.offset(i);
                 *b_ptr.offset(i) = tmp;
@@ -266,8 +314,10 @@ impl<T> IndexMut<Line> for Storage<T> {
 
 #[cfg(test)]
 mod tests {
+    use std::mem;
+
     use crate::grid::row::Row;
-    use crate::grid::storage::{Storage, MAX_CACHE_SIZE};
+    use crate::grid::storage::{MemoryUsage, Storage, MAX_CACHE_SIZE};
     use crate::grid::GridCell;
     use crate::index::{Column, Line};
     use crate::term::cell::Flags;
@@ -748,6 +798,27 @@ mod tests {
         assert_eq!(storage.inner, expected_storage.inner);
     }
 
+    #[test]
+    fn memory_usage() {
+        let row_size = mem::size_of::<Row<char>>() + mem::size_of::<char>();
+
+        let mut storage = Storage::<char>::with_capacity(3, 1);
+        storage.rotate(1);
+        assert_eq!(storage.memory_usage(), MemoryUsage { visible: 3 * row_size, history: 0 });
+
+        // Buffered rows are counted towards the history.
+        storage.initialize(2, 1);
+        let usage = storage.memory_usage();
+        assert_eq!(usage.visible, 3 * row_size);
+        assert!(usage.history >= MAX_CACHE_SIZE * row_size);
+
+        // Shrinking frees the buffered rows.
+        storage.shrink_to_fit();
+        assert_eq!(storage.inner.len(), 5);
+        assert_eq!(storage.memory_usage().visible, 3 * row_size);
+        assert!(storage.memory_usage().history < usage.history);
+    }
+
     #[test]
     fn rotate_wrap_zero() {
         let mut storage: Storage<char> = Storage {
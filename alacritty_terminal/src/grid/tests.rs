@@ -54,6 +54,74 @@ fn scroll_up() {
     assert_eq!(grid[Line(9)].occ, 0);
 }
 
+// Scrolling inside a region keeps the history untouched.
+#[test]
+fn scroll_up_in_region() {
+    let mut grid = Grid::<usize>::new(10, 1, 10);
+    for i in 0..10 {
+        grid[Line(i as i32)][Column(0)] = i;
+    }
+
+    grid.scroll_up_in_region::<usize>(&(Line(0)..Line(5)), 2);
+
+    let lines: Vec<_> = (0..10).map(|i| grid[Line(i)][Column(0)]).collect();
+    assert_eq!(lines, [2, 3, 4, 0, 0, 5, 6, 7, 8, 9]);
+    assert_eq!(grid[Line(3)].occ, 0);
+    assert_eq!(grid[Line(4)].occ, 0);
+    assert_eq!(grid.history_size(), 0);
+    assert_eq!(grid.history_position(), 0);
+}
+
+// The history position keeps counting once the history is full.
+#[test]
+fn history_position() {
+    let mut grid = Grid::<usize>::new(3, 1, 2);
+    grid[Line(2)][Column(0)] = 1;
+
+    grid.scroll_up::<usize>(&(Line(0)..Line(3)), 1);
+    assert_eq!(grid.history_position(), 1);
+    assert_eq!(grid[Line(1)][Column(0)], 1);
+
+    grid.scroll_up::<usize>(&(Line(0)..Line(3)), 3);
+    assert_eq!(grid.history_position(), 4);
+    assert_eq!(grid.history_size(), 2);
+    assert_eq!(grid[Line(-2)][Column(0)], 1);
+}
+
+// The history position follows lines pulled from history and is invalidated by reflow.
+#[test]
+fn history_position_resize() {
+    let mut grid = Grid::<Cell>::new(2, 2, 10);
+    grid[Line(1)][Column(0)] = cell('1');
+    grid.scroll_up(&(Line(0)..Line(2)), 2);
+    let position = grid.history_position();
+    assert_eq!(grid[Line(-1)][Column(0)], cell('1'));
+
+    grid.resize(false, 4, 2);
+    assert_eq!(grid.history_shift(position), -2);
+    assert_eq!(grid[Line(1)][Column(0)], cell('1'));
+
+    let position = grid.history_position();
+    grid.resize(false, 4, 3);
+    assert_eq!(grid.history_shift(position), 0);
+
+    grid.resize(true, 4, 2);
+    assert!(grid.history_shift(position) >= grid.total_lines() as i32);
+}
+
+// Clearing the history leaves positions in the visible area untouched.
+#[test]
+fn history_position_clear_history() {
+    let mut grid = Grid::<usize>::new(2, 1, 10);
+    grid.scroll_up::<usize>(&(Line(0)..Line(2)), 2);
+    let position = grid.history_position();
+
+    grid.clear_history();
+
+    assert_eq!(grid.history_shift(position), 0);
+    assert_eq!(grid.topmost_line(), Line(0));
+}
+
 // Scroll down moves lines downward.
 #[test]
 fn scroll_down() {
@@ -348,6 +416,71 @@ fn shrink_reflow_disabled() {
     assert_eq!(grid[Line(0)][Column(1)], cell('2'));
 }
 
+#[test]
+fn diff_changed_cells() {
+    let old = Grid::<usize>::new(3, 5, 0);
+    let mut grid = old.clone();
+
+    assert_eq!(grid.diff(&old), Vec::new());
+
+    grid[Line(0)][Column(1)] = 1;
+    grid[Line(0)][Column(3)] = 1;
+    grid[Line(2)][Column(4)] = 1;
+
+    assert_eq!(grid.diff(&old), vec![
+        LineDamage { line: Line(0), left: Column(1), right: Column(3) },
+        LineDamage { line: Line(2), left: Column(4), right: Column(4) },
+    ]);
+}
+
+#[test]
+fn diff_new_lines() {
+    let old = Grid::<usize>::new(2, 2, 1);
+    let mut grid = old.clone();
+
+    grid[Line(1)][Column(0)] = 1;
+    grid.scroll_up::<usize>(&(Line(0)..Line(2)), 1);
+
+    assert_eq!(grid.diff(&old), vec![
+        LineDamage { line: Line(-1), left: Column(0), right: Column(1) },
+        LineDamage { line: Line(0), left: Column(0), right: Column(0) },
+    ]);
+
+    let mut grid = Grid::<usize>::new(2, 3, 0);
+    grid[Line(0)][Column(0)] = 1;
+
+    assert_eq!(grid.diff(&old), vec![
+        LineDamage { line: Line(0), left: Column(0), right: Column(2) },
+        LineDamage { line: Line(1), left: Column(0), right: Column(2) },
+    ]);
+}
+
+#[test]
+fn copy_overlapping_region() {
+    let mut grid = Grid::<usize>::new(3, 4, 0);
+    for line in 0..3 {
+        for column in 0..4 {
+            grid[Line(line)][Column(column)] = line as usize * 4 + column;
+        }
+    }
+
+    let start = Point::new(Line(0), Column(0));
+    let end = Point::new(Line(1), Column(1));
+    grid.copy_region(start, end, Point::new(Line(1), Column(1)));
+
+    let cells: Vec<Vec<usize>> = (0..3).map(|line| grid[Line(line)][..].to_vec()).collect();
+    assert_eq!(cells, vec![vec![0, 1, 2, 3], vec![4, 0, 1, 7], vec![8, 4, 5, 11]]);
+}
+
+#[test]
+fn fill_region() {
+    let mut grid = Grid::<usize>::new(3, 3, 0);
+    grid.fill_region(Point::new(Line(1), Column(1)), Point::new(Line(2), Column(1)), &1);
+
+    let cells: Vec<Vec<usize>> = (0..3).map(|line| grid[Line(line)][..].to_vec()).collect();
+    assert_eq!(cells, vec![vec![0, 0, 0], vec![0, 1, 0], vec![0, 1, 0]]);
+}
+
 // https://github.com/rust-lang/rust-clippy/pull/6375
 #[allow(clippy::all)]
 fn cell(c: char) -> Cell {
@@ -7,6 +7,8 @@ use std::ops::{Add, AddAssign, Deref, Sub, SubAssign};
 
 use serde::{Deserialize, Serialize};
 
+use alacritty_config::{ConfigTemplate, Template};
+
 use crate::grid::Dimensions;
 
 /// The side of a cell.
@@ -229,6 +231,12 @@ impl fmt::Display for Column {
     }
 }
 
+impl ConfigTemplate for Column {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.value(key, self);
+    }
+}
+
 macro_rules! ops {
     ($ty:ty, $construct:expr, $primitive:ty) => {
         impl Deref for $ty {
@@ -1,29 +1,40 @@
 use std::boxed::Box;
+use std::fmt::Write;
 
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
 use crate::ansi::{Color, NamedColor};
+use crate::graphics::GraphicCell;
 use crate::grid::{self, GridCell};
 use crate::index::Column;
 
 bitflags! {
     #[derive(Serialize, Deserialize)]
-    pub struct Flags: u16 {
-        const INVERSE                   = 0b0000_0000_0000_0001;
-        const BOLD                      = 0b0000_0000_0000_0010;
-        const ITALIC                    = 0b0000_0000_0000_0100;
-        const BOLD_ITALIC               = 0b0000_0000_0000_0110;
-        const UNDERLINE                 = 0b0000_0000_0000_1000;
-        const WRAPLINE                  = 0b0000_0000_0001_0000;
-        const WIDE_CHAR                 = 0b0000_0000_0010_0000;
-        const WIDE_CHAR_SPACER          = 0b0000_0000_0100_0000;
-        const DIM                       = 0b0000_0000_1000_0000;
-        const DIM_BOLD                  = 0b0000_0000_1000_0010;
-        const HIDDEN                    = 0b0000_0001_0000_0000;
-        const STRIKEOUT                 = 0b0000_0010_0000_0000;
-        const LEADING_WIDE_CHAR_SPACER  = 0b0000_0100_0000_0000;
-        const DOUBLE_UNDERLINE          = 0b0000_1000_0000_0000;
+    pub struct Flags: u32 {
+        const INVERSE                   = 0b0000_0000_0000_0000_0000_0000_0000_0001;
+        const BOLD                      = 0b0000_0000_0000_0000_0000_0000_0000_0010;
+        const ITALIC                    = 0b0000_0000_0000_0000_0000_0000_0000_0100;
+        const BOLD_ITALIC               = 0b0000_0000_0000_0000_0000_0000_0000_0110;
+        const UNDERLINE                 = 0b0000_0000_0000_0000_0000_0000_0000_1000;
+        const WRAPLINE                  = 0b0000_0000_0000_0000_0000_0000_0001_0000;
+        const WIDE_CHAR                 = 0b0000_0000_0000_0000_0000_0000_0010_0000;
+        const WIDE_CHAR_SPACER          = 0b0000_0000_0000_0000_0000_0000_0100_0000;
+        const DIM                       = 0b0000_0000_0000_0000_0000_0000_1000_0000;
+        const DIM_BOLD                  = 0b0000_0000_0000_0000_0000_0000_1000_0010;
+        const HIDDEN                    = 0b0000_0000_0000_0000_0000_0001_0000_0000;
+        const STRIKEOUT                 = 0b0000_0000_0000_0000_0000_0010_0000_0000;
+        const LEADING_WIDE_CHAR_SPACER  = 0b0000_0000_0000_0000_0000_0100_0000_0000;
+        const DOUBLE_UNDERLINE          = 0b0000_0000_0000_0000_0000_1000_0000_0000;
+        const GRAPHICS                  = 0b0000_0000_0000_0000_0001_0000_0000_0000;
+        const UNDERCURL                 = 0b0000_0000_0000_0000_0010_0000_0000_0000;
+        const DOTTED_UNDERLINE          = 0b0000_0000_0000_0000_0100_0000_0000_0000;
+        const DASHED_UNDERLINE          = 0b0000_0000_0000_0000_1000_0000_0000_0000;
+        const ALL_UNDERLINES            = 0b0000_0000_0000_0000_1110_1000_0000_1000;
+        const BLINK                     = 0b0000_0000_0000_0001_0000_0000_0000_0000;
+        const PROTECTED                 = 0b0000_0000_0000_0010_0000_0000_0000_0000;
+        const RAPID_BLINK               = 0b0000_0000_0000_0100_0000_0000_0000_0000;
+        const ALL_BLINKS                = 0b0000_0000_0000_0101_0000_0000_0000_0000;
     }
 }
 
@@ -53,6 +64,9 @@ impl ResetDiscriminant<Color> for Cell {
 #[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
 struct CellExtra {
     zerowidth: Vec<char>,
+
+    #[serde(skip)]
+    graphic: Option<GraphicCell>,
 }
 
 /// Content and attributes of a single cell in the terminal grid.
@@ -92,6 +106,19 @@ impl Cell {
         self.extra.get_or_insert_with(Default::default).zerowidth.push(c);
     }
 
+    /// Part of a graphic shown in this cell.
+    #[inline]
+    pub fn graphic(&self) -> Option<&GraphicCell> {
+        self.extra.as_ref().and_then(|extra| extra.graphic.as_ref())
+    }
+
+    /// Show part of a graphic in this cell.
+    #[inline]
+    pub fn set_graphic(&mut self, graphic: GraphicCell) {
+        self.extra.get_or_insert_with(Default::default).graphic = Some(graphic);
+        self.flags.insert(Flags::GRAPHICS);
+    }
+
     /// Free all dynamically allocated cell storage.
     #[inline]
     pub fn drop_extra(&mut self) {
@@ -107,6 +134,106 @@ impl Cell {
         self.drop_extra();
         self.c = ' ';
     }
+
+    /// Write the cell content as text with ANSI escapes.
+    ///
+    /// Only the escape sequences required to change the attributes of `last` to the attributes of
+    /// this cell are written, which allows serializing consecutive cells efficiently.
+    pub fn as_escape(&self, buf: &mut String, last: &Self) {
+        if self.fg != last.fg || self.bg != last.bg || self.sgr_flags() != last.sgr_flags() {
+            self.write_sgr(buf, last);
+        }
+
+        // The spacer's content is already covered by the wide char itself.
+        if self.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
+            return;
+        }
+
+        buf.push(self.c);
+        for c in self.zerowidth().into_iter().flatten() {
+            buf.push(*c);
+        }
+    }
+
+    /// Write the SGR escape changing the attributes of `last` to the attributes of this cell.
+    fn write_sgr(&self, buf: &mut String, last: &Self) {
+        let _ = write!(buf, "\x1b[{}m", self.sgr_params(last).join(";"));
+    }
+
+    /// SGR parameters changing the attributes of `last` to the attributes of this cell.
+    pub(crate) fn sgr_params(&self, last: &Self) -> Vec<String> {
+        let mut params = Vec::new();
+
+        // Attributes can't be removed individually, so everything is reset instead.
+        let default = Cell::default();
+        let last = if (last.sgr_flags() - self.sgr_flags()).is_empty() {
+            last
+        } else {
+            params.push(String::from("0"));
+            &default
+        };
+
+        let added = self.sgr_flags() - last.sgr_flags();
+        for (flag, param) in &[
+            (Flags::BOLD, "1"),
+            (Flags::DIM, "2"),
+            (Flags::ITALIC, "3"),
+            (Flags::UNDERLINE, "4"),
+            (Flags::DOUBLE_UNDERLINE, "4:2"),
+            (Flags::UNDERCURL, "4:3"),
+            (Flags::DOTTED_UNDERLINE, "4:4"),
+            (Flags::DASHED_UNDERLINE, "4:5"),
+            (Flags::BLINK, "5"),
+            (Flags::RAPID_BLINK, "6"),
+            (Flags::INVERSE, "7"),
+            (Flags::HIDDEN, "8"),
+            (Flags::STRIKEOUT, "9"),
+        ] {
+            if added.contains(*flag) {
+                params.push(String::from(*param));
+            }
+        }
+
+        if self.fg != last.fg {
+            params.push(color_param(self.fg, false));
+        }
+        if self.bg != last.bg {
+            params.push(color_param(self.bg, true));
+        }
+
+        params
+    }
+
+    /// Cell flags which can be represented using SGR escapes.
+    #[inline]
+    pub(crate) fn sgr_flags(&self) -> Flags {
+        self.flags
+            - (Flags::WRAPLINE
+                | Flags::WIDE_CHAR
+                | Flags::WIDE_CHAR_SPACER
+                | Flags::LEADING_WIDE_CHAR_SPACER
+                | Flags::GRAPHICS
+                | Flags::PROTECTED)
+    }
+}
+
+/// SGR parameter for setting the foreground or background color.
+fn color_param(color: Color, background: bool) -> String {
+    let offset = if background { 10 } else { 0 };
+
+    match color {
+        Color::Spec(rgb) => format!("{};2;{};{};{}", 38 + offset, rgb.r, rgb.g, rgb.b),
+        Color::Indexed(index) => format!("{};5;{}", 38 + offset, index),
+        Color::Named(named) => match named as usize {
+            index @ 0..=7 => (30 + offset + index).to_string(),
+            index @ 8..=15 => (82 + offset + index).to_string(),
+            // Dim colors are stored in the grid using the `DIM` flag, so only the base is required.
+            _ => match named.to_bright() as usize {
+                index @ 0..=7 => (30 + offset + index).to_string(),
+                _ => (39 + offset).to_string(),
+            },
+        },
+    }
 }
 
 impl GridCell for Cell {
@@ -117,12 +244,12 @@ impl GridCell for Cell {
             && self.fg == Color::Named(NamedColor::Foreground)
             && !self.flags.intersects(
                 Flags::INVERSE
-                    | Flags::UNDERLINE
-                    | Flags::DOUBLE_UNDERLINE
+                    | Flags::ALL_UNDERLINES
                     | Flags::STRIKEOUT
                     | Flags::WRAPLINE
                     | Flags::WIDE_CHAR_SPACER
-                    | Flags::LEADING_WIDE_CHAR_SPACER,
+                    | Flags::LEADING_WIDE_CHAR_SPACER
+                    | Flags::GRAPHICS,
             )
             && self.extra.as_ref().map(|extra| extra.zerowidth.is_empty()) != Some(false)
     }
@@ -179,10 +306,12 @@ impl LineLength for grid::Row<Cell> {
 
 #[cfg(test)]
 mod tests {
-    use super::{Cell, LineLength};
+    use super::{Cell, Flags, LineLength};
 
+    use crate::ansi::{Color, NamedColor};
     use crate::grid::Row;
     use crate::index::Column;
+    use crate::term::color::Rgb;
 
     #[test]
     fn line_length_works() {
@@ -199,4 +328,45 @@ mod tests {
 
         assert_eq!(row.line_length(), Column(10));
     }
+
+    #[test]
+    fn as_escape_only_writes_changes() {
+        let fg = Color::Named(NamedColor::Red);
+        let bold = Cell { c: 'a', fg, flags: Flags::BOLD, ..Cell::default() };
+        let mut buf = String::new();
+
+        bold.as_escape(&mut buf, &Cell::default());
+        bold.as_escape(&mut buf, &bold);
+        assert_eq!(buf, "\x1b[1;31maa");
+    }
+
+    #[test]
+    fn as_escape_resets_removed_attributes() {
+        let bold = Cell { c: 'a', flags: Flags::BOLD, ..Cell::default() };
+        let rgb = Cell { c: 'b', bg: Color::Spec(Rgb { r: 1, g: 2, b: 3 }), ..Cell::default() };
+        let mut buf = String::new();
+
+        rgb.as_escape(&mut buf, &bold);
+        assert_eq!(buf, "\x1b[0;48;2;1;2;3mb");
+    }
+
+    #[test]
+    fn as_escape_underline_styles() {
+        let undercurl = Cell { c: 'a', flags: Flags::UNDERCURL, ..Cell::default() };
+        let dashed = Cell { c: 'b', flags: Flags::DASHED_UNDERLINE, ..Cell::default() };
+        let mut buf = String::new();
+
+        undercurl.as_escape(&mut buf, &Cell::default());
+        dashed.as_escape(&mut buf, &undercurl);
+        assert_eq!(buf, "\x1b[4:3ma\x1b[0;4:5mb");
+    }
+
+    #[test]
+    fn as_escape_skips_wide_char_spacer() {
+        let spacer = Cell { flags: Flags::WIDE_CHAR_SPACER, ..Cell::default() };
+        let mut buf = String::new();
+
+        spacer.as_escape(&mut buf, &Cell::default());
+        assert!(buf.is_empty());
+    }
 }
@@ -0,0 +1,121 @@
+//! Validation of grid invariants, enabled by the `debug_checks` feature.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::grid::{Dimensions, Grid};
+use crate::index::{Column, Line, Point};
+use crate::term::cell::{Cell, Flags};
+
+/// Broken grid invariant.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// Cursor is outside of the visible grid.
+    CursorOutOfBounds(Point),
+
+    /// Wide character is not followed by a spacer.
+    MissingSpacer(Point),
+
+    /// Spacer does not follow a wide character.
+    OrphanedSpacer(Point),
+
+    /// Leading wide char spacer outside the last column of a wrapped line.
+    MisplacedLeadingSpacer(Point),
+
+    /// Line wrap flag outside the last column.
+    MisplacedWrapline(Point),
+
+    /// Modified cells after the occupied count of a row.
+    InvalidOccupied(Line),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (description, point) = match self {
+            Violation::CursorOutOfBounds(point) => ("cursor out of bounds", point),
+            Violation::MissingSpacer(point) => ("wide char without spacer", point),
+            Violation::OrphanedSpacer(point) => ("spacer without wide char", point),
+            Violation::MisplacedLeadingSpacer(point) => {
+                ("leading wide char spacer outside of wrapped last column", point)
+            },
+            Violation::MisplacedWrapline(point) => ("wrapline outside of last column", point),
+            Violation::InvalidOccupied(line) => {
+                return write!(f, "modified cells after occupied count in line {}", line);
+            },
+        };
+
+        write!(f, "{} at line {}, column {}", description, point.line, point.column)
+    }
+}
+
+/// Find all invariant violations in the visible part of the grid.
+///
+/// The scrollback history is not checked, since it is only ever rotated into from the screen.
+pub fn check_grid(grid: &Grid<Cell>) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    let cursor = grid.cursor.point;
+    if cursor.line < 0 || cursor.line >= grid.screen_lines() || cursor.column >= grid.columns() {
+        violations.push(Violation::CursorOutOfBounds(cursor));
+    }
+
+    let last_column = grid.last_column();
+    for line in (0..grid.screen_lines() as i32).map(Line) {
+        let row = &grid[line];
+
+        if !row.has_valid_occ() {
+            violations.push(Violation::InvalidOccupied(line));
+        }
+
+        for column in (0..grid.columns()).map(Column) {
+            let point = Point::new(line, column);
+            let flags = row[column].flags;
+
+            if flags.contains(Flags::WIDE_CHAR)
+                && (column == last_column
+                    || !row[column + 1].flags.contains(Flags::WIDE_CHAR_SPACER))
+            {
+                violations.push(Violation::MissingSpacer(point));
+            }
+
+            if flags.contains(Flags::WIDE_CHAR_SPACER)
+                && (column == 0 || !row[column - 1].flags.contains(Flags::WIDE_CHAR))
+            {
+                violations.push(Violation::OrphanedSpacer(point));
+            }
+
+            if flags.contains(Flags::LEADING_WIDE_CHAR_SPACER)
+                && (column != last_column || !flags.contains(Flags::WRAPLINE))
+            {
+                violations.push(Violation::MisplacedLeadingSpacer(point));
+            }
+
+            if flags.contains(Flags::WRAPLINE) && column != last_column {
+                violations.push(Violation::MisplacedWrapline(point));
+            }
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_broken_wide_chars() {
+        let mut grid = Grid::<Cell>::new(2, 3, 0);
+        assert_eq!(check_grid(&grid), Vec::new());
+
+        grid[Line(0)][Column(2)].flags.insert(Flags::WIDE_CHAR);
+        grid[Line(1)][Column(0)].flags.insert(Flags::WIDE_CHAR_SPACER);
+        grid[Line(1)][Column(1)].flags.insert(Flags::WRAPLINE);
+
+        let expected = vec![
+            Violation::MissingSpacer(Point::new(Line(0), Column(2))),
+            Violation::OrphanedSpacer(Point::new(Line(1), Column(0))),
+            Violation::MisplacedWrapline(Point::new(Line(1), Column(1))),
+        ];
+        assert_eq!(check_grid(&grid), expected);
+    }
+}
@@ -7,6 +7,8 @@ use serde::de::{Error as _, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_yaml::Value;
 
+use alacritty_config::{ConfigTemplate, Template};
+
 use crate::ansi::NamedColor;
 
 /// Number of terminal colors.
@@ -143,6 +145,12 @@ impl Display for Rgb {
     }
 }
 
+impl ConfigTemplate for Rgb {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        template.value(key, format_args!("'{}'", self));
+    }
+}
+
 impl FromStr for Rgb {
     type Err = ();
 
@@ -193,6 +201,16 @@ impl Default for CellRgb {
     }
 }
 
+impl ConfigTemplate for CellRgb {
+    fn write_template(&self, key: &str, template: &mut Template) {
+        match self {
+            Self::CellForeground => template.value(key, "CellForeground"),
+            Self::CellBackground => template.value(key, "CellBackground"),
+            Self::Rgb(rgb) => rgb.write_template(key, template),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for CellRgb {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -0,0 +1,145 @@
+//! Logical lines spanning multiple wrapped grid lines.
+
+use std::iter::FusedIterator;
+
+use crate::grid::Dimensions;
+use crate::index::{Column, Line, Point};
+use crate::term::cell::Flags;
+use crate::term::Term;
+
+/// Line of text which is spread over one or more grid lines through line wrapping.
+///
+/// Both bounds are absolute grid lines, with negative lines referring to the scrollback history.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LogicalLine {
+    /// First grid line of the logical line.
+    pub start: Line,
+
+    /// Last grid line of the logical line.
+    pub end: Line,
+}
+
+impl LogicalLine {
+    /// Check if a grid line is part of this logical line.
+    #[inline]
+    pub fn contains(&self, line: Line) -> bool {
+        line >= self.start && line <= self.end
+    }
+
+    /// First cell of the logical line.
+    #[inline]
+    pub fn start_point(&self) -> Point {
+        Point::new(self.start, Column(0))
+    }
+
+    /// Last cell of the logical line.
+    #[inline]
+    pub fn end_point<D: Dimensions>(&self, dimensions: &D) -> Point {
+        Point::new(self.end, dimensions.last_column())
+    }
+}
+
+impl<T> Term<T> {
+    /// Iterate over all logical lines, from the top of the scrollback history to the bottom of
+    /// the screen.
+    pub fn logical_lines(&self) -> LogicalLines<'_, T> {
+        LogicalLines { term: self, front: self.topmost_line(), back: self.bottommost_line() }
+    }
+
+    /// Logical line containing the specified grid line.
+    pub fn logical_line(&self, line: Line) -> LogicalLine {
+        let topmost_line = self.topmost_line();
+        let bottommost_line = self.bottommost_line();
+
+        let mut start = line;
+        while start > topmost_line && self.line_wraps(start - 1) {
+            start -= 1;
+        }
+
+        let mut end = line;
+        while end < bottommost_line && self.line_wraps(end) {
+            end += 1;
+        }
+
+        LogicalLine { start, end }
+    }
+
+    /// Check if a grid line continues on the next line.
+    #[inline]
+    fn line_wraps(&self, line: Line) -> bool {
+        self.grid[line][self.last_column()].flags.contains(Flags::WRAPLINE)
+    }
+}
+
+/// Iterator over the logical lines of a terminal.
+pub struct LogicalLines<'a, T> {
+    term: &'a Term<T>,
+    front: Line,
+    back: Line,
+}
+
+impl<'a, T> Iterator for LogicalLines<'a, T> {
+    type Item = LogicalLine;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front > self.back {
+            return None;
+        }
+
+        let mut end = self.front;
+        while end < self.back && self.term.line_wraps(end) {
+            end += 1;
+        }
+
+        let line = LogicalLine { start: self.front, end };
+        self.front = end + 1;
+
+        Some(line)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for LogicalLines<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front > self.back {
+            return None;
+        }
+
+        let mut start = self.back;
+        while start > self.front && self.term.line_wraps(start - 1) {
+            start -= 1;
+        }
+
+        let line = LogicalLine { start, end: self.back };
+        self.back = start - 1;
+
+        Some(line)
+    }
+}
+
+impl<'a, T> FusedIterator for LogicalLines<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::term::test::mock_term;
+
+    #[test]
+    fn logical_lines() {
+        let term = mock_term("abc\r\ndef\nghi\r\njkl\nmno\npqr");
+
+        let lines: Vec<_> = term.logical_lines().collect();
+        assert_eq!(lines, vec![
+            LogicalLine { start: Line(0), end: Line(0) },
+            LogicalLine { start: Line(1), end: Line(2) },
+            LogicalLine { start: Line(3), end: Line(5) },
+        ]);
+
+        let reversed: Vec<_> = term.logical_lines().rev().collect();
+        assert_eq!(reversed, lines.iter().rev().copied().collect::<Vec<_>>());
+
+        assert_eq!(term.logical_line(Line(4)), LogicalLine { start: Line(3), end: Line(5) });
+        assert_eq!(term.logical_line(Line(0)), LogicalLine { start: Line(0), end: Line(0) });
+        assert_eq!(lines[1].end_point(&term), Point::new(Line(2), Column(2)));
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::cmp::{max, min};
 use std::ops::{Index, IndexMut, Range};
+use std::path::Path;
 use std::sync::Arc;
 use std::{mem, ptr, str};
 
@@ -11,19 +12,28 @@ use serde::{Deserialize, Serialize};
 use unicode_width::UnicodeWidthChar;
 
 use crate::ansi::{
-    self, Attr, CharsetIndex, Color, CursorShape, CursorStyle, Handler, NamedColor, StandardCharset,
+    self, Attr, CharsetIndex, Color, CursorShape, CursorStyle, FileUrl, Handler, KeyboardModes,
+    KeyboardModesApplyBehavior, ModifyOtherKeys, NamedColor, Rectangle, ShellMark, StandardCharset,
+    StatusSetting,
 };
-use crate::config::Config;
-use crate::event::{Event, EventListener};
-use crate::grid::{Dimensions, Grid, GridIterator, Scroll};
-use crate::index::{self, Boundary, Column, Direction, Line, Point, Side};
-use crate::selection::{Selection, SelectionRange};
+use crate::config::{Config, Osc52};
+use crate::event::{Event, EventListener, UnhandledSequence};
+use crate::graphics::kitty::{self, Action as KittyAction};
+use crate::graphics::{GraphicCell, GraphicData, GraphicId, Graphics, UpdateQueues};
+use crate::grid::{Dimensions, Grid, GridIterator, LineMarks, MemoryUsage, Scroll};
+use crate::index::{Boundary, Column, Direction, Line, Point, Side};
+use crate::selection::{Selection, SelectionRange, SelectionType};
 use crate::term::cell::{Cell, Flags, LineLength};
 use crate::term::color::{Colors, Rgb};
-use crate::vi_mode::{ViModeCursor, ViMotion};
+use crate::tty;
+use crate::vi_mode::{TextObject, TextObjectScope, ViModeCursor, ViMotion};
 
 pub mod cell;
+#[cfg(feature = "debug_checks")]
+mod checks;
 pub mod color;
+pub mod lines;
+pub mod runs;
 pub mod search;
 
 /// Minimum number of columns.
@@ -37,32 +47,44 @@ pub const MIN_SCREEN_LINES: usize = 1;
 /// Max size of the window title stack.
 const TITLE_STACK_MAX_DEPTH: usize = 4096;
 
+/// Position of the kitty keyboard protocol modes in the terminal mode.
+const KEYBOARD_MODES_SHIFT: u32 = 18;
+
+/// Max size of the keyboard mode stack.
+const KEYBOARD_MODE_STACK_MAX_DEPTH: usize = TITLE_STACK_MAX_DEPTH;
+
 /// Default tab interval, corresponding to terminfo `it` value.
 const INITIAL_TABSTOPS: usize = 8;
 
 bitflags! {
     pub struct TermMode: u32 {
-        const NONE                = 0;
-        const SHOW_CURSOR         = 0b0000_0000_0000_0000_0001;
-        const APP_CURSOR          = 0b0000_0000_0000_0000_0010;
-        const APP_KEYPAD          = 0b0000_0000_0000_0000_0100;
-        const MOUSE_REPORT_CLICK  = 0b0000_0000_0000_0000_1000;
-        const BRACKETED_PASTE     = 0b0000_0000_0000_0001_0000;
-        const SGR_MOUSE           = 0b0000_0000_0000_0010_0000;
-        const MOUSE_MOTION        = 0b0000_0000_0000_0100_0000;
-        const LINE_WRAP           = 0b0000_0000_0000_1000_0000;
-        const LINE_FEED_NEW_LINE  = 0b0000_0000_0001_0000_0000;
-        const ORIGIN              = 0b0000_0000_0010_0000_0000;
-        const INSERT              = 0b0000_0000_0100_0000_0000;
-        const FOCUS_IN_OUT        = 0b0000_0000_1000_0000_0000;
-        const ALT_SCREEN          = 0b0000_0001_0000_0000_0000;
-        const MOUSE_DRAG          = 0b0000_0010_0000_0000_0000;
-        const MOUSE_MODE          = 0b0000_0010_0000_0100_1000;
-        const UTF8_MOUSE          = 0b0000_0100_0000_0000_0000;
-        const ALTERNATE_SCROLL    = 0b0000_1000_0000_0000_0000;
-        const VI                  = 0b0001_0000_0000_0000_0000;
-        const URGENCY_HINTS       = 0b0010_0000_0000_0000_0000;
-        const ANY                 = std::u32::MAX;
+        const NONE                    = 0;
+        const SHOW_CURSOR             = 0b0000_0000_0000_0000_0000_0001;
+        const APP_CURSOR              = 0b0000_0000_0000_0000_0000_0010;
+        const APP_KEYPAD              = 0b0000_0000_0000_0000_0000_0100;
+        const MOUSE_REPORT_CLICK      = 0b0000_0000_0000_0000_0000_1000;
+        const BRACKETED_PASTE         = 0b0000_0000_0000_0000_0001_0000;
+        const SGR_MOUSE               = 0b0000_0000_0000_0000_0010_0000;
+        const MOUSE_MOTION            = 0b0000_0000_0000_0000_0100_0000;
+        const LINE_WRAP               = 0b0000_0000_0000_0000_1000_0000;
+        const LINE_FEED_NEW_LINE      = 0b0000_0000_0000_0001_0000_0000;
+        const ORIGIN                  = 0b0000_0000_0000_0010_0000_0000;
+        const INSERT                  = 0b0000_0000_0000_0100_0000_0000;
+        const FOCUS_IN_OUT            = 0b0000_0000_0000_1000_0000_0000;
+        const ALT_SCREEN              = 0b0000_0000_0001_0000_0000_0000;
+        const MOUSE_DRAG              = 0b0000_0000_0010_0000_0000_0000;
+        const MOUSE_MODE              = 0b0000_0000_0010_0000_0100_1000;
+        const UTF8_MOUSE              = 0b0000_0000_0100_0000_0000_0000;
+        const ALTERNATE_SCROLL        = 0b0000_0000_1000_0000_0000_0000;
+        const VI                      = 0b0000_0001_0000_0000_0000_0000;
+        const URGENCY_HINTS           = 0b0000_0010_0000_0000_0000_0000;
+        const DISAMBIGUATE_ESC_CODES  = 0b0000_0100_0000_0000_0000_0000;
+        const REPORT_EVENT_TYPES      = 0b0000_1000_0000_0000_0000_0000;
+        const REPORT_ALTERNATE_KEYS   = 0b0001_0000_0000_0000_0000_0000;
+        const REPORT_ALL_KEYS_AS_ESC  = 0b0010_0000_0000_0000_0000_0000;
+        const KITTY_KEYBOARD_PROTOCOL = 0b0011_1100_0000_0000_0000_0000;
+        const SGR_PIXEL_MOUSE         = 0b0100_0000_0000_0000_0000_0000;
+        const ANY                     = std::u32::MAX;
     }
 }
 
@@ -75,6 +97,37 @@ impl Default for TermMode {
     }
 }
 
+impl From<KeyboardModes> for TermMode {
+    fn from(modes: KeyboardModes) -> TermMode {
+        TermMode::from_bits_truncate(u32::from(modes.bits()) << KEYBOARD_MODES_SHIFT)
+    }
+}
+
+/// Padding around the terminal grid in pixels.
+#[derive(Serialize, Deserialize, Default, Debug, Copy, Clone, PartialEq)]
+pub struct Padding {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Padding {
+    /// Padding with identical values for opposite edges.
+    pub fn symmetric(x: f32, y: f32) -> Self {
+        Self { top: y, right: x, bottom: y, left: x }
+    }
+}
+
+/// Rectangle in physical pixels, relative to the top-left corner of the window.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PixelRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
 /// Terminal size info.
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 pub struct SizeInfo {
@@ -90,12 +143,20 @@ pub struct SizeInfo {
     /// Height of individual cell.
     cell_height: f32,
 
-    /// Horizontal window padding.
+    /// Left window padding.
     padding_x: f32,
 
-    /// Horizontal window padding.
+    /// Top window padding.
     padding_y: f32,
 
+    /// Right window padding.
+    #[serde(default)]
+    padding_right: f32,
+
+    /// Bottom window padding.
+    #[serde(default)]
+    padding_bottom: f32,
+
     /// Number of lines in the viewport.
     screen_lines: usize,
 
@@ -110,19 +171,43 @@ impl SizeInfo {
         height: f32,
         cell_width: f32,
         cell_height: f32,
-        mut padding_x: f32,
-        mut padding_y: f32,
+        padding_x: f32,
+        padding_y: f32,
+        dynamic_padding: bool,
+    ) -> SizeInfo {
+        let padding = Padding::symmetric(padding_x, padding_y);
+        Self::with_padding(width, height, cell_width, cell_height, padding, dynamic_padding)
+    }
+
+    /// Create size info with independent padding for every edge.
+    pub fn with_padding(
+        width: f32,
+        height: f32,
+        cell_width: f32,
+        cell_height: f32,
+        mut padding: Padding,
         dynamic_padding: bool,
     ) -> SizeInfo {
         if dynamic_padding {
-            padding_x = Self::dynamic_padding(padding_x.floor(), width, cell_width);
-            padding_y = Self::dynamic_padding(padding_y.floor(), height, cell_height);
+            let (left, right) = Self::dynamic_padding(
+                padding.left.floor(),
+                padding.right.floor(),
+                width,
+                cell_width,
+            );
+            let (top, bottom) = Self::dynamic_padding(
+                padding.top.floor(),
+                padding.bottom.floor(),
+                height,
+                cell_height,
+            );
+            padding = Padding { top, right, bottom, left };
         }
 
-        let lines = (height - 2. * padding_y) / cell_height;
+        let lines = (height - padding.top - padding.bottom) / cell_height;
         let screen_lines = max(lines as usize, MIN_SCREEN_LINES);
 
-        let columns = (width - 2. * padding_x) / cell_width;
+        let columns = (width - padding.left - padding.right) / cell_width;
         let columns = max(columns as usize, MIN_COLUMNS);
 
         SizeInfo {
@@ -130,8 +215,10 @@ impl SizeInfo {
             height,
             cell_width,
             cell_height,
-            padding_x: padding_x.floor(),
-            padding_y: padding_y.floor(),
+            padding_x: padding.left.floor(),
+            padding_y: padding.top.floor(),
+            padding_right: padding.right.floor(),
+            padding_bottom: padding.bottom.floor(),
             screen_lines,
             columns,
         }
@@ -183,10 +270,67 @@ impl SizeInfo {
         self.padding_y
     }
 
+    #[inline]
+    pub fn padding_right(&self) -> f32 {
+        self.padding_right
+    }
+
+    #[inline]
+    pub fn padding_bottom(&self) -> f32 {
+        self.padding_bottom
+    }
+
+    /// Padding of all edges, including dynamic padding.
+    #[inline]
+    pub fn padding(&self) -> Padding {
+        Padding {
+            top: self.padding_y,
+            right: self.padding_right,
+            bottom: self.padding_bottom,
+            left: self.padding_x,
+        }
+    }
+
+    /// Magnify the grid without changing the number of lines and columns.
+    ///
+    /// The window dimensions stay the same, so parts of the magnified grid are outside of the
+    /// window. The offset is subtracted from all positions, which allows panning across the grid.
+    pub fn magnify(&self, factor_x: f32, factor_y: f32, offset_x: f32, offset_y: f32) -> SizeInfo {
+        let padding_x = (self.padding_x * factor_x - offset_x).floor();
+        let padding_y = (self.padding_y * factor_y - offset_y).floor();
+        let width = (self.width - self.padding_x - self.padding_right) * factor_x;
+        let height = (self.height - self.padding_y - self.padding_bottom) * factor_y;
+
+        SizeInfo {
+            cell_width: self.cell_width * factor_x,
+            cell_height: self.cell_height * factor_y,
+            padding_x,
+            padding_y,
+            padding_right: self.width - padding_x - width,
+            padding_bottom: self.height - padding_y - height,
+            ..*self
+        }
+    }
+
+    /// Pixel area covered by cells in a single line, starting at a viewport position.
+    ///
+    /// The viewport position is not required to be inside the grid, which allows computing the
+    /// location of lines below the grid, like the search bar.
+    #[inline]
+    pub fn cell_rect(&self, point: Point<usize>, columns: usize) -> PixelRect {
+        PixelRect {
+            x: self.padding_x + point.column.0 as f32 * self.cell_width,
+            y: self.padding_y + point.line as f32 * self.cell_height,
+            width: columns as f32 * self.cell_width,
+            height: self.cell_height,
+        }
+    }
+
     /// Calculate padding to spread it evenly around the terminal content.
     #[inline]
-    fn dynamic_padding(padding: f32, dimension: f32, cell_dimension: f32) -> f32 {
-        padding + ((dimension - 2. * padding) % cell_dimension) / 2.
+    fn dynamic_padding(start: f32, end: f32, dimension: f32, cell_dimension: f32) -> (f32, f32) {
+        let extra = ((dimension - start - end) % cell_dimension) / 2.;
+        (start + extra, end + extra)
     }
 }
 
@@ -228,9 +372,6 @@ pub struct Term<T> {
     /// primary grid. Otherwise it is the alternate screen buffer.
     inactive_grid: Grid<Cell>,
 
-    /// Index into `charsets`, pointing to what ASCII is currently being mapped to.
-    active_charset: CharsetIndex,
-
     /// Tabstops.
     tabs: TabStops,
 
@@ -262,13 +403,47 @@ pub struct Term<T> {
     /// Current title of the window.
     title: Option<String>,
 
+    /// Current icon name of the window.
+    icon_name: Option<String>,
+
     /// Stack of saved window titles. When a title is popped from this stack, the `title` for the
     /// term is set.
     title_stack: Vec<Option<String>>,
 
+    /// Stack of saved icon names.
+    icon_name_stack: Vec<Option<String>>,
+
+    /// Working directory reported by the shell.
+    working_directory: Option<FileUrl>,
+
+    /// Window name used for targeting IPC messages.
+    window_name: Option<String>,
+
+    /// Stack of kitty keyboard protocol modes, the last one is active.
+    keyboard_mode_stack: Vec<KeyboardModes>,
+
+    /// Keyboard mode stack of the inactive screen.
+    inactive_keyboard_mode_stack: Vec<KeyboardModes>,
+
+    /// Encoding of modified keys set through xterm's `modifyOtherKeys`.
+    modify_other_keys: ModifyOtherKeys,
+
     /// Information about cell dimensions.
     cell_width: usize,
     cell_height: usize,
+
+    /// Inline graphics shown in the grid.
+    graphics: Graphics,
+
+    /// Clipboard access through OSC 52.
+    osc52: Osc52,
+
+    /// Save lines scrolled out of partial scrolling regions in the history.
+    region_history: bool,
+
+    /// Grid invariant violations which have already been reported.
+    #[cfg(feature = "debug_checks")]
+    violations: Vec<checks::Violation>,
 }
 
 impl<T> Term<T> {
@@ -303,7 +478,6 @@ impl<T> Term<T> {
         Term {
             grid,
             inactive_grid: alt,
-            active_charset: Default::default(),
             vi_mode_cursor: Default::default(),
             tabs,
             mode: Default::default(),
@@ -316,10 +490,22 @@ impl<T> Term<T> {
             event_proxy,
             is_focused: true,
             title: None,
+            icon_name: None,
             title_stack: Vec::new(),
+            icon_name_stack: Vec::new(),
+            working_directory: None,
+            window_name: None,
+            keyboard_mode_stack: Vec::new(),
+            inactive_keyboard_mode_stack: Vec::new(),
+            modify_other_keys: ModifyOtherKeys::default(),
             selection: None,
             cell_width: size.cell_width as usize,
             cell_height: size.cell_height as usize,
+            graphics: Graphics::default(),
+            osc52: config.osc52,
+            region_history: config.scrolling.region_history,
+            #[cfg(feature = "debug_checks")]
+            violations: Vec::new(),
         }
     }
 
@@ -330,6 +516,8 @@ impl<T> Term<T> {
         self.semantic_escape_chars = config.selection.semantic_escape_chars.to_owned();
         self.default_cursor_style = config.cursor.style();
         self.vi_mode_cursor_style = config.cursor.vi_mode_style();
+        self.osc52 = config.osc52;
+        self.region_history = config.scrolling.region_history;
 
         let title_event = match &self.title {
             Some(title) => Event::Title(title.clone()),
@@ -338,6 +526,13 @@ impl<T> Term<T> {
 
         self.event_proxy.send_event(title_event);
 
+        let icon_name_event = match &self.icon_name {
+            Some(icon_name) => Event::IconName(icon_name.clone()),
+            None => Event::ResetIconName,
+        };
+
+        self.event_proxy.send_event(icon_name_event);
+
         if self.mode.contains(TermMode::ALT_SCREEN) {
             self.inactive_grid.update_history(config.scrolling.history() as usize);
         } else {
@@ -345,6 +540,27 @@ impl<T> Term<T> {
         }
     }
 
+    /// Start a new selection, replacing the active one.
+    ///
+    /// The selection's type controls how it is expanded, see [`Selection`] for details.
+    pub fn start_selection(&mut self, ty: SelectionType, point: Point, side: Side) {
+        self.selection = Some(Selection::new(ty, point, side));
+    }
+
+    /// Move the end of the active selection.
+    ///
+    /// This does nothing if there is no active selection.
+    pub fn update_selection(&mut self, point: Point, side: Side) {
+        if let Some(selection) = &mut self.selection {
+            selection.update(point, side);
+        }
+    }
+
+    /// Remove the active selection.
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
     /// Convert the active selection to a String.
     pub fn selection_to_string(&self) -> Option<String> {
         let selection_range = self.selection.as_ref().and_then(|s| s.to_range(self))?;
@@ -383,6 +599,55 @@ impl<T> Term<T> {
         res
     }
 
+    /// Convert range between two points to a String with ANSI escapes for all cell attributes.
+    pub fn bounds_to_escaped_string(&self, start: Point, end: Point) -> String {
+        let default = Cell::default();
+        let mut last = &default;
+        let mut res = String::new();
+
+        for line in (start.line.0..=end.line.0).map(Line::from) {
+            let grid_line = &self.grid[line];
+            let start_col = if line == start.line { start.column } else { Column(0) };
+            let end_col = if line == end.line { end.column } else { self.last_column() };
+            let line_length = min(grid_line.line_length(), end_col + 1);
+
+            let mut tab_mode = false;
+            for column in (start_col.0..line_length.0).map(Column::from) {
+                let cell = &grid_line[column];
+
+                // Skip over cells until next tab-stop once a tab was found.
+                if tab_mode {
+                    if self.tabs[column] {
+                        tab_mode = false;
+                    } else {
+                        continue;
+                    }
+                }
+
+                if cell.c == '\t' {
+                    tab_mode = true;
+                }
+
+                cell.as_escape(&mut res, last);
+                last = cell;
+            }
+
+            if end_col >= self.last_column()
+                && (line_length.0 == 0
+                    || !grid_line[line_length - 1].flags.contains(Flags::WRAPLINE))
+            {
+                res.push('\n');
+            }
+        }
+
+        // Reset attributes to avoid leaking them into the consumer's terminal.
+        if last.fg != default.fg || last.bg != default.bg || !last.sgr_flags().is_empty() {
+            res.push_str("\x1b[0m");
+        }
+
+        res
+    }
+
     /// Convert a single line in the grid to a String.
     fn line_to_string(
         &self,
@@ -447,6 +712,77 @@ impl<T> Term<T> {
         text
     }
 
+    /// Title set by the application running inside the terminal.
+    #[inline]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Icon name set by the application running inside the terminal.
+    #[inline]
+    pub fn icon_name(&self) -> Option<&str> {
+        self.icon_name.as_deref()
+    }
+
+    /// Working directory reported by the shell using `OSC 7`.
+    ///
+    /// Directories on other hosts, like those reported over SSH, are ignored.
+    pub fn working_directory(&self) -> Option<&Path> {
+        let url = self.working_directory.as_ref()?;
+        if url.is_local(tty::hostname().as_deref()) {
+            Some(&url.path)
+        } else {
+            None
+        }
+    }
+
+    /// Window name set by the user or using `OSC 1337`.
+    #[inline]
+    pub fn window_name(&self) -> Option<&str> {
+        self.window_name.as_deref()
+    }
+
+    /// Find the closest prompt above or below a line, using the `OSC 133` shell marks.
+    pub fn prompt_line(&self, line: Line, direction: Direction) -> Option<Line> {
+        let is_prompt = |line: &Line| self.grid[*line].marks().contains(LineMarks::PROMPT_START);
+
+        match direction {
+            Direction::Left => (self.topmost_line().0..line.0).rev().map(Line).find(is_prompt),
+            Direction::Right => (line.0 + 1..=self.bottommost_line().0).map(Line).find(is_prompt),
+        }
+    }
+
+    /// First and last line of the most recent command output, using the `OSC 133` shell marks.
+    pub fn last_command_output(&self) -> Option<(Line, Line)> {
+        let bottommost_line = self.bottommost_line();
+        let start = (self.topmost_line().0..=bottommost_line.0)
+            .rev()
+            .map(Line)
+            .find(|line| self.grid[*line].marks().contains(LineMarks::OUTPUT_START))?;
+
+        // Output ends before the end mark or the next prompt, or at the cursor while running.
+        let end = (start.0..=bottommost_line.0)
+            .map(Line)
+            .find(|line| {
+                let marks = self.grid[*line].marks();
+                marks.contains(LineMarks::COMMAND_END)
+                    || (*line > start && marks.contains(LineMarks::PROMPT_START))
+            })
+            .map_or(self.grid.cursor.point.line, |line| {
+                if self.grid[line].marks().contains(LineMarks::OUTPUT_END) {
+                    line
+                } else {
+                    line - 1
+                }
+            });
+
+        if end < start {
+            None
+        } else {
+            Some((start, end))
+        }
+    }
+
     /// Terminal content required for rendering.
     #[inline]
     pub fn renderable_content(&self) -> RenderableContent<'_>
@@ -464,6 +800,34 @@ impl<T> Term<T> {
         &self.grid
     }
 
+    /// Graphics which were added or removed since the last call.
+    #[inline]
+    pub fn graphics_take_queues(&mut self) -> Option<UpdateQueues> {
+        self.graphics.take_queues()
+    }
+
+    /// Colors changed by escape sequences, overriding the configured colors.
+    pub fn colors(&self) -> &Colors {
+        &self.colors
+    }
+
+    /// Estimate the memory used by the primary and alternate screen.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let grid = self.grid.memory_usage();
+        let inactive_grid = self.inactive_grid.memory_usage();
+
+        MemoryUsage {
+            visible: grid.visible + inactive_grid.visible,
+            history: grid.history + inactive_grid.history,
+        }
+    }
+
+    /// Free memory which is not required for storing the terminal's content.
+    pub fn shrink_to_fit(&mut self) {
+        self.grid.shrink_to_fit();
+        self.inactive_grid.shrink_to_fit();
+    }
+
     /// Mutable access for swapping out the grid during tests.
     #[cfg(test)]
     pub fn grid_mut(&mut self) -> &mut Grid<Cell> {
@@ -525,14 +889,33 @@ impl<T> Term<T> {
         &self.mode
     }
 
+    /// Encoding of modified keys requested by the application.
+    #[inline]
+    pub fn modify_other_keys(&self) -> ModifyOtherKeys {
+        self.modify_other_keys
+    }
+
+    /// Lines affected by scrolling, as set by DECSTBM.
+    #[inline]
+    pub fn scroll_region(&self) -> &Range<Line> {
+        &self.scroll_region
+    }
+
+    /// Columns with a tab stop.
+    pub fn tab_stops(&self) -> impl Iterator<Item = Column> + '_ {
+        (0..self.columns()).map(Column).filter(move |&column| self.tabs[column])
+    }
+
     /// Swap primary and alternate screen buffer.
     pub fn swap_alt(&mut self) {
-        if !self.mode.contains(TermMode::ALT_SCREEN) {
+        let leaving_alt = self.mode.contains(TermMode::ALT_SCREEN);
+
+        if !leaving_alt {
             // Set alt screen cursor to the current primary screen cursor.
             self.inactive_grid.cursor = self.grid.cursor.clone();
 
             // Drop information about the primary screens saved cursor.
-            self.grid.saved_cursor = self.grid.cursor.clone();
+            self.save_cursor();
 
             // Reset alternate screen contents.
             self.inactive_grid.reset_region(..);
@@ -541,6 +924,28 @@ impl<T> Term<T> {
         mem::swap(&mut self.grid, &mut self.inactive_grid);
         self.mode ^= TermMode::ALT_SCREEN;
         self.selection = None;
+
+        // Both screens have their own keyboard modes.
+        mem::swap(&mut self.keyboard_mode_stack, &mut self.inactive_keyboard_mode_stack);
+        self.apply_keyboard_modes();
+
+        // Restore the origin mode saved when entering the alternate screen.
+        if leaving_alt {
+            self.mode.set(TermMode::ORIGIN, self.grid.saved_cursor.origin);
+        }
+    }
+
+    /// Update the terminal mode to the keyboard modes on top of the stack.
+    fn apply_keyboard_modes(&mut self) {
+        let modes = self.keyboard_mode_stack.last().copied().unwrap_or(KeyboardModes::NO_MODE);
+        self.mode.remove(TermMode::KITTY_KEYBOARD_PROTOCOL);
+        self.mode.insert(TermMode::from(modes));
+    }
+
+    /// Save the active cursor state of the current screen.
+    fn save_cursor(&mut self) {
+        self.grid.saved_cursor = self.grid.cursor.clone();
+        self.grid.saved_cursor.origin = self.mode.contains(TermMode::ORIGIN);
     }
 
     /// Scroll screen down.
@@ -580,15 +985,23 @@ impl<T> Term<T> {
         self.selection = self.selection.take().and_then(|s| s.rotate(self, &region, lines as i32));
 
         // Scroll from origin to bottom less number of lines.
-        self.grid.scroll_up(&region, lines);
+        let full_screen = region.start == 0 && region.end == self.screen_lines();
+        if full_screen || self.region_history {
+            self.grid.scroll_up(&region, lines);
+        } else {
+            self.grid.scroll_up_in_region(&region, lines);
+        }
     }
 
-    fn deccolm(&mut self)
+    /// Switch between 80 and 132 column mode (DECCOLM).
+    fn deccolm(&mut self, columns: usize)
     where
         T: EventListener,
     {
-        // Setting 132 column font makes no sense, but run the other side effects.
-        // Clear scrolling region.
+        // The terminal can't change its own size, so ask the UI to resize the window.
+        self.event_proxy.send_event(Event::ColumnMode(columns));
+
+        // Clear scrolling region and move the cursor home.
         self.set_scrolling_region(1, None);
 
         // Clear grid.
@@ -658,6 +1071,29 @@ impl<T> Term<T> {
         self.vi_mode_recompute_selection();
     }
 
+    /// Select a text object around the vi mode cursor.
+    pub fn vi_select_text_object(&mut self, object: TextObject, scope: TextObjectScope)
+    where
+        T: EventListener,
+    {
+        // Require vi mode to be active.
+        if !self.mode.contains(TermMode::VI) {
+            return;
+        }
+
+        let bounds = match object.bounds(self, self.vi_mode_cursor.point, scope) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let mut selection = Selection::new(SelectionType::Simple, *bounds.start(), Side::Left);
+        selection.update(*bounds.end(), Side::Right);
+        self.selection = Some(selection);
+
+        // Move the cursor to the end of the object, like vi's visual mode.
+        self.vi_goto_point(*bounds.end());
+    }
+
     /// Update the active selection to match the vi mode cursor position.
     #[inline]
     fn vi_mode_recompute_selection(&mut self) {
@@ -723,6 +1159,38 @@ impl<T> Term<T> {
         &self.semantic_escape_chars
     }
 
+    /// Pixel area covered by the terminal cursor.
+    ///
+    /// The rectangle spans both cells of wide characters and is independent of the cursor's
+    /// visibility, so it can be used to position the IME or popups anchored to the cursor.
+    ///
+    /// Returns `None` when the cursor is scrolled out of the viewport.
+    pub fn cursor_rect(&self, size: &SizeInfo) -> Option<PixelRect> {
+        let point = self.grid.cursor.point;
+        let line = point.line.0 + self.grid.display_offset() as i32;
+        if line < 0 || line >= self.screen_lines() as i32 {
+            return None;
+        }
+
+        let is_wide = self.grid[point].flags.contains(Flags::WIDE_CHAR);
+        let columns = if is_wide { 2 } else { 1 };
+
+        Some(size.cell_rect(Point::new(line as usize, point.column), columns))
+    }
+
+    /// Pixel area of preedit text with a width in cells, inserted at the terminal cursor.
+    ///
+    /// The area is clamped to the end of the cursor's line. Returns `None` when the cursor is
+    /// scrolled out of the viewport.
+    pub fn preedit_rect(&self, size: &SizeInfo, width: usize) -> Option<PixelRect> {
+        let mut rect = self.cursor_rect(size)?;
+
+        let available = self.columns() - self.grid.cursor.point.column.0;
+        rect.width = min(width, available) as f32 * size.cell_width();
+
+        Some(rect)
+    }
+
     /// Active terminal cursor style.
     ///
     /// While vi mode is active, this will automatically return the vi mode cursor style.
@@ -761,84 +1229,368 @@ impl<T> Term<T> {
         self.grid.cursor.input_needs_wrap = false;
     }
 
-    /// Write `c` to the cell at the cursor position.
-    #[inline(always)]
-    fn write_at_cursor(&mut self, c: char) {
-        let c = self.grid.cursor.charsets[self.active_charset].map(c);
-        let fg = self.grid.cursor.template.fg;
-        let bg = self.grid.cursor.template.bg;
-        let flags = self.grid.cursor.template.flags;
+    /// Cover the cells starting at the cursor position with a graphic.
+    ///
+    /// The cursor is left on the last line of the graphic, at the column where it started. When
+    /// `scroll` is disabled, lines of the graphic below the bottom of the screen are cut off.
+    ///
+    /// Returns the graphic's ID and the number of columns it covers.
+    fn place_graphic(&mut self, graphic: GraphicData, scroll: bool) -> Option<(GraphicId, usize)>
+    where
+        T: EventListener,
+    {
+        let (cell_width, cell_height) = (self.cell_width, self.cell_height);
+        if cell_width == 0 || cell_height == 0 {
+            return None;
+        }
 
-        let mut cursor_cell = self.grid.cursor_cell();
+        let left = self.grid.cursor.point.column;
+        let columns = min((graphic.width + cell_width - 1) / cell_width, self.columns() - left.0);
+        let lines = (graphic.height + cell_height - 1) / cell_height;
 
-        // Clear all related cells when overwriting a fullwidth cell.
-        if cursor_cell.flags.intersects(Flags::WIDE_CHAR | Flags::WIDE_CHAR_SPACER) {
-            // Remove wide char and spacer.
-            let wide = cursor_cell.flags.contains(Flags::WIDE_CHAR);
-            let point = self.grid.cursor.point;
-            if wide && point.column + 1 < self.columns() {
-                self.grid[point.line][point.column + 1].flags.remove(Flags::WIDE_CHAR_SPACER);
-            } else {
-                self.grid[point.line][point.column - 1].clear_wide();
+        let texture = self.graphics.add(graphic, cell_width, cell_height);
+        let bg = self.grid.cursor.template.bg;
+
+        for line in 0..lines {
+            if line > 0 {
+                if scroll {
+                    self.linefeed();
+                } else if self.grid.cursor.point.line + 1 < self.screen_lines() {
+                    self.grid.cursor.point.line += 1;
+                } else {
+                    break;
+                }
             }
 
-            // Remove leading spacers.
-            if point.column <= 1 && point.line != self.topmost_line() {
-                let column = self.last_column();
-                self.grid[point.line - 1i32][column].flags.remove(Flags::LEADING_WIDE_CHAR_SPACER);
+            let cursor_line = self.grid.cursor.point.line;
+
+            // Clear wide characters which are only partially covered by the graphic.
+            let row = &mut self.grid[cursor_line];
+            if left.0 > 0 && row[left].flags.contains(Flags::WIDE_CHAR_SPACER) {
+                row[left - 1].clear_wide();
+            }
+            let right = left + columns;
+            let covers_wide = columns > 0 && row[right - 1].flags.contains(Flags::WIDE_CHAR);
+            if covers_wide && right.0 < row.len() {
+                row[right].flags.remove(Flags::WIDE_CHAR_SPACER);
             }
 
-            cursor_cell = self.grid.cursor_cell();
+            for column in 0..columns {
+                let cell = &mut self.grid[cursor_line][left + column];
+                *cell = Cell::default();
+                cell.bg = bg;
+                cell.set_graphic(GraphicCell {
+                    texture: texture.clone(),
+                    offset_x: (column * cell_width) as u16,
+                    offset_y: (line * cell_height) as u16,
+                });
+            }
         }
 
-        cursor_cell.drop_extra();
-
-        cursor_cell.c = c;
-        cursor_cell.fg = fg;
-        cursor_cell.bg = bg;
-        cursor_cell.flags = flags;
+        Some((texture.id, columns))
     }
-}
 
-impl<T> Dimensions for Term<T> {
-    #[inline]
-    fn columns(&self) -> usize {
-        self.grid.columns()
-    }
+    /// Execute a complete kitty graphics command.
+    fn kitty_graphics_command(&mut self, command: &mut kitty::Command) -> Result<(), kitty::Error>
+    where
+        T: EventListener,
+    {
+        match command.action {
+            KittyAction::Query => command.decode().map(drop),
+            KittyAction::Transmit | KittyAction::TransmitAndDisplay => {
+                let graphic = command.decode()?;
 
-    #[inline]
-    fn screen_lines(&self) -> usize {
-        self.grid.screen_lines()
-    }
+                if command.image_id == 0 && command.image_number != 0 {
+                    command.image_id = self.graphics.kitty.next_id();
+                }
 
-    #[inline]
-    fn total_lines(&self) -> usize {
-        self.grid.total_lines()
-    }
-}
+                let placement = if command.action == KittyAction::TransmitAndDisplay {
+                    self.place_kitty_graphic(command, &graphic)?
+                } else {
+                    None
+                };
 
-impl<T: EventListener> Handler for Term<T> {
-    /// A character to be displayed.
-    #[inline(never)]
-    fn input(&mut self, c: char) {
-        // Number of cells the char will occupy.
-        let width = match c.width() {
-            Some(width) => width,
-            None => return,
-        };
+                if command.image_id != 0 {
+                    self.graphics.kitty.insert(command.image_id, graphic);
+                }
 
-        // Handle zero-width characters.
-        if width == 0 {
-            // Get previous column.
-            let mut column = self.grid.cursor.point.column;
-            if !self.grid.cursor.input_needs_wrap {
-                column.0 = column.saturating_sub(1);
-            }
+                if let Some(placement) = placement {
+                    self.graphics.kitty.add_placement(command.image_id, placement);
+                }
 
-            // Put zerowidth characters over first fullwidth character cell.
-            let line = self.grid.cursor.point.line;
-            if self.grid[line][column].flags.contains(Flags::WIDE_CHAR_SPACER) {
-                column.0 = column.saturating_sub(1);
+                Ok(())
+            },
+            KittyAction::Display => {
+                let graphic = match self.graphics.kitty.image(command.image_id) {
+                    Some(graphic) => graphic.clone(),
+                    None => return Err(kitty::Error::NotFound),
+                };
+
+                if let Some(placement) = self.place_kitty_graphic(command, &graphic)? {
+                    self.graphics.kitty.add_placement(command.image_id, placement);
+                }
+
+                Ok(())
+            },
+            KittyAction::Delete => {
+                self.delete_kitty_graphics(command);
+                Ok(())
+            },
+        }
+    }
+
+    /// Display a kitty graphics image at the cursor position.
+    ///
+    /// Returns the ID of the graphic created for the placement.
+    fn place_kitty_graphic(
+        &mut self,
+        command: &kitty::Command,
+        graphic: &GraphicData,
+    ) -> Result<Option<GraphicId>, kitty::Error>
+    where
+        T: EventListener,
+    {
+        let graphic = command.placement(graphic, self.cell_width, self.cell_height)?;
+
+        let origin = self.grid.cursor.point;
+        let (id, columns) = match self.place_graphic(graphic, command.move_cursor) {
+            Some(placement) => placement,
+            None => return Ok(None),
+        };
+
+        // Continue to the right of the graphic, on its last line.
+        if !command.move_cursor {
+            self.grid.cursor.point = origin;
+        } else if origin.column + columns < self.columns() {
+            self.grid.cursor.point.column = origin.column + columns;
+        } else {
+            self.grid.cursor.point.column = self.last_column();
+            self.grid.cursor.input_needs_wrap = true;
+        }
+
+        Ok(Some(id))
+    }
+
+    /// Remove kitty graphics images from the grid.
+    ///
+    /// Lowercase delete specifiers only remove the images from the grid, while uppercase ones
+    /// also free the stored image data.
+    fn delete_kitty_graphics(&mut self, command: &kitty::Command) {
+        let (ids, top) = match command.delete {
+            // Delete all images visible on the screen.
+            b'a' | b'A' => (self.graphics.kitty.all_placements(), Line(0)),
+            // Delete all images with the specified ID, including the scrollback history.
+            b'i' | b'I' => (self.graphics.kitty.placements(command.image_id), self.topmost_line()),
+            delete => {
+                debug!("Unsupported kitty graphics delete specifier: {:?}", delete as char);
+                return;
+            },
+        };
+
+        for line in top.0..=self.bottommost_line().0 {
+            for cell in self.grid[Line(line)][..].iter_mut() {
+                if cell.graphic().map_or(false, |graphic| ids.contains(&graphic.texture.id)) {
+                    let bg = cell.bg;
+                    *cell = Cell::default();
+                    cell.bg = bg;
+                }
+            }
+        }
+
+        match command.delete {
+            b'A' => self.graphics.kitty.clear(),
+            b'I' => self.graphics.kitty.remove(command.image_id),
+            _ => (),
+        }
+    }
+
+    /// Convert a rectangle to its top left and bottom right point, clamped to the screen.
+    ///
+    /// Returns `None` if the rectangle is empty.
+    fn rectangle_bounds(&self, rectangle: Rectangle) -> Option<(Point, Point)> {
+        let (line_offset, max_line) = if self.mode.contains(TermMode::ORIGIN) {
+            (self.scroll_region.start, self.scroll_region.end - 1)
+        } else {
+            (Line(0), self.bottommost_line())
+        };
+        let last_column = self.last_column();
+
+        let to_line = |line: usize| min(line_offset + (line - 1), max_line);
+        let top = to_line(rectangle.top);
+        let bottom = rectangle.bottom.map_or(max_line, to_line);
+        let left = min(Column(rectangle.left - 1), last_column);
+        let right =
+            rectangle.right.map_or(last_column, |right| min(Column(right - 1), last_column));
+
+        if top > bottom || left > right {
+            return None;
+        }
+
+        Some((Point::new(top, left), Point::new(bottom, right)))
+    }
+
+    /// Remove wide chars and line wraps broken by modifying a rectangular area.
+    fn repair_rectangle_edges(&mut self, start: Point, end: Point) {
+        let last_column = self.last_column();
+
+        for line in (start.line.0..=end.line.0).map(Line::from) {
+            let row = &mut self.grid[line];
+
+            // Line wraps copied away from the last column.
+            for column in (start.column.0..min(end.column.0 + 1, last_column.0)).map(Column) {
+                row[column].flags.remove(Flags::WRAPLINE | Flags::LEADING_WIDE_CHAR_SPACER);
+            }
+
+            // Wide chars split at the left edge.
+            let left = start.column;
+            if row[left].flags.contains(Flags::WIDE_CHAR_SPACER)
+                && (left == 0 || !row[left - 1].flags.contains(Flags::WIDE_CHAR))
+            {
+                row[left].flags.remove(Flags::WIDE_CHAR_SPACER);
+                row[left].c = ' ';
+            }
+            if left > 0
+                && row[left - 1].flags.contains(Flags::WIDE_CHAR)
+                && !row[left].flags.contains(Flags::WIDE_CHAR_SPACER)
+            {
+                row[left - 1].clear_wide();
+            }
+
+            // Wide chars split at the right edge.
+            let right = end.column;
+            if row[right].flags.contains(Flags::WIDE_CHAR)
+                && (right == last_column || !row[right + 1].flags.contains(Flags::WIDE_CHAR_SPACER))
+            {
+                row[right].clear_wide();
+            }
+            if right < last_column
+                && row[right + 1].flags.contains(Flags::WIDE_CHAR_SPACER)
+                && !row[right].flags.contains(Flags::WIDE_CHAR)
+            {
+                row[right + 1].flags.remove(Flags::WIDE_CHAR_SPACER);
+                row[right + 1].c = ' ';
+            }
+        }
+    }
+
+    /// Clear all cells between two points which are not protected by DECSCA.
+    ///
+    /// Both points are inclusive. Wide chars are always cleared together with their spacer, even
+    /// if only one of them is within the cleared area.
+    fn clear_unprotected(&mut self, start: Point, end: Point) {
+        let bg = self.grid.cursor.template.bg;
+        let last_column = self.last_column();
+
+        for line in (start.line.0..=end.line.0).map(Line::from) {
+            let row = &mut self.grid[line];
+
+            let mut start_column = if line == start.line { start.column } else { Column(0) };
+            if start_column > 0 && row[start_column].flags.contains(Flags::WIDE_CHAR_SPACER) {
+                start_column -= 1;
+            }
+
+            let mut end_column = if line == end.line { end.column } else { last_column };
+            if end_column < last_column && row[end_column].flags.contains(Flags::WIDE_CHAR) {
+                end_column += 1;
+            }
+
+            let mut protected = false;
+            for cell in &mut row[start_column..end_column + 1] {
+                // Spacers share the protection of their wide char.
+                if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    protected = cell.flags.contains(Flags::PROTECTED);
+                }
+
+                if !protected {
+                    *cell = bg.into();
+                }
+            }
+        }
+
+        self.grid.cursor.input_needs_wrap = false;
+
+        let range = start.line..=end.line;
+        self.selection = self.selection.take().filter(|s| !s.intersects_range(range));
+    }
+
+    /// Write `c` to the cell at the cursor position.
+    #[inline(always)]
+    fn write_at_cursor(&mut self, c: char) {
+        let c = self.grid.cursor.charsets[self.grid.cursor.active_charset].map(c);
+        let fg = self.grid.cursor.template.fg;
+        let bg = self.grid.cursor.template.bg;
+        let flags = self.grid.cursor.template.flags;
+
+        let mut cursor_cell = self.grid.cursor_cell();
+
+        // Clear all related cells when overwriting a fullwidth cell.
+        if cursor_cell.flags.intersects(Flags::WIDE_CHAR | Flags::WIDE_CHAR_SPACER) {
+            // Remove wide char and spacer.
+            let wide = cursor_cell.flags.contains(Flags::WIDE_CHAR);
+            let point = self.grid.cursor.point;
+            if wide && point.column + 1 < self.columns() {
+                self.grid[point.line][point.column + 1].flags.remove(Flags::WIDE_CHAR_SPACER);
+            } else {
+                self.grid[point.line][point.column - 1].clear_wide();
+            }
+
+            // Remove leading spacers.
+            if point.column <= 1 && point.line != self.topmost_line() {
+                let column = self.last_column();
+                self.grid[point.line - 1i32][column].flags.remove(Flags::LEADING_WIDE_CHAR_SPACER);
+            }
+
+            cursor_cell = self.grid.cursor_cell();
+        }
+
+        cursor_cell.drop_extra();
+
+        cursor_cell.c = c;
+        cursor_cell.fg = fg;
+        cursor_cell.bg = bg;
+        cursor_cell.flags = flags;
+    }
+}
+
+impl<T> Dimensions for Term<T> {
+    #[inline]
+    fn columns(&self) -> usize {
+        self.grid.columns()
+    }
+
+    #[inline]
+    fn screen_lines(&self) -> usize {
+        self.grid.screen_lines()
+    }
+
+    #[inline]
+    fn total_lines(&self) -> usize {
+        self.grid.total_lines()
+    }
+}
+
+impl<T: EventListener> Handler for Term<T> {
+    /// A character to be displayed.
+    #[inline(never)]
+    fn input(&mut self, c: char) {
+        // Number of cells the char will occupy.
+        let width = match c.width() {
+            Some(width) => width,
+            None => return,
+        };
+
+        // Handle zero-width characters.
+        if width == 0 {
+            // Get previous column.
+            let mut column = self.grid.cursor.point.column;
+            if !self.grid.cursor.input_needs_wrap {
+                column.0 = column.saturating_sub(1);
+            }
+
+            // Put zerowidth characters over first fullwidth character cell.
+            let line = self.grid.cursor.point.line;
+            if self.grid[line][column].flags.contains(Flags::WIDE_CHAR_SPACER) {
+                column.0 = column.saturating_sub(1);
             }
 
             self.grid[line][column].push_zerowidth(c);
@@ -961,6 +1713,8 @@ impl<T: EventListener> Handler for Term<T> {
         for cell in &mut row[source.0..destination] {
             *cell = bg.into();
         }
+
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
@@ -1050,7 +1804,7 @@ impl<T: EventListener> Handler for Term<T> {
         while self.grid.cursor.point.column < self.columns() && count != 0 {
             count -= 1;
 
-            let c = self.grid.cursor.charsets[self.active_charset].map('\t');
+            let c = self.grid.cursor.charsets[self.grid.cursor.active_charset].map('\t');
             let cell = self.grid.cursor_cell();
             if cell.c == ' ' {
                 cell.c = c;
@@ -1099,6 +1853,8 @@ impl<T: EventListener> Handler for Term<T> {
         } else if next < self.screen_lines() {
             self.grid.cursor.point.line += 1;
         }
+
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     /// Set current position as a tabstop.
@@ -1166,9 +1922,13 @@ impl<T: EventListener> Handler for Term<T> {
     fn insert_blank_lines(&mut self, lines: usize) {
         trace!("Inserting blank {} lines", lines);
 
+        // Lines can only be inserted inside the scrolling region.
         let origin = self.grid.cursor.point.line;
         if self.scroll_region.contains(&origin) {
             self.scroll_down_relative(origin, lines);
+
+            self.grid.cursor.point.column = Column(0);
+            self.grid.cursor.input_needs_wrap = false;
         }
     }
 
@@ -1179,8 +1939,12 @@ impl<T: EventListener> Handler for Term<T> {
 
         trace!("Deleting {} lines", lines);
 
+        // Lines can only be deleted inside the scrolling region.
         if lines > 0 && self.scroll_region.contains(&origin) {
             self.scroll_up_relative(origin, lines);
+
+            self.grid.cursor.point.column = Column(0);
+            self.grid.cursor.input_needs_wrap = false;
         }
     }
 
@@ -1200,6 +1964,75 @@ impl<T: EventListener> Handler for Term<T> {
         for cell in &mut row[start..end] {
             *cell = bg.into();
         }
+
+        self.grid.cursor.input_needs_wrap = false;
+    }
+
+    fn copy_rectangle(&mut self, rectangle: Rectangle, line: usize, column: usize) {
+        trace!("Copying rectangle {:?} to line={}, column={}", rectangle, line, column);
+
+        let (start, end) = match self.rectangle_bounds(rectangle) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let destination = Rectangle { top: line, left: column, bottom: None, right: None };
+        let destination = match self.rectangle_bounds(destination) {
+            Some((destination, _)) => destination,
+            None => return,
+        };
+
+        // Clip the copied area to the screen, or the scrolling region in origin mode.
+        let max_line = if self.mode.contains(TermMode::ORIGIN) {
+            self.scroll_region.end - 1
+        } else {
+            self.bottommost_line()
+        };
+        let lines = min(end.line - start.line, max_line - destination.line);
+        let columns = min(end.column - start.column, self.last_column() - destination.column);
+        let end = Point::new(start.line + lines, start.column + columns);
+
+        self.grid.copy_region(start, end, destination);
+
+        let destination_end = Point::new(destination.line + lines, destination.column + columns);
+        self.repair_rectangle_edges(destination, destination_end);
+    }
+
+    fn fill_rectangle(&mut self, c: char, rectangle: Rectangle) {
+        trace!("Filling rectangle {:?} with {:?}", rectangle, c);
+
+        let (start, end) = match self.rectangle_bounds(rectangle) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        // Fill with the current attributes, ignoring the temporary wide char flags.
+        let template = &self.grid.cursor.template;
+        let wide_flags =
+            Flags::WIDE_CHAR | Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
+        let flags = template.flags & !(wide_flags | Flags::WRAPLINE);
+        let mut cell = Cell::from(template.bg);
+        cell.c = c;
+        cell.fg = template.fg;
+        cell.flags = flags;
+
+        self.grid.fill_region(start, end, &cell);
+        self.repair_rectangle_edges(start, end);
+    }
+
+    fn erase_rectangle(&mut self, rectangle: Rectangle) {
+        trace!("Erasing rectangle {:?}", rectangle);
+
+        let (start, end) = match self.rectangle_bounds(rectangle) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        // Cleared cells have current background color set.
+        let cell = Cell::from(self.grid.cursor.template.bg);
+
+        self.grid.fill_region(start, end, &cell);
+        self.repair_rectangle_edges(start, end);
     }
 
     #[inline]
@@ -1209,10 +2042,10 @@ impl<T: EventListener> Handler for Term<T> {
         let bg = cursor.template.bg;
 
         // Ensure deleting within terminal bounds.
-        let count = min(count, columns);
-
         let start = cursor.point.column.0;
-        let end = min(start + count, columns - 1);
+        let count = min(count, columns - start);
+
+        let end = start + count;
         let num_cells = columns - end;
 
         let line = cursor.point.line;
@@ -1228,34 +2061,46 @@ impl<T: EventListener> Handler for Term<T> {
         for cell in &mut row[end..] {
             *cell = bg.into();
         }
+
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
     fn move_backward_tabs(&mut self, count: u16) {
         trace!("Moving backward {} tabs", count);
 
+        // Without any previous tab stop, the cursor stops at the first column.
         for _ in 0..count {
-            let mut col = self.grid.cursor.point.column;
-            for i in (0..(col.0)).rev() {
-                if self.tabs[index::Column(i)] {
-                    col = index::Column(i);
-                    break;
-                }
-            }
-            self.grid.cursor.point.column = col;
+            let col = self.grid.cursor.point.column;
+            self.grid.cursor.point.column =
+                (0..col.0).rev().map(Column).find(|&i| self.tabs[i]).unwrap_or(Column(0));
         }
+
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
     fn move_forward_tabs(&mut self, count: u16) {
-        trace!("[unimplemented] Moving forward {} tabs", count);
+        trace!("Moving forward {} tabs", count);
+
+        // Without any following tab stop, the cursor stops at the last column.
+        let last_column = self.last_column();
+        for _ in 0..count {
+            let col = self.grid.cursor.point.column;
+            self.grid.cursor.point.column = (col.0 + 1..=last_column.0)
+                .map(Column)
+                .find(|&i| self.tabs[i])
+                .unwrap_or(last_column);
+        }
+
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     #[inline]
     fn save_cursor_position(&mut self) {
         trace!("Saving cursor position");
 
-        self.grid.saved_cursor = self.grid.cursor.clone();
+        self.save_cursor();
     }
 
     #[inline]
@@ -1263,6 +2108,7 @@ impl<T: EventListener> Handler for Term<T> {
         trace!("Restoring cursor position");
 
         self.grid.cursor = self.grid.saved_cursor.clone();
+        self.mode.set(TermMode::ORIGIN, self.grid.cursor.origin);
     }
 
     #[inline]
@@ -1293,10 +2139,49 @@ impl<T: EventListener> Handler for Term<T> {
             },
         }
 
+        self.grid.cursor.input_needs_wrap = false;
+
         let range = self.grid.cursor.point.line..=self.grid.cursor.point.line;
         self.selection = self.selection.take().filter(|s| !s.intersects_range(range));
     }
 
+    #[inline]
+    fn selective_clear_line(&mut self, mode: ansi::LineClearMode) {
+        trace!("Selectively clearing line: {:?}", mode);
+
+        let point = self.grid.cursor.point;
+        let (start, end) = match mode {
+            ansi::LineClearMode::Right => (point.column, self.last_column()),
+            ansi::LineClearMode::Left => (Column(0), point.column),
+            ansi::LineClearMode::All => (Column(0), self.last_column()),
+        };
+
+        self.clear_unprotected(Point::new(point.line, start), Point::new(point.line, end));
+    }
+
+    #[inline]
+    fn selective_clear_screen(&mut self, mode: ansi::ClearMode) {
+        trace!("Selectively clearing screen: {:?}", mode);
+
+        let point = self.grid.cursor.point;
+        let bottom_right = Point::new(self.bottommost_line(), self.last_column());
+        match mode {
+            ansi::ClearMode::Above => self.clear_unprotected(Point::new(Line(0), Column(0)), point),
+            ansi::ClearMode::Below => self.clear_unprotected(point, bottom_right),
+            ansi::ClearMode::All => {
+                self.clear_unprotected(Point::new(Line(0), Column(0)), bottom_right)
+            },
+            // Scrollback history cannot be erased selectively.
+            ansi::ClearMode::Saved => (),
+        }
+    }
+
+    #[inline]
+    fn set_character_protection(&mut self, protected: bool) {
+        trace!("Setting character protection: {}", protected);
+        self.grid.cursor.template.flags.set(Flags::PROTECTED, protected);
+    }
+
     /// Set the indexed color value.
     #[inline]
     fn set_color(&mut self, index: usize, color: Rgb) {
@@ -1304,20 +2189,23 @@ impl<T: EventListener> Handler for Term<T> {
         self.colors[index] = Some(color);
     }
 
-    /// Write a foreground/background color escape sequence with the current color.
+    /// Write a color escape sequence with the current color.
     #[inline]
-    fn dynamic_color_sequence(&mut self, code: u8, index: usize, terminator: &str) {
-        trace!("Requested write of escape sequence for color code {}: color[{}]", code, index);
+    fn dynamic_color_sequence(&mut self, prefix: String, index: usize, terminator: &str) {
+        trace!("Requested write of escape sequence for color code {}: color[{}]", prefix, index);
+
+        // Colors modified by escape sequences are known to the terminal, so they can be
+        // reported without asking the display.
+        if let Some(color) = self.colors[index] {
+            let text = color_sequence(&prefix, color, terminator);
+            self.event_proxy.send_event(Event::PtyWrite(text));
+            return;
+        }
 
         let terminator = terminator.to_owned();
         self.event_proxy.send_event(Event::ColorRequest(
             index,
-            Arc::new(move |color| {
-                format!(
-                    "\x1b]{};rgb:{1:02x}{1:02x}/{2:02x}{2:02x}/{3:02x}{3:02x}{4}",
-                    code, color.r, color.g, color.b, terminator
-                )
-            }),
+            Arc::new(move |color| color_sequence(&prefix, color, &terminator)),
         ));
     }
 
@@ -1331,6 +2219,11 @@ impl<T: EventListener> Handler for Term<T> {
     /// Store data into clipboard.
     #[inline]
     fn clipboard_store(&mut self, clipboard: u8, base64: &[u8]) {
+        if !self.osc52.allows_copy() {
+            debug!("Ignoring OSC 52 clipboard store");
+            return;
+        }
+
         let clipboard_type = match clipboard {
             b'c' => ClipboardType::Clipboard,
             b'p' | b's' => ClipboardType::Selection,
@@ -1338,15 +2231,19 @@ impl<T: EventListener> Handler for Term<T> {
         };
 
         if let Ok(bytes) = base64::decode(base64) {
-            if let Ok(text) = String::from_utf8(bytes) {
-                self.event_proxy.send_event(Event::ClipboardStore(clipboard_type, text));
-            }
+            let text = strip_invalid_utf8(&bytes);
+            self.event_proxy.send_event(Event::ClipboardStore(clipboard_type, text));
         }
     }
 
     /// Load data from clipboard.
     #[inline]
     fn clipboard_load(&mut self, clipboard: u8, terminator: &str) {
+        if !self.osc52.allows_paste() {
+            debug!("Ignoring OSC 52 clipboard load");
+            return;
+        }
+
         let clipboard_type = match clipboard {
             b'c' => ClipboardType::Clipboard,
             b'p' | b's' => ClipboardType::Selection,
@@ -1376,7 +2273,7 @@ impl<T: EventListener> Handler for Term<T> {
                 let cursor = self.grid.cursor.point;
 
                 // If clearing more than one line.
-                if cursor.line > 1 {
+                if cursor.line > 0 {
                     // Fully clear all lines before the current line.
                     self.grid.reset_region(..cursor.line);
                 }
@@ -1387,6 +2284,8 @@ impl<T: EventListener> Handler for Term<T> {
                     *cell = bg.into();
                 }
 
+                self.grid.cursor.input_needs_wrap = false;
+
                 let range = Line(0)..=cursor.line;
                 self.selection = self.selection.take().filter(|s| !s.intersects_range(range));
             },
@@ -1400,6 +2299,8 @@ impl<T: EventListener> Handler for Term<T> {
                     self.grid.reset_region((cursor.line + 1)..);
                 }
 
+                self.grid.cursor.input_needs_wrap = false;
+
                 let range = cursor.line..Line(screen_lines as i32);
                 self.selection = self.selection.take().filter(|s| !s.intersects_range(range));
             },
@@ -1410,12 +2311,17 @@ impl<T: EventListener> Handler for Term<T> {
                     self.grid.clear_viewport();
                 }
 
+                self.grid.cursor.input_needs_wrap = false;
                 self.selection = None;
             },
             ansi::ClearMode::Saved if self.history_size() > 0 => {
                 self.grid.clear_history();
 
                 self.selection = self.selection.take().filter(|s| !s.intersects_range(..Line(0)));
+
+                // Move the vi mode cursor out of the removed history.
+                let vi_point = self.vi_mode_cursor.point;
+                self.vi_mode_cursor.point = vi_point.grid_clamp(self, Boundary::Grid);
             },
             // We have no history to clear.
             ansi::ClearMode::Saved => (),
@@ -1440,16 +2346,23 @@ impl<T: EventListener> Handler for Term<T> {
     fn reset_state(&mut self) {
         if self.mode.contains(TermMode::ALT_SCREEN) {
             mem::swap(&mut self.grid, &mut self.inactive_grid);
+            self.event_proxy.send_event(Event::AlternateScreen(false));
         }
-        self.active_charset = Default::default();
         self.cursor_style = None;
         self.grid.reset();
         self.inactive_grid.reset();
         self.scroll_region = Line(0)..Line(self.screen_lines() as i32);
         self.tabs = TabStops::new(self.columns());
         self.title_stack = Vec::new();
+        self.icon_name_stack = Vec::new();
+        self.keyboard_mode_stack = Vec::new();
+        self.inactive_keyboard_mode_stack = Vec::new();
+        self.modify_other_keys = ModifyOtherKeys::default();
         self.title = None;
+        self.icon_name = None;
+        self.colors = Colors::default();
         self.selection = None;
+        self.graphics.kitty.clear();
 
         // Preserve vi mode across resets.
         self.mode &= TermMode::VI;
@@ -1468,6 +2381,8 @@ impl<T: EventListener> Handler for Term<T> {
         } else {
             self.grid.cursor.point.line = max(self.grid.cursor.point.line - 1, Line(0));
         }
+
+        self.grid.cursor.input_needs_wrap = false;
     }
 
     /// Set a terminal attribute.
@@ -1481,34 +2396,52 @@ impl<T: EventListener> Handler for Term<T> {
             Attr::Reset => {
                 cursor.template.fg = Color::Named(NamedColor::Foreground);
                 cursor.template.bg = Color::Named(NamedColor::Background);
-                cursor.template.flags = Flags::empty();
+                // Character protection is only changed by DECSCA.
+                cursor.template.flags &= Flags::PROTECTED;
             },
             Attr::Reverse => cursor.template.flags.insert(Flags::INVERSE),
             Attr::CancelReverse => cursor.template.flags.remove(Flags::INVERSE),
             Attr::Bold => cursor.template.flags.insert(Flags::BOLD),
+            #[allow(deprecated)]
             Attr::CancelBold => cursor.template.flags.remove(Flags::BOLD),
             Attr::Dim => cursor.template.flags.insert(Flags::DIM),
             Attr::CancelBoldDim => cursor.template.flags.remove(Flags::BOLD | Flags::DIM),
             Attr::Italic => cursor.template.flags.insert(Flags::ITALIC),
             Attr::CancelItalic => cursor.template.flags.remove(Flags::ITALIC),
             Attr::Underline => {
-                cursor.template.flags.remove(Flags::DOUBLE_UNDERLINE);
+                cursor.template.flags.remove(Flags::ALL_UNDERLINES);
                 cursor.template.flags.insert(Flags::UNDERLINE);
             },
             Attr::DoubleUnderline => {
-                cursor.template.flags.remove(Flags::UNDERLINE);
+                cursor.template.flags.remove(Flags::ALL_UNDERLINES);
                 cursor.template.flags.insert(Flags::DOUBLE_UNDERLINE);
             },
-            Attr::CancelUnderline => {
-                cursor.template.flags.remove(Flags::UNDERLINE | Flags::DOUBLE_UNDERLINE);
+            Attr::Undercurl => {
+                cursor.template.flags.remove(Flags::ALL_UNDERLINES);
+                cursor.template.flags.insert(Flags::UNDERCURL);
+            },
+            Attr::DottedUnderline => {
+                cursor.template.flags.remove(Flags::ALL_UNDERLINES);
+                cursor.template.flags.insert(Flags::DOTTED_UNDERLINE);
             },
+            Attr::DashedUnderline => {
+                cursor.template.flags.remove(Flags::ALL_UNDERLINES);
+                cursor.template.flags.insert(Flags::DASHED_UNDERLINE);
+            },
+            Attr::CancelUnderline => cursor.template.flags.remove(Flags::ALL_UNDERLINES),
+            Attr::BlinkSlow => {
+                cursor.template.flags.remove(Flags::ALL_BLINKS);
+                cursor.template.flags.insert(Flags::BLINK);
+            },
+            Attr::BlinkFast => {
+                cursor.template.flags.remove(Flags::ALL_BLINKS);
+                cursor.template.flags.insert(Flags::RAPID_BLINK);
+            },
+            Attr::CancelBlink => cursor.template.flags.remove(Flags::ALL_BLINKS),
             Attr::Hidden => cursor.template.flags.insert(Flags::HIDDEN),
             Attr::CancelHidden => cursor.template.flags.remove(Flags::HIDDEN),
             Attr::Strike => cursor.template.flags.insert(Flags::STRIKEOUT),
             Attr::CancelStrike => cursor.template.flags.remove(Flags::STRIKEOUT),
-            _ => {
-                debug!("Term got unhandled attr: {:?}", attr);
-            },
         }
     }
 
@@ -1520,6 +2453,7 @@ impl<T: EventListener> Handler for Term<T> {
             ansi::Mode::SwapScreenAndSetRestoreCursor => {
                 if !self.mode.contains(TermMode::ALT_SCREEN) {
                     self.swap_alt();
+                    self.event_proxy.send_event(Event::AlternateScreen(true));
                 }
             },
             ansi::Mode::ShowCursor => self.mode.insert(TermMode::SHOW_CURSOR),
@@ -1544,19 +2478,24 @@ impl<T: EventListener> Handler for Term<T> {
             ansi::Mode::BracketedPaste => self.mode.insert(TermMode::BRACKETED_PASTE),
             // Mouse encodings are mutually exclusive.
             ansi::Mode::SgrMouse => {
-                self.mode.remove(TermMode::UTF8_MOUSE);
+                self.mode.remove(TermMode::UTF8_MOUSE | TermMode::SGR_PIXEL_MOUSE);
                 self.mode.insert(TermMode::SGR_MOUSE);
             },
+            ansi::Mode::SgrPixelMouse => {
+                self.mode.remove(TermMode::UTF8_MOUSE | TermMode::SGR_MOUSE);
+                self.mode.insert(TermMode::SGR_PIXEL_MOUSE);
+            },
             ansi::Mode::Utf8Mouse => {
-                self.mode.remove(TermMode::SGR_MOUSE);
+                self.mode.remove(TermMode::SGR_MOUSE | TermMode::SGR_PIXEL_MOUSE);
                 self.mode.insert(TermMode::UTF8_MOUSE);
             },
             ansi::Mode::AlternateScroll => self.mode.insert(TermMode::ALTERNATE_SCROLL),
             ansi::Mode::LineWrap => self.mode.insert(TermMode::LINE_WRAP),
             ansi::Mode::LineFeedNewLine => self.mode.insert(TermMode::LINE_FEED_NEW_LINE),
             ansi::Mode::Origin => self.mode.insert(TermMode::ORIGIN),
-            ansi::Mode::ColumnMode => self.deccolm(),
+            ansi::Mode::ColumnMode => self.deccolm(132),
             ansi::Mode::Insert => self.mode.insert(TermMode::INSERT),
+            ansi::Mode::ApplicationKeypad => self.mode.insert(TermMode::APP_KEYPAD),
             ansi::Mode::BlinkingCursor => {
                 let style = self.cursor_style.get_or_insert(self.default_cursor_style);
                 style.blinking = true;
@@ -1573,6 +2512,7 @@ impl<T: EventListener> Handler for Term<T> {
             ansi::Mode::SwapScreenAndSetRestoreCursor => {
                 if self.mode.contains(TermMode::ALT_SCREEN) {
                     self.swap_alt();
+                    self.event_proxy.send_event(Event::AlternateScreen(false));
                 }
             },
             ansi::Mode::ShowCursor => self.mode.remove(TermMode::SHOW_CURSOR),
@@ -1592,13 +2532,15 @@ impl<T: EventListener> Handler for Term<T> {
             ansi::Mode::ReportFocusInOut => self.mode.remove(TermMode::FOCUS_IN_OUT),
             ansi::Mode::BracketedPaste => self.mode.remove(TermMode::BRACKETED_PASTE),
             ansi::Mode::SgrMouse => self.mode.remove(TermMode::SGR_MOUSE),
+            ansi::Mode::SgrPixelMouse => self.mode.remove(TermMode::SGR_PIXEL_MOUSE),
             ansi::Mode::Utf8Mouse => self.mode.remove(TermMode::UTF8_MOUSE),
             ansi::Mode::AlternateScroll => self.mode.remove(TermMode::ALTERNATE_SCROLL),
             ansi::Mode::LineWrap => self.mode.remove(TermMode::LINE_WRAP),
             ansi::Mode::LineFeedNewLine => self.mode.remove(TermMode::LINE_FEED_NEW_LINE),
             ansi::Mode::Origin => self.mode.remove(TermMode::ORIGIN),
-            ansi::Mode::ColumnMode => self.deccolm(),
+            ansi::Mode::ColumnMode => self.deccolm(80),
             ansi::Mode::Insert => self.mode.remove(TermMode::INSERT),
+            ansi::Mode::ApplicationKeypad => self.mode.remove(TermMode::APP_KEYPAD),
             ansi::Mode::BlinkingCursor => {
                 let style = self.cursor_style.get_or_insert(self.default_cursor_style);
                 style.blinking = false;
@@ -1653,7 +2595,7 @@ impl<T: EventListener> Handler for Term<T> {
     #[inline]
     fn set_active_charset(&mut self, index: CharsetIndex) {
         trace!("Setting active charset {:?}", index);
-        self.active_charset = index;
+        self.grid.cursor.active_charset = index;
     }
 
     #[inline]
@@ -1689,18 +2631,23 @@ impl<T: EventListener> Handler for Term<T> {
     }
 
     #[inline]
-    fn push_title(&mut self) {
-        trace!("Pushing '{:?}' onto title stack", self.title);
+    fn set_icon_name(&mut self, icon_name: Option<String>) {
+        trace!("Setting icon name to '{:?}'", icon_name);
 
-        if self.title_stack.len() >= TITLE_STACK_MAX_DEPTH {
-            let removed = self.title_stack.remove(0);
-            trace!(
-                "Removing '{:?}' from bottom of title stack that exceeds its maximum depth",
-                removed
-            );
-        }
+        self.icon_name = icon_name.clone();
+
+        let icon_name_event = match icon_name {
+            Some(icon_name) => Event::IconName(icon_name),
+            None => Event::ResetIconName,
+        };
+
+        self.event_proxy.send_event(icon_name_event);
+    }
 
-        self.title_stack.push(self.title.clone());
+    #[inline]
+    fn push_title(&mut self) {
+        trace!("Pushing '{:?}' onto title stack", self.title);
+        push_title_stack(&mut self.title_stack, self.title.clone());
     }
 
     #[inline]
@@ -1713,6 +2660,20 @@ impl<T: EventListener> Handler for Term<T> {
         }
     }
 
+    #[inline]
+    fn push_icon_name(&mut self) {
+        trace!("Pushing '{:?}' onto icon name stack", self.icon_name);
+        push_title_stack(&mut self.icon_name_stack, self.icon_name.clone());
+    }
+
+    #[inline]
+    fn pop_icon_name(&mut self) {
+        if let Some(popped) = self.icon_name_stack.pop() {
+            trace!("Icon name '{:?}' popped from stack", popped);
+            self.set_icon_name(popped);
+        }
+    }
+
     #[inline]
     fn text_area_size_pixels(&mut self) {
         let width = self.cell_width * self.columns();
@@ -1726,6 +2687,233 @@ impl<T: EventListener> Handler for Term<T> {
         let text = format!("\x1b[8;{};{}t", self.screen_lines(), self.columns());
         self.event_proxy.send_event(Event::PtyWrite(text));
     }
+
+    #[inline]
+    fn push_keyboard_modes(&mut self, modes: KeyboardModes) {
+        trace!("Pushing {:?} onto keyboard mode stack", modes);
+
+        if self.keyboard_mode_stack.len() >= KEYBOARD_MODE_STACK_MAX_DEPTH {
+            self.keyboard_mode_stack.remove(0);
+        }
+
+        self.keyboard_mode_stack.push(modes);
+        self.apply_keyboard_modes();
+    }
+
+    #[inline]
+    fn pop_keyboard_modes(&mut self, to_pop: u16) {
+        trace!("Popping {} keyboard modes from the stack", to_pop);
+
+        let len = self.keyboard_mode_stack.len().saturating_sub(to_pop as usize);
+        self.keyboard_mode_stack.truncate(len);
+        self.apply_keyboard_modes();
+    }
+
+    #[inline]
+    fn set_keyboard_modes(&mut self, modes: KeyboardModes, behavior: KeyboardModesApplyBehavior) {
+        trace!("Setting keyboard modes to {:?} with {:?}", modes, behavior);
+
+        let active = self.keyboard_mode_stack.last().copied().unwrap_or(KeyboardModes::NO_MODE);
+        let modes = match behavior {
+            KeyboardModesApplyBehavior::Replace => modes,
+            KeyboardModesApplyBehavior::Union => active | modes,
+            KeyboardModesApplyBehavior::Difference => active - modes,
+        };
+
+        // Without any pushed modes, the implicit base modes are changed.
+        match self.keyboard_mode_stack.last_mut() {
+            Some(active) => *active = modes,
+            None => self.keyboard_mode_stack.push(modes),
+        }
+        self.apply_keyboard_modes();
+    }
+
+    #[inline]
+    fn set_modify_other_keys(&mut self, mode: ModifyOtherKeys) {
+        trace!("Setting modifyOtherKeys to {:?}", mode);
+        self.modify_other_keys = mode;
+    }
+
+    #[inline]
+    fn report_modify_other_keys(&mut self) {
+        let level = match self.modify_other_keys {
+            ModifyOtherKeys::Reset => 0,
+            ModifyOtherKeys::EnableExceptWellDefined => 1,
+            ModifyOtherKeys::EnableAll => 2,
+        };
+        let text = format!("\x1b[>4;{}m", level);
+        self.event_proxy.send_event(Event::PtyWrite(text));
+    }
+
+    #[inline]
+    fn report_keyboard_modes(&mut self) {
+        let modes = self.keyboard_mode_stack.last().copied().unwrap_or(KeyboardModes::NO_MODE);
+        let text = format!("\x1b[?{}u", modes.bits());
+        self.event_proxy.send_event(Event::PtyWrite(text));
+    }
+
+    #[inline]
+    fn report_status_setting(&mut self, setting: Option<StatusSetting>) {
+        trace!("Reporting status setting {:?}", setting);
+
+        let status = match setting {
+            Some(StatusSetting::Sgr) => {
+                let mut params = vec![String::from("0")];
+                params.extend(self.grid.cursor.template.sgr_params(&Cell::default()));
+                format!("{}m", params.join(";"))
+            },
+            Some(StatusSetting::CursorStyle) => {
+                let style = self.cursor_style.unwrap_or(self.default_cursor_style);
+                let shape = match style.shape {
+                    CursorShape::Underline => 3,
+                    CursorShape::Beam => 5,
+                    _ => 1,
+                };
+                format!("{} q", shape + if style.blinking { 0 } else { 1 })
+            },
+            Some(StatusSetting::ScrollingRegion) => {
+                format!("{};{}r", self.scroll_region.start.0 + 1, self.scroll_region.end.0)
+            },
+            Some(StatusSetting::CharacterProtection) => {
+                let protected = self.grid.cursor.template.flags.contains(Flags::PROTECTED);
+                format!("{}\"q", if protected { 1 } else { 0 })
+            },
+            None => {
+                self.event_proxy.send_event(Event::PtyWrite(String::from("\x1bP0$r\x1b\\")));
+                return;
+            },
+        };
+
+        let text = format!("\x1bP1$r{}\x1b\\", status);
+        self.event_proxy.send_event(Event::PtyWrite(text));
+    }
+
+    #[inline]
+    fn report_capability(&mut self, name: Option<String>) {
+        trace!("Reporting capability {:?}", name);
+
+        let name = match name {
+            Some(name) => name,
+            None => {
+                self.event_proxy.send_event(Event::PtyWrite(String::from("\x1bP0+r\x1b\\")));
+                return;
+            },
+        };
+
+        let hex_name = hex_encode(name.as_bytes());
+        self.event_proxy.send_event(Event::CapabilityRequest(
+            name,
+            Arc::new(move |value| match value {
+                Some("") => format!("\x1bP1+r{}\x1b\\", hex_name),
+                Some(value) => {
+                    format!("\x1bP1+r{}={}\x1b\\", hex_name, hex_encode(value.as_bytes()))
+                },
+                None => format!("\x1bP0+r{}\x1b\\", hex_name),
+            }),
+        ));
+    }
+
+    #[inline]
+    fn set_working_directory(&mut self, working_directory: Option<FileUrl>) {
+        trace!("Setting working directory to {:?}", working_directory);
+        self.working_directory = working_directory;
+    }
+
+    #[inline]
+    fn unhandled_dcs(&mut self, params: &[u16], intermediates: &[u8], action: char, data: &[u8]) {
+        let sequence = UnhandledSequence::Dcs {
+            params: params.to_vec(),
+            intermediates: intermediates.to_vec(),
+            action,
+            data: data.to_vec(),
+        };
+        self.event_proxy.send_event(Event::UnhandledSequence(sequence));
+    }
+
+    #[inline]
+    fn unhandled_apc(&mut self, data: &[u8]) {
+        // Chunks of a kitty graphics payload can not be interrupted by other sequences.
+        self.graphics.kitty.cancel_chunked();
+
+        let sequence = UnhandledSequence::Apc(data.to_vec());
+        self.event_proxy.send_event(Event::UnhandledSequence(sequence));
+    }
+
+    #[inline]
+    fn set_window_name(&mut self, name: Option<String>) {
+        trace!("Setting window name to {:?}", name);
+        self.window_name = name;
+    }
+
+    #[inline]
+    fn set_shell_mark(&mut self, mark: ShellMark) {
+        trace!("Setting shell mark {:?}", mark);
+
+        let mark = match mark {
+            ShellMark::PromptStart => LineMarks::PROMPT_START,
+            ShellMark::CommandStart => LineMarks::COMMAND_START,
+            ShellMark::OutputStart => LineMarks::OUTPUT_START,
+            ShellMark::CommandEnd => LineMarks::COMMAND_END,
+        };
+
+        let line = self.grid.cursor.point.line;
+        self.grid[line].marks.insert(mark);
+
+        // Keep the last line of output without a trailing newline.
+        let ends_output = mark.intersects(LineMarks::COMMAND_END | LineMarks::PROMPT_START);
+        if ends_output && self.grid.cursor.point.column > 0 {
+            self.grid[line].marks.insert(LineMarks::OUTPUT_END);
+        }
+    }
+
+    #[inline]
+    fn insert_graphic(&mut self, graphic: GraphicData) {
+        trace!("Inserting graphic: {}x{}", graphic.width, graphic.height);
+
+        // Continue below the graphic, at the column where it started.
+        if self.place_graphic(graphic, true).is_some() {
+            self.linefeed();
+        }
+    }
+
+    #[cfg(feature = "debug_checks")]
+    fn check_invariants(&mut self, input: &[u8]) {
+        let violations = checks::check_grid(&self.grid);
+        for violation in violations.iter().filter(|v| !self.violations.contains(v)) {
+            let input = String::from_utf8_lossy(input);
+            log::error!("Grid invariant violated after input {:?}: {}", input, violation);
+        }
+        self.violations = violations;
+    }
+
+    #[inline]
+    fn kitty_graphics(&mut self, command: kitty::Command) {
+        trace!("Kitty graphics command: {:?}", command.action);
+
+        let mut command = match self.graphics.kitty.assemble(command) {
+            Some(command) => command,
+            None => return,
+        };
+
+        let result = self.kitty_graphics_command(&mut command);
+        if let Err(err) = result {
+            debug!("Kitty graphics command failed: {}", err);
+        }
+
+        if let Some(response) = command.response(result) {
+            self.event_proxy.send_event(Event::PtyWrite(response));
+        }
+    }
+}
+
+/// Push onto a title or icon name stack, dropping the oldest entry once it is full.
+fn push_title_stack(stack: &mut Vec<Option<String>>, title: Option<String>) {
+    if stack.len() >= TITLE_STACK_MAX_DEPTH {
+        let removed = stack.remove(0);
+        trace!("Removing '{:?}' from bottom of stack that exceeds its maximum depth", removed);
+    }
+
+    stack.push(title);
 }
 
 /// Terminal version for escape sequence reports.
@@ -1749,6 +2937,45 @@ fn version_number(mut version: &str) -> usize {
     version_number
 }
 
+/// Format an OSC color report in the 16 bit per channel `rgb:` format used by xterm.
+fn color_sequence(prefix: &str, color: Rgb, terminator: &str) -> String {
+    format!(
+        "\x1b]{};rgb:{1:02x}{1:02x}/{2:02x}{2:02x}/{3:02x}{3:02x}{4}",
+        prefix, color.r, color.g, color.b, terminator
+    )
+}
+
+/// Encode bytes as pairs of hex digits.
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Decode UTF-8 text, dropping all invalid byte sequences.
+fn strip_invalid_utf8(mut bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len());
+
+    loop {
+        match str::from_utf8(bytes) {
+            Ok(valid) => {
+                text.push_str(valid);
+                break;
+            },
+            Err(err) => {
+                let (valid, invalid) = bytes.split_at(err.valid_up_to());
+                text.push_str(str::from_utf8(valid).unwrap_or_default());
+
+                // Without an error length, the text ends in the middle of a character.
+                match err.error_len() {
+                    Some(len) => bytes = &invalid[len..],
+                    None => break,
+                }
+            },
+        }
+    }
+
+    text
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClipboardType {
     Clipboard,
@@ -1930,6 +3157,94 @@ mod tests {
     use crate::selection::{Selection, SelectionType};
     use crate::term::cell::{Cell, Flags};
 
+    /// Event listener recording the events selected by its filter.
+    struct EventRecorder<T> {
+        events: Arc<std::sync::Mutex<Vec<T>>>,
+        filter: fn(Event) -> Option<T>,
+    }
+
+    impl<T> EventRecorder<T> {
+        fn new(filter: fn(Event) -> Option<T>) -> Self {
+            Self { events: Default::default(), filter }
+        }
+
+        /// Take all events recorded so far.
+        fn take(&self) -> Vec<T> {
+            mem::take(&mut *self.events.lock().unwrap())
+        }
+    }
+
+    impl EventRecorder<String> {
+        /// Recorder for the text written to the PTY.
+        fn pty_writes() -> Self {
+            Self::new(|event| match event {
+                Event::PtyWrite(text) => Some(text),
+                _ => None,
+            })
+        }
+    }
+
+    impl<T> Clone for EventRecorder<T> {
+        fn clone(&self) -> Self {
+            Self { events: self.events.clone(), filter: self.filter }
+        }
+    }
+
+    impl<T> EventListener for EventRecorder<T> {
+        fn send_event(&self, event: Event) {
+            if let Some(event) = (self.filter)(event) {
+                self.events.lock().unwrap().push(event);
+            }
+        }
+    }
+
+    #[test]
+    fn size_info_asymmetric_padding() {
+        let padding = Padding { top: 1., right: 2., bottom: 3., left: 4. };
+        let size = SizeInfo::with_padding(26., 34., 5., 10., padding, false);
+
+        assert_eq!(size.columns(), 4);
+        assert_eq!(size.screen_lines(), 3);
+        assert_eq!(size.padding_x(), 4.);
+        assert_eq!(size.padding_y(), 1.);
+
+        let size = SizeInfo::with_padding(28., 36., 5., 10., padding, true);
+        assert_eq!(size.padding(), Padding { top: 2., right: 3., bottom: 4., left: 5. });
+    }
+
+    #[test]
+    fn size_info_magnify() {
+        let size = SizeInfo::new(24., 44., 5., 10., 2., 2., false);
+        let zoomed = size.magnify(2., 2., 10., 20.);
+
+        assert_eq!((zoomed.columns(), zoomed.screen_lines()), (4, 4));
+        assert_eq!((zoomed.width(), zoomed.height()), (24., 44.));
+        assert_eq!((zoomed.cell_width(), zoomed.cell_height()), (10., 20.));
+        assert_eq!(zoomed.padding(), Padding { top: -16., right: -10., bottom: -20., left: -6. });
+        assert_eq!(zoomed.cell_rect(Point::new(1, Column(1)), 1).x, 4.);
+    }
+
+    #[test]
+    fn cursor_rect() {
+        let size = SizeInfo::new(54., 44., 5., 10., 2., 2., false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+
+        term.grid.cursor.point = Point::new(Line(1), Column(2));
+        let rect = PixelRect { x: 12., y: 12., width: 5., height: 10. };
+        assert_eq!(term.cursor_rect(&size), Some(rect));
+
+        term.grid[Line(1)][Column(2)].flags.insert(Flags::WIDE_CHAR);
+        assert_eq!(term.cursor_rect(&size).map(|rect| rect.width), Some(10.));
+
+        term.grid.cursor.point.column = Column(8);
+        assert_eq!(term.preedit_rect(&size, 5).map(|rect| rect.width), Some(10.));
+
+        term.scroll_up(1);
+        term.scroll_display(Scroll::Delta(1));
+        term.grid.cursor.point.line = Line(3);
+        assert_eq!(term.cursor_rect(&size), None);
+    }
+
     #[test]
     fn semantic_selection_works() {
         let size = SizeInfo::new(5., 3., 1.0, 1.0, 0.0, 0.0, false);
@@ -1978,6 +3293,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn block_selection_api() {
+        let size = SizeInfo::new(5., 3., 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        for line in 0..3 {
+            for column in 0..5 {
+                term.grid[Line(line)][Column(column)].c = (b'a' + column as u8) as char;
+            }
+        }
+
+        term.start_selection(SelectionType::Block, Point::new(Line(0), Column(1)), Side::Left);
+        term.update_selection(Point::new(Line(1), Column(2)), Side::Right);
+        assert_eq!(term.selection_to_string(), Some(String::from("bc\nbc")));
+
+        term.clear_selection();
+        assert_eq!(term.selection_to_string(), None);
+    }
+
     #[test]
     fn line_selection_works() {
         let size = SizeInfo::new(5., 1., 1.0, 1.0, 0.0, 0.0, false);
@@ -1999,6 +3332,25 @@ mod tests {
         assert_eq!(term.selection_to_string(), Some(String::from("\"aa\"a\n")));
     }
 
+    #[test]
+    fn bounds_to_escaped_string_resets_attributes() {
+        let size = SizeInfo::new(3.0, 2.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut grid: Grid<Cell> = Grid::new(2, 3, 0);
+        grid[Line(0)][Column(0)].c = 'a';
+        grid[Line(0)][Column(0)].flags = Flags::BOLD;
+        grid[Line(0)][Column(1)].c = 'b';
+        grid[Line(1)][Column(0)].c = 'c';
+        grid[Line(1)][Column(0)].flags = Flags::ITALIC;
+
+        mem::swap(&mut term.grid, &mut grid);
+
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(1), Column(2));
+        let text = term.bounds_to_escaped_string(start, end);
+        assert_eq!(text, "\x1b[1ma\x1b[0mb\n\x1b[3mc\n\x1b[0m");
+    }
+
     #[test]
     fn selecting_empty_line() {
         let size = SizeInfo::new(3.0, 3.0, 1.0, 1.0, 0.0, 0.0, false);
@@ -2070,6 +3422,33 @@ mod tests {
         assert_eq!(term.grid, scrolled_grid);
     }
 
+    #[test]
+    fn clear_saved_lines_in_history() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        // Add five lines of scrollback.
+        for byte in b"\x1b[17H\n\n\n\n\n" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.grid().history_size(), 5);
+
+        // Scroll into history and move the vi mode cursor there.
+        term.scroll_display(Scroll::Delta(3));
+        term.toggle_vi_mode();
+        term.vi_mode_cursor.point = Point::new(Line(-3), Column(0));
+
+        // Clear the history.
+        for byte in b"\x1b[3J" {
+            parser.advance(&mut term, *byte);
+        }
+
+        assert_eq!(term.grid().history_size(), 0);
+        assert_eq!(term.grid().display_offset(), 0);
+        assert_eq!(term.vi_mode_cursor.point, Point::new(Line(0), Column(0)));
+    }
+
     #[test]
     fn grow_lines_updates_active_cursor_pos() {
         let mut size = SizeInfo::new(100.0, 10.0, 1.0, 1.0, 0.0, 0.0, false);
@@ -2210,6 +3589,874 @@ mod tests {
         assert_eq!(term.title, None);
     }
 
+    #[test]
+    fn icon_name_stack() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+        let mut advance = |term: &mut Term<()>, input: &[u8]| {
+            for byte in input {
+                parser.advance(term, *byte);
+            }
+        };
+
+        // Both are pushed by default.
+        advance(&mut term, b"\x1b]0;first\x07\x1b[22t\x1b]0;second\x07");
+        assert_eq!(term.title_stack, [Some(String::from("first"))]);
+        assert_eq!(term.icon_name_stack, [Some(String::from("first"))]);
+
+        // Icon name and title are pushed and popped separately.
+        advance(&mut term, b"\x1b[22;1t\x1b]0;third\x07\x1b[23;2t");
+        assert_eq!(term.title, Some("first".into()));
+        assert_eq!(term.icon_name, Some("third".into()));
+        advance(&mut term, b"\x1b[23;1t");
+        assert_eq!(term.icon_name, Some("second".into()));
+        assert!(term.title_stack.is_empty());
+
+        advance(&mut term, b"\x1b[23;0t");
+        assert_eq!(term.title, Some("first".into()));
+        assert_eq!(term.icon_name, Some("first".into()));
+        assert!(term.icon_name_stack.is_empty());
+
+        // Icon name stack is cleared when terminal state is reset.
+        advance(&mut term, b"\x1b[22;1t\x1bc");
+        assert!(term.icon_name_stack.is_empty());
+    }
+
+    #[test]
+    fn alternate_screen_events() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::new(|event| match event {
+            Event::AlternateScreen(_) => Some(format!("{:?}", event)),
+            _ => None,
+        });
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+
+        term.set_mode(ansi::Mode::SwapScreenAndSetRestoreCursor);
+        term.set_mode(ansi::Mode::SwapScreenAndSetRestoreCursor);
+        term.unset_mode(ansi::Mode::SwapScreenAndSetRestoreCursor);
+        term.set_mode(ansi::Mode::SwapScreenAndSetRestoreCursor);
+        term.reset_state();
+
+        let events = recorder.take();
+        assert_eq!(events, [
+            "AlternateScreen(true)",
+            "AlternateScreen(false)",
+            "AlternateScreen(true)",
+            "AlternateScreen(false)",
+        ]);
+    }
+
+    #[test]
+    fn column_mode() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::new(|event| match event {
+            Event::ColumnMode(_) => Some(format!("{:?}", event)),
+            _ => None,
+        });
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+
+        term.set_scrolling_region(2, Some(5));
+        term.goto(Line(3), Column(3));
+        term.input('a');
+
+        term.set_mode(ansi::Mode::ColumnMode);
+
+        assert_eq!(term.grid.cursor.point, Point::new(Line(0), Column(0)));
+        assert_eq!(term.scroll_region, Line(0)..Line(17));
+        assert_eq!(term.grid[Line(3)][Column(3)].c, ' ');
+
+        term.unset_mode(ansi::Mode::ColumnMode);
+
+        let events = recorder.take();
+        assert_eq!(events, ["ColumnMode(132)", "ColumnMode(80)"]);
+    }
+
+    #[test]
+    fn saved_cursor_full_state() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+
+        term.set_scrolling_region(2, Some(5));
+        term.set_mode(ansi::Mode::Origin);
+        term.terminal_attribute(Attr::Bold);
+        term.configure_charset(CharsetIndex::G1, StandardCharset::SpecialCharacterAndLineDrawing);
+        term.set_active_charset(CharsetIndex::G1);
+        term.goto(Line(1), Column(6));
+        term.input('a');
+        term.save_cursor_position();
+
+        term.terminal_attribute(Attr::Reset);
+        term.set_active_charset(CharsetIndex::G0);
+        term.unset_mode(ansi::Mode::Origin);
+        term.goto(Line(0), Column(0));
+        term.restore_cursor_position();
+
+        assert_eq!(term.grid.cursor.point, Point::new(Line(2), Column(6)));
+        assert!(term.grid.cursor.template.flags.contains(Flags::BOLD));
+        assert!(term.grid.cursor.input_needs_wrap);
+        assert!(term.mode.contains(TermMode::ORIGIN));
+
+        // Pending wrap and charset are restored for the next character.
+        term.input('a');
+        assert_eq!(term.grid[Line(3)][Column(0)].c, '▒');
+        assert!(term.grid[Line(3)][Column(0)].flags.contains(Flags::BOLD));
+    }
+
+    #[test]
+    fn saved_cursor_per_screen() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+
+        term.goto(Line(2), Column(2));
+        term.set_mode(ansi::Mode::SwapScreenAndSetRestoreCursor);
+
+        term.goto(Line(5), Column(5));
+        term.set_mode(ansi::Mode::Origin);
+        term.save_cursor_position();
+        term.goto(Line(0), Column(0));
+
+        // Origin mode is restored when leaving the alternate screen.
+        term.unset_mode(ansi::Mode::SwapScreenAndSetRestoreCursor);
+        assert!(!term.mode.contains(TermMode::ORIGIN));
+
+        term.restore_cursor_position();
+        assert_eq!(term.grid.cursor.point, Point::new(Line(2), Column(2)));
+        assert!(!term.mode.contains(TermMode::ORIGIN));
+
+        term.set_mode(ansi::Mode::SwapScreenAndSetRestoreCursor);
+        term.restore_cursor_position();
+        assert_eq!(term.grid.cursor.point, Point::new(Line(5), Column(5)));
+        assert!(term.mode.contains(TermMode::ORIGIN));
+    }
+
+    #[test]
+    fn pending_wrap() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+
+        // Fill the second line, leaving the cursor in the last column with a pending wrap.
+        let pending_term = || {
+            let mut term = Term::new(&MockConfig::default(), size, ());
+            term.goto(Line(1), Column(0));
+            for _ in 0..term.columns() {
+                term.input('a');
+            }
+
+            assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(6)));
+            assert!(term.grid.cursor.input_needs_wrap);
+
+            term
+        };
+
+        let clearing: &[(&str, fn(&mut Term<()>))] = &[
+            ("CR", |term| term.carriage_return()),
+            ("BS", |term| term.backspace()),
+            ("LF", |term| term.linefeed()),
+            ("RI", |term| term.reverse_index()),
+            ("CUP", |term| term.goto(Line(1), Column(6))),
+            ("CUU", |term| term.move_up(1)),
+            ("CUD", |term| term.move_down(1)),
+            ("CUF", |term| term.move_forward(Column(1))),
+            ("CUB", |term| term.move_backward(Column(1))),
+            ("CHA", |term| term.goto_col(Column(6))),
+            ("VPA", |term| term.goto_line(Line(1))),
+            ("CNL", |term| term.move_down_and_cr(1)),
+            ("CPL", |term| term.move_up_and_cr(1)),
+            ("CBT", |term| term.move_backward_tabs(1)),
+            ("CHT", |term| term.move_forward_tabs(1)),
+            ("EL 0", |term| term.clear_line(ansi::LineClearMode::Right)),
+            ("EL 1", |term| term.clear_line(ansi::LineClearMode::Left)),
+            ("EL 2", |term| term.clear_line(ansi::LineClearMode::All)),
+            ("ED 0", |term| term.clear_screen(ansi::ClearMode::Below)),
+            ("ED 1", |term| term.clear_screen(ansi::ClearMode::Above)),
+            ("ED 2", |term| term.clear_screen(ansi::ClearMode::All)),
+            ("ECH", |term| term.erase_chars(Column(1))),
+            ("ICH", |term| term.insert_blank(1)),
+            ("DCH", |term| term.delete_chars(1)),
+            ("IL", |term| term.insert_blank_lines(1)),
+            ("DL", |term| term.delete_lines(1)),
+            ("DECRC", |term| term.restore_cursor_position()),
+        ];
+
+        for (name, operation) in clearing {
+            let mut term = pending_term();
+            operation(&mut term);
+            assert!(!term.grid.cursor.input_needs_wrap, "{} did not clear pending wrap", name);
+
+            // The next character is written at the cursor without wrapping.
+            let point = term.grid.cursor.point;
+            term.input('b');
+            assert_eq!(term.grid[point].c, 'b', "{} wrapped the next character", name);
+        }
+
+        let preserving: &[(&str, fn(&mut Term<()>))] = &[
+            ("SGR", |term| term.terminal_attribute(Attr::Bold)),
+            ("DECSC", |term| term.save_cursor_position()),
+            ("BEL", |term| term.bell()),
+            ("OSC 2", |term| term.set_title(Some("title".into()))),
+            ("zerowidth", |term| term.input('\u{301}')),
+        ];
+
+        for (name, operation) in preserving {
+            let mut term = pending_term();
+            operation(&mut term);
+            assert!(term.grid.cursor.input_needs_wrap, "{} cleared pending wrap", name);
+
+            // The next character is written to the start of the next line.
+            term.input('b');
+            assert_eq!(term.grid[Line(2)][Column(0)].c, 'b', "{} prevented wrapping", name);
+            assert!(term.grid[Line(1)][Column(6)].flags.contains(Flags::WRAPLINE));
+        }
+    }
+
+    #[test]
+    fn sixel_graphic_covers_cells() {
+        let size = SizeInfo::new(100., 60., 10., 20., 0., 0., false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+
+        // Insert a 12x24 pixel graphic at the start of the second line, in the third column.
+        let mut parser = ansi::Processor::new();
+        let sixel = "\x1b[2;3H\x1bPq#1!12~-!12~-!12~-!12~\x1b\\";
+        for byte in sixel.bytes() {
+            parser.advance(&mut term, byte);
+        }
+
+        // The graphic covers two lines, scrolling the terminal to make room for the cursor.
+        let graphic = |line: i32, column: usize| {
+            term.grid[Line(line)][Column(column)].graphic().map(|g| (g.offset_x, g.offset_y))
+        };
+        assert_eq!(graphic(0, 2), Some((0, 0)));
+        assert_eq!(graphic(1, 3), Some((10, 20)));
+        assert_eq!(graphic(1, 4), None);
+        assert_eq!(term.grid.cursor.point, Point::new(Line(2), Column(2)));
+        assert!(term.grid[Line(0)][Column(2)].flags.contains(Flags::GRAPHICS));
+
+        let queues = term.graphics_take_queues().unwrap();
+        assert_eq!((queues.pending[0].width, queues.pending[0].height), (12, 24));
+
+        // Text replaces the graphic.
+        term.goto(Line(0), Column(2));
+        term.input('a');
+        assert_eq!(term.grid[Line(0)][Column(2)].graphic(), None);
+        assert!(!term.grid[Line(0)][Column(2)].flags.contains(Flags::GRAPHICS));
+    }
+
+    #[test]
+    fn sixel_graphic_clears_partial_wide_chars() {
+        let size = SizeInfo::new(100., 60., 10., 20., 0., 0., false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+
+        // Cover the spacer of one wide character and the start of the next one.
+        let mut parser = ansi::Processor::new();
+        let input = "\x1b[1;2H\u{6c49}\u{5b57}\x1b[1;3H\x1bPq#1!12~\x1b\\";
+        for byte in input.bytes() {
+            parser.advance(&mut term, byte);
+        }
+
+        let flags = |column: usize| term.grid[Line(0)][Column(column)].flags;
+        assert!(flags(2).contains(Flags::GRAPHICS));
+        assert!(flags(3).contains(Flags::GRAPHICS));
+        assert!(!flags(1).contains(Flags::WIDE_CHAR));
+        assert_eq!(term.grid[Line(0)][Column(1)].c, ' ');
+        assert!(!flags(4).contains(Flags::WIDE_CHAR_SPACER));
+    }
+
+    #[test]
+    fn kitty_graphic_covers_cells() {
+        let size = SizeInfo::new(100., 60., 10., 20., 0., 0., false);
+        let recorder = EventRecorder::pty_writes();
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+
+        // Transmit and display a 12x24 pixel RGB image in the third column of the first line.
+        let payload = base64::encode(vec![255u8; 12 * 24 * 3]);
+        let command = format!("\x1b[1;3H\x1b_Ga=T,f=24,s=12,v=24,i=1;{}\x1b\\", payload);
+        let mut parser = ansi::Processor::new();
+        for byte in command.bytes() {
+            parser.advance(&mut term, byte);
+        }
+
+        let graphic = |line: i32, column: usize| {
+            term.grid[Line(line)][Column(column)].graphic().map(|g| (g.offset_x, g.offset_y))
+        };
+        assert_eq!(graphic(0, 2), Some((0, 0)));
+        assert_eq!(graphic(1, 3), Some((10, 20)));
+        assert_eq!(graphic(2, 2), None);
+
+        // The cursor is moved to the right of the image, on its last line.
+        assert_eq!(term.grid.cursor.point, Point::new(Line(1), Column(4)));
+
+        let events = recorder.take();
+        assert_eq!(events, ["\x1b_Gi=1;OK\x1b\\"]);
+
+        // Deleting the image clears its cells.
+        for byte in "\x1b_Ga=d,d=I,i=1\x1b\\".bytes() {
+            parser.advance(&mut term, byte);
+        }
+        assert_eq!(term.grid[Line(0)][Column(2)].graphic(), None);
+        assert_eq!(term.grid[Line(1)][Column(3)].graphic(), None);
+    }
+
+    #[test]
+    fn osc52_clipboard_access() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::new(|event| match event {
+            Event::ClipboardStore(..) | Event::ClipboardLoad(..) => Some(format!("{:?}", event)),
+            _ => None,
+        });
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+
+        // Reading the clipboard is blocked by default.
+        term.clipboard_store(b'c', b"dGVzdA==");
+        term.clipboard_load(b'c', "\x07");
+        assert_eq!(recorder.take(), ["ClipboardStore(Clipboard, test)"]);
+
+        term.update_config(&MockConfig { osc52: Osc52::OnlyPaste, ..Default::default() });
+        term.clipboard_store(b'c', b"dGVzdA==");
+        term.clipboard_load(b'c', "\x07");
+        assert_eq!(recorder.take(), ["ClipboardLoad(Clipboard)"]);
+    }
+
+    #[test]
+    fn kitty_keyboard_mode_stack() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::pty_writes();
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+        let mut parser = ansi::Processor::new();
+
+        // Push, extend and query the modes.
+        for byte in b"\x1b[>1u\x1b[=8;2u\x1b[?u" {
+            parser.advance(&mut term, *byte);
+        }
+        let kitty_mode = TermMode::DISAMBIGUATE_ESC_CODES | TermMode::REPORT_ALL_KEYS_AS_ESC;
+        assert_eq!(term.mode & TermMode::KITTY_KEYBOARD_PROTOCOL, kitty_mode);
+        assert_eq!(recorder.take(), ["\x1b[?9u"]);
+
+        // The alternate screen has its own stack.
+        for byte in b"\x1b[?1049h\x1b[>2u" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.mode & TermMode::KITTY_KEYBOARD_PROTOCOL, TermMode::REPORT_EVENT_TYPES);
+
+        for byte in b"\x1b[?1049l" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.mode & TermMode::KITTY_KEYBOARD_PROTOCOL, kitty_mode);
+
+        // Popping more modes than pushed resets them.
+        for byte in b"\x1b[<5u" {
+            parser.advance(&mut term, *byte);
+        }
+        assert!(term.keyboard_mode_stack.is_empty());
+        assert!(!term.mode.intersects(TermMode::KITTY_KEYBOARD_PROTOCOL));
+    }
+
+    #[test]
+    fn underline_styles() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        for byte in b"\x1b[4:3ma\x1b[4:4mb\x1b[4:5mc\x1b[4:0md" {
+            parser.advance(&mut term, *byte);
+        }
+
+        let flags: Vec<_> =
+            (0..4).map(|i| term.grid[Line(0)][Column(i)].flags & Flags::ALL_UNDERLINES).collect();
+        let expected =
+            [Flags::UNDERCURL, Flags::DOTTED_UNDERLINE, Flags::DASHED_UNDERLINE, Flags::empty()];
+        assert_eq!(flags, expected);
+    }
+
+    #[test]
+    fn blink_attribute() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        for byte in b"\x1b[5ma\x1b[25mb\x1b[6mc\x1b[5md" {
+            parser.advance(&mut term, *byte);
+        }
+
+        let blinking: Vec<_> =
+            (0..4).map(|i| term.grid[Line(0)][Column(i)].flags & Flags::ALL_BLINKS).collect();
+        let expected = [Flags::BLINK, Flags::empty(), Flags::RAPID_BLINK, Flags::BLINK];
+        assert_eq!(blinking, expected);
+    }
+
+    #[test]
+    fn cursor_styles() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::new(|event| match event {
+            Event::CursorBlinkingChange(blinking) => Some(blinking),
+            _ => None,
+        });
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+        let mut parser = ansi::Processor::new();
+
+        let expected = [
+            (CursorShape::Block, true),
+            (CursorShape::Block, false),
+            (CursorShape::Underline, true),
+            (CursorShape::Underline, false),
+            (CursorShape::Beam, true),
+            (CursorShape::Beam, false),
+        ];
+        for (i, (shape, blinking)) in expected.iter().enumerate() {
+            for byte in format!("\x1b[{} q", i + 1).bytes() {
+                parser.advance(&mut term, byte);
+            }
+            assert_eq!(term.cursor_style(), CursorStyle { shape: *shape, blinking: *blinking });
+        }
+
+        // Both DECSCUSR 0 and a terminal reset restore the configured style.
+        for byte in b"\x1b[5 q\x1b[0 q" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.cursor_style(), term.default_cursor_style);
+        for byte in b"\x1b[3 q\x1bc" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.cursor_style(), term.default_cursor_style);
+
+        let events = recorder.take();
+        assert_eq!(events, [true, false, true, false, true, false, true, false, true, false]);
+    }
+
+    #[test]
+    fn rectangle_operations() {
+        let size = SizeInfo::new(5.0, 4.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        let text = |term: &Term<()>| -> Vec<String> {
+            (0..4)
+                .map(|line| term.grid[Line(line)][..].iter().map(|cell| cell.c).collect())
+                .collect()
+        };
+
+        // Fill, copy and erase part of the copied area.
+        for byte in b"\x1b[65;1;1;2;2$x\x1b[1;1;2;2;1;3;3;1$v\x1b[2;2;2;3$z" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(text(&term), ["AA   ", "A    ", "  AA ", "  AA "]);
+
+        // Erasing the spacer of a wide char removes the wide char.
+        for byte in "\x1b[4;1H字\x1b[4;2;4;2$z".bytes() {
+            parser.advance(&mut term, byte);
+        }
+        assert_eq!(text(&term)[3], "  AA ");
+        assert!(!term.grid[Line(3)][Column(0)].flags.contains(Flags::WIDE_CHAR));
+
+        // Coordinates are relative to and clamped by the scrolling region in origin mode.
+        for byte in b"\x1b[2;3r\x1b[?6h\x1b[1;1;9;9$z" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(text(&term), ["AA   ", "     ", "     ", "  AA "]);
+    }
+
+    #[test]
+    fn modify_other_keys() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::pty_writes();
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+        let mut parser = ansi::Processor::new();
+
+        for byte in b"\x1b[>4;2m\x1b[?4m" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.modify_other_keys(), ModifyOtherKeys::EnableAll);
+        assert_eq!(recorder.take(), ["\x1b[>4;2m"]);
+
+        // Omitting the value resets the encoding.
+        for byte in b"\x1b[>4m" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.modify_other_keys(), ModifyOtherKeys::Reset);
+    }
+
+    #[test]
+    fn status_setting_reports() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::pty_writes();
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+        let mut parser = ansi::Processor::new();
+
+        let input = b"\x1b[1;31m\x1bP$qm\x1b\\\x1b[3 q\x1bP$q q\x1b\\\
+                      \x1b[2;10r\x1bP$qr\x1b\\\x1bP$qx\x1b\\";
+        for byte in &input[..] {
+            parser.advance(&mut term, *byte);
+        }
+
+        assert_eq!(recorder.take(), [
+            "\x1bP1$r0;1;31m\x1b\\",
+            "\x1bP1$r3 q\x1b\\",
+            "\x1bP1$r2;10r\x1b\\",
+            "\x1bP0$r\x1b\\",
+        ]);
+    }
+
+    #[test]
+    fn color_query_reports() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::new(|event| match event {
+            Event::PtyWrite(text) => Some(text),
+            Event::ColorRequest(_, format) => Some(format(Rgb { r: 0x12, g: 0x34, b: 0x56 })),
+            _ => None,
+        });
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+        let mut parser = ansi::Processor::new();
+
+        let input = b"\x1b]4;1;rgb:ff/00/80;2;?\x07\x1b]4;1;?\x1b\\\x1b]11;?\x07";
+        for byte in &input[..] {
+            parser.advance(&mut term, *byte);
+        }
+
+        assert_eq!(recorder.take(), [
+            "\x1b]4;2;rgb:1212/3434/5656\x07",
+            "\x1b]4;1;rgb:ffff/0000/8080\x1b\\",
+            "\x1b]11;rgb:1212/3434/5656\x07",
+        ]);
+    }
+
+    #[test]
+    fn color_reset() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        let red = Some(Rgb { r: 0xff, g: 0, b: 0 });
+        let set_colors = b"\x1b]4;1;#ff0000;2;#ff0000\x07\x1b]10;#ff0000;#ff0000;#ff0000\x07";
+        let mut set_and_reset = |term: &mut Term<()>, reset: &[u8]| {
+            for byte in set_colors.iter().chain(reset) {
+                parser.advance(term, *byte);
+            }
+        };
+
+        set_and_reset(&mut term, b"\x1b]104;2\x07\x1b]110\x07\x1b]112\x07");
+        assert_eq!(term.colors()[1], red);
+        assert_eq!(term.colors()[2], None);
+        assert_eq!(term.colors()[NamedColor::Foreground], None);
+        assert_eq!(term.colors()[NamedColor::Background], red);
+        assert_eq!(term.colors()[NamedColor::Cursor], None);
+
+        set_and_reset(&mut term, b"\x1b]104;\x07\x1b]111\x07");
+        assert_eq!(term.colors()[1], None);
+        assert_eq!(term.colors()[2], None);
+        assert_eq!(term.colors()[NamedColor::Foreground], red);
+        assert_eq!(term.colors()[NamedColor::Background], None);
+
+        set_and_reset(&mut term, b"\x1bc");
+        assert_eq!(term.colors()[1], None);
+        assert_eq!(term.colors()[NamedColor::Foreground], None);
+    }
+
+    #[test]
+    fn icon_name_and_title() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        for byte in b"\x1b]0;both\x07\x1b]1;icon\x07" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.title(), Some("both"));
+        assert_eq!(term.icon_name(), Some("icon"));
+
+        for byte in b"\x1b]2;title\x07" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.title(), Some("title"));
+        assert_eq!(term.icon_name(), Some("icon"));
+
+        for byte in b"\x1bc" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.title(), None);
+        assert_eq!(term.icon_name(), None);
+    }
+
+    #[test]
+    fn region_history() {
+        let mut config = MockConfig::default();
+        config.scrolling.region_history = false;
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&config, size, ());
+        let mut parser = ansi::Processor::new();
+
+        // Lines scrolled out of partial regions are discarded.
+        for byte in b"\x1b[1;3r\x1b[3Ha\n\nb\x1b[2M" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.grid().history_size(), 0);
+
+        // Lines scrolled out of the entire screen are still saved.
+        for byte in b"\x1b[r\x1b[17H\n\n" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.grid().history_size(), 2);
+
+        config.scrolling.region_history = true;
+        term.update_config(&config);
+        for byte in b"\x1b[1;3r\x1b[3H\n" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.grid().history_size(), 3);
+    }
+
+    #[test]
+    fn mouse_encodings() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let encodings = TermMode::UTF8_MOUSE | TermMode::SGR_MOUSE | TermMode::SGR_PIXEL_MOUSE;
+
+        term.set_mode(ansi::Mode::SgrMouse);
+        term.set_mode(ansi::Mode::SgrPixelMouse);
+        assert_eq!(term.mode & encodings, TermMode::SGR_PIXEL_MOUSE);
+
+        term.set_mode(ansi::Mode::Utf8Mouse);
+        assert_eq!(term.mode & encodings, TermMode::UTF8_MOUSE);
+
+        term.set_mode(ansi::Mode::SgrPixelMouse);
+        term.unset_mode(ansi::Mode::SgrPixelMouse);
+        assert!(!term.mode.intersects(encodings));
+    }
+
+    #[test]
+    fn tab_stops() {
+        let size = SizeInfo::new(40.0, 5.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        // Replace the default stops with stops at the 4th and 11th column.
+        for byte in b"\x1b[3g\x1b[4G\x1bH\x1b[11G\x1bH\r" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.tab_stops().collect::<Vec<_>>(), vec![Column(3), Column(10)]);
+
+        term.put_tab(1);
+        assert_eq!(term.grid.cursor.point.column, Column(3));
+
+        term.move_forward_tabs(1);
+        assert_eq!(term.grid.cursor.point.column, Column(10));
+
+        term.move_forward_tabs(2);
+        assert_eq!(term.grid.cursor.point.column, Column(39));
+
+        term.move_backward_tabs(1);
+        assert_eq!(term.grid.cursor.point.column, Column(10));
+
+        term.move_backward_tabs(3);
+        assert_eq!(term.grid.cursor.point.column, Column(0));
+
+        // Clear the stop at the cursor.
+        for byte in b"\x1b[11G\x1b[g\r\x1b[2I" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.tab_stops().collect::<Vec<_>>(), vec![Column(3)]);
+        assert_eq!(term.grid.cursor.point.column, Column(39));
+    }
+
+    #[test]
+    fn selective_erase() {
+        let size = SizeInfo::new(5.0, 3.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        // Protect the middle of the first two lines, SGR 0 must not reset the protection.
+        for byte in b"ab\x1b[1\"q\x1b[0mcd\x1b[\"qe\r\nfg\x1b[1\"qh\x1b[2\"qij\r\nklmno" {
+            parser.advance(&mut term, *byte);
+        }
+        assert!(term.grid[Line(0)][Column(2)].flags.contains(Flags::PROTECTED));
+        assert!(!term.grid[Line(0)][Column(4)].flags.contains(Flags::PROTECTED));
+
+        // Selective erase keeps protected cells.
+        for byte in b"\x1b[1;1H\x1b[?K\x1b[2;5H\x1b[?1J" {
+            parser.advance(&mut term, *byte);
+        }
+        let line_text = |term: &Term<()>, line| {
+            term.grid[Line(line)][..].iter().map(|cell| cell.c).collect::<String>()
+        };
+        assert_eq!(line_text(&term, 0), "  cd ");
+        assert_eq!(line_text(&term, 1), "  h  ");
+        assert_eq!(line_text(&term, 2), "klmno");
+
+        // Regular erase ignores protection.
+        for byte in b"\x1b[2J" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(line_text(&term, 0), "     ");
+        assert_eq!(line_text(&term, 1), "     ");
+    }
+
+    #[test]
+    fn selective_erase_wide_chars() {
+        let size = SizeInfo::new(5.0, 2.0, 1.0, 1.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        // Erase a protected and an unprotected wide char, ending on the unprotected spacer.
+        let input = "\x1b[1\"q字\x1b[0\"q字a\x1b[1;4H\x1b[?1K";
+        // Erase a wide char starting on its spacer.
+        let input = format!("{}\x1b[2;1Ha字bc\x1b[2;3H\x1b[?K", input);
+        for byte in input.as_bytes() {
+            parser.advance(&mut term, *byte);
+        }
+
+        let line_text = |term: &Term<()>, line| {
+            term.grid[Line(line)][..].iter().map(|cell| cell.c).collect::<String>()
+        };
+        assert_eq!(line_text(&term, 0), "字   a");
+        assert!(term.grid[Line(0)][Column(1)].flags.contains(Flags::WIDE_CHAR_SPACER));
+        assert!(!term.grid[Line(0)][Column(3)].flags.contains(Flags::WIDE_CHAR_SPACER));
+        assert_eq!(line_text(&term, 1), "a    ");
+        assert!(!term.grid[Line(1)][Column(1)].flags.contains(Flags::WIDE_CHAR));
+    }
+
+    #[test]
+    fn working_directory() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        for byte in b"\x1b]7;file://localhost/tmp/a;b%20c\x07" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.working_directory(), Some(Path::new("/tmp/a;b c")));
+
+        for byte in b"\x1b]7;\x07" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.working_directory(), None);
+    }
+
+    #[test]
+    fn window_name() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        term.set_window_name(Some("cli".into()));
+        assert_eq!(term.window_name(), Some("cli"));
+
+        // The name belongs to the window, so it survives a terminal reset.
+        for byte in b"\x1b]1337;WindowName=osc\x07\x1bc" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.window_name(), Some("osc"));
+    }
+
+    #[test]
+    fn shell_marks() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        let input = b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\r\n\
+                      \x1b]133;D;0\x07\x1b]133;A\x07$ ";
+        for byte in &input[..] {
+            parser.advance(&mut term, *byte);
+        }
+
+        let marks = term.grid()[Line(0)].marks();
+        assert_eq!(marks, LineMarks::PROMPT_START | LineMarks::COMMAND_START);
+        assert_eq!(term.prompt_line(Line(3), Direction::Left), Some(Line(0)));
+        assert_eq!(term.prompt_line(Line(0), Direction::Right), Some(Line(3)));
+        assert_eq!(term.prompt_line(Line(3), Direction::Right), None);
+        assert_eq!(term.last_command_output(), Some((Line(1), Line(2))));
+
+        // Marks move into the scrollback history with their lines.
+        for byte in b"\x1b[17H\n\n\n\n\n" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.prompt_line(Line(0), Direction::Left), Some(Line(-2)));
+        assert_eq!(term.last_command_output(), Some((Line(-4), Line(-3))));
+
+        // Lines reused at the bottom of the screen have no marks.
+        assert_eq!(term.grid()[Line(16)].marks(), LineMarks::empty());
+    }
+
+    #[test]
+    fn command_output_without_trailing_newline() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let mut term = Term::new(&MockConfig::default(), size, ());
+        let mut parser = ansi::Processor::new();
+
+        let input = b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\
+                      \x1b]133;D;0\x07\r\n\x1b]133;A\x07$ ";
+        for byte in &input[..] {
+            parser.advance(&mut term, *byte);
+        }
+
+        assert_eq!(term.last_command_output(), Some((Line(1), Line(2))));
+
+        // Output on the prompt's line is kept when the shell does not report the command end.
+        let input = b"\x1b]133;B\x07ls\r\n\x1b]133;C\x07c\r\nd\x1b]133;A\x07$ ";
+        for byte in &input[..] {
+            parser.advance(&mut term, *byte);
+        }
+
+        assert_eq!(term.last_command_output(), Some((Line(4), Line(5))));
+    }
+
+    #[test]
+    fn capability_reports() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::new(|event| match event {
+            Event::PtyWrite(text) => Some(text),
+            Event::CapabilityRequest(name, format) => match name.as_str() {
+                "TN" => Some(format(Some("alacritty"))),
+                "am" => Some(format(Some(""))),
+                _ => Some(format(None)),
+            },
+            _ => None,
+        });
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+        let mut parser = ansi::Processor::new();
+
+        for byte in &b"\x1bP+q544e;616d;7878;7\x1b\\"[..] {
+            parser.advance(&mut term, *byte);
+        }
+
+        assert_eq!(recorder.take(), [
+            "\x1bP1+r544E=616C61637269747479\x1b\\",
+            "\x1bP1+r616D\x1b\\",
+            "\x1bP0+r7878\x1b\\",
+            "\x1bP0+r\x1b\\",
+        ]);
+    }
+
+    #[test]
+    fn unhandled_sequence_events() {
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0.0, 0.0, false);
+        let recorder = EventRecorder::new(|event| match event {
+            Event::UnhandledSequence(sequence) => Some(sequence),
+            _ => None,
+        });
+        let mut term = Term::new(&MockConfig::default(), size, recorder.clone());
+        let mut parser = ansi::Processor::new();
+
+        for byte in &b"\x1bP1;2$zdata\x1b\\\x1b_apc\x1b\\"[..] {
+            parser.advance(&mut term, *byte);
+        }
+
+        assert_eq!(recorder.take(), [
+            UnhandledSequence::Dcs {
+                params: vec![1, 2],
+                intermediates: vec![b'$'],
+                action: 'z',
+                data: b"data".to_vec(),
+            },
+            UnhandledSequence::Apc(b"apc".to_vec()),
+        ]);
+    }
+
+    #[test]
+    fn clipboard_text_sanitization() {
+        assert_eq!(strip_invalid_utf8(b"copy"), "copy");
+        assert_eq!(strip_invalid_utf8(b"a\xffb\xc0\xafc\xe2\x94"), "abc");
+        assert_eq!(strip_invalid_utf8(b"\xe2\x94\x82\xff"), "│");
+    }
+
     #[test]
     fn parse_cargo_version() {
         assert!(version_number(env!("CARGO_PKG_VERSION")) >= 10_01);
@@ -0,0 +1,114 @@
+//! Adjacent cells with identical styling.
+//!
+//! Grouping cells into runs allows renderers to resolve fonts and draw backgrounds once for many
+//! cells, and provides natural boundaries for shaping text.
+
+use crate::grid::Indexed;
+use crate::term::cell::{Cell, Flags};
+
+/// Cell which can be grouped into runs.
+pub trait RunCell {
+    /// Check if `next` directly follows this cell on the same line, with identical styling.
+    fn continues_with(&self, next: &Self) -> bool;
+}
+
+/// Text attributes of a cell, without the flags describing its layout.
+#[inline]
+pub fn style_flags(flags: Flags) -> Flags {
+    flags
+        - Flags::WRAPLINE
+        - Flags::WIDE_CHAR
+        - Flags::WIDE_CHAR_SPACER
+        - Flags::LEADING_WIDE_CHAR_SPACER
+        - Flags::PROTECTED
+}
+
+impl<'a> RunCell for Indexed<&'a Cell> {
+    /// Wide characters are followed by their spacer, so adjacent cells are always one column
+    /// apart.
+    fn continues_with(&self, next: &Self) -> bool {
+        next.point.line == self.point.line
+            && next.point.column == self.point.column + 1
+            && next.fg == self.fg
+            && next.bg == self.bg
+            && style_flags(next.flags) == style_flags(self.flags)
+    }
+}
+
+/// Iterator splitting cells into runs of adjacent cells with identical styling.
+///
+/// Runs borrow the cells they consist of, so splitting cells into runs does not require any
+/// allocations. The cells are expected in the order of the grid's display iterator.
+pub struct RenderableRuns<'a, T> {
+    cells: &'a [T],
+}
+
+impl<'a, T> RenderableRuns<'a, T> {
+    pub fn new(cells: &'a [T]) -> Self {
+        Self { cells }
+    }
+}
+
+impl<'a, T: RunCell> Iterator for RenderableRuns<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cells.is_empty() {
+            return None;
+        }
+
+        let mut len = 1;
+        while len < self.cells.len() && self.cells[len - 1].continues_with(&self.cells[len]) {
+            len += 1;
+        }
+
+        let (run, remaining) = self.cells.split_at(len);
+        self.cells = remaining;
+
+        Some(run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ansi::{Color, NamedColor};
+    use crate::index::{Column, Line, Point};
+
+    #[test]
+    fn runs_of_grid_cells() {
+        let mut wide = Cell::default();
+        wide.c = '字';
+        wide.flags = Flags::WIDE_CHAR;
+        let mut spacer = Cell::default();
+        spacer.flags = Flags::WIDE_CHAR_SPACER | Flags::WRAPLINE;
+        let mut red = Cell::default();
+        red.fg = Color::Named(NamedColor::Red);
+        let mut bold = Cell::default();
+        bold.flags = Flags::BOLD;
+
+        let cells = [
+            (0, 0, &wide),
+            (0, 1, &spacer),
+            (0, 2, &Cell::default()),
+            (0, 3, &red),
+            (0, 5, &red),
+            (1, 0, &bold),
+            (1, 1, &bold),
+        ];
+        let cells: Vec<_> = cells
+            .iter()
+            .map(|&(line, column, cell)| Indexed {
+                point: Point::new(Line(line), Column(column)),
+                cell,
+            })
+            .collect();
+
+        let runs: Vec<_> = RenderableRuns::new(&cells)
+            .map(|run| (run[0].point.line.0, run[0].point.column.0, run.len()))
+            .collect();
+
+        assert_eq!(runs, [(0, 0, 3), (0, 3, 1), (0, 5, 1), (1, 0, 2)]);
+    }
+}
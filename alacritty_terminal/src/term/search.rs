@@ -1,5 +1,4 @@
 use std::cmp::max;
-use std::mem;
 use std::ops::RangeInclusive;
 
 use regex_automata::{dense, DenseDFA, Error as RegexError, DFA};
@@ -203,30 +202,26 @@ impl<T> Term<T> {
 
         let mut iter = self.grid.iter_from(start);
         let mut state = dfa.start_state();
-        let mut last_wrapped = false;
         let mut regex_match = None;
 
         let mut cell = iter.cell();
         self.skip_fullwidth(&mut iter, &mut cell, direction);
-        let mut c = cell.c;
 
         let mut point = iter.point();
+        let mut line = self.logical_line(point.line);
 
         loop {
-            // Convert char to array of bytes.
-            let mut buf = [0; 4];
-            let utf8_len = c.encode_utf8(&mut buf).len();
-
-            // Pass char to DFA as individual bytes.
-            for i in 0..utf8_len {
-                // Inverse byte order when going left.
-                let byte = match direction {
-                    Direction::Right => buf[i],
-                    Direction::Left => buf[utf8_len - i - 1],
-                };
-
-                // Since we get the state from the DFA, it doesn't need to be checked.
-                state = unsafe { dfa.next_state_unchecked(state, byte) };
+            // Pass the cell's characters to the DFA, including all zerowidth characters.
+            let zerowidth = cell.zerowidth().unwrap_or(&[]);
+            match direction {
+                Direction::Right => {
+                    state = Self::advance_dfa(dfa, state, cell.c, direction);
+                    state = Self::advance_dfa_zerowidth(dfa, state, zerowidth, direction);
+                },
+                Direction::Left => {
+                    state = Self::advance_dfa_zerowidth(dfa, state, zerowidth, direction);
+                    state = Self::advance_dfa(dfa, state, cell.c, direction);
+                },
             }
 
             // Handle regex state changes.
@@ -244,7 +239,7 @@ impl<T> Term<T> {
             }
 
             // Advance grid cell iterator.
-            let mut cell = match next(&mut iter) {
+            cell = match next(&mut iter) {
                 Some(Indexed { cell, .. }) => cell,
                 None => {
                     // Wrap around to other end of the scrollback buffer.
@@ -255,31 +250,82 @@ impl<T> Term<T> {
                 },
             };
             self.skip_fullwidth(&mut iter, &mut cell, direction);
-            let wrapped = cell.flags.contains(Flags::WRAPLINE);
-            c = cell.c;
-
-            let last_point = mem::replace(&mut point, iter.point());
+            point = iter.point();
 
             // Handle linebreaks.
-            if (last_point.column == last_column && point.column == Column(0) && !last_wrapped)
-                || (last_point.column == Column(0) && point.column == last_column && !wrapped)
-            {
+            if !line.contains(point.line) {
                 match regex_match {
                     Some(_) => break,
                     None => state = dfa.start_state(),
                 }
-            }
 
-            last_wrapped = wrapped;
+                line = self.logical_line(point.line);
+            }
         }
 
         regex_match
     }
 
+    /// Pass a single character to the DFA.
+    ///
+    /// Bytes are passed in reverse order when searching to the left.
+    #[inline]
+    fn advance_dfa<D: DFA>(dfa: &D, mut state: D::ID, c: char, direction: Direction) -> D::ID {
+        // Convert char to array of bytes.
+        let mut buf = [0; 4];
+        let utf8_len = c.encode_utf8(&mut buf).len();
+
+        // Pass char to DFA as individual bytes.
+        for i in 0..utf8_len {
+            // Inverse byte order when going left.
+            let byte = match direction {
+                Direction::Right => buf[i],
+                Direction::Left => buf[utf8_len - i - 1],
+            };
+
+            // Since we get the state from the DFA, it doesn't need to be checked.
+            state = unsafe { dfa.next_state_unchecked(state, byte) };
+        }
+
+        state
+    }
+
+    /// Pass a cell's zerowidth characters to the DFA.
+    ///
+    /// If the zerowidth characters cannot be part of a match, they are ignored so matches on
+    /// the base character alone are still found.
+    #[inline]
+    fn advance_dfa_zerowidth<D: DFA>(
+        dfa: &D,
+        state: D::ID,
+        zerowidth: &[char],
+        direction: Direction,
+    ) -> D::ID {
+        let mut zerowidth_state = state;
+        match direction {
+            Direction::Right => {
+                for c in zerowidth {
+                    zerowidth_state = Self::advance_dfa(dfa, zerowidth_state, *c, direction);
+                }
+            },
+            Direction::Left => {
+                for c in zerowidth.iter().rev() {
+                    zerowidth_state = Self::advance_dfa(dfa, zerowidth_state, *c, direction);
+                }
+            },
+        }
+
+        if dfa.is_dead_state(zerowidth_state) {
+            state
+        } else {
+            zerowidth_state
+        }
+    }
+
     /// Advance a grid iterator over fullwidth characters.
     fn skip_fullwidth<'a>(
         &self,
-        iter: &'a mut GridIterator<'_, Cell>,
+        iter: &mut GridIterator<'a, Cell>,
         cell: &mut &'a Cell,
         direction: Direction,
     ) {
@@ -362,18 +408,17 @@ impl<T> Term<T> {
         point.line = max(point.line, self.topmost_line());
 
         let mut iter = self.grid.iter_from(point);
-        let last_column = self.columns() - 1;
+        let line = self.logical_line(point.line);
 
         let wide = Flags::WIDE_CHAR | Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
         while let Some(cell) = iter.prev() {
-            if !cell.flags.intersects(wide) && self.semantic_escape_chars.contains(cell.c) {
+            // Stop at the start of the logical line or the first escape char.
+            if !line.contains(cell.point.line)
+                || (!cell.flags.intersects(wide) && self.semantic_escape_chars.contains(cell.c))
+            {
                 break;
             }
 
-            if cell.point.column == last_column && !cell.flags.contains(Flags::WRAPLINE) {
-                break; // cut off if on new line or hit escape char
-            }
-
             point = cell.point;
         }
 
@@ -386,47 +431,30 @@ impl<T> Term<T> {
         point.line = max(point.line, self.topmost_line());
 
         let wide = Flags::WIDE_CHAR | Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
-        let last_column = self.columns() - 1;
+        let line = self.logical_line(point.line);
 
         for cell in self.grid.iter_from(point) {
-            if !cell.flags.intersects(wide) && self.semantic_escape_chars.contains(cell.c) {
+            // Stop at the end of the logical line or the first escape char.
+            if !line.contains(cell.point.line)
+                || (!cell.flags.intersects(wide) && self.semantic_escape_chars.contains(cell.c))
+            {
                 break;
             }
 
             point = cell.point;
-
-            if point.column == last_column && !cell.flags.contains(Flags::WRAPLINE) {
-                break; // cut off if on new line or hit escape char
-            }
         }
 
         point
     }
 
     /// Find the beginning of the current line across linewraps.
-    pub fn line_search_left(&self, mut point: Point) -> Point {
-        while point.line > self.topmost_line()
-            && self.grid[point.line - 1i32][self.last_column()].flags.contains(Flags::WRAPLINE)
-        {
-            point.line -= 1;
-        }
-
-        point.column = Column(0);
-
-        point
+    pub fn line_search_left(&self, point: Point) -> Point {
+        self.logical_line(point.line).start_point()
     }
 
     /// Find the end of the current line across linewraps.
-    pub fn line_search_right(&self, mut point: Point) -> Point {
-        while point.line + 1 < self.screen_lines()
-            && self.grid[point.line][self.last_column()].flags.contains(Flags::WRAPLINE)
-        {
-            point.line += 1;
-        }
-
-        point.column = self.last_column();
-
-        point
+    pub fn line_search_right(&self, point: Point) -> Point {
+        self.logical_line(point.line).end_point(self)
     }
 }
 
@@ -823,4 +851,43 @@ mod tests {
         let match_end = Point::new(Line(1), Column(2));
         assert_eq!(term.regex_search_left(&dfas, start, end), Some(match_start..=match_end));
     }
+
+    #[test]
+    fn zerowidth() {
+        let mut term = mock_term("xex");
+        term.grid_mut()[Line(0)][Column(1)].push_zerowidth('\u{301}');
+
+        let dfas = RegexSearch::new("e\u{301}").unwrap();
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(0), Column(2));
+        let match_point = Point::new(Line(0), Column(1));
+        assert_eq!(term.regex_search_right(&dfas, start, end), Some(match_point..=match_point));
+        assert_eq!(term.regex_search_left(&dfas, end, start), Some(match_point..=match_point));
+    }
+
+    #[test]
+    fn zerowidth_ignored() {
+        let mut term = mock_term("cafe\r\nx\u{2764}x");
+        term.grid_mut()[Line(0)][Column(3)].push_zerowidth('\u{301}');
+        term.grid_mut()[Line(1)][Column(1)].push_zerowidth('\u{fe0f}');
+
+        // Zerowidth characters do not prevent matching their base character.
+        let dfas = RegexSearch::new("cafe").unwrap();
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(0), Column(3));
+        assert_eq!(term.regex_search_right(&dfas, start, end), Some(start..=end));
+        assert_eq!(term.regex_search_left(&dfas, end, start), Some(start..=end));
+
+        let dfas = RegexSearch::new("\u{2764}").unwrap();
+        let start = Point::new(Line(1), Column(0));
+        let end = Point::new(Line(1), Column(2));
+        let match_point = Point::new(Line(1), Column(1));
+        assert_eq!(term.regex_search_right(&dfas, start, end), Some(match_point..=match_point));
+        assert_eq!(term.regex_search_left(&dfas, end, start), Some(match_point..=match_point));
+
+        // Zerowidth characters are still required when part of the regex.
+        let dfas = RegexSearch::new("e\u{301}").unwrap();
+        let start = Point::new(Line(1), Column(0));
+        assert_eq!(term.regex_search_right(&dfas, start, end), None);
+    }
 }
@@ -1,6 +1,8 @@
 //! TTY related functionality.
 
+use std::fmt::{self, Display, Formatter};
 use std::path::PathBuf;
+use std::time::Duration;
 use std::{env, io};
 
 use crate::config::Config;
@@ -41,8 +43,34 @@ pub trait EventedReadWrite {
 /// Events concerning TTY child processes.
 #[derive(Debug, PartialEq)]
 pub enum ChildEvent {
-    /// Indicates the child has exited.
-    Exited,
+    /// Indicates the child has exited, with its status if it could be determined.
+    Exited(Option<ExitStatus>),
+}
+
+/// Reason for the termination of a child process.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// The process exited with an exit code.
+    Code(i32),
+
+    /// The process was terminated by a signal.
+    Signal(i32),
+}
+
+impl ExitStatus {
+    /// Check if the process exited without any errors.
+    pub fn success(self) -> bool {
+        self == ExitStatus::Code(0)
+    }
+}
+
+impl Display for ExitStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitStatus::Code(code) => write!(f, "exited with code {}", code),
+            ExitStatus::Signal(signal) => write!(f, "was terminated by signal {}", signal),
+        }
+    }
 }
 
 /// A pseudoterminal (or PTY).
@@ -57,6 +85,14 @@ pub trait EventedPty: EventedReadWrite {
     ///
     /// Returns `Some(event)` on success, or `None` if there are no events to retrieve.
     fn next_child_event(&mut self) -> Option<ChildEvent>;
+
+    /// Ask the child to exit, since the terminal is shutting down.
+    ///
+    /// Returns how long to wait for the child to exit, before it is killed once the PTY is
+    /// dropped.
+    fn hang_up(&mut self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Setup environment variables.
@@ -9,25 +9,26 @@ use std::io;
 use std::mem::MaybeUninit;
 use std::os::unix::{
     io::{AsRawFd, FromRawFd, RawFd},
-    process::CommandExt,
+    process::{CommandExt, ExitStatusExt},
 };
 use std::process::{Child, Command, Stdio};
 use std::ptr;
 use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use std::time::Duration;
 
 use libc::{self, c_int, pid_t, winsize, TIOCSCTTY};
-use log::error;
+use log::{error, info};
 use mio::unix::EventedFd;
 use nix::pty::openpty;
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 use nix::sys::termios::{self, InputFlags, SetArg};
 use signal_hook::{self as sighook, iterator::Signals};
 
-use crate::config::{Config, Program};
+use crate::config::{Config, Login, Program, Shell};
 use crate::event::OnResize;
 use crate::grid::Dimensions;
 use crate::term::SizeInfo;
-use crate::tty::{ChildEvent, EventedPty, EventedReadWrite};
+use crate::tty::{ChildEvent, EventedPty, EventedReadWrite, ExitStatus};
 
 /// Process ID of child process.
 ///
@@ -52,6 +53,19 @@ pub fn master_fd() -> RawFd {
     FD.load(Ordering::Relaxed) as RawFd
 }
 
+/// Process group of the PTY's foreground job, if it is not the child itself.
+pub fn foreground_process_group() -> Option<pid_t> {
+    let pgid = unsafe { libc::tcgetpgrp(master_fd()) };
+    Some(pgid).filter(|&pgid| pgid > 0 && pgid != child_pid())
+}
+
+/// Name of the local host.
+pub fn hostname() -> Option<String> {
+    let mut buf = [0; 256];
+    let hostname = nix::unistd::gethostname(&mut buf).ok()?;
+    hostname.to_str().ok().map(String::from)
+}
+
 /// Get raw fds for master/slave ends of a new PTY.
 fn make_pty(size: winsize) -> (RawFd, RawFd) {
     let mut win_size = size;
@@ -137,14 +151,13 @@ pub struct Pty {
     token: mio::Token,
     signals: Signals,
     signals_token: mio::Token,
+    kill_timeout: Option<Duration>,
+    hung_up: bool,
 }
 
 #[cfg(target_os = "macos")]
 fn default_shell(pw: &Passwd<'_>) -> Program {
-    let shell_name = pw.shell.rsplit('/').next().unwrap();
-    let argv = vec![String::from("-c"), format!("exec -a -{} {}", shell_name, pw.shell)];
-
-    Program::WithArgs { program: "/bin/bash".to_owned(), args: argv }
+    Program::Just(pw.shell.to_owned())
 }
 
 #[cfg(not(target_os = "macos"))]
@@ -152,6 +165,18 @@ fn default_shell(pw: &Passwd<'_>) -> Program {
     Program::Just(env::var("SHELL").unwrap_or_else(|_| pw.shell.to_owned()))
 }
 
+/// Check if the shell should be started as login shell.
+///
+/// Without explicit configuration, only the default shell on macOS is a login shell, since
+/// terminals there are expected to start one.
+fn is_login_shell(shell: &Shell) -> bool {
+    match shell.login {
+        Login::Always => true,
+        Login::Never => false,
+        Login::Auto => shell.program.is_none() && cfg!(target_os = "macos"),
+    }
+}
+
 /// Create a new TTY and return a handle to interact with it.
 pub fn new<C>(config: &Config<C>, size: &SizeInfo, window_id: Option<usize>) -> Pty {
     let (master, slave) = make_pty(size.to_winsize());
@@ -166,12 +191,19 @@ pub fn new<C>(config: &Config<C>, size: &SizeInfo, window_id: Option<usize>) ->
     let mut buf = [0; 1024];
     let pw = get_pw_entry(&mut buf);
 
-    let shell = match config.shell.as_ref() {
+    let shell = match config.shell.program.as_ref() {
         Some(shell) => Cow::Borrowed(shell),
         None => Cow::Owned(default_shell(&pw)),
     };
 
     let mut builder = Command::new(shell.program());
+
+    // Login shells are signaled by a leading dash in their name.
+    if is_login_shell(&config.shell) {
+        let shell_name = shell.program().rsplit('/').next().unwrap();
+        builder.arg0(format!("-{}", shell_name));
+    }
+
     for arg in shell.args() {
         builder.arg(arg);
     }
@@ -189,9 +221,10 @@ pub fn new<C>(config: &Config<C>, size: &SizeInfo, window_id: Option<usize>) ->
     builder.env("USER", pw.name);
     builder.env("HOME", pw.dir);
 
-    // Set $SHELL environment variable on macOS, since login does not do it for us.
+    // Set $SHELL environment variable on macOS, since it is not set for applications started
+    // outside of a terminal.
     #[cfg(target_os = "macos")]
-    builder.env("SHELL", config.shell.as_ref().map(|sh| sh.program()).unwrap_or(pw.shell));
+    builder.env("SHELL", config.shell.program.as_ref().map(|sh| sh.program()).unwrap_or(pw.shell));
 
     if let Some(window_id) = window_id {
         builder.env("WINDOWID", format!("{}", window_id));
@@ -248,6 +281,8 @@ pub fn new<C>(config: &Config<C>, size: &SizeInfo, window_id: Option<usize>) ->
                 token: mio::Token::from(0),
                 signals,
                 signals_token: mio::Token::from(0),
+                kill_timeout: config.shutdown.kill_timeout(),
+                hung_up: false,
             };
             pty.on_resize(size);
             pty
@@ -256,6 +291,23 @@ pub fn new<C>(config: &Config<C>, size: &SizeInfo, window_id: Option<usize>) ->
     }
 }
 
+impl Drop for Pty {
+    /// Kill the child if it is still running once the event loop stopped waiting for it.
+    fn drop(&mut self) {
+        // Waiting for the child would block, so it is only hung up.
+        if !self.hung_up {
+            self.hang_up();
+            return;
+        }
+
+        if let (Some(timeout), Ok(None)) = (self.kill_timeout, self.child.try_wait()) {
+            info!("Killing child process, which did not exit within {:?} after hangup", timeout);
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
 impl EventedReadWrite for Pty {
     type Reader = File;
     type Writer = File;
@@ -338,7 +390,14 @@ impl EventedPty for Pty {
                     None
                 },
                 Ok(None) => None,
-                Ok(_) => Some(ChildEvent::Exited),
+                Ok(Some(status)) => {
+                    let status = match (status.code(), status.signal()) {
+                        (Some(code), _) => Some(ExitStatus::Code(code)),
+                        (None, Some(signal)) => Some(ExitStatus::Signal(signal)),
+                        (None, None) => None,
+                    };
+                    Some(ChildEvent::Exited(status))
+                },
             }
         })
     }
@@ -347,6 +406,24 @@ impl EventedPty for Pty {
     fn child_event_token(&self) -> mio::Token {
         self.signals_token
     }
+
+    fn hang_up(&mut self) -> Option<Duration> {
+        self.hung_up = true;
+
+        if let Ok(Some(_)) = self.child.try_wait() {
+            return None;
+        }
+
+        // Hang up the shell and its foreground job, like closing the PTY would.
+        unsafe {
+            if let Some(pgid) = foreground_process_group() {
+                libc::killpg(pgid, libc::SIGHUP);
+            }
+            libc::kill(self.child.id() as pid_t, libc::SIGHUP);
+        }
+
+        self.kill_timeout
+    }
 }
 
 /// Types that can produce a `libc::winsize`.
@@ -4,11 +4,19 @@ use std::sync::atomic::{AtomicPtr, Ordering};
 
 use mio_extras::channel::{channel, Receiver, Sender};
 
+use winapi::shared::minwindef::DWORD;
 use winapi::shared::ntdef::{BOOLEAN, HANDLE, PVOID};
+use winapi::um::processthreadsapi::GetExitCodeProcess;
 use winapi::um::winbase::{RegisterWaitForSingleObject, UnregisterWait, INFINITE};
 use winapi::um::winnt::{WT_EXECUTEINWAITTHREAD, WT_EXECUTEONLYONCE};
 
-use crate::tty::ChildEvent;
+use crate::tty::{ChildEvent, ExitStatus};
+
+/// Data passed to the child exit callback.
+struct ChildExitContext {
+    event_tx: Sender<ChildEvent>,
+    child_handle: HANDLE,
+}
 
 /// WinAPI callback to run when child process exits.
 extern "system" fn child_exit_callback(ctx: PVOID, timed_out: BOOLEAN) {
@@ -16,8 +24,16 @@ extern "system" fn child_exit_callback(ctx: PVOID, timed_out: BOOLEAN) {
         return;
     }
 
-    let event_tx: Box<_> = unsafe { Box::from_raw(ctx as *mut Sender<ChildEvent>) };
-    let _ = event_tx.send(ChildEvent::Exited);
+    let ctx: Box<_> = unsafe { Box::from_raw(ctx as *mut ChildExitContext) };
+
+    let mut exit_code: DWORD = 0;
+    let status = if unsafe { GetExitCodeProcess(ctx.child_handle, &mut exit_code) } != 0 {
+        Some(ExitStatus::Code(exit_code as i32))
+    } else {
+        None
+    };
+
+    let _ = ctx.event_tx.send(ChildEvent::Exited(status));
 }
 
 pub struct ChildExitWatcher {
@@ -30,14 +46,14 @@ impl ChildExitWatcher {
         let (event_tx, event_rx) = channel::<ChildEvent>();
 
         let mut wait_handle: HANDLE = 0 as HANDLE;
-        let sender_ref = Box::new(event_tx);
+        let ctx = Box::new(ChildExitContext { event_tx, child_handle });
 
         let success = unsafe {
             RegisterWaitForSingleObject(
                 &mut wait_handle,
                 child_handle,
                 Some(child_exit_callback),
-                Box::into_raw(sender_ref) as PVOID,
+                Box::into_raw(ctx) as PVOID,
                 INFINITE,
                 WT_EXECUTEINWAITTHREAD | WT_EXECUTEONLYONCE,
             )
@@ -98,6 +114,9 @@ mod tests {
         poll.poll(&mut events, Some(WAIT_TIMEOUT)).unwrap();
         assert_eq!(events.iter().next().unwrap().token(), child_events_token);
         // Verify that at least one `ChildEvent::Exited` was received.
-        assert_eq!(child_exit_watcher.event_rx().try_recv(), Ok(ChildEvent::Exited));
+        assert_eq!(
+            child_exit_watcher.event_rx().try_recv(),
+            Ok(ChildEvent::Exited(Some(ExitStatus::Code(1))))
+        );
     }
 }
@@ -1,3 +1,4 @@
+use std::env;
 use std::ffi::OsStr;
 use std::io;
 use std::iter::once;
@@ -154,7 +155,7 @@ impl EventedPty for Pty {
         match self.child_watcher.event_rx().try_recv() {
             Ok(ev) => Some(ev),
             Err(TryRecvError::Empty) => None,
-            Err(TryRecvError::Disconnected) => Some(ChildEvent::Exited),
+            Err(TryRecvError::Disconnected) => Some(ChildEvent::Exited(None)),
         }
     }
 }
@@ -167,7 +168,7 @@ impl OnResize for Pty {
 
 fn cmdline<C>(config: &Config<C>) -> String {
     let default_shell = Program::Just("powershell".to_owned());
-    let shell = config.shell.as_ref().unwrap_or(&default_shell);
+    let shell = config.shell.program.as_ref().unwrap_or(&default_shell);
 
     once(shell.program().as_ref())
         .chain(shell.args().iter().map(|a| a.as_ref()))
@@ -175,6 +176,11 @@ fn cmdline<C>(config: &Config<C>) -> String {
         .join(" ")
 }
 
+/// Name of the local host.
+pub fn hostname() -> Option<String> {
+    env::var("COMPUTERNAME").ok()
+}
+
 /// Converts the string slice into a Windows-standard representation for "W"-
 /// suffixed function variants, which accept UTF-16 encoded string values.
 pub fn win32_string<S: AsRef<OsStr> + ?Sized>(value: &S) -> Vec<u16> {
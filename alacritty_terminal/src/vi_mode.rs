@@ -1,9 +1,10 @@
 use std::cmp::{max, min};
+use std::ops::RangeInclusive;
 
 use alacritty_config_derive::ConfigDeserialize;
 
 use crate::event::EventListener;
-use crate::grid::{Dimensions, GridCell};
+use crate::grid::{BidirectionalIterator, Dimensions, GridCell};
 use crate::index::{Boundary, Column, Direction, Line, Point, Side};
 use crate::term::cell::Flags;
 use crate::term::Term;
@@ -51,6 +52,68 @@ pub enum ViMotion {
     Bracket,
 }
 
+/// Region of text around the vi mode cursor which can be selected at once.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextObject {
+    /// Semantically separated word, like `w` in vi.
+    Semantic,
+    /// Whitespace separated word, like `W` in vi.
+    Word,
+    /// Text between two identical quote characters.
+    Quote(char),
+    /// Text between an opening and a closing bracket.
+    Bracket(char, char),
+}
+
+/// Part of a text object which is selected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextObjectScope {
+    /// Text object without surrounding whitespace, quotes or brackets, like `i` in vi.
+    Inner,
+    /// Text object including surrounding whitespace, quotes or brackets, like `a` in vi.
+    Around,
+}
+
+impl TextObject {
+    /// Get the text object for the character following `i` or `a` in vi.
+    pub fn from_char(c: char) -> Option<Self> {
+        let object = match c {
+            'w' => Self::Semantic,
+            'W' => Self::Word,
+            '"' | '\'' | '`' => Self::Quote(c),
+            '(' | ')' | 'b' => Self::Bracket('(', ')'),
+            '[' | ']' => Self::Bracket('[', ']'),
+            '{' | '}' | 'B' => Self::Bracket('{', '}'),
+            '<' | '>' => Self::Bracket('<', '>'),
+            _ => return None,
+        };
+
+        Some(object)
+    }
+
+    /// Get the bounds of the text object around a point.
+    pub fn bounds<T>(
+        self,
+        term: &Term<T>,
+        point: Point,
+        scope: TextObjectScope,
+    ) -> Option<RangeInclusive<Point>> {
+        match self {
+            Self::Semantic => {
+                let wide = Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
+                let is_word = |point: Point| {
+                    let cell = &term.grid()[point];
+                    cell.flags.intersects(wide) || !term.semantic_escape_chars().contains(cell.c)
+                };
+                Some(word_object(term, point, scope, is_word))
+            },
+            Self::Word => Some(word_object(term, point, scope, |point| !is_space(term, point))),
+            Self::Quote(quote) => quote_object(term, point, quote, scope),
+            Self::Bracket(open, close) => bracket_object(term, point, (open, close), scope),
+        }
+    }
+}
+
 /// Cursor tracking vi mode position.
 #[derive(Default, Copy, Clone)]
 pub struct ViModeCursor {
@@ -384,6 +447,169 @@ fn is_boundary<T>(term: &Term<T>, point: Point, direction: Direction) -> bool {
             && direction == Direction::Right)
 }
 
+/// Select a word, with `is_word` deciding which cells are part of words.
+///
+/// Cells which are neither whitespace nor part of a word are selected individually.
+fn word_object<T, F>(
+    term: &Term<T>,
+    point: Point,
+    scope: TextObjectScope,
+    is_word: F,
+) -> RangeInclusive<Point>
+where
+    F: Fn(Point) -> bool,
+{
+    let point = term.expand_wide(point, Direction::Left);
+    let line = term.logical_line(point.line);
+    let (line_start, line_end) = (line.start_point(), line.end_point(term));
+    let is_whitespace = |point| is_space(term, point);
+
+    let (mut start, mut end) = if is_whitespace(point) {
+        expand_run(term, point, is_whitespace)
+    } else if is_word(point) {
+        expand_run(term, point, &is_word)
+    } else {
+        (point, point)
+    };
+
+    if scope == TextObjectScope::Inner {
+        return start..=end;
+    }
+
+    if is_whitespace(point) {
+        // Whitespace is selected together with the following word.
+        if end < line_end {
+            let next = end.add(term, Boundary::None, 1);
+            end = if is_word(next) { expand_run(term, next, &is_word).1 } else { next };
+        }
+    } else {
+        // Prefer trailing whitespace, unless it only pads the rest of the line.
+        let trailing = if end < line_end {
+            let next = end.add(term, Boundary::None, 1);
+            Some(next).filter(|&next| is_whitespace(next))
+        } else {
+            None
+        };
+
+        match trailing.map(|next| expand_run(term, next, is_whitespace).1) {
+            Some(trailing_end) if trailing_end < line_end => end = trailing_end,
+            _ if start > line_start && is_whitespace(start.sub(term, Boundary::None, 1)) => {
+                start = expand_run(term, start.sub(term, Boundary::None, 1), is_whitespace).0;
+            },
+            Some(trailing_end) => end = trailing_end,
+            None => (),
+        }
+    }
+
+    start..=end
+}
+
+/// Select the text between two quotes of the point's logical line.
+///
+/// If the point is not quoted, the next quoted text on the line is selected instead.
+fn quote_object<T>(
+    term: &Term<T>,
+    point: Point,
+    quote: char,
+    scope: TextObjectScope,
+) -> Option<RangeInclusive<Point>> {
+    let line = term.logical_line(point.line);
+    let quotes: Vec<Point> = (line.start.0..=line.end.0)
+        .flat_map(|line| {
+            (0..term.columns()).map(move |column| Point::new(Line(line), Column(column)))
+        })
+        .filter(|&point| term.grid()[point].c == quote)
+        .collect();
+
+    let pair = quotes.chunks_exact(2).find(|pair| pair[1] >= point)?;
+    inner_or_around(term, pair[0], pair[1], scope)
+}
+
+/// Select the text between the brackets enclosing a point.
+fn bracket_object<T>(
+    term: &Term<T>,
+    point: Point,
+    (open, close): (char, char),
+    scope: TextObjectScope,
+) -> Option<RangeInclusive<Point>> {
+    let start = if term.grid()[point].c == open {
+        point
+    } else {
+        unmatched_bracket(term, point, (open, close), Direction::Left)?
+    };
+    let end = unmatched_bracket(term, start, (open, close), Direction::Right)?;
+
+    inner_or_around(term, start, end, scope)
+}
+
+/// Find the first bracket in a direction which is not matched by a bracket in between.
+fn unmatched_bracket<T>(
+    term: &Term<T>,
+    point: Point,
+    (open, close): (char, char),
+    direction: Direction,
+) -> Option<Point> {
+    let (target, nested) = match direction {
+        Direction::Left => (open, close),
+        Direction::Right => (close, open),
+    };
+
+    let mut iter = term.grid().iter_from(point);
+    let mut depth = 0;
+    loop {
+        let cell = match direction {
+            Direction::Left => iter.prev()?,
+            Direction::Right => iter.next()?,
+        };
+
+        if cell.c == target && depth == 0 {
+            return Some(cell.point);
+        } else if cell.c == target {
+            depth -= 1;
+        } else if cell.c == nested {
+            depth += 1;
+        }
+    }
+}
+
+/// Select the text between two delimiters, or including them.
+fn inner_or_around<D: Dimensions>(
+    dimensions: &D,
+    start: Point,
+    end: Point,
+    scope: TextObjectScope,
+) -> Option<RangeInclusive<Point>> {
+    match scope {
+        TextObjectScope::Around => Some(start..=end),
+        TextObjectScope::Inner => {
+            let start = start.add(dimensions, Boundary::None, 1);
+            let end = end.sub(dimensions, Boundary::None, 1);
+            Some(start..=end).filter(|_| start <= end)
+        },
+    }
+}
+
+/// Expand a point to all adjacent cells of its logical line which match a predicate.
+fn expand_run<T, F>(term: &Term<T>, point: Point, matches: F) -> (Point, Point)
+where
+    F: Fn(Point) -> bool,
+{
+    let line = term.logical_line(point.line);
+    let (line_start, line_end) = (line.start_point(), line.end_point(term));
+
+    let mut start = point;
+    while start > line_start && matches(start.sub(term, Boundary::None, 1)) {
+        start = start.sub(term, Boundary::None, 1);
+    }
+
+    let mut end = point;
+    while end < line_end && matches(end.add(term, Boundary::None, 1)) {
+        end = end.add(term, Boundary::None, 1);
+    }
+
+    (start, end)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -756,4 +982,32 @@ mod tests {
         cursor = cursor.motion(&mut term, ViMotion::WordLeft);
         assert_eq!(cursor.point, Point::new(Line(0), Column(0)));
     }
+
+    #[test]
+    fn text_objects() {
+        let term = crate::term::test::mock_term("foo(bar, \"baz qux\") end");
+        let bounds = |object: TextObject, column, scope| {
+            let bounds = object.bounds(&term, Point::new(Line(0), Column(column)), scope)?;
+            Some(bounds.start().column.0..=bounds.end().column.0)
+        };
+        let (inner, around) = (TextObjectScope::Inner, TextObjectScope::Around);
+
+        assert_eq!(bounds(TextObject::Semantic, 5, inner), Some(4..=6));
+        assert_eq!(bounds(TextObject::Semantic, 5, around), Some(4..=6));
+        assert_eq!(bounds(TextObject::Word, 11, inner), Some(9..=12));
+        assert_eq!(bounds(TextObject::Word, 11, around), Some(9..=13));
+        assert_eq!(bounds(TextObject::Word, 21, around), Some(19..=22));
+
+        let quote = TextObject::Quote('"');
+        assert_eq!(bounds(quote, 14, inner), Some(10..=16));
+        assert_eq!(bounds(quote, 14, around), Some(9..=17));
+        assert_eq!(bounds(quote, 1, inner), Some(10..=16));
+        assert_eq!(bounds(quote, 20, inner), None);
+
+        let parens = TextObject::Bracket('(', ')');
+        assert_eq!(bounds(parens, 14, inner), Some(4..=17));
+        assert_eq!(bounds(parens, 14, around), Some(3..=18));
+        assert_eq!(bounds(parens, 18, inner), Some(4..=17));
+        assert_eq!(bounds(TextObject::Bracket('[', ']'), 14, inner), None);
+    }
 }
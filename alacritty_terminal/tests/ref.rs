@@ -9,7 +9,7 @@ use alacritty_terminal::ansi;
 use alacritty_terminal::config::MockConfig;
 use alacritty_terminal::event::{Event, EventListener};
 use alacritty_terminal::grid::{Dimensions, Grid};
-use alacritty_terminal::index::{Column, Line};
+use alacritty_terminal::index::Column;
 use alacritty_terminal::term::cell::Cell;
 use alacritty_terminal::term::{SizeInfo, Term};
 
@@ -67,6 +67,8 @@ ref_tests! {
     saved_cursor_alt
     sgr
     underline
+    bce_clear
+    insert_delete_region
 }
 
 fn read_u8<P>(path: P) -> Vec<u8>
@@ -117,19 +119,19 @@ fn ref_test(dir: &Path) {
     term_grid.truncate();
 
     if grid != term_grid {
-        for i in 0..grid.total_lines() {
-            for j in 0..grid.columns() {
-                let cell = &term_grid[Line(i as i32)][Column(j)];
-                let original_cell = &grid[Line(i as i32)][Column(j)];
-                if original_cell != cell {
-                    println!(
-                        "[{i}][{j}] {original:?} => {now:?}",
-                        i = i,
-                        j = j,
-                        original = original_cell,
-                        now = cell,
-                    );
-                }
+        for damage in term_grid.diff(&grid) {
+            let line = damage.line;
+            let in_original = line >= grid.topmost_line() && grid.columns() == term_grid.columns();
+
+            for column in (damage.left.0..=damage.right.0).map(Column) {
+                let original = if in_original { Some(&grid[line][column]) } else { None };
+                println!(
+                    "[{line}][{column}] {original:?} => {now:?}",
+                    line = line,
+                    column = column,
+                    original = original,
+                    now = term_grid[line][column],
+                );
             }
         }
 
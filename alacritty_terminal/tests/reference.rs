@@ -0,0 +1,161 @@
+//! Comparison of the terminal state against screens captured from reference terminals.
+//!
+//! Every test directory contains the raw `input` bytes and one `<terminal>.screen` file per
+//! reference terminal, which can be created using `scripts/reference-screen.sh`.
+
+use std::fs;
+use std::path::Path;
+
+use alacritty_terminal::ansi;
+use alacritty_terminal::config::MockConfig;
+use alacritty_terminal::event::{Event, EventListener};
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::Line;
+use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::{SizeInfo, Term};
+
+macro_rules! reference_tests {
+    ($($name:ident)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let test_dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/reference"));
+                let test_path = test_dir.join(stringify!($name));
+                reference_test(&test_path);
+            }
+        )*
+    }
+}
+
+reference_tests! {
+    autowrap
+    cursor_movement
+    erase
+    insert_delete
+    scroll_region
+    wide_chars
+}
+
+#[derive(Copy, Clone)]
+struct Mock;
+
+impl EventListener for Mock {
+    fn send_event(&self, _event: Event) {}
+}
+
+/// Visible state of a terminal.
+#[derive(Debug)]
+struct Screen {
+    columns: usize,
+    lines: usize,
+    cursor: (usize, usize),
+    text: Vec<String>,
+}
+
+impl Screen {
+    /// Parse a screen captured from a reference terminal.
+    fn parse(content: &str) -> Screen {
+        let mut lines = content.lines();
+
+        let size = lines.next().and_then(|line| line.strip_prefix("size: ")).unwrap();
+        let mut size = size.split('x').map(|value| value.parse().unwrap());
+        let (columns, screen_lines) = (size.next().unwrap(), size.next().unwrap());
+
+        let cursor = lines.next().and_then(|line| line.strip_prefix("cursor: ")).unwrap();
+        let mut cursor = cursor.split(',').map(|value| value.parse().unwrap());
+        let cursor = (cursor.next().unwrap(), cursor.next().unwrap());
+
+        let text = lines.map(|line| line.trim_end().to_owned()).collect();
+
+        Screen { columns, lines: screen_lines, cursor, text }
+    }
+
+    /// Extract the visible state of Alacritty's terminal.
+    fn from_term(term: &Term<Mock>) -> Screen {
+        let grid = term.grid();
+
+        let mut text = Vec::new();
+        for line in (0..grid.screen_lines() as i32).map(Line) {
+            let mut content = String::new();
+            for cell in grid[line][..].iter() {
+                if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    continue;
+                }
+
+                // Tabs are only stored in cells to preserve them when copying.
+                content.push(if cell.c == '\t' { ' ' } else { cell.c });
+                content.extend(cell.zerowidth().into_iter().flatten());
+            }
+            text.push(content.trim_end().to_owned());
+        }
+
+        let cursor = grid.cursor.point;
+
+        Screen {
+            columns: grid.columns(),
+            lines: grid.screen_lines(),
+            cursor: (cursor.line.0 as usize, cursor.column.0),
+            text,
+        }
+    }
+
+    /// Describe all differences to the reference screen.
+    fn divergences(&self, reference: &Screen) -> Vec<String> {
+        let mut divergences = Vec::new();
+
+        if self.cursor != reference.cursor {
+            divergences
+                .push(format!("cursor: expected {:?}, got {:?}", reference.cursor, self.cursor));
+        }
+
+        for line in 0..self.text.len().max(reference.text.len()) {
+            let expected = reference.text.get(line).map(String::as_str).unwrap_or_default();
+            let actual = self.text.get(line).map(String::as_str).unwrap_or_default();
+            if expected != actual {
+                divergences
+                    .push(format!("line {}: expected {:?}, got {:?}", line, expected, actual));
+            }
+        }
+
+        divergences
+    }
+}
+
+fn reference_test(dir: &Path) {
+    let input = fs::read(dir.join("input")).unwrap();
+
+    let mut references: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |extension| extension == "screen"))
+        .collect();
+    references.sort();
+    assert!(!references.is_empty(), "no reference screens in {:?}", dir);
+
+    let mut failed = false;
+    for path in references {
+        let reference = Screen::parse(&fs::read_to_string(&path).unwrap());
+
+        let size =
+            SizeInfo::new(reference.columns as f32, reference.lines as f32, 1., 1., 0., 0., false);
+        let mut terminal = Term::new(&MockConfig::default(), size, Mock);
+        let mut parser = ansi::Processor::new();
+
+        for byte in &input {
+            parser.advance(&mut terminal, *byte);
+        }
+
+        let screen = Screen::from_term(&terminal);
+        assert_eq!((screen.columns, screen.lines), (reference.columns, reference.lines));
+
+        let terminal_name = path.file_stem().unwrap().to_string_lossy();
+        for divergence in screen.divergences(&reference) {
+            println!("[{}] {}", terminal_name, divergence);
+            failed = true;
+        }
+    }
+
+    if failed {
+        panic!("Reference test failed; screen diverges from reference terminal");
+    }
+}
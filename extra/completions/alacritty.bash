@@ -11,7 +11,7 @@ _alacritty()
     cur="${COMP_WORDS[COMP_CWORD]}"
     prev="${COMP_WORDS[COMP_CWORD-1]}"
     prevprev="${COMP_WORDS[COMP_CWORD-2]}"
-    opts="-h --help -V --version --print-events -q -qq -v -vv -vvv --ref-test --hold -e --command --config-file -o --option -t --title --embed --class --working-directory"
+    opts="-h --help -V --version --print-events --print-config --measure-startup -q -qq -v -vv -vvv --ref-test --hold --pager -e --command --config-file -o --option -t --title --embed --monitor --workspace --class --name --working-directory"
 
     # If `--command` or `-e` is used, stop completing
     for i in "${!COMP_WORDS[@]}"; do
@@ -35,7 +35,7 @@ _alacritty()
             compopt -o filenames
             COMPREPLY=( $(compgen -f -- "${cur}") )
             return 0;;
-        --class | --title | -t)
+        --class | --title | -t | --name | --monitor | --workspace)
             # Don't complete here
             return 0;;
         --working-directory)
@@ -20,9 +20,18 @@ complete -c alacritty \
 complete -c alacritty \
   -l "class" \
   -d "Defines the window class"
+complete -c alacritty \
+  -l "name" \
+  -d "Defines the window name used for targeting IPC messages"
 complete -c alacritty \
   -l "embed" \
   -d "Defines the X11 window ID (as a decimal integer) to embed Alacritty within"
+complete -c alacritty \
+  -l "monitor" \
+  -d "Index of the monitor the window is placed on"
+complete -c alacritty \
+  -l "workspace" \
+  -d "Index of the workspace the window is opened on"
 complete -c alacritty \
   -x \
   -a '(__fish_complete_directories (commandline -ct))' \
@@ -31,6 +40,9 @@ complete -c alacritty \
 complete -c alacritty \
   -l "hold" \
   -d "Remain open after child process exits"
+complete -c alacritty \
+  -l "pager" \
+  -d "Display stdin in vi mode instead of starting a shell"
 complete -c alacritty \
   -s "o" \
   -l "option" \
@@ -41,6 +53,14 @@ complete \
   -c alacritty \
   -l "print-events" \
   -d "Print all events to stdout"
+complete \
+  -c alacritty \
+  -l "print-config" \
+  -d "Print the effective configuration on startup"
+complete \
+  -c alacritty \
+  -l "measure-startup" \
+  -d "Print the time spent in each stage of the startup"
 complete \
   -c alacritty \
   -s "q" \
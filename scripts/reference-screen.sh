@@ -0,0 +1,35 @@
+#!/usr/bin/env bash
+
+# Capture the screen of tmux after writing a byte stream to it, for use as a
+# reference in `alacritty_terminal/tests/reference`.
+#
+# Usage: ./reference-screen.sh <INPUT> <COLUMNS> <LINES> > tmux.screen
+
+if [ $# -ne 3 ]; then
+    echo "Usage: $0 <INPUT> <COLUMNS> <LINES>"
+    exit 1
+fi
+
+# Make sure tmux is available.
+if [ ! -x "$(command -v tmux)" ]
+then
+    echo "Cannot find tmux, please make sure it's installed."
+    exit 1
+fi
+
+input=$(realpath "$1")
+columns=$2
+lines=$3
+
+# Use a separate server, so the user's configuration and sessions are not touched.
+socket="alacritty-reference-$$"
+tmux -L "$socket" -f /dev/null new-session -d -s reference -x "$columns" -y "$lines" \
+    "stty -opost; cat '$input'; sleep 10" \; set-option -t reference status off
+
+# Give tmux some time to process the input.
+sleep 1
+
+tmux -L "$socket" display-message -p -t reference \
+    "size: #{pane_width}x#{pane_height}"$'\n'"cursor: #{cursor_y},#{cursor_x}"
+tmux -L "$socket" capture-pane -p -t reference
+tmux -L "$socket" kill-server